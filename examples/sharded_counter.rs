@@ -0,0 +1,49 @@
+//! Compares the time it takes threads to increment a `ShardedCounter`
+//! against a single `AtomicU64`.
+//!
+//! ```text
+//! cargo run --release --example sharded_counter
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dt::concurrent::ShardedCounter;
+
+const INCREMENTS: u64 = 10_000_000;
+
+fn run<F>(threads: u64, increment: F) -> Duration
+where
+    F: Fn() + Sync,
+{
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                for _ in 0..INCREMENTS / threads {
+                    increment();
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn main() {
+    let parallelism = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{} increments, {} cores", INCREMENTS, parallelism);
+    for threads in [1, 2, 4, 8, 16] {
+        let atomic = AtomicU64::new(0);
+        let atomic_time = run(threads, || {
+            atomic.fetch_add(1, Ordering::Relaxed);
+        });
+        let sharded = ShardedCounter::new();
+        let sharded_time = run(threads, || sharded.increment());
+        assert_eq!(atomic.load(Ordering::Relaxed), sharded.sum());
+        println!(
+            "{:>2} threads: AtomicU64 {:>8.1?}, ShardedCounter {:>8.1?}",
+            threads, atomic_time, sharded_time,
+        );
+    }
+}
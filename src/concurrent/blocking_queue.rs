@@ -0,0 +1,496 @@
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::containers::ArrayDeque;
+
+/// The error of pushing to a [`BlockingQueue`].
+///
+/// This enum is returned by [`BlockingQueue::push()`] and
+/// [`BlockingQueue::push_timeout()`]. See their documentation for more.
+///
+/// [`BlockingQueue::push()`]: BlockingQueue::push
+/// [`BlockingQueue::push_timeout()`]: BlockingQueue::push_timeout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// The queue stayed full until the timeout elapsed.
+    Timeout(T),
+    /// The queue is closed.
+    Closed(T),
+}
+
+impl<T> PushError<T> {
+    /// Returns the element that was refused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue = BlockingQueue::new(1);
+    /// queue.close();
+    /// let err = queue.push('a').unwrap_err();
+    /// assert_eq!(err.into_value(), 'a');
+    /// ```
+    pub fn into_value(self) -> T {
+        match self {
+            Self::Timeout(value) | Self::Closed(value) => value,
+        }
+    }
+}
+
+/// The error of popping from a [`BlockingQueue`] with a timeout.
+///
+/// This enum is returned by [`BlockingQueue::pop_timeout()`]. See its
+/// documentation for more.
+///
+/// [`BlockingQueue::pop_timeout()`]: BlockingQueue::pop_timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopError {
+    /// The queue stayed empty until the timeout elapsed.
+    Timeout,
+    /// The queue is closed and empty.
+    Closed,
+}
+
+struct State<T> {
+    items: ArrayDeque<T>,
+    closed: bool,
+}
+
+/// A bounded FIFO queue that can be shared between threads, whose producers
+/// wait while it is full, and whose consumers wait while it is empty.
+///
+/// The elements are kept in an [`ArrayDeque`] behind a [`Mutex`], allocated
+/// once with the capacity of the queue. A thread that cannot go on waits on
+/// one of two [`Condvar`]s, one for the queue not being full and one for it
+/// not being empty, and is woken up by the thread that changes that.
+///
+/// Closing the queue wakes up every waiting thread. No element can be
+/// pushed afterwards, but the elements already in the queue can still be
+/// popped, and popping from a closed queue that is empty returns at once,
+/// which lets the consumers know that they are done.
+///
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::BlockingQueue;
+///
+/// let queue = Arc::new(BlockingQueue::new(2));
+/// let consumer = {
+///     let queue = Arc::clone(&queue);
+///     thread::spawn(move || {
+///         let mut sum = 0;
+///         while let Some(value) = queue.pop() {
+///             sum += value;
+///         }
+///         sum
+///     })
+/// };
+/// for i in 1..=100 {
+///     queue.push(i).unwrap();
+/// }
+/// queue.close();
+/// assert_eq!(consumer.join().unwrap(), 5050);
+/// ```
+pub struct BlockingQueue<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+}
+
+impl<T> fmt::Debug for BlockingQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingQueue")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> BlockingQueue<T> {
+    /// Creates an empty BlockingQueue that holds at most `capacity`
+    /// elements.
+    ///
+    /// # Panics
+    ///
+    /// Capacity must be positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue: BlockingQueue<u32> = BlockingQueue::new(16);
+    /// assert_eq!(queue.capacity(), 16);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            state: Mutex::new(State {
+                items: ArrayDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the number of elements the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue: BlockingQueue<u32> = BlockingQueue::new(4);
+    /// assert_eq!(queue.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// Other threads may push or pop at the same time, so the answer may be
+    /// stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue = BlockingQueue::new(4);
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().items.len()
+    }
+
+    /// Returns true if the queue has no elements.
+    ///
+    /// Other threads may push or pop at the same time, so the answer may be
+    /// stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue = BlockingQueue::new(4);
+    /// assert!(queue.is_empty());
+    /// queue.push(1).unwrap();
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().items.is_empty()
+    }
+
+    /// Appends the element to the back of the queue, waiting for as long as
+    /// the queue is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PushError::Closed`] with the element if the queue is
+    /// closed, including while waiting.
+    ///
+    /// [`PushError::Closed`]: crate::concurrent::PushError::Closed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::{BlockingQueue, PushError};
+    ///
+    /// let queue = BlockingQueue::new(1);
+    /// assert_eq!(queue.push(1), Ok(()));
+    /// queue.close();
+    /// assert_eq!(queue.push(2), Err(PushError::Closed(2)));
+    /// ```
+    pub fn push(&self, value: T) -> Result<(), PushError<T>> {
+        let mut state = self.state.lock().unwrap();
+        while !state.closed && state.items.len() == self.capacity {
+            state = self.not_full.wait(state).unwrap();
+        }
+        self.push_locked(state, value)
+    }
+
+    /// Appends the element to the back of the queue, waiting for at most the
+    /// timeout for the queue to have room for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PushError::Timeout`] with the element if the queue is still
+    /// full when the timeout elapses, and [`PushError::Closed`] with the
+    /// element if the queue is closed.
+    ///
+    /// [`PushError::Timeout`]: crate::concurrent::PushError::Timeout
+    /// [`PushError::Closed`]: crate::concurrent::PushError::Closed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use dt::concurrent::{BlockingQueue, PushError};
+    ///
+    /// let queue = BlockingQueue::new(1);
+    /// let timeout = Duration::from_millis(10);
+    /// assert_eq!(queue.push_timeout(1, timeout), Ok(()));
+    /// assert_eq!(queue.push_timeout(2, timeout), Err(PushError::Timeout(2)));
+    /// ```
+    pub fn push_timeout(
+        &self,
+        value: T,
+        timeout: Duration,
+    ) -> Result<(), PushError<T>> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        while !state.closed && state.items.len() == self.capacity {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PushError::Timeout(value));
+            }
+            state =
+                self.not_full.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        self.push_locked(state, value)
+    }
+
+    /// Removes the front element and returns it, waiting for as long as the
+    /// queue is empty, or None if the queue is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue = BlockingQueue::new(2);
+    /// queue.push(1).unwrap();
+    /// queue.close();
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        while !state.closed && state.items.is_empty() {
+            state = self.not_empty.wait(state).unwrap();
+        }
+        self.pop_locked(state)
+    }
+
+    /// Removes the front element and returns it, waiting for at most the
+    /// timeout for an element to be pushed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PopError::Timeout`] if the queue is still empty when the
+    /// timeout elapses, and [`PopError::Closed`] if the queue is closed and
+    /// empty.
+    ///
+    /// [`PopError::Timeout`]: crate::concurrent::PopError::Timeout
+    /// [`PopError::Closed`]: crate::concurrent::PopError::Closed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use dt::concurrent::{BlockingQueue, PopError};
+    ///
+    /// let queue = BlockingQueue::new(1);
+    /// let timeout = Duration::from_millis(10);
+    /// queue.push(1).unwrap();
+    /// assert_eq!(queue.pop_timeout(timeout), Ok(1));
+    /// assert_eq!(queue.pop_timeout(timeout), Err(PopError::Timeout));
+    /// queue.close();
+    /// assert_eq!(queue.pop_timeout(timeout), Err(PopError::Closed));
+    /// ```
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        while !state.closed && state.items.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PopError::Timeout);
+            }
+            state = self
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+        self.pop_locked(state).ok_or(PopError::Closed)
+    }
+
+    /// Closes the queue, and wakes up every thread waiting on it.
+    ///
+    /// The elements already in the queue can still be popped, but no more
+    /// can be pushed. Closing a closed queue does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue: BlockingQueue<u32> = BlockingQueue::new(1);
+    /// assert!(!queue.is_closed());
+    /// queue.close();
+    /// assert!(queue.is_closed());
+    /// ```
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// Returns true if the queue is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::BlockingQueue;
+    ///
+    /// let queue: BlockingQueue<u32> = BlockingQueue::new(1);
+    /// queue.close();
+    /// assert!(queue.is_closed());
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    // Pushes the element once the queue is closed or has room for it.
+    fn push_locked(
+        &self,
+        mut state: MutexGuard<'_, State<T>>,
+        value: T,
+    ) -> Result<(), PushError<T>> {
+        if state.closed {
+            return Err(PushError::Closed(value));
+        }
+        state.items.push_back(value);
+        drop(state);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    // Pops an element once the queue is closed or has one.
+    fn pop_locked(&self, mut state: MutexGuard<'_, State<T>>) -> Option<T> {
+        let value = state.items.pop_front()?;
+        drop(state);
+        self.not_full.notify_one();
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn producers_and_consumers_until_closed() {
+        const PRODUCERS: usize = 3;
+        const CONSUMERS: usize = 3;
+        const PER_PRODUCER: usize = 2_000;
+
+        let queue = Arc::new(BlockingQueue::new(4));
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    while let Some(value) = queue.pop() {
+                        assert!(queue.len() <= queue.capacity());
+                        popped.push(value);
+                    }
+                    popped
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        queue.close();
+
+        let mut seen = vec![vec![false; PER_PRODUCER]; PRODUCERS];
+        for consumer in consumers {
+            let popped = consumer.join().unwrap();
+            // Each consumer sees the elements of a producer in the order
+            // they were pushed.
+            let mut last = [None; PRODUCERS];
+            for (p, i) in popped {
+                assert!(last[p].is_none_or(|j| j < i));
+                last[p] = Some(i);
+                assert!(!seen[p][i]);
+                seen[p][i] = true;
+            }
+        }
+        assert!(seen.iter().flatten().all(|&s| s));
+    }
+
+    #[test]
+    fn close_wakes_up_waiting_threads() {
+        let queue = Arc::new(BlockingQueue::new(1));
+        queue.push(0).unwrap();
+        let pusher = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.push(1))
+        };
+        let empty = Arc::new(BlockingQueue::<i32>::new(1));
+        let popper = {
+            let empty = Arc::clone(&empty);
+            thread::spawn(move || empty.pop())
+        };
+        queue.close();
+        empty.close();
+        assert_eq!(pusher.join().unwrap(), Err(PushError::Closed(1)));
+        assert_eq!(popper.join().unwrap(), None);
+        assert_eq!(queue.pop(), Some(0));
+    }
+
+    #[test]
+    fn timeouts_wait_for_room_or_elements() {
+        let queue = Arc::new(BlockingQueue::new(1));
+        let timeout = Duration::from_millis(20);
+        let start = Instant::now();
+        assert_eq!(queue.pop_timeout(timeout), Err(PopError::Timeout));
+        assert!(start.elapsed() >= timeout);
+
+        // An element pushed while waiting is popped before the timeout.
+        let pusher = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.push(1))
+        };
+        let long = Duration::from_secs(60);
+        assert_eq!(queue.pop_timeout(long), Ok(1));
+        pusher.join().unwrap().unwrap();
+
+        queue.push(2).unwrap();
+        assert_eq!(queue.push_timeout(3, timeout), Err(PushError::Timeout(3)));
+        let popper = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.pop())
+        };
+        assert_eq!(queue.push_timeout(3, long), Ok(()));
+        assert_eq!(popper.join().unwrap(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+    }
+}
@@ -0,0 +1,32 @@
+use std::ops::{Deref, DerefMut};
+
+/// A value aligned to, and padded to the length of, a cache line, so that
+/// values written by different threads never share a line.
+///
+/// Lines are 64 bytes on most processors, but some of them prefetch pairs of
+/// lines, so the alignment is 128 bytes.
+#[derive(Debug, Default)]
+#[repr(align(128))]
+pub(crate) struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
@@ -0,0 +1,418 @@
+//! Epoch-based memory reclamation.
+//!
+//! A lock-free structure cannot free a node as soon as it unlinks it, since
+//! other threads may have loaded a pointer to the node just before, and may
+//! still read it. With epochs, a thread [`pin()`]s itself before it loads
+//! pointers out of a shared structure, and unlinked nodes are retired with
+//! [`Guard::defer_destroy()`] instead of being freed.
+//!
+//! There is a global epoch counter, and a pinned thread announces the epoch
+//! it saw when it was pinned. The global epoch only advances once every
+//! pinned thread has announced the current epoch. A node retired in epoch
+//! `e` was unlinked before the epoch became `e + 1`, so the threads pinned
+//! in epoch `e + 1` or later never saw it, and once the global epoch reaches
+//! `e + 2`, no thread that could still see the node is pinned, and it is
+//! freed.
+//!
+//! The retired nodes are kept in a list of the thread that retired them, and
+//! freed by that thread once enough of them pile up. The nodes left when a
+//! thread exits are handed to the others. A thread that stays pinned holds
+//! back the epoch, so no memory is freed until it unpins.
+//!
+//! [`pin()`]: crate::concurrent::epoch::pin
+//! [`Guard::defer_destroy()`]: Guard::defer_destroy
+//!
+//! # Examples
+//!
+//! ```
+//! use std::ptr;
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//!
+//! use dt::concurrent::epoch;
+//!
+//! let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+//!
+//! let guard = epoch::pin();
+//! let old = shared.swap(Box::into_raw(Box::new(2)), Ordering::AcqRel);
+//! // Another thread may still be reading the old value, so it is only freed
+//! // once every thread pinned before the swap has unpinned.
+//! unsafe { guard.defer_destroy(old) };
+//! drop(guard);
+//!
+//! let last = shared.swap(ptr::null_mut(), Ordering::AcqRel);
+//! drop(unsafe { Box::from_raw(last) });
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The bit of a participant's state that is set while it is pinned. Epochs
+/// are counted in steps of two, so the bit never collides with them.
+const PINNED: usize = 1;
+
+/// One step of the epoch counter.
+const STEP: usize = 2;
+
+/// The number of retired nodes a thread keeps before it tries to free them.
+const COLLECT_THRESHOLD: usize = 64;
+
+/// The state of a thread: the epoch it saw when it was pinned, and whether it
+/// is pinned.
+#[derive(Debug)]
+struct Participant {
+    state: AtomicUsize,
+}
+
+/// A retired node, with the epoch it was retired in.
+struct Garbage {
+    epoch: usize,
+    ptr: *mut (),
+    destroy: unsafe fn(*mut ()),
+}
+
+// A retired node is only freed once, and `Guard::defer_destroy` requires its
+// destructor to be safe to run from any thread.
+#[allow(unsafe_code)]
+unsafe impl Send for Garbage {}
+
+impl Garbage {
+    fn is_expired(&self, global: usize) -> bool {
+        global.wrapping_sub(self.epoch) >= 2 * STEP
+    }
+
+    #[allow(unsafe_code)]
+    fn destroy(self) {
+        // SAFETY: The node is expired, so no thread can still read it.
+        unsafe { (self.destroy)(self.ptr) }
+    }
+}
+
+struct Global {
+    epoch: AtomicUsize,
+    participants: Mutex<Vec<Arc<Participant>>>,
+    // The retired nodes left by the threads that exited.
+    orphans: Mutex<Vec<Garbage>>,
+}
+
+static GLOBAL: Global = Global {
+    epoch: AtomicUsize::new(0),
+    participants: Mutex::new(Vec::new()),
+    orphans: Mutex::new(Vec::new()),
+};
+
+impl Global {
+    /// Advances the global epoch if every pinned participant saw the current
+    /// one, and returns the global epoch. The calling thread must be pinned,
+    /// so the epoch cannot move more than one step past the one it saw.
+    fn try_advance(&self) -> usize {
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        // Advancing is only an optimization, so it is skipped rather than
+        // waiting on a registering or exiting thread.
+        let participants = match self.participants.try_lock() {
+            Ok(participants) => participants,
+            Err(_) => return epoch,
+        };
+        for participant in participants.iter() {
+            let state = participant.state.load(Ordering::Relaxed);
+            if state & PINNED != 0 && state & !PINNED != epoch {
+                return epoch;
+            }
+        }
+        drop(participants);
+        fence(Ordering::Acquire);
+        let next = epoch.wrapping_add(STEP);
+        self.epoch.store(next, Ordering::Release);
+        next
+    }
+}
+
+/// The per-thread state.
+struct Local {
+    participant: Arc<Participant>,
+    // The number of live guards of the thread.
+    guards: Cell<usize>,
+    garbage: RefCell<Vec<Garbage>>,
+}
+
+impl Local {
+    fn register() -> Self {
+        let participant = Arc::new(Participant {
+            state: AtomicUsize::new(0),
+        });
+        GLOBAL
+            .participants
+            .lock()
+            .unwrap()
+            .push(Arc::clone(&participant));
+        Self {
+            participant,
+            guards: Cell::new(0),
+            garbage: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Tries to advance the global epoch, and frees the expired nodes of the
+    /// thread, and those left by exited threads.
+    fn collect(&self) {
+        let global = GLOBAL.try_advance();
+        // The destructors run after the list is released, since they may
+        // retire more nodes.
+        let mut expired = Vec::new();
+        {
+            let mut garbage = self.garbage.borrow_mut();
+            let mut i = 0;
+            while i < garbage.len() {
+                if garbage[i].is_expired(global) {
+                    expired.push(garbage.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        if let Ok(mut orphans) = GLOBAL.orphans.try_lock() {
+            let mut i = 0;
+            while i < orphans.len() {
+                if orphans[i].is_expired(global) {
+                    expired.push(orphans.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        expired.into_iter().for_each(Garbage::destroy);
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        let mut participants = GLOBAL.participants.lock().unwrap();
+        participants.retain(|p| !Arc::ptr_eq(p, &self.participant));
+        drop(participants);
+        let garbage = mem::take(self.garbage.get_mut());
+        GLOBAL.orphans.lock().unwrap().extend(garbage);
+    }
+}
+
+thread_local! {
+    static LOCAL: Local = Local::register();
+}
+
+/// Pins the current thread until the returned guard is dropped.
+///
+/// The nodes retired while the thread is pinned are not freed before it
+/// unpins, so the pointers it loads out of shared structures stay valid for
+/// as long as it holds the guard. Pinning a thread that is already pinned
+/// only returns another guard.
+///
+/// # Panics
+///
+/// Panics if called while the thread-local storage of the thread is being
+/// destroyed.
+///
+/// # Examples
+///
+/// ```
+/// use dt::concurrent::epoch;
+///
+/// let guard = epoch::pin();
+/// let nested = epoch::pin();
+/// drop(guard);
+/// drop(nested);
+/// ```
+pub fn pin() -> Guard {
+    LOCAL.with(|local| {
+        let guards = local.guards.get();
+        local.guards.set(guards + 1);
+        if guards == 0 {
+            let epoch = GLOBAL.epoch.load(Ordering::Relaxed);
+            local
+                .participant
+                .state
+                .store(epoch | PINNED, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+        }
+    });
+    Guard {
+        _marker: PhantomData,
+    }
+}
+
+/// A guard that keeps the current thread pinned.
+///
+/// This struct is created by [`pin()`]. See its documentation for more.
+///
+/// [`pin()`]: crate::concurrent::epoch::pin
+pub struct Guard {
+    // A guard belongs to the thread that pinned itself.
+    _marker: PhantomData<*mut ()>,
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Guard").finish_non_exhaustive()
+    }
+}
+
+impl Guard {
+    /// Retires the node, which is freed as a [`Box`] once no thread that
+    /// could have loaded a pointer to it is pinned.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must come from [`Box::into_raw()`], and the node must be
+    /// unlinked from every shared structure, so that no thread pinned after
+    /// this call can load it. The node must be retired only once, and it
+    /// must be safe to drop on any thread at any later time.
+    ///
+    /// [`Box::into_raw()`]: std::boxed::Box::into_raw
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::epoch;
+    ///
+    /// let node = Box::into_raw(Box::new([0u8; 64]));
+    /// let guard = epoch::pin();
+    /// unsafe { guard.defer_destroy(node) };
+    /// ```
+    #[allow(unsafe_code)]
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        unsafe fn destroy<T>(ptr: *mut ()) {
+            drop(Box::from_raw(ptr as *mut T));
+        }
+
+        // The node is tagged with an epoch read after it was unlinked.
+        fence(Ordering::SeqCst);
+        let garbage = Garbage {
+            epoch: GLOBAL.epoch.load(Ordering::Relaxed),
+            ptr: ptr as *mut (),
+            destroy: destroy::<T>,
+        };
+        LOCAL.with(|local| {
+            let len = {
+                let mut list = local.garbage.borrow_mut();
+                list.push(garbage);
+                list.len()
+            };
+            if len >= COLLECT_THRESHOLD {
+                local.collect();
+            }
+        });
+    }
+
+    /// Tries to advance the global epoch, and frees the retired nodes that
+    /// no pinned thread can see anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::epoch;
+    ///
+    /// let guard = epoch::pin();
+    /// guard.flush();
+    /// ```
+    pub fn flush(&self) {
+        LOCAL.with(Local::collect);
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        // The guard may outlive the thread-local state when it is dropped by
+        // another thread-local destructor, in which case the thread has
+        // already unregistered.
+        let _ = LOCAL.try_with(|local| {
+            let guards = local.guards.get() - 1;
+            local.guards.set(guards);
+            if guards == 0 {
+                local.participant.state.store(0, Ordering::Release);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    struct Flag(Arc<AtomicBool>);
+
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn pinned_thread_holds_back_reclamation() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let node = Box::into_raw(Box::new(Flag(Arc::clone(&dropped))));
+
+        let pinned = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let pinned = Arc::clone(&pinned);
+            let dropped = Arc::clone(&dropped);
+            thread::spawn(move || {
+                let _guard = pin();
+                pinned.store(true, Ordering::SeqCst);
+                // Stay pinned for a while, during which the node must stay
+                // alive.
+                for _ in 0..1_000 {
+                    assert!(!dropped.load(Ordering::SeqCst));
+                    thread::yield_now();
+                }
+            })
+        };
+        while !pinned.load(Ordering::SeqCst) {
+            thread::yield_now();
+        }
+
+        let guard = pin();
+        unsafe { guard.defer_destroy(node) };
+        drop(guard);
+        for _ in 0..100 {
+            pin().flush();
+        }
+        reader.join().unwrap();
+
+        // Other tests may pin threads too, so the epoch is given time to move.
+        for _ in 0..10_000 {
+            if dropped.load(Ordering::SeqCst) {
+                break;
+            }
+            pin().flush();
+            thread::yield_now();
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn exited_threads_hand_over_their_nodes() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&dropped);
+        thread::spawn(move || {
+            let node = Box::into_raw(Box::new(Flag(flag)));
+            unsafe { pin().defer_destroy(node) };
+        })
+        .join()
+        .unwrap();
+
+        for _ in 0..10_000 {
+            if dropped.load(Ordering::SeqCst) {
+                break;
+            }
+            pin().flush();
+            thread::yield_now();
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
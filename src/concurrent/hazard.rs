@@ -0,0 +1,533 @@
+//! Hazard pointer memory reclamation.
+//!
+//! A lock-free structure cannot free a node as soon as it unlinks it, since
+//! other threads may have loaded a pointer to the node just before, and may
+//! still read it. With hazard pointers, a thread publishes the pointer it is
+//! about to read in one of its hazard slots, with
+//! [`HazardPointer::protect()`], and unlinked nodes are handed to
+//! [`retire()`] instead of being freed. A retired node is only freed once no
+//! hazard slot holds its address.
+//!
+//! A protected load publishes the pointer, and then loads it again to check
+//! that it was not unlinked in the meantime. A node that is still linked
+//! after the pointer was published cannot be freed before the slot is
+//! cleared, since a thread retires a node only after unlinking it, and then
+//! scans every slot before freeing it.
+//!
+//! Unlike with [epochs], a stalled thread only holds back the nodes it
+//! protects, rather than all of the retired ones, at the cost of a fence
+//! for every protected load, and of protecting each pointer separately.
+//!
+//! The retired nodes are kept in a list of the thread that retired them, and
+//! freed by that thread once enough of them pile up. The nodes left when a
+//! thread exits are handed to the others.
+//!
+//! [`HazardPointer::protect()`]: HazardPointer::protect
+//! [`retire()`]: crate::concurrent::hazard::retire
+//! [epochs]: crate::concurrent::epoch
+//!
+//! # Examples
+//!
+//! ```
+//! use std::ptr;
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//!
+//! use dt::concurrent::hazard::{self, HazardPointer};
+//!
+//! let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+//!
+//! let mut hazard = HazardPointer::new();
+//! let value = hazard.protect(&shared);
+//! let old = shared.swap(Box::into_raw(Box::new(2)), Ordering::AcqRel);
+//! // The old value is protected, so it is only freed once the hazard pointer
+//! // is reset.
+//! unsafe { hazard::retire(old) };
+//! assert_eq!(unsafe { *value }, 1);
+//! hazard.reset();
+//!
+//! let last = shared.swap(ptr::null_mut(), Ordering::AcqRel);
+//! drop(unsafe { Box::from_raw(last) });
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The number of retired nodes a thread keeps before it scans the hazard
+/// slots to free them.
+const SCAN_THRESHOLD: usize = 64;
+
+/// A slot holding the address a thread protects, or null.
+#[derive(Debug)]
+struct Slot {
+    ptr: AtomicPtr<()>,
+}
+
+/// A retired node.
+struct Retired {
+    ptr: *mut (),
+    destroy: unsafe fn(*mut ()),
+}
+
+// A retired node is only freed once, and `retire` requires its destructor to
+// be safe to run from any thread.
+#[allow(unsafe_code)]
+unsafe impl Send for Retired {}
+
+impl Retired {
+    #[allow(unsafe_code)]
+    fn destroy(self) {
+        // SAFETY: No slot held the address of the node after it was
+        // unlinked, so no thread can still read it.
+        unsafe { (self.destroy)(self.ptr) }
+    }
+}
+
+struct Global {
+    slots: Mutex<Vec<Arc<Slot>>>,
+    // The retired nodes left by the threads that exited.
+    orphans: Mutex<Vec<Retired>>,
+}
+
+static GLOBAL: Global = Global {
+    slots: Mutex::new(Vec::new()),
+    orphans: Mutex::new(Vec::new()),
+};
+
+impl Global {
+    /// Returns the sorted addresses held by the slots of every thread.
+    fn protected(&self) -> Vec<*mut ()> {
+        // The nodes were unlinked before the slots are read, so a thread that
+        // publishes an address afterwards finds its node unlinked.
+        fence(Ordering::SeqCst);
+        let mut protected: Vec<_> = self
+            .slots
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|slot| slot.ptr.load(Ordering::Relaxed))
+            .filter(|ptr| !ptr.is_null())
+            .collect();
+        fence(Ordering::Acquire);
+        protected.sort_unstable();
+        protected
+    }
+
+    fn unregister(&self, slots: &[Arc<Slot>]) {
+        self.slots
+            .lock()
+            .unwrap()
+            .retain(|s| !slots.iter().any(|slot| Arc::ptr_eq(s, slot)));
+    }
+}
+
+/// The per-thread state.
+struct Local {
+    // The slots of the thread that no hazard pointer uses.
+    free: RefCell<Vec<Arc<Slot>>>,
+    retired: RefCell<Vec<Retired>>,
+}
+
+impl Local {
+    fn acquire(&self) -> Arc<Slot> {
+        self.free.borrow_mut().pop().unwrap_or_else(|| {
+            let slot = Arc::new(Slot {
+                ptr: AtomicPtr::new(ptr::null_mut()),
+            });
+            GLOBAL.slots.lock().unwrap().push(Arc::clone(&slot));
+            slot
+        })
+    }
+
+    /// Frees the retired nodes of the thread, and those left by exited
+    /// threads, that no slot protects.
+    fn scan(&self) {
+        let protected = GLOBAL.protected();
+        let is_free = |r: &Retired| protected.binary_search(&r.ptr).is_err();
+        // The destructors run after the list is released, since they may
+        // retire more nodes.
+        let mut freed = Vec::new();
+        {
+            let mut retired = self.retired.borrow_mut();
+            let mut i = 0;
+            while i < retired.len() {
+                if is_free(&retired[i]) {
+                    freed.push(retired.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        if let Ok(mut orphans) = GLOBAL.orphans.try_lock() {
+            let mut i = 0;
+            while i < orphans.len() {
+                if is_free(&orphans[i]) {
+                    freed.push(orphans.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        freed.into_iter().for_each(Retired::destroy);
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        GLOBAL.unregister(self.free.get_mut());
+        let retired = mem::take(self.retired.get_mut());
+        GLOBAL.orphans.lock().unwrap().extend(retired);
+    }
+}
+
+thread_local! {
+    static LOCAL: Local = const {
+        Local {
+            free: RefCell::new(Vec::new()),
+            retired: RefCell::new(Vec::new()),
+        }
+    };
+}
+
+/// A hazard slot of the current thread, which protects one node at a time
+/// from being freed.
+///
+/// The slot is released when the hazard pointer is dropped, and reused by
+/// the next hazard pointer of the thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::AtomicPtr;
+///
+/// use dt::concurrent::hazard::HazardPointer;
+///
+/// let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+/// let mut hazard = HazardPointer::new();
+/// let value = hazard.protect(&shared);
+/// assert_eq!(unsafe { *value }, 1);
+/// # drop(hazard);
+/// # drop(unsafe { Box::from_raw(shared.into_inner()) });
+/// ```
+pub struct HazardPointer {
+    slot: Arc<Slot>,
+    // A hazard pointer belongs to the thread that owns its slot.
+    _marker: PhantomData<*mut ()>,
+}
+
+impl fmt::Debug for HazardPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HazardPointer")
+            .field("ptr", &self.slot.ptr.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl Default for HazardPointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HazardPointer {
+    /// Creates a hazard pointer that protects nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called while the thread-local storage of the thread is being
+    /// destroyed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::hazard::HazardPointer;
+    ///
+    /// let hazard = HazardPointer::new();
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            slot: LOCAL.with(Local::acquire),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the pointer out of the source, and protects the node it points
+    /// to, which stays valid until the hazard pointer protects another node,
+    /// is reset, or is dropped.
+    ///
+    /// The node must only be freed by [`retire()`] after it is unlinked from
+    /// the source. Whatever the hazard pointer protected before is no longer
+    /// protected.
+    ///
+    /// [`retire()`]: crate::concurrent::hazard::retire
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicPtr;
+    ///
+    /// use dt::concurrent::hazard::HazardPointer;
+    ///
+    /// let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+    /// let mut hazard = HazardPointer::new();
+    /// let value = hazard.protect(&shared);
+    /// assert_eq!(unsafe { *value }, 1);
+    /// # drop(hazard);
+    /// # drop(unsafe { Box::from_raw(shared.into_inner()) });
+    /// ```
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.slot.ptr.store(ptr as *mut (), Ordering::Relaxed);
+            // The slot is published before the source is loaded again, so a
+            // thread that unlinks the node afterwards sees the slot when it
+            // scans.
+            fence(Ordering::SeqCst);
+            let again = src.load(Ordering::Acquire);
+            if again == ptr {
+                return ptr;
+            }
+            ptr = again;
+        }
+    }
+
+    /// Stops protecting the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicPtr;
+    ///
+    /// use dt::concurrent::hazard::HazardPointer;
+    ///
+    /// let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+    /// let mut hazard = HazardPointer::new();
+    /// hazard.protect(&shared);
+    /// hazard.reset();
+    /// # drop(unsafe { Box::from_raw(shared.into_inner()) });
+    /// ```
+    pub fn reset(&mut self) {
+        self.slot.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        self.reset();
+        // The hazard pointer may outlive the thread-local state when it is
+        // dropped by another thread-local destructor, in which case its slot
+        // is unregistered directly.
+        let slot = Arc::clone(&self.slot);
+        if LOCAL
+            .try_with(|local| local.free.borrow_mut().push(slot))
+            .is_err()
+        {
+            GLOBAL.unregister(&[Arc::clone(&self.slot)]);
+        }
+    }
+}
+
+/// Retires the node, which is freed as a [`Box`] once no hazard pointer
+/// protects it.
+///
+/// # Safety
+///
+/// The pointer must come from [`Box::into_raw()`], and the node must be
+/// unlinked from every shared structure, so that no hazard pointer can
+/// protect it after this call. The node must be retired only once, and it
+/// must be safe to drop on any thread at any later time.
+///
+/// [`Box::into_raw()`]: std::boxed::Box::into_raw
+///
+/// # Panics
+///
+/// Panics if called while the thread-local storage of the thread is being
+/// destroyed.
+///
+/// # Examples
+///
+/// ```
+/// use dt::concurrent::hazard;
+///
+/// let node = Box::into_raw(Box::new([0u8; 64]));
+/// unsafe { hazard::retire(node) };
+/// ```
+#[allow(unsafe_code)]
+pub unsafe fn retire<T>(ptr: *mut T) {
+    unsafe fn destroy<T>(ptr: *mut ()) {
+        drop(Box::from_raw(ptr as *mut T));
+    }
+
+    let retired = Retired {
+        ptr: ptr as *mut (),
+        destroy: destroy::<T>,
+    };
+    LOCAL.with(|local| {
+        let len = {
+            let mut list = local.retired.borrow_mut();
+            list.push(retired);
+            list.len()
+        };
+        if len >= SCAN_THRESHOLD {
+            local.scan();
+        }
+    });
+}
+
+/// Frees the nodes retired by the current thread, or left by exited threads,
+/// that no hazard pointer protects.
+///
+/// # Panics
+///
+/// Panics if called while the thread-local storage of the thread is being
+/// destroyed.
+///
+/// # Examples
+///
+/// ```
+/// use dt::concurrent::hazard;
+///
+/// hazard::flush();
+/// ```
+pub fn flush() {
+    LOCAL.with(Local::scan);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::thread;
+
+    struct Flag(Arc<AtomicBool>);
+
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn protected_node_is_not_freed() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let node = Box::into_raw(Box::new(Flag(Arc::clone(&dropped))));
+        let shared = Arc::new(AtomicPtr::new(node));
+
+        let protected = Arc::new(AtomicBool::new(false));
+        let release = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let shared = Arc::clone(&shared);
+            let protected = Arc::clone(&protected);
+            let release = Arc::clone(&release);
+            thread::spawn(move || {
+                let mut hazard = HazardPointer::new();
+                hazard.protect(&shared);
+                protected.store(true, Ordering::SeqCst);
+                while !release.load(Ordering::SeqCst) {
+                    thread::yield_now();
+                }
+            })
+        };
+        while !protected.load(Ordering::SeqCst) {
+            thread::yield_now();
+        }
+
+        let old = shared.swap(ptr::null_mut(), Ordering::AcqRel);
+        unsafe { retire(old) };
+        for _ in 0..10 {
+            flush();
+        }
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        release.store(true, Ordering::SeqCst);
+        reader.join().unwrap();
+        flush();
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn exited_threads_hand_over_their_nodes() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&dropped);
+        thread::spawn(move || {
+            let node = Box::into_raw(Box::new(Flag(flag)));
+            unsafe { retire(node) };
+        })
+        .join()
+        .unwrap();
+
+        // Another test may hold the orphans while it scans.
+        for _ in 0..10_000 {
+            if dropped.load(Ordering::SeqCst) {
+                break;
+            }
+            flush();
+            thread::yield_now();
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn readers_never_see_freed_nodes() {
+        const READERS: usize = 3;
+        const SWAPS: usize = 10_000;
+
+        struct Node {
+            value: usize,
+            check: usize,
+            drops: Arc<AtomicUsize>,
+        }
+
+        impl Drop for Node {
+            fn drop(&mut self) {
+                self.check = 0;
+                self.drops.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let node = |value| {
+            Box::into_raw(Box::new(Node {
+                value,
+                check: !value,
+                drops: Arc::clone(&drops),
+            }))
+        };
+        let shared = Arc::new(AtomicPtr::new(node(0)));
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut hazard = HazardPointer::new();
+                    while !done.load(Ordering::SeqCst) {
+                        let node = unsafe { &*hazard.protect(&shared) };
+                        assert_eq!(node.check, !node.value);
+                        thread::yield_now();
+                    }
+                })
+            })
+            .collect();
+        for i in 1..=SWAPS {
+            let old = shared.swap(node(i), Ordering::AcqRel);
+            unsafe { retire(old) };
+        }
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        flush();
+        assert_eq!(drops.load(Ordering::SeqCst), SWAPS);
+        drop(unsafe { Box::from_raw(shared.load(Ordering::Relaxed)) });
+        assert_eq!(drops.load(Ordering::SeqCst), SWAPS + 1);
+    }
+}
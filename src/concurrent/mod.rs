@@ -0,0 +1,26 @@
+//! Concurrent data structures
+mod blocking_queue;
+mod cache_padded;
+pub mod epoch;
+pub mod hazard;
+mod mpmc_queue;
+mod ms_queue;
+mod rcu_cell;
+mod rcu_map;
+mod seq_lock;
+mod sharded_counter;
+mod sharded_hash_map;
+mod skip_list_map;
+mod spsc_ring;
+mod work_stealing_deque;
+pub use blocking_queue::{BlockingQueue, PopError, PushError};
+pub use mpmc_queue::MpmcQueue;
+pub use ms_queue::MsQueue;
+pub use rcu_cell::{RcuCell, Snapshot};
+pub use rcu_map::RcuMap;
+pub use seq_lock::SeqLock;
+pub use sharded_counter::ShardedCounter;
+pub use sharded_hash_map::ShardedHashMap;
+pub use skip_list_map::SkipListMap;
+pub use spsc_ring::{Consumer, Producer, SpscRing};
+pub use work_stealing_deque::{Stealer, WorkStealingDeque};
@@ -0,0 +1,391 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::cache_padded::CachePadded;
+
+struct Slot<T> {
+    // Equals the index of the next push that may write the slot while it is
+    // free, and that index plus one while it holds a value.
+    seq: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A bounded lock-free multi-producer multi-consumer FIFO queue, by Vyukov.
+///
+/// The elements are kept in a ring of slots whose number is a power of two,
+/// with a head and a tail index that count every pop and push, and that are
+/// claimed with compare-and-swaps. Each slot also holds a sequence number
+/// that tells the turn of the slot. The slot of index `i` is free for the
+/// push of index `i` when its sequence number is `i`, and holds the value
+/// for the pop of index `i` when its sequence number is `i + 1`. Once the
+/// value is taken, the sequence number becomes `i + capacity`, which frees
+/// the slot for the push one lap later.
+///
+/// A push or a pop that finds the sequence number of its slot one lap
+/// behind knows that the queue is full or empty, without reading the other
+/// index. The producers and the consumers only contend on their own index,
+/// and the two indices sit on separate cache lines. Unlike a [`SpscRing`],
+/// the queue may be shared by any number of producers and consumers, at
+/// the cost of a compare-and-swap on every operation.
+///
+/// [`SpscRing`]: crate::concurrent::SpscRing
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::MpmcQueue;
+///
+/// let queue = Arc::new(MpmcQueue::new(64));
+/// let producers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let queue = Arc::clone(&queue);
+///         thread::spawn(move || {
+///             for i in 0..10 {
+///                 queue.try_push(t * 10 + i).unwrap();
+///             }
+///         })
+///     })
+///     .collect();
+/// for producer in producers {
+///     producer.join().unwrap();
+/// }
+///
+/// let mut values: Vec<_> = std::iter::from_fn(|| queue.try_pop()).collect();
+/// values.sort();
+/// assert_eq!(values, (0..40).collect::<Vec<_>>());
+/// ```
+pub struct MpmcQueue<T> {
+    slots: Box<[Slot<T>]>,
+    // The number of slots minus one.
+    mask: usize,
+    // The number of pops claimed.
+    head: CachePadded<AtomicUsize>,
+    // The number of pushes claimed.
+    tail: CachePadded<AtomicUsize>,
+}
+
+// The values are moved in and out of the queue by any thread, and a slot is
+// only used by the thread that claimed its turn.
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Send for MpmcQueue<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Sync for MpmcQueue<T> {}
+
+impl<T> fmt::Debug for MpmcQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MpmcQueue")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> MpmcQueue<T> {
+    /// Creates an empty MpmcQueue that holds at most `capacity` elements,
+    /// rounded up to the next power of two. The queue holds at least two
+    /// elements, since with a single slot, the sequence number of a full
+    /// slot would tell the next push that the slot is free.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue: MpmcQueue<u32> = MpmcQueue::new(5);
+    /// assert_eq!(queue.capacity(), 8);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        let capacity = capacity.max(2).next_power_of_two();
+        Self {
+            slots: (0..capacity)
+                .map(|i| Slot {
+                    seq: AtomicUsize::new(i),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect(),
+            mask: capacity - 1,
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the number of elements the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue: MpmcQueue<u32> = MpmcQueue::new(16);
+    /// assert_eq!(queue.capacity(), 16);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// Other threads may push or pop at the same time, so the answer may be
+    /// stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue = MpmcQueue::new(4);
+    /// queue.try_push(1).unwrap();
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            // The tail is read again, so the two reads belong together.
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head).min(self.capacity());
+            }
+        }
+    }
+
+    /// Returns true if the queue has no elements.
+    ///
+    /// Other threads may push or pop at the same time, so the answer may be
+    /// stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue: MpmcQueue<u32> = MpmcQueue::new(4);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends the element to the back of the queue, or returns it back if
+    /// the queue is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns the element if the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue = MpmcQueue::new(2);
+    /// assert_eq!(queue.try_push(1), Ok(()));
+    /// assert_eq!(queue.try_push(2), Ok(()));
+    /// assert_eq!(queue.try_push(3), Err(3));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_push(&self, value: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[tail & self.mask];
+            let seq = slot.seq.load(Ordering::Acquire);
+            let lag = seq.wrapping_sub(tail) as isize;
+            if lag == 0 {
+                // The slot is free for this push, if the turn is claimed.
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    tail.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: Only the thread that claimed the turn uses
+                        // the slot until the sequence number is published.
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.seq.store(tail.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if lag < 0 {
+                // The slot still holds the value pushed one lap earlier.
+                return Err(value);
+            } else {
+                // Another push claimed the turn first.
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the front element and returns it, or None if the queue is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MpmcQueue;
+    ///
+    /// let queue = MpmcQueue::new(4);
+    /// queue.try_push(1).unwrap();
+    /// assert_eq!(queue.try_pop(), Some(1));
+    /// assert_eq!(queue.try_pop(), None);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[head & self.mask];
+            let seq = slot.seq.load(Ordering::Acquire);
+            let lag = seq.wrapping_sub(head.wrapping_add(1)) as isize;
+            if lag == 0 {
+                // The slot holds the value for this pop, if the turn is
+                // claimed.
+                match self.head.compare_exchange_weak(
+                    head,
+                    head.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: Only the thread that claimed the turn uses
+                        // the slot until the sequence number is published.
+                        let value =
+                            unsafe { (*slot.value.get()).as_ptr().read() };
+                        slot.seq.store(
+                            head.wrapping_add(self.capacity()),
+                            Ordering::Release,
+                        );
+                        return Some(value);
+                    }
+                    Err(current) => head = current,
+                }
+            } else if lag < 0 {
+                // The slot has not been pushed to in this lap yet.
+                return None;
+            } else {
+                // Another pop claimed the turn first.
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for MpmcQueue<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut index = head;
+        while index != tail {
+            let slot = &mut self.slots[index & self.mask];
+            // SAFETY: No operation is in flight, so the slots between the
+            // head and the tail hold values.
+            unsafe { slot.value.get_mut().as_mut_ptr().drop_in_place() };
+            index = index.wrapping_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = MpmcQueue::new(32);
+        let popped: Vec<Vec<(usize, usize)>> = thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        let mut value = (p, i);
+                        while let Err(back) = queue.try_push(value) {
+                            value = back;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+            let consumers: Vec<_> = (0..CONSUMERS)
+                .map(|c| {
+                    let queue = &queue;
+                    s.spawn(move || {
+                        // The consumers split the elements between them.
+                        let mut count = PRODUCERS * PER_PRODUCER / CONSUMERS;
+                        if c == 0 {
+                            count += PRODUCERS * PER_PRODUCER % CONSUMERS;
+                        }
+                        let mut popped = Vec::with_capacity(count);
+                        while popped.len() < count {
+                            match queue.try_pop() {
+                                Some(value) => popped.push(value),
+                                None => thread::yield_now(),
+                            }
+                        }
+                        popped
+                    })
+                })
+                .collect();
+            consumers.into_iter().map(|c| c.join().unwrap()).collect()
+        });
+
+        let mut seen = vec![vec![false; PER_PRODUCER]; PRODUCERS];
+        for values in popped {
+            // Each consumer sees the elements of a producer in the order
+            // they were pushed.
+            let mut last = [None; PRODUCERS];
+            for (p, i) in values {
+                assert!(last[p].is_none_or(|j| j < i));
+                last[p] = Some(i);
+                assert!(!seen[p][i]);
+                seen[p][i] = true;
+            }
+        }
+        assert!(seen.iter().flatten().all(|&s| s));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn full_and_empty_across_laps() {
+        assert_eq!(MpmcQueue::<u32>::new(1).capacity(), 2);
+        let queue = MpmcQueue::new(3);
+        for lap in 0..5 {
+            for i in 0..4 {
+                assert_eq!(queue.try_push(lap * 4 + i), Ok(()));
+            }
+            assert_eq!(queue.try_push(0), Err(0));
+            assert_eq!(queue.len(), 4);
+            for i in 0..4 {
+                assert_eq!(queue.try_pop(), Some(lap * 4 + i));
+            }
+            assert_eq!(queue.try_pop(), None);
+        }
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let value = Arc::new(());
+        let queue = MpmcQueue::new(4);
+        for _ in 0..4 {
+            queue.try_push(Arc::clone(&value)).unwrap();
+        }
+        drop(queue.try_pop());
+        assert_eq!(Arc::strong_count(&value), 4);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,345 @@
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use super::epoch;
+
+struct Node<T> {
+    // Uninitialized in the sentinel node at the head, whose value was either
+    // never set or already taken.
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(value: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+/// An unbounded lock-free multi-producer multi-consumer FIFO queue, by
+/// Michael and Scott.
+///
+/// The elements are kept in a singly linked list that starts with a
+/// sentinel node. The head points to the sentinel, whose successor holds the
+/// front element, and the tail points to the last node, or lags one node
+/// behind it. A push links a new node after the last one with a
+/// compare-and-swap, and then swings the tail to it. A pop swings the head
+/// to the successor of the sentinel with a compare-and-swap, takes the value
+/// out of the successor, which becomes the new sentinel, and retires the old
+/// one. A thread that finds the tail lagging behind swings it forward
+/// before going on, so no thread waits for another one to finish its
+/// operation.
+///
+/// The linearization points are:
+///
+/// - for a push, the compare-and-swap that links the new node;
+/// - for a successful pop, the compare-and-swap that swings the head;
+/// - for a pop that finds the queue empty, the load of the null successor of
+///   the sentinel.
+///
+/// The retired nodes are freed with [epoch-based reclamation], so a node is
+/// never freed while another thread may still read it.
+///
+/// [epoch-based reclamation]: crate::concurrent::epoch
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::MsQueue;
+///
+/// let queue = Arc::new(MsQueue::new());
+/// let producers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let queue = Arc::clone(&queue);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 queue.push(t * 100 + i);
+///             }
+///         })
+///     })
+///     .collect();
+/// for producer in producers {
+///     producer.join().unwrap();
+/// }
+///
+/// let mut values: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+/// values.sort();
+/// assert_eq!(values, (0..400).collect::<Vec<_>>());
+/// ```
+pub struct MsQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+}
+
+// The values are moved in and out of the queue by any thread, and never
+// shared between threads.
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Send for MsQueue<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T> Default for MsQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for MsQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsQueue").finish_non_exhaustive()
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T> MsQueue<T> {
+    /// Creates an empty MsQueue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MsQueue;
+    ///
+    /// let queue: MsQueue<u32> = MsQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        let sentinel = Node::new(MaybeUninit::uninit());
+        Self {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+        }
+    }
+
+    /// Returns true if the queue has no elements.
+    ///
+    /// Other threads may push or pop at the same time, so the answer may be
+    /// stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// assert!(queue.is_empty());
+    /// queue.push(1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        let _guard = epoch::pin();
+        let head = self.head.load(Ordering::Acquire);
+        // SAFETY: The thread is pinned, so the node is not freed.
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+
+    /// Appends the element to the back of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.pop(), Some(1));
+    /// ```
+    pub fn push(&self, value: T) {
+        let node = Node::new(MaybeUninit::new(value));
+        let _guard = epoch::pin();
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: The thread is pinned, so the node is not freed.
+            let next = unsafe { &(*tail).next };
+            let last = next.load(Ordering::Acquire);
+            if !last.is_null() {
+                // The tail lags behind, so it is swung forward first.
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    last,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+            if next
+                .compare_exchange(
+                    ptr::null_mut(),
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                // Another thread may have swung the tail already.
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                return;
+            }
+        }
+    }
+
+    /// Removes the front element and returns it, or None if the queue is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::MsQueue;
+    ///
+    /// let queue = MsQueue::new();
+    /// queue.push(1);
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: The thread is pinned, so the node is not freed.
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                return None;
+            }
+            if head == tail {
+                // The head must not pass the tail, or the tail would point to
+                // a retired node.
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(
+                    head,
+                    next,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .is_ok()
+            {
+                // SAFETY: Only the thread that swung the head past the old
+                // sentinel takes the value of the new one, and the old one
+                // is unlinked and retired once.
+                unsafe {
+                    let value = (*next).value.as_ptr().read();
+                    guard.defer_destroy(head);
+                    return Some(value);
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: No other thread can reach the nodes anymore. The sentinel
+        // has no value, and the nodes after it all hold one.
+        unsafe {
+            let sentinel = Box::from_raw(*self.head.get_mut());
+            let mut node = sentinel.next.load(Ordering::Relaxed);
+            while !node.is_null() {
+                let mut boxed = Box::from_raw(node);
+                boxed.value.as_mut_ptr().drop_in_place();
+                node = boxed.next.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = Arc::new(MsQueue::new());
+        let done = Arc::new(AtomicUsize::new(0));
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = Arc::clone(&queue);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i));
+                    }
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    loop {
+                        let finished = done.load(Ordering::SeqCst) == PRODUCERS;
+                        match queue.pop() {
+                            Some(value) => popped.push(value),
+                            None if finished => return popped,
+                            None => thread::yield_now(),
+                        }
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut seen = vec![vec![false; PER_PRODUCER]; PRODUCERS];
+        for consumer in consumers {
+            let popped = consumer.join().unwrap();
+            // Each consumer sees the elements of a producer in the order
+            // they were pushed.
+            let mut last = [None; PRODUCERS];
+            for (p, i) in popped {
+                assert!(last[p].is_none_or(|j| j < i));
+                last[p] = Some(i);
+                assert!(!seen[p][i]);
+                seen[p][i] = true;
+            }
+        }
+        assert!(seen.iter().flatten().all(|&s| s));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let value = Arc::new(());
+        let queue = MsQueue::new();
+        for _ in 0..10 {
+            queue.push(Arc::clone(&value));
+        }
+        drop(queue.pop());
+        assert_eq!(Arc::strong_count(&value), 10);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,322 @@
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+
+use super::epoch::{self, Guard};
+
+/// A cell that can be shared between threads, whose value is read through
+/// snapshots and replaced as a whole, in the style of read-copy-update.
+///
+/// The value is kept behind a pointer. A reader pins itself, loads the
+/// pointer, and reads the value it points to for as long as it holds the
+/// [`Snapshot`], without taking any lock or waiting for other threads. A
+/// writer builds the new value, usually from a copy of the current one, and
+/// swaps the pointer to it, so readers see either the old value or the new
+/// one, and never a value that is partially updated. The old value is freed
+/// with [epoch-based reclamation], once the readers that may still hold a
+/// snapshot of it are gone.
+///
+/// The writers are serialized by a lock, so an update is never lost to
+/// another one that read the same value. This suits values that are read
+/// much more often than they are written, since every write copies the
+/// value.
+///
+/// [epoch-based reclamation]: crate::concurrent::epoch
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::RcuCell;
+///
+/// let config = Arc::new(RcuCell::new(vec!["a"]));
+/// let reader = {
+///     let config = Arc::clone(&config);
+///     thread::spawn(move || {
+///         let snapshot = config.read();
+///         // The snapshot stays the same, whatever the writer does.
+///         assert!(*snapshot == ["a"] || *snapshot == ["a", "b"]);
+///     })
+/// };
+/// config.update(|v| {
+///     let mut v = v.clone();
+///     v.push("b");
+///     v
+/// });
+/// reader.join().unwrap();
+/// assert_eq!(*config.read(), ["a", "b"]);
+/// ```
+pub struct RcuCell<T> {
+    ptr: AtomicPtr<T>,
+    writer: Mutex<()>,
+}
+
+// The value is read by any thread, and dropped by whichever thread frees it.
+#[allow(unsafe_code)]
+unsafe impl<T: Send + Sync> Send for RcuCell<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Send + Sync> Sync for RcuCell<T> {}
+
+impl<T: Default> Default for RcuCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RcuCell").field(&*self.read()).finish()
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T> RcuCell<T> {
+    /// Creates an RcuCell holding the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let cell = RcuCell::new(1);
+    /// assert_eq!(*cell.read(), 1);
+    /// ```
+    pub fn new(value: T) -> Self {
+        Self {
+            ptr: AtomicPtr::new(Box::into_raw(Box::new(value))),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns a snapshot of the current value.
+    ///
+    /// The snapshot keeps the thread pinned, so no retired value is freed
+    /// while it is alive, and it should not be held for long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let cell = RcuCell::new(1);
+    /// let snapshot = cell.read();
+    /// cell.store(2);
+    /// assert_eq!(*snapshot, 1);
+    /// assert_eq!(*cell.read(), 2);
+    /// ```
+    pub fn read(&self) -> Snapshot<'_, T> {
+        let guard = epoch::pin();
+        let ptr = self.ptr.load(Ordering::Acquire);
+        // SAFETY: The thread is pinned, so the value is not freed while the
+        // snapshot is alive.
+        let value = unsafe { &*ptr };
+        Snapshot {
+            _guard: guard,
+            value,
+        }
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let mut cell = RcuCell::new(1);
+    /// *cell.get_mut() += 1;
+    /// assert_eq!(*cell.read(), 2);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: The cell is borrowed mutably, so there are no readers.
+        unsafe { &mut **self.ptr.get_mut() }
+    }
+
+    /// Consumes the cell, and returns the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let cell = RcuCell::new(1);
+    /// assert_eq!(cell.into_inner(), 1);
+    /// ```
+    pub fn into_inner(mut self) -> T {
+        let ptr = *self.ptr.get_mut();
+        mem::forget(self);
+        // SAFETY: The cell is consumed, so there are no readers, and its
+        // destructor does not run.
+        *unsafe { Box::from_raw(ptr) }
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T> RcuCell<T>
+where
+    T: Send + 'static,
+{
+    /// Replaces the value with the one returned by the closure, which is
+    /// given the current value.
+    ///
+    /// The other writers wait until the update is done, so no update is
+    /// lost, while the readers keep reading the current value until the new
+    /// one is stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let cell = RcuCell::new(vec![1, 2]);
+    /// cell.update(|v| v.iter().map(|x| x * 10).collect());
+    /// assert_eq!(*cell.read(), [10, 20]);
+    /// ```
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let _writer = self.writer.lock().unwrap();
+        let current = self.ptr.load(Ordering::Acquire);
+        // SAFETY: Only the writers swap the value out, and they are locked
+        // out.
+        let value = f(unsafe { &*current });
+        self.swap(value);
+    }
+
+    /// Replaces the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuCell;
+    ///
+    /// let cell = RcuCell::new(1);
+    /// cell.store(2);
+    /// assert_eq!(*cell.read(), 2);
+    /// ```
+    pub fn store(&self, value: T) {
+        let _writer = self.writer.lock().unwrap();
+        self.swap(value);
+    }
+
+    fn swap(&self, value: T) {
+        let guard = epoch::pin();
+        let new = Box::into_raw(Box::new(value));
+        let old = self.ptr.swap(new, Ordering::AcqRel);
+        // SAFETY: The old value can no longer be loaded from the cell, and
+        // is only swapped out once.
+        unsafe { guard.defer_destroy(old) };
+    }
+}
+
+impl<T> Drop for RcuCell<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: No snapshot outlives the cell, and the current value was
+        // never retired.
+        drop(unsafe { Box::from_raw(*self.ptr.get_mut()) });
+    }
+}
+
+/// A snapshot of the value of an `RcuCell`.
+///
+/// This struct is created by [`RcuCell::read()`]. See its documentation for
+/// more.
+pub struct Snapshot<'a, T> {
+    _guard: Guard,
+    value: &'a T,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Snapshot<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> Deref for Snapshot<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn readers_see_whole_values() {
+        const READERS: usize = 3;
+        const UPDATES: usize = 2_000;
+
+        // Every value holds the same number in all of its elements.
+        let cell = Arc::new(RcuCell::new(vec![0; 16]));
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let cell = Arc::clone(&cell);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(Ordering::SeqCst) {
+                        let snapshot = cell.read();
+                        assert!(snapshot.iter().all(|&x| x == snapshot[0]));
+                        // The updates are seen in order.
+                        assert!(snapshot[0] >= last);
+                        last = snapshot[0];
+                        drop(snapshot);
+                        thread::yield_now();
+                    }
+                })
+            })
+            .collect();
+        let writers: Vec<_> = (0..2)
+            .map(|_| {
+                let cell = Arc::clone(&cell);
+                thread::spawn(move || {
+                    for _ in 0..UPDATES {
+                        cell.update(|v| v.iter().map(|x| x + 1).collect());
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(*cell.read(), vec![2 * UPDATES; 16]);
+    }
+
+    #[test]
+    fn drops_values() {
+        let value = Arc::new(());
+        let cell = RcuCell::new(Arc::clone(&value));
+        for _ in 0..10 {
+            cell.store(Arc::clone(&value));
+        }
+        drop(cell);
+        // The replaced values are only dropped once the epoch has moved,
+        // which other tests may hold back for a while.
+        for _ in 0..10_000 {
+            if Arc::strong_count(&value) == 1 {
+                break;
+            }
+            epoch::pin().flush();
+            thread::yield_now();
+        }
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,274 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use super::RcuCell;
+use crate::containers::PersistentOrdMap;
+
+/// An ordered map that can be shared between threads, whose readers never
+/// wait, built on an [`RcuCell`] holding a [`PersistentOrdMap`].
+///
+/// The readers look up keys in the current version of the map, or take a
+/// snapshot of it to iterate over a version that does not change. A writer
+/// builds the next version and swaps it in. Copying the map on every write
+/// would take linear time, but a version of a [`PersistentOrdMap`] shares
+/// all but a logarithmic number of its nodes with the previous one, so a
+/// write only copies the path to the key.
+///
+/// [`RcuCell`]: crate::concurrent::RcuCell
+/// [`PersistentOrdMap`]: crate::containers::PersistentOrdMap
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::RcuMap;
+///
+/// let routes = Arc::new(RcuMap::new());
+/// routes.insert("/", 1);
+/// let reader = {
+///     let routes = Arc::clone(&routes);
+///     thread::spawn(move || {
+///         let snapshot = routes.snapshot();
+///         // The snapshot has either one route or both.
+///         assert!(snapshot.len() == 1 || snapshot.len() == 2);
+///         assert_eq!(snapshot.get("/"), Some(&1));
+///     })
+/// };
+/// routes.insert("/about", 2);
+/// reader.join().unwrap();
+/// assert_eq!(routes.get("/about"), Some(2));
+/// ```
+pub struct RcuMap<K, V> {
+    cell: RcuCell<PersistentOrdMap<K, V>>,
+}
+
+impl<K, V> Default for RcuMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RcuMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.cell.read().iter()).finish()
+    }
+}
+
+impl<K, V> RcuMap<K, V> {
+    /// Creates an empty RcuMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map: RcuMap<i32, i32> = RcuMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            cell: RcuCell::new(PersistentOrdMap::new()),
+        }
+    }
+
+    /// Returns the current version of the map, which is not affected by
+    /// later writes.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// map.insert(1, "a");
+    /// let snapshot = map.snapshot();
+    /// map.insert(2, "b");
+    /// assert_eq!(snapshot.len(), 1);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn snapshot(&self) -> PersistentOrdMap<K, V> {
+        self.cell.read().clone()
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.cell.read().len()
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.cell.read().is_empty()
+    }
+}
+
+impl<K: Ord, V> RcuMap<K, V> {
+    /// Returns a clone of the value corresponding to the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some("a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        self.cell.read().get(key).cloned()
+    }
+
+    /// Returns true if the map contains a value for the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.cell.read().contains_key(key)
+    }
+}
+
+impl<K, V> RcuMap<K, V>
+where
+    K: Ord + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Inserts a key-value pair into the map, and returns the old value of
+    /// the key if the map had it.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.insert(37, "b"), Some("a"));
+    /// assert_eq!(map.get(&37), Some("b"));
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        self.cell.update(|map| {
+            old = map.get(&key).cloned();
+            map.insert(key, value)
+        });
+        old
+    }
+
+    /// Removes a key from the map, and returns its value if the map had it.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::RcuMap;
+    ///
+    /// let map = RcuMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut old = None;
+        self.cell.update(|map| {
+            old = map.get(key).cloned();
+            map.remove(key)
+        });
+        old
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_writers_and_snapshots() {
+        const THREADS: u64 = 4;
+        const PER_THREAD: u64 = 500;
+
+        let map = Arc::new(RcuMap::new());
+        let threads: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    for i in 0..PER_THREAD {
+                        let key = t * PER_THREAD + i;
+                        assert_eq!(map.insert(key, i), None);
+                        if i.is_multiple_of(3) {
+                            assert_eq!(map.remove(&key), Some(i));
+                        }
+                        // A snapshot is a consistent version of the map.
+                        let snapshot = map.snapshot();
+                        assert_eq!(snapshot.iter().count(), snapshot.len());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let expected: BTreeMap<_, _> = (0..THREADS)
+            .flat_map(|t| (0..PER_THREAD).map(move |i| (t * PER_THREAD + i, i)))
+            .filter(|(_, i)| !i.is_multiple_of(3))
+            .collect();
+        let snapshot = map.snapshot();
+        assert!(snapshot.iter().map(|(k, v)| (*k, *v)).eq(expected));
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt;
+use std::ptr;
+
+#[cfg(all(test, loom))]
+use loom::{
+    cell::UnsafeCell,
+    hint,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+    thread,
+};
+#[cfg(not(all(test, loom)))]
+use std::{
+    hint,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+    thread,
+};
+
+// The value is accessed through closures, like loom's cell, so that loom can
+// check the accesses when the tests are run under it.
+#[cfg(not(all(test, loom)))]
+struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(not(all(test, loom)))]
+impl<T> UnsafeCell<T> {
+    const fn new(value: T) -> Self {
+        Self(std::cell::UnsafeCell::new(value))
+    }
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(*const T) -> R,
+    {
+        f(self.0.get())
+    }
+
+    fn with_mut<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(*mut T) -> R,
+    {
+        f(self.0.get())
+    }
+
+    fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+/// The number of times a thread spins before it yields to the others while
+/// it waits for a writer.
+const SPINS: u32 = 16;
+
+// Waits a little longer at every attempt, and lets the writer run once
+// spinning took too long, since it may not be running at all.
+fn backoff(attempt: &mut u32) {
+    if *attempt < SPINS {
+        for _ in 0..1 << (*attempt).min(6) {
+            hint::spin_loop();
+        }
+        *attempt += 1;
+    } else {
+        thread::yield_now();
+    }
+}
+
+/// A lock around a value that is read optimistically, without writing to
+/// shared memory, and written exclusively.
+///
+/// The lock is a sequence number that is odd while a write is in progress.
+/// A writer makes it odd, writes the value, and makes it even again, so
+/// every write adds two to it. A reader loads the sequence number, copies
+/// the value, and loads the sequence number again. If both are the same
+/// even number, no write overlapped the copy, and it is returned. Otherwise
+/// the copy may be torn, with parts of the old value and of the new one, and
+/// it is thrown away and read again.
+///
+/// Readers never block writers, and do not contend with each other on a
+/// cache line, so the lock suits small values that are read very often and
+/// seldom written, such as a pair of timestamps or statistics. It has
+/// constraints that a [`RwLock`] does not have:
+///
+/// - The value must be [`Copy`], since a torn copy is thrown away without
+///   being dropped, and it is returned by value rather than borrowed.
+/// - A reader may retry forever while writers keep writing, so frequent
+///   writes starve the readers.
+/// - The copy of the value races with the writes, which the Rust memory
+///   model does not allow for non-atomic accesses. The copy is volatile, and
+///   its result is only used once the sequence numbers show that no write
+///   overlapped it, which is how seqlocks are written in practice, but tools
+///   that check for data races, such as Miri and loom, report it.
+///
+/// The tests of the lock can be run under loom, which checks them in every
+/// possible interleaving of the threads:
+///
+/// ```text
+/// RUSTFLAGS="--cfg loom" cargo test --release --lib seq_lock
+/// ```
+///
+/// [`RwLock`]: std::sync::RwLock
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::SeqLock;
+///
+/// // Both coordinates always move together.
+/// let position = Arc::new(SeqLock::new((0, 0)));
+/// let reader = {
+///     let position = Arc::clone(&position);
+///     thread::spawn(move || {
+///         for _ in 0..1000 {
+///             let (x, y) = position.read();
+///             assert_eq!(x, y);
+///         }
+///     })
+/// };
+/// for i in 1..=1000 {
+///     position.write((i, i));
+/// }
+/// reader.join().unwrap();
+/// assert_eq!(position.read(), (1000, 1000));
+/// ```
+pub struct SeqLock<T> {
+    seq: AtomicUsize,
+    value: UnsafeCell<T>,
+}
+
+// The value is copied in and out by any thread, and only written by one
+// thread at a time.
+#[allow(unsafe_code)]
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SeqLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SeqLock").field(&self.read()).finish()
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T: Copy> SeqLock<T> {
+    /// Creates a SeqLock holding the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    /// assert_eq!(lock.read(), 1);
+    /// ```
+    pub fn new(value: T) -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Returns a copy of the value, retrying until no write overlaps the
+    /// copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new([1, 2, 3]);
+    /// assert_eq!(lock.read(), [1, 2, 3]);
+    /// ```
+    pub fn read(&self) -> T {
+        let mut attempt = 0;
+        loop {
+            if let Some(value) = self.try_read() {
+                return value;
+            }
+            backoff(&mut attempt);
+        }
+    }
+
+    /// Returns a copy of the value, or None if a write is in progress or
+    /// overlaps the copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    /// assert_eq!(lock.try_read(), Some(1));
+    /// ```
+    pub fn try_read(&self) -> Option<T> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq & 1 == 1 {
+            return None;
+        }
+        // SAFETY: The pointer is valid, and the copy is only returned if no
+        // write overlapped it, in which case it is not torn.
+        let value = self.value.with(|ptr| unsafe { ptr::read_volatile(ptr) });
+        // The copy is done before the sequence number is checked again.
+        fence(Ordering::Acquire);
+        (self.seq.load(Ordering::Relaxed) == seq).then_some(value)
+    }
+
+    /// Replaces the value, waiting for the other writers to finish first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    /// lock.write(2);
+    /// assert_eq!(lock.read(), 2);
+    /// ```
+    pub fn write(&self, value: T) {
+        let _guard = self.lock();
+        // SAFETY: The sequence number is odd, so the other writers are
+        // locked out, and the readers throw away what they copy.
+        self.value
+            .with_mut(|ptr| unsafe { ptr::write_volatile(ptr, value) });
+    }
+
+    /// Replaces the value with the one returned by the closure, which is
+    /// given the current value, while the other writers wait.
+    ///
+    /// If the closure panics, the value is left as it was and the lock is
+    /// released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    /// lock.update(|x| x + 1);
+    /// assert_eq!(lock.read(), 2);
+    /// ```
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(T) -> T,
+    {
+        let _guard = self.lock();
+        // SAFETY: The sequence number is odd, so the other writers are
+        // locked out, and the readers throw away what they copy. The value
+        // is only written once `f` has returned.
+        self.value.with_mut(|ptr| unsafe {
+            ptr::write_volatile(ptr, f(ptr::read(ptr)));
+        });
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let mut lock = SeqLock::new(1);
+    /// *lock.get_mut() += 1;
+    /// assert_eq!(lock.read(), 2);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: The lock is borrowed mutably, so no other thread can
+        // access the value.
+        self.value.with_mut(|ptr| unsafe { &mut *ptr })
+    }
+
+    /// Consumes the lock, and returns the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    /// assert_eq!(lock.into_inner(), 1);
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    // Makes the sequence number odd, and returns a guard that makes it even
+    // again when it is dropped.
+    fn lock(&self) -> WriteGuard<'_, T> {
+        let mut attempt = 0;
+        loop {
+            let seq = self.seq.load(Ordering::Relaxed);
+            if seq & 1 == 0
+                && self
+                    .seq
+                    .compare_exchange_weak(
+                        seq,
+                        seq.wrapping_add(1),
+                        Ordering::Acquire,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                // The value is written after the sequence number is odd.
+                fence(Ordering::Release);
+                return WriteGuard { lock: self, seq };
+            }
+            backoff(&mut attempt);
+        }
+    }
+}
+
+// Releases the lock taken by a writer, even if the writer panics, so that
+// the other writers and the readers are not locked out forever.
+struct WriteGuard<'a, T> {
+    lock: &'a SeqLock<T>,
+    // The even sequence number from before the lock was taken.
+    seq: usize,
+}
+
+impl<'a, T> Drop for WriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock
+            .seq
+            .store(self.seq.wrapping_add(2), Ordering::Release);
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    #[test]
+    fn readers_never_see_torn_values() {
+        const READERS: usize = 3;
+        const WRITERS: usize = 2;
+        const UPDATES: u64 = 5_000;
+
+        // Every value holds the same number in all of its elements.
+        let lock = Arc::new(SeqLock::new([0u64; 8]));
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(Ordering::SeqCst) {
+                        let value = lock.read();
+                        assert!(value.iter().all(|&x| x == value[0]));
+                        // The writes are seen in order.
+                        assert!(value[0] >= last);
+                        last = value[0];
+                    }
+                })
+            })
+            .collect();
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || {
+                    for _ in 0..UPDATES {
+                        lock.update(|v| v.map(|x| x + 1));
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(lock.read(), [WRITERS as u64 * UPDATES; 8]);
+    }
+
+    #[test]
+    fn panicking_update_releases_the_lock() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let lock = SeqLock::new(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            lock.update(|_| panic!("update failed"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(lock.try_read(), Some(1));
+
+        lock.update(|x| x + 1);
+        lock.write(lock.read() * 10);
+        assert_eq!(lock.read(), 20);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+
+    #[test]
+    fn writers_exclude_each_other() {
+        loom::model(|| {
+            let lock = Arc::new(SeqLock::new(0));
+            let writer = {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || lock.update(|x| x + 1))
+            };
+            lock.update(|x| x + 1);
+            writer.join().unwrap();
+            assert_eq!(lock.read(), 2);
+        });
+    }
+
+    #[test]
+    fn reads_after_a_write_see_it() {
+        loom::model(|| {
+            let lock = Arc::new(SeqLock::new(0));
+            let writer = {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || lock.write(1))
+            };
+            writer.join().unwrap();
+            assert_eq!(lock.try_read(), Some(1));
+        });
+    }
+
+    // The copy made by a reader is not ordered with the write it overlaps,
+    // which loom reports as a data race, even though the sequence numbers
+    // make the reader throw the copy away.
+    #[test]
+    #[should_panic]
+    fn reads_race_with_writes() {
+        loom::model(|| {
+            let lock = Arc::new(SeqLock::new(0));
+            let reader = {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || {
+                    if let Some(value) = lock.try_read() {
+                        assert!(value == 0 || value == 1);
+                    }
+                })
+            };
+            lock.write(1);
+            reader.join().unwrap();
+        });
+    }
+}
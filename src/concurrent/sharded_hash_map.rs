@@ -0,0 +1,419 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::RwLock;
+use std::thread;
+
+use super::cache_padded::CachePadded;
+use crate::containers::{DefaultHashBuilder, LinkedHashMap};
+
+type Shard<K, V, S> = RwLock<LinkedHashMap<K, V, S>>;
+
+/// A hash map that can be shared between threads, made of a fixed number of
+/// [`LinkedHashMap`] shards that are each protected by a [`RwLock`].
+///
+/// A key is hashed once to pick its shard, and only that shard is locked to
+/// operate on the key, so threads that work on keys in different shards do
+/// not contend with each other. Lookups only take a read lock, so they also
+/// run alongside each other within a shard. The shard is chosen from the
+/// high bits of the hash, while a shard picks its bucket from the remainder
+/// of the hash, so the keys of a shard still spread over all of its buckets.
+///
+/// The operations that go over all shards, such as [`len`] and
+/// [`for_each`], lock one shard at a time. They do not see a snapshot of the
+/// whole map if other threads modify it at the same time.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`len`]: ShardedHashMap::len
+/// [`for_each`]: ShardedHashMap::for_each
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::ShardedHashMap;
+///
+/// let map = Arc::new(ShardedHashMap::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let map = Arc::clone(&map);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 map.insert(t * 100 + i, i);
+///             }
+///         })
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+///
+/// assert_eq!(map.len(), 400);
+/// assert_eq!(map.get_cloned(&142), Some(42));
+/// ```
+pub struct ShardedHashMap<K, V, S = DefaultHashBuilder> {
+    shards: Box<[CachePadded<Shard<K, V, S>>]>,
+    hasher_builder: S,
+}
+
+impl<K, V, S> fmt::Debug for ShardedHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedHashMap")
+            .field("shards", &self.shards.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Default for ShardedHashMap<K, V, DefaultHashBuilder> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ShardedHashMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty `ShardedHashMap` with four shards for each thread
+    /// that the system can run in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map: ShardedHashMap<&str, i32> = ShardedHashMap::new();
+    /// assert!(map.shards() >= 4);
+    /// ```
+    pub fn new() -> Self {
+        let parallelism =
+            thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(4 * parallelism)
+    }
+
+    /// Creates an empty `ShardedHashMap` with the given number of shards.
+    ///
+    /// # Panics
+    ///
+    /// Shard count must be positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map: ShardedHashMap<&str, i32> = ShardedHashMap::with_shards(16);
+    /// assert_eq!(map.shards(), 16);
+    /// ```
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_and_hasher(shards, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> ShardedHashMap<K, V, S> {
+    /// Creates an empty `ShardedHashMap` with the given number of shards,
+    /// which will use the given hash builder to hash keys.
+    ///
+    /// The same hash builder is used to pick the shard of a key and, cloned
+    /// into every shard, to pick its bucket within the shard.
+    ///
+    /// # Panics
+    ///
+    /// Shard count must be positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let s = RandomState::new();
+    /// let map = ShardedHashMap::with_shards_and_hasher(8, s);
+    /// map.insert(1, 2);
+    /// ```
+    pub fn with_shards_and_hasher(shards: usize, hasher_builder: S) -> Self
+    where
+        S: Clone,
+    {
+        assert!(shards > 0, "Shard count must be positive");
+        let shards = (0..shards)
+            .map(|_| {
+                let shard = LinkedHashMap::with_hasher(hasher_builder.clone());
+                CachePadded::new(RwLock::new(shard))
+            })
+            .collect();
+        Self {
+            shards,
+            hasher_builder,
+        }
+    }
+
+    /// Returns the number of shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map: ShardedHashMap<&str, i32> = ShardedHashMap::with_shards(3);
+    /// assert_eq!(map.shards(), 3);
+    /// ```
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let s = RandomState::new();
+    /// let map: ShardedHashMap<i32, i32> =
+    ///     ShardedHashMap::with_shards_and_hasher(8, s);
+    /// let hasher: &RandomState = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Returns the number of elements in the map, which is summed over all
+    /// shards.
+    ///
+    /// This operation should compute in O(s) time, where s is the number of
+    /// shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// assert_eq!(map.len(), 0);
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap().len())
+            .sum()
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// This operation should compute in O(s) time, where s is the number of
+    /// shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| shard.read().unwrap().is_empty())
+    }
+
+    /// Calls the closure on every key-value pair of the map.
+    ///
+    /// The shards are visited one after another, and each of them is
+    /// read-locked while the closure is called on its entries, which are
+    /// visited in the order they were inserted into the shard. The closure
+    /// must not modify the map, or it may deadlock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    ///
+    /// let mut sum = 0;
+    /// map.for_each(|_, v| sum += v);
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V),
+    {
+        for shard in self.shards.iter() {
+            for (k, v) in shard.read().unwrap().iter() {
+                f(k, v);
+            }
+        }
+    }
+}
+
+impl<K, V, S> ShardedHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, None is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the
+    /// old value is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.insert(37, "b"), Some("a"));
+    /// assert_eq!(map.get_cloned(&37), Some("b"));
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).write().unwrap().insert(key, value)
+    }
+
+    /// Returns a clone of the value corresponding to the key.
+    ///
+    /// The value is cloned while the shard is read-locked, because a
+    /// reference to it could not outlive the lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// map.insert(1, "a".to_string());
+    /// assert_eq!(map.get_cloned(&1), Some("a".to_string()));
+    /// assert_eq!(map.get_cloned(&2), None);
+    /// ```
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.shard(key).read().unwrap().get(key).cloned()
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).read().unwrap().contains_key(key)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::ShardedHashMap;
+    ///
+    /// let map = ShardedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).write().unwrap().remove(key)
+    }
+
+    fn shard<Q>(&self, key: &Q) -> &Shard<K, V, S>
+    where
+        Q: Hash + ?Sized,
+    {
+        // Multiplying by the number of shards and keeping the high half maps
+        // the hash onto the shards by its high bits.
+        let hash = u128::from(self.hasher_builder.hash_one(key));
+        let index = (hash * self.shards.len() as u128) >> 64;
+        &self.shards[index as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn concurrent_writers_and_readers() {
+        const THREADS: u64 = 4;
+        const PER_THREAD: u64 = 2_000;
+
+        let map = Arc::new(ShardedHashMap::with_shards(7));
+        let threads: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    for i in 0..PER_THREAD {
+                        let key = t * PER_THREAD + i;
+                        assert_eq!(map.insert(key, i), None);
+                        assert_eq!(map.get_cloned(&key), Some(i));
+                        // Removes a quarter of the keys again.
+                        if i.is_multiple_of(4) {
+                            assert_eq!(map.remove(&key), Some(i));
+                            assert!(!map.contains_key(&key));
+                        }
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut expected = HashMap::new();
+        for t in 0..THREADS {
+            for i in 0..PER_THREAD {
+                if !i.is_multiple_of(4) {
+                    expected.insert(t * PER_THREAD + i, i);
+                }
+            }
+        }
+        let mut actual = HashMap::new();
+        map.for_each(|&k, &v| assert!(actual.insert(k, v).is_none()));
+        assert_eq!(actual, expected);
+        assert_eq!(map.len(), expected.len());
+    }
+
+    #[test]
+    fn keys_spread_over_shards() {
+        let map = ShardedHashMap::with_shards(8);
+        for i in 0..8_000 {
+            map.insert(i, ());
+        }
+        for shard in map.shards.iter() {
+            let len = shard.read().unwrap().len();
+            assert!(len > 500 && len < 1_500, "unbalanced shard: {}", len);
+        }
+    }
+}
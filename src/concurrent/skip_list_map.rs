@@ -0,0 +1,668 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{
+    AtomicIsize, AtomicPtr, AtomicU64, AtomicUsize, Ordering,
+};
+
+use super::epoch::{self, Guard};
+
+const MAX_HEIGHT: usize = 32;
+
+type Tower<K, V> = [AtomicPtr<Node<K, V>>];
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The inserter building the tower and the remover that marked the node
+    // each hold a reference, and the last one to drop it retires the node.
+    refs: AtomicUsize,
+    // The successors of the node at each level. The lowest bit of a pointer
+    // marks the node as removed at that level.
+    tower: Box<Tower<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, height: usize) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            key,
+            value,
+            refs: AtomicUsize::new(2),
+            tower: (0..height).map(|_| AtomicPtr::default()).collect(),
+        }))
+    }
+}
+
+fn tag<T>(ptr: *mut T) -> *mut T {
+    ptr.map_addr(|addr| addr | 1)
+}
+
+fn untag<T>(ptr: *mut T) -> *mut T {
+    ptr.map_addr(|addr| addr & !1)
+}
+
+fn is_tagged<T>(ptr: *mut T) -> bool {
+    ptr.addr() & 1 == 1
+}
+
+// The nodes around the position of a key at each level: the last node whose
+// key is smaller, given by its tower, and the first node whose key is not.
+struct Position<'a, K, V> {
+    preds: [&'a Tower<K, V>; MAX_HEIGHT],
+    succs: [*mut Node<K, V>; MAX_HEIGHT],
+}
+
+/// An ordered map that can be shared between threads, implemented as a
+/// lock-free skip list, by Fraser.
+///
+/// The entries are kept sorted in a linked list at the bottom level, and
+/// each entry is also linked into a random number of the lists above it,
+/// with every level holding about half of the entries of the level below.
+/// A search goes down from the top level, moving forward in a level as long
+/// as the next key is smaller, so it skips over most of the entries.
+///
+/// An insert links the new node into the bottom level with a
+/// compare-and-swap, which makes it visible, and then links it into the
+/// levels above one by one. A remove marks the successor pointers of the
+/// node from the top level down. Marking the bottom level removes the entry
+/// from the map, and the searches that come across a marked node unlink it
+/// from the level they are in. The unlinked nodes are freed with
+/// [epoch-based reclamation], so a node is never freed while another thread
+/// may still read it.
+///
+/// The linearization points are:
+///
+/// - for an insert, the compare-and-swap that links the node into the bottom
+///   level;
+/// - for a remove, the compare-and-swap that marks the bottom level;
+/// - for an operation that finds the key absent, or present, the load of
+///   the bottom-level pointer to the node that follows its position, or of
+///   the successor of its node.
+///
+/// The values are never modified after an insert, so inserting a key that
+/// is already present leaves the map unchanged, and [`get`] returns a clone
+/// of the value.
+///
+/// [epoch-based reclamation]: crate::concurrent::epoch
+/// [`get`]: SkipListMap::get
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dt::concurrent::SkipListMap;
+///
+/// let map = Arc::new(SkipListMap::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let map = Arc::clone(&map);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 map.insert(i * 4 + t, t);
+///             }
+///         })
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+///
+/// assert_eq!(map.len(), 400);
+/// assert_eq!(map.get(&42), Some(2));
+/// let keys: Vec<_> = map.iter().map(|(k, _)| k).collect();
+/// assert_eq!(keys, (0..400).collect::<Vec<_>>());
+/// ```
+pub struct SkipListMap<K, V> {
+    head: [AtomicPtr<Node<K, V>>; MAX_HEIGHT],
+    // The number of levels in use, which never decreases.
+    height: AtomicUsize,
+    // May briefly go below zero when an entry is removed before its insert
+    // has counted it.
+    len: AtomicIsize,
+    seed: AtomicU64,
+}
+
+// The keys and values are shared between threads, and dropped by whichever
+// thread frees their node.
+#[allow(unsafe_code)]
+unsafe impl<K: Send + Sync, V: Send + Sync> Send for SkipListMap<K, V> {}
+#[allow(unsafe_code)]
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for SkipListMap<K, V> {}
+
+impl<K, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> fmt::Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkipListMap")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> SkipListMap<K, V> {
+    /// Creates an empty SkipListMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map: SkipListMap<u32, &str> = SkipListMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            head: Default::default(),
+            height: AtomicUsize::new(1),
+            len: AtomicIsize::new(0),
+            seed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// Other threads may insert or remove at the same time, so the answer
+    /// may be stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed).max(0) as usize
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// Other threads may insert or remove at the same time, so the answer
+    /// may be stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Draws the height of a new node, which is h with probability 2^-h.
+    fn random_height(&self) -> usize {
+        let mut z = self
+            .seed
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z.trailing_zeros() as usize + 1).min(MAX_HEIGHT)
+    }
+}
+
+#[allow(unsafe_code)]
+impl<K, V> SkipListMap<K, V>
+where
+    K: Ord + Send + 'static,
+    V: Send + 'static,
+{
+    /// Inserts a key-value pair into the map, and returns true, if the map
+    /// does not contain the key.
+    ///
+    /// If the map does contain the key, false is returned, and the map is
+    /// left unchanged, since other threads may be reading the current value.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// assert!(map.insert(37, "a"));
+    /// assert!(!map.insert(37, "b"));
+    /// assert_eq!(map.get(&37), Some("a"));
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let guard = epoch::pin();
+        let height = self.random_height();
+        self.height.fetch_max(height, Ordering::Relaxed);
+        let node = Node::new(key, value, height);
+        // SAFETY: The node stays alive until it is retired below.
+        let n = unsafe { &*node };
+
+        let mut pos = loop {
+            let (found, pos) = self.find(&n.key);
+            if found {
+                // SAFETY: The node was never shared.
+                drop(unsafe { Box::from_raw(node) });
+                return false;
+            }
+            for (level, next) in n.tower.iter().enumerate() {
+                next.store(pos.succs[level], Ordering::Relaxed);
+            }
+            if pos.preds[0][0]
+                .compare_exchange(
+                    pos.succs[0],
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                break pos;
+            }
+        };
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        'build: for level in 1..height {
+            loop {
+                let next = n.tower[level].load(Ordering::Acquire);
+                let succ = pos.succs[level];
+                // The node is being removed, so it is not linked any
+                // further.
+                if is_tagged(next) {
+                    break 'build;
+                }
+                if next != succ
+                    && n.tower[level]
+                        .compare_exchange(
+                            next,
+                            succ,
+                            Ordering::Release,
+                            Ordering::Relaxed,
+                        )
+                        .is_err()
+                {
+                    break 'build;
+                }
+                if pos.preds[level][level]
+                    .compare_exchange(
+                        succ,
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+                pos = self.find(&n.key).1;
+                if pos.succs[0] != node {
+                    break 'build;
+                }
+            }
+        }
+        self.release(node, &guard);
+        true
+    }
+
+    /// Returns a clone of the value corresponding to the key.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// map.insert(1, "a".to_string());
+    /// assert_eq!(map.get(&1), Some("a".to_string()));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        let _guard = epoch::pin();
+        let (found, pos) = self.find(key);
+        // SAFETY: The thread is pinned, so the node is not freed.
+        found.then(|| unsafe { (*pos.succs[0]).value.clone() })
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let _guard = epoch::pin();
+        self.find(key).0
+    }
+
+    /// Removes a key from the map, returning a clone of the value at the key
+    /// if the key was previously in the map.
+    ///
+    /// The value itself is dropped once no other thread can read it.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        let guard = epoch::pin();
+        let (found, pos) = self.find(key);
+        if !found {
+            return None;
+        }
+        let node = pos.succs[0];
+        // SAFETY: The thread is pinned, so the node is not freed.
+        let n = unsafe { &*node };
+        for next in n.tower[1..].iter().rev() {
+            let _ =
+                next.fetch_update(Ordering::AcqRel, Ordering::Acquire, |p| {
+                    (!is_tagged(p)).then(|| tag(p))
+                });
+        }
+        // Only one thread marks the bottom level, and removes the entry.
+        n.tower[0]
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |p| {
+                (!is_tagged(p)).then(|| tag(p))
+            })
+            .ok()?;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        let value = n.value.clone();
+        self.release(node, &guard);
+        Some(value)
+    }
+
+    /// Returns an iterator over the entries of the map, sorted by key, which
+    /// yields clones of the keys and values.
+    ///
+    /// The iterator keeps the thread pinned, and sees the entries that are
+    /// in the map when it reaches their position. An entry that is inserted
+    /// or removed by another thread during the iteration may or may not be
+    /// yielded. No memory is freed while the iterator is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SkipListMap;
+    ///
+    /// let map = SkipListMap::new();
+    /// map.insert(2, "b");
+    /// map.insert(1, "a");
+    /// map.insert(3, "c");
+    ///
+    /// let entries: Vec<_> = map.iter().collect();
+    /// assert_eq!(entries, vec![(1, "a"), (2, "b"), (3, "c")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            _guard: epoch::pin(),
+            tower: &self.head,
+        }
+    }
+
+    // Finds the position of the key at every level in use, and whether the
+    // key is in the map, in which case its node is the bottom successor. The
+    // marked nodes that are passed over are unlinked on the way. The thread
+    // must be pinned while the position is used.
+    fn find<Q>(&self, key: &Q) -> (bool, Position<'_, K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        'retry: loop {
+            let mut pos = Position {
+                preds: [&self.head[..]; MAX_HEIGHT],
+                succs: [ptr::null_mut(); MAX_HEIGHT],
+            };
+            let mut pred: &Tower<K, V> = &self.head;
+            for level in (0..self.height.load(Ordering::Relaxed)).rev() {
+                let mut curr = pred[level].load(Ordering::Acquire);
+                // The predecessor is being removed, and its successor at
+                // this level can no longer be changed.
+                if is_tagged(curr) {
+                    continue 'retry;
+                }
+                while !curr.is_null() {
+                    // SAFETY: The thread is pinned, so the node is not
+                    // freed.
+                    let c = unsafe { &*curr };
+                    let succ = c.tower[level].load(Ordering::Acquire);
+                    if is_tagged(succ) {
+                        let succ = untag(succ);
+                        if pred[level]
+                            .compare_exchange(
+                                curr,
+                                succ,
+                                Ordering::AcqRel,
+                                Ordering::Acquire,
+                            )
+                            .is_err()
+                        {
+                            continue 'retry;
+                        }
+                        curr = succ;
+                    } else if c.key.borrow() < key {
+                        pred = &c.tower;
+                        curr = succ;
+                    } else {
+                        break;
+                    }
+                }
+                pos.preds[level] = pred;
+                pos.succs[level] = curr;
+            }
+            let curr = pos.succs[0];
+            // SAFETY: The thread is pinned, so the node is not freed.
+            let found =
+                !curr.is_null() && unsafe { (*curr).key.borrow() } == key;
+            return (found, pos);
+        }
+    }
+
+    // Drops a reference to the node. The last one unlinks the node from
+    // every level, since its tower is neither built nor unlinked any
+    // further by the other, and retires it.
+    fn release(&self, node: *mut Node<K, V>, guard: &Guard) {
+        // SAFETY: The thread is pinned, so the node is not freed.
+        let n = unsafe { &*node };
+        if n.refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+            let _ = self.find(&n.key);
+            // SAFETY: The node is marked at every level it is linked into,
+            // so the search has unlinked it from all of them, and only the
+            // last reference retires it.
+            unsafe { guard.defer_destroy(node) };
+        }
+    }
+}
+
+impl<K, V> Drop for SkipListMap<K, V> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: No other thread can reach the nodes anymore, and the ones
+        // that were retired are no longer linked.
+        unsafe {
+            let mut node = *self.head[0].get_mut();
+            while !node.is_null() {
+                let mut boxed = Box::from_raw(node);
+                node = untag(*boxed.tower[0].get_mut());
+            }
+        }
+    }
+}
+
+/// An iterator over the entries of a `SkipListMap`, sorted by key.
+///
+/// This struct is created by [`SkipListMap::iter()`]. See its documentation
+/// for more.
+pub struct Iter<'a, K, V> {
+    _guard: Guard,
+    tower: &'a Tower<K, V>,
+}
+
+impl<K, V> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Iterator for Iter<'_, K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    type Item = (K, V);
+
+    #[allow(unsafe_code)]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = untag(self.tower[0].load(Ordering::Acquire));
+            if node.is_null() {
+                return None;
+            }
+            // SAFETY: The iterator keeps the thread pinned, so the node is
+            // not freed.
+            let n = unsafe { &*node };
+            self.tower = &n.tower;
+            if !is_tagged(n.tower[0].load(Ordering::Acquire)) {
+                return Some((n.key.clone(), n.value.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_inserts_and_removes() {
+        const THREADS: u64 = 4;
+        const KEYS: u64 = 1_000;
+
+        let map = Arc::new(SkipListMap::new());
+        let threads: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    // Every thread goes over all keys, so they race on each
+                    // of them.
+                    let mut inserted = 0;
+                    let mut removed = 0;
+                    for round in 0..4 {
+                        for i in 0..KEYS {
+                            let key = (i * 7 + t * 13 + round) % KEYS;
+                            if (key + round).is_multiple_of(2) {
+                                inserted += map.insert(key, key) as i64;
+                            } else if let Some(v) = map.remove(&key) {
+                                assert_eq!(v, key);
+                                removed += 1;
+                            }
+                        }
+                    }
+                    inserted - removed
+                })
+            })
+            .collect();
+        let net: i64 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        let entries: Vec<_> = map.iter().collect();
+        assert_eq!(entries.len() as i64, net);
+        assert_eq!(map.len() as i64, net);
+        assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+        for (k, v) in entries {
+            assert_eq!(k, v);
+            assert_eq!(map.get(&k), Some(v));
+        }
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let map = SkipListMap::new();
+        let mut expected = BTreeMap::new();
+        let mut x = 1u64;
+        for _ in 0..5_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 500;
+            if x >> 32 & 1 == 0 {
+                assert_eq!(map.insert(key, x), !expected.contains_key(&key),);
+                expected.entry(key).or_insert(x);
+            } else {
+                assert_eq!(map.remove(&key), expected.remove(&key));
+            }
+            assert_eq!(map.len(), expected.len());
+        }
+        let entries: Vec<_> = map.iter().collect();
+        let expected: Vec<_> = expected.into_iter().collect();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn drops_entries() {
+        let value = Arc::new(());
+        let map = SkipListMap::new();
+        for i in 0..10 {
+            map.insert(i, Arc::clone(&value));
+        }
+        drop(map.remove(&3));
+        drop(map);
+        // The removed entry is only dropped once the epoch has moved, which
+        // other tests may hold back for a while.
+        for _ in 0..10_000 {
+            if Arc::strong_count(&value) == 1 {
+                break;
+            }
+            epoch::pin().flush();
+            thread::yield_now();
+        }
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,490 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::cache_padded::CachePadded;
+
+/// A bounded wait-free queue for a single producer thread and a single
+/// consumer thread.
+///
+/// The elements are kept in a ring of slots, with a head index that only
+/// the consumer moves, and a tail index that only the producer moves. The
+/// indices count every push and pop, and wrap around the ring when they are
+/// used, so the queue is full when they are a capacity apart. The producer
+/// writes a slot before it publishes the new tail, and the consumer reads a
+/// slot before it publishes the new head, so neither ever touches a slot
+/// that the other one is using.
+///
+/// The two indices sit on separate cache lines, so the producer and the
+/// consumer do not invalidate each other's line on every operation. Each
+/// side also remembers the last index it read from the other side, and only
+/// reads it again once the remembered one says the queue is full or empty.
+/// The batched operations move many elements while publishing the index
+/// once.
+///
+/// The queue is used through the [`Producer`] and the [`Consumer`] returned
+/// by [`SpscRing::split()`], which borrows the queue mutably, so there is
+/// only ever one of each.
+///
+/// [`Producer`]: crate::concurrent::Producer
+/// [`Consumer`]: crate::concurrent::Consumer
+/// [`SpscRing::split()`]: SpscRing::split
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use dt::concurrent::SpscRing;
+///
+/// let mut ring = SpscRing::new(16);
+/// let (mut producer, mut consumer) = ring.split();
+/// thread::scope(|s| {
+///     s.spawn(move || {
+///         for i in 0..1000 {
+///             while producer.try_push(i).is_err() {
+///                 thread::yield_now();
+///             }
+///         }
+///     });
+///
+///     let mut expected = 0;
+///     while expected < 1000 {
+///         if let Some(i) = consumer.try_pop() {
+///             assert_eq!(i, expected);
+///             expected += 1;
+///         }
+///     }
+/// });
+/// ```
+pub struct SpscRing<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // The number of elements popped.
+    head: CachePadded<AtomicUsize>,
+    // The number of elements pushed.
+    tail: CachePadded<AtomicUsize>,
+}
+
+// The producer and the consumer each use the ring from their own thread,
+// and a slot is only ever used by one of them at a time.
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Send for SpscRing<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Sync for SpscRing<T> {}
+
+impl<T> fmt::Debug for SpscRing<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpscRing")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> SpscRing<T> {
+    /// Creates an empty SpscRing that holds at most `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let ring: SpscRing<u32> = SpscRing::new(8);
+    /// assert_eq!(ring.capacity(), 8);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the number of elements the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let ring: SpscRing<u32> = SpscRing::new(8);
+    /// assert_eq!(ring.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(8);
+    /// let (mut producer, _) = ring.split();
+    /// producer.try_push(1).unwrap();
+    /// assert_eq!(ring.len(), 1);
+    /// ```
+    pub fn len(&mut self) -> usize {
+        self.tail.get_mut().wrapping_sub(*self.head.get_mut())
+    }
+
+    /// Returns true if the queue has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring: SpscRing<u32> = SpscRing::new(8);
+    /// assert!(ring.is_empty());
+    /// ```
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits the queue into its producer and its consumer, which can be
+    /// sent to different threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(8);
+    /// let (mut producer, mut consumer) = ring.split();
+    /// producer.try_push('a').unwrap();
+    /// assert_eq!(consumer.try_pop(), Some('a'));
+    /// ```
+    pub fn split(&mut self) -> (Producer<'_, T>, Consumer<'_, T>) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let ring = &*self;
+        (Producer { ring, tail, head }, Consumer { ring, head, tail })
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index % self.slots.len()].get()
+    }
+}
+
+impl<T> Drop for SpscRing<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut index = head;
+        while index != tail {
+            // SAFETY: The slots between the head and the tail hold values.
+            unsafe { (*self.slot(index)).as_mut_ptr().drop_in_place() };
+            index = index.wrapping_add(1);
+        }
+    }
+}
+
+/// The pushing half of a [`SpscRing`].
+///
+/// This struct is created by [`SpscRing::split()`]. See its documentation
+/// for more.
+///
+/// [`SpscRing`]: crate::concurrent::SpscRing
+/// [`SpscRing::split()`]: crate::concurrent::SpscRing::split
+pub struct Producer<'a, T> {
+    ring: &'a SpscRing<T>,
+    tail: usize,
+    // The last head read from the consumer.
+    head: usize,
+}
+
+impl<'a, T> fmt::Debug for Producer<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> Producer<'a, T> {
+    /// Returns the number of elements the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring: SpscRing<u32> = SpscRing::new(8);
+    /// let (producer, _) = ring.split();
+    /// assert_eq!(producer.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Appends the element to the back of the queue, or returns it back if
+    /// the queue is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns the element if the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(1);
+    /// let (mut producer, _) = ring.split();
+    /// assert_eq!(producer.try_push(1), Ok(()));
+    /// assert_eq!(producer.try_push(2), Err(2));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.free() == 0 {
+            return Err(value);
+        }
+        // SAFETY: The slot is free, and the consumer does not read it before
+        // the new tail is published.
+        unsafe { (*self.ring.slot(self.tail)).write(value) };
+        self.tail = self.tail.wrapping_add(1);
+        self.ring.tail.store(self.tail, Ordering::Release);
+        Ok(())
+    }
+
+    /// Appends elements taken from the iterator to the back of the queue
+    /// until it is full or the iterator is exhausted, and returns the number
+    /// of elements appended. The elements are only made visible to the
+    /// consumer once all of them are written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(4);
+    /// let (mut producer, mut consumer) = ring.split();
+    /// let mut values = 0..6;
+    /// assert_eq!(producer.try_push_batch(&mut values), 4);
+    /// assert_eq!(values.next(), Some(4));
+    /// assert_eq!(consumer.try_pop(), Some(0));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_push_batch<I>(&mut self, values: &mut I) -> usize
+    where
+        I: Iterator<Item = T>,
+    {
+        let free = self.free();
+        let mut pushed = 0;
+        while pushed < free {
+            let value = match values.next() {
+                Some(value) => value,
+                None => break,
+            };
+            let index = self.tail.wrapping_add(pushed);
+            // SAFETY: The slot is free, and the consumer does not read it
+            // before the new tail is published.
+            unsafe { (*self.ring.slot(index)).write(value) };
+            pushed += 1;
+        }
+        if pushed > 0 {
+            self.tail = self.tail.wrapping_add(pushed);
+            self.ring.tail.store(self.tail, Ordering::Release);
+        }
+        pushed
+    }
+
+    /// Returns the number of free slots, only reading the head of the
+    /// consumer again when the last one read leaves no free slot.
+    fn free(&mut self) -> usize {
+        let capacity = self.ring.capacity();
+        if self.tail.wrapping_sub(self.head) == capacity {
+            self.head = self.ring.head.load(Ordering::Acquire);
+        }
+        capacity - self.tail.wrapping_sub(self.head)
+    }
+}
+
+/// The popping half of a [`SpscRing`].
+///
+/// This struct is created by [`SpscRing::split()`]. See its documentation
+/// for more.
+///
+/// [`SpscRing`]: crate::concurrent::SpscRing
+/// [`SpscRing::split()`]: crate::concurrent::SpscRing::split
+pub struct Consumer<'a, T> {
+    ring: &'a SpscRing<T>,
+    head: usize,
+    // The last tail read from the producer.
+    tail: usize,
+}
+
+impl<'a, T> fmt::Debug for Consumer<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> Consumer<'a, T> {
+    /// Returns the number of elements the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring: SpscRing<u32> = SpscRing::new(8);
+    /// let (_, consumer) = ring.split();
+    /// assert_eq!(consumer.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Removes the front element and returns it, or None if the queue is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(4);
+    /// let (mut producer, mut consumer) = ring.split();
+    /// producer.try_push(1).unwrap();
+    /// assert_eq!(consumer.try_pop(), Some(1));
+    /// assert_eq!(consumer.try_pop(), None);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_pop(&mut self) -> Option<T> {
+        if self.available() == 0 {
+            return None;
+        }
+        // SAFETY: The slot holds a value, and the producer does not write it
+        // before the new head is published.
+        let value = unsafe { (*self.ring.slot(self.head)).as_ptr().read() };
+        self.head = self.head.wrapping_add(1);
+        self.ring.head.store(self.head, Ordering::Release);
+        Some(value)
+    }
+
+    /// Removes up to `max` elements from the front of the queue, appends
+    /// them to the vector, and returns their number. The slots are only
+    /// handed back to the producer once all of them are read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::SpscRing;
+    ///
+    /// let mut ring = SpscRing::new(8);
+    /// let (mut producer, mut consumer) = ring.split();
+    /// producer.try_push_batch(&mut (0..5));
+    ///
+    /// let mut values = Vec::new();
+    /// assert_eq!(consumer.try_pop_batch(&mut values, 3), 3);
+    /// assert_eq!(consumer.try_pop_batch(&mut values, 3), 2);
+    /// assert_eq!(values, [0, 1, 2, 3, 4]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn try_pop_batch(&mut self, out: &mut Vec<T>, max: usize) -> usize {
+        let popped = self.available().min(max);
+        out.reserve(popped);
+        for i in 0..popped {
+            let index = self.head.wrapping_add(i);
+            // SAFETY: The slot holds a value, and the producer does not
+            // write it before the new head is published.
+            out.push(unsafe { (*self.ring.slot(index)).as_ptr().read() });
+        }
+        if popped > 0 {
+            self.head = self.head.wrapping_add(popped);
+            self.ring.head.store(self.head, Ordering::Release);
+        }
+        popped
+    }
+
+    /// Returns the number of elements ready to be popped, only reading the
+    /// tail of the producer again when the last one read leaves none.
+    fn available(&mut self) -> usize {
+        if self.tail == self.head {
+            self.tail = self.ring.tail.load(Ordering::Acquire);
+        }
+        self.tail.wrapping_sub(self.head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn transfers_in_order_across_threads() {
+        const COUNT: usize = 100_000;
+
+        let mut ring = SpscRing::new(64);
+        let (mut producer, mut consumer) = ring.split();
+        thread::scope(|s| {
+            s.spawn(move || {
+                let mut next = 0;
+                while next < COUNT {
+                    // Alternate between single and batched pushes.
+                    let pushed = if next.is_multiple_of(2) {
+                        producer.try_push(next).map_or(0, |()| 1)
+                    } else {
+                        let end = COUNT.min(next + 7);
+                        producer.try_push_batch(&mut (next..end))
+                    };
+                    if pushed == 0 {
+                        thread::yield_now();
+                    }
+                    next += pushed;
+                }
+            });
+
+            let mut popped = Vec::new();
+            while popped.len() < COUNT {
+                let count = if popped.len().is_multiple_of(2) {
+                    match consumer.try_pop() {
+                        Some(value) => {
+                            popped.push(value);
+                            1
+                        }
+                        None => 0,
+                    }
+                } else {
+                    consumer.try_pop_batch(&mut popped, 5)
+                };
+                if count == 0 {
+                    thread::yield_now();
+                }
+            }
+            assert!(popped.into_iter().eq(0..COUNT));
+            assert_eq!(consumer.try_pop(), None);
+        });
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let value = Arc::new(());
+        let mut ring = SpscRing::new(4);
+        {
+            let (mut producer, mut consumer) = ring.split();
+            for _ in 0..4 {
+                producer.try_push(Arc::clone(&value)).unwrap();
+            }
+            drop(consumer.try_pop());
+            producer.try_push(Arc::clone(&value)).unwrap();
+        }
+        assert_eq!(ring.len(), 4);
+        assert_eq!(Arc::strong_count(&value), 5);
+        drop(ring);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,544 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{fence, AtomicIsize, AtomicPtr, Ordering};
+use std::sync::Arc;
+
+use super::cache_padded::CachePadded;
+use super::epoch;
+
+/// The number of slots of the first buffer.
+const MIN_CAPACITY: usize = 16;
+
+/// A circular array of slots, indexed by the positions of the deque modulo
+/// its capacity.
+struct Buffer<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+#[allow(unsafe_code)]
+impl<T> Buffer<T> {
+    fn alloc(capacity: usize) -> *mut Self {
+        let slots = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        Box::into_raw(Box::new(Self { slots }))
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, index: isize) -> *mut MaybeUninit<T> {
+        self.slots[index as usize & (self.capacity() - 1)].get()
+    }
+
+    /// Writes the value at the index.
+    ///
+    /// # Safety
+    ///
+    /// Only the owner writes, and the slot must not hold a value that may
+    /// still be taken.
+    unsafe fn write(&self, index: isize, value: T) {
+        (*self.slot(index)).write(value);
+    }
+
+    /// Reads a copy of the value at the index, which is only initialized
+    /// once the caller has claimed it.
+    ///
+    /// # Safety
+    ///
+    /// The slot must have been written.
+    unsafe fn read(&self, index: isize) -> MaybeUninit<T> {
+        // A thief may read a slot that it then fails to claim, and the read
+        // is volatile so that it is never assumed to be the only one.
+        self.slot(index).read_volatile()
+    }
+}
+
+struct Inner<T> {
+    // The position of the next element to steal.
+    top: CachePadded<AtomicIsize>,
+    // The position after the last element pushed.
+    bottom: CachePadded<AtomicIsize>,
+    buffer: CachePadded<AtomicPtr<Buffer<T>>>,
+}
+
+impl<T> Drop for Inner<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        let top = *self.top.get_mut();
+        let bottom = *self.bottom.get_mut();
+        let buffer = *self.buffer.get_mut();
+        // SAFETY: No handle is left, so the elements between the top and the
+        // bottom are owned by the deque, and the buffer is not shared.
+        unsafe {
+            for index in top..bottom {
+                (*(*buffer).slot(index)).as_mut_ptr().drop_in_place();
+            }
+            drop(Box::from_raw(buffer));
+        }
+    }
+}
+
+/// A lock-free work-stealing deque, by Chase and Lev, whose owner pushes and
+/// pops elements at the bottom, while other threads steal them from the top.
+///
+/// The elements sit in a circular array between a top and a bottom
+/// position. The owner pushes and pops at the bottom without contention, so
+/// it runs its tasks in last-in first-out order, which keeps their data hot
+/// in its cache, and only has to race the thieves for the last element. A
+/// thief claims the top element with a compare-and-swap on the top, so the
+/// oldest tasks, which tend to be the largest, are the ones stolen.
+///
+/// When the array is full, the owner copies the elements into an array
+/// twice as large, and publishes it. Thieves may still be reading the old
+/// array, which holds the same elements at the same positions, so it is
+/// retired with [epoch-based reclamation] instead of being freed at once.
+///
+/// The orderings follow Lê, Pop, Cohen and Zappa Nardelli's version of the
+/// algorithm for weak memory models.
+///
+/// [epoch-based reclamation]: crate::concurrent::epoch
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+///
+/// use dt::concurrent::WorkStealingDeque;
+///
+/// let worker = WorkStealingDeque::new();
+/// for task in 1..=1000 {
+///     worker.push(task);
+/// }
+///
+/// let total = AtomicUsize::new(0);
+/// thread::scope(|s| {
+///     for _ in 0..3 {
+///         let stealer = worker.stealer();
+///         let total = &total;
+///         s.spawn(move || {
+///             while let Some(task) = stealer.steal() {
+///                 total.fetch_add(task, Ordering::Relaxed);
+///             }
+///         });
+///     }
+///     while let Some(task) = worker.pop() {
+///         total.fetch_add(task, Ordering::Relaxed);
+///     }
+/// });
+/// assert_eq!(total.into_inner(), 500500);
+/// ```
+pub struct WorkStealingDeque<T> {
+    inner: Arc<Inner<T>>,
+    // Only the owner pushes and pops, so it cannot be shared.
+    _marker: PhantomData<*mut ()>,
+}
+
+// The owner may be moved to another thread along with its elements.
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Send for WorkStealingDeque<T> {}
+
+impl<T> Default for WorkStealingDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for WorkStealingDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkStealingDeque")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T> WorkStealingDeque<T> {
+    /// Creates an empty WorkStealingDeque, owned by the current thread until
+    /// it is sent to another one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker: WorkStealingDeque<u32> = WorkStealingDeque::new();
+    /// assert!(worker.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                top: CachePadded::new(AtomicIsize::new(0)),
+                bottom: CachePadded::new(AtomicIsize::new(0)),
+                buffer: CachePadded::new(AtomicPtr::new(Buffer::alloc(
+                    MIN_CAPACITY,
+                ))),
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the deque.
+    ///
+    /// Thieves may steal at the same time, so the answer may be stale by the
+    /// time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// worker.push(1);
+    /// worker.push(2);
+    /// assert_eq!(worker.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        len(&self.inner)
+    }
+
+    /// Returns true if the deque has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// assert!(worker.is_empty());
+    /// worker.push(1);
+    /// assert!(!worker.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a handle through which other threads steal the elements of
+    /// the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// let stealer = worker.stealer();
+    /// worker.push(1);
+    /// assert_eq!(stealer.steal(), Some(1));
+    /// ```
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Pushes an element at the bottom of the deque, moving the elements
+    /// into an array twice as large if it is full.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// worker.push(1);
+    /// worker.push(2);
+    /// assert_eq!(worker.pop(), Some(2));
+    /// ```
+    pub fn push(&self, value: T) {
+        let inner = &*self.inner;
+        let bottom = inner.bottom.load(Ordering::Relaxed);
+        let top = inner.top.load(Ordering::Acquire);
+        let mut buffer = inner.buffer.load(Ordering::Relaxed);
+        // SAFETY: Only the owner replaces the buffer, so it is not freed.
+        if (bottom - top) as usize >= unsafe { (*buffer).capacity() } {
+            buffer = self.grow(top, bottom);
+        }
+        // SAFETY: The slot is outside of the elements, so no thief takes it.
+        unsafe { (*buffer).write(bottom, value) };
+        fence(Ordering::Release);
+        inner.bottom.store(bottom + 1, Ordering::Relaxed);
+    }
+
+    /// Removes the element at the bottom of the deque, which is the last one
+    /// pushed, and returns it, or None if the deque is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// worker.push(1);
+    /// worker.push(2);
+    /// assert_eq!(worker.pop(), Some(2));
+    /// assert_eq!(worker.pop(), Some(1));
+    /// assert_eq!(worker.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let inner = &*self.inner;
+        let bottom = inner.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = inner.buffer.load(Ordering::Relaxed);
+        // The bottom is lowered first, so that thieves stop short of the
+        // element before the top is read.
+        inner.bottom.store(bottom, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let top = inner.top.load(Ordering::Relaxed);
+        if top > bottom {
+            inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+        // SAFETY: The slot holds an element between the top and the bottom.
+        let value = unsafe { (*buffer).read(bottom) };
+        if top == bottom {
+            // The last element may be claimed by a thief as well.
+            let won = inner
+                .top
+                .compare_exchange(
+                    top,
+                    top + 1,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                )
+                .is_ok();
+            inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            if !won {
+                return None;
+            }
+        }
+        // SAFETY: The element was claimed by the owner.
+        Some(unsafe { value.assume_init() })
+    }
+
+    /// Copies the elements into a buffer twice as large, publishes it, and
+    /// retires the old one.
+    fn grow(&self, top: isize, bottom: isize) -> *mut Buffer<T> {
+        let inner = &*self.inner;
+        let old = inner.buffer.load(Ordering::Relaxed);
+        // SAFETY: Only the owner replaces the buffer, and the elements are
+        // copied bitwise, so each is still owned by whoever claims it.
+        unsafe {
+            let new = Buffer::alloc((*old).capacity() * 2);
+            for index in top..bottom {
+                (*new).slot(index).write((*old).read(index));
+            }
+            inner.buffer.store(new, Ordering::Release);
+            epoch::pin().defer_destroy(old);
+            new
+        }
+    }
+}
+
+fn len<T>(inner: &Inner<T>) -> usize {
+    let bottom = inner.bottom.load(Ordering::Relaxed);
+    let top = inner.top.load(Ordering::Relaxed);
+    bottom.saturating_sub(top).max(0) as usize
+}
+
+/// A handle through which threads steal elements from the top of a
+/// [`WorkStealingDeque`].
+///
+/// This struct is created by [`WorkStealingDeque::stealer()`]. See its
+/// documentation for more.
+///
+/// [`WorkStealingDeque`]: crate::concurrent::WorkStealingDeque
+/// [`WorkStealingDeque::stealer()`]: WorkStealingDeque::stealer
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+// Thieves only take elements with a compare-and-swap on the top.
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Send for Stealer<T> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Send> Sync for Stealer<T> {}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stealer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stealer")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[allow(unsafe_code)]
+impl<T> Stealer<T> {
+    /// Returns the number of elements in the deque.
+    ///
+    /// The owner and other thieves may change the deque at the same time, so
+    /// the answer may be stale by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// worker.push(1);
+    /// assert_eq!(worker.stealer().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        len(&self.inner)
+    }
+
+    /// Returns true if the deque has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker: WorkStealingDeque<u32> = WorkStealingDeque::new();
+    /// assert!(worker.stealer().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the element at the top of the deque, which is the first one
+    /// pushed, and returns it, or None if the deque is empty. A thief that
+    /// loses the race for the top element to another one tries again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::concurrent::WorkStealingDeque;
+    ///
+    /// let worker = WorkStealingDeque::new();
+    /// worker.push(1);
+    /// worker.push(2);
+    /// let stealer = worker.stealer();
+    /// assert_eq!(stealer.steal(), Some(1));
+    /// assert_eq!(stealer.steal(), Some(2));
+    /// assert_eq!(stealer.steal(), None);
+    /// ```
+    pub fn steal(&self) -> Option<T> {
+        let inner = &*self.inner;
+        let _guard = epoch::pin();
+        loop {
+            let top = inner.top.load(Ordering::Acquire);
+            fence(Ordering::SeqCst);
+            let bottom = inner.bottom.load(Ordering::Acquire);
+            if top >= bottom {
+                return None;
+            }
+            let buffer = inner.buffer.load(Ordering::Acquire);
+            // SAFETY: The thread is pinned, so the buffer is not freed, and
+            // the copy is only used once the element is claimed.
+            let value = unsafe { (*buffer).read(top) };
+            if inner
+                .top
+                .compare_exchange(
+                    top,
+                    top + 1,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                // SAFETY: The element was claimed by this thief.
+                return Some(unsafe { value.assume_init() });
+            }
+            // The copy is discarded without being dropped, since the element
+            // belongs to whoever claimed it.
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    #[test]
+    fn owner_is_lifo_and_thieves_are_fifo() {
+        let worker = WorkStealingDeque::new();
+        let stealer = worker.stealer();
+        for i in 0..1000 {
+            worker.push(i);
+        }
+        assert_eq!(worker.len(), 1000);
+        assert_eq!(stealer.steal(), Some(0));
+        assert_eq!(worker.pop(), Some(999));
+        assert_eq!(stealer.steal(), Some(1));
+        assert_eq!(worker.len(), 997);
+    }
+
+    #[test]
+    fn every_element_is_taken_once() {
+        const THIEVES: usize = 4;
+        const COUNT: usize = 50_000;
+
+        let worker = WorkStealingDeque::new();
+        let done = AtomicBool::new(false);
+        let mut taken = thread::scope(|s| {
+            let thieves: Vec<_> = (0..THIEVES)
+                .map(|_| {
+                    let stealer = worker.stealer();
+                    let done = &done;
+                    s.spawn(move || {
+                        let mut stolen = Vec::new();
+                        loop {
+                            let finished = done.load(Ordering::SeqCst);
+                            match stealer.steal() {
+                                Some(value) => stolen.push(value),
+                                None if finished => return stolen,
+                                None => thread::yield_now(),
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            // The owner pushes in bursts, so the array grows while thieves
+            // steal, and pops some of the elements itself.
+            let mut popped = Vec::new();
+            for i in 0..COUNT {
+                worker.push(Box::new(i));
+                if i.is_multiple_of(3) {
+                    popped.extend(worker.pop());
+                }
+            }
+            while let Some(value) = worker.pop() {
+                popped.push(value);
+            }
+            done.store(true, Ordering::SeqCst);
+            for thief in thieves {
+                popped.extend(thief.join().unwrap());
+            }
+            popped
+        });
+        taken.sort();
+        assert!(taken.into_iter().map(|b| *b).eq(0..COUNT));
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let value = Arc::new(());
+        let worker = WorkStealingDeque::new();
+        for _ in 0..100 {
+            worker.push(Arc::clone(&value));
+        }
+        let stealer = worker.stealer();
+        drop(stealer.steal());
+        drop(worker);
+        assert_eq!(Arc::strong_count(&value), 100);
+        drop(stealer);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
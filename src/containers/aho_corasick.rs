@@ -0,0 +1,335 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+const ROOT: usize = 0;
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct State {
+    // The transitions of the trie, sorted by their bytes.
+    next: Vec<(u8, usize)>,
+    // The state of the longest proper suffix of this state's string that is
+    // also a prefix of a pattern.
+    fail: usize,
+    // The nearest state along the fail links that ends a pattern, or NIL.
+    dict: usize,
+    // The patterns that are exactly this state's string.
+    outputs: Vec<usize>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            next: Vec::new(),
+            fail: ROOT,
+            dict: NIL,
+            outputs: Vec::new(),
+        }
+    }
+
+    fn search(&self, b: u8) -> Result<usize, usize> {
+        self.next.binary_search_by_key(&b, |&(b, _)| b)
+    }
+
+    fn goto(&self, b: u8) -> Option<usize> {
+        self.search(b).ok().map(|i| self.next[i].1)
+    }
+}
+
+/// An automaton that finds all the occurrences of a set of patterns in a
+/// text, in a single pass over the text.
+///
+/// The patterns are put in a trie, whose nodes are the states of the
+/// automaton. Every state also has a fail link to the state of the longest
+/// proper suffix of its string that is in the trie, so when the next byte of
+/// the text has no transition, the automaton falls back along the fail links
+/// instead of starting over. A second link to the nearest state along the fail
+/// links that ends a pattern lists the patterns that end at each position.
+///
+/// Building the automaton takes O(m) time, where m is the total length of
+/// the patterns. Searching a text of length n takes O(n + k) time, where k is
+/// the number of matches.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::AhoCorasick;
+///
+/// let ac = AhoCorasick::new(&["he", "she", "his", "hers"]);
+/// let found: Vec<_> = ac
+///     .find_iter("ushers")
+///     .map(|m| (m.pattern, m.start, m.end))
+///     .collect();
+/// assert_eq!(found, [(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+/// ```
+#[derive(Debug)]
+pub struct AhoCorasick {
+    states: Vec<State>,
+    // The lengths of the patterns, by their indices.
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Creates an AhoCorasick automaton that finds the patterns. The patterns
+    /// are identified in the matches by their positions in the iterator.
+    ///
+    /// This operation should compute in O(m) time, where m is the total
+    /// length of the patterns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pattern is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::AhoCorasick;
+    ///
+    /// let ac = AhoCorasick::new(vec![b"ab".to_vec(), b"bc".to_vec()]);
+    /// assert_eq!(ac.len(), 2);
+    /// ```
+    pub fn new<I>(patterns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut states = alloc::vec![State::new()];
+        let mut lens = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "Patterns must not be empty");
+            let mut s = ROOT;
+            for &b in pattern {
+                s = match states[s].search(b) {
+                    Ok(i) => states[s].next[i].1,
+                    Err(i) => {
+                        let new = states.len();
+                        states.push(State::new());
+                        states[s].next.insert(i, (b, new));
+                        new
+                    }
+                };
+            }
+            states[s].outputs.push(lens.len());
+            lens.push(pattern.len());
+        }
+
+        // The fail link of a state only depends on shallower states, so they
+        // are computed in breadth-first order. The states at depth 1 keep
+        // their fail links to the root.
+        let mut queue: VecDeque<usize> =
+            states[ROOT].next.iter().map(|&(_, s)| s).collect();
+        while let Some(s) = queue.pop_front() {
+            for i in 0..states[s].next.len() {
+                let (b, child) = states[s].next[i];
+                let mut f = states[s].fail;
+                let fail = loop {
+                    if let Some(t) = states[f].goto(b) {
+                        break t;
+                    }
+                    if f == ROOT {
+                        break ROOT;
+                    }
+                    f = states[f].fail;
+                };
+                states[child].fail = fail;
+                states[child].dict = if states[fail].outputs.is_empty() {
+                    states[fail].dict
+                } else {
+                    fail
+                };
+                queue.push_back(child);
+            }
+        }
+        Self { states, lens }
+    }
+
+    /// Returns the number of patterns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::AhoCorasick;
+    ///
+    /// let ac = AhoCorasick::new(&["a", "b", "a"]);
+    /// assert_eq!(ac.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// Returns true if there are no patterns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::AhoCorasick;
+    ///
+    /// let ac = AhoCorasick::new(Vec::<String>::new());
+    /// assert!(ac.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lens.is_empty()
+    }
+
+    /// Returns an iterator visiting all the occurrences of the patterns in the
+    /// haystack, including the overlapping ones. The matches are ordered by
+    /// their ends, and the matches with the same end from the longest to the
+    /// shortest. A pattern given more than once matches once for every time
+    /// it was given.
+    ///
+    /// Visiting the k matches should compute in O(n + k) time, where n is the
+    /// length of the haystack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::AhoCorasick;
+    ///
+    /// let ac = AhoCorasick::new(&["aa", "a"]);
+    /// let found: Vec<_> =
+    ///     ac.find_iter("aaa").map(|m| m.start..m.end).collect();
+    /// assert_eq!(found, [0..1, 0..2, 1..2, 1..3, 2..3]);
+    /// ```
+    pub fn find_iter<'a, 'h, H>(&'a self, haystack: &'h H) -> FindIter<'a, 'h>
+    where
+        H: AsRef<[u8]> + ?Sized,
+    {
+        FindIter {
+            ac: self,
+            haystack: haystack.as_ref(),
+            pos: 0,
+            state: ROOT,
+            output_state: NIL,
+            output: 0,
+        }
+    }
+
+    fn step(&self, mut s: usize, b: u8) -> usize {
+        loop {
+            if let Some(next) = self.states[s].goto(b) {
+                return next;
+            }
+            if s == ROOT {
+                return ROOT;
+            }
+            s = self.states[s].fail;
+        }
+    }
+}
+
+/// An occurrence of a pattern of an [`AhoCorasick`] automaton in a haystack.
+///
+/// [`AhoCorasick`]: crate::containers::AhoCorasick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AhoCorasickMatch {
+    /// Index of the pattern, in the order the patterns were given.
+    pub pattern: usize,
+    /// Index of the first byte of the occurrence in the haystack.
+    pub start: usize,
+    /// Index after the last byte of the occurrence in the haystack.
+    pub end: usize,
+}
+
+/// An iterator over the occurrences of the patterns of an AhoCorasick
+/// automaton in a haystack.
+///
+/// This struct is created by [`AhoCorasick::find_iter()`]. See its
+/// documentation for more.
+///
+/// [`AhoCorasick::find_iter()`]: crate::containers::AhoCorasick::find_iter
+#[derive(Debug)]
+pub struct FindIter<'a, 'h> {
+    ac: &'a AhoCorasick,
+    haystack: &'h [u8],
+    pos: usize,
+    state: usize,
+    // The state whose patterns are being reported, along the dictionary
+    // links, with the position of the next pattern to report.
+    output_state: usize,
+    output: usize,
+}
+
+impl<'a, 'h> Iterator for FindIter<'a, 'h> {
+    type Item = AhoCorasickMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.output_state != NIL {
+                let state = &self.ac.states[self.output_state];
+                if let Some(&pattern) = state.outputs.get(self.output) {
+                    self.output += 1;
+                    return Some(AhoCorasickMatch {
+                        pattern,
+                        start: self.pos - self.ac.lens[pattern],
+                        end: self.pos,
+                    });
+                }
+                self.output_state = state.dict;
+                self.output = 0;
+            }
+            let &b = self.haystack.get(self.pos)?;
+            self.pos += 1;
+            self.state = self.ac.step(self.state, b);
+            self.output_state = self.state;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn naive(patterns: &[Vec<u8>], haystack: &[u8]) -> Vec<AhoCorasickMatch> {
+        let mut found = Vec::new();
+        for end in 1..=haystack.len() {
+            let mut here: Vec<_> = patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| haystack[..end].ends_with(p))
+                .map(|(pattern, p)| AhoCorasickMatch {
+                    pattern,
+                    start: end - p.len(),
+                    end,
+                })
+                .collect();
+            here.sort_by_key(|m| (m.start, m.pattern));
+            found.extend(here);
+        }
+        found
+    }
+
+    #[test]
+    fn matches_naive_search() {
+        let mut rng = SplitMix64::new(31);
+        let mut word = |max_len: u64| -> Vec<u8> {
+            let len = 1 + rng.next_u64() % max_len;
+            (0..len)
+                .map(|_| b"ab"[(rng.next_u64() % 2) as usize])
+                .collect()
+        };
+        for _ in 0..50 {
+            let patterns: Vec<_> = (0..8).map(|_| word(5)).collect();
+            let haystack = word(100);
+            let ac = AhoCorasick::new(&patterns);
+            let mut found: Vec<_> = ac.find_iter(&haystack).collect();
+            // Patterns given twice are reported from the same state, in the
+            // order they were given.
+            found.sort_by_key(|m| (m.end, m.start, m.pattern));
+            assert_eq!(found, naive(&patterns, &haystack));
+        }
+    }
+
+    #[test]
+    fn no_patterns() {
+        let ac = AhoCorasick::new(Vec::<Vec<u8>>::new());
+        assert_eq!(ac.find_iter("abc").count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Patterns must not be empty")]
+    fn empty_pattern() {
+        AhoCorasick::new(&["a", ""]);
+    }
+}
@@ -0,0 +1,339 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::{cmp, mem, slice};
+
+/// The number of bytes of the first chunk of [`Arena`].
+///
+/// [`Arena`]: crate::containers::Arena
+const FIRST_CHUNK_BYTES: usize = 1024;
+
+/// The chunks of [`Arena`]. Only the current chunk is pushed into, and it is
+/// never pushed into beyond its capacity, so its values never move.
+///
+/// [`Arena`]: crate::containers::Arena
+#[derive(Debug)]
+struct Chunks<T> {
+    current: Vec<T>,
+    rest: Vec<Vec<T>>,
+}
+
+impl<T> Chunks<T> {
+    /// Makes sure the current chunk has room for `additional` values, by
+    /// starting a new chunk if it does not.
+    fn reserve(&mut self, additional: usize) {
+        if self.current.capacity() - self.current.len() >= additional {
+            return;
+        }
+        let capacity = cmp::max(self.current.capacity() * 2, additional);
+        let full =
+            mem::replace(&mut self.current, Vec::with_capacity(capacity));
+        self.rest.push(full);
+    }
+}
+
+/// An allocator of values of a single type, which are all dropped together
+/// with the arena.
+///
+/// The values are pushed into chunks, and a chunk is never grown, so a value
+/// stays at the same address until the arena is dropped, and allocating
+/// returns a mutable reference that lives as long as the arena. Once a chunk
+/// is full, a new chunk twice as large is started. Allocating only takes a
+/// shared reference to the arena, so nodes allocated from it can point to
+/// one another.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+/// use dt::containers::Arena;
+///
+/// struct Node<'a> {
+///     value: i32,
+///     next: Cell<Option<&'a Node<'a>>>,
+/// }
+///
+/// let arena = Arena::new();
+/// let a = arena.alloc(Node { value: 1, next: Cell::new(None) });
+/// let b = arena.alloc(Node { value: 2, next: Cell::new(None) });
+/// // The nodes form a cycle.
+/// a.next.set(Some(b));
+/// b.next.set(Some(a));
+/// assert_eq!(a.next.get().unwrap().next.get().unwrap().value, 1);
+/// ```
+pub struct Arena<T> {
+    chunks: RefCell<Chunks<T>>,
+}
+
+impl<T> core::fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Arena")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Arena<T> {
+    /// Creates an empty Arena, whose first chunk holds about a kilobyte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena: Arena<u64> = Arena::new();
+    /// assert!(arena.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        let size = cmp::max(mem::size_of::<T>(), 1);
+        Self::with_capacity(cmp::max(FIRST_CHUNK_BYTES / size, 1))
+    }
+
+    /// Creates an empty Arena whose first chunk holds `capacity` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena: Arena<u64> = Arena::with_capacity(100);
+    /// assert!(arena.stats().bytes_reserved >= 800);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            chunks: RefCell::new(Chunks {
+                current: Vec::with_capacity(capacity),
+                rest: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns the number of values allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::new();
+    /// arena.alloc(1);
+    /// arena.alloc_iter(0..3);
+    /// assert_eq!(arena.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.rest.iter().map(Vec::len).sum::<usize>()
+    }
+
+    /// Returns true if no values have been allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::new();
+    /// assert!(arena.is_empty());
+    /// arena.alloc(1);
+    /// assert!(!arena.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the value into the arena, and returns a mutable reference to it.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::new();
+    /// let x = arena.alloc(41);
+    /// *x += 1;
+    /// assert_eq!(*x, 42);
+    /// ```
+    #[allow(unsafe_code, clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.reserve(1);
+        let index = chunks.current.len();
+        chunks.current.push(value);
+        // SAFETY: See `alloc_vec`.
+        unsafe { &mut *chunks.current.as_mut_ptr().add(index) }
+    }
+
+    /// Moves all the values of the iterator into the arena, next to each
+    /// other, and returns a mutable reference to them.
+    ///
+    /// The iterator is run to completion before any value is moved, so it may
+    /// allocate from the arena itself.
+    ///
+    /// This operation should compute in amortized O(n) time, where n is the
+    /// number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::new();
+    /// let squares = arena.alloc_iter((1..=4).map(|x| x * x));
+    /// assert_eq!(squares, [1, 4, 9, 16]);
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_iter<I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.alloc_vec(iter.into_iter().collect())
+    }
+
+    /// Moves the values into the current chunk, starting a new one if they
+    /// do not fit.
+    #[allow(unsafe_code, clippy::mut_from_ref)]
+    fn alloc_vec(&self, values: Vec<T>) -> &mut [T] {
+        let mut chunks = self.chunks.borrow_mut();
+        let n = values.len();
+        chunks.reserve(n);
+        let start = chunks.current.len();
+        chunks.current.extend(values);
+        // SAFETY: The chunk had room for the values, so it was not
+        // reallocated and the values before them did not move. The values
+        // are never moved nor dropped until the arena is dropped, and no
+        // other reference to them is handed out, so the slice can live as
+        // long as the arena.
+        unsafe {
+            let ptr = chunks.current.as_mut_ptr().add(start);
+            slice::from_raw_parts_mut(ptr, n)
+        }
+    }
+
+    /// Returns a snapshot of the memory used by the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::with_capacity(2);
+    /// arena.alloc_iter(0..3u32);
+    /// let stats = arena.stats();
+    /// assert_eq!(stats.chunks, 2);
+    /// assert_eq!(stats.bytes_used, 12);
+    /// ```
+    pub fn stats(&self) -> ArenaStats {
+        let chunks = self.chunks.borrow();
+        let all = || chunks.rest.iter().chain(Some(&chunks.current));
+        let size = mem::size_of::<T>();
+        ArenaStats {
+            chunks: all().filter(|c| c.capacity() > 0).count(),
+            bytes_used: all().map(|c| c.len() * size).sum(),
+            bytes_reserved: all().map(|c| c.capacity() * size).sum(),
+        }
+    }
+
+    /// Returns the values allocated, in the order of their allocation,
+    /// consuming the arena.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Arena;
+    ///
+    /// let arena = Arena::with_capacity(1);
+    /// arena.alloc(1);
+    /// arena.alloc_iter(2..5);
+    /// assert_eq!(arena.into_vec(), [1, 2, 3, 4]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        let chunks = self.chunks.into_inner();
+        let mut values = Vec::new();
+        for chunk in chunks.rest.into_iter().chain(Some(chunks.current)) {
+            values.extend(chunk);
+        }
+        values
+    }
+}
+
+/// A snapshot of the memory used by an [`Arena`].
+///
+/// This struct is created by [`Arena::stats()`]. See its documentation for
+/// more.
+///
+/// [`Arena`]: crate::containers::Arena
+/// [`Arena::stats()`]: crate::containers::Arena::stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// Number of chunks allocated.
+    pub chunks: usize,
+    /// Number of bytes taken by the values allocated.
+    pub bytes_used: usize,
+    /// Number of bytes of all the chunks, used or not.
+    pub bytes_reserved: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+
+    #[test]
+    fn values_stay_in_place() {
+        let arena = Arena::with_capacity(1);
+        let mut refs = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..1000 {
+            if i % 7 == 0 {
+                refs.extend(arena.alloc_iter(i..i + 3).iter());
+                expected.extend(i..i + 3);
+            } else {
+                refs.push(&*arena.alloc(i));
+                expected.push(i);
+            }
+        }
+        assert!(refs.iter().map(|&&x| x).eq(expected.iter().copied()));
+        assert_eq!(arena.len(), expected.len());
+        // Chunks double in size, so there are logarithmically many.
+        assert!(arena.stats().chunks <= 12);
+        assert_eq!(arena.into_vec(), expected);
+    }
+
+    #[test]
+    fn drops_all_values() {
+        struct Counted(Rc<Cell<usize>>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let arena = Arena::with_capacity(3);
+        for _ in 0..10 {
+            arena.alloc(Counted(drops.clone()));
+        }
+        arena.alloc_iter((0..5).map(|_| Counted(drops.clone())));
+        assert_eq!(drops.get(), 0);
+        drop(arena);
+        assert_eq!(drops.get(), 15);
+    }
+
+    #[test]
+    fn zero_sized_values() {
+        let arena = Arena::new();
+        for _ in 0..100 {
+            arena.alloc(());
+        }
+        assert_eq!(arena.len(), 100);
+        assert_eq!(arena.stats().bytes_used, 0);
+    }
+}
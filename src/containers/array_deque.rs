@@ -0,0 +1,820 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// A double-ended queue implemented with a growable ring buffer.
+///
+/// The elements are stored in a single array whose start can be anywhere in
+/// the array. Pushing to the back writes past the last element and pushing to
+/// the front writes before the first one, wrapping around the ends of the
+/// array when needed. Once the array is full, its content is moved into an
+/// array that is twice as large.
+///
+/// Compared to [`DoublyLinkedList`], this allows pushing and popping at either
+/// end in amortized constant time without allocating for every element, and
+/// allows indexing in constant time.
+///
+/// [`DoublyLinkedList`]: crate::containers::DoublyLinkedList
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::ArrayDeque;
+///
+/// let mut deque = ArrayDeque::new();
+/// deque.push_back(2);
+/// deque.push_back(3);
+/// deque.push_front(1);
+///
+/// assert_eq!(deque[0], 1);
+/// assert_eq!(deque.pop_back(), Some(3));
+/// assert_eq!(deque.pop_front(), Some(1));
+/// assert_eq!(deque.len(), 1);
+/// ```
+pub struct ArrayDeque<T> {
+    // The slots in `buf[head..]` followed by the slots in `buf[..head]` hold
+    // the elements of the deque in order, only the first `len` of them are
+    // initialized.
+    buf: Vec<MaybeUninit<T>>,
+    head: usize,
+    len: usize,
+}
+
+/// The capacity of the first array allocated by an empty [`ArrayDeque`].
+///
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+const MIN_CAPACITY: usize = 4;
+
+/// Reinterprets a slice of initialized slots as a slice of their values.
+///
+/// # Safety
+///
+/// Every slot in `slots` must be initialized.
+#[allow(unsafe_code)]
+unsafe fn assume_init_slice<T>(slots: &[MaybeUninit<T>]) -> &[T] {
+    // SAFETY: `MaybeUninit<T>` has the same layout as `T`, and the caller
+    // guarantees that the values are initialized.
+    &*(slots as *const [MaybeUninit<T>] as *const [T])
+}
+
+/// Reinterprets a slice of initialized slots as a slice of their values.
+///
+/// # Safety
+///
+/// Every slot in `slots` must be initialized.
+#[allow(unsafe_code)]
+unsafe fn assume_init_slice_mut<T>(slots: &mut [MaybeUninit<T>]) -> &mut [T] {
+    // SAFETY: `MaybeUninit<T>` has the same layout as `T`, and the caller
+    // guarantees that the values are initialized.
+    &mut *(slots as *mut [MaybeUninit<T>] as *mut [T])
+}
+
+impl<T> Default for ArrayDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for ArrayDeque<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArrayDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone> Clone for ArrayDeque<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> ArrayDeque<T> {
+    /// Creates an empty ArrayDeque.
+    ///
+    /// The deque will not allocate until elements are pushed onto it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let deque: ArrayDeque<u32> = ArrayDeque::new();
+    /// assert_eq!(deque.capacity(), 0);
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Creates an empty ArrayDeque with space for at least `capacity`
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let deque: ArrayDeque<u32> = ArrayDeque::with_capacity(10);
+    /// assert!(deque.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(capacity);
+        buf.resize_with(capacity, MaybeUninit::uninit);
+        Self {
+            buf,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements the deque can hold without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::with_capacity(2);
+    /// deque.push_back(1);
+    /// deque.push_back(2);
+    /// assert_eq!(deque.capacity(), 2);
+    ///
+    /// deque.push_back(3);
+    /// assert_eq!(deque.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the number of elements in the deque.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert_eq!(deque.len(), 0);
+    /// deque.push_back(1);
+    /// assert_eq!(deque.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the deque is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert!(deque.is_empty());
+    /// deque.push_front(1);
+    /// assert!(!deque.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all elements from the deque, keeping its capacity.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(1);
+    /// deque.clear();
+    /// assert!(deque.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+        self.head = 0;
+    }
+
+    /// Provides a reference to the element at the given index, or None if the
+    /// index is out of bounds. The element at index 0 is the front of the
+    /// deque.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(3);
+    /// deque.push_back(4);
+    /// deque.push_front(2);
+    /// assert_eq!(deque.get(0), Some(&2));
+    /// assert_eq!(deque.get(2), Some(&4));
+    /// assert_eq!(deque.get(3), None);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = &self.buf[self.physical(index)];
+        // SAFETY: The index is in bounds, so the slot holds an element.
+        Some(unsafe { slot.assume_init_ref() })
+    }
+
+    /// Provides a mutable reference to the element at the given index, or
+    /// None if the index is out of bounds.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(3);
+    /// if let Some(x) = deque.get_mut(0) {
+    ///     *x = 7;
+    /// }
+    /// assert_eq!(deque[0], 7);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let idx = self.physical(index);
+        // SAFETY: The index is in bounds, so the slot holds an element.
+        Some(unsafe { self.buf[idx].assume_init_mut() })
+    }
+
+    /// Provides a reference to the front element, or None if the deque is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert_eq!(deque.front(), None);
+    /// deque.push_back(1);
+    /// deque.push_back(2);
+    /// assert_eq!(deque.front(), Some(&1));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Provides a reference to the back element, or None if the deque is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert_eq!(deque.back(), None);
+    /// deque.push_back(1);
+    /// deque.push_back(2);
+    /// assert_eq!(deque.back(), Some(&2));
+    /// ```
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    /// Appends an element to the back of the deque.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(1);
+    /// deque.push_back(3);
+    /// assert_eq!(deque.back(), Some(&3));
+    /// ```
+    pub fn push_back(&mut self, value: T) {
+        self.grow_if_full();
+        let idx = self.physical(self.len);
+        self.buf[idx] = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    /// Prepends an element to the front of the deque.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_front(1);
+    /// deque.push_front(3);
+    /// assert_eq!(deque.front(), Some(&3));
+    /// ```
+    pub fn push_front(&mut self, value: T) {
+        self.grow_if_full();
+        self.head = self.physical(self.capacity() - 1);
+        self.buf[self.head] = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    /// Removes the last element and returns it, or None if the deque is
+    /// empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert_eq!(deque.pop_back(), None);
+    /// deque.push_back(1);
+    /// deque.push_back(3);
+    /// assert_eq!(deque.pop_back(), Some(3));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let idx = self.physical(self.len);
+        let slot = mem::replace(&mut self.buf[idx], MaybeUninit::uninit());
+        // SAFETY: The slot was the last one holding an element, and it is no
+        // longer counted in `len` so it won't be read again.
+        Some(unsafe { slot.assume_init() })
+    }
+
+    /// Removes the first element and returns it, or None if the deque is
+    /// empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// assert_eq!(deque.pop_front(), None);
+    /// deque.push_back(1);
+    /// deque.push_back(3);
+    /// assert_eq!(deque.pop_front(), Some(1));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let slot =
+            mem::replace(&mut self.buf[self.head], MaybeUninit::uninit());
+        self.head = self.physical(1);
+        self.len -= 1;
+        // SAFETY: The slot was the first one holding an element, and the head
+        // has been moved past it so it won't be read again.
+        Some(unsafe { slot.assume_init() })
+    }
+
+    /// Returns a pair of slices which contain, in order, the elements of the
+    /// deque. The second slice is only non-empty when the elements wrap
+    /// around the end of the underlying array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::with_capacity(4);
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    /// assert_eq!(deque.as_slices(), (&[1][..], &[2, 3][..]));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.ranges();
+        // SAFETY: The ranges only cover slots that hold elements.
+        unsafe {
+            (
+                assume_init_slice(&self.buf[front]),
+                assume_init_slice(&self.buf[back]),
+            )
+        }
+    }
+
+    /// Returns a pair of mutable slices which contain, in order, the elements
+    /// of the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::with_capacity(4);
+    /// deque.push_back(2);
+    /// deque.push_front(1);
+    /// let (front, back) = deque.as_mut_slices();
+    /// front[0] = 10;
+    /// back[0] = 20;
+    /// assert_eq!(deque.as_slices(), (&[10][..], &[20][..]));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.ranges();
+        // The front range always starts at the head and the back range always
+        // starts at 0, so splitting at the head separates them.
+        let (low, high) = self.buf.split_at_mut(front.start);
+        // SAFETY: The ranges only cover slots that hold elements.
+        unsafe {
+            (
+                assume_init_slice_mut(&mut high[..front.len()]),
+                assume_init_slice_mut(&mut low[back]),
+            )
+        }
+    }
+
+    /// Rearranges the underlying array so that the elements of the deque are
+    /// stored contiguously, and returns them as a single mutable slice.
+    ///
+    /// This operation should compute in O(n) time, where n is the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::with_capacity(4);
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    ///
+    /// deque.make_contiguous().sort_by(|a, b| b.cmp(a));
+    /// assert_eq!(deque.as_slices(), (&[3, 2, 1][..], &[][..]));
+    /// ```
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.capacity() {
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    /// Provides a front-to-back iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    ///
+    /// let mut iter = deque.iter();
+    /// assert_eq!(iter.next(), Some(&1));
+    /// assert_eq!(iter.next_back(), Some(&3));
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    /// Provides a front-to-back iterator with mutable references.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ArrayDeque;
+    ///
+    /// let mut deque = ArrayDeque::new();
+    /// deque.push_back(2);
+    /// deque.push_front(1);
+    /// for x in deque.iter_mut() {
+    ///     *x *= 10;
+    /// }
+    /// assert_eq!(deque[0], 10);
+    /// assert_eq!(deque[1], 20);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+
+    /// Returns the position in the underlying array of the element at the
+    /// given index in the deque.
+    fn physical(&self, index: usize) -> usize {
+        let idx = self.head + index;
+        if idx >= self.capacity() {
+            idx - self.capacity()
+        } else {
+            idx
+        }
+    }
+
+    /// Returns the ranges of the underlying array that hold the elements,
+    /// from the front of the deque to its back.
+    fn ranges(&self) -> (core::ops::Range<usize>, core::ops::Range<usize>) {
+        let end = self.head + self.len;
+        if end > self.capacity() {
+            (self.head..self.capacity(), 0..end - self.capacity())
+        } else {
+            (self.head..end, 0..0)
+        }
+    }
+
+    /// Moves the elements into an array that is twice as large if there is no
+    /// vacant slot left. The elements start at the beginning of the new array.
+    fn grow_if_full(&mut self) {
+        if self.len < self.capacity() {
+            return;
+        }
+        let capacity = (self.capacity() * 2).max(MIN_CAPACITY);
+        let mut buf = Vec::with_capacity(capacity);
+        buf.resize_with(capacity, MaybeUninit::uninit);
+        for (i, slot) in buf.iter_mut().enumerate().take(self.len) {
+            let idx = self.physical(i);
+            *slot = mem::replace(&mut self.buf[idx], MaybeUninit::uninit());
+        }
+        self.buf = buf;
+        self.head = 0;
+    }
+}
+
+impl<T> Index<usize> for ArrayDeque<T> {
+    type Output = T;
+
+    /// Returns a reference to the element at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> IndexMut<usize> for ArrayDeque<T> {
+    /// Returns a mutable reference to the element at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> FromIterator<T> for ArrayDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T> Extend<T> for ArrayDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+/// An iterator over the elements of an [`ArrayDeque`].
+///
+/// This struct is created by [`ArrayDeque::iter()`]. See its documentation
+/// for more.
+///
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+/// [`ArrayDeque::iter()`]: crate::containers::ArrayDeque::iter
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    /// The elements that wrapped around the end of the array are only
+    /// visited once the ones before the end have all been visited.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a ArrayDeque<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A mutable iterator over the elements of an [`ArrayDeque`].
+///
+/// This struct is created by [`ArrayDeque::iter_mut()`]. See its
+/// documentation for more.
+///
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+/// [`ArrayDeque::iter_mut()`]: crate::containers::ArrayDeque::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a mut ArrayDeque<T> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An owning iterator over the elements of an [`ArrayDeque`].
+///
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    deque: ArrayDeque<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len(), Some(self.deque.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for ArrayDeque<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { deque: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut deque = ArrayDeque::new();
+        for i in 0..10 {
+            deque.push_back(i);
+            deque.push_front(-i - 1);
+        }
+        assert_eq!(deque.len(), 20);
+        assert!(deque.iter().copied().eq(-10..10));
+        assert!(deque.iter().rev().copied().eq((-10..10).rev()));
+
+        for i in 0..10 {
+            assert_eq!(deque.pop_front(), Some(-10 + i));
+            assert_eq!(deque.pop_back(), Some(9 - i));
+        }
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut deque = ArrayDeque::with_capacity(4);
+        // Move the head to the middle of the array so that the elements wrap
+        // around its end.
+        deque.push_back(0);
+        deque.push_back(0);
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend(1..=4);
+        assert_eq!(deque.capacity(), 4);
+        assert_eq!(deque.as_slices(), (&[1, 2][..], &[3, 4][..]));
+        assert!(deque.iter().copied().eq(1..=4));
+        assert_eq!((deque[0], deque[3]), (1, 4));
+
+        for x in deque.iter_mut() {
+            *x *= 2;
+        }
+        assert_eq!(deque.make_contiguous(), &[2, 4, 6, 8]);
+        assert_eq!(deque.as_slices(), (&[2, 4, 6, 8][..], &[][..]));
+
+        // Growing keeps the elements in order.
+        deque.push_front(0);
+        assert_eq!(deque.capacity(), 8);
+        assert!(deque.into_iter().eq(vec![0, 2, 4, 6, 8]));
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        let mut deque = ArrayDeque::new();
+        for _ in 0..5 {
+            deque.push_front(Rc::clone(&rc));
+        }
+        deque.pop_back();
+        assert_eq!(Rc::strong_count(&rc), 5);
+
+        let mut iter = deque.clone().into_iter();
+        iter.next();
+        assert_eq!(Rc::strong_count(&rc), 8);
+        drop(iter);
+        drop(deque);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
@@ -0,0 +1,921 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+
+/// The index of a missing leaf.
+const NIL: usize = usize::MAX;
+
+/// The number of children of an internal node of a BPlusTreeMap created with
+/// [`BPlusTreeMap::new()`].
+///
+/// [`BPlusTreeMap::new()`]: crate::containers::BPlusTreeMap::new
+const DEFAULT_ORDER: usize = 32;
+
+#[derive(Debug)]
+struct Leaf<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    prev: usize,
+    next: usize,
+}
+
+#[derive(Debug)]
+struct Internal<K> {
+    // The key at index `i` separates the child at index `i`, whose keys are
+    // all less than it, from the child at index `i + 1`, whose keys are all
+    // greater than or equal to it.
+    keys: Vec<K>,
+    children: Vec<usize>,
+}
+
+/// Returns mutable references to the two elements at `a` and `b`, which must
+/// be different.
+fn two_mut<T>(slice: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    if a < b {
+        let (head, tail) = slice.split_at_mut(b);
+        (&mut head[a], &mut tail[0])
+    } else {
+        let (head, tail) = slice.split_at_mut(a);
+        (&mut tail[0], &mut head[b])
+    }
+}
+
+/// An ordered map implemented with a B+ tree.
+///
+/// A B+ tree is a search tree whose nodes have many children, and whose
+/// leaves are all at the same depth. The entries are only stored in the
+/// leaves, and the internal nodes only hold copies of some keys that guide the
+/// search down to the right leaf. The leaves are linked to their neighbors
+/// like a doubly-linked list, so that iterating over a range of keys only
+/// takes one search down the tree, followed by a walk along the leaves.
+///
+/// An internal node has at most `order` children and, apart from the root, at
+/// least half as many. A leaf holds at most `order - 1` entries and, apart
+/// from the root, at least half as many. Full nodes are split on insertion,
+/// and nodes that are less than half full after a removal borrow entries from
+/// a sibling or are merged with it.
+///
+/// The nodes are kept in vectors and link to each other by index, with the
+/// slots of merged nodes reused for new ones.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::BPlusTreeMap;
+///
+/// let mut map = BPlusTreeMap::new();
+/// for k in 0..1000 {
+///     map.insert(k, k * k);
+/// }
+///
+/// assert_eq!(map.get(&12), Some(&144));
+/// let squares: Vec<_> = map.range(10..13).map(|(_, v)| *v).collect();
+/// assert_eq!(squares, [100, 121, 144]);
+/// ```
+pub struct BPlusTreeMap<K, V> {
+    leaves: Vec<Leaf<K, V>>,
+    internals: Vec<Internal<K>>,
+    free_leaves: Vec<usize>,
+    free_internals: Vec<usize>,
+    // The root is a leaf if the height is 0, and an internal node otherwise.
+    root: usize,
+    height: usize,
+    order: usize,
+    len: usize,
+}
+
+impl<K: Ord, V> Default for BPlusTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for BPlusTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> BPlusTreeMap<K, V> {
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of children of an internal node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let map: BPlusTreeMap<i32, ()> = BPlusTreeMap::with_order(8);
+    /// assert_eq!(map.order(), 8);
+    /// ```
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let map: BPlusTreeMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// let keys: Vec<_> = map.iter().rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [3, 2, 1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let first = self.edge_leaf(0);
+        let last = self.edge_leaf(usize::MAX);
+        Iter {
+            range: Range {
+                leaves: &self.leaves,
+                front: (first, 0),
+                back: (last, self.leaves[last].keys.len()),
+            },
+            len: self.len,
+        }
+    }
+
+    /// Returns the leftmost leaf if `side` is 0, and the rightmost leaf
+    /// otherwise.
+    fn edge_leaf(&self, side: usize) -> usize {
+        let mut i = self.root;
+        for _ in 0..self.height {
+            let children = &self.internals[i].children;
+            i = children[side.min(children.len() - 1)];
+        }
+        i
+    }
+
+    fn min_leaf_len(&self) -> usize {
+        (self.order - 1) / 2
+    }
+
+    fn min_children(&self) -> usize {
+        self.order.div_ceil(2)
+    }
+
+    fn alloc_leaf(&mut self, leaf: Leaf<K, V>) -> usize {
+        match self.free_leaves.pop() {
+            Some(i) => {
+                self.leaves[i] = leaf;
+                i
+            }
+            None => {
+                self.leaves.push(leaf);
+                self.leaves.len() - 1
+            }
+        }
+    }
+
+    fn alloc_internal(&mut self, internal: Internal<K>) -> usize {
+        match self.free_internals.pop() {
+            Some(i) => {
+                self.internals[i] = internal;
+                i
+            }
+            None => {
+                self.internals.push(internal);
+                self.internals.len() - 1
+            }
+        }
+    }
+
+    /// Moves the second half of the entries of the leaf `i` into a new leaf
+    /// that follows it, and returns the new leaf.
+    fn split_leaf(&mut self, i: usize) -> usize {
+        let mid = self.leaves[i].keys.len() / 2;
+        let keys = self.leaves[i].keys.split_off(mid);
+        let values = self.leaves[i].values.split_off(mid);
+        let next = self.leaves[i].next;
+        let new = self.alloc_leaf(Leaf {
+            keys,
+            values,
+            prev: i,
+            next,
+        });
+        if next != NIL {
+            self.leaves[next].prev = new;
+        }
+        self.leaves[i].next = new;
+        new
+    }
+
+    /// Moves the second half of the keys and children of the internal node
+    /// `i` into a new node, and returns the key that separates them along
+    /// with the new node.
+    fn split_internal(&mut self, i: usize) -> (K, usize) {
+        let node = &mut self.internals[i];
+        let mid = node.keys.len() / 2;
+        let keys = node.keys.split_off(mid + 1);
+        let children = node.children.split_off(mid + 1);
+        let separator = node.keys.pop().expect("node has a middle key");
+        let new = self.alloc_internal(Internal { keys, children });
+        (separator, new)
+    }
+
+    /// Restores the minimum size of the leaf at index `i` among the children
+    /// of the internal node `parent`, by borrowing from or merging with a
+    /// sibling.
+    fn fix_leaf(&mut self, parent: usize, i: usize)
+    where
+        K: Clone,
+    {
+        let min = self.min_leaf_len();
+        let children = &self.internals[parent].children;
+        let node = children[i];
+        let left = if i > 0 { children[i - 1] } else { NIL };
+        let right = children.get(i + 1).copied().unwrap_or(NIL);
+
+        if left != NIL && self.leaves[left].keys.len() > min {
+            let (left, node) = two_mut(&mut self.leaves, left, node);
+            let key = left.keys.pop().expect("sibling is not empty");
+            let value = left.values.pop().expect("sibling is not empty");
+            node.keys.insert(0, key);
+            node.values.insert(0, value);
+            self.internals[parent].keys[i - 1] = node.keys[0].clone();
+        } else if right != NIL && self.leaves[right].keys.len() > min {
+            let (node, right) = two_mut(&mut self.leaves, node, right);
+            node.keys.push(right.keys.remove(0));
+            node.values.push(right.values.remove(0));
+            self.internals[parent].keys[i] = right.keys[0].clone();
+        } else {
+            // Merge the pair of siblings into the first one.
+            let (first, second, k) = if left != NIL {
+                (left, node, i - 1)
+            } else {
+                (node, right, i)
+            };
+            let (first_leaf, second_leaf) =
+                two_mut(&mut self.leaves, first, second);
+            first_leaf.keys.append(&mut second_leaf.keys);
+            first_leaf.values.append(&mut second_leaf.values);
+            let next = second_leaf.next;
+            first_leaf.next = next;
+            if next != NIL {
+                self.leaves[next].prev = first;
+            }
+            self.internals[parent].keys.remove(k);
+            self.internals[parent].children.remove(k + 1);
+            self.free_leaves.push(second);
+        }
+    }
+
+    /// Restores the minimum size of the internal node at index `i` among the
+    /// children of the internal node `parent`, by borrowing from or merging
+    /// with a sibling. Keys move down from and up into the parent.
+    fn fix_internal(&mut self, parent: usize, i: usize) {
+        let min = self.min_children();
+        let children = &self.internals[parent].children;
+        let node = children[i];
+        let left = if i > 0 { children[i - 1] } else { NIL };
+        let right = children.get(i + 1).copied().unwrap_or(NIL);
+
+        if left != NIL && self.internals[left].children.len() > min {
+            let key =
+                self.internals[left].keys.pop().expect("sibling has keys");
+            let child = self.internals[left].children.pop().expect("sibling");
+            let separator =
+                mem::replace(&mut self.internals[parent].keys[i - 1], key);
+            self.internals[node].keys.insert(0, separator);
+            self.internals[node].children.insert(0, child);
+        } else if right != NIL && self.internals[right].children.len() > min {
+            let key = self.internals[right].keys.remove(0);
+            let child = self.internals[right].children.remove(0);
+            let separator =
+                mem::replace(&mut self.internals[parent].keys[i], key);
+            self.internals[node].keys.push(separator);
+            self.internals[node].children.push(child);
+        } else {
+            let (first, second, k) = if left != NIL {
+                (left, node, i - 1)
+            } else {
+                (node, right, i)
+            };
+            let separator = self.internals[parent].keys.remove(k);
+            self.internals[parent].children.remove(k + 1);
+            let (first_node, second_node) =
+                two_mut(&mut self.internals, first, second);
+            first_node.keys.push(separator);
+            first_node.keys.append(&mut second_node.keys);
+            first_node.children.append(&mut second_node.children);
+            self.free_internals.push(second);
+        }
+    }
+}
+
+impl<K: Ord, V> BPlusTreeMap<K, V> {
+    /// Creates an empty BPlusTreeMap, whose internal nodes have at most 32
+    /// children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self::with_order(DEFAULT_ORDER)
+    }
+
+    /// Creates an empty BPlusTreeMap, whose internal nodes have at most
+    /// `order` children and whose leaves have at most `order - 1` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the order is less than 3.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::with_order(4);
+    /// map.insert(1, "a");
+    /// ```
+    pub fn with_order(order: usize) -> Self {
+        assert!(order >= 3, "Order must be at least 3");
+        Self {
+            leaves: vec![Leaf {
+                keys: Vec::new(),
+                values: Vec::new(),
+                prev: NIL,
+                next: NIL,
+            }],
+            internals: Vec::new(),
+            free_leaves: Vec::new(),
+            free_internals: Vec::new(),
+            root: 0,
+            height: 0,
+            order,
+            len: 0,
+        }
+    }
+
+    /// Inserts a key-value pair into the map and returns the old value of the
+    /// key, or None if the key was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        K: Clone,
+    {
+        let (path, leaf) = self.descend(&key);
+        let pos = match self.leaves[leaf].keys.binary_search(&key) {
+            Ok(pos) => {
+                let old = &mut self.leaves[leaf].values[pos];
+                return Some(mem::replace(old, value));
+            }
+            Err(pos) => pos,
+        };
+        self.leaves[leaf].keys.insert(pos, key);
+        self.leaves[leaf].values.insert(pos, value);
+        self.len += 1;
+        if self.leaves[leaf].keys.len() < self.order {
+            return None;
+        }
+
+        // Split the full nodes on the way back up to the root.
+        let new = self.split_leaf(leaf);
+        let mut carry = (self.leaves[new].keys[0].clone(), new);
+        for &(parent, i) in path.iter().rev() {
+            let (separator, child) = carry;
+            self.internals[parent].keys.insert(i, separator);
+            self.internals[parent].children.insert(i + 1, child);
+            if self.internals[parent].children.len() <= self.order {
+                return None;
+            }
+            carry = self.split_internal(parent);
+        }
+        let (separator, child) = carry;
+        self.root = self.alloc_internal(Internal {
+            keys: vec![separator],
+            children: vec![self.root, child],
+        });
+        self.height += 1;
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (leaf, pos) = self.find(key)?;
+        Some(&self.leaves[leaf].values[pos])
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, 10);
+    /// *map.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (leaf, pos) = self.find(key)?;
+        Some(&mut self.leaves[leaf].values[pos])
+    }
+
+    /// Returns true if the key is in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes the key from the map and returns its value, or None if the key
+    /// was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let mut map = BPlusTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Clone,
+        Q: Ord + ?Sized,
+    {
+        let (path, leaf) = self.descend(key);
+        let pos = self.leaves[leaf]
+            .keys
+            .binary_search_by(|k| k.borrow().cmp(key))
+            .ok()?;
+        self.leaves[leaf].keys.remove(pos);
+        let value = self.leaves[leaf].values.remove(pos);
+        self.len -= 1;
+
+        // Fix the nodes that are less than half full on the way back up to
+        // the root.
+        let mut node_len = self.leaves[leaf].keys.len();
+        let mut min = self.min_leaf_len();
+        for (depth, &(parent, i)) in path.iter().enumerate().rev() {
+            if node_len >= min {
+                break;
+            }
+            if depth + 1 == path.len() {
+                self.fix_leaf(parent, i);
+            } else {
+                self.fix_internal(parent, i);
+            }
+            node_len = self.internals[parent].children.len();
+            min = self.min_children();
+        }
+        if self.height > 0 && self.internals[self.root].children.len() == 1 {
+            self.free_internals.push(self.root);
+            self.root = self.internals[self.root].children.pop().unwrap();
+            self.height -= 1;
+        }
+        Some(value)
+    }
+
+    /// Returns an iterator visiting the entries of the map whose keys are in
+    /// the range, in ascending order of their keys. Only the first leaf of
+    /// the range is searched for, and the iterator then walks along the
+    /// linked leaves.
+    ///
+    /// This operation should compute in O(log(n)) time, and each step of the
+    /// iterator in O(1) amortized time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BPlusTreeMap;
+    ///
+    /// let map: BPlusTreeMap<_, _> = (0..100).map(|k| (k, ())).collect();
+    /// assert!(map.range(20..30).map(|(k, _)| *k).eq(20..30));
+    /// assert!(map.range(..=3).rev().map(|(k, _)| *k).eq((0..4).rev()));
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        match (start, end) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+                panic!("Range start and end are equal and excluded")
+            }
+            (
+                Bound::Included(s) | Bound::Excluded(s),
+                Bound::Included(e) | Bound::Excluded(e),
+            ) if s > e => panic!("Range start is greater than range end"),
+            _ => {}
+        }
+        let front = match start {
+            Bound::Included(key) => self.gap(key, false),
+            Bound::Excluded(key) => self.gap(key, true),
+            Bound::Unbounded => (self.edge_leaf(0), 0),
+        };
+        let back = match end {
+            Bound::Included(key) => self.gap(key, true),
+            Bound::Excluded(key) => self.gap(key, false),
+            Bound::Unbounded => {
+                let last = self.edge_leaf(usize::MAX);
+                (last, self.leaves[last].keys.len())
+            }
+        };
+        Range {
+            leaves: &self.leaves,
+            front,
+            back,
+        }
+    }
+
+    /// Returns the path of internal nodes and child indices from the root down
+    /// to the leaf that holds the key or would hold it, and the leaf.
+    fn descend<Q>(&self, key: &Q) -> (Vec<(usize, usize)>, usize)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut path = Vec::with_capacity(self.height);
+        let mut i = self.root;
+        for _ in 0..self.height {
+            let node = &self.internals[i];
+            let child = node.keys.partition_point(|k| k.borrow() <= key);
+            path.push((i, child));
+            i = node.children[child];
+        }
+        (path, i)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (_, leaf) = self.descend(key);
+        let keys = &self.leaves[leaf].keys;
+        let pos = keys.binary_search_by(|k| k.borrow().cmp(key)).ok()?;
+        Some((leaf, pos))
+    }
+
+    /// Returns the position in the leaves just before the key, or just after
+    /// it if `after` is true.
+    fn gap<Q>(&self, key: &Q, after: bool) -> (usize, usize)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (_, leaf) = self.descend(key);
+        let keys = &self.leaves[leaf].keys;
+        let pos = if after {
+            keys.partition_point(|k| k.borrow() <= key)
+        } else {
+            keys.partition_point(|k| k.borrow() < key)
+        };
+        (leaf, pos)
+    }
+}
+
+impl<K: Ord + Clone, V> FromIterator<(K, V)> for BPlusTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord + Clone, V> Extend<(K, V)> for BPlusTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BPlusTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over a range of the entries of a BPlusTreeMap, in ascending
+/// order of their keys.
+///
+/// This struct is created by [`BPlusTreeMap::range()`]. See its documentation
+/// for more.
+///
+/// [`BPlusTreeMap::range()`]: crate::containers::BPlusTreeMap::range
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    leaves: &'a [Leaf<K, V>],
+    // The positions between entries at which the range starts and ends,
+    // given as a leaf and an index into it. The end of a leaf and the start
+    // of the next one are the same position.
+    front: (usize, usize),
+    back: (usize, usize),
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.front == self.back {
+                return None;
+            }
+            let (i, pos) = self.front;
+            let leaf = &self.leaves[i];
+            if pos < leaf.keys.len() {
+                self.front.1 += 1;
+                return Some((&leaf.keys[pos], &leaf.values[pos]));
+            }
+            self.front = (leaf.next, 0);
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.front == self.back {
+                return None;
+            }
+            let (i, pos) = self.back;
+            let leaf = &self.leaves[i];
+            if pos > 0 {
+                self.back.1 -= 1;
+                return Some((&leaf.keys[pos - 1], &leaf.values[pos - 1]));
+            }
+            self.back = (leaf.prev, self.leaves[leaf.prev].keys.len());
+        }
+    }
+}
+
+/// An iterator over the entries of a BPlusTreeMap, in ascending order of
+/// their keys.
+///
+/// This struct is created by [`BPlusTreeMap::iter()`]. See its documentation
+/// for more.
+///
+/// [`BPlusTreeMap::iter()`]: crate::containers::BPlusTreeMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next_back()?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the sizes of the nodes, the separators, the depth of the leaves
+    /// and the links between them, and returns the number of entries.
+    fn check<K: Ord + Clone, V>(map: &BPlusTreeMap<K, V>) -> usize {
+        let mut leaves = Vec::new();
+        let mut stack = vec![(map.root, 0, None, None)];
+        while let Some((i, depth, lo, hi)) = stack.pop() {
+            let in_bounds = |k: &K| {
+                lo.as_ref().is_none_or(|lo| lo <= k)
+                    && hi.as_ref().is_none_or(|hi| k < hi)
+            };
+            let is_root = i == map.root && depth == 0;
+            if depth == map.height {
+                let leaf = &map.leaves[i];
+                assert!(leaf.keys.len() < map.order);
+                assert!(is_root || leaf.keys.len() >= map.min_leaf_len());
+                assert_eq!(leaf.keys.len(), leaf.values.len());
+                assert!(leaf.keys.windows(2).all(|w| w[0] < w[1]));
+                assert!(leaf.keys.iter().all(in_bounds));
+                leaves.push((i, lo));
+            } else {
+                let node = &map.internals[i];
+                assert_eq!(node.keys.len() + 1, node.children.len());
+                assert!(node.children.len() <= map.order);
+                assert!(is_root || node.children.len() >= map.min_children());
+                assert!(node.keys.iter().all(in_bounds));
+                for (c, &child) in node.children.iter().enumerate().rev() {
+                    let lo = if c == 0 {
+                        lo.clone()
+                    } else {
+                        Some(node.keys[c - 1].clone())
+                    };
+                    let hi = node.keys.get(c).cloned().or_else(|| hi.clone());
+                    stack.push((child, depth + 1, lo, hi));
+                }
+            }
+        }
+        // The leaves were visited from left to right.
+        let mut prev = NIL;
+        for &(i, _) in &leaves {
+            assert_eq!(map.leaves[i].prev, prev);
+            if prev != NIL {
+                assert_eq!(map.leaves[prev].next, i);
+            }
+            prev = i;
+        }
+        assert_eq!(map.leaves[prev].next, NIL);
+        leaves.iter().map(|&(i, _)| map.leaves[i].keys.len()).sum()
+    }
+
+    #[test]
+    fn insert_remove() {
+        for &order in &[3, 4, 5, 32] {
+            let mut map = BPlusTreeMap::with_order(order);
+            let mut expected = BTreeMap::new();
+            for k in (0..1000).map(|k| (k * 37) % 997) {
+                assert_eq!(map.insert(k, k), expected.insert(k, k));
+            }
+            assert_eq!(check(&map), expected.len());
+            for k in (0..1000).map(|k| (k * 53) % 1009) {
+                assert_eq!(map.remove(&k), expected.remove(&k));
+                assert_eq!(check(&map), expected.len());
+            }
+            assert!(map.iter().eq(expected.iter()));
+            assert!(map.iter().rev().eq(expected.iter().rev()));
+
+            for k in expected.keys().copied().collect::<Vec<_>>() {
+                map.remove(&k);
+            }
+            assert_eq!(check(&map), 0);
+            assert_eq!(map.height, 0);
+            // The slots of the merged nodes are reused.
+            map.extend((0..1000).map(|k| (k, k)));
+            assert_eq!(check(&map), 1000);
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        let mut map = BPlusTreeMap::with_order(4);
+        let mut expected = BTreeMap::new();
+        for k in 0..200 {
+            map.insert(k * 2, ());
+            expected.insert(k * 2, ());
+        }
+        let keys =
+            |r: Range<'_, i32, ()>| r.map(|(k, _)| *k).collect::<Vec<_>>();
+        let expected_keys =
+            |r: alloc::collections::btree_map::Range<'_, i32, ()>| {
+                r.map(|(k, _)| *k).collect::<Vec<_>>()
+            };
+
+        for (lo, hi) in [(3, 9), (4, 10), (0, 400), (-5, 0), (399, 500)] {
+            assert_eq!(
+                keys(map.range(lo..hi)),
+                expected_keys(expected.range(lo..hi))
+            );
+            assert_eq!(
+                keys(map.range(lo..=hi)),
+                expected_keys(expected.range(lo..=hi))
+            );
+            let bounds = (Bound::Excluded(lo), Bound::Included(hi));
+            assert_eq!(
+                keys(map.range(bounds)),
+                expected_keys(expected.range(bounds))
+            );
+            assert!(map.range(lo..hi).rev().eq(expected.range(lo..hi).rev()));
+        }
+        assert_eq!(keys(map.range(..5)), [0, 2, 4]);
+        assert_eq!(keys(map.range(395..)), [396, 398]);
+        assert!(keys(map.range(5..5)).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Range start is greater than range end")]
+    fn inverted_range() {
+        let map: BPlusTreeMap<_, _> = (0..10).map(|k| (k, ())).collect();
+        let (start, end) = (5, 3);
+        map.range(start..end);
+    }
+}
@@ -0,0 +1,471 @@
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+
+/// A priority queue implemented with a binary heap.
+///
+/// This is a max-heap: the greatest element is always at the front. The
+/// elements are stored in an array where the children of the element at index
+/// `i` are at indices `2 * i + 1` and `2 * i + 2`, and no element is less than
+/// any of its children. Wrapping the elements in [`core::cmp::Reverse`] turns
+/// it into a min-heap.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::BinaryHeap;
+///
+/// let mut heap = BinaryHeap::new();
+/// heap.push(1);
+/// heap.push(5);
+/// heap.push(2);
+///
+/// assert_eq!(heap.peek(), Some(&5));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), Some(2));
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryHeap<T> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> BinaryHeap<T> {
+    /// Creates an empty BinaryHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Creates an empty BinaryHeap with space for at least `capacity`
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::with_capacity(10);
+    /// heap.push(4);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Pushes an element onto the heap.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    ///
+    /// assert_eq!(heap.len(), 3);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Removes the greatest element from the heap and returns it, or None if
+    /// it is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from(vec![1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let mut item = self.data.pop()?;
+        if !self.data.is_empty() {
+            core::mem::swap(&mut item, &mut self.data[0]);
+            self.sift_down(0);
+        }
+        Some(item)
+    }
+
+    /// Returns the greatest element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.push(1);
+    /// heap.push(5);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns a mutable reference to the greatest element in the heap, or
+    /// None if it is empty.
+    ///
+    /// The heap is fixed up when the returned guard is dropped, so the
+    /// element can be changed freely through it.
+    ///
+    /// This operation should compute in O(log(n)) time if the element is
+    /// changed, and O(1) time otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from(vec![1, 5, 2]);
+    /// {
+    ///     let mut val = heap.peek_mut().unwrap();
+    ///     *val = 0;
+    /// }
+    /// assert_eq!(heap.peek(), Some(&2));
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(PeekMut {
+            heap: self,
+            changed: false,
+        })
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![1, 3]);
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all elements from the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from(vec![1, 3]);
+    /// heap.clear();
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Returns an iterator visiting all elements in the underlying array, in
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![1, 2, 3, 4]);
+    /// assert_eq!(heap.iter().sum::<i32>(), 10);
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Consumes the heap and returns the underlying array, in arbitrary
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let heap = BinaryHeap::from(vec![1, 2, 3, 4]);
+    /// let mut vec = heap.into_vec();
+    /// vec.sort();
+    /// assert_eq!(vec, [1, 2, 3, 4]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    ///
+    /// This is heapsort: the greatest element is repeatedly swapped to the
+    /// end of the array, and the rest of the array is turned back into a
+    /// heap.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from(vec![1, 2, 4, 5, 7]);
+    /// heap.push(6);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3, 4, 5, 6, 7]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut end = self.data.len();
+        while end > 1 {
+            end -= 1;
+            self.data.swap(0, end);
+            self.sift_down_range(0, end);
+        }
+        self.data
+    }
+
+    /// Moves the element at `pos` up until its parent is not less than it.
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.data[pos] <= self.data[parent] {
+                break;
+            }
+            self.data.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    /// Moves the element at `pos` down until none of its children is greater
+    /// than it.
+    fn sift_down(&mut self, pos: usize) {
+        self.sift_down_range(pos, self.data.len());
+    }
+
+    /// Same as [`sift_down`], but only considers the elements before `end`
+    /// to be part of the heap.
+    ///
+    /// [`sift_down`]: BinaryHeap::sift_down
+    fn sift_down_range(&mut self, mut pos: usize, end: usize) {
+        loop {
+            let mut child = 2 * pos + 1;
+            if child >= end {
+                break;
+            }
+            if child + 1 < end && self.data[child] < self.data[child + 1] {
+                child += 1;
+            }
+            if self.data[pos] >= self.data[child] {
+                break;
+            }
+            self.data.swap(pos, child);
+            pos = child;
+        }
+    }
+
+    /// Turns the underlying array into a heap, by sifting down every element
+    /// that has a child starting from the last one.
+    ///
+    /// Most elements are near the bottom of the heap and only move down a
+    /// few levels, which bounds the work to O(n) instead of the O(n*log(n))
+    /// of pushing the elements one by one.
+    fn rebuild(&mut self) {
+        let mut n = self.data.len() / 2;
+        while n > 0 {
+            n -= 1;
+            self.sift_down(n);
+        }
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for BinaryHeap<T> {
+    /// Converts a `Vec<T>` into a `BinaryHeap<T>` in place.
+    ///
+    /// This operation should compute in O(n) time.
+    fn from(vec: Vec<T>) -> Self {
+        let mut heap = Self { data: vec };
+        heap.rebuild();
+        heap
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BinaryHeap<T> {
+    type Item = &'a T;
+
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+/// A mutable reference to the greatest element of a [`BinaryHeap`].
+///
+/// This struct is created by [`BinaryHeap::peek_mut()`]. See its
+/// documentation for more.
+///
+/// [`BinaryHeap`]: crate::containers::BinaryHeap
+/// [`BinaryHeap::peek_mut()`]: crate::containers::BinaryHeap::peek_mut
+#[derive(Debug)]
+pub struct PeekMut<'a, T: Ord> {
+    heap: &'a mut BinaryHeap<T>,
+    changed: bool,
+}
+
+impl<'a, T: Ord> PeekMut<'a, T> {
+    /// Removes the peeked element from the heap and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{BinaryHeap, PeekMut};
+    ///
+    /// let mut heap = BinaryHeap::from(vec![1, 5, 2]);
+    /// let top = heap.peek_mut().unwrap();
+    /// assert_eq!(PeekMut::pop(top), 5);
+    /// assert_eq!(heap.len(), 2);
+    /// ```
+    pub fn pop(mut this: PeekMut<'a, T>) -> T {
+        // Popping leaves a valid heap, so there is nothing left to fix up.
+        this.changed = false;
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<'a, T: Ord> Deref for PeekMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord> DerefMut for PeekMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.changed = true;
+        &mut self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord> Drop for PeekMut<'a, T> {
+    fn drop(&mut self) {
+        if self.changed {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Reverse;
+
+    #[test]
+    fn push_pop() {
+        let mut heap = BinaryHeap::new();
+        for &x in &[5, 1, 8, 3, 9, 2, 7] {
+            heap.push(x);
+        }
+        assert_eq!(heap.len(), 7);
+
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, vec![9, 8, 7, 5, 3, 2, 1]);
+
+        let mut heap: BinaryHeap<_> =
+            [5, 1, 8].iter().map(|&x| Reverse(x)).collect();
+        assert_eq!(heap.pop(), Some(Reverse(1)));
+    }
+
+    #[test]
+    fn heapify() {
+        let data: Vec<_> = (0..100).map(|x| (x * 37) % 101).collect();
+        let heap = BinaryHeap::from(data.clone());
+        for i in 1..heap.len() {
+            assert!(heap.data[(i - 1) / 2] >= heap.data[i]);
+        }
+
+        let mut sorted = data;
+        sorted.sort_unstable();
+        assert_eq!(heap.into_sorted_vec(), sorted);
+    }
+
+    #[test]
+    fn peek_mut() {
+        let mut heap = BinaryHeap::from(vec![2, 4, 6, 8]);
+        *heap.peek_mut().unwrap() -= 7;
+        assert_eq!(heap.peek(), Some(&6));
+
+        // Reading through the guard leaves the heap as it is.
+        assert_eq!(*heap.peek_mut().unwrap(), 6);
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 6);
+        assert_eq!(heap.into_sorted_vec(), vec![1, 2, 4]);
+    }
+}
@@ -0,0 +1,419 @@
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::mem;
+
+/// A binomial tree. A tree of rank `k` has `2^k` nodes, and its children are
+/// trees of rank `0` to `k - 1` stored in that order.
+#[derive(Debug, Clone)]
+struct Tree<T> {
+    item: T,
+    children: Vec<Tree<T>>,
+}
+
+impl<T: Ord> Tree<T> {
+    fn rank(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Links two trees of the same rank into a tree whose rank is one
+    /// higher, by making the root with the greater element the last child of
+    /// the other.
+    fn link(mut self, mut other: Self) -> Self {
+        debug_assert_eq!(self.rank(), other.rank());
+        if other.item < self.item {
+            mem::swap(&mut self, &mut other);
+        }
+        self.children.push(other);
+        self
+    }
+}
+
+/// A priority queue implemented with a binomial heap.
+///
+/// This is a min-heap: the least element is always at the front. The heap is
+/// a list of binomial trees with distinct ranks, in which no node is less
+/// than its parent. Since a tree of rank `k` holds exactly `2^k` elements, the
+/// ranks of the trees are the positions of the set bits in the binary
+/// representation of the number of elements.
+///
+/// Melding two heaps is then the same as adding two binary numbers: trees of
+/// the same rank are linked into a tree of the next rank, which is carried
+/// over to the next position.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::BinomialHeap;
+///
+/// let mut a: BinomialHeap<_> = vec![5, 1, 4].into_iter().collect();
+/// let b: BinomialHeap<_> = vec![3, 2].into_iter().collect();
+///
+/// a.meld(b);
+/// assert_eq!(a.len(), 5);
+/// assert_eq!(a.ranks().collect::<Vec<_>>(), [0, 2]);
+/// assert_eq!(a.pop_min(), Some(1));
+/// assert_eq!(a.pop_min(), Some(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinomialHeap<T> {
+    // The tree of rank `k`, if any, is at index `k`.
+    trees: Vec<Option<Tree<T>>>,
+    len: usize,
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> BinomialHeap<T> {
+    /// Creates an empty BinomialHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            trees: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pushes an element onto the heap.
+    ///
+    /// This is the same as melding the heap with a heap holding a single
+    /// tree of rank 0, which is like incrementing a binary counter.
+    ///
+    /// This operation should compute in amortized O(1) time, and O(log(n))
+    /// time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// ```
+    pub fn push(&mut self, item: T) {
+        let mut carry = Tree {
+            item,
+            children: Vec::new(),
+        };
+        let mut rank = 0;
+        loop {
+            if rank == self.trees.len() {
+                self.trees.push(None);
+            }
+            match self.trees[rank].take() {
+                Some(tree) => carry = tree.link(carry),
+                None => {
+                    self.trees[rank] = Some(carry);
+                    break;
+                }
+            }
+            rank += 1;
+        }
+        self.len += 1;
+    }
+
+    /// Returns the least element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// assert_eq!(heap.peek_min(), None);
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// ```
+    pub fn peek_min(&self) -> Option<&T> {
+        self.min_rank().map(|rank| match &self.trees[rank] {
+            Some(tree) => &tree.item,
+            None => unreachable!("no tree of rank {}", rank),
+        })
+    }
+
+    /// Removes the least element from the heap and returns it, or None if it
+    /// is empty.
+    ///
+    /// The children of the removed root form a binomial heap of their own,
+    /// which is melded back into the heap.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut heap = BinomialHeap::new();
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop_min(), Some(1));
+    /// assert_eq!(heap.pop_min(), Some(2));
+    /// assert_eq!(heap.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<T> {
+        let rank = self.min_rank()?;
+        let tree = self.trees[rank].take().expect("tree is present");
+        while let Some(None) = self.trees.last() {
+            self.trees.pop();
+        }
+        self.len -= 1 << rank;
+
+        let children = BinomialHeap {
+            trees: tree.children.into_iter().map(Some).collect(),
+            len: (1 << rank) - 1,
+        };
+        self.meld(children);
+        Some(tree.item)
+    }
+
+    /// Moves all the elements of `other` into the heap.
+    ///
+    /// The trees of both heaps are added rank by rank, like the digits of
+    /// two binary numbers. When two or three trees of the same rank meet, two
+    /// of them are linked into a tree of the next rank that is carried over.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let mut a: BinomialHeap<_> = vec![1, 3].into_iter().collect();
+    /// let b: BinomialHeap<_> = vec![2, 4].into_iter().collect();
+    /// a.meld(b);
+    /// assert_eq!(a.into_sorted_vec(), [1, 2, 3, 4]);
+    /// ```
+    pub fn meld(&mut self, other: BinomialHeap<T>) {
+        let mut carry: Option<Tree<T>> = None;
+        let mut others = other.trees.into_iter();
+        let mut rank = 0;
+        loop {
+            let theirs = others.next();
+            if theirs.is_none() && carry.is_none() {
+                break;
+            }
+            if rank == self.trees.len() {
+                self.trees.push(None);
+            }
+            let ours = self.trees[rank].take();
+            let (digit, next) = match (ours, theirs.flatten(), carry.take()) {
+                (None, None, None) => (None, None),
+                (Some(t), None, None)
+                | (None, Some(t), None)
+                | (None, None, Some(t)) => (Some(t), None),
+                (Some(a), Some(b), None)
+                | (Some(a), None, Some(b))
+                | (None, Some(a), Some(b)) => (None, Some(a.link(b))),
+                (Some(a), Some(b), Some(c)) => (Some(c), Some(a.link(b))),
+            };
+            self.trees[rank] = digit;
+            carry = next;
+            rank += 1;
+        }
+        while let Some(None) = self.trees.last() {
+            self.trees.pop();
+        }
+        self.len += other.len;
+    }
+
+    /// Returns an iterator over the ranks of the trees in the heap, in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// // 11 = 0b1011, so there are trees of rank 0, 1 and 3.
+    /// let heap: BinomialHeap<_> = (0..11).collect();
+    /// assert_eq!(heap.ranks().collect::<Vec<_>>(), [0, 1, 3]);
+    /// ```
+    pub fn ranks(&self) -> Ranks<'_, T> {
+        Ranks {
+            iter: self.trees.iter().enumerate(),
+        }
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BinomialHeap;
+    ///
+    /// let heap: BinomialHeap<_> = vec![3, 1, 2].into_iter().collect();
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len);
+        while let Some(item) = self.pop_min() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    /// Returns the rank of the tree whose root is the least element.
+    fn min_rank(&self) -> Option<usize> {
+        let mut min: Option<(usize, &T)> = None;
+        for (rank, tree) in self.trees.iter().enumerate() {
+            if let Some(tree) = tree {
+                match min {
+                    Some((_, item)) if *item <= tree.item => {}
+                    _ => min = Some((rank, &tree.item)),
+                }
+            }
+        }
+        min.map(|(rank, _)| rank)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinomialHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<T: Ord> Extend<T> for BinomialHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+/// An iterator over the ranks of the trees of a [`BinomialHeap`].
+///
+/// This struct is created by [`BinomialHeap::ranks()`]. See its
+/// documentation for more.
+///
+/// [`BinomialHeap`]: crate::containers::BinomialHeap
+/// [`BinomialHeap::ranks()`]: crate::containers::BinomialHeap::ranks
+#[derive(Debug)]
+pub struct Ranks<'a, T> {
+    iter: core::iter::Enumerate<core::slice::Iter<'a, Option<Tree<T>>>>,
+}
+
+impl<'a, T> Iterator for Ranks<'a, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .by_ref()
+            .find(|(_, tree)| tree.is_some())
+            .map(|(rank, _)| rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every tree has the rank of its position, holds `2^k`
+    /// elements, and is heap-ordered.
+    fn check<T: Ord>(heap: &BinomialHeap<T>) {
+        fn size<T: Ord>(tree: &Tree<T>) -> usize {
+            for (rank, child) in tree.children.iter().enumerate() {
+                assert_eq!(child.rank(), rank);
+                assert!(tree.item <= child.item);
+            }
+            1 + tree.children.iter().map(size).sum::<usize>()
+        }
+        let mut len = 0;
+        for (rank, tree) in heap.trees.iter().enumerate() {
+            if let Some(tree) = tree {
+                assert_eq!(tree.rank(), rank);
+                assert_eq!(size(tree), 1 << rank);
+                len += 1 << rank;
+            }
+        }
+        assert_eq!(len, heap.len());
+        assert!(!matches!(heap.trees.last(), Some(None)));
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut heap = BinomialHeap::new();
+        for x in (0..100).map(|x| (x * 37) % 101) {
+            heap.push(x);
+            check(&heap);
+        }
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop_min() {
+            check(&heap);
+            popped.push(x);
+        }
+        let mut expected: Vec<_> = (0..100).map(|x| (x * 37) % 101).collect();
+        expected.sort_unstable();
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn meld_carries() {
+        for n in 0..20 {
+            for m in 0..20 {
+                let mut a: BinomialHeap<_> = (0..n).collect();
+                let b: BinomialHeap<_> = (n..n + m).collect();
+                a.meld(b);
+                check(&a);
+                let ranks: Vec<_> = a.ranks().collect();
+                let bits: Vec<_> =
+                    (0..8).filter(|i| (n + m) >> i & 1 == 1).collect();
+                assert_eq!(ranks, bits);
+                assert!(a.into_sorted_vec().into_iter().eq(0..n + m));
+            }
+        }
+    }
+}
@@ -0,0 +1,541 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not,
+};
+
+pub(crate) const BITS: usize = u64::BITS as usize;
+
+/// A growable vector of bits, packed 64 to a word.
+///
+/// The bits past the length in the last word are always zero, so counting
+/// and comparing work on whole words, and the bitwise operations between
+/// vectors of the same length combine 64 bits at a time.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::BitVec;
+///
+/// let mut a: BitVec = [true, false, true, true].iter().copied().collect();
+/// let b: BitVec = [false, false, true, false].iter().copied().collect();
+/// assert_eq!(a.count_ones(), 3);
+///
+/// a ^= &b;
+/// let ones: Vec<_> = a.ones().collect();
+/// assert_eq!(ones, [0, 3]);
+/// assert_eq!((!&a).ones().collect::<Vec<_>>(), [1, 2]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl BitVec {
+    /// Creates an empty BitVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits = BitVec::new();
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a BitVec of the given length whose bits are all the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits = BitVec::from_elem(70, true);
+    /// assert_eq!(bits.count_ones(), 70);
+    /// ```
+    pub fn from_elem(len: usize, value: bool) -> Self {
+        let fill = if value { !0 } else { 0 };
+        let mut bits = Self {
+            words: alloc::vec![fill; len.div_ceil(BITS)],
+            len,
+        };
+        bits.clear_tail();
+        bits
+    }
+
+    /// Returns the number of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::new();
+    /// bits.push(false);
+    /// assert_eq!(bits.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::new();
+    /// assert!(bits.is_empty());
+    /// bits.push(true);
+    /// assert!(!bits.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a bit.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::new();
+    /// bits.push(true);
+    /// assert_eq!(bits.get(0), Some(true));
+    /// ```
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(BITS) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Removes the last bit and returns it, or None if there are no bits.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::new();
+    /// bits.push(true);
+    /// assert_eq!(bits.pop(), Some(true));
+    /// assert_eq!(bits.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<bool> {
+        let value = self.get(self.len.checked_sub(1)?)?;
+        self.set(self.len - 1, false);
+        self.len -= 1;
+        if self.len.is_multiple_of(BITS) {
+            self.words.pop();
+        }
+        Some(value)
+    }
+
+    /// Returns the bit at the index, or None if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits = BitVec::from_elem(3, true);
+    /// assert_eq!(bits.get(2), Some(true));
+    /// assert_eq!(bits.get(3), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.words[index / BITS] >> (index % BITS) & 1 == 1)
+    }
+
+    /// Sets the bit at the index to the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::from_elem(3, false);
+    /// bits.set(1, true);
+    /// assert_eq!(bits.get(1), Some(true));
+    /// ```
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "Out of bounds access");
+        let mask = 1 << (index % BITS);
+        if value {
+            self.words[index / BITS] |= mask;
+        } else {
+            self.words[index / BITS] &= !mask;
+        }
+    }
+
+    /// Returns the number of bits that are set.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits: BitVec = [true, false, true].iter().copied().collect();
+    /// assert_eq!(bits.count_ones(), 2);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns the number of bits that are set before the index.
+    ///
+    /// This operation should compute in O(index / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is greater than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits: BitVec = [true, false, true].iter().copied().collect();
+    /// assert_eq!(bits.rank(2), 1);
+    /// assert_eq!(bits.rank(3), 2);
+    /// ```
+    pub fn rank(&self, index: usize) -> usize {
+        assert!(index <= self.len, "Out of bounds access");
+        let whole: usize = self.words[..index / BITS]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        let rest = match index % BITS {
+            0 => 0,
+            r => (self.words[index / BITS] << (BITS - r)).count_ones() as usize,
+        };
+        whole + rest
+    }
+
+    /// Returns an iterator visiting the bits in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let bits: BitVec = [true, false].iter().copied().collect();
+    /// assert_eq!(bits.iter().collect::<Vec<_>>(), [true, false]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            bits: self,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator visiting the indices of the bits that are set, in
+    /// increasing order.
+    ///
+    /// Visiting the k indices should compute in O(n / 64 + k) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BitVec;
+    ///
+    /// let mut bits = BitVec::from_elem(100, false);
+    /// bits.set(3, true);
+    /// bits.set(70, true);
+    /// assert_eq!(bits.ones().collect::<Vec<_>>(), [3, 70]);
+    /// ```
+    pub fn ones(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    /// Zeroes the bits past the length in the last word.
+    fn clear_tail(&mut self) {
+        if !self.len.is_multiple_of(BITS) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % BITS)) - 1;
+        }
+    }
+
+    fn combine<F>(&mut self, other: &Self, f: F)
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(self.len, other.len, "Lengths must be equal");
+        for (a, &b) in self.words.iter_mut().zip(&other.words) {
+            *a = f(*a, b);
+        }
+    }
+}
+
+impl BitAndAssign<&BitVec> for BitVec {
+    /// Keeps the bits that are set in both vectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of the vectors are different.
+    fn bitand_assign(&mut self, other: &BitVec) {
+        self.combine(other, |a, b| a & b);
+    }
+}
+
+impl BitOrAssign<&BitVec> for BitVec {
+    /// Sets the bits that are set in either vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of the vectors are different.
+    fn bitor_assign(&mut self, other: &BitVec) {
+        self.combine(other, |a, b| a | b);
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    /// Sets the bits that are set in exactly one of the vectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of the vectors are different.
+    fn bitxor_assign(&mut self, other: &BitVec) {
+        self.combine(other, |a, b| a ^ b);
+    }
+}
+
+impl BitAnd for &BitVec {
+    type Output = BitVec;
+
+    fn bitand(self, other: &BitVec) -> BitVec {
+        let mut bits = self.clone();
+        bits &= other;
+        bits
+    }
+}
+
+impl BitOr for &BitVec {
+    type Output = BitVec;
+
+    fn bitor(self, other: &BitVec) -> BitVec {
+        let mut bits = self.clone();
+        bits |= other;
+        bits
+    }
+}
+
+impl BitXor for &BitVec {
+    type Output = BitVec;
+
+    fn bitxor(self, other: &BitVec) -> BitVec {
+        let mut bits = self.clone();
+        bits ^= other;
+        bits
+    }
+}
+
+impl Not for BitVec {
+    type Output = BitVec;
+
+    fn not(mut self) -> BitVec {
+        for w in &mut self.words {
+            *w = !*w;
+        }
+        self.clear_tail();
+        self
+    }
+}
+
+impl Not for &BitVec {
+    type Output = BitVec;
+
+    fn not(self) -> BitVec {
+        !self.clone()
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = Self::new();
+        bits.extend(iter);
+        bits
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the bits of a BitVec.
+///
+/// This struct is created by [`BitVec::iter()`]. See its documentation for
+/// more.
+///
+/// [`BitVec::iter()`]: crate::containers::BitVec::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    bits: &'a BitVec,
+    index: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.bits.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+/// An iterator over the indices of the bits that are set in a sequence of
+/// words.
+///
+/// This struct is created by [`BitVec::ones()`] and [`FixedBitSet::iter()`].
+/// See their documentation for more.
+///
+/// [`BitVec::ones()`]: crate::containers::BitVec::ones
+/// [`FixedBitSet::iter()`]: crate::containers::FixedBitSet::iter
+#[derive(Debug, Clone)]
+pub struct Ones<'a> {
+    words: &'a [u64],
+    // The index of the first bit of the current word, and its bits that are
+    // yet to be visited.
+    base: usize,
+    word: u64,
+}
+
+impl<'a> Ones<'a> {
+    pub(crate) fn new(words: &'a [u64]) -> Self {
+        match words.split_first() {
+            Some((&word, words)) => Self {
+                words,
+                base: 0,
+                word,
+            },
+            None => Self {
+                words,
+                base: 0,
+                word: 0,
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.word == 0 {
+            let (&word, words) = self.words.split_first()?;
+            self.words = words;
+            self.word = word;
+            self.base += BITS;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        // Clears the lowest bit that is set.
+        self.word &= self.word - 1;
+        Some(self.base + bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn random(rng: &mut SplitMix64, len: usize) -> (BitVec, Vec<bool>) {
+        let bools: Vec<bool> =
+            (0..len).map(|_| rng.next_u64() % 3 == 1).collect();
+        (bools.iter().copied().collect(), bools)
+    }
+
+    #[test]
+    fn matches_vec_of_bools() {
+        let mut rng = SplitMix64::new(59);
+        for len in [0, 1, 63, 64, 65, 130, 200] {
+            let (a, va) = random(&mut rng, len);
+            let (b, vb) = random(&mut rng, len);
+            assert_eq!(a.iter().collect::<Vec<_>>(), va);
+            let ones: Vec<_> = (0..len).filter(|&i| va[i]).collect();
+            assert_eq!(a.ones().collect::<Vec<_>>(), ones);
+            for i in 0..=len {
+                assert_eq!(a.rank(i), va[..i].iter().filter(|&&v| v).count());
+            }
+
+            let zip = |f: fn(bool, bool) -> bool| -> BitVec {
+                va.iter().zip(&vb).map(|(&x, &y)| f(x, y)).collect()
+            };
+            assert_eq!(&a & &b, zip(|x, y| x & y));
+            assert_eq!(&a | &b, zip(|x, y| x | y));
+            assert_eq!(&a ^ &b, zip(|x, y| x ^ y));
+            let not = !&a;
+            assert_eq!(not, zip(|x, _| !x));
+            assert_eq!(not.count_ones(), len - a.count_ones());
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut bits = BitVec::new();
+        for i in 0..150 {
+            bits.push(i % 7 == 0);
+        }
+        assert_eq!(bits.count_ones(), 22);
+        for i in (0..150).rev() {
+            assert_eq!(bits.pop(), Some(i % 7 == 0));
+            assert_eq!(bits, (0..i).map(|i| i % 7 == 0).collect());
+        }
+        assert_eq!(bits.pop(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lengths must be equal")]
+    fn different_lengths() {
+        let mut bits = BitVec::from_elem(3, true);
+        bits &= &BitVec::from_elem(4, true);
+    }
+}
@@ -0,0 +1,566 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+/// An ordered map implemented with a plain binary search tree.
+///
+/// Every key in the left subtree of a node is less than the node's key, and
+/// every key in its right subtree is greater. Nothing is done to keep the tree
+/// balanced, so its shape depends on the order of the insertions: random keys
+/// give a height of O(log(n)) on average, while sorted keys give a tree that
+/// is a linked list, and every operation then takes O(n) time. The map is
+/// meant as the baseline that the balanced trees are measured against, and
+/// [`BstMap::height()`] shows how far the tree is from being balanced.
+///
+/// All the operations walk down the tree in a loop rather than recursively,
+/// so a degenerate tree does not overflow the stack.
+///
+/// [`BstMap::height()`]: crate::containers::BstMap::height
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::BstMap;
+///
+/// let mut map = BstMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(5, "e");
+///
+/// assert_eq!(map.get(&1), Some(&"a"));
+/// assert_eq!(map.floor(&4), Some((&3, &"c")));
+/// assert_eq!(map.ceiling(&4), Some((&5, &"e")));
+/// assert_eq!(map.height(), 2);
+/// ```
+pub struct BstMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V> Default for BstMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for BstMap<K, V> {
+    /// A degenerate tree is as deep as it is large, so the nodes are freed one
+    /// by one instead of recursively.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> =
+            self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for BstMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> BstMap<K, V> {
+    /// Creates an empty BstMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes on the longest path from the root down to a
+    /// leaf, or 0 if the map is empty.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let balanced: BstMap<_, _> =
+    ///     vec![(2, ()), (1, ()), (3, ())].into_iter().collect();
+    /// assert_eq!(balanced.height(), 2);
+    ///
+    /// let degenerate: BstMap<_, _> = (0..3).map(|k| (k, ())).collect();
+    /// assert_eq!(degenerate.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack: Vec<(&Node<K, V>, usize)> = self
+            .root
+            .as_deref()
+            .map(|node| (node, 1))
+            .into_iter()
+            .collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            for child in [&node.left, &node.right].iter().copied().flatten() {
+                stack.push((child, depth + 1));
+            }
+        }
+        height
+    }
+
+    /// Inserts a key-value pair into the map and returns the old value of the
+    /// key, or None if the key was not in the map.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let link = self.find_link_mut(&key);
+        match link {
+            Some(node) => Some(mem::replace(&mut node.value, value)),
+            None => {
+                *link = Some(Box::new(Node {
+                    key,
+                    value,
+                    left: None,
+                    right: None,
+                }));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the map.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, 10);
+    /// *map.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find_link_mut(key)
+            .as_deref_mut()
+            .map(|node| &mut node.value)
+    }
+
+    /// Returns true if the key is in the map.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key from the map and returns its value, or None if the key
+    /// was not in the map.
+    ///
+    /// A node with two children is replaced by the least node of its right
+    /// subtree, which has no left child and can be unlinked directly.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let mut map = BstMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let link = self.find_link_mut(key);
+        let mut node = link.take()?;
+        *link = match (node.left.take(), node.right.take()) {
+            (None, child) | (child, None) => child,
+            (Some(left), Some(right)) => {
+                let mut right = Some(right);
+                let mut min_link = &mut right;
+                while min_link.as_ref().is_some_and(|n| n.left.is_some()) {
+                    min_link = &mut min_link.as_mut().unwrap().left;
+                }
+                let mut min = min_link.take().unwrap();
+                *min_link = min.right.take();
+                min.left = Some(left);
+                min.right = right;
+                Some(min)
+            }
+        };
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Returns the entry with the greatest key that is less than or equal to
+    /// the given key, or None if there is no such entry.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let map: BstMap<_, _> = vec![(1, "a"), (3, "c")].into_iter().collect();
+    /// assert_eq!(map.floor(&2), Some((&1, &"a")));
+    /// assert_eq!(map.floor(&3), Some((&3, &"c")));
+    /// assert_eq!(map.floor(&0), None);
+    /// ```
+    pub fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => {
+                    found = Some(node);
+                    &node.right
+                }
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            };
+        }
+        found.map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns the entry with the least key that is greater than or equal to
+    /// the given key, or None if there is no such entry.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let map: BstMap<_, _> = vec![(1, "a"), (3, "c")].into_iter().collect();
+    /// assert_eq!(map.ceiling(&2), Some((&3, &"c")));
+    /// assert_eq!(map.ceiling(&1), Some((&1, &"a")));
+    /// assert_eq!(map.ceiling(&4), None);
+    /// ```
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => {
+                    found = Some(node);
+                    &node.left
+                }
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            };
+        }
+        found.map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::BstMap;
+    ///
+    /// let map: BstMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+
+    /// Returns the link that holds the key, or the empty link where the key
+    /// would be inserted.
+    fn find_link_mut<Q>(&mut self, key: &Q) -> &mut Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &mut self.root;
+        loop {
+            let ord = match link {
+                Some(node) => key.cmp(node.key.borrow()),
+                None => return link,
+            };
+            link = match ord {
+                Ordering::Less => &mut link.as_mut().unwrap().left,
+                Ordering::Greater => &mut link.as_mut().unwrap().right,
+                Ordering::Equal => return link,
+            };
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BstMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BstMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a BstMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a BstMap, in ascending order of their keys.
+///
+/// This struct is created by [`BstMap::iter()`]. See its documentation for
+/// more.
+///
+/// [`BstMap::iter()`]: crate::containers::BstMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    // The nodes whose left subtrees have been visited but which have not been
+    // yielded themselves, with the next one on top.
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the keys are ordered within the bounds of each subtree, and
+    /// returns the size of the tree.
+    fn check<K: Ord, V>(
+        link: &Link<K, V>,
+        lo: Option<&K>,
+        hi: Option<&K>,
+    ) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                assert!(lo.is_none_or(|lo| *lo < node.key));
+                assert!(hi.is_none_or(|hi| node.key < *hi));
+                1 + check(&node.left, lo, Some(&node.key))
+                    + check(&node.right, Some(&node.key), hi)
+            }
+        }
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = BstMap::new();
+        for k in (0..100).map(|k| (k * 37) % 100) {
+            assert_eq!(map.insert(k, k * 2), None);
+        }
+        assert_eq!(check(&map.root, None, None), 100);
+        assert_eq!(map.insert(37, 0), Some(74));
+        assert_eq!(map.get(&37), Some(&0));
+
+        for k in (0..100).step_by(3) {
+            let expected = if k == 37 { 0 } else { k * 2 };
+            assert_eq!(map.remove(&k), Some(expected));
+            assert_eq!(check(&map.root, None, None), map.len());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 66);
+        assert!(map
+            .iter()
+            .map(|(k, _)| *k)
+            .eq((0..100).filter(|k| k % 3 != 0)));
+    }
+
+    #[test]
+    fn floor_ceiling() {
+        let map: BstMap<_, _> = (0..10).map(|k| (k * 10, k)).collect();
+        assert_eq!(map.floor(&-1), None);
+        assert_eq!(map.floor(&45), Some((&40, &4)));
+        assert_eq!(map.floor(&1000), Some((&90, &9)));
+        assert_eq!(map.ceiling(&-1), Some((&0, &0)));
+        assert_eq!(map.ceiling(&45), Some((&50, &5)));
+        assert_eq!(map.ceiling(&91), None);
+    }
+
+    #[test]
+    fn degenerate_height() {
+        // Sorted keys build a tree that is a single right spine.
+        let sorted: BstMap<_, _> = (0..5000).map(|k| (k, ())).collect();
+        assert_eq!(sorted.height(), 5000);
+        assert_eq!(sorted.iter().len(), 5000);
+        assert!(sorted.contains_key(&4999));
+    }
+}
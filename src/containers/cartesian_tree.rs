@@ -0,0 +1,426 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+const NIL: usize = usize::MAX;
+
+/// A binary tree over the indices of a sequence that is a min-heap on the
+/// values and a binary search tree on the indices.
+///
+/// The root is the index of the smallest value, and its left and right
+/// subtrees are the Cartesian trees of the elements before and after it, so
+/// an in-order walk visits the indices in increasing order. Among equal
+/// values, the leftmost one is the ancestor. The tree is built in one pass
+/// that keeps the right spine on a stack: a new index pops every index with
+/// a greater value, takes the last one popped as its left child, and becomes
+/// the right child of the index left on top.
+///
+/// The smallest value of a range of indices is at the lowest common ancestor
+/// of its two ends, which reduces range minimum queries to lowest common
+/// ancestor queries, and the [`EulerTour`] of the tree reduces those back to
+/// range minimum queries over depths that differ by one between neighbors.
+///
+/// [`EulerTour`]: crate::containers::EulerTour
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::CartesianTree;
+///
+/// let tree = CartesianTree::new(&[3, 1, 4, 1, 5, 9, 2, 6]);
+/// assert_eq!(tree.root(), Some(1));
+/// assert_eq!(tree.left(1), Some(0));
+/// assert_eq!(tree.right(1), Some(3));
+/// assert_eq!(tree.right(3), Some(6));
+/// assert_eq!(tree.parent(4), Some(6));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CartesianTree<T> {
+    values: Vec<T>,
+    parents: Vec<usize>,
+    lefts: Vec<usize>,
+    rights: Vec<usize>,
+    root: usize,
+}
+
+impl<T: Clone + Ord> CartesianTree<T> {
+    /// Creates a CartesianTree over a copy of the values.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[2, 0, 1]);
+    /// assert_eq!(tree.root(), Some(1));
+    /// assert_eq!(tree.left(1), Some(0));
+    /// assert_eq!(tree.right(1), Some(2));
+    /// ```
+    pub fn new(values: &[T]) -> Self {
+        let n = values.len();
+        let mut parents = vec![NIL; n];
+        let mut lefts = vec![NIL; n];
+        let mut rights = vec![NIL; n];
+        // The right spine of the tree of the values seen so far, from the
+        // root down.
+        let mut spine: Vec<usize> = Vec::new();
+        for i in 0..n {
+            let mut last = NIL;
+            while let Some(&top) = spine.last() {
+                if values[top] <= values[i] {
+                    break;
+                }
+                last = spine.pop().unwrap();
+            }
+            if last != NIL {
+                lefts[i] = last;
+                parents[last] = i;
+            }
+            if let Some(&top) = spine.last() {
+                rights[top] = i;
+                parents[i] = top;
+            }
+            spine.push(i);
+        }
+        Self {
+            values: values.to_vec(),
+            parents,
+            lefts,
+            rights,
+            root: spine.first().copied().unwrap_or(NIL),
+        }
+    }
+}
+
+impl<T> CartesianTree<T> {
+    /// Returns the number of elements in the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[1, 2, 3]);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the sequence has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::<i32>::new(&[]);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the values of the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3]);
+    /// assert_eq!(tree.values(), [5, 3]);
+    /// ```
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the index of the smallest value, which is the root of the
+    /// tree, or None if the sequence is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3, 4]);
+    /// assert_eq!(tree.root(), Some(1));
+    /// ```
+    pub fn root(&self) -> Option<usize> {
+        to_option(self.root)
+    }
+
+    /// Returns the parent of the index, or None if it is the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3, 4]);
+    /// assert_eq!(tree.parent(2), Some(1));
+    /// assert_eq!(tree.parent(1), None);
+    /// ```
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        to_option(self.parents[index])
+    }
+
+    /// Returns the left child of the index, which is the index of the
+    /// smallest value between its previous smaller value and itself, or None
+    /// if there is none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3, 4]);
+    /// assert_eq!(tree.left(1), Some(0));
+    /// assert_eq!(tree.left(0), None);
+    /// ```
+    pub fn left(&self, index: usize) -> Option<usize> {
+        to_option(self.lefts[index])
+    }
+
+    /// Returns the right child of the index, which is the index of the
+    /// smallest value between itself and its next smaller value, or None if
+    /// there is none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3, 4]);
+    /// assert_eq!(tree.right(1), Some(2));
+    /// assert_eq!(tree.right(2), None);
+    /// ```
+    pub fn right(&self, index: usize) -> Option<usize> {
+        to_option(self.rights[index])
+    }
+
+    /// Returns the Euler tour of the tree, which lists every index each time
+    /// a depth-first walk from the root visits it.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tree = CartesianTree::new(&[5, 3, 4]);
+    /// let tour = tree.euler_tour();
+    /// assert_eq!(tour.nodes(), [1, 0, 1, 2, 1]);
+    /// assert_eq!(tour.depths(), [0, 1, 0, 1, 0]);
+    /// assert_eq!(tour.first_visit(2), 3);
+    /// ```
+    pub fn euler_tour(&self) -> EulerTour {
+        let n = self.len();
+        let mut tour = EulerTour {
+            nodes: Vec::with_capacity(2 * n),
+            depths: Vec::with_capacity(2 * n),
+            first: vec![NIL; n],
+        };
+        if n == 0 {
+            return tour;
+        }
+        // The path from the root, with the number of children of each index
+        // that were visited.
+        let mut path = vec![(self.root, 0)];
+        tour.visit(self.root, 0);
+        while let Some((index, visited)) = path.last_mut() {
+            let child = match visited {
+                0 => self.lefts[*index],
+                1 => self.rights[*index],
+                _ => {
+                    path.pop();
+                    if let Some(&(parent, _)) = path.last() {
+                        tour.visit(parent, path.len() - 1);
+                    }
+                    continue;
+                }
+            };
+            *visited += 1;
+            if child != NIL {
+                path.push((child, 0));
+                tour.visit(child, path.len() - 1);
+            }
+        }
+        tour
+    }
+}
+
+fn to_option(index: usize) -> Option<usize> {
+    match index {
+        NIL => None,
+        _ => Some(index),
+    }
+}
+
+/// The Euler tour of a [`CartesianTree`], which lists the indices of the
+/// tree with their depths in the order a depth-first walk visits them, each
+/// index once when the walk reaches it and once after returning from each
+/// of its children.
+///
+/// The lowest common ancestor of two indices is the index of the smallest
+/// depth in the tour between their first visits, so a range minimum
+/// structure over the depths, such as a [`SparseTable`], answers range
+/// minimum queries over the original sequence.
+///
+/// This struct is created by [`CartesianTree::euler_tour()`]. See its
+/// documentation for more.
+///
+/// [`CartesianTree`]: crate::containers::CartesianTree
+/// [`CartesianTree::euler_tour()`]: CartesianTree::euler_tour
+/// [`SparseTable`]: crate::containers::SparseTable
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::CartesianTree;
+///
+/// let values = [3, 1, 4, 1, 5, 9, 2, 6];
+/// let tour = CartesianTree::new(&values).euler_tour();
+///
+/// // The smallest value in 4..=7 is at the shallowest index of the tour
+/// // between the first visits of 4 and 7.
+/// let (a, b) = (tour.first_visit(4), tour.first_visit(7));
+/// let at = (a.min(b)..=a.max(b))
+///     .min_by_key(|&i| tour.depths()[i])
+///     .unwrap();
+/// assert_eq!(tour.nodes()[at], 6);
+/// assert_eq!(values[6], 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EulerTour {
+    nodes: Vec<usize>,
+    depths: Vec<usize>,
+    first: Vec<usize>,
+}
+
+impl EulerTour {
+    /// Returns the indices of the tree in the order of the tour, which has
+    /// `2n - 1` entries for a tree of n indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tour = CartesianTree::new(&[1, 2]).euler_tour();
+    /// assert_eq!(tour.nodes(), [0, 1, 0]);
+    /// ```
+    pub fn nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+
+    /// Returns the depths of the indices in the order of the tour, where the
+    /// root has depth zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tour = CartesianTree::new(&[1, 2]).euler_tour();
+    /// assert_eq!(tour.depths(), [0, 1, 0]);
+    /// ```
+    pub fn depths(&self) -> &[usize] {
+        &self.depths
+    }
+
+    /// Returns the position in the tour of the first visit of the index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CartesianTree;
+    ///
+    /// let tour = CartesianTree::new(&[2, 1]).euler_tour();
+    /// assert_eq!(tour.nodes(), [1, 0, 1]);
+    /// assert_eq!(tour.first_visit(0), 1);
+    /// ```
+    pub fn first_visit(&self, index: usize) -> usize {
+        self.first[index]
+    }
+
+    fn visit(&mut self, index: usize, depth: usize) {
+        if self.first[index] == NIL {
+            self.first[index] = self.nodes.len();
+        }
+        self.nodes.push(index);
+        self.depths.push(depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn answers_range_minimum_queries() {
+        let mut rng = SplitMix64::new(59);
+        for n in [0, 1, 2, 10, 100] {
+            let values: Vec<u64> =
+                (0..n).map(|_| rng.next_u64() % 10).collect();
+            let tree = CartesianTree::new(&values);
+            for i in 0..n {
+                if let Some(parent) = tree.parent(i) {
+                    assert!(values[parent] <= values[i]);
+                    let child = if parent < i {
+                        tree.right(parent)
+                    } else {
+                        tree.left(parent)
+                    };
+                    assert_eq!(child, Some(i));
+                }
+            }
+            let tour = tree.euler_tour();
+            assert_eq!(tour.nodes().len(), (2 * n).saturating_sub(1));
+            assert!(tour
+                .depths()
+                .windows(2)
+                .all(|w| w[0] + 1 == w[1] || w[1] + 1 == w[0]));
+            for i in 0..n {
+                assert_eq!(tour.nodes()[tour.first_visit(i)], i);
+                for j in i..n {
+                    let (a, b) = (tour.first_visit(i), tour.first_visit(j));
+                    let at = (a.min(b)..=a.max(b))
+                        .min_by_key(|&k| tour.depths()[k])
+                        .unwrap();
+                    let expected = (i..=j).min_by_key(|&k| values[k]).unwrap();
+                    assert_eq!(tour.nodes()[at], expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sorted_values_make_a_path() {
+        let values: Vec<_> = (0..10_000).collect();
+        let tree = CartesianTree::new(&values);
+        assert_eq!(tree.root(), Some(0));
+        assert_eq!(tree.right(9_998), Some(9_999));
+        let tour = tree.euler_tour();
+        assert_eq!(tour.depths().iter().max(), Some(&9_999));
+    }
+}
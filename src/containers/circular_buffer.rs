@@ -0,0 +1,486 @@
+use core::slice;
+
+use super::array_deque::ArrayDeque;
+
+/// What a [`CircularBuffer`] does when an element is pushed while it is
+/// full.
+///
+/// [`CircularBuffer`]: crate::containers::CircularBuffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Refuses the new element and gives it back in a [`BufferFull`] error.
+    ///
+    /// [`BufferFull`]: crate::containers::BufferFull
+    Reject,
+    /// Drops the oldest element to make room for the new one.
+    Overwrite,
+}
+
+/// The error of pushing to a full [`CircularBuffer`] that rejects new
+/// elements when it is full.
+///
+/// This struct is returned by [`CircularBuffer::push()`]. See its
+/// documentation for more.
+///
+/// [`CircularBuffer`]: crate::containers::CircularBuffer
+/// [`CircularBuffer::push()`]: crate::containers::CircularBuffer::push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferFull<T> {
+    value: T,
+}
+
+impl<T> BufferFull<T> {
+    /// Returns the element that was refused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(1, Overflow::Reject);
+    /// buffer.push('a').unwrap();
+    /// let err = buffer.push('b').unwrap_err();
+    /// assert_eq!(err.into_value(), 'b');
+    /// ```
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+/// A queue of at most a fixed number of elements, which either refuses new
+/// elements or drops its oldest ones when it is full, as chosen by its
+/// [`Overflow`] mode.
+///
+/// The elements are kept in an [`ArrayDeque`] that is allocated once with
+/// the capacity of the buffer and never grows, so that pushing never
+/// allocates. In overwrite mode, the buffer always holds the most recent
+/// elements pushed, which makes it suited to keeping the tail of a log or of
+/// a stream of measurements.
+///
+/// [`Overflow`]: crate::containers::Overflow
+/// [`ArrayDeque`]: crate::containers::ArrayDeque
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{CircularBuffer, Overflow};
+///
+/// let mut log = CircularBuffer::new(3, Overflow::Overwrite);
+/// for line in ["a", "b", "c", "d"] {
+///     log.push(line).unwrap();
+/// }
+/// assert_eq!(log.iter().collect::<Vec<_>>(), [&"b", &"c", &"d"]);
+/// assert_eq!(log.latest(2).collect::<Vec<_>>(), [&"c", &"d"]);
+///
+/// let mut queue = CircularBuffer::new(1, Overflow::Reject);
+/// queue.push(1).unwrap();
+/// assert!(queue.push(2).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircularBuffer<T> {
+    buf: ArrayDeque<T>,
+    capacity: usize,
+    mode: Overflow,
+}
+
+impl<T> CircularBuffer<T> {
+    /// Creates an empty CircularBuffer that holds at most `capacity`
+    /// elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let buffer: CircularBuffer<u32> =
+    ///     CircularBuffer::new(8, Overflow::Overwrite);
+    /// assert_eq!(buffer.capacity(), 8);
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn new(capacity: usize, mode: Overflow) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            buf: ArrayDeque::with_capacity(capacity),
+            capacity,
+            mode,
+        }
+    }
+
+    /// Returns the maximum number of elements in the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let buffer: CircularBuffer<u32> =
+    ///     CircularBuffer::new(8, Overflow::Reject);
+    /// assert_eq!(buffer.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns what the buffer does when an element is pushed while it is
+    /// full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let buffer: CircularBuffer<u32> =
+    ///     CircularBuffer::new(8, Overflow::Reject);
+    /// assert_eq!(buffer.mode(), Overflow::Reject);
+    /// ```
+    pub fn mode(&self) -> Overflow {
+        self.mode
+    }
+
+    /// Changes what the buffer does when an element is pushed while it is
+    /// full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(1, Overflow::Reject);
+    /// buffer.push(1).unwrap();
+    /// buffer.set_mode(Overflow::Overwrite);
+    /// assert_eq!(buffer.push(2), Ok(Some(1)));
+    /// ```
+    pub fn set_mode(&mut self, mode: Overflow) {
+        self.mode = mode;
+    }
+
+    /// Returns the number of elements in the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// buffer.push(1).unwrap();
+    /// assert_eq!(buffer.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns true if the buffer has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// assert!(buffer.is_empty());
+    /// buffer.push(1).unwrap();
+    /// assert!(!buffer.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns true if the buffer holds as many elements as its capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(1, Overflow::Overwrite);
+    /// assert!(!buffer.is_full());
+    /// buffer.push(1).unwrap();
+    /// assert!(buffer.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.buf.len() == self.capacity
+    }
+
+    /// Appends the element as the newest one.
+    ///
+    /// If the buffer is full, the element is either refused, or replaces the
+    /// oldest element, which is returned, depending on the mode of the
+    /// buffer.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error holding the element if the buffer is full and its
+    /// mode is [`Overflow::Reject`].
+    ///
+    /// [`Overflow::Reject`]: crate::containers::Overflow::Reject
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// assert_eq!(buffer.push(1), Ok(None));
+    /// assert_eq!(buffer.push(2), Ok(None));
+    /// assert_eq!(buffer.push(3), Ok(Some(1)));
+    ///
+    /// let mut buffer = CircularBuffer::new(1, Overflow::Reject);
+    /// assert_eq!(buffer.push(1), Ok(None));
+    /// assert!(buffer.push(2).is_err());
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<Option<T>, BufferFull<T>> {
+        let overwritten = if self.is_full() {
+            match self.mode {
+                Overflow::Reject => return Err(BufferFull { value }),
+                Overflow::Overwrite => self.buf.pop_front(),
+            }
+        } else {
+            None
+        };
+        self.buf.push_back(value);
+        Ok(overwritten)
+    }
+
+    /// Removes the oldest element and returns it, or None if the buffer is
+    /// empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Reject);
+    /// buffer.push(1).unwrap();
+    /// buffer.push(2).unwrap();
+    /// assert_eq!(buffer.pop(), Some(1));
+    /// assert_eq!(buffer.pop(), Some(2));
+    /// assert_eq!(buffer.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        self.buf.pop_front()
+    }
+
+    /// Returns a reference to the oldest element, or None if the buffer is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// for i in 0..5 {
+    ///     buffer.push(i).unwrap();
+    /// }
+    /// assert_eq!(buffer.oldest(), Some(&3));
+    /// ```
+    pub fn oldest(&self) -> Option<&T> {
+        self.buf.front()
+    }
+
+    /// Returns a reference to the newest element, or None if the buffer is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// for i in 0..5 {
+    ///     buffer.push(i).unwrap();
+    /// }
+    /// assert_eq!(buffer.newest(), Some(&4));
+    /// ```
+    pub fn newest(&self) -> Option<&T> {
+        self.buf.back()
+    }
+
+    /// Returns a reference to the element at the index, counted from the
+    /// oldest element, or None if the index is out of bounds.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// for i in 0..5 {
+    ///     buffer.push(i).unwrap();
+    /// }
+    /// assert_eq!(buffer.get(1), Some(&4));
+    /// assert_eq!(buffer.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.buf.get(index)
+    }
+
+    /// Removes all the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(2, Overflow::Overwrite);
+    /// buffer.push(1).unwrap();
+    /// buffer.clear();
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    /// Returns an iterator over the elements from the oldest to the newest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(3, Overflow::Overwrite);
+    /// for i in 0..5 {
+    ///     buffer.push(i).unwrap();
+    /// }
+    /// assert_eq!(buffer.iter().collect::<Vec<_>>(), [&2, &3, &4]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.latest(self.len())
+    }
+
+    /// Returns an iterator over the `n` newest elements, or all of them if
+    /// there are fewer, from the oldest to the newest.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CircularBuffer, Overflow};
+    ///
+    /// let mut buffer = CircularBuffer::new(4, Overflow::Overwrite);
+    /// for i in 0..6 {
+    ///     buffer.push(i).unwrap();
+    /// }
+    /// assert_eq!(buffer.latest(2).collect::<Vec<_>>(), [&4, &5]);
+    /// assert_eq!(buffer.latest(10).count(), 4);
+    /// ```
+    pub fn latest(&self, n: usize) -> Iter<'_, T> {
+        let (front, back) = self.buf.as_slices();
+        let skip = self.len().saturating_sub(n);
+        let (front, back) = if skip <= front.len() {
+            (&front[skip..], back)
+        } else {
+            (&[][..], &back[skip - front.len()..])
+        };
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CircularBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`CircularBuffer`], from the oldest to
+/// the newest.
+///
+/// This struct is created by [`CircularBuffer::iter()`] and
+/// [`CircularBuffer::latest()`]. See their documentation for more.
+///
+/// [`CircularBuffer`]: crate::containers::CircularBuffer
+/// [`CircularBuffer::iter()`]: crate::containers::CircularBuffer::iter
+/// [`CircularBuffer::latest()`]: crate::containers::CircularBuffer::latest
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn matches_vec_deque() {
+        for capacity in 1..6 {
+            for mode in [Overflow::Reject, Overflow::Overwrite] {
+                let mut buffer = CircularBuffer::new(capacity, mode);
+                let mut expected = VecDeque::new();
+                for i in 0..40 {
+                    if i % 7 == 3 {
+                        assert_eq!(buffer.pop(), expected.pop_front());
+                        continue;
+                    }
+                    let pushed = buffer.push(i);
+                    if expected.len() < capacity {
+                        assert_eq!(pushed, Ok(None));
+                        expected.push_back(i);
+                    } else if mode == Overflow::Overwrite {
+                        assert_eq!(pushed, Ok(expected.pop_front()));
+                        expected.push_back(i);
+                    } else {
+                        assert_eq!(pushed.unwrap_err().into_value(), i);
+                    }
+                    assert!(buffer.iter().eq(expected.iter()));
+                    assert!(buffer.iter().rev().eq(expected.iter().rev()));
+                    for n in 0..=capacity + 1 {
+                        let skip = expected.len().saturating_sub(n);
+                        let latest: Vec<_> = buffer.latest(n).collect();
+                        assert_eq!(latest.len(), expected.len() - skip);
+                        assert!(latest
+                            .into_iter()
+                            .eq(expected.iter().skip(skip)));
+                    }
+                    assert_eq!(buffer.is_full(), expected.len() == capacity);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity must be positive")]
+    fn zero_capacity() {
+        CircularBuffer::<u32>::new(0, Overflow::Overwrite);
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of time for containers whose entries expire.
+///
+/// Containers read the current time through this trait instead of calling
+/// [`Instant::now`] directly, so that tests can control the passage of time
+/// with a [`ManualClock`].
+///
+/// [`ManualClock`]: crate::containers::ManualClock
+pub trait Clock: fmt::Debug {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A [`Clock`] that reads the system's monotonic clock.
+///
+/// [`Clock`]: crate::containers::Clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A [`Clock`] that only moves forward when it is told to.
+///
+/// Clones of a `ManualClock` share the same time, so a clone can be handed to
+/// a container while the original is used to advance the time.
+///
+/// [`Clock`]: crate::containers::Clock
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dt::containers::{Clock, ManualClock};
+///
+/// let clock = ManualClock::new();
+/// let other = clock.clone();
+///
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(other.now() - start, Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl ManualClock {
+    /// Creates a `ManualClock` that is stopped at the current time.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use super::fnv::FnvHasher;
+use super::rng::SplitMix64;
+
+/// A table of counters that estimates how many times every item of a stream
+/// was seen, in memory that does not depend on the number of items.
+///
+/// Every item is counted once in every row, at a column picked by hashing
+/// it. Different items can share a counter, so a counter overestimates the
+/// count of each of its items but never underestimates it, and the estimate
+/// of an item is its smallest counter. With a width of ⌈e / ε⌉ and a depth of
+/// ⌈ln(1 / δ)⌉, an estimate exceeds the true count by more than ε times the
+/// total of all counts with probability at most δ.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::CountMinSketch;
+///
+/// let mut sketch = CountMinSketch::with_error(0.01, 0.01);
+/// for word in "the cat and the dog and the bird".split(' ') {
+///     sketch.add(word, 1);
+/// }
+/// assert_eq!(sketch.estimate("the"), 3);
+/// assert!(sketch.estimate("and") >= 2);
+/// assert_eq!(sketch.total(), 8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: usize,
+    // The counters, row after row.
+    counters: Vec<u64>,
+    total: u64,
+}
+
+impl CountMinSketch {
+    /// Creates a CountMinSketch with the given number of counters per row,
+    /// and of rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width or the depth is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let sketch = CountMinSketch::new(100, 4);
+    /// assert_eq!((sketch.width(), sketch.depth()), (100, 4));
+    /// ```
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "Dimensions must be positive");
+        Self {
+            width,
+            depth,
+            counters: alloc::vec![0; width * depth],
+            total: 0,
+        }
+    }
+
+    /// Creates a CountMinSketch whose estimates exceed the true counts by
+    /// more than `epsilon` times the total of all counts with probability at
+    /// most `delta`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` or `delta` is not between 0 and 1, exclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let sketch = CountMinSketch::with_error(0.001, 0.01);
+    /// assert_eq!((sketch.width(), sketch.depth()), (2719, 5));
+    /// ```
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon < 1.0 && delta > 0.0 && delta < 1.0,
+            "Error bounds must be between 0 and 1"
+        );
+        let width = core::f64::consts::E / epsilon;
+        let mut depth = 0;
+        // The smallest depth with e^depth >= 1 / delta, without logarithms.
+        let mut bound = 1.0;
+        while bound * delta < 1.0 {
+            bound *= core::f64::consts::E;
+            depth += 1;
+        }
+        Self::new(ceil(width), depth)
+    }
+
+    /// Returns the number of counters per row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let sketch = CountMinSketch::new(100, 4);
+    /// assert_eq!(sketch.width(), 100);
+    /// ```
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let sketch = CountMinSketch::new(100, 4);
+    /// assert_eq!(sketch.depth(), 4);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the total of all the counts added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let mut sketch = CountMinSketch::new(100, 4);
+    /// sketch.add("a", 2);
+    /// sketch.add("b", 3);
+    /// assert_eq!(sketch.total(), 5);
+    /// ```
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Adds the count to the item. The counters saturate at `u64::MAX`.
+    ///
+    /// This operation should compute in O(d) time, where d is the depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let mut sketch = CountMinSketch::new(100, 4);
+    /// sketch.add(&42, 5);
+    /// assert_eq!(sketch.estimate(&42), 5);
+    /// ```
+    pub fn add<T>(&mut self, item: &T, count: u64)
+    where
+        T: Hash + ?Sized,
+    {
+        let width = self.width;
+        let mut columns = Columns::new(item, width);
+        for row in self.counters.chunks_exact_mut(width) {
+            let counter = &mut row[columns.next()];
+            *counter = counter.saturating_add(count);
+        }
+        self.total = self.total.saturating_add(count);
+    }
+
+    /// Returns an estimate of the total count added to the item, which is
+    /// never below the true one.
+    ///
+    /// This operation should compute in O(d) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let mut sketch = CountMinSketch::new(100, 4);
+    /// sketch.add("a", 1);
+    /// sketch.add("a", 1);
+    /// assert!(sketch.estimate("a") >= 2);
+    /// ```
+    pub fn estimate<T>(&self, item: &T) -> u64
+    where
+        T: Hash + ?Sized,
+    {
+        let mut columns = Columns::new(item, self.width);
+        self.counters
+            .chunks_exact(self.width)
+            .map(|row| row[columns.next()])
+            .min()
+            .unwrap()
+    }
+
+    /// Adds the counts of the other sketch to this one, as if the items of
+    /// both streams had been added to this one.
+    ///
+    /// This operation should compute in O(w * d) time, where w is the width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sketches have different widths or depths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let mut a = CountMinSketch::new(100, 4);
+    /// let mut b = CountMinSketch::new(100, 4);
+    /// a.add("x", 1);
+    /// b.add("x", 2);
+    /// a.merge(&b);
+    /// assert_eq!(a.estimate("x"), 3);
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "Dimensions must be equal"
+        );
+        for (a, &b) in self.counters.iter_mut().zip(&other.counters) {
+            *a = a.saturating_add(b);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    /// Resets all the counters to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CountMinSketch;
+    ///
+    /// let mut sketch = CountMinSketch::new(100, 4);
+    /// sketch.add("a", 1);
+    /// sketch.clear();
+    /// assert_eq!(sketch.estimate("a"), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c = 0);
+        self.total = 0;
+    }
+}
+
+/// The columns of an item in the successive rows, drawn from a generator
+/// seeded with the hash of the item.
+struct Columns {
+    rng: SplitMix64,
+    width: u64,
+}
+
+impl Columns {
+    fn new<T: Hash + ?Sized>(item: &T, width: usize) -> Self {
+        let mut hasher = FnvHasher::default();
+        item.hash(&mut hasher);
+        Self {
+            rng: SplitMix64::new(hasher.finish()),
+            width: width as u64,
+        }
+    }
+
+    fn next(&mut self) -> usize {
+        (self.rng.next_u64() % self.width) as usize
+    }
+}
+
+/// Rounds up a positive number that fits in a `usize`.
+fn ceil(x: f64) -> usize {
+    let n = x as usize;
+    if (n as f64) < x {
+        n + 1
+    } else {
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_within_bounds() {
+        let (epsilon, delta) = (0.01, 0.01);
+        let mut sketch = CountMinSketch::with_error(epsilon, delta);
+        // A skewed stream where item i is seen 1000 / (i + 1) times.
+        let counts: Vec<u64> = (0..1000).map(|i| 1000 / (i + 1)).collect();
+        for (i, &count) in counts.iter().enumerate() {
+            sketch.add(&i, count);
+        }
+        let total: u64 = counts.iter().sum();
+        assert_eq!(sketch.total(), total);
+        let bound = (epsilon * total as f64) as u64;
+        let mut over = 0;
+        for (i, &count) in counts.iter().enumerate() {
+            let estimate = sketch.estimate(&i);
+            assert!(estimate >= count);
+            over += (estimate - count > bound) as usize;
+        }
+        assert!(over as f64 <= 2.0 * delta * counts.len() as f64);
+    }
+
+    #[test]
+    fn merge_matches_single_sketch() {
+        let mut all = CountMinSketch::new(50, 3);
+        let mut a = CountMinSketch::new(50, 3);
+        let mut b = CountMinSketch::new(50, 3);
+        for i in 0..200u32 {
+            all.add(&i, 1);
+            if i % 2 == 0 {
+                a.add(&i, 1);
+            } else {
+                b.add(&i, 1);
+            }
+        }
+        a.merge(&b);
+        assert_eq!(a, all);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dimensions must be equal")]
+    fn merge_different_dimensions() {
+        CountMinSketch::new(50, 3).merge(&CountMinSketch::new(50, 4));
+    }
+}
@@ -0,0 +1,572 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use core::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Sub, SubAssign,
+};
+use core::{cmp, mem};
+
+use super::linked_hash_map::{self, DefaultHashBuilder, LinkedHashMap};
+
+/// A multiset, which counts how many times each item was added.
+///
+/// The counts are kept in a [`LinkedHashMap`] from the items to their counts,
+/// and an item is removed once its count drops to zero, so every item in the
+/// counter has a positive count. Like Python's `Counter`, two counters can be
+/// added, subtracted, intersected with `&` (keeping the smaller counts), and
+/// united with `|` (keeping the larger counts).
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Counter;
+///
+/// let words: Counter<_> = "the cat and the hat and the bat"
+///     .split(' ')
+///     .collect();
+/// assert_eq!(words.count("the"), 3);
+/// assert_eq!(words.count("dog"), 0);
+/// assert_eq!(words.most_common(2), [(&"the", 3), (&"and", 2)]);
+///
+/// let hats: Counter<_> = ["hat", "hat"].iter().copied().collect();
+/// let all = &words + &hats;
+/// assert_eq!(all.count("hat"), 3);
+/// assert_eq!(all.total(), 10);
+/// ```
+#[derive(Debug)]
+pub struct Counter<T, S = DefaultHashBuilder> {
+    map: LinkedHashMap<T, usize, S>,
+    total: usize,
+}
+
+impl<T, S> Default for Counter<T, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<T, S> Clone for Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut counter = Self::with_hasher(self.map.hasher().clone());
+        counter.map.reserve(self.map.len());
+        for (item, count) in self.iter() {
+            counter.add_n(item.clone(), count);
+        }
+        counter
+    }
+}
+
+impl<T> Counter<T, DefaultHashBuilder> {
+    /// Creates an empty Counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<char> = Counter::new();
+    /// assert!(counter.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T, S> Counter<T, S> {
+    /// Creates an empty Counter that uses the given hash builder to hash the
+    /// items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Counter, FnvBuildHasher};
+    ///
+    /// let mut counter = Counter::with_hasher(FnvBuildHasher::default());
+    /// counter.add('a');
+    /// assert_eq!(counter.count(&'a'), 1);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self {
+            map: LinkedHashMap::with_hasher(hasher_builder),
+            total: 0,
+        }
+    }
+
+    /// Returns the number of distinct items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "abracadabra".chars().collect();
+    /// assert_eq!(counter.len(), 5);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the sum of the counts of all the items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "abracadabra".chars().collect();
+    /// assert_eq!(counter.total(), 11);
+    /// ```
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns true if the counter has no items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let mut counter = Counter::new();
+    /// assert!(counter.is_empty());
+    /// counter.add('a');
+    /// assert!(!counter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns an iterator over the items and their counts, in the order the
+    /// items were first added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "abba".chars().collect();
+    /// let counts: Vec<_> = counter.iter().collect();
+    /// assert_eq!(counts, [(&'a', 2), (&'b', 2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Returns the `n` items with the largest counts and their counts, from
+    /// the most common to the least. Items with equal counts are in the order
+    /// they were first added.
+    ///
+    /// This operation should compute in O(m log(m)) time, where m is the
+    /// number of distinct items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "abracadabra".chars().collect();
+    /// assert_eq!(counter.most_common(3), [(&'a', 5), (&'b', 2), (&'r', 2)]);
+    /// assert_eq!(counter.most_common(10).len(), 5);
+    /// ```
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut counts: Vec<_> = self.iter().collect();
+        counts.sort_by_key(|&(_, count)| cmp::Reverse(count));
+        counts.truncate(n);
+        counts
+    }
+}
+
+impl<T, S> Counter<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Adds the item once.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let mut counter = Counter::new();
+    /// counter.add("a");
+    /// counter.add("a");
+    /// assert_eq!(counter.count("a"), 2);
+    /// ```
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    /// Adds the item `n` times.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let mut counter = Counter::new();
+    /// counter.add_n("a", 3);
+    /// counter.add_n("b", 0);
+    /// assert_eq!(counter.count("a"), 3);
+    /// assert!(!counter.contains("b"));
+    /// ```
+    pub fn add_n(&mut self, item: T, n: usize) {
+        if n == 0 {
+            return;
+        }
+        *self.map.entry(item).or_insert(0) += n;
+        self.total += n;
+    }
+
+    /// Removes the item once, and returns true if the counter had it.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let mut counter: Counter<_> = "aab".chars().collect();
+    /// assert!(counter.remove(&'a'));
+    /// assert_eq!(counter.count(&'a'), 1);
+    /// assert!(!counter.remove(&'c'));
+    /// ```
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_n(item, 1) == 1
+    }
+
+    /// Removes the item up to `n` times, and returns the number of times it
+    /// was removed. The item is dropped once its count reaches zero.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let mut counter: Counter<_> = "aaab".chars().collect();
+    /// assert_eq!(counter.remove_n(&'a', 2), 2);
+    /// assert_eq!(counter.remove_n(&'a', 2), 1);
+    /// assert!(!counter.contains(&'a'));
+    /// ```
+    pub fn remove_n<Q>(&mut self, item: &Q, n: usize) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let count = match self.map.get_mut(item) {
+            Some(count) => count,
+            None => return 0,
+        };
+        let removed = cmp::min(*count, n);
+        *count -= removed;
+        if *count == 0 {
+            self.map.remove(item);
+        }
+        self.total -= removed;
+        removed
+    }
+
+    /// Returns the number of times the item was added, or 0 if the counter
+    /// does not have it.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "aab".chars().collect();
+    /// assert_eq!(counter.count(&'a'), 2);
+    /// assert_eq!(counter.count(&'z'), 0);
+    /// ```
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(item).copied().unwrap_or(0)
+    }
+
+    /// Returns true if the counter has the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Counter;
+    ///
+    /// let counter: Counter<_> = "ab".chars().collect();
+    /// assert!(counter.contains(&'a'));
+    /// assert!(!counter.contains(&'c'));
+    /// ```
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(item)
+    }
+
+    /// Keeps the items whose count is positive after `f` changes it, dropping
+    /// the others.
+    fn update_counts<F>(&mut self, mut f: F)
+    where
+        S: Default,
+        F: FnMut(&T, usize) -> usize,
+    {
+        let map = mem::take(&mut self.map);
+        self.total = 0;
+        for (item, count) in map {
+            let count = f(&item, count);
+            self.add_n(item, count);
+        }
+    }
+}
+
+impl<T, S> Extend<T> for Counter<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T, S> FromIterator<T> for Counter<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::default();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T, S> AddAssign<&Counter<T, S>> for Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Adds the counts of the other counter.
+    fn add_assign(&mut self, other: &Counter<T, S>) {
+        for (item, count) in other {
+            self.add_n(item.clone(), count);
+        }
+    }
+}
+
+impl<T, S> SubAssign<&Counter<T, S>> for Counter<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Subtracts the counts of the other counter, dropping the items whose
+    /// count reaches zero.
+    fn sub_assign(&mut self, other: &Counter<T, S>) {
+        for (item, count) in other {
+            self.remove_n(item, count);
+        }
+    }
+}
+
+impl<T, S> BitAndAssign<&Counter<T, S>> for Counter<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    /// Keeps the smaller count of each item, dropping the items that are not
+    /// in the other counter.
+    fn bitand_assign(&mut self, other: &Counter<T, S>) {
+        self.update_counts(|item, count| cmp::min(count, other.count(item)));
+    }
+}
+
+impl<T, S> BitOrAssign<&Counter<T, S>> for Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Keeps the larger count of each item.
+    fn bitor_assign(&mut self, other: &Counter<T, S>) {
+        for (item, count) in other {
+            let extra = count.saturating_sub(self.count(item));
+            self.add_n(item.clone(), extra);
+        }
+    }
+}
+
+impl<T, S> Add for &Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, S>;
+
+    fn add(self, other: &Counter<T, S>) -> Counter<T, S> {
+        let mut counter = self.clone();
+        counter += other;
+        counter
+    }
+}
+
+impl<T, S> Sub for &Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, S>;
+
+    fn sub(self, other: &Counter<T, S>) -> Counter<T, S> {
+        let mut counter = self.clone();
+        counter -= other;
+        counter
+    }
+}
+
+impl<T, S> BitAnd for &Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Clone + Default,
+{
+    type Output = Counter<T, S>;
+
+    fn bitand(self, other: &Counter<T, S>) -> Counter<T, S> {
+        let mut counter = self.clone();
+        counter &= other;
+        counter
+    }
+}
+
+impl<T, S> BitOr for &Counter<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, S>;
+
+    fn bitor(self, other: &Counter<T, S>) -> Counter<T, S> {
+        let mut counter = self.clone();
+        counter |= other;
+        counter
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a Counter<T, S> {
+    type Item = (&'a T, usize);
+
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items and the counts of a [`Counter`].
+///
+/// This struct is created by [`Counter::iter()`]. See its documentation for
+/// more.
+///
+/// [`Counter`]: crate::containers::Counter
+/// [`Counter::iter()`]: crate::containers::Counter::iter
+#[derive(Debug)]
+pub struct Iter<'a, T, S> {
+    iter: linked_hash_map::Iter<'a, T, usize, S>,
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(item, &count)| (item, count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T, S> ExactSizeIterator for Iter<'a, T, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Counter;
+    use crate::containers::rng::SplitMix64;
+    use core::cmp;
+
+    fn random_counter(rng: &mut SplitMix64) -> (Counter<u64>, [usize; 8]) {
+        let mut counter: Counter<u64> = Counter::new();
+        let mut naive = [0; 8];
+        for _ in 0..20 {
+            let item = rng.next_u64() % 8;
+            counter.add(item);
+            naive[item as usize] += 1;
+        }
+        (counter, naive)
+    }
+
+    fn assert_counts(counter: &Counter<u64>, naive: &[usize; 8]) {
+        for (item, &count) in naive.iter().enumerate() {
+            assert_eq!(counter.count(&(item as u64)), count);
+        }
+        assert_eq!(counter.len(), naive.iter().filter(|&&c| c > 0).count());
+        assert_eq!(counter.total(), naive.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn arithmetic_matches_naive() {
+        let mut rng = SplitMix64::new(29);
+        for _ in 0..50 {
+            let (a, x) = random_counter(&mut rng);
+            let (b, y) = random_counter(&mut rng);
+            let combine = |f: fn(usize, usize) -> usize| {
+                let mut z = [0; 8];
+                for i in 0..8 {
+                    z[i] = f(x[i], y[i]);
+                }
+                z
+            };
+            assert_counts(&(&a + &b), &combine(|p, q| p + q));
+            assert_counts(&(&a - &b), &combine(usize::saturating_sub));
+            assert_counts(&(&a & &b), &combine(cmp::min));
+            assert_counts(&(&a | &b), &combine(cmp::max));
+        }
+    }
+
+    #[test]
+    fn most_common_is_sorted_and_stable() {
+        let counter: Counter<_> = "mississippi".chars().collect();
+        assert_eq!(
+            counter.most_common(4),
+            [(&'i', 4), (&'s', 4), (&'p', 2), (&'m', 1)]
+        );
+        assert!(counter.most_common(0).is_empty());
+    }
+}
@@ -0,0 +1,533 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::slice;
+
+use super::metric::Metric;
+
+/// The index of a missing node.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    item: T,
+    level: i32,
+    children: Vec<usize>,
+}
+
+/// Returns 2 to the power of the level, rounded to 0 or infinity when it
+/// does not fit in a float.
+fn pow2(level: i32) -> f64 {
+    if level < -1022 {
+        0.0
+    } else if level > 1023 {
+        f64::INFINITY
+    } else {
+        f64::from_bits(((level + 1023) as u64) << 52)
+    }
+}
+
+/// A cover tree, which finds the values closest to a query in any metric
+/// space, and adds values one at a time.
+///
+/// Every node has a level `i`, and its children are at level `i - 1`. The
+/// tree keeps two invariants, where the covering distance of a node at level
+/// `i` is `2^i`:
+///
+/// - Covering: a child is at most the covering distance of its parent away
+///   from it.
+/// - Separation: the children of a node at level `i` are more than `2^(i-1)`
+///   away from each other.
+///
+/// By the covering invariant, the descendants of a node at level `i` are
+/// less than `2^(i+1)` away from it, so a search skips the subtrees that
+/// cannot hold a value closer than those it found. By the separation
+/// invariant, a node only has a few children when the values have a low
+/// intrinsic dimension, whatever the dimension of the space they are in.
+/// The distance between values is given by the [`Metric`] `M`.
+///
+/// A value is inserted as a child of the deepest node that covers it. A
+/// value too far from the root to be covered becomes the new root, after
+/// the tree has been raised by repeatedly moving a leaf to the top. A value
+/// equal to one in the tree is added again, one level below it.
+///
+/// The nodes are kept in a vector and link to each other by index.
+///
+/// [`Metric`]: crate::containers::Metric
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{CoverTree, Euclidean};
+///
+/// let mut tree = CoverTree::<_, Euclidean>::new();
+/// for point in [[0.0, 0.0], [10.0, 0.0], [0.0, 3.0], [4.0, 4.0]] {
+///     tree.insert(point);
+/// }
+/// assert_eq!(tree.nearest(&[1.0, 3.0]), Some((&[0.0, 3.0], 1.0)));
+///
+/// let closest: Vec<_> =
+///     tree.k_nearest(&[5.0, 5.0], 2).into_iter().map(|(p, _)| *p).collect();
+/// assert_eq!(closest, [[4.0, 4.0], [0.0, 3.0]]);
+/// ```
+pub struct CoverTree<T, M> {
+    nodes: Vec<Node<T>>,
+    root: usize,
+    metric: PhantomData<M>,
+}
+
+impl<T: Clone, M> Clone for CoverTree<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            root: self.root,
+            metric: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, M> fmt::Debug for CoverTree<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, M> Default for CoverTree<T, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, M> CoverTree<T, M> {
+    /// Creates an empty CoverTree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Euclidean};
+    ///
+    /// let tree: CoverTree<[f64; 2], Euclidean> = CoverTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            metric: PhantomData,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Euclidean};
+    ///
+    /// let mut tree = CoverTree::<_, Euclidean>::new();
+    /// tree.insert([1.0]);
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the tree has no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Euclidean};
+    ///
+    /// let mut tree = CoverTree::<_, Euclidean>::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert([1.0]);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the values, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Levenshtein};
+    ///
+    /// let tree: CoverTree<_, Levenshtein> =
+    ///     vec!["a", "b"].into_iter().collect();
+    /// let mut words: Vec<_> = tree.iter().copied().collect();
+    /// words.sort();
+    /// assert_eq!(words, ["a", "b"]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.nodes.iter(),
+        }
+    }
+}
+
+impl<T, M: Metric<T>> CoverTree<T, M> {
+    /// Adds the value to the tree.
+    ///
+    /// This operation should compute in O(log(n)) time for values of a low
+    /// intrinsic dimension, and O(n) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Euclidean};
+    ///
+    /// let mut tree = CoverTree::<_, Euclidean>::new();
+    /// tree.insert([1.0, 2.0]);
+    /// tree.insert([1.0, 2.0]);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn insert(&mut self, item: T) {
+        let index = self.nodes.len();
+        if self.root == NIL {
+            self.nodes.push(Node {
+                item,
+                level: 0,
+                children: Vec::new(),
+            });
+            self.root = index;
+            return;
+        }
+        let mut d = M::distance(&self.nodes[self.root].item, &item);
+        if d > pow2(self.nodes[self.root].level) {
+            if self.nodes[self.root].children.is_empty() {
+                // A lone root covers more by simply moving up.
+                while d > pow2(self.nodes[self.root].level) {
+                    self.nodes[self.root].level += 1;
+                }
+            } else {
+                // A leaf is within twice the covering distance of the root,
+                // so it can cover the root one level above it.
+                while d > 2.0 * pow2(self.nodes[self.root].level) {
+                    let leaf = self.remove_leaf();
+                    self.nodes[leaf].level = self.nodes[self.root].level + 1;
+                    self.nodes[leaf].children.push(self.root);
+                    self.root = leaf;
+                    d = M::distance(&self.nodes[self.root].item, &item);
+                }
+                self.nodes.push(Node {
+                    item,
+                    level: self.nodes[self.root].level + 1,
+                    children: vec![self.root],
+                });
+                self.root = index;
+                return;
+            }
+        }
+        // The value goes under the deepest node that covers it, and is then
+        // more than a covering distance away from the children of its
+        // parent, as the separation needs.
+        let mut parent = self.root;
+        'descend: loop {
+            for &child in &self.nodes[parent].children {
+                let node = &self.nodes[child];
+                if M::distance(&node.item, &item) <= pow2(node.level) {
+                    parent = child;
+                    continue 'descend;
+                }
+            }
+            break;
+        }
+        let level = self.nodes[parent].level - 1;
+        self.nodes[parent].children.push(index);
+        self.nodes.push(Node {
+            item,
+            level,
+            children: Vec::new(),
+        });
+    }
+
+    /// Returns the value closest to the query and its distance, or None if
+    /// the tree is empty. Ties are broken arbitrarily.
+    ///
+    /// This operation should compute in O(log(n)) time for values of a low
+    /// intrinsic dimension, and O(n) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Levenshtein};
+    ///
+    /// let tree: CoverTree<_, Levenshtein> =
+    ///     vec!["kitten", "sitting", "mitten"].into_iter().collect();
+    /// assert_eq!(tree.nearest(&"smitten"), Some((&"mitten", 1.0)));
+    /// ```
+    pub fn nearest(&self, query: &T) -> Option<(&T, f64)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// Returns the `k` values closest to the query and their distances, from
+    /// the closest to the farthest. Fewer values are returned if the tree has
+    /// fewer than `k`.
+    ///
+    /// This operation should compute in O(k log(n)) time for values of a low
+    /// intrinsic dimension, and O(nk) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CoverTree, Euclidean};
+    ///
+    /// let tree: CoverTree<_, Euclidean> =
+    ///     (0..10).map(|i| [i as f64]).collect();
+    /// let closest: Vec<_> =
+    ///     tree.k_nearest(&[4.2], 3).iter().map(|(p, _)| p[0]).collect();
+    /// assert_eq!(closest, [4.0, 5.0, 3.0]);
+    /// ```
+    pub fn k_nearest(&self, query: &T, k: usize) -> Vec<(&T, f64)> {
+        let mut best = Vec::with_capacity(k);
+        if k > 0 && self.root != NIL {
+            let d = M::distance(query, &self.nodes[self.root].item);
+            self.search(self.root, d, query, k, &mut best);
+        }
+        best.into_iter()
+            .map(|(d, i)| (&self.nodes[i].item, d))
+            .collect()
+    }
+
+    /// Detaches a leaf from the tree, which must have more than one node,
+    /// and returns its index.
+    fn remove_leaf(&mut self) -> usize {
+        let mut parent = self.root;
+        loop {
+            let child = *self.nodes[parent].children.last().expect("Not leaf");
+            if self.nodes[child].children.is_empty() {
+                self.nodes[parent].children.pop();
+                return child;
+            }
+            parent = child;
+        }
+    }
+
+    /// Keeps the `k` closest values of the subtree, sorted by their distance
+    /// to the query, in `best`. The distance from the query to the root of
+    /// the subtree is `d`.
+    fn search(
+        &self,
+        i: usize,
+        d: f64,
+        query: &T,
+        k: usize,
+        best: &mut Vec<(f64, usize)>,
+    ) {
+        if best.len() < k || d < best[best.len() - 1].0 {
+            if best.len() == k {
+                best.pop();
+            }
+            let at = best.partition_point(|&(other, _)| other <= d);
+            best.insert(at, (d, i));
+        }
+        // The closest children are searched first, so that the farther ones
+        // are more likely to be skipped.
+        let mut children: Vec<_> = self.nodes[i]
+            .children
+            .iter()
+            .map(|&child| (M::distance(query, &self.nodes[child].item), child))
+            .collect();
+        children.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (d, child) in children {
+            // The descendants of the child are less than twice its covering
+            // distance away from it.
+            let reach = 2.0 * pow2(self.nodes[child].level);
+            if best.len() < k || d - reach < best[best.len() - 1].0 {
+                self.search(child, d, query, k, best);
+            }
+        }
+    }
+}
+
+impl<T, M: Metric<T>> FromIterator<T> for CoverTree<T, M> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T, M: Metric<T>> Extend<T> for CoverTree<T, M> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T, M> IntoIterator for &'a CoverTree<T, M> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a CoverTree.
+///
+/// This struct is created by [`CoverTree::iter()`]. See its documentation
+/// for more.
+///
+/// [`CoverTree::iter()`]: crate::containers::CoverTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    iter: slice::Iter<'a, Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|node| &node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::metric::{Euclidean, Levenshtein};
+    use crate::containers::rng::SplitMix64;
+    use alloc::string::String;
+
+    fn check_invariants<T, M: Metric<T>>(tree: &CoverTree<T, M>) {
+        let mut seen = 1;
+        let mut stack = vec![tree.root];
+        while let Some(i) = stack.pop() {
+            let node = &tree.nodes[i];
+            for (j, &a) in node.children.iter().enumerate() {
+                let child = &tree.nodes[a];
+                assert_eq!(child.level, node.level - 1);
+                let d = M::distance(&node.item, &child.item);
+                assert!(d <= pow2(node.level));
+                for &b in &node.children[..j] {
+                    let d = M::distance(&child.item, &tree.nodes[b].item);
+                    assert!(d > pow2(node.level - 1));
+                }
+                stack.push(a);
+            }
+            seen += node.children.len();
+        }
+        assert_eq!(seen, tree.len());
+    }
+
+    fn check<T: Clone, M: Metric<T>>(items: &[T], queries: &[T]) {
+        let mut tree = CoverTree::<T, M>::new();
+        for (n, item) in items.iter().enumerate() {
+            tree.insert(item.clone());
+            if n % 50 == 0 {
+                check_invariants(&tree);
+            }
+        }
+        check_invariants(&tree);
+        for query in queries {
+            let mut expected: Vec<_> =
+                items.iter().map(|x| M::distance(query, x)).collect();
+            expected.sort_by(f64::total_cmp);
+            for k in [0, 1, 5, items.len() + 1] {
+                let found: Vec<_> =
+                    tree.k_nearest(query, k).iter().map(|&(_, d)| d).collect();
+                assert_eq!(found, expected[..k.min(items.len())]);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_and_single_point_trees() {
+        let mut tree = CoverTree::<[f64; 2], Euclidean>::new();
+        assert_eq!(tree.nearest(&[0.0, 0.0]), None);
+        assert!(tree.k_nearest(&[0.0, 0.0], 3).is_empty());
+
+        tree.insert([3.0, 4.0]);
+        check_invariants(&tree);
+        assert_eq!(tree.nearest(&[0.0, 0.0]), Some((&[3.0, 4.0], 5.0)));
+        assert_eq!(tree.k_nearest(&[0.0, 0.0], 3).len(), 1);
+        check::<_, Euclidean>(&[[3.0, 4.0]], &[[0.0, 0.0], [3.0, 4.0]]);
+    }
+
+    #[test]
+    fn duplicate_points() {
+        // Every copy is at distance 0 from the others, so each one goes
+        // under the previous copy, a level deeper.
+        let points = vec![[1.0, 1.0]; 50];
+        let mut tree = CoverTree::<_, Euclidean>::new();
+        for &point in &points {
+            tree.insert(point);
+        }
+        check_invariants(&tree);
+        assert_eq!(tree.k_nearest(&[1.0, 1.0], 60).len(), 50);
+        assert_eq!(tree.nearest(&[0.0, 1.0]), Some((&[1.0, 1.0], 1.0)));
+
+        let mut mixed = points;
+        mixed.extend(vec![[5.0, 1.0]; 5]);
+        check::<_, Euclidean>(&mixed, &[[1.0, 1.0], [3.0, 1.0], [9.0, 9.0]]);
+    }
+
+    #[test]
+    fn raising_the_root() {
+        // A lone root moves up until it covers the new value.
+        let mut tree = CoverTree::<_, Euclidean>::new();
+        tree.insert([0.0]);
+        tree.insert([100.0]);
+        check_invariants(&tree);
+
+        // A root with children is covered by a leaf promoted above it, as
+        // many times as needed, and then by the new value itself.
+        for x in [1e4, 1e6, -1e9, 0.5] {
+            tree.insert([x]);
+            check_invariants(&tree);
+        }
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.nearest(&[-1e8]), Some((&[0.0], 1e8)));
+        check::<_, Euclidean>(
+            &[[0.0], [100.0], [1e4], [1e6], [-1e9], [0.5]],
+            &[[0.0], [2e6], [-2e9]],
+        );
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut rng = SplitMix64::new(11);
+        // Coordinates on a grid with far outliers, so that the root has to
+        // be raised, and repeated points.
+        let point = |rng: &mut SplitMix64| {
+            let scale = if rng.next_u64().is_multiple_of(20) {
+                1000.0
+            } else {
+                1.0
+            };
+            [0, 1, 2].map(|_| (rng.next_u64() % 8) as f64 * scale)
+        };
+        let points: Vec<_> = (0..400).map(|_| point(&mut rng)).collect();
+        let queries: Vec<_> = (0..50).map(|_| point(&mut rng)).collect();
+        check::<_, Euclidean>(&points, &queries);
+
+        let word = |rng: &mut SplitMix64| -> String {
+            let len = rng.next_u64() % 6;
+            (0..len)
+                .map(|_| (b'a' + (rng.next_u64() % 3) as u8) as char)
+                .collect()
+        };
+        let words: Vec<_> = (0..200).map(|_| word(&mut rng)).collect();
+        let queries: Vec<_> = (0..50).map(|_| word(&mut rng)).collect();
+        check::<_, Levenshtein>(&words, &queries);
+    }
+}
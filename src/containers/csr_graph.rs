@@ -0,0 +1,321 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Zip;
+use core::slice;
+
+use super::graph::Graph;
+
+/// An immutable directed graph in compressed sparse row form, where the
+/// edges that leave each node are next to each other in memory.
+///
+/// The nodes are numbered from 0. The targets of all the edges are kept in
+/// one array, sorted by their sources, with their data in another array in
+/// the same order, and the edges that leave node `i` are those from
+/// `offsets[i]` to `offsets[i + 1]`. Looking up the edges of a node takes
+/// O(1) time, and walking through them reads contiguous memory, but the
+/// graph cannot change after it is built.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::CsrGraph;
+///
+/// let edges = vec![(0, 1, 'a'), (2, 3, 'b'), (0, 2, 'c')];
+/// let graph = CsrGraph::from_edges(4, edges);
+/// assert_eq!(graph.neighbors(0), [1, 2]);
+/// assert_eq!(graph.edge_weights(0), ['a', 'c']);
+/// assert!(graph.neighbors(1).is_empty());
+/// assert_eq!(graph.edge_count(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrGraph<E> {
+    // The edges of node `i` are at `offsets[i]..offsets[i + 1]` in `targets`
+    // and `weights`.
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<E>,
+}
+
+impl<E> CsrGraph<E> {
+    /// Creates a CsrGraph with `node_count` nodes and the edges, given by
+    /// their sources, their targets and their data. The edges that leave a
+    /// node keep the order they are given in.
+    ///
+    /// This operation should compute in O(n + e) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source or the target of an edge is not less than
+    /// `node_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let edges = vec![(2, 0, 1), (0, 1, 2), (2, 1, 3)];
+    /// let graph = CsrGraph::from_edges(3, edges);
+    /// assert_eq!(graph.neighbors(2), [0, 1]);
+    /// assert_eq!(graph.edge_weights(2), [1, 3]);
+    /// ```
+    pub fn from_edges<I>(node_count: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, E)>,
+    {
+        let edges: Vec<_> = edges.into_iter().collect();
+        let mut offsets = vec![0; node_count + 1];
+        for &(source, target, _) in &edges {
+            assert!(
+                source < node_count && target < node_count,
+                "Out of bounds access"
+            );
+            offsets[source + 1] += 1;
+        }
+        for i in 0..node_count {
+            offsets[i + 1] += offsets[i];
+        }
+        // A counting sort by source, which places each edge at the next free
+        // position of its source.
+        let mut next = offsets.clone();
+        let mut order = vec![0; edges.len()];
+        for (i, &(source, _, _)) in edges.iter().enumerate() {
+            order[i] = next[source];
+            next[source] += 1;
+        }
+        let mut targets = vec![0; edges.len()];
+        let mut slots: Vec<Option<E>> = edges.iter().map(|_| None).collect();
+        for ((_, target, weight), at) in edges.into_iter().zip(order) {
+            targets[at] = target;
+            slots[at] = Some(weight);
+        }
+        Self {
+            offsets,
+            targets,
+            weights: slots.into_iter().map(Option::unwrap).collect(),
+        }
+    }
+
+    /// Returns the number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::<()>::from_edges(5, vec![]);
+    /// assert_eq!(graph.node_count(), 5);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Returns the number of edges in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(2, vec![(0, 1, ()), (1, 0, ())]);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns the number of edges that leave the node.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not less than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(2, vec![(0, 1, ()), (0, 0, ())]);
+    /// assert_eq!(graph.out_degree(0), 2);
+    /// assert_eq!(graph.out_degree(1), 0);
+    /// ```
+    pub fn out_degree(&self, node: usize) -> usize {
+        self.offsets[node + 1] - self.offsets[node]
+    }
+
+    /// Returns the targets of the edges that leave the node.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not less than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(3, vec![(0, 2, ()), (0, 1, ())]);
+    /// assert_eq!(graph.neighbors(0), [2, 1]);
+    /// ```
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Returns the data of the edges that leave the node, in the order of
+    /// [`CsrGraph::neighbors()`].
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// [`CsrGraph::neighbors()`]: crate::containers::CsrGraph::neighbors
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not less than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(3, vec![(0, 2, 5), (0, 1, 7)]);
+    /// assert_eq!(graph.edge_weights(0), [5, 7]);
+    /// ```
+    pub fn edge_weights(&self, node: usize) -> &[E] {
+        &self.weights[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Returns an iterator over the targets and the data of the edges that
+    /// leave the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not less than the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(3, vec![(0, 2, 5), (0, 1, 7)]);
+    /// let edges: Vec<_> = graph.edges(0).collect();
+    /// assert_eq!(edges, [(2, &5), (1, &7)]);
+    /// ```
+    pub fn edges(&self, node: usize) -> Edges<'_, E> {
+        Edges {
+            iter: self.neighbors(node).iter().zip(self.edge_weights(node)),
+        }
+    }
+}
+
+/// An iterator over the targets and the data of the edges of a node of a
+/// [`CsrGraph`].
+///
+/// This struct is created by [`CsrGraph::edges()`]. See its documentation
+/// for more.
+///
+/// [`CsrGraph`]: crate::containers::CsrGraph
+/// [`CsrGraph::edges()`]: crate::containers::CsrGraph::edges
+#[derive(Debug)]
+pub struct Edges<'a, E> {
+    iter: Zip<slice::Iter<'a, usize>, slice::Iter<'a, E>>,
+}
+
+impl<'a, E> Iterator for Edges<'a, E> {
+    type Item = (usize, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(&target, weight)| (target, weight))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Edges<'a, E> {}
+
+impl<N, E: Clone> From<&Graph<N, E>> for CsrGraph<E> {
+    /// Creates a CsrGraph with the edges of the graph, where node `i` is the
+    /// node of the graph whose index is `i`. The indices of removed nodes
+    /// become nodes without edges, and an undirected edge becomes an edge in
+    /// each direction, or one edge if it joins a node to itself.
+    fn from(graph: &Graph<N, E>) -> Self {
+        let edges = graph.node_indices().flat_map(|node| {
+            graph.edges(node).map(move |(_, target, weight)| {
+                (node.index(), target.index(), weight.clone())
+            })
+        });
+        Self::from_edges(graph.node_bound(), edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_adjacency_lists() {
+        let mut rng = SplitMix64::new(41);
+        for directed in [true, false] {
+            let mut graph = if directed {
+                Graph::new_directed()
+            } else {
+                Graph::new_undirected()
+            };
+            let nodes: Vec<_> = (0..50).map(|i| graph.add_node(i)).collect();
+            for step in 0..300 {
+                let a = nodes[(rng.next_u64() % 50) as usize];
+                let b = nodes[(rng.next_u64() % 50) as usize];
+                graph.add_edge(a, b, step);
+            }
+            graph.remove_node(nodes[7]);
+            let csr = CsrGraph::from(&graph);
+            assert_eq!(csr.node_count(), 50);
+            for i in 0..50 {
+                let mut expected: Vec<_> = graph
+                    .node_indices()
+                    .find(|node| node.index() == i)
+                    .map(|node| {
+                        graph
+                            .edges(node)
+                            .map(|(_, next, &w)| (next.index(), w))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut found: Vec<_> =
+                    csr.edges(i).map(|(next, &w)| (next, w)).collect();
+                assert_eq!(csr.out_degree(i), found.len());
+                expected.sort_unstable();
+                found.sort_unstable();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_order_of_edges() {
+        let edges: Vec<_> =
+            (0..100).map(|i| (i % 7, (i * 3) % 10, i)).collect();
+        let graph = CsrGraph::from_edges(10, edges.clone());
+        for node in 0..10 {
+            let expected: Vec<_> = edges
+                .iter()
+                .filter(|&&(source, _, _)| source == node)
+                .map(|&(_, target, w)| (target, w))
+                .collect();
+            let found: Vec<_> =
+                graph.edges(node).map(|(t, &w)| (t, w)).collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn rejects_unknown_nodes() {
+        CsrGraph::from_edges(2, vec![(0, 2, ())]);
+    }
+}
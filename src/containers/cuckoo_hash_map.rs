@@ -0,0 +1,747 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::{Chain, FromIterator};
+use core::mem;
+use core::slice;
+
+use super::linked_hash_map::DefaultHashBuilder;
+use super::rng::SplitMix64;
+
+/// The number of slots of each table when the map first allocates.
+const MIN_TABLE_LEN: usize = 8;
+
+/// The number of entries that can wait in the stash for a slot.
+const STASH_CAPACITY: usize = 4;
+
+/// The number of failed attempts to place all entries with new hash
+/// functions after which the tables are made larger.
+const ATTEMPTS_BEFORE_GROWING: usize = 4;
+
+/// A slot of a table.
+type Slot<K, V> = Option<(K, V)>;
+
+/// The slots of both tables, one after the other.
+type Slots<'a, K, V> =
+    Chain<slice::Iter<'a, Slot<K, V>>, slice::Iter<'a, Slot<K, V>>>;
+
+/// A hash map where every key can only be in one of two slots, which makes
+/// lookups take constant time in the worst case.
+///
+/// The map has two tables of the same size, each with its own hash function,
+/// and a key is either at its slot in the first table or at its slot in the
+/// second one. A lookup checks both slots. An insertion puts the entry in its
+/// slot of the first table, and if that slot is taken, displaces the entry
+/// there to its slot in the other table, which may in turn displace another
+/// entry, and so on until an entry lands in an empty slot. When this goes on
+/// for too long, the displacements are likely to be going around a cycle,
+/// and the homeless entry is put in a small stash that lookups also check.
+/// When the stash is full, the map picks new hash functions and rehashes all
+/// of its entries, and after a few such attempts, it doubles its tables.
+///
+/// The tables are kept at most half full, above which cycles quickly become
+/// frequent. The hash functions are the hash builder `S` applied to the key
+/// and a seed of the table, so new functions are new seeds.
+///
+/// [`CuckooHashMap::stats()`] counts the displacements and rehashes, which
+/// can be compared with the chains of a [`LinkedHashMap`] for the same keys.
+///
+/// [`CuckooHashMap::stats()`]: CuckooHashMap::stats
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::CuckooHashMap;
+///
+/// let mut ages = CuckooHashMap::new();
+/// ages.insert("alice", 31);
+/// ages.insert("bob", 27);
+/// assert_eq!(ages.insert("alice", 32), Some(31));
+///
+/// assert_eq!(ages.get("alice"), Some(&32));
+/// assert_eq!(ages.remove("bob"), Some(27));
+/// assert!(!ages.contains_key("bob"));
+/// assert_eq!(ages.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct CuckooHashMap<K, V, S = DefaultHashBuilder> {
+    tables: [Vec<Slot<K, V>>; 2],
+    seeds: [u64; 2],
+    stash: Vec<(K, V)>,
+    len: usize,
+    hasher_builder: S,
+    rng: SplitMix64,
+    displacements: usize,
+    longest_displacement: usize,
+    cycles: usize,
+    rehashes: usize,
+}
+
+/// A snapshot of the internal layout of a [`CuckooHashMap`], and counts of
+/// the work its insertions did.
+///
+/// This struct is created by [`CuckooHashMap::stats()`]. See its
+/// documentation for more.
+///
+/// [`CuckooHashMap`]: crate::containers::CuckooHashMap
+/// [`CuckooHashMap::stats()`]: crate::containers::CuckooHashMap::stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CuckooHashMapStats {
+    /// Number of slots in both tables.
+    pub slots: usize,
+    /// Number of entries, including those in the stash.
+    pub entries: usize,
+    /// Number of entries in the stash.
+    pub stashed: usize,
+    /// Number of entries that insertions moved to their other slot,
+    /// including while rehashing.
+    pub displacements: usize,
+    /// Largest number of entries that a single placement moved.
+    pub longest_displacement: usize,
+    /// Number of times an entry could not be placed with the stash full,
+    /// which made the map pick new hash functions.
+    pub cycles: usize,
+    /// Number of times all entries were rehashed, because of a cycle or
+    /// because the tables grew.
+    pub rehashes: usize,
+}
+
+impl CuckooHashMapStats {
+    /// Returns the number of entries per slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// assert_eq!(map.stats().load_factor(), 0.0);
+    ///
+    /// map.insert("foo", 42);
+    /// assert!(map.stats().load_factor() > 0.0);
+    /// ```
+    pub fn load_factor(&self) -> f64 {
+        if self.slots == 0 {
+            return 0.0;
+        }
+        self.entries as f64 / self.slots as f64
+    }
+}
+
+impl<K, V, S> Default for CuckooHashMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<K, V> CuckooHashMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty CuckooHashMap.
+    ///
+    /// The map does not allocate until it is first inserted into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let map: CuckooHashMap<&str, i32> = CuckooHashMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<K, V, S> CuckooHashMap<K, V, S> {
+    /// Creates an empty CuckooHashMap that uses the given hash builder to
+    /// hash the keys.
+    ///
+    /// The map does not allocate until it is first inserted into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CuckooHashMap, FnvBuildHasher};
+    ///
+    /// let mut map = CuckooHashMap::with_hasher(FnvBuildHasher::default());
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        let mut rng = SplitMix64::new(SplitMix64::DEFAULT_SEED);
+        Self {
+            tables: [Vec::new(), Vec::new()],
+            seeds: [rng.next_u64(), rng.next_u64()],
+            stash: Vec::new(),
+            len: 0,
+            hasher_builder,
+            rng,
+            displacements: 0,
+            longest_displacement: 0,
+            cycles: 0,
+            rehashes: 0,
+        }
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{CuckooHashMap, FnvBuildHasher};
+    ///
+    /// let map: CuckooHashMap<i32, i32, _> =
+    ///     CuckooHashMap::with_hasher(FnvBuildHasher::default());
+    /// let _: &FnvBuildHasher = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries the map can hold before its tables
+    /// grow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// assert_eq!(map.capacity(), 0);
+    /// map.insert(1, 1);
+    /// assert!(map.capacity() >= 1);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.tables[0].len()
+    }
+
+    /// Returns an iterator over the entries of the map, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// let mut entries: Vec<_> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, [(&"a", &1), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.tables[0].iter().chain(self.tables[1].iter()),
+            stash: self.stash.iter(),
+            len: self.len,
+        }
+    }
+
+    /// Returns a snapshot of the map's internal layout, and counts of the
+    /// work its insertions did.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// for i in 0..1000 {
+    ///     map.insert(i, i);
+    /// }
+    ///
+    /// let stats = map.stats();
+    /// assert_eq!(stats.entries, 1000);
+    /// assert!(stats.load_factor() <= 0.5);
+    /// assert!(stats.displacements > 0);
+    /// assert!(stats.rehashes > 0);
+    /// ```
+    pub fn stats(&self) -> CuckooHashMapStats {
+        CuckooHashMapStats {
+            slots: self.tables[0].len() + self.tables[1].len(),
+            entries: self.len,
+            stashed: self.stash.len(),
+            displacements: self.displacements,
+            longest_displacement: self.longest_displacement,
+            cycles: self.cycles,
+            rehashes: self.rehashes,
+        }
+    }
+
+    /// Removes all entries from the map, and keeps its tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for table in &mut self.tables {
+            table.iter_mut().for_each(|slot| *slot = None);
+        }
+        self.stash.clear();
+        self.len = 0;
+    }
+}
+
+impl<K, V, S> CuckooHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, [`None`] is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the
+    /// old value is returned. The key is not updated.
+    ///
+    /// This operation should compute in expected O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.insert(37, "b"), Some("a"));
+    /// assert_eq!(map.get(&37), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(mem::replace(old, value));
+        }
+        // The tables are kept at most half full.
+        if self.len + 1 > self.capacity() {
+            let len = (2 * self.capacity()).max(MIN_TABLE_LEN);
+            self.rebuild(len, Vec::new());
+        }
+        if let Err(entry) = self.place((key, value)) {
+            let len = self.capacity();
+            self.cycles += 1;
+            self.seeds = [self.rng.next_u64(), self.rng.next_u64()];
+            self.rebuild(len, alloc::vec![entry]);
+        }
+        self.len += 1;
+        None
+    }
+
+    /// Returns a reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (table, i) = self.find(key)?;
+        let (_, value) = match table {
+            Some(table) => self.tables[table][i].as_ref()?,
+            None => &self.stash[i],
+        };
+        Some(value)
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (table, i) = self.find(key)?;
+        let (_, value) = match table {
+            Some(table) => self.tables[table][i].as_mut()?,
+            None => &mut self.stash[i],
+        };
+        Some(value)
+    }
+
+    /// Returns true if the map has the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes a key from the map, returning its value if the key was in the
+    /// map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::CuckooHashMap;
+    ///
+    /// let mut map = CuckooHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (table, i) = self.find(key)?;
+        let (_, value) = match table {
+            Some(table) => self.tables[table][i].take()?,
+            None => self.stash.swap_remove(i),
+        };
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns the slot of the key in the table.
+    fn index<Q>(&self, table: usize, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        let hash = self.hasher_builder.hash_one((self.seeds[table], key));
+        hash as usize & (self.tables[table].len() - 1)
+    }
+
+    /// Returns the table and the slot of the key, or None and its position
+    /// in the stash, or None if the map does not have it.
+    fn find<Q>(&self, key: &Q) -> Option<(Option<usize>, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.capacity() > 0 {
+            for table in 0..2 {
+                let i = self.index(table, key);
+                if let Some((k, _)) = &self.tables[table][i] {
+                    if k.borrow() == key {
+                        return Some((Some(table), i));
+                    }
+                }
+            }
+        }
+        let i = self.stash.iter().position(|(k, _)| k.borrow() == key)?;
+        Some((None, i))
+    }
+
+    /// Puts an entry whose key is not in the map in one of its slots, moving
+    /// the entries in its way, or in the stash, and returns the entry left
+    /// without a slot if the stash is full.
+    fn place(&mut self, mut entry: (K, V)) -> Result<(), (K, V)> {
+        // A cycle shows after about as many displacements as there are
+        // levels in a balanced tree of the slots.
+        let limit = 2 * self.capacity().trailing_zeros() as usize + 4;
+        let mut table = 0;
+        for moved in 0..=limit {
+            let i = self.index(table, &entry.0);
+            match self.tables[table][i].replace(entry) {
+                None => {
+                    self.displacements += moved;
+                    self.longest_displacement =
+                        self.longest_displacement.max(moved);
+                    return Ok(());
+                }
+                Some(displaced) => entry = displaced,
+            }
+            table ^= 1;
+        }
+        self.displacements += limit + 1;
+        self.longest_displacement = self.longest_displacement.max(limit + 1);
+        if self.stash.len() < STASH_CAPACITY {
+            self.stash.push(entry);
+            return Ok(());
+        }
+        Err(entry)
+    }
+
+    /// Rehashes all entries, and the given ones that are not in the map,
+    /// into tables of the given length, picking new hash functions until
+    /// they all have a place.
+    fn rebuild(&mut self, mut len: usize, mut entries: Vec<(K, V)>) {
+        let mut attempts = 0;
+        loop {
+            self.rehashes += 1;
+            for table in &mut self.tables {
+                entries.extend(table.drain(..).flatten());
+            }
+            entries.append(&mut self.stash);
+            if attempts > 0 {
+                self.seeds = [self.rng.next_u64(), self.rng.next_u64()];
+                if attempts % ATTEMPTS_BEFORE_GROWING == 0 {
+                    len *= 2;
+                }
+            }
+            for table in &mut self.tables {
+                table.resize_with(len, || None);
+            }
+            attempts += 1;
+            let mut failed = None;
+            while let Some(entry) = entries.pop() {
+                if let Err(entry) = self.place(entry) {
+                    failed = Some(entry);
+                    break;
+                }
+            }
+            match failed {
+                None => return,
+                Some(entry) => {
+                    self.cycles += 1;
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for CuckooHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CuckooHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a CuckooHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a CuckooHashMap.
+///
+/// This struct is created by [`CuckooHashMap::iter()`]. See its
+/// documentation for more.
+///
+/// [`CuckooHashMap::iter()`]: crate::containers::CuckooHashMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    slots: Slots<'a, K, V>,
+    stash: slice::Iter<'a, (K, V)>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self
+            .slots
+            .by_ref()
+            .flatten()
+            .next()
+            .or_else(|| self.stash.next())?;
+        self.len -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::fnv::FnvBuildHasher;
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+
+    /// A hasher that keeps only a few values of the hash, so that keys often
+    /// share both of their slots.
+    #[derive(Debug, Clone)]
+    struct FewBits(u64);
+
+    impl BuildHasher for FewBits {
+        type Hasher = FewBitsHasher;
+
+        fn build_hasher(&self) -> FewBitsHasher {
+            FewBitsHasher(FnvBuildHasher::default().build_hasher(), self.0)
+        }
+    }
+
+    struct FewBitsHasher(crate::containers::FnvHasher, u64);
+
+    impl Hasher for FewBitsHasher {
+        fn finish(&self) -> u64 {
+            (self.0.finish() >> 32) % self.1
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.write(bytes);
+        }
+    }
+
+    #[test]
+    fn matches_hash_map() {
+        let mut map = CuckooHashMap::with_hasher(FewBits(32));
+        let mut expected = HashMap::new();
+        let mut rng = SplitMix64::new(23);
+        for _ in 0..20_000 {
+            let key = rng.next_u64() % 30;
+            match rng.next_u64() % 4 {
+                0 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                1 => assert_eq!(map.get(&key), expected.get(&key)),
+                _ => {
+                    let value = rng.next_u64();
+                    assert_eq!(
+                        map.insert(key, value),
+                        expected.insert(key, value)
+                    );
+                }
+            }
+            assert_eq!(map.len(), expected.len());
+        }
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable();
+        let mut expected: Vec<_> = expected.iter().collect();
+        expected.sort_unstable();
+        assert_eq!(entries, expected);
+
+        let stats = map.stats();
+        assert!(stats.displacements > 0);
+        assert!(stats.stashed <= STASH_CAPACITY);
+        assert!(stats.load_factor() <= 0.5);
+    }
+
+    #[test]
+    fn rehashes_on_cycles() {
+        // Only eight slots can be told apart, so the last entries fit only
+        // if the hash functions leave no slot empty and the stash is full.
+        let mut map = CuckooHashMap::with_hasher(FewBits(4));
+        let mut rng = SplitMix64::new(12);
+        let keys: Vec<_> = (0..12).map(|_| rng.next_u64()).collect();
+        for &key in &keys {
+            map.insert(key, key);
+        }
+        for key in &keys {
+            assert_eq!(map.get(key), Some(key));
+        }
+        let stats = map.stats();
+        assert_eq!(stats.entries, 12);
+        assert_eq!(stats.stashed, STASH_CAPACITY);
+        assert!(stats.cycles > 0);
+        assert!(stats.rehashes > stats.cycles);
+    }
+}
@@ -0,0 +1,580 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::array_deque::ArrayDeque;
+use super::graph::{EdgeIndex, Graph, NodeIndex};
+use super::ops::{Monoid, Sum};
+
+/// The error of adding an edge to a [`Dag`] that would make a cycle.
+///
+/// This struct is returned by [`Dag::add_edge()`]. See its documentation for
+/// more.
+///
+/// [`Dag`]: crate::containers::Dag
+/// [`Dag::add_edge()`]: crate::containers::Dag::add_edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WouldCycle<E> {
+    weight: E,
+    cycle: Vec<NodeIndex>,
+}
+
+impl<E> WouldCycle<E> {
+    /// Returns the nodes of the cycle the edge would make, starting from its
+    /// target. Each node has an edge to the next one, and the last node is
+    /// the source of the rejected edge, which would close the cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// dag.add_edge(a, b, ()).unwrap();
+    /// let err = dag.add_edge(b, a, ()).unwrap_err();
+    /// assert_eq!(err.cycle(), [a, b]);
+    /// ```
+    pub fn cycle(&self) -> &[NodeIndex] {
+        &self.cycle
+    }
+
+    /// Returns the data of the rejected edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let err = dag.add_edge(a, a, "loop").unwrap_err();
+    /// assert_eq!(err.into_weight(), "loop");
+    /// ```
+    pub fn into_weight(self) -> E {
+        self.weight
+    }
+}
+
+/// A directed acyclic graph, which refuses the edges that would make a
+/// cycle.
+///
+/// The nodes and the edges are kept in a directed [`Graph`]. Before adding
+/// an edge, a breadth-first search from its target looks for its source, and
+/// the edge is refused if the source is found, with the path that was found
+/// as the cycle. The topological order is computed with Kahn's algorithm,
+/// which repeatedly takes a node that no remaining edge enters.
+///
+/// [`Graph`]: crate::containers::Graph
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Dag;
+///
+/// let mut dag = Dag::new();
+/// let std = dag.add_node("std");
+/// let alloc = dag.add_node("alloc");
+/// let core = dag.add_node("core");
+/// dag.add_edge(core, alloc, 1).unwrap();
+/// dag.add_edge(alloc, std, 2).unwrap();
+/// dag.add_edge(core, std, 1).unwrap();
+/// assert!(dag.add_edge(std, core, 1).is_err());
+///
+/// let order: Vec<_> = dag.topological_order().map(|n| dag[n]).collect();
+/// assert_eq!(order, ["core", "alloc", "std"]);
+/// assert_eq!(dag.longest_path(), Some((3, vec![core, alloc, std])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dag<N, E> {
+    graph: Graph<N, E>,
+}
+
+impl<N, E> Default for Dag<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, E> Dag<N, E> {
+    /// Creates an empty Dag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let dag: Dag<(), ()> = Dag::new();
+    /// assert_eq!(dag.node_count(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            graph: Graph::new_directed(),
+        }
+    }
+
+    /// Returns the directed graph of the nodes and the edges, to query it
+    /// with the methods of [`Graph`].
+    ///
+    /// [`Graph`]: crate::containers::Graph
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// dag.add_edge(a, b, ()).unwrap();
+    /// assert_eq!(dag.graph().neighbors(a).collect::<Vec<_>>(), [b]);
+    /// ```
+    pub fn graph(&self) -> &Graph<N, E> {
+        &self.graph
+    }
+
+    /// Returns the number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag: Dag<_, ()> = Dag::new();
+    /// dag.add_node('a');
+    /// assert_eq!(dag.node_count(), 1);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Returns the number of edges in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// dag.add_edge(a, b, ()).unwrap();
+    /// assert_eq!(dag.edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Adds a node with the data to the graph and returns its index.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag: Dag<_, ()> = Dag::new();
+    /// let a = dag.add_node('a');
+    /// assert_eq!(dag[a], 'a');
+    /// ```
+    pub fn add_node(&mut self, weight: N) -> NodeIndex {
+        self.graph.add_node(weight)
+    }
+
+    /// Adds an edge with the data from the source to the target and returns
+    /// its index, unless the source can be reached from the target.
+    ///
+    /// This operation should compute in O(n + e) time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the cycle the edge would make, along with its data, if the
+    /// source can be reached from the target. An edge from a node to itself
+    /// is a cycle of one node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source or the target is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// let c = dag.add_node('c');
+    /// dag.add_edge(a, b, ()).unwrap();
+    /// dag.add_edge(b, c, ()).unwrap();
+    /// assert_eq!(dag.add_edge(c, a, ()).unwrap_err().cycle(), [a, b, c]);
+    /// assert!(dag.add_edge(a, c, ()).is_ok());
+    /// ```
+    pub fn add_edge(
+        &mut self,
+        source: NodeIndex,
+        target: NodeIndex,
+        weight: E,
+    ) -> Result<EdgeIndex, WouldCycle<E>> {
+        assert!(
+            self.graph.contains_node(source)
+                && self.graph.contains_node(target),
+            "Invalid node"
+        );
+        match self.find_path(target, source) {
+            Some(cycle) => Err(WouldCycle { weight, cycle }),
+            None => Ok(self.graph.add_edge(source, target, weight)),
+        }
+    }
+
+    /// Removes the node and all its edges from the graph, and returns its
+    /// data, or None if it is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// dag.add_edge(a, b, ()).unwrap();
+    /// assert_eq!(dag.remove_node(b), Some('b'));
+    /// assert_eq!(dag.edge_count(), 0);
+    /// ```
+    pub fn remove_node(&mut self, node: NodeIndex) -> Option<N> {
+        self.graph.remove_node(node)
+    }
+
+    /// Removes the edge from the graph and returns its data, or None if it is
+    /// not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let a = dag.add_node('a');
+    /// let b = dag.add_node('b');
+    /// let ab = dag.add_edge(a, b, 1).unwrap();
+    /// assert_eq!(dag.remove_edge(ab), Some(1));
+    /// assert!(dag.add_edge(b, a, 2).is_ok());
+    /// ```
+    pub fn remove_edge(&mut self, edge: EdgeIndex) -> Option<E> {
+        self.graph.remove_edge(edge)
+    }
+
+    /// Returns an iterator over the nodes in a topological order, where
+    /// every edge goes from a node to a later node. The nodes are ordered as
+    /// the iterator advances.
+    ///
+    /// This operation should compute in O(n + e) time to go through all the
+    /// nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let shirt = dag.add_node("shirt");
+    /// let tie = dag.add_node("tie");
+    /// let jacket = dag.add_node("jacket");
+    /// dag.add_edge(tie, jacket, ()).unwrap();
+    /// dag.add_edge(shirt, tie, ()).unwrap();
+    /// let order: Vec<_> = dag.topological_order().map(|n| dag[n]).collect();
+    /// assert_eq!(order, ["shirt", "tie", "jacket"]);
+    /// ```
+    pub fn topological_order(&self) -> Topo<'_, N, E> {
+        let mut in_degree = vec![0; self.graph.node_bound()];
+        let mut ready = ArrayDeque::new();
+        for node in self.graph.node_indices() {
+            in_degree[node.index()] = self.graph.incoming_edges(node).len();
+            if in_degree[node.index()] == 0 {
+                ready.push_back(node);
+            }
+        }
+        Topo {
+            graph: &self.graph,
+            in_degree,
+            ready,
+            len: self.graph.node_count(),
+        }
+    }
+
+    /// Returns a breadth-first path from the start to the end, both
+    /// included.
+    fn find_path(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+    ) -> Option<Vec<NodeIndex>> {
+        let mut parents = vec![None; self.graph.node_bound()];
+        let mut queue = ArrayDeque::new();
+        queue.push_back(start);
+        parents[start.index()] = Some(start);
+        while let Some(node) = queue.pop_front() {
+            if node == end {
+                let mut path = vec![end];
+                let mut node = end;
+                while node != start {
+                    node = parents[node.index()].unwrap();
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.graph.neighbors(node) {
+                if parents[next.index()].is_none() {
+                    parents[next.index()] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<N, E> Dag<N, E>
+where
+    E: Clone + Ord,
+    Sum: Monoid<E>,
+{
+    /// Returns the length of the longest path of the graph, where the data
+    /// of an edge is its length, with the nodes of the path. Returns None if
+    /// the graph has no nodes.
+    ///
+    /// The longest path that ends at each node is found by going through the
+    /// nodes in topological order, so that the paths to a node are known
+    /// before the edges that leave it are followed. A path can be a single
+    /// node, whose length is zero.
+    ///
+    /// This operation should compute in O(n + e) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Dag;
+    ///
+    /// let mut dag = Dag::new();
+    /// let design = dag.add_node("design");
+    /// let build = dag.add_node("build");
+    /// let docs = dag.add_node("docs");
+    /// let ship = dag.add_node("ship");
+    /// dag.add_edge(design, build, 5).unwrap();
+    /// dag.add_edge(design, docs, 2).unwrap();
+    /// dag.add_edge(build, ship, 3).unwrap();
+    /// dag.add_edge(docs, ship, 1).unwrap();
+    /// let (length, path) = dag.longest_path().unwrap();
+    /// assert_eq!(length, 8);
+    /// assert_eq!(path, [design, build, ship]);
+    /// ```
+    pub fn longest_path(&self) -> Option<(E, Vec<NodeIndex>)> {
+        let bound = self.graph.node_bound();
+        let mut lengths: Vec<Option<E>> = vec![None; bound];
+        let mut predecessors = vec![None; bound];
+        let mut best: Option<(E, NodeIndex)> = None;
+        for node in self.topological_order() {
+            // A path that starts at the node is better than the paths that
+            // reach it with a negative length.
+            let zero = Sum::identity();
+            let length = match lengths[node.index()].take() {
+                Some(length) if length > zero => length,
+                _ => {
+                    predecessors[node.index()] = None;
+                    zero
+                }
+            };
+            for (_, next, weight) in self.graph.edges(node) {
+                let candidate = Sum::combine(&length, weight);
+                let longer = match &lengths[next.index()] {
+                    Some(known) => candidate > *known,
+                    None => true,
+                };
+                if longer {
+                    lengths[next.index()] = Some(candidate);
+                    predecessors[next.index()] = Some(node);
+                }
+            }
+            if best.as_ref().is_none_or(|(known, _)| length > *known) {
+                best = Some((length, node));
+            }
+        }
+        let (length, end) = best?;
+        let mut path = vec![end];
+        let mut node = end;
+        while let Some(previous) = predecessors[node.index()] {
+            path.push(previous);
+            node = previous;
+        }
+        path.reverse();
+        Some((length, path))
+    }
+}
+
+impl<N, E> core::ops::Index<NodeIndex> for Dag<N, E> {
+    type Output = N;
+
+    fn index(&self, node: NodeIndex) -> &Self::Output {
+        &self.graph[node]
+    }
+}
+
+impl<N, E> core::ops::IndexMut<NodeIndex> for Dag<N, E> {
+    fn index_mut(&mut self, node: NodeIndex) -> &mut Self::Output {
+        &mut self.graph[node]
+    }
+}
+
+impl<N, E> core::ops::Index<EdgeIndex> for Dag<N, E> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeIndex) -> &Self::Output {
+        &self.graph[edge]
+    }
+}
+
+impl<N, E> core::ops::IndexMut<EdgeIndex> for Dag<N, E> {
+    fn index_mut(&mut self, edge: EdgeIndex) -> &mut Self::Output {
+        &mut self.graph[edge]
+    }
+}
+
+/// An iterator over the nodes of a [`Dag`] in topological order.
+///
+/// This struct is created by [`Dag::topological_order()`]. See its
+/// documentation for more.
+///
+/// [`Dag`]: crate::containers::Dag
+/// [`Dag::topological_order()`]: crate::containers::Dag::topological_order
+#[derive(Debug)]
+pub struct Topo<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    // The number of edges from the nodes that are not returned yet.
+    in_degree: Vec<usize>,
+    // The nodes that no edge from the remaining nodes enters.
+    ready: ArrayDeque<NodeIndex>,
+    len: usize,
+}
+
+impl<'a, N, E> Iterator for Topo<'a, N, E> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.ready.pop_front()?;
+        self.len -= 1;
+        for next in self.graph.neighbors(node) {
+            self.in_degree[next.index()] -= 1;
+            if self.in_degree[next.index()] == 0 {
+                self.ready.push_back(next);
+            }
+        }
+        Some(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, N, E> ExactSizeIterator for Topo<'a, N, E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn random_edges_keep_acyclic() {
+        let mut rng = SplitMix64::new(31);
+        let n = 40;
+        let mut dag = Dag::new();
+        let nodes: Vec<_> = (0..n).map(|i| dag.add_node(i)).collect();
+        let mut reach = vec![vec![false; n]; n];
+        for (i, row) in reach.iter_mut().enumerate() {
+            row[i] = true;
+        }
+        for step in 0..400 {
+            let a = (rng.next_u64() % n as u64) as usize;
+            let b = (rng.next_u64() % n as u64) as usize;
+            match dag.add_edge(nodes[a], nodes[b], step) {
+                Ok(_) => {
+                    assert!(!reach[b][a]);
+                    // Everything that reaches `a` now reaches what `b`
+                    // reaches.
+                    let from_b = reach[b].clone();
+                    for row in reach.iter_mut().filter(|row| row[a]) {
+                        for (x, &y) in row.iter_mut().zip(&from_b) {
+                            *x |= y;
+                        }
+                    }
+                }
+                Err(err) => {
+                    assert!(reach[b][a]);
+                    let cycle = err.cycle();
+                    assert_eq!(cycle[0], nodes[b]);
+                    assert_eq!(cycle[cycle.len() - 1], nodes[a]);
+                    for w in cycle.windows(2) {
+                        assert!(dag.graph().find_edge(w[0], w[1]).is_some());
+                    }
+                    assert_eq!(err.into_weight(), step);
+                }
+            }
+        }
+
+        let order: Vec<_> = dag.topological_order().collect();
+        assert_eq!(order.len(), n);
+        let mut position = vec![0; n];
+        for (i, node) in order.iter().enumerate() {
+            position[node.index()] = i;
+        }
+        for edge in dag.graph().edge_indices() {
+            let (a, b) = dag.graph().edge_endpoints(edge).unwrap();
+            assert!(position[a.index()] < position[b.index()]);
+        }
+    }
+
+    #[test]
+    fn longest_path_matches_brute_force() {
+        fn longest_from(dag: &Dag<usize, i64>, node: NodeIndex) -> i64 {
+            dag.graph()
+                .edges(node)
+                .map(|(_, next, &w)| w + longest_from(dag, next))
+                .max()
+                .unwrap_or(0)
+                .max(0)
+        }
+
+        let mut rng = SplitMix64::new(32);
+        for _ in 0..50 {
+            let n = 12;
+            let mut dag = Dag::new();
+            let nodes: Vec<_> = (0..n).map(|i| dag.add_node(i)).collect();
+            for _ in 0..25 {
+                let a = (rng.next_u64() % n as u64) as usize;
+                let b = (rng.next_u64() % n as u64) as usize;
+                let w = (rng.next_u64() % 10) as i64 - 3;
+                let _ = dag.add_edge(nodes[a], nodes[b], w);
+            }
+            let expected =
+                nodes.iter().map(|&v| longest_from(&dag, v)).max().unwrap();
+            let (length, path) = dag.longest_path().unwrap();
+            assert_eq!(length, expected);
+            let total: i64 = path
+                .windows(2)
+                .map(|w| {
+                    dag.graph()
+                        .edges(w[0])
+                        .filter(|&(_, next, _)| next == w[1])
+                        .map(|(_, _, &weight)| weight)
+                        .max()
+                        .unwrap()
+                })
+                .sum();
+            assert_eq!(total, length);
+        }
+        assert_eq!(Dag::<(), i64>::new().longest_path(), None);
+    }
+}
@@ -0,0 +1,510 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+// The index of the node whose right and left lists link the headers of the
+// columns that are not covered.
+const ROOT: usize = 0;
+const NIL: usize = usize::MAX;
+
+/// A sparse 0/1 matrix for solving exact cover problems with Knuth's
+/// Algorithm X, where every one of the matrix is a node of a circular doubly
+/// linked list across its row and of another down its column.
+///
+/// An exact cover is a set of rows that has exactly one one in every column.
+/// Every column has a header node, and the headers of the columns that are
+/// still to be covered are linked in a list of their own. Covering a column
+/// takes its header out of that list and takes the other rows that have a
+/// one in that column out of every other column, and uncovering it puts them
+/// back in the reverse order. A node taken out of a list keeps its links, so
+/// putting it back takes O(1) time, which makes backtracking cheap. The
+/// search always branches on the column with the fewest rows left.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::DancingLinks;
+///
+/// let mut matrix = DancingLinks::new(7);
+/// matrix.add_row([0, 3, 6]);
+/// matrix.add_row([0, 3]);
+/// matrix.add_row([3, 4, 6]);
+/// matrix.add_row([2, 4, 5]);
+/// matrix.add_row([1, 2, 5, 6]);
+/// matrix.add_row([1, 6]);
+///
+/// let solutions: Vec<_> = matrix.solve_exact_cover().collect();
+/// assert_eq!(solutions, [vec![1, 3, 5]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DancingLinks {
+    // The root, then the headers of the columns, then the ones of the rows.
+    nodes: Vec<Link>,
+    // The number of rows left in each column, indexed by header.
+    sizes: Vec<usize>,
+    columns: usize,
+    rows: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Link {
+    left: usize,
+    right: usize,
+    up: usize,
+    down: usize,
+    header: usize,
+    row: usize,
+}
+
+impl DancingLinks {
+    /// Creates a DancingLinks matrix with the given number of columns and no
+    /// rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let matrix = DancingLinks::new(4);
+    /// assert_eq!(matrix.columns(), 4);
+    /// assert_eq!(matrix.rows(), 0);
+    /// ```
+    pub fn new(columns: usize) -> Self {
+        let nodes = (0..=columns)
+            .map(|i| Link {
+                left: if i == 0 { columns } else { i - 1 },
+                right: if i == columns { 0 } else { i + 1 },
+                up: i,
+                down: i,
+                header: i,
+                row: NIL,
+            })
+            .collect();
+        Self {
+            nodes,
+            sizes: vec![0; columns + 1],
+            columns,
+            rows: 0,
+        }
+    }
+
+    /// Returns the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let matrix = DancingLinks::new(4);
+    /// assert_eq!(matrix.columns(), 4);
+    /// ```
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(4);
+    /// matrix.add_row([0, 2]);
+    /// assert_eq!(matrix.rows(), 1);
+    /// ```
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Adds a row with a one in each of the given columns, and returns its
+    /// index. Rows should only be added while no column is covered, and a row
+    /// without ones is never part of a solution.
+    ///
+    /// This operation should compute in O(k) time for a row with k ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column is out of bounds or is given more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(4);
+    /// assert_eq!(matrix.add_row([0, 2]), 0);
+    /// assert_eq!(matrix.add_row([1, 3]), 1);
+    /// assert_eq!(matrix.column_size(2), 1);
+    /// ```
+    pub fn add_row<I>(&mut self, columns: I) -> usize
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let row = self.rows;
+        let mut first = NIL;
+        for column in columns {
+            assert!(column < self.columns, "Out of bounds access");
+            let header = column + 1;
+            let last = self.nodes[header].up;
+            assert!(self.nodes[last].row != row, "Duplicate column");
+            let node = self.nodes.len();
+            let (left, right) = match first {
+                NIL => (node, node),
+                _ => (self.nodes[first].left, first),
+            };
+            self.nodes.push(Link {
+                left,
+                right,
+                up: last,
+                down: header,
+                header,
+                row,
+            });
+            self.nodes[last].down = node;
+            self.nodes[header].up = node;
+            self.nodes[left].right = node;
+            self.nodes[right].left = node;
+            self.sizes[header] += 1;
+            if first == NIL {
+                first = node;
+            }
+        }
+        self.rows += 1;
+        row
+    }
+
+    /// Returns the number of rows left with a one in the column, which does
+    /// not count the rows taken out by covering other columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(2);
+    /// matrix.add_row([0, 1]);
+    /// matrix.add_row([1]);
+    /// assert_eq!(matrix.column_size(1), 2);
+    /// matrix.cover(0);
+    /// assert_eq!(matrix.column_size(1), 1);
+    /// ```
+    pub fn column_size(&self, column: usize) -> usize {
+        assert!(column < self.columns, "Out of bounds access");
+        self.sizes[column + 1]
+    }
+
+    /// Covers the column, taking it out of the columns to be covered and
+    /// taking every row with a one in it out of the other columns.
+    ///
+    /// Covered columns must be uncovered in the reverse order of covering.
+    ///
+    /// This operation should compute in O(r) time, where r is the number of
+    /// ones in the rows taken out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(3);
+    /// matrix.add_row([0, 1]);
+    /// matrix.add_row([1, 2]);
+    /// matrix.cover(0);
+    /// assert_eq!(matrix.column_size(1), 1);
+    /// matrix.uncover(0);
+    /// assert_eq!(matrix.column_size(1), 2);
+    /// ```
+    pub fn cover(&mut self, column: usize) {
+        assert!(column < self.columns, "Out of bounds access");
+        self.cover_header(column + 1);
+    }
+
+    /// Uncovers the column, undoing [`DancingLinks::cover()`].
+    ///
+    /// The column must be the one covered most recently and not uncovered
+    /// yet.
+    ///
+    /// This operation should compute in O(r) time, where r is the number of
+    /// ones in the rows put back.
+    ///
+    /// [`DancingLinks::cover()`]: crate::containers::DancingLinks::cover
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(2);
+    /// matrix.add_row([0, 1]);
+    /// matrix.cover(1);
+    /// assert_eq!(matrix.column_size(0), 0);
+    /// matrix.uncover(1);
+    /// assert_eq!(matrix.column_size(0), 1);
+    /// ```
+    pub fn uncover(&mut self, column: usize) {
+        assert!(column < self.columns, "Out of bounds access");
+        self.uncover_header(column + 1);
+    }
+
+    /// Returns an iterator over the exact covers of the columns that are not
+    /// covered, each given as the indices of its rows in increasing order.
+    ///
+    /// The matrix is back to its state before the search once the iterator
+    /// is exhausted or dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DancingLinks;
+    ///
+    /// let mut matrix = DancingLinks::new(3);
+    /// matrix.add_row([0, 1, 2]);
+    /// matrix.add_row([0]);
+    /// matrix.add_row([1, 2]);
+    /// matrix.add_row([1]);
+    ///
+    /// let mut solutions: Vec<_> = matrix.solve_exact_cover().collect();
+    /// solutions.sort();
+    /// assert_eq!(solutions, [vec![0], vec![1, 2]]);
+    /// ```
+    pub fn solve_exact_cover(&mut self) -> Solutions<'_> {
+        Solutions {
+            matrix: self,
+            chosen: Vec::new(),
+            descend: true,
+            done: false,
+        }
+    }
+
+    fn cover_header(&mut self, header: usize) {
+        let Link { left, right, .. } = self.nodes[header];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        let mut i = self.nodes[header].down;
+        while i != header {
+            let mut j = self.nodes[i].right;
+            while j != i {
+                let Link { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = down;
+                self.nodes[down].up = up;
+                self.sizes[self.nodes[j].header] -= 1;
+                j = self.nodes[j].right;
+            }
+            i = self.nodes[i].down;
+        }
+    }
+
+    fn uncover_header(&mut self, header: usize) {
+        let mut i = self.nodes[header].up;
+        while i != header {
+            let mut j = self.nodes[i].left;
+            while j != i {
+                let Link { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = j;
+                self.nodes[down].up = j;
+                self.sizes[self.nodes[j].header] += 1;
+                j = self.nodes[j].left;
+            }
+            i = self.nodes[i].up;
+        }
+        let Link { left, right, .. } = self.nodes[header];
+        self.nodes[left].right = header;
+        self.nodes[right].left = header;
+    }
+
+    // Covers the columns of the other ones of the row of the node.
+    fn choose(&mut self, node: usize) {
+        let mut j = self.nodes[node].right;
+        while j != node {
+            self.cover_header(self.nodes[j].header);
+            j = self.nodes[j].right;
+        }
+    }
+
+    // Undoes `choose`.
+    fn unchoose(&mut self, node: usize) {
+        let mut j = self.nodes[node].left;
+        while j != node {
+            self.uncover_header(self.nodes[j].header);
+            j = self.nodes[j].left;
+        }
+    }
+}
+
+/// An iterator over the exact covers of a [`DancingLinks`] matrix.
+///
+/// This struct is created by [`DancingLinks::solve_exact_cover()`]. See its
+/// documentation for more.
+///
+/// [`DancingLinks`]: crate::containers::DancingLinks
+/// [`DancingLinks::solve_exact_cover()`]: DancingLinks::solve_exact_cover
+#[derive(Debug)]
+pub struct Solutions<'a> {
+    matrix: &'a mut DancingLinks,
+    // The node of the row chosen at each level of the search.
+    chosen: Vec<usize>,
+    // Whether the search goes down from the current level, or backtracks to
+    // the next row of the level above.
+    descend: bool,
+    done: bool,
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let matrix = &mut *self.matrix;
+        while !self.done {
+            if self.descend {
+                if matrix.nodes[ROOT].right == ROOT {
+                    self.descend = false;
+                    let mut rows: Vec<_> = self
+                        .chosen
+                        .iter()
+                        .map(|&i| matrix.nodes[i].row)
+                        .collect();
+                    rows.sort_unstable();
+                    return Some(rows);
+                }
+                // Branching on the column with the fewest rows keeps the
+                // search tree small.
+                let mut header = matrix.nodes[ROOT].right;
+                let mut j = matrix.nodes[header].right;
+                while j != ROOT {
+                    if matrix.sizes[j] < matrix.sizes[header] {
+                        header = j;
+                    }
+                    j = matrix.nodes[j].right;
+                }
+                if matrix.sizes[header] == 0 {
+                    self.descend = false;
+                    continue;
+                }
+                matrix.cover_header(header);
+                let node = matrix.nodes[header].down;
+                matrix.choose(node);
+                self.chosen.push(node);
+            } else {
+                let node = match self.chosen.pop() {
+                    Some(node) => node,
+                    None => {
+                        self.done = true;
+                        break;
+                    }
+                };
+                matrix.unchoose(node);
+                let header = matrix.nodes[node].header;
+                let next = matrix.nodes[node].down;
+                if next == header {
+                    matrix.uncover_header(header);
+                } else {
+                    matrix.choose(next);
+                    self.chosen.push(next);
+                    self.descend = true;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Drop for Solutions<'a> {
+    fn drop(&mut self) {
+        while let Some(node) = self.chosen.pop() {
+            self.matrix.unchoose(node);
+            self.matrix.uncover_header(self.matrix.nodes[node].header);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn brute_force(columns: usize, rows: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut solutions = Vec::new();
+        for subset in 0..1usize << rows.len() {
+            let mut counts = vec![0; columns];
+            let chosen: Vec<_> =
+                (0..rows.len()).filter(|i| subset >> i & 1 == 1).collect();
+            for &i in &chosen {
+                for &c in &rows[i] {
+                    counts[c] += 1;
+                }
+            }
+            let has_empty = chosen.iter().any(|&i| rows[i].is_empty());
+            if !has_empty && counts.iter().all(|&count| count == 1) {
+                solutions.push(chosen);
+            }
+        }
+        solutions
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(53);
+        for _ in 0..200 {
+            let columns = (rng.next_u64() % 7) as usize;
+            let row_count = (rng.next_u64() % 12) as usize;
+            let rows: Vec<Vec<usize>> = (0..row_count)
+                .map(|_| {
+                    (0..columns)
+                        .filter(|_| rng.next_u64().is_multiple_of(3))
+                        .collect()
+                })
+                .collect();
+            let mut matrix = DancingLinks::new(columns);
+            for row in &rows {
+                matrix.add_row(row.iter().copied());
+            }
+            let mut expected = brute_force(columns, &rows);
+            expected.sort();
+            let mut found: Vec<_> = matrix.solve_exact_cover().collect();
+            found.sort();
+            assert_eq!(found, expected);
+
+            // Stopping the search early restores the matrix.
+            drop(matrix.solve_exact_cover().next());
+            let mut again: Vec<_> = matrix.solve_exact_cover().collect();
+            again.sort();
+            assert_eq!(again, expected);
+            for c in 0..columns {
+                let count = rows.iter().filter(|row| row.contains(&c)).count();
+                assert_eq!(matrix.column_size(c), count);
+            }
+        }
+    }
+
+    #[test]
+    fn respects_covered_columns() {
+        let mut matrix = DancingLinks::new(3);
+        matrix.add_row([0, 1]);
+        matrix.add_row([2]);
+        matrix.add_row([1, 2]);
+        matrix.cover(0);
+        let solutions: Vec<_> = matrix.solve_exact_cover().collect();
+        assert_eq!(solutions, [vec![2]]);
+        matrix.uncover(0);
+        let solutions: Vec<_> = matrix.solve_exact_cover().collect();
+        assert_eq!(solutions, [vec![0, 1]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate column")]
+    fn rejects_duplicate_columns() {
+        DancingLinks::new(3).add_row([1, 2, 1]);
+    }
+}
@@ -0,0 +1,365 @@
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use super::binary_heap::BinaryHeap;
+use super::clock::{Clock, SystemClock};
+
+/// An item of the queue with its deadline, and the number of items pushed
+/// before it.
+#[derive(Debug)]
+struct Entry<T> {
+    item: T,
+    deadline: Instant,
+    seq: u64,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The earliest deadline is the greatest, and among equal deadlines,
+        // the earlier item.
+        other
+            .deadline
+            .cmp(&self.deadline)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A queue of items that each become available at a deadline.
+///
+/// The items are kept in a [`BinaryHeap`], ordered by their deadlines, so
+/// the item that is due first is always at the front. Items with the same
+/// deadline become available in the order they were pushed. The queue does
+/// not wait by itself: [`DelayQueue::next_deadline()`] tells the caller
+/// until when it can sleep, and [`DelayQueue::pop_ready()`] then returns
+/// the items that are due.
+///
+/// The current time is given to [`DelayQueue::pop_ready()`] by the caller,
+/// or read from a [`Clock`] by [`DelayQueue::poll()`] and
+/// [`DelayQueue::push_after()`]. The clock is the system's clock unless
+/// another one is given with [`DelayQueue::with_clock()`].
+///
+/// [`BinaryHeap`]: crate::containers::BinaryHeap
+/// [`Clock`]: crate::containers::Clock
+/// [`DelayQueue::next_deadline()`]: DelayQueue::next_deadline
+/// [`DelayQueue::pop_ready()`]: DelayQueue::pop_ready
+/// [`DelayQueue::poll()`]: DelayQueue::poll
+/// [`DelayQueue::push_after()`]: DelayQueue::push_after
+/// [`DelayQueue::with_clock()`]: DelayQueue::with_clock
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dt::containers::{DelayQueue, ManualClock};
+///
+/// let clock = ManualClock::new();
+/// let mut retries = DelayQueue::with_clock(clock.clone());
+/// retries.push_after("b", Duration::from_secs(20));
+/// retries.push_after("a", Duration::from_secs(10));
+/// assert_eq!(retries.poll(), None);
+///
+/// clock.advance(Duration::from_secs(15));
+/// assert_eq!(retries.poll(), Some("a"));
+/// assert_eq!(retries.poll(), None);
+///
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(retries.poll(), Some("b"));
+/// assert!(retries.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct DelayQueue<T> {
+    heap: BinaryHeap<Entry<T>>,
+    seq: u64,
+    clock: Box<dyn Clock + Send + Sync>,
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DelayQueue<T> {
+    /// Creates an empty DelayQueue that reads the time from the system's
+    /// clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let queue: DelayQueue<u32> = DelayQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+
+    /// Creates an empty DelayQueue that reads the time from the given
+    /// clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{DelayQueue, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(clock.clone());
+    /// queue.push_after(1, Duration::from_secs(5));
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(queue.poll(), Some(1));
+    /// ```
+    pub fn with_clock<C>(clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        Self {
+            heap: BinaryHeap::new(),
+            seq: 0,
+            clock: Box::new(clock),
+        }
+    }
+
+    /// Returns the number of items in the queue, whether they are due or
+    /// not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// queue.push_after(1, Duration::from_secs(60));
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns true if the queue has no items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// assert!(queue.is_empty());
+    /// queue.push_after(1, Duration::from_secs(60));
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Removes all items from the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// queue.push_after(1, Duration::from_secs(60));
+    /// queue.clear();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.seq = 0;
+    }
+
+    /// Adds an item that becomes available at the deadline.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    /// queue.push(1, deadline);
+    /// assert_eq!(queue.next_deadline(), Some(deadline));
+    /// ```
+    pub fn push(&mut self, item: T, deadline: Instant) {
+        self.heap.push(Entry {
+            item,
+            deadline,
+            seq: self.seq,
+        });
+        self.seq += 1;
+    }
+
+    /// Adds an item that becomes available once the delay has passed, as
+    /// measured by the clock of the queue.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{Clock, DelayQueue, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(clock.clone());
+    /// queue.push_after(1, Duration::from_secs(5));
+    /// let deadline = clock.now() + Duration::from_secs(5);
+    /// assert_eq!(queue.next_deadline(), Some(deadline));
+    /// ```
+    pub fn push_after(&mut self, item: T, delay: Duration) {
+        let deadline = self.clock.now() + delay;
+        self.push(item, deadline);
+    }
+
+    /// Returns the earliest deadline of the items in the queue, or None if
+    /// it is empty.
+    ///
+    /// The deadline may already be past, in which case an item is ready.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// assert_eq!(queue.next_deadline(), None);
+    /// let now = Instant::now();
+    /// queue.push("b", now + Duration::from_secs(2));
+    /// queue.push("a", now + Duration::from_secs(1));
+    /// assert_eq!(queue.next_deadline(), Some(now + Duration::from_secs(1)));
+    /// ```
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.heap.peek().map(|entry| entry.deadline)
+    }
+
+    /// Removes the item with the earliest deadline and returns it, if that
+    /// deadline is not later than `now`, or returns None.
+    ///
+    /// Calling this until it returns None yields all the items that are
+    /// due, in the order of their deadlines.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use dt::containers::DelayQueue;
+    ///
+    /// let mut queue = DelayQueue::new();
+    /// let start = Instant::now();
+    /// queue.push("c", start + Duration::from_secs(3));
+    /// queue.push("a", start + Duration::from_secs(1));
+    /// queue.push("b", start + Duration::from_secs(2));
+    ///
+    /// let now = start + Duration::from_secs(2);
+    /// let mut ready = Vec::new();
+    /// while let Some(item) = queue.pop_ready(now) {
+    ///     ready.push(item);
+    /// }
+    /// assert_eq!(ready, ["a", "b"]);
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn pop_ready(&mut self, now: Instant) -> Option<T> {
+        if self.next_deadline()? > now {
+            return None;
+        }
+        self.heap.pop().map(|entry| entry.item)
+    }
+
+    /// Removes the item with the earliest deadline and returns it, if that
+    /// deadline has passed according to the clock of the queue, or returns
+    /// None.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{DelayQueue, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut queue = DelayQueue::with_clock(clock.clone());
+    /// queue.push_after(1, Duration::from_secs(5));
+    /// assert_eq!(queue.poll(), None);
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(queue.poll(), Some(1));
+    /// ```
+    pub fn poll(&mut self) -> Option<T> {
+        let now = self.clock.now();
+        self.pop_ready(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use crate::containers::ManualClock;
+
+    #[test]
+    fn pops_due_items_in_deadline_order() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut queue = DelayQueue::with_clock(clock.clone());
+        let mut rng = SplitMix64::new(SplitMix64::DEFAULT_SEED);
+        let mut pending = Vec::new();
+        for i in 0..2_000 {
+            if rng.next_u64().is_multiple_of(3) {
+                clock.advance(Duration::from_millis(rng.next_u64() % 50));
+                let now = clock.now();
+                let mut due: Vec<_> = pending
+                    .iter()
+                    .filter(|&&(deadline, _)| deadline <= now)
+                    .copied()
+                    .collect();
+                pending.retain(|&(deadline, _)| deadline > now);
+                // The items are numbered in the order they were pushed, so
+                // ties are broken by the order of insertion.
+                due.sort();
+                let due: Vec<_> = due.into_iter().map(|(_, i)| i).collect();
+                let popped: Vec<_> =
+                    std::iter::from_fn(|| queue.poll()).collect();
+                assert_eq!(popped, due);
+            } else {
+                // Deadlines on a coarse grid, so that many of them are equal.
+                let millis = rng.next_u64() % 20 * 10
+                    + (clock.now() - start).as_millis() as u64;
+                let deadline = start + Duration::from_millis(millis);
+                queue.push(i, deadline);
+                pending.push((deadline, i));
+            }
+            assert_eq!(queue.len(), pending.len());
+            assert_eq!(
+                queue.next_deadline(),
+                pending.iter().map(|&(deadline, _)| deadline).min(),
+            );
+        }
+    }
+}
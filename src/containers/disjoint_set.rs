@@ -0,0 +1,311 @@
+use alloc::vec::Vec;
+
+/// A partition of the elements `0..n` into disjoint sets, also known as
+/// union-find.
+///
+/// Every set is a tree of its elements, named by its root. Finding the set of
+/// an element walks up to the root and halves the path on the way, and
+/// merging two sets hangs the root of lower rank below the other, so the trees
+/// stay flat. Both operations take amortized O(α(n)) time, where α is the
+/// inverse Ackermann function, which is below 5 for any practical n.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::DisjointSet;
+///
+/// // Kruskal's algorithm picks the lightest edges that join two components.
+/// let mut edges = [(4, 0, 1), (1, 1, 2), (3, 0, 2), (2, 2, 3)];
+/// edges.sort();
+/// let mut set = DisjointSet::with_len(4);
+/// let mut weight = 0;
+/// for &(w, a, b) in &edges {
+///     if set.union(a, b) {
+///         weight += w;
+///     }
+/// }
+/// assert_eq!(weight, 6);
+/// assert_eq!(set.count(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    // The ranks of the roots, which bound the heights of their trees.
+    ranks: Vec<u8>,
+    // The sizes of the sets, by their roots.
+    sizes: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSet {
+    /// Creates an empty DisjointSet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let set = DisjointSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a DisjointSet of the elements `0..len`, each in a set of its
+    /// own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let set = DisjointSet::with_len(3);
+    /// assert_eq!(set.len(), 3);
+    /// assert_eq!(set.count(), 3);
+    /// ```
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            ranks: alloc::vec![0; len],
+            sizes: alloc::vec![1; len],
+            count: len,
+        }
+    }
+
+    /// Returns the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// set.make_set();
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns true if there are no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::new();
+    /// assert!(set.is_empty());
+    /// set.make_set();
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(3);
+    /// set.union(0, 2);
+    /// assert_eq!(set.count(), 2);
+    /// ```
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Adds an element in a set of its own, and returns it.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(2);
+    /// assert_eq!(set.make_set(), 2);
+    /// assert_eq!(set.count(), 3);
+    /// ```
+    pub fn make_set(&mut self) -> usize {
+        let x = self.parents.len();
+        self.parents.push(x);
+        self.ranks.push(0);
+        self.sizes.push(1);
+        self.count += 1;
+        x
+    }
+
+    /// Returns the root of the set of the element, which is the same for all
+    /// the elements of a set until it is merged with another one.
+    ///
+    /// This operation should compute in amortized O(α(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(3);
+    /// set.union(0, 1);
+    /// assert_eq!(set.find(0), set.find(1));
+    /// assert_ne!(set.find(0), set.find(2));
+    /// ```
+    pub fn find(&mut self, mut x: usize) -> usize {
+        assert!(x < self.len(), "Out of bounds access");
+        while self.parents[x] != x {
+            // Path halving: every other element on the path skips its parent.
+            let grandparent = self.parents[self.parents[x]];
+            self.parents[x] = grandparent;
+            x = grandparent;
+        }
+        x
+    }
+
+    /// Merges the sets of the two elements. Returns false if they were
+    /// already in the same set.
+    ///
+    /// This operation should compute in amortized O(α(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(3);
+    /// assert!(set.union(0, 1));
+    /// assert!(!set.union(1, 0));
+    /// ```
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.ranks[a] < self.ranks[b] {
+            core::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if self.ranks[a] == self.ranks[b] {
+            self.ranks[a] += 1;
+        }
+        self.count -= 1;
+        true
+    }
+
+    /// Returns true if the two elements are in the same set.
+    ///
+    /// This operation should compute in amortized O(α(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(3);
+    /// set.union(0, 1);
+    /// set.union(1, 2);
+    /// assert!(set.same_set(0, 2));
+    /// ```
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Returns the number of elements in the set of the element.
+    ///
+    /// This operation should compute in amortized O(α(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::DisjointSet;
+    ///
+    /// let mut set = DisjointSet::with_len(4);
+    /// set.union(0, 1);
+    /// set.union(1, 2);
+    /// assert_eq!(set.set_size(2), 3);
+    /// assert_eq!(set.set_size(3), 1);
+    /// ```
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_naive_labels() {
+        let mut rng = SplitMix64::new(71);
+        let n = 200;
+        let mut set = DisjointSet::with_len(n);
+        // Every element is labeled with its set, and merging relabels.
+        let mut labels: Vec<usize> = (0..n).collect();
+        for _ in 0..300 {
+            let a = (rng.next_u64() % n as u64) as usize;
+            let b = (rng.next_u64() % n as u64) as usize;
+            let (la, lb) = (labels[a], labels[b]);
+            assert_eq!(set.union(a, b), la != lb);
+            labels
+                .iter_mut()
+                .filter(|l| **l == lb)
+                .for_each(|l| *l = la);
+
+            let mut distinct = labels.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(set.count(), distinct.len());
+            let c = (rng.next_u64() % n as u64) as usize;
+            let size = labels.iter().filter(|&&l| l == labels[c]).count();
+            assert_eq!(set.set_size(c), size);
+            assert_eq!(set.same_set(a, c), labels[a] == labels[c]);
+        }
+    }
+
+    #[test]
+    fn ranks_stay_logarithmic() {
+        let mut set = DisjointSet::new();
+        for _ in 0..1024 {
+            set.make_set();
+        }
+        let mut step = 1;
+        while step < 1024 {
+            for a in (0..1024).step_by(2 * step) {
+                set.union(a, a + step);
+            }
+            step *= 2;
+        }
+        assert_eq!(set.count(), 1);
+        assert_eq!(set.ranks.iter().max(), Some(&10));
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn find_out_of_bounds() {
+        DisjointSet::with_len(2).find(2);
+    }
+}
@@ -1,4 +1,5 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use alloc::boxed::Box;
+use core::{marker::PhantomData, ptr::NonNull};
 
 #[derive(Debug)]
 struct Node<T> {
@@ -577,7 +578,7 @@ impl<T> DoublyLinkedList<T> {
         assert!(at <= len, "Cannot split off at a nonexistent index");
 
         if at == 0 {
-            return std::mem::take(self);
+            return core::mem::take(self);
         } else if at == len {
             return Self::new();
         }
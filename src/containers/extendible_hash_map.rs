@@ -0,0 +1,831 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use core::mem;
+use core::slice;
+
+use super::linked_hash_map::DefaultHashBuilder;
+
+/// The number of entries a bucket holds when none is given.
+const DEFAULT_BUCKET_CAPACITY: usize = 4;
+
+/// A hash map that grows by splitting one full bucket at a time, finding the
+/// buckets through a directory that doubles when a split needs more bits of
+/// the hash.
+///
+/// The directory has `2^global_depth` slots, and a key belongs to the bucket
+/// in the slot given by the lowest `global_depth` bits of its hash. Each
+/// bucket holds at most a fixed number of entries and has a local depth, the
+/// number of low bits that all of its keys share, so a bucket of local depth
+/// `d` is in `2^(global_depth - d)` slots of the directory. When an entry is
+/// inserted into a full bucket, the bucket is split in two on the next bit of
+/// the hash, and if its local depth was already the global depth, the
+/// directory is first doubled by copying it after itself.
+///
+/// This is the scheme databases use to index buckets that are disk pages: a
+/// lookup reads one page, and growing rewrites only the page that split. The
+/// map never merges buckets or shrinks its directory when entries are
+/// removed. Keys whose hashes are equal can not be told apart by any split,
+/// so a bucket of only such keys grows past its capacity instead.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::ExtendibleHashMap;
+///
+/// let mut ages = ExtendibleHashMap::new();
+/// ages.insert("alice", 31);
+/// ages.insert("bob", 27);
+/// assert_eq!(ages.insert("alice", 32), Some(31));
+///
+/// assert_eq!(ages.get("alice"), Some(&32));
+/// assert_eq!(ages.remove("bob"), Some(27));
+/// assert!(!ages.contains_key("bob"));
+/// assert_eq!(ages.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct ExtendibleHashMap<K, V, S = DefaultHashBuilder> {
+    directory: Vec<usize>,
+    buckets: Vec<Bucket<K, V>>,
+    global_depth: usize,
+    bucket_capacity: usize,
+    len: usize,
+    hasher_builder: S,
+}
+
+#[derive(Debug)]
+struct Bucket<K, V> {
+    local_depth: usize,
+    entries: Vec<(u64, K, V)>,
+}
+
+impl<K, V, S> Default for ExtendibleHashMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<K, V> ExtendibleHashMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty ExtendibleHashMap with buckets of 4 entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let map: ExtendibleHashMap<&str, i32> = ExtendibleHashMap::new();
+    /// assert!(map.is_empty());
+    /// assert_eq!(map.bucket_capacity(), 4);
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates an empty ExtendibleHashMap whose buckets hold the given
+    /// number of entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let map: ExtendibleHashMap<&str, i32> =
+    ///     ExtendibleHashMap::with_bucket_capacity(16);
+    /// assert_eq!(map.bucket_capacity(), 16);
+    /// ```
+    pub fn with_bucket_capacity(bucket_capacity: usize) -> Self {
+        Self::with_bucket_capacity_and_hasher(
+            bucket_capacity,
+            Default::default(),
+        )
+    }
+}
+
+impl<K, V, S> ExtendibleHashMap<K, V, S> {
+    /// Creates an empty ExtendibleHashMap with buckets of 4 entries that uses
+    /// the given hash builder to hash the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{ExtendibleHashMap, FnvBuildHasher};
+    ///
+    /// let mut map =
+    ///     ExtendibleHashMap::with_hasher(FnvBuildHasher::default());
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self::with_bucket_capacity_and_hasher(
+            DEFAULT_BUCKET_CAPACITY,
+            hasher_builder,
+        )
+    }
+
+    /// Creates an empty ExtendibleHashMap whose buckets hold the given
+    /// number of entries, and that uses the given hash builder to hash the
+    /// keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{ExtendibleHashMap, FnvBuildHasher};
+    ///
+    /// let mut map = ExtendibleHashMap::with_bucket_capacity_and_hasher(
+    ///     2,
+    ///     FnvBuildHasher::default(),
+    /// );
+    /// map.insert(1, "a");
+    /// assert_eq!(map.bucket_capacity(), 2);
+    /// ```
+    pub fn with_bucket_capacity_and_hasher(
+        bucket_capacity: usize,
+        hasher_builder: S,
+    ) -> Self {
+        assert!(bucket_capacity > 0, "Bucket capacity must be positive");
+        Self {
+            directory: alloc::vec![0],
+            buckets: alloc::vec![Bucket {
+                local_depth: 0,
+                entries: Vec::new(),
+            }],
+            global_depth: 0,
+            bucket_capacity,
+            len: 0,
+            hasher_builder,
+        }
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{ExtendibleHashMap, FnvBuildHasher};
+    ///
+    /// let map: ExtendibleHashMap<i32, i32, _> =
+    ///     ExtendibleHashMap::with_hasher(FnvBuildHasher::default());
+    /// let _: &FnvBuildHasher = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries a bucket holds before it splits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let map: ExtendibleHashMap<i32, i32> =
+    ///     ExtendibleHashMap::with_bucket_capacity(8);
+    /// assert_eq!(map.bucket_capacity(), 8);
+    /// ```
+    pub fn bucket_capacity(&self) -> usize {
+        self.bucket_capacity
+    }
+
+    /// Returns the number of bits of the hash that index the directory,
+    /// which has `2^global_depth` slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::with_bucket_capacity(2);
+    /// assert_eq!(map.global_depth(), 0);
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert!(map.global_depth() >= 6);
+    /// ```
+    pub fn global_depth(&self) -> usize {
+        self.global_depth
+    }
+
+    /// Returns the number of buckets, which is at most the number of slots
+    /// in the directory since buckets of smaller local depth are in more
+    /// than one slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::with_bucket_capacity(2);
+    /// assert_eq!(map.bucket_count(), 1);
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert!(map.bucket_count() >= 50);
+    /// assert!(map.bucket_count() <= 1 << map.global_depth());
+    /// ```
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns an iterator over the local depths of the buckets, in the
+    /// order they were created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::with_bucket_capacity(2);
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// let global_depth = map.global_depth();
+    /// assert!(map.local_depths().all(|depth| depth <= global_depth));
+    /// ```
+    pub fn local_depths(&self) -> LocalDepths<'_, K, V> {
+        LocalDepths {
+            buckets: self.buckets.iter(),
+        }
+    }
+
+    /// Returns an iterator over the entries of the map, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// let mut entries: Vec<_> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, [(&"a", &1), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            entries: [].iter(),
+            len: self.len,
+        }
+    }
+
+    /// Removes all entries, keeping a single empty bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// assert_eq!(map.global_depth(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.directory.clear();
+        self.directory.push(0);
+        self.buckets.truncate(1);
+        self.buckets[0].local_depth = 0;
+        self.buckets[0].entries.clear();
+        self.global_depth = 0;
+        self.len = 0;
+    }
+}
+
+impl<K, V, S> ExtendibleHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, None is returned. If the
+    /// map did have this key present, the value is updated, and the old
+    /// value is returned. The key is not updated.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.insert(37, "b"), Some("a"));
+    /// assert_eq!(map.get(&37), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher_builder.hash_one(&key);
+        let mut bucket = self.bucket_of(hash);
+        if let Some((_, _, old)) = self.buckets[bucket]
+            .entries
+            .iter_mut()
+            .find(|(h, k, _)| *h == hash && *k == key)
+        {
+            return Some(mem::replace(old, value));
+        }
+        while self.buckets[bucket].entries.len() >= self.bucket_capacity {
+            // Splitting can only separate the keys on a bit where their
+            // hashes differ.
+            let entries = &self.buckets[bucket].entries;
+            if entries.iter().all(|(h, _, _)| *h == hash) {
+                break;
+            }
+            self.split(bucket);
+            bucket = self.bucket_of(hash);
+        }
+        self.buckets[bucket].entries.push((hash, key, value));
+        self.len += 1;
+        None
+    }
+
+    /// Returns a reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        Some(&self.buckets[bucket].entries[i].2)
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        Some(&mut self.buckets[bucket].entries[i].2)
+    }
+
+    /// Returns true if the map has a value for the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Returns the local depth of the bucket the key belongs to, whether or
+    /// not the map has the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::with_bucket_capacity(1);
+    /// assert_eq!(map.local_depth(&1), 0);
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert!(map.local_depth(&1) > 0);
+    /// assert!(map.local_depth(&1) <= map.global_depth());
+    /// ```
+    pub fn local_depth<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher_builder.hash_one(key);
+        self.buckets[self.bucket_of(hash)].local_depth
+    }
+
+    /// Removes a key from the map, returning its value if the key was in the
+    /// map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ExtendibleHashMap;
+    ///
+    /// let mut map = ExtendibleHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        let (_, _, value) = self.buckets[bucket].entries.swap_remove(i);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns the bucket and the position in it of the key.
+    fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher_builder.hash_one(key);
+        let bucket = self.bucket_of(hash);
+        let i = self.buckets[bucket]
+            .entries
+            .iter()
+            .position(|(h, k, _)| *h == hash && k.borrow() == key)?;
+        Some((bucket, i))
+    }
+
+    /// Returns the bucket in the directory slot of the hash.
+    fn bucket_of(&self, hash: u64) -> usize {
+        self.directory[hash as usize & (self.directory.len() - 1)]
+    }
+
+    /// Splits a bucket in two on the bit after its local depth, doubling the
+    /// directory if the bucket is in only one of its slots.
+    fn split(&mut self, bucket: usize) {
+        let depth = self.buckets[bucket].local_depth;
+        if depth == self.global_depth {
+            self.directory.extend_from_within(..);
+            self.global_depth += 1;
+        }
+        let bit = 1 << depth;
+        let (moved, kept) = mem::take(&mut self.buckets[bucket].entries)
+            .into_iter()
+            .partition(|(h, _, _)| *h as usize & bit != 0);
+        self.buckets[bucket].entries = kept;
+        self.buckets[bucket].local_depth = depth + 1;
+        let sibling = self.buckets.len();
+        self.buckets.push(Bucket {
+            local_depth: depth + 1,
+            entries: moved,
+        });
+        // The slots of the bucket are those whose lowest `depth` bits are
+        // its pattern, and half of them now belong to the sibling.
+        let pattern = self
+            .directory
+            .iter()
+            .position(|&b| b == bucket)
+            .expect("a bucket is in at least one slot");
+        let pattern = pattern & (bit - 1);
+        let mut slot = pattern | bit;
+        while slot < self.directory.len() {
+            self.directory[slot] = sibling;
+            slot += bit << 1;
+        }
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ExtendibleHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ExtendibleHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ExtendibleHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an ExtendibleHashMap.
+///
+/// This struct is created by [`ExtendibleHashMap::iter()`]. See its
+/// documentation for more.
+///
+/// [`ExtendibleHashMap::iter()`]: crate::containers::ExtendibleHashMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Bucket<K, V>>,
+    entries: slice::Iter<'a, (u64, K, V)>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, key, value)) = self.entries.next() {
+                self.len -= 1;
+                return Some((key, value));
+            }
+            self.entries = self.buckets.next()?.entries.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// An iterator over the local depths of the buckets of an ExtendibleHashMap.
+///
+/// This struct is created by [`ExtendibleHashMap::local_depths()`]. See its
+/// documentation for more.
+///
+/// [`ExtendibleHashMap::local_depths()`]:
+/// crate::containers::ExtendibleHashMap::local_depths
+#[derive(Debug)]
+pub struct LocalDepths<'a, K, V> {
+    buckets: slice::Iter<'a, Bucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for LocalDepths<'a, K, V> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buckets.next().map(|bucket| bucket.local_depth)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.buckets.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for LocalDepths<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use std::collections::HashMap;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    /// A hasher that hashes a `u64` to itself, so that the tests choose the
+    /// bits that the keys share. A tuple is hashed to its last `u64`.
+    #[derive(Debug, Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, _: &[u8]) {
+            unreachable!("only u64 keys are hashed");
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n;
+        }
+    }
+
+    type Identity = BuildHasherDefault<IdentityHasher>;
+
+    /// Checks that every bucket is in the slots whose low bits are the ones
+    /// its keys share, and holds at most its capacity.
+    fn check_layout<K, V, S>(map: &ExtendibleHashMap<K, V, S>) {
+        assert_eq!(map.directory.len(), 1 << map.global_depth);
+        let mut slots = alloc::vec![0; map.buckets.len()];
+        for (slot, &bucket) in map.directory.iter().enumerate() {
+            slots[bucket] += 1;
+            let depth = map.buckets[bucket].local_depth;
+            let mask = (1 << depth) - 1;
+            for (hash, _, _) in &map.buckets[bucket].entries {
+                assert_eq!(*hash as usize & mask, slot & mask);
+            }
+        }
+        for (bucket, &count) in map.buckets.iter().zip(&slots) {
+            assert!(bucket.local_depth <= map.global_depth);
+            assert_eq!(count, 1 << (map.global_depth - bucket.local_depth));
+            assert!(bucket.entries.len() <= map.bucket_capacity);
+        }
+    }
+
+    #[test]
+    fn empty_map() {
+        let mut map: ExtendibleHashMap<u64, u64> = ExtendibleHashMap::new();
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.local_depth(&1), 0);
+        assert_eq!(map.global_depth(), 0);
+        assert_eq!(map.bucket_count(), 1);
+        assert_eq!(map.iter().next(), None);
+        check_layout(&map);
+    }
+
+    #[test]
+    fn doubling_and_local_splits() {
+        let mut map: ExtendibleHashMap<u64, (), _> =
+            ExtendibleHashMap::with_bucket_capacity_and_hasher(
+                2,
+                Identity::default(),
+            );
+        map.insert(0b000, ());
+        map.insert(0b001, ());
+        assert_eq!((map.global_depth(), map.bucket_count()), (0, 1));
+
+        // The only bucket is as deep as the directory, which doubles so that
+        // the bucket can split on the lowest bit.
+        map.insert(0b010, ());
+        assert_eq!((map.global_depth(), map.bucket_count()), (1, 2));
+        map.insert(0b011, ());
+        check_layout(&map);
+
+        // Splitting `{000, 010}` on the second bit doubles the directory
+        // again, leaving `{001, 011}` in two of its slots.
+        map.insert(0b100, ());
+        assert_eq!((map.global_depth(), map.bucket_count()), (2, 3));
+        assert_eq!(map.local_depth(&0b100), 2);
+        assert_eq!(map.local_depth(&0b001), 1);
+        check_layout(&map);
+
+        // Splitting `{001, 011}` only takes over one of its slots, without
+        // doubling the directory.
+        map.insert(0b101, ());
+        assert_eq!((map.global_depth(), map.bucket_count()), (2, 4));
+        assert_eq!(map.local_depth(&0b001), 2);
+        assert_eq!(map.local_depths().collect::<Vec<_>>(), [2, 2, 2, 2]);
+        check_layout(&map);
+
+        // Removing entries never merges buckets nor shrinks the directory.
+        for key in 0..6 {
+            assert_eq!(map.remove(&key), Some(()));
+        }
+        assert_eq!((map.global_depth(), map.bucket_count()), (2, 4));
+        check_layout(&map);
+    }
+
+    #[test]
+    fn equal_hashes_overflow_the_bucket() {
+        // Keys whose hashes are equal can not be split, so the bucket grows
+        // past its capacity instead of doubling the directory forever.
+        let mut map: ExtendibleHashMap<(u64, u64), u64, _> =
+            ExtendibleHashMap::with_bucket_capacity_and_hasher(
+                2,
+                Identity::default(),
+            );
+        for i in 0..5 {
+            map.insert((i, 7), i);
+        }
+        assert_eq!(map.len(), 5);
+        assert_eq!((map.global_depth(), map.bucket_count()), (0, 1));
+        assert!((0..5).all(|i| map.get(&(i, 7)) == Some(&i)));
+
+        // A key with another hash still splits the bucket.
+        map.insert((0, 6), 5);
+        assert_eq!((map.global_depth(), map.bucket_count()), (1, 2));
+        assert_eq!(map.get(&(0, 6)), Some(&5));
+    }
+
+    #[test]
+    fn matches_hash_map() {
+        for capacity in [1, 3, 8] {
+            let mut map = ExtendibleHashMap::with_bucket_capacity(capacity);
+            let mut expected = HashMap::new();
+            let mut rng = SplitMix64::new(capacity as u64);
+            for _ in 0..5_000 {
+                let key = rng.next_u64() % 500;
+                match rng.next_u64() % 4 {
+                    0 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                    1 => assert_eq!(map.get(&key), expected.get(&key)),
+                    _ => {
+                        let value = rng.next_u64();
+                        assert_eq!(
+                            map.insert(key, value),
+                            expected.insert(key, value)
+                        );
+                    }
+                }
+                assert_eq!(map.len(), expected.len());
+            }
+            check_layout(&map);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable();
+            let mut expected: Vec<_> = expected.iter().collect();
+            expected.sort_unstable();
+            assert_eq!(entries, expected);
+        }
+    }
+}
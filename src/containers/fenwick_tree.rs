@@ -0,0 +1,308 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::RangeBounds;
+
+use super::ops::{self, Group, Sum};
+
+/// A sequence that updates single elements and combines the elements of any
+/// prefix in O(log(n)) time, also known as a binary indexed tree.
+///
+/// The elements are combined with the operation `Op`, which must be a
+/// commutative [`Group`] over `T`, adding numbers by default. The node at the
+/// 1-based position i holds the combination of the elements in the positions
+/// `i - lowbit(i) + 1..=i`, where `lowbit(i)` is the lowest set bit of i, so a
+/// prefix is the combination of one node for every set bit of its length. A
+/// range is the difference of two prefixes.
+///
+/// The default operation is only used where the type is written out, so a
+/// tree of sums is created with a type annotation, as in the example below.
+///
+/// Compared to a [`SegmentTree`], it only needs n nodes and a few bit
+/// operations, but it cannot answer queries for operations without an
+/// inverse, like taking the minimum.
+///
+/// [`Group`]: crate::containers::Group
+/// [`SegmentTree`]: crate::containers::SegmentTree
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::FenwickTree;
+///
+/// let mut tree: FenwickTree<_> = FenwickTree::from_slice(&[1, 2, 3, 4]);
+/// assert_eq!(tree.prefix_sum(3), 6);
+/// tree.add(1, 10);
+/// assert_eq!(tree.range_sum(1..4), 19);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T, Op = Sum> {
+    // The node at the 1-based position i is at index i - 1.
+    tree: Vec<T>,
+    op: PhantomData<Op>,
+}
+
+impl<T, Op> FenwickTree<T, Op> {
+    /// Returns the number of elements in the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree = FenwickTree::<i32>::new(4);
+    /// assert_eq!(tree.len(), 4);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns true if the sequence has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree = FenwickTree::<i32>::new(0);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
+
+impl<T: Clone, Op: Group<T>> FenwickTree<T, Op> {
+    /// Creates a FenwickTree with `len` elements, all set to the identity of
+    /// the operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree = FenwickTree::<i32>::new(4);
+    /// assert_eq!(tree.prefix_sum(4), 0);
+    /// ```
+    pub fn new(len: usize) -> Self {
+        Self {
+            tree: vec![Op::identity(); len],
+            op: PhantomData,
+        }
+    }
+
+    /// Creates a FenwickTree over a copy of the values.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree: FenwickTree<_> = FenwickTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(tree.prefix_sum(3), 6);
+    /// ```
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut tree = values.to_vec();
+        // Every node passes its combination on to its parent, which comes
+        // later in the array.
+        for i in 1..=tree.len() {
+            let parent = i + lowbit(i);
+            if parent <= tree.len() {
+                tree[parent - 1] = Op::combine(&tree[parent - 1], &tree[i - 1]);
+            }
+        }
+        Self {
+            tree,
+            op: PhantomData,
+        }
+    }
+
+    /// Combines the delta into the element at the index.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let mut tree: FenwickTree<_> = FenwickTree::new(3);
+    /// tree.add(1, 5);
+    /// tree.add(1, -2);
+    /// assert_eq!(tree.range_sum(1..2), 3);
+    /// ```
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "Out of bounds access");
+        let mut i = index + 1;
+        while i <= self.len() {
+            self.tree[i - 1] = Op::combine(&self.tree[i - 1], &delta);
+            i += lowbit(i);
+        }
+    }
+
+    /// Returns the combination of the first `len` elements.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the number of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree: FenwickTree<_> = FenwickTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(tree.prefix_sum(0), 0);
+    /// assert_eq!(tree.prefix_sum(2), 3);
+    /// ```
+    pub fn prefix_sum(&self, len: usize) -> T {
+        assert!(len <= self.len(), "Out of bounds access");
+        let mut sum = Op::identity();
+        let mut i = len;
+        while i > 0 {
+            sum = Op::combine(&self.tree[i - 1], &sum);
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    /// Returns the combination of the elements in the range.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or if its start is greater than
+    /// its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// let tree: FenwickTree<_> = FenwickTree::from_slice(&[1, 2, 3, 4]);
+    /// assert_eq!(tree.range_sum(1..3), 5);
+    /// assert_eq!(tree.range_sum(2..), 7);
+    /// ```
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let range = ops::to_range(range, self.len());
+        Op::difference(
+            &self.prefix_sum(range.end),
+            &self.prefix_sum(range.start),
+        )
+    }
+
+    /// Returns the length of the longest prefix whose combination is at most
+    /// the target, by descending the implicit tree from the largest node.
+    ///
+    /// The prefix combinations must not decrease as the prefixes grow, as
+    /// with sums of elements that are not negative. Then, the element at the
+    /// returned index, if any, is the one where the running combination first
+    /// exceeds the target, which finds the bucket of a value in a cumulative
+    /// distribution.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FenwickTree;
+    ///
+    /// // The buckets cover 0..3, 3..4, and 4..9.
+    /// let tree: FenwickTree<_> = FenwickTree::from_slice(&[3, 1, 5]);
+    /// assert_eq!(tree.find_by_prefix(&2), 0);
+    /// assert_eq!(tree.find_by_prefix(&3), 1);
+    /// assert_eq!(tree.find_by_prefix(&8), 2);
+    /// assert_eq!(tree.find_by_prefix(&9), 3);
+    /// ```
+    pub fn find_by_prefix(&self, target: &T) -> usize
+    where
+        T: PartialOrd,
+    {
+        let mut len = 0;
+        let mut sum = Op::identity();
+        let mut step = match self.len() {
+            0 => 0,
+            n => 1 << (usize::BITS - 1 - n.leading_zeros()),
+        };
+        while step > 0 {
+            if len + step <= self.len() {
+                let next = Op::combine(&sum, &self.tree[len + step - 1]);
+                if next <= *target {
+                    len += step;
+                    sum = next;
+                }
+            }
+            step >>= 1;
+        }
+        len
+    }
+}
+
+/// Returns the lowest set bit of i.
+fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(5);
+        let mut values: Vec<i64> = (0..200)
+            .map(|_| (rng.next_u64() % 100) as i64 - 50)
+            .collect();
+        let mut tree: FenwickTree<_> = FenwickTree::from_slice(&values);
+        for _ in 0..2000 {
+            let i = rng.next_u64() as usize % values.len();
+            let j = rng.next_u64() as usize % (values.len() + 1);
+            let delta = (rng.next_u64() % 100) as i64 - 50;
+            values[i] += delta;
+            tree.add(i, delta);
+            assert_eq!(tree.prefix_sum(j), values[..j].iter().sum());
+            let (a, b) = (i.min(j), i.max(j));
+            assert_eq!(tree.range_sum(a..b), values[a..b].iter().sum());
+        }
+        let new = FenwickTree::<_, Sum>::from_slice(&values);
+        assert_eq!(new.tree, tree.tree);
+    }
+
+    #[test]
+    fn find_by_prefix_matches_scan() {
+        let mut rng = SplitMix64::new(9);
+        let values: Vec<u32> =
+            (0..100).map(|_| (rng.next_u64() % 10) as u32).collect();
+        let tree: FenwickTree<_> = FenwickTree::from_slice(&values);
+        let total: u32 = values.iter().sum();
+        for target in 0..=total + 1 {
+            let mut sum = 0;
+            let expected = values
+                .iter()
+                .take_while(|&&v| {
+                    sum += v;
+                    sum <= target
+                })
+                .count();
+            assert_eq!(tree.find_by_prefix(&target), expected);
+        }
+        assert_eq!(FenwickTree::<u32>::new(0).find_by_prefix(&5), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn add_out_of_bounds() {
+        let mut tree: FenwickTree<_> = FenwickTree::new(2);
+        tree.add(2, 1);
+    }
+}
@@ -0,0 +1,710 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+type Link<T> = NonNull<Node<T>>;
+
+/// A node of [`FibonacciHeap`]. Siblings are chained together in a circular
+/// doubly-linked list, and a node only points to one of its children.
+///
+/// [`FibonacciHeap`]: crate::containers::FibonacciHeap
+#[derive(Debug)]
+struct Node<T> {
+    item: T,
+    parent: Option<Link<T>>,
+    child: Option<Link<T>>,
+    left: Link<T>,
+    right: Link<T>,
+    degree: usize,
+    // Whether the node has lost a child since it became the child of its
+    // current parent.
+    marked: bool,
+    // Shared with the handles of the node, and cleared once the node is freed.
+    alive: Rc<Cell<bool>>,
+}
+
+/// Identifies a [`FibonacciHeap`]. When a heap is merged into another one,
+/// its identity starts pointing to the identity of the other heap, so the
+/// handles of its elements keep working with the merged heap.
+///
+/// [`FibonacciHeap`]: crate::containers::FibonacciHeap
+#[derive(Debug, Default)]
+struct HeapId {
+    merged_into: RefCell<Option<Rc<HeapId>>>,
+}
+
+impl HeapId {
+    /// Returns the identity of the heap that currently holds the elements
+    /// of this heap, and shortens the path to it for the next lookups.
+    fn resolve(self: &Rc<Self>) -> Rc<Self> {
+        let mut root = Rc::clone(self);
+        loop {
+            let next = root.merged_into.borrow().clone();
+            match next {
+                Some(next) => root = next,
+                None => break,
+            }
+        }
+        let mut it = Rc::clone(self);
+        while !Rc::ptr_eq(&it, &root) {
+            let next = it.merged_into.replace(Some(Rc::clone(&root)));
+            it = next.expect("identity is not a root");
+        }
+        root
+    }
+}
+
+/// A handle to an element of a [`FibonacciHeap`].
+///
+/// A handle stays valid until its element is removed from the heap, and
+/// follows the element when its heap is merged into another one.
+///
+/// [`FibonacciHeap`]: crate::containers::FibonacciHeap
+#[derive(Debug)]
+pub struct FibonacciHeapHandle<T> {
+    node: Link<T>,
+    alive: Rc<Cell<bool>>,
+    heap: Rc<HeapId>,
+}
+
+impl<T> Clone for FibonacciHeapHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            node: self.node,
+            alive: Rc::clone(&self.alive),
+            heap: Rc::clone(&self.heap),
+        }
+    }
+}
+
+/// A priority queue implemented with a Fibonacci heap.
+///
+/// This is a min-heap: the least element is always at the front. The heap is
+/// a collection of trees in which no node is less than its parent, and whose
+/// roots are kept in a list along with a pointer to the least one.
+///
+/// The heap does as little work as possible until it has to: pushing an
+/// element and merging two heaps only add trees to the list of roots, and
+/// decreasing a key cuts its node off from its parent. Popping the least
+/// element then consolidates the roots, by linking trees of the same degree
+/// until no two roots have the same degree. Cascading cuts keep the trees
+/// bushy enough for the number of roots left after consolidating to be
+/// logarithmic, which gives the heap its amortized bounds.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::FibonacciHeap;
+///
+/// let mut a = FibonacciHeap::new();
+/// a.push(5);
+/// let h = a.push(8);
+///
+/// let mut b = FibonacciHeap::new();
+/// b.push(3);
+///
+/// a.merge(b);
+/// a.decrease_key(&h, 1);
+/// assert_eq!(a.pop(), Some(1));
+/// assert_eq!(a.pop(), Some(3));
+/// assert_eq!(a.pop(), Some(5));
+/// assert_eq!(a.pop(), None);
+/// ```
+#[derive(Debug)]
+pub struct FibonacciHeap<T> {
+    min: Option<Link<T>>,
+    len: usize,
+    id: Rc<HeapId>,
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T: Ord> Default for FibonacciHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for FibonacciHeap<T> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        let mut lists: Vec<Link<T>> = self.min.take().into_iter().collect();
+        while let Some(start) = lists.pop() {
+            let mut it = start;
+            loop {
+                // SAFETY: Every node is reachable from exactly one list, and
+                // each list is visited once, so every node is freed exactly
+                // once. Pointers are only compared after being freed.
+                let node = unsafe { Box::from_raw(it.as_ptr()) };
+                lists.extend(node.child);
+                node.alive.set(false);
+                it = node.right;
+                drop(node);
+                if it == start {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+    /// Creates an empty FibonacciHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            min: None,
+            len: 0,
+            id: Rc::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pushes an element onto the heap and returns a handle to it.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// let h = heap.push(3);
+    /// assert_eq!(heap.get(&h), Some(&3));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn push(&mut self, item: T) -> FibonacciHeapHandle<T> {
+        let alive = Rc::new(Cell::new(true));
+        let node = Box::into_raw(Box::new(Node {
+            item,
+            parent: None,
+            child: None,
+            left: NonNull::dangling(),
+            right: NonNull::dangling(),
+            degree: 0,
+            marked: false,
+            alive: Rc::clone(&alive),
+        }));
+        // SAFETY: The pointer comes from a box, so it is not null.
+        let node = unsafe { NonNull::new_unchecked(node) };
+        // SAFETY: The node has just been allocated and is not linked to any
+        // other node yet.
+        unsafe {
+            (*node.as_ptr()).left = node;
+            (*node.as_ptr()).right = node;
+            self.add_root(node);
+        }
+        self.len += 1;
+        FibonacciHeapHandle {
+            node,
+            alive,
+            heap: Rc::clone(&self.id),
+        }
+    }
+
+    /// Moves all the elements of `other` into the heap. The handles of the
+    /// elements of `other` can be used with this heap afterward.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut a = FibonacciHeap::new();
+    /// a.push(2);
+    /// let mut b = FibonacciHeap::new();
+    /// let h = b.push(1);
+    ///
+    /// a.merge(b);
+    /// assert_eq!(a.len(), 2);
+    /// assert_eq!(a.get(&h), Some(&1));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn merge(&mut self, mut other: FibonacciHeap<T>) {
+        *other.id.merged_into.borrow_mut() = Some(Rc::clone(&self.id));
+        if let Some(min) = other.min.take() {
+            // SAFETY: The roots of `other` are not part of this heap, and
+            // `other` gives them up.
+            unsafe { self.add_root(min) };
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Returns the least element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: The least root is owned by the heap, and the reference
+        // can't outlive it.
+        self.min.map(|min| unsafe { &(*min.as_ptr()).item })
+    }
+
+    /// Removes the least element from the heap and returns it, or None if it
+    /// is empty.
+    ///
+    /// This operation should compute in amortized O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn pop(&mut self) -> Option<T> {
+        let z = self.min?;
+        // SAFETY: All the nodes that are accessed are owned by the heap. The
+        // least root is unlinked from every other node before it is freed.
+        unsafe {
+            if let Some(child) = (*z.as_ptr()).child.take() {
+                let mut it = child;
+                loop {
+                    (*it.as_ptr()).parent = None;
+                    it = (*it.as_ptr()).right;
+                    if it == child {
+                        break;
+                    }
+                }
+                splice(z, child);
+            }
+            let right = (*z.as_ptr()).right;
+            unlink(z);
+            if right == z {
+                self.min = None;
+            } else {
+                self.min = Some(right);
+                self.consolidate();
+            }
+            self.len -= 1;
+            let node = Box::from_raw(z.as_ptr());
+            node.alive.set(false);
+            Some(node.item)
+        }
+    }
+
+    /// Returns true if the handle refers to an element of the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// let h = heap.push(1);
+    /// assert!(heap.contains(&h));
+    /// heap.pop();
+    /// assert!(!heap.contains(&h));
+    /// ```
+    pub fn contains(&self, handle: &FibonacciHeapHandle<T>) -> bool {
+        handle.alive.get() && Rc::ptr_eq(&handle.heap.resolve(), &self.id)
+    }
+
+    /// Returns the element the handle refers to, or None if it is not in the
+    /// heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// let h = heap.push("a");
+    /// assert_eq!(heap.get(&h), Some(&"a"));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn get(&self, handle: &FibonacciHeapHandle<T>) -> Option<&T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        // SAFETY: The node is alive and owned by this heap.
+        Some(unsafe { &(*handle.node.as_ptr()).item })
+    }
+
+    /// Replaces the element the handle refers to with a lesser one.
+    ///
+    /// If the element becomes less than its parent, its node is cut off and
+    /// moved to the list of roots. A parent that loses a second child is cut
+    /// off as well, and so on up the tree.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle does not refer to an element of the heap, or if
+    /// `item` is greater than the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.push(2);
+    /// let h = heap.push(3);
+    ///
+    /// heap.decrease_key(&h, 1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn decrease_key(&mut self, handle: &FibonacciHeapHandle<T>, item: T) {
+        assert!(self.contains(handle), "Invalid handle");
+        let x = handle.node;
+        // SAFETY: The node is alive and owned by this heap, so is every node
+        // it is linked to.
+        unsafe {
+            assert!(
+                item <= (*x.as_ptr()).item,
+                "New key is greater than the current key"
+            );
+            (*x.as_ptr()).item = item;
+            if let Some(y) = (*x.as_ptr()).parent {
+                if (*x.as_ptr()).item < (*y.as_ptr()).item {
+                    self.cut(x, y);
+                    self.cascading_cut(y);
+                }
+            }
+            let min = self.min.expect("heap is not empty");
+            if (*x.as_ptr()).item < (*min.as_ptr()).item {
+                self.min = Some(x);
+            }
+        }
+    }
+
+    /// Removes the element the handle refers to and returns it, or None if it
+    /// is not in the heap.
+    ///
+    /// The node is cut off as if its key was decreased below every other key,
+    /// then popped.
+    ///
+    /// This operation should compute in amortized O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FibonacciHeap;
+    ///
+    /// let mut heap = FibonacciHeap::new();
+    /// heap.push(1);
+    /// let h = heap.push(2);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.remove(&h), Some(2));
+    /// assert_eq!(heap.remove(&h), None);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(3));
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn remove(&mut self, handle: &FibonacciHeapHandle<T>) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        let x = handle.node;
+        // SAFETY: The node is alive and owned by this heap, so is every node
+        // it is linked to.
+        unsafe {
+            if let Some(y) = (*x.as_ptr()).parent {
+                self.cut(x, y);
+                self.cascading_cut(y);
+            }
+        }
+        self.min = Some(x);
+        self.pop()
+    }
+
+    /// Adds the list of nodes that contains `node` to the list of roots, and
+    /// updates the least root.
+    ///
+    /// # Safety
+    ///
+    /// The nodes must be valid roots that are not part of the heap yet.
+    #[allow(unsafe_code)]
+    unsafe fn add_root(&mut self, node: Link<T>) {
+        match self.min {
+            None => self.min = Some(node),
+            Some(min) => {
+                splice(min, node);
+                if (*node.as_ptr()).item < (*min.as_ptr()).item {
+                    self.min = Some(node);
+                }
+            }
+        }
+    }
+
+    /// Links roots of the same degree together until every root has a
+    /// different degree, then finds the least root.
+    ///
+    /// # Safety
+    ///
+    /// `self.min` must point to one of the roots, not necessarily the least.
+    #[allow(unsafe_code)]
+    unsafe fn consolidate(&mut self) {
+        let start = self.min.expect("heap is not empty");
+        let mut roots = Vec::new();
+        let mut it = start;
+        loop {
+            roots.push(it);
+            it = (*it.as_ptr()).right;
+            if it == start {
+                break;
+            }
+        }
+
+        let mut by_degree: Vec<Option<Link<T>>> = Vec::new();
+        for mut x in roots {
+            let mut degree = (*x.as_ptr()).degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+                let mut y = match by_degree[degree].take() {
+                    Some(y) => y,
+                    None => break,
+                };
+                if (*y.as_ptr()).item < (*x.as_ptr()).item {
+                    core::mem::swap(&mut x, &mut y);
+                }
+                self.link(y, x);
+                degree += 1;
+            }
+            by_degree[degree] = Some(x);
+        }
+
+        self.min = None;
+        for x in by_degree.into_iter().flatten() {
+            match self.min {
+                Some(min) if (*min.as_ptr()).item <= (*x.as_ptr()).item => {}
+                _ => self.min = Some(x),
+            }
+        }
+    }
+
+    /// Makes the root `y` a child of the root `x`.
+    ///
+    /// # Safety
+    ///
+    /// `x` and `y` must be distinct roots of the heap.
+    #[allow(unsafe_code)]
+    unsafe fn link(&mut self, y: Link<T>, x: Link<T>) {
+        unlink(y);
+        (*y.as_ptr()).parent = Some(x);
+        (*y.as_ptr()).marked = false;
+        match (*x.as_ptr()).child {
+            Some(child) => splice(child, y),
+            None => (*x.as_ptr()).child = Some(y),
+        }
+        (*x.as_ptr()).degree += 1;
+    }
+
+    /// Moves `x` from the children of `y` to the list of roots.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be a child of `y`, and the heap must have a least root.
+    #[allow(unsafe_code)]
+    unsafe fn cut(&mut self, x: Link<T>, y: Link<T>) {
+        if (*y.as_ptr()).child == Some(x) {
+            let right = (*x.as_ptr()).right;
+            (*y.as_ptr()).child = if right == x { None } else { Some(right) };
+        }
+        unlink(x);
+        (*y.as_ptr()).degree -= 1;
+        (*x.as_ptr()).parent = None;
+        (*x.as_ptr()).marked = false;
+        splice(self.min.expect("heap is not empty"), x);
+    }
+
+    /// Marks `y` after it lost a child, or cuts it off from its parent if it
+    /// was already marked, and so on up the tree.
+    ///
+    /// # Safety
+    ///
+    /// `y` must be a node of the heap.
+    #[allow(unsafe_code)]
+    unsafe fn cascading_cut(&mut self, mut y: Link<T>) {
+        while let Some(z) = (*y.as_ptr()).parent {
+            if !(*y.as_ptr()).marked {
+                (*y.as_ptr()).marked = true;
+                break;
+            }
+            self.cut(y, z);
+            y = z;
+        }
+    }
+}
+
+/// Joins the circular lists that contain `a` and `b` into one, by inserting
+/// the list of `b` to the right of `a`.
+///
+/// # Safety
+///
+/// `a` and `b` must be valid nodes that are in different lists.
+#[allow(unsafe_code)]
+unsafe fn splice<T>(a: Link<T>, b: Link<T>) {
+    let a_right = (*a.as_ptr()).right;
+    let b_left = (*b.as_ptr()).left;
+    (*a.as_ptr()).right = b;
+    (*b.as_ptr()).left = a;
+    (*b_left.as_ptr()).right = a_right;
+    (*a_right.as_ptr()).left = b_left;
+}
+
+/// Removes `x` from its circular list, leaving it in a list of its own.
+///
+/// # Safety
+///
+/// `x` must be a valid node.
+#[allow(unsafe_code)]
+unsafe fn unlink<T>(x: Link<T>) {
+    let left = (*x.as_ptr()).left;
+    let right = (*x.as_ptr()).right;
+    (*left.as_ptr()).right = right;
+    (*right.as_ptr()).left = left;
+    (*x.as_ptr()).left = x;
+    (*x.as_ptr()).right = x;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let mut heap = FibonacciHeap::new();
+        for &x in &[5, 1, 8, 3, 9, 2, 7, 3] {
+            heap.push(x);
+        }
+        assert_eq!(heap.len(), 8);
+
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, vec![1, 2, 3, 3, 5, 7, 8, 9]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn decrease_key_and_remove() {
+        let mut heap = FibonacciHeap::new();
+        let handles: Vec<_> = (0..200).map(|x| heap.push(x * 10)).collect();
+        // Consolidate the heap into deep trees so that cuts cascade.
+        assert_eq!(heap.pop(), Some(0));
+
+        for (i, h) in handles.iter().enumerate().skip(1).step_by(2) {
+            heap.decrease_key(h, i as i32 * 10 - 15);
+        }
+        for h in handles.iter().skip(2).step_by(4) {
+            assert!(heap.remove(h).is_some());
+        }
+        assert_eq!(heap.remove(&handles[0]), None);
+
+        let mut expected: Vec<_> = (1..200)
+            .filter(|i| i % 4 != 2)
+            .map(|i| if i % 2 == 1 { i * 10 - 15 } else { i * 10 })
+            .collect();
+        expected.sort_unstable();
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn merge_keeps_handles() {
+        let mut a = FibonacciHeap::new();
+        let mut b = FibonacciHeap::new();
+        let mut c = FibonacciHeap::new();
+        let ha = a.push(10);
+        let hb = b.push(20);
+        let hc = c.push(30);
+        b.merge(c);
+        a.merge(b);
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(&ha) && a.contains(&hb) && a.contains(&hc));
+
+        a.decrease_key(&hc, 5);
+        assert_eq!(a.pop(), Some(5));
+        assert_eq!(a.remove(&hb), Some(20));
+        assert!(!a.contains(&hc));
+
+        // A handle from another heap is rejected.
+        let other = FibonacciHeap::<i32>::new();
+        assert!(!other.contains(&ha));
+        assert_eq!(other.get(&ha), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid handle")]
+    fn foreign_handle() {
+        let mut a = FibonacciHeap::new();
+        let mut b = FibonacciHeap::new();
+        let h = a.push(1);
+        b.push(2);
+        b.decrease_key(&h, 0);
+    }
+}
@@ -0,0 +1,466 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::bit_vec::{Ones, BITS};
+
+/// A set of the integers below a capacity fixed at construction, stored as
+/// one bit per integer.
+///
+/// Membership is a single bit test, and the set algebra between two sets
+/// combines 64 integers per word operation.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::FixedBitSet;
+///
+/// let mut a = FixedBitSet::with_capacity(100);
+/// a.insert(1);
+/// a.insert(70);
+/// let mut b = FixedBitSet::with_capacity(100);
+/// b.insert(70);
+///
+/// assert!(b.is_subset(&a));
+/// a.difference_with(&b);
+/// assert!(a.is_disjoint(&b));
+/// assert_eq!(a.iter().collect::<Vec<_>>(), [1]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FixedBitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl fmt::Debug for FixedBitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FixedBitSet {
+    /// Creates an empty FixedBitSet that can hold the integers below the
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let set = FixedBitSet::with_capacity(10);
+    /// assert_eq!(set.capacity(), 10);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: alloc::vec![0; capacity.div_ceil(BITS)],
+            capacity,
+        }
+    }
+
+    /// Returns the number of integers the set can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let set = FixedBitSet::with_capacity(3);
+    /// assert_eq!(set.capacity(), 3);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of integers in the set.
+    ///
+    /// This operation should compute in O(n / 64) time, where n is the
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// set.insert(0);
+    /// set.insert(2);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns true if the set is empty.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// assert!(set.is_empty());
+    /// set.insert(1);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Inserts the integer. Returns true if it was not already in the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integer is not below the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// ```
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, mask) = self.locate(value);
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        inserted
+    }
+
+    /// Removes the integer. Returns true if it was in the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integer is not below the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// set.insert(1);
+    /// assert!(set.remove(1));
+    /// assert!(!set.remove(1));
+    /// ```
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, mask) = self.locate(value);
+        let removed = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        removed
+    }
+
+    /// Returns true if the integer is in the set. The integers that are not
+    /// below the capacity are never in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// set.insert(1);
+    /// assert!(set.contains(1));
+    /// assert!(!set.contains(2));
+    /// assert!(!set.contains(100));
+    /// ```
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity
+            && self.words[value / BITS] >> (value % BITS) & 1 == 1
+    }
+
+    /// Removes all the integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(3);
+    /// set.insert(1);
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Adds the integers of the other set to this one.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// a.insert(0);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// b.insert(2);
+    /// a.union_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [0, 2]);
+    /// ```
+    pub fn union_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a | b);
+    }
+
+    /// Keeps the integers that are also in the other set.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// a.insert(0);
+    /// a.insert(1);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// b.insert(1);
+    /// a.intersect_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [1]);
+    /// ```
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a & b);
+    }
+
+    /// Removes the integers that are in the other set.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// a.insert(0);
+    /// a.insert(1);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// b.insert(1);
+    /// a.difference_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [0]);
+    /// ```
+    pub fn difference_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a & !b);
+    }
+
+    /// Keeps the integers that are in exactly one of the sets.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// a.insert(0);
+    /// a.insert(1);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// b.insert(1);
+    /// b.insert(2);
+    /// a.symmetric_difference_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [0, 2]);
+    /// ```
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a ^ b);
+    }
+
+    /// Returns true if every integer of this set is in the other set.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// a.insert(1);
+    /// b.insert(1);
+    /// b.insert(2);
+    /// assert!(a.is_subset(&b));
+    /// assert!(!b.is_subset(&a));
+    /// ```
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.zip(other).all(|(a, b)| a & !b == 0)
+    }
+
+    /// Returns true if no integer is in both sets.
+    ///
+    /// This operation should compute in O(n / 64) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacities of the sets are different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut a = FixedBitSet::with_capacity(3);
+    /// let mut b = FixedBitSet::with_capacity(3);
+    /// a.insert(0);
+    /// b.insert(1);
+    /// assert!(a.is_disjoint(&b));
+    /// b.insert(0);
+    /// assert!(!a.is_disjoint(&b));
+    /// ```
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.zip(other).all(|(a, b)| a & b == 0)
+    }
+
+    /// Returns an iterator visiting the integers of the set in increasing
+    /// order.
+    ///
+    /// Visiting the k integers should compute in O(n / 64 + k) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FixedBitSet;
+    ///
+    /// let mut set = FixedBitSet::with_capacity(200);
+    /// set.insert(150);
+    /// set.insert(3);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [3, 150]);
+    /// ```
+    pub fn iter(&self) -> Ones<'_> {
+        Ones::new(&self.words)
+    }
+
+    fn locate(&self, value: usize) -> (usize, u64) {
+        assert!(value < self.capacity, "Out of bounds access");
+        (value / BITS, 1 << (value % BITS))
+    }
+
+    fn zip<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> impl Iterator<Item = (u64, u64)> + 'a {
+        assert_eq!(self.capacity, other.capacity, "Capacities must be equal");
+        self.words.iter().copied().zip(other.words.iter().copied())
+    }
+
+    fn combine<F>(&mut self, other: &Self, f: F)
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(self.capacity, other.capacity, "Capacities must be equal");
+        for (a, &b) in self.words.iter_mut().zip(&other.words) {
+            *a = f(*a, b);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a FixedBitSet {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for FixedBitSet {
+    /// Inserts the integers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an integer is not below the capacity.
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeSet;
+
+    fn random(rng: &mut SplitMix64, capacity: usize) -> FixedBitSet {
+        let mut set = FixedBitSet::with_capacity(capacity);
+        for _ in 0..capacity / 2 {
+            set.insert((rng.next_u64() % capacity as u64) as usize);
+        }
+        set
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = SplitMix64::new(61);
+        for capacity in [1, 63, 64, 65, 200] {
+            let a = random(&mut rng, capacity);
+            let b = random(&mut rng, capacity);
+            let sa: BTreeSet<_> = a.iter().collect();
+            let sb: BTreeSet<_> = b.iter().collect();
+            assert_eq!(a.len(), sa.len());
+
+            let mut set = a.clone();
+            set.union_with(&b);
+            assert!(set.iter().eq(sa.union(&sb).copied()));
+            let mut set = a.clone();
+            set.intersect_with(&b);
+            assert!(set.iter().eq(sa.intersection(&sb).copied()));
+            let mut set = a.clone();
+            set.difference_with(&b);
+            assert!(set.iter().eq(sa.difference(&sb).copied()));
+            let mut set = a.clone();
+            set.symmetric_difference_with(&b);
+            assert!(set.iter().eq(sa.symmetric_difference(&sb).copied()));
+
+            assert_eq!(a.is_subset(&b), sa.is_subset(&sb));
+            assert_eq!(a.is_disjoint(&b), sa.is_disjoint(&sb));
+            let mut inter = a.clone();
+            inter.intersect_with(&b);
+            assert!(inter.is_subset(&a) && inter.is_subset(&b));
+            let mut diff = a.clone();
+            diff.difference_with(&b);
+            assert!(diff.is_disjoint(&b));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn insert_out_of_bounds() {
+        FixedBitSet::with_capacity(64).insert(64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacities must be equal")]
+    fn different_capacities() {
+        let mut set = FixedBitSet::with_capacity(3);
+        set.union_with(&FixedBitSet::with_capacity(4));
+    }
+}
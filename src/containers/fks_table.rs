@@ -0,0 +1,582 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use core::mem;
+use core::slice;
+
+use super::linked_hash_map::DefaultHashBuilder;
+use super::rng::SplitMix64;
+
+/// A read-only hash table with no collisions, which finds a key with two
+/// hashes in the worst case, built with the two-level scheme of Fredman,
+/// Komlós and Szemerédi.
+///
+/// The first level hashes the `n` keys into `n` buckets. The second level
+/// gives a bucket of `b` keys its own table of `b^2` slots and a hash
+/// function that puts the keys in different slots. A random function has
+/// no collisions on such a table with probability more than 1/2, so the
+/// builder tries new functions until it finds one. The first level function
+/// is likewise retried until the second level tables have at most `4n`
+/// slots in total, which also holds with probability more than 1/2, so
+/// building takes expected O(n) time and the table takes O(n) space.
+///
+/// The hash functions are the hash builder `S` applied to the key and a
+/// seed, so new functions are new seeds. The hash builder must give values
+/// that depend on the seed, or building does not finish.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::FksTable;
+///
+/// let table: FksTable<_, _> =
+///     vec![("alice", 31), ("bob", 27), ("carol", 45)].into_iter().collect();
+///
+/// assert_eq!(table.len(), 3);
+/// assert_eq!(table.get("bob"), Some(&27));
+/// assert_eq!(table.get("dave"), None);
+/// assert!(table.slot_count() <= 4 * table.len());
+/// ```
+#[derive(Debug)]
+pub struct FksTable<K, V, S = DefaultHashBuilder> {
+    seed: u64,
+    buckets: Vec<Bucket>,
+    slots: Vec<Option<(K, V)>>,
+    len: usize,
+    hasher_builder: S,
+}
+
+/// The second level table of a bucket, which is a range of the slots.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    seed: u64,
+    offset: usize,
+    len: usize,
+}
+
+impl<K, V> FksTable<K, V, DefaultHashBuilder>
+where
+    K: Hash + Eq,
+{
+    /// Creates an FksTable with the given entries. If a key is given more
+    /// than once, the table has its last value.
+    ///
+    /// This operation should compute in expected O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![(1, "a"), (2, "b"), (1, "c")]);
+    /// assert_eq!(table.len(), 2);
+    /// assert_eq!(table.get(&1), Some(&"c"));
+    /// ```
+    pub fn new(entries: Vec<(K, V)>) -> Self {
+        Self::with_hasher(entries, Default::default())
+    }
+}
+
+impl<K, V, S> FksTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Creates an FksTable with the given entries that uses the given hash
+    /// builder to hash the keys. If a key is given more than once, the
+    /// table has its last value.
+    ///
+    /// This operation should compute in expected O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FksTable, FnvBuildHasher};
+    ///
+    /// let table = FksTable::with_hasher(
+    ///     vec![(1, "a"), (2, "b")],
+    ///     FnvBuildHasher::default(),
+    /// );
+    /// assert_eq!(table.get(&2), Some(&"b"));
+    /// ```
+    pub fn with_hasher(entries: Vec<(K, V)>, hasher_builder: S) -> Self {
+        let mut rng = SplitMix64::new(SplitMix64::DEFAULT_SEED);
+        let n = entries.len();
+        let mut hashes: Vec<u64>;
+        let mut seed;
+        let mut sizes = alloc::vec![0; n];
+        loop {
+            seed = rng.next_u64();
+            hashes = entries
+                .iter()
+                .map(|(key, _)| hasher_builder.hash_one((seed, key)))
+                .collect();
+            sizes.iter_mut().for_each(|size| *size = 0);
+            for &hash in &hashes {
+                sizes[reduce(hash, n)] += 1;
+            }
+            // Equal keys are counted more than once here, which only makes
+            // the bound stricter.
+            if sizes.iter().map(|&b| b * b).sum::<usize>() <= 4 * n {
+                break;
+            }
+        }
+
+        let mut groups: Vec<Vec<(K, V)>> =
+            sizes.iter().map(|&b| Vec::with_capacity(b)).collect();
+        for (entry, hash) in entries.into_iter().zip(hashes) {
+            let group = &mut groups[reduce(hash, n)];
+            match group.iter().position(|(key, _)| *key == entry.0) {
+                Some(i) => group[i].1 = entry.1,
+                None => group.push(entry),
+            }
+        }
+
+        let mut buckets = Vec::with_capacity(n);
+        let mut slots = Vec::new();
+        let mut len = 0;
+        for group in groups {
+            let bucket = Bucket {
+                seed: Self::collision_free_seed(
+                    &hasher_builder,
+                    &group,
+                    &mut rng,
+                ),
+                offset: slots.len(),
+                len: group.len() * group.len(),
+            };
+            slots.resize_with(slots.len() + bucket.len, || None);
+            len += group.len();
+            for entry in group {
+                let hash = hasher_builder.hash_one((bucket.seed, &entry.0));
+                slots[bucket.offset + reduce(hash, bucket.len)] = Some(entry);
+            }
+            buckets.push(bucket);
+        }
+        Self {
+            seed,
+            buckets,
+            slots,
+            len,
+            hasher_builder,
+        }
+    }
+
+    /// Returns a reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the table's key type, but Hash
+    /// and Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![(1, "a")]);
+    /// assert_eq!(table.get(&1), Some(&"a"));
+    /// assert_eq!(table.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.slot_of(key)?;
+        match &self.slots[slot] {
+            Some((k, value)) if k.borrow() == key => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key. The keys of the
+    /// table can not change, but their values can.
+    ///
+    /// The key may be any borrowed form of the table's key type, but Hash
+    /// and Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let mut table = FksTable::new(vec![(1, "a")]);
+    /// if let Some(x) = table.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(table.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.slot_of(key)?;
+        match &mut self.slots[slot] {
+            Some((k, value)) if (*k).borrow() == key => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the table has a value for the key.
+    ///
+    /// The key may be any borrowed form of the table's key type, but Hash
+    /// and Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![(1, "a")]);
+    /// assert!(table.contains_key(&1));
+    /// assert!(!table.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns the only slot where the key can be.
+    fn slot_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: Hash + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let hash = self.hasher_builder.hash_one((self.seed, key));
+        let bucket = self.buckets[reduce(hash, self.buckets.len())];
+        if bucket.len == 0 {
+            return None;
+        }
+        let hash = self.hasher_builder.hash_one((bucket.seed, key));
+        Some(bucket.offset + reduce(hash, bucket.len))
+    }
+
+    /// Returns a seed that puts the distinct keys of a bucket in different
+    /// slots of a table of the square of their number.
+    fn collision_free_seed(
+        hasher_builder: &S,
+        group: &[(K, V)],
+        rng: &mut SplitMix64,
+    ) -> u64 {
+        let len = group.len() * group.len();
+        let mut taken = alloc::vec![false; len];
+        loop {
+            let seed = rng.next_u64();
+            taken.iter_mut().for_each(|t| *t = false);
+            let collides = group.iter().any(|(key, _)| {
+                let slot = reduce(hasher_builder.hash_one((seed, key)), len);
+                mem::replace(&mut taken[slot], true)
+            });
+            if !collides {
+                return seed;
+            }
+        }
+    }
+}
+
+impl<K, V, S> FksTable<K, V, S> {
+    /// Returns a reference to the table's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FksTable, FnvBuildHasher};
+    ///
+    /// let table: FksTable<i32, i32, _> =
+    ///     FksTable::with_hasher(Vec::new(), FnvBuildHasher::default());
+    /// let _: &FnvBuildHasher = table.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Returns the number of entries in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![(1, "a"), (2, "b")]);
+    /// assert_eq!(table.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the table has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table: FksTable<i32, i32> = FksTable::new(Vec::new());
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of slots in the second level tables, which is at
+    /// most 4 times the number of entries the table was built with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table: FksTable<_, _> = (0..1000).map(|i| (i, i)).collect();
+    /// assert!(table.slot_count() >= table.len());
+    /// assert!(table.slot_count() <= 4 * table.len());
+    /// ```
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of first level buckets, which is the number of
+    /// entries the table was built with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![(1, "a"), (2, "b"), (1, "c")]);
+    /// assert_eq!(table.bucket_count(), 3);
+    /// ```
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns an iterator over the entries of the table, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::FksTable;
+    ///
+    /// let table = FksTable::new(vec![("a", 1), ("b", 2)]);
+    /// let mut entries: Vec<_> = table.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, [(&"a", &1), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            len: self.len,
+        }
+    }
+}
+
+/// Maps a hash to one of `n` values.
+fn reduce(hash: u64, n: usize) -> usize {
+    (hash % n as u64) as usize
+}
+
+impl<K, V, S> FromIterator<(K, V)> for FksTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        Self::with_hasher(iter.into_iter().collect(), Default::default())
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FksTable<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an FksTable.
+///
+/// This struct is created by [`FksTable::iter()`]. See its documentation
+/// for more.
+///
+/// [`FksTable::iter()`]: crate::containers::FksTable::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    slots: slice::Iter<'a, Option<(K, V)>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.slots.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+    use std::rc::Rc;
+
+    /// A hash builder that hashes every key to 0 with the first seeds it is
+    /// given, and records the seeds in order.
+    #[derive(Debug)]
+    struct BadSeeds {
+        bad: usize,
+        seeds: Rc<RefCell<Vec<u64>>>,
+    }
+
+    impl BuildHasher for BadSeeds {
+        type Hasher = BadSeedsHasher;
+
+        fn build_hasher(&self) -> Self::Hasher {
+            BadSeedsHasher {
+                bad: self.bad,
+                seeds: Rc::clone(&self.seeds),
+                words: Vec::new(),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct BadSeedsHasher {
+        bad: usize,
+        seeds: Rc<RefCell<Vec<u64>>>,
+        words: Vec<u64>,
+    }
+
+    impl Hasher for BadSeedsHasher {
+        fn finish(&self) -> u64 {
+            let (seed, key) = (self.words[0], self.words[1]);
+            let mut seeds = self.seeds.borrow_mut();
+            let i = match seeds.iter().position(|&s| s == seed) {
+                Some(i) => i,
+                None => {
+                    seeds.push(seed);
+                    seeds.len() - 1
+                }
+            };
+            if i < self.bad {
+                0
+            } else {
+                SplitMix64::new(seed ^ key).next_u64()
+            }
+        }
+
+        fn write(&mut self, _: &[u8]) {
+            unreachable!("only seeds and u64 keys are hashed");
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.words.push(n);
+        }
+    }
+
+    fn bad_seeds_table(
+        n: u64,
+        bad: usize,
+    ) -> (FksTable<u64, u64, BadSeeds>, Vec<u64>) {
+        let seeds = Rc::new(RefCell::new(Vec::new()));
+        let entries: Vec<_> = (0..n).map(|key| (key, key * 10)).collect();
+        let hasher_builder = BadSeeds {
+            bad,
+            seeds: Rc::clone(&seeds),
+        };
+        let table = FksTable::with_hasher(entries, hasher_builder);
+        assert_eq!(table.len(), n as usize);
+        assert!(table.slot_count() <= 4 * n as usize);
+        assert!((0..n).all(|key| table.get(&key) == Some(&(key * 10))));
+        assert_eq!(table.get(&n), None);
+        (table, seeds.take())
+    }
+
+    #[test]
+    fn empty_and_single_key_tables() {
+        let table: FksTable<u64, u64> = FksTable::new(Vec::new());
+        assert!(table.is_empty());
+        assert_eq!((table.bucket_count(), table.slot_count()), (0, 0));
+        assert_eq!(table.get(&1), None);
+        assert_eq!(table.iter().next(), None);
+
+        let mut table = FksTable::new(vec![(1, "a")]);
+        assert_eq!((table.bucket_count(), table.slot_count()), (1, 1));
+        assert_eq!(table.get(&1), Some(&"a"));
+        // Every key hashes to the only slot, which holds another key.
+        assert!((2..100).all(|key| table.get_mut(&key).is_none()));
+
+        let table = FksTable::new(vec![(1, "a"), (1, "b")]);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.bucket_count(), 2);
+        assert_eq!(table.iter().collect::<Vec<_>>(), [(&1, &"b")]);
+    }
+
+    #[test]
+    fn colliding_second_level_tables_are_rebuilt() {
+        // The first seed puts the 4 keys in the same bucket, which is allowed
+        // with 16 slots, and the second one puts them in the same slot.
+        let (table, seeds) = bad_seeds_table(4, 2);
+        assert_eq!(table.seed, seeds[0]);
+        assert_eq!(table.buckets[0].len, 16);
+        assert!(seeds.len() >= 3);
+        assert_eq!(Some(&table.buckets[0].seed), seeds.last());
+    }
+
+    #[test]
+    fn crowded_first_levels_are_rebuilt() {
+        // The first two seeds put the 50 keys in the same bucket, which would
+        // need 2500 slots.
+        let (table, seeds) = bad_seeds_table(50, 2);
+        assert_eq!(table.seed, seeds[2]);
+        assert_eq!(table.bucket_count(), 50);
+    }
+
+    #[test]
+    fn matches_hash_map() {
+        let mut rng = SplitMix64::new(37);
+        for n in [0, 1, 2, 10, 100, 1000] {
+            let entries: Vec<_> = (0..n)
+                .map(|_| (rng.next_u64() % (2 * n + 1), rng.next_u64()))
+                .collect();
+            let table = FksTable::new(entries.clone());
+            let expected: HashMap<_, _> = entries.into_iter().collect();
+
+            assert_eq!(table.len(), expected.len());
+            assert!(table.slot_count() <= 4 * n as usize);
+            for key in 0..2 * n + 1 {
+                assert_eq!(table.get(&key), expected.get(&key));
+            }
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_unstable();
+            let mut expected: Vec<_> = expected.iter().collect();
+            expected.sort_unstable();
+            assert_eq!(entries, expected);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use core::hash::{BuildHasherDefault, Hasher};
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A 64-bit [FNV-1a] hasher.
+///
+/// FNV-1a is fast on short keys and needs no source of randomness, so it can
+/// be used without `std`. The hash of a key is the same on every run of the
+/// program, which makes maps using it vulnerable to HashDoS attacks when the
+/// keys come from an untrusted source.
+///
+/// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+///
+/// # Examples
+///
+/// ```
+/// use std::hash::Hasher;
+/// use dt::containers::FnvHasher;
+///
+/// let mut hasher = FnvHasher::default();
+/// hasher.write(b"hello");
+/// assert_eq!(hasher.finish(), 0xa430_d846_80aa_bd0b);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher {
+    hash: u64,
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self { hash: OFFSET_BASIS }
+    }
+}
+
+impl FnvHasher {
+    /// Creates a `FnvHasher` whose state starts from `key` instead of the
+    /// standard offset basis.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use dt::containers::FnvHasher;
+    ///
+    /// let mut a = FnvHasher::with_key(1);
+    /// let mut b = FnvHasher::with_key(2);
+    /// a.write(b"hello");
+    /// b.write(b"hello");
+    /// assert_ne!(a.finish(), b.finish());
+    /// ```
+    pub fn with_key(key: u64) -> Self {
+        Self { hash: key }
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(PRIME);
+        }
+    }
+}
+
+/// A [`BuildHasher`] that creates [`FnvHasher`]s.
+///
+/// [`BuildHasher`]: core::hash::BuildHasher
+/// [`FnvHasher`]: crate::containers::FnvHasher
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{FnvBuildHasher, LinkedHashMap};
+///
+/// let mut map = LinkedHashMap::with_hasher(FnvBuildHasher::default());
+/// map.insert("foo", 42);
+/// assert_eq!(map.get("foo"), Some(&42));
+/// ```
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fnv1a(bytes: &[u8]) -> u64 {
+        let mut hasher = FnvHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn known_values() {
+        // Test vectors from the reference implementation.
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn incremental() {
+        let mut hasher = FnvHasher::default();
+        hasher.write(b"foo");
+        hasher.write(b"bar");
+        assert_eq!(hasher.finish(), fnv1a(b"foobar"));
+    }
+}
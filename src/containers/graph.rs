@@ -0,0 +1,1081 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::Enumerate;
+use core::slice;
+
+use super::array_deque::ArrayDeque;
+use super::fixed_bit_set::FixedBitSet;
+
+/// The index of a node of a [`Graph`].
+///
+/// [`Graph`]: crate::containers::Graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIndex(u32);
+
+impl NodeIndex {
+    /// Returns the number of the node, which is less than
+    /// [`Graph::node_bound()`].
+    ///
+    /// [`Graph::node_bound()`]: crate::containers::Graph::node_bound
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<(), ()> = Graph::new_directed();
+    /// graph.add_node(());
+    /// assert_eq!(graph.add_node(()).index(), 1);
+    /// ```
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The index of an edge of a [`Graph`].
+///
+/// [`Graph`]: crate::containers::Graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeIndex(u32);
+
+impl EdgeIndex {
+    /// Returns the number of the edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// assert_eq!(graph.add_edge(a, a, ()).index(), 0);
+    /// ```
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<N> {
+    weight: N,
+    // The edges that leave the node, or all the edges that touch it in an
+    // undirected graph.
+    outgoing: Vec<EdgeIndex>,
+    // The edges that enter the node, empty in an undirected graph.
+    incoming: Vec<EdgeIndex>,
+}
+
+#[derive(Debug, Clone)]
+struct Edge<E> {
+    weight: E,
+    source: NodeIndex,
+    target: NodeIndex,
+}
+
+/// Removes the edge from the list of edges of a node.
+fn unlink(edges: &mut Vec<EdgeIndex>, edge: EdgeIndex) {
+    if let Some(at) = edges.iter().position(|&e| e == edge) {
+        edges.swap_remove(at);
+    }
+}
+
+/// A directed or undirected graph with data on its nodes and edges, stored as
+/// adjacency lists.
+///
+/// The nodes and the edges are kept in two arrays and are referred to by
+/// their positions in them, and each node lists the edges that leave it and
+/// the edges that enter it. An edge of an undirected graph is listed once by
+/// each of its ends, and can be followed from either. Several edges can join
+/// the same nodes, and an edge can join a node to itself.
+///
+/// Removing a node or an edge leaves a hole in its array, which the next
+/// node or edge added takes, so the index of a removed node or edge may refer
+/// to another one later.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Graph;
+///
+/// let mut graph = Graph::new_undirected();
+/// let paris = graph.add_node("Paris");
+/// let lyon = graph.add_node("Lyon");
+/// let nice = graph.add_node("Nice");
+/// graph.add_edge(paris, lyon, 465);
+/// graph.add_edge(lyon, nice, 470);
+///
+/// let reachable: Vec<_> = graph.bfs(nice).map(|n| graph[n]).collect();
+/// assert_eq!(reachable, ["Nice", "Lyon", "Paris"]);
+/// assert_eq!(graph.find_edge(nice, lyon).map(|e| graph[e]), Some(470));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    nodes: Vec<Option<Node<N>>>,
+    edges: Vec<Option<Edge<E>>>,
+    // The holes in `nodes` and `edges`.
+    free_nodes: Vec<NodeIndex>,
+    free_edges: Vec<EdgeIndex>,
+    node_count: usize,
+    edge_count: usize,
+    directed: bool,
+}
+
+impl<N, E> Graph<N, E> {
+    /// Creates an empty directed Graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let graph: Graph<(), ()> = Graph::new_directed();
+    /// assert!(graph.is_directed());
+    /// ```
+    pub fn new_directed() -> Self {
+        Self::new(true)
+    }
+
+    /// Creates an empty undirected Graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let graph: Graph<(), ()> = Graph::new_undirected();
+    /// assert!(!graph.is_directed());
+    /// ```
+    pub fn new_undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            free_nodes: Vec::new(),
+            free_edges: Vec::new(),
+            node_count: 0,
+            edge_count: 0,
+            directed,
+        }
+    }
+
+    /// Returns true if the edges of the graph go from their source to their
+    /// target only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let graph: Graph<(), ()> = Graph::new_directed();
+    /// assert!(graph.is_directed());
+    /// ```
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// Returns the number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// graph.add_node('a');
+    /// assert_eq!(graph.node_count(), 1);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns the number of edges in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// graph.add_edge(a, a, 1);
+    /// assert_eq!(graph.edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Returns a number greater than the indices of all the nodes, which is
+    /// the size of an array indexed by the nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// graph.add_node('b');
+    /// graph.remove_node(a);
+    /// assert_eq!(graph.node_count(), 1);
+    /// assert_eq!(graph.node_bound(), 2);
+    /// ```
+    pub fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Adds a node with the data to the graph and returns its index.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// assert_eq!(graph.node_weight(a), Some(&'a'));
+    /// ```
+    pub fn add_node(&mut self, weight: N) -> NodeIndex {
+        let node = Node {
+            weight,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        };
+        self.node_count += 1;
+        match self.free_nodes.pop() {
+            Some(index) => {
+                self.nodes[index.index()] = Some(node);
+                index
+            }
+            None => {
+                let index =
+                    u32::try_from(self.nodes.len()).expect("Capacity overflow");
+                self.nodes.push(Some(node));
+                NodeIndex(index)
+            }
+        }
+    }
+
+    /// Adds an edge with the data from the source to the target and returns
+    /// its index.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source or the target is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ab = graph.add_edge(a, b, 5);
+    /// assert_eq!(graph.edge_endpoints(ab), Some((a, b)));
+    /// ```
+    pub fn add_edge(
+        &mut self,
+        source: NodeIndex,
+        target: NodeIndex,
+        weight: E,
+    ) -> EdgeIndex {
+        assert!(
+            self.contains_node(source) && self.contains_node(target),
+            "Invalid node"
+        );
+        let edge = Edge {
+            weight,
+            source,
+            target,
+        };
+        let index = match self.free_edges.pop() {
+            Some(index) => {
+                self.edges[index.index()] = Some(edge);
+                index
+            }
+            None => {
+                let index =
+                    u32::try_from(self.edges.len()).expect("Capacity overflow");
+                self.edges.push(Some(edge));
+                EdgeIndex(index)
+            }
+        };
+        self.edge_count += 1;
+        self.node_mut(source).outgoing.push(index);
+        if self.directed {
+            self.node_mut(target).incoming.push(index);
+        } else if source != target {
+            self.node_mut(target).outgoing.push(index);
+        }
+        index
+    }
+
+    /// Removes the node and all its edges from the graph, and returns its
+    /// data, or None if it is not in the graph.
+    ///
+    /// This operation should compute in O(e) time, where e is the number of
+    /// edges of the node and of their other ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// graph.add_edge(a, b, ());
+    /// assert_eq!(graph.remove_node(b), Some('b'));
+    /// assert_eq!(graph.remove_node(b), None);
+    /// assert_eq!(graph.edge_count(), 0);
+    /// ```
+    pub fn remove_node(&mut self, node: NodeIndex) -> Option<N> {
+        let slot = self.nodes.get_mut(node.index())?.take()?;
+        self.node_count -= 1;
+        self.free_nodes.push(node);
+        for edge in slot.outgoing.into_iter().chain(slot.incoming) {
+            // A loop is listed twice by a directed node.
+            if self.edges[edge.index()].is_some() {
+                self.remove_edge(edge);
+            }
+        }
+        Some(slot.weight)
+    }
+
+    /// Removes the edge from the graph and returns its data, or None if it is
+    /// not in the graph.
+    ///
+    /// This operation should compute in O(e) time, where e is the number of
+    /// edges of its ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_undirected();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ab = graph.add_edge(a, b, 5);
+    /// assert_eq!(graph.remove_edge(ab), Some(5));
+    /// assert_eq!(graph.neighbors(b).count(), 0);
+    /// ```
+    pub fn remove_edge(&mut self, edge: EdgeIndex) -> Option<E> {
+        let slot = self.edges.get_mut(edge.index())?.take()?;
+        self.edge_count -= 1;
+        self.free_edges.push(edge);
+        let directed = self.directed;
+        if let Some(Some(source)) = self.nodes.get_mut(slot.source.index()) {
+            unlink(&mut source.outgoing, edge);
+        }
+        if let Some(Some(target)) = self.nodes.get_mut(slot.target.index()) {
+            if directed {
+                unlink(&mut target.incoming, edge);
+            } else {
+                unlink(&mut target.outgoing, edge);
+            }
+        }
+        Some(slot.weight)
+    }
+
+    /// Returns true if the node is in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// assert!(graph.contains_node(a));
+    /// graph.remove_node(a);
+    /// assert!(!graph.contains_node(a));
+    /// ```
+    pub fn contains_node(&self, node: NodeIndex) -> bool {
+        matches!(self.nodes.get(node.index()), Some(Some(_)))
+    }
+
+    /// Returns a reference to the data of the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// assert_eq!(graph.node_weight(a), Some(&'a'));
+    /// ```
+    pub fn node_weight(&self, node: NodeIndex) -> Option<&N> {
+        match self.nodes.get(node.index()) {
+            Some(Some(node)) => Some(&node.weight),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the data of the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node(1);
+    /// *graph.node_weight_mut(a).unwrap() += 1;
+    /// assert_eq!(graph[a], 2);
+    /// ```
+    pub fn node_weight_mut(&mut self, node: NodeIndex) -> Option<&mut N> {
+        match self.nodes.get_mut(node.index()) {
+            Some(Some(node)) => Some(&mut node.weight),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the data of the edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let aa = graph.add_edge(a, a, 'x');
+    /// assert_eq!(graph.edge_weight(aa), Some(&'x'));
+    /// ```
+    pub fn edge_weight(&self, edge: EdgeIndex) -> Option<&E> {
+        match self.edges.get(edge.index()) {
+            Some(Some(edge)) => Some(&edge.weight),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the data of the edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let aa = graph.add_edge(a, a, 1);
+    /// *graph.edge_weight_mut(aa).unwrap() += 1;
+    /// assert_eq!(graph[aa], 2);
+    /// ```
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex) -> Option<&mut E> {
+        match self.edges.get_mut(edge.index()) {
+            Some(Some(edge)) => Some(&mut edge.weight),
+            _ => None,
+        }
+    }
+
+    /// Returns the source and the target of the edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ba = graph.add_edge(b, a, ());
+    /// assert_eq!(graph.edge_endpoints(ba), Some((b, a)));
+    /// ```
+    pub fn edge_endpoints(
+        &self,
+        edge: EdgeIndex,
+    ) -> Option<(NodeIndex, NodeIndex)> {
+        match self.edges.get(edge.index()) {
+            Some(Some(edge)) => Some((edge.source, edge.target)),
+            _ => None,
+        }
+    }
+
+    /// Returns an edge from the source to the target, or between them in an
+    /// undirected graph.
+    ///
+    /// This operation should compute in O(e) time, where e is the number of
+    /// edges of the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ab = graph.add_edge(a, b, ());
+    /// assert_eq!(graph.find_edge(a, b), Some(ab));
+    /// assert_eq!(graph.find_edge(b, a), None);
+    /// ```
+    pub fn find_edge(
+        &self,
+        source: NodeIndex,
+        target: NodeIndex,
+    ) -> Option<EdgeIndex> {
+        self.edges(source)
+            .find(|&(_, other, _)| other == target)
+            .map(|(edge, _, _)| edge)
+    }
+
+    /// Returns an iterator over the edges that leave the node, or all its
+    /// edges in an undirected graph, with the indices of their other ends and
+    /// their data. The iterator is empty if the node is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_undirected();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ab = graph.add_edge(a, b, 5);
+    /// assert_eq!(graph.edges(b).collect::<Vec<_>>(), [(ab, a, &5)]);
+    /// ```
+    pub fn edges(&self, node: NodeIndex) -> Edges<'_, E> {
+        let edges: &[EdgeIndex] = match self.nodes.get(node.index()) {
+            Some(Some(node)) => &node.outgoing,
+            _ => &[],
+        };
+        Edges {
+            graph_edges: &self.edges,
+            node,
+            edges: edges.iter(),
+        }
+    }
+
+    /// Returns an iterator over the edges that enter the node, or all its
+    /// edges in an undirected graph, with the indices of their other ends and
+    /// their data. The iterator is empty if the node is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let ab = graph.add_edge(a, b, 5);
+    /// let incoming: Vec<_> = graph.incoming_edges(b).collect();
+    /// assert_eq!(incoming, [(ab, a, &5)]);
+    /// assert_eq!(graph.incoming_edges(a).count(), 0);
+    /// ```
+    pub fn incoming_edges(&self, node: NodeIndex) -> Edges<'_, E> {
+        if !self.directed {
+            return self.edges(node);
+        }
+        let edges: &[EdgeIndex] = match self.nodes.get(node.index()) {
+            Some(Some(node)) => &node.incoming,
+            _ => &[],
+        };
+        Edges {
+            graph_edges: &self.edges,
+            node,
+            edges: edges.iter(),
+        }
+    }
+
+    /// Returns an iterator over the nodes that the edges of
+    /// [`Graph::edges()`] lead to. A node joined by several edges is returned
+    /// once per edge.
+    ///
+    /// [`Graph::edges()`]: crate::containers::Graph::edges
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let c = graph.add_node('c');
+    /// graph.add_edge(a, b, ());
+    /// graph.add_edge(a, c, ());
+    /// graph.add_edge(b, c, ());
+    /// let mut neighbors: Vec<_> = graph.neighbors(a).collect();
+    /// neighbors.sort();
+    /// assert_eq!(neighbors, [b, c]);
+    /// ```
+    pub fn neighbors(&self, node: NodeIndex) -> Neighbors<'_, E> {
+        Neighbors {
+            edges: self.edges(node),
+        }
+    }
+
+    /// Returns an iterator over the indices of the nodes, in increasing
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<_, ()> = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let c = graph.add_node('c');
+    /// graph.remove_node(b);
+    /// assert_eq!(graph.node_indices().collect::<Vec<_>>(), [a, c]);
+    /// ```
+    pub fn node_indices(&self) -> NodeIndices<'_, N> {
+        NodeIndices {
+            nodes: self.nodes.iter().enumerate(),
+            len: self.node_count,
+        }
+    }
+
+    /// Returns an iterator over the indices of the edges, in increasing
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let aa = graph.add_edge(a, a, ());
+    /// assert_eq!(graph.edge_indices().collect::<Vec<_>>(), [aa]);
+    /// ```
+    pub fn edge_indices(&self) -> EdgeIndices<'_, E> {
+        EdgeIndices {
+            edges: self.edges.iter().enumerate(),
+            len: self.edge_count,
+        }
+    }
+
+    /// Returns an iterator over the nodes reachable from the start, in
+    /// breadth-first order. The nodes are visited as the iterator advances.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let nodes: Vec<_> = (0..5).map(|i| graph.add_node(i)).collect();
+    /// graph.add_edge(nodes[0], nodes[1], ());
+    /// graph.add_edge(nodes[0], nodes[2], ());
+    /// graph.add_edge(nodes[1], nodes[3], ());
+    /// graph.add_edge(nodes[4], nodes[0], ());
+    /// let order: Vec<_> = graph.bfs(nodes[0]).map(|n| graph[n]).collect();
+    /// assert_eq!(order, [0, 1, 2, 3]);
+    /// ```
+    pub fn bfs(&self, start: NodeIndex) -> Bfs<'_, N, E> {
+        assert!(self.contains_node(start), "Invalid node");
+        let mut discovered = FixedBitSet::with_capacity(self.node_bound());
+        discovered.insert(start.index());
+        let mut queue = ArrayDeque::new();
+        queue.push_back(start);
+        Bfs {
+            graph: self,
+            discovered,
+            queue,
+        }
+    }
+
+    /// Returns an iterator over the nodes reachable from the start, in
+    /// depth-first preorder. The nodes are visited as the iterator advances,
+    /// and the edges of a node are followed in the order of
+    /// [`Graph::edges()`].
+    ///
+    /// [`Graph::edges()`]: crate::containers::Graph::edges
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let nodes: Vec<_> = (0..5).map(|i| graph.add_node(i)).collect();
+    /// graph.add_edge(nodes[0], nodes[1], ());
+    /// graph.add_edge(nodes[0], nodes[2], ());
+    /// graph.add_edge(nodes[1], nodes[3], ());
+    /// graph.add_edge(nodes[4], nodes[0], ());
+    /// let order: Vec<_> = graph.dfs(nodes[0]).map(|n| graph[n]).collect();
+    /// assert_eq!(order, [0, 1, 3, 2]);
+    /// ```
+    pub fn dfs(&self, start: NodeIndex) -> Dfs<'_, N, E> {
+        assert!(self.contains_node(start), "Invalid node");
+        Dfs {
+            graph: self,
+            visited: FixedBitSet::with_capacity(self.node_bound()),
+            stack: vec![start],
+        }
+    }
+
+    fn node_mut(&mut self, node: NodeIndex) -> &mut Node<N> {
+        self.nodes[node.index()].as_mut().expect("Invalid node")
+    }
+}
+
+impl<N, E> core::ops::Index<NodeIndex> for Graph<N, E> {
+    type Output = N;
+
+    fn index(&self, node: NodeIndex) -> &Self::Output {
+        self.node_weight(node).expect("Invalid node")
+    }
+}
+
+impl<N, E> core::ops::IndexMut<NodeIndex> for Graph<N, E> {
+    fn index_mut(&mut self, node: NodeIndex) -> &mut Self::Output {
+        self.node_weight_mut(node).expect("Invalid node")
+    }
+}
+
+impl<N, E> core::ops::Index<EdgeIndex> for Graph<N, E> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeIndex) -> &Self::Output {
+        self.edge_weight(edge).expect("Invalid edge")
+    }
+}
+
+impl<N, E> core::ops::IndexMut<EdgeIndex> for Graph<N, E> {
+    fn index_mut(&mut self, edge: EdgeIndex) -> &mut Self::Output {
+        self.edge_weight_mut(edge).expect("Invalid edge")
+    }
+}
+
+/// An iterator over the edges of a node of a [`Graph`].
+///
+/// This struct is created by [`Graph::edges()`] and
+/// [`Graph::incoming_edges()`]. See their documentation for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::edges()`]: crate::containers::Graph::edges
+/// [`Graph::incoming_edges()`]: crate::containers::Graph::incoming_edges
+#[derive(Debug)]
+pub struct Edges<'a, E> {
+    graph_edges: &'a [Option<Edge<E>>],
+    node: NodeIndex,
+    edges: slice::Iter<'a, EdgeIndex>,
+}
+
+impl<'a, E> Iterator for Edges<'a, E> {
+    type Item = (EdgeIndex, NodeIndex, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &index = self.edges.next()?;
+        let edge = self.graph_edges[index.index()].as_ref().unwrap();
+        let other = if edge.source == self.node {
+            edge.target
+        } else {
+            edge.source
+        };
+        Some((index, other, &edge.weight))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Edges<'a, E> {}
+
+/// An iterator over the neighbors of a node of a [`Graph`].
+///
+/// This struct is created by [`Graph::neighbors()`]. See its documentation
+/// for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::neighbors()`]: crate::containers::Graph::neighbors
+#[derive(Debug)]
+pub struct Neighbors<'a, E> {
+    edges: Edges<'a, E>,
+}
+
+impl<'a, E> Iterator for Neighbors<'a, E> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.edges.next().map(|(_, node, _)| node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Neighbors<'a, E> {}
+
+/// An iterator over the indices of the nodes of a [`Graph`].
+///
+/// This struct is created by [`Graph::node_indices()`]. See its
+/// documentation for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::node_indices()`]: crate::containers::Graph::node_indices
+#[derive(Debug)]
+pub struct NodeIndices<'a, N> {
+    nodes: Enumerate<slice::Iter<'a, Option<Node<N>>>>,
+    len: usize,
+}
+
+impl<'a, N> Iterator for NodeIndices<'a, N> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, node) in self.nodes.by_ref() {
+            if node.is_some() {
+                self.len -= 1;
+                return Some(NodeIndex(index as u32));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, N> ExactSizeIterator for NodeIndices<'a, N> {}
+
+/// An iterator over the indices of the edges of a [`Graph`].
+///
+/// This struct is created by [`Graph::edge_indices()`]. See its
+/// documentation for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::edge_indices()`]: crate::containers::Graph::edge_indices
+#[derive(Debug)]
+pub struct EdgeIndices<'a, E> {
+    edges: Enumerate<slice::Iter<'a, Option<Edge<E>>>>,
+    len: usize,
+}
+
+impl<'a, E> Iterator for EdgeIndices<'a, E> {
+    type Item = EdgeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, edge) in self.edges.by_ref() {
+            if edge.is_some() {
+                self.len -= 1;
+                return Some(EdgeIndex(index as u32));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, E> ExactSizeIterator for EdgeIndices<'a, E> {}
+
+/// A breadth-first traversal of a [`Graph`].
+///
+/// This struct is created by [`Graph::bfs()`]. See its documentation for
+/// more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::bfs()`]: crate::containers::Graph::bfs
+#[derive(Debug)]
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    // The nodes that were queued.
+    discovered: FixedBitSet,
+    queue: ArrayDeque<NodeIndex>,
+}
+
+impl<'a, N, E> Iterator for Bfs<'a, N, E> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        for next in self.graph.neighbors(node) {
+            if self.discovered.insert(next.index()) {
+                self.queue.push_back(next);
+            }
+        }
+        Some(node)
+    }
+}
+
+/// A depth-first traversal of a [`Graph`].
+///
+/// This struct is created by [`Graph::dfs()`]. See its documentation for
+/// more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::dfs()`]: crate::containers::Graph::dfs
+#[derive(Debug)]
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    visited: FixedBitSet,
+    // The nodes to visit, which may have been visited since they were
+    // pushed.
+    stack: Vec<NodeIndex>,
+}
+
+impl<'a, N, E> Iterator for Dfs<'a, N, E> {
+    type Item = NodeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if !self.visited.insert(node.index()) {
+                continue;
+            }
+            // The neighbors are pushed in reverse, so that the first one is
+            // visited first.
+            let start = self.stack.len();
+            let visited = &self.visited;
+            self.stack.extend(
+                self.graph
+                    .neighbors(node)
+                    .filter(|next| !visited.contains(next.index())),
+            );
+            self.stack[start..].reverse();
+            return Some(node);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    /// Returns the nodes reachable from the start in a graph given as an
+    /// adjacency matrix.
+    fn reachable(matrix: &[Vec<usize>], start: usize) -> Vec<usize> {
+        let mut seen = vec![false; matrix.len()];
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(node) = stack.pop() {
+            for (next, &count) in matrix[node].iter().enumerate() {
+                if count > 0 && !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        (0..matrix.len()).filter(|&i| seen[i]).collect()
+    }
+
+    fn check_random(directed: bool, seed: u64) {
+        let mut rng = SplitMix64::new(seed);
+        let n = 30;
+        let mut graph = if directed {
+            Graph::new_directed()
+        } else {
+            Graph::new_undirected()
+        };
+        let nodes: Vec<_> = (0..n).map(|i| graph.add_node(i)).collect();
+        let mut matrix = vec![vec![0; n]; n];
+        let mut edges: Vec<(EdgeIndex, usize, usize, usize)> = Vec::new();
+        for step in 0..300 {
+            let a = (rng.next_u64() % n as u64) as usize;
+            let b = (rng.next_u64() % n as u64) as usize;
+            if !edges.is_empty() && rng.next_u64().is_multiple_of(4) {
+                let at = (rng.next_u64() % edges.len() as u64) as usize;
+                let (edge, a, b, weight) = edges.swap_remove(at);
+                assert_eq!(graph.remove_edge(edge), Some(weight));
+                matrix[a][b] -= 1;
+                if !directed && a != b {
+                    matrix[b][a] -= 1;
+                }
+                continue;
+            }
+            let edge = graph.add_edge(nodes[a], nodes[b], step);
+            edges.push((edge, a, b, step));
+            matrix[a][b] += 1;
+            if !directed && a != b {
+                matrix[b][a] += 1;
+            }
+        }
+        assert_eq!(graph.edge_count(), edges.len());
+        for a in 0..n {
+            let mut neighbors: Vec<_> =
+                graph.neighbors(nodes[a]).map(|v| v.index()).collect();
+            neighbors.sort_unstable();
+            let mut expected = Vec::new();
+            for (b, &count) in matrix[a].iter().enumerate() {
+                expected.extend((0..count).map(|_| b));
+            }
+            assert_eq!(neighbors, expected);
+
+            let expected = reachable(&matrix, a);
+            for order in [
+                graph.bfs(nodes[a]).collect::<Vec<_>>(),
+                graph.dfs(nodes[a]).collect(),
+            ] {
+                assert_eq!(order[0], nodes[a]);
+                let mut found: Vec<_> =
+                    order.iter().map(|v| v.index()).collect();
+                found.sort_unstable();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn directed_matches_adjacency_matrix() {
+        check_random(true, 5);
+    }
+
+    #[test]
+    fn undirected_matches_adjacency_matrix() {
+        check_random(false, 6);
+    }
+
+    #[test]
+    fn bfs_visits_by_distance() {
+        let mut graph = Graph::new_undirected();
+        let nodes: Vec<_> = (0..10).map(|i| graph.add_node(i)).collect();
+        for i in 0..9 {
+            graph.add_edge(nodes[i], nodes[i + 1], ());
+        }
+        let order: Vec<_> = graph.bfs(nodes[5]).map(|n| graph[n]).collect();
+        let distances: Vec<_> =
+            order.iter().map(|&i: &i32| (i - 5).abs()).collect();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(order.len(), 10);
+    }
+
+    #[test]
+    fn remove_node_removes_its_edges() {
+        let mut graph = Graph::new_directed();
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+        let c = graph.add_node('c');
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, c, 2);
+        graph.add_edge(c, b, 3);
+        graph.add_edge(b, b, 4);
+        assert_eq!(graph.remove_node(b), Some('b'));
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.neighbors(a).count(), 0);
+        assert_eq!(graph.incoming_edges(c).count(), 0);
+        let d = graph.add_node('d');
+        assert_eq!(d, b);
+        assert_eq!(graph.neighbors(d).count(), 0);
+        assert_eq!(graph.node_count(), 3);
+    }
+}
@@ -0,0 +1,449 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// A growable array that stores its elements in leaves of equal size,
+/// referenced by a top array, which is a hashed array tree.
+///
+/// With leaves of `2^k` elements and room for `2^k` leaves in the top
+/// array, the element at index i is at position `i % 2^k` of leaf `i / 2^k`.
+/// Pushing only allocates a new leaf when the last one is full, and once
+/// the top array is full with `4^k` elements, the elements are moved into
+/// leaves twice as large, which costs O(1) amortized time per push. The top
+/// array and the free space of the last leaf are both O(sqrt(n)), so the
+/// array never holds much more memory than its elements, and growing never
+/// needs one allocation as large as all the elements, unlike a [`Vec`] that
+/// doubles its buffer.
+///
+/// [`Vec`]: alloc::vec::Vec
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::HatVec;
+///
+/// let mut v: HatVec<_> = (0..100).collect();
+/// assert_eq!(v.len(), 100);
+/// assert_eq!(v[42], 42);
+///
+/// v[42] = 0;
+/// assert_eq!(v.pop(), Some(99));
+/// assert_eq!(v.iter().sum::<i32>(), (0..99).sum::<i32>() - 42);
+/// ```
+#[derive(Clone)]
+pub struct HatVec<T> {
+    // Every leaf but the last is full.
+    leaves: Vec<Vec<T>>,
+    // The leaves hold `2^shift` elements, and there is room for `2^shift` of
+    // them.
+    shift: u32,
+    len: usize,
+}
+
+impl<T> Default for HatVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for HatVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> HatVec<T> {
+    /// Creates an empty HatVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let v: HatVec<u32> = HatVec::new();
+    /// assert!(v.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            leaves: Vec::new(),
+            shift: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v = HatVec::new();
+    /// v.push(1);
+    /// assert_eq!(v.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the array has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v = HatVec::new();
+    /// assert!(v.is_empty());
+    /// v.push(1);
+    /// assert!(!v.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the array can hold before its leaves
+    /// have to grow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let v: HatVec<_> = (0..10).collect();
+    /// assert_eq!(v.capacity(), 16);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        1 << (2 * self.shift)
+    }
+
+    /// Appends the element to the back of the array.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v = HatVec::new();
+    /// v.push(1);
+    /// v.push(2);
+    /// assert_eq!(v[1], 2);
+    /// ```
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.rebuild(self.shift + 1);
+        }
+        if self.len >> self.shift == self.leaves.len() {
+            self.leaves.push(Vec::with_capacity(1 << self.shift));
+        }
+        self.leaves.last_mut().unwrap().push(value);
+        self.len += 1;
+    }
+
+    /// Removes the last element and returns it, or None if the array is
+    /// empty.
+    ///
+    /// Once the array holds an eighth of its capacity, its elements are
+    /// moved into leaves half as large.
+    ///
+    /// This operation should compute in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v = HatVec::new();
+    /// v.push(1);
+    /// assert_eq!(v.pop(), Some(1));
+    /// assert_eq!(v.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let leaf = self.leaves.last_mut()?;
+        let value = leaf.pop();
+        if leaf.is_empty() {
+            self.leaves.pop();
+        }
+        self.len -= 1;
+        if self.shift > 1 && self.len <= self.capacity() / 8 {
+            self.rebuild(self.shift - 1);
+        }
+        value
+    }
+
+    /// Returns a reference to the element at the index, or None if the index
+    /// is out of bounds.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let v: HatVec<_> = (0..10).collect();
+    /// assert_eq!(v.get(7), Some(&7));
+    /// assert_eq!(v.get(10), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let mask = (1 << self.shift) - 1;
+        Some(&self.leaves[index >> self.shift][index & mask])
+    }
+
+    /// Returns a mutable reference to the element at the index, or None if
+    /// the index is out of bounds.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v: HatVec<_> = (0..10).collect();
+    /// *v.get_mut(7).unwrap() = 70;
+    /// assert_eq!(v[7], 70);
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let mask = (1 << self.shift) - 1;
+        Some(&mut self.leaves[index >> self.shift][index & mask])
+    }
+
+    /// Removes all the elements, and frees the leaves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v: HatVec<_> = (0..10).collect();
+    /// v.clear();
+    /// assert!(v.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the elements of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let v: HatVec<_> = (1..4).collect();
+    /// assert_eq!(v.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            leaves: self.leaves.iter(),
+            leaf: [].iter(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over mutable references to the elements of the
+    /// array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::HatVec;
+    ///
+    /// let mut v: HatVec<_> = (1..4).collect();
+    /// v.iter_mut().for_each(|x| *x *= 10);
+    /// assert_eq!(v.iter().collect::<Vec<_>>(), [&10, &20, &30]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            leaves: self.leaves.iter_mut(),
+            leaf: [].iter_mut(),
+            len: self.len,
+        }
+    }
+
+    /// Moves the elements into leaves of `2^shift` elements.
+    fn rebuild(&mut self, shift: u32) {
+        let size = 1 << shift;
+        let mut leaves = Vec::with_capacity(size);
+        for value in mem::take(&mut self.leaves).into_iter().flatten() {
+            if leaves.last().is_none_or(|leaf: &Vec<T>| leaf.len() == size) {
+                leaves.push(Vec::with_capacity(size));
+            }
+            leaves.last_mut().unwrap().push(value);
+        }
+        self.leaves = leaves;
+        self.shift = shift;
+    }
+}
+
+impl<T> Index<usize> for HatVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> IndexMut<usize> for HatVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> FromIterator<T> for HatVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T> Extend<T> for HatVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a HatVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut HatVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the elements of a [`HatVec`].
+///
+/// This struct is created by [`HatVec::iter()`]. See its documentation for
+/// more.
+///
+/// [`HatVec`]: crate::containers::HatVec
+/// [`HatVec::iter()`]: crate::containers::HatVec::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    leaves: slice::Iter<'a, Vec<T>>,
+    leaf: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.leaf.next() {
+                self.len -= 1;
+                return Some(value);
+            }
+            self.leaf = self.leaves.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// An iterator over mutable references to the elements of a [`HatVec`].
+///
+/// This struct is created by [`HatVec::iter_mut()`]. See its documentation
+/// for more.
+///
+/// [`HatVec`]: crate::containers::HatVec
+/// [`HatVec::iter_mut()`]: crate::containers::HatVec::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    leaves: slice::IterMut<'a, Vec<T>>,
+    leaf: slice::IterMut<'a, T>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.leaf.next() {
+                self.len -= 1;
+                return Some(value);
+            }
+            self.leaf = self.leaves.next()?.iter_mut();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_vec() {
+        let mut rng = SplitMix64::new(67);
+        let mut v = HatVec::new();
+        let mut expected = Vec::new();
+        for step in 0..20_000 {
+            match rng.next_u64() % 8 {
+                0..=2 if !expected.is_empty() => {
+                    assert_eq!(v.pop(), expected.pop());
+                }
+                3 if !expected.is_empty() => {
+                    let i = (rng.next_u64() % expected.len() as u64) as usize;
+                    v[i] = step;
+                    expected[i] = step;
+                }
+                _ => {
+                    v.push(step);
+                    expected.push(step);
+                }
+            }
+            assert_eq!(v.len(), expected.len());
+            assert!(v.len() <= v.capacity());
+            assert!(v.leaves.len() <= 1 << v.shift);
+            let i = rng.next_u64() as usize % (expected.len() + 1);
+            assert_eq!(v.get(i), expected.get(i));
+        }
+        assert!(v.iter().eq(expected.iter()));
+        while v.pop().is_some() {}
+        assert!(v.capacity() <= 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn index_out_of_bounds() {
+        let v: HatVec<_> = (0..3).collect();
+        let _ = v[3];
+    }
+}
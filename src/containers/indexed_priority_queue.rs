@@ -0,0 +1,409 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::Hash;
+
+use super::linked_hash_map::LinkedHashMap;
+
+/// A priority queue whose elements are keys, each with a priority that can be
+/// looked up and changed through the key.
+///
+/// This is a min-queue: the key with the least priority is always at the
+/// front. The keys and their priorities are kept in a binary heap, and a
+/// [`LinkedHashMap`] records the position of each key in the heap. Every time
+/// an entry moves in the heap, its recorded position is updated, so the entry
+/// of a key can be found in O(1) time and moved to its new place in O(log(n))
+/// time after its priority changes.
+///
+/// This is what algorithms like Dijkstra's and Prim's need, where the
+/// distance of a vertex is lowered many times before it is visited.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::IndexedPriorityQueue;
+///
+/// let mut queue = IndexedPriorityQueue::new();
+/// queue.push("a", 5);
+/// queue.push("b", 3);
+/// queue.push("c", 4);
+///
+/// queue.change_priority("a", 1);
+/// assert_eq!(queue.pop_min(), Some(("a", 1)));
+/// assert_eq!(queue.pop_min(), Some(("b", 3)));
+/// assert!(queue.contains("c"));
+/// ```
+#[derive(Debug)]
+pub struct IndexedPriorityQueue<K, P> {
+    heap: Vec<(K, P)>,
+    positions: LinkedHashMap<K, usize>,
+}
+
+impl<K, P> Default for IndexedPriorityQueue<K, P>
+where
+    K: Hash + Eq + Clone,
+    P: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, P> IndexedPriorityQueue<K, P>
+where
+    K: Hash + Eq + Clone,
+    P: Ord,
+{
+    /// Creates an empty IndexedPriorityQueue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 'a');
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            heap: Vec::new(),
+            positions: LinkedHashMap::default(),
+        }
+    }
+
+    /// Returns the number of keys in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 'a');
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns true if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// assert!(queue.is_empty());
+    /// queue.push(1, 'a');
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns true if the key is in the queue.
+    ///
+    /// The key may be any borrowed form of the queue's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 'a');
+    /// assert!(queue.contains(&1));
+    /// assert!(!queue.contains(&2));
+    /// ```
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.positions.contains_key(key)
+    }
+
+    /// Returns the priority of the key, or None if it is not in the queue.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push(1, 'a');
+    /// assert_eq!(queue.priority(&1), Some(&'a'));
+    /// assert_eq!(queue.priority(&2), None);
+    /// ```
+    pub fn priority<Q>(&self, key: &Q) -> Option<&P>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = *self.positions.get(key)?;
+        Some(&self.heap[pos].1)
+    }
+
+    /// Pushes a key with the given priority onto the queue. If the key is
+    /// already in the queue, its priority is changed instead and the old
+    /// priority is returned.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// assert_eq!(queue.push("a", 2), None);
+    /// assert_eq!(queue.push("a", 1), Some(2));
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if let Some(&pos) = self.positions.get(&key) {
+            return Some(self.set_priority(pos, priority));
+        }
+        let pos = self.heap.len();
+        self.positions.insert(key.clone(), pos);
+        self.heap.push((key, priority));
+        self.sift_up(pos);
+        None
+    }
+
+    /// Returns the key with the least priority along with its priority, or
+    /// None if the queue is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// assert_eq!(queue.peek_min(), None);
+    /// queue.push("a", 2);
+    /// queue.push("b", 1);
+    /// assert_eq!(queue.peek_min(), Some((&"b", &1)));
+    /// ```
+    pub fn peek_min(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|(k, p)| (k, p))
+    }
+
+    /// Removes the key with the least priority and returns it along with its
+    /// priority, or None if the queue is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push("a", 2);
+    /// queue.push("b", 1);
+    /// assert_eq!(queue.pop_min(), Some(("b", 1)));
+    /// assert_eq!(queue.pop_min(), Some(("a", 2)));
+    /// assert_eq!(queue.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(K, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        Some(self.remove_at(0))
+    }
+
+    /// Changes the priority of the key and returns the old priority, or None
+    /// if the key is not in the queue.
+    ///
+    /// The priority can be made either lower or higher.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("b", 2);
+    ///
+    /// assert_eq!(queue.change_priority("a", 3), Some(1));
+    /// assert_eq!(queue.change_priority("c", 3), None);
+    /// assert_eq!(queue.peek_min(), Some((&"b", &2)));
+    /// ```
+    pub fn change_priority<Q>(&mut self, key: &Q, priority: P) -> Option<P>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = *self.positions.get(key)?;
+        Some(self.set_priority(pos, priority))
+    }
+
+    /// Removes the key from the queue and returns it along with its priority,
+    /// or None if it is not in the queue.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IndexedPriorityQueue;
+    ///
+    /// let mut queue = IndexedPriorityQueue::new();
+    /// queue.push("a", 1);
+    /// queue.push("b", 2);
+    ///
+    /// assert_eq!(queue.remove("a"), Some(("a", 1)));
+    /// assert_eq!(queue.remove("a"), None);
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, P)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = *self.positions.get(key)?;
+        Some(self.remove_at(pos))
+    }
+
+    /// Replaces the priority of the entry at `pos`, moves the entry to its
+    /// new place, and returns the old priority.
+    fn set_priority(&mut self, pos: usize, priority: P) -> P {
+        let old = core::mem::replace(&mut self.heap[pos].1, priority);
+        if self.heap[pos].1 < old {
+            self.sift_up(pos);
+        } else {
+            self.sift_down(pos);
+        }
+        old
+    }
+
+    /// Removes the entry at `pos` by replacing it with the last entry, which
+    /// is then moved to its new place.
+    fn remove_at(&mut self, pos: usize) -> (K, P) {
+        let last = self.heap.len() - 1;
+        self.swap(pos, last);
+        let (key, priority) = self.heap.pop().expect("heap is not empty");
+        self.positions.remove(&key);
+        if pos < self.heap.len() {
+            self.sift_up(pos);
+            self.sift_down(pos);
+        }
+        (key, priority)
+    }
+
+    /// Swaps the entries at `a` and `b`, and records their new positions.
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        for &pos in &[a, b] {
+            if let Some(p) = self.positions.get_mut(&self.heap[pos].0) {
+                *p = pos;
+            }
+        }
+    }
+
+    /// Moves the entry at `pos` up until its parent's priority is not greater
+    /// than its own.
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.heap[parent].1 <= self.heap[pos].1 {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    /// Moves the entry at `pos` down until none of its children has a lesser
+    /// priority.
+    fn sift_down(&mut self, mut pos: usize) {
+        let len = self.heap.len();
+        loop {
+            let mut child = 2 * pos + 1;
+            if child >= len {
+                break;
+            }
+            if child + 1 < len && self.heap[child + 1].1 < self.heap[child].1 {
+                child += 1;
+            }
+            if self.heap[pos].1 <= self.heap[child].1 {
+                break;
+            }
+            self.swap(pos, child);
+            pos = child;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<K: Hash + Eq + Clone, P: Ord>(queue: &IndexedPriorityQueue<K, P>) {
+        assert_eq!(queue.positions.len(), queue.heap.len());
+        for (pos, (key, _)) in queue.heap.iter().enumerate() {
+            assert_eq!(queue.positions.get(key), Some(&pos));
+            if pos > 0 {
+                assert!(queue.heap[(pos - 1) / 2].1 <= queue.heap[pos].1);
+            }
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut queue = IndexedPriorityQueue::new();
+        for i in 0..100 {
+            assert_eq!(queue.push(i, (i * 37) % 101), None);
+        }
+        check(&queue);
+
+        let mut popped = Vec::new();
+        while let Some((key, priority)) = queue.pop_min() {
+            assert_eq!(priority, (key * 37) % 101);
+            popped.push(priority);
+            check(&queue);
+        }
+        assert!(popped.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(popped.len(), 100);
+    }
+
+    #[test]
+    fn change_and_remove() {
+        let mut queue = IndexedPriorityQueue::new();
+        for i in 0..50 {
+            queue.push(i, i);
+        }
+        for i in (0..50).step_by(3) {
+            assert_eq!(queue.change_priority(&i, 100 - i), Some(i));
+            check(&queue);
+        }
+        for i in (1..50).step_by(5) {
+            assert!(queue.remove(&i).is_some());
+            check(&queue);
+        }
+        assert_eq!(queue.remove(&1), None);
+        assert_eq!(queue.priority(&3), Some(&97));
+
+        let mut expected: Vec<_> = (0..50)
+            .filter(|i| i % 5 != 1)
+            .map(|i| if i % 3 == 0 { 100 - i } else { i })
+            .collect();
+        expected.sort_unstable();
+        let popped: Vec<_> =
+            core::iter::from_fn(|| queue.pop_min().map(|(_, p)| p)).collect();
+        assert_eq!(popped, expected);
+    }
+}
@@ -0,0 +1,562 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::Range;
+use core::slice;
+
+/// A map from disjoint half-open ranges of keys to values.
+///
+/// The ranges are kept in a vector sorted by their starts, so the range
+/// holding a key is found with a binary search in O(log(n)) time, and the
+/// ranges overlapping a query are next to each other. Inserting a range
+/// overwrites the parts of the ranges it overlaps, splitting the ranges that
+/// stick out of either end, and merges it with the ranges that touch it and
+/// have an equal value, so the map never holds two adjacent ranges with
+/// equal values. Inserting or removing a range shifts the ranges after it,
+/// which takes O(n) time.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::IntervalMap;
+///
+/// let mut memory = IntervalMap::new();
+/// memory.insert(0x0000..0x4000, "text");
+/// memory.insert(0x4000..0x8000, "heap");
+///
+/// // The stack overwrites the end of the heap, which is split.
+/// assert!(memory.insert(0x7000..0x9000, "stack"));
+/// assert_eq!(memory.get(&0x6fff), Some(&"heap"));
+/// assert_eq!(memory.get(&0x7000), Some(&"stack"));
+///
+/// let mapped: Vec<_> = memory.overlapping(0x3000..0x7800).collect();
+/// assert_eq!(
+///     mapped,
+///     [
+///         (&(0x0000..0x4000), &"text"),
+///         (&(0x4000..0x7000), &"heap"),
+///         (&(0x7000..0x9000), &"stack"),
+///     ]
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntervalMap<K, V> {
+    // Sorted, disjoint, and non-empty ranges, where adjacent ranges have
+    // different values.
+    entries: Vec<(Range<K>, V)>,
+}
+
+impl<K, V> Default for IntervalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> IntervalMap<K, V> {
+    /// Creates an empty IntervalMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let map: IntervalMap<u32, &str> = IntervalMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the number of ranges in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..5, 'a');
+    /// map.insert(5..9, 'a');
+    /// map.insert(9..10, 'b');
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map has no ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(0..5, 'a');
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all the ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..5, 'a');
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns an iterator visiting all the ranges of the map in ascending
+    /// order, with their values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let map: IntervalMap<_, _> =
+    ///     vec![(4..6, 'b'), (0..2, 'a')].into_iter().collect();
+    /// let ranges: Vec<_> = map.iter().map(|(r, _)| r.clone()).collect();
+    /// assert_eq!(ranges, [0..2, 4..6]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.entries.iter(),
+        }
+    }
+}
+
+impl<K: Ord, V> IntervalMap<K, V> {
+    /// Returns a reference to the value of the range holding the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..5, 'a');
+    /// assert_eq!(map.get(&4), Some(&'a'));
+    /// assert_eq!(map.get(&5), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    /// Returns the range holding the key, and its value.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..5, 'a');
+    /// map.insert(3..8, 'b');
+    /// assert_eq!(map.get_key_value(&4), Some((&(3..8), &'b')));
+    /// ```
+    pub fn get_key_value(&self, key: &K) -> Option<(&Range<K>, &V)> {
+        let i = self.entries.partition_point(|(r, _)| r.end <= *key);
+        match self.entries.get(i) {
+            Some((r, v)) if r.start <= *key => Some((r, v)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a range of the map holds the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..5, 'a');
+    /// assert!(map.contains_key(&0));
+    /// assert!(!map.contains_key(&5));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    /// Returns an iterator visiting the ranges that overlap the given range
+    /// in ascending order, with their values.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let map: IntervalMap<_, _> =
+    ///     vec![(0..2, 'a'), (4..6, 'b'), (8..9, 'c')].into_iter().collect();
+    /// let values: Vec<_> = map.overlapping(1..5).map(|(_, v)| *v).collect();
+    /// assert_eq!(values, ['a', 'b']);
+    /// assert_eq!(map.overlapping(2..4).count(), 0);
+    /// ```
+    pub fn overlapping(&self, range: Range<K>) -> Iter<'_, K, V> {
+        let (lo, hi) = self.bounds(&range);
+        Iter {
+            iter: self.entries[lo..hi].iter(),
+        }
+    }
+
+    /// Returns the indices of the first range ending after the given range
+    /// starts, and of the first range starting at or after the given range
+    /// ends, so the ranges between them overlap it. An empty range overlaps
+    /// nothing.
+    fn bounds(&self, range: &Range<K>) -> (usize, usize) {
+        let lo = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        if range.start >= range.end {
+            return (lo, lo);
+        }
+        let hi = self.entries.partition_point(|(r, _)| r.start < range.end);
+        (lo, hi)
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> IntervalMap<K, V> {
+    /// Removes the parts of the ranges that overlap the given range, and
+    /// returns true if there were any. A range that sticks out of both ends
+    /// of the given range is split in two.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// assert!(map.remove(3..5));
+    /// assert!(!map.remove(3..5));
+    ///
+    /// let ranges: Vec<_> = map.iter().map(|(r, _)| r.clone()).collect();
+    /// assert_eq!(ranges, [0..3, 5..10]);
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) -> bool {
+        let (lo, hi) = self.bounds(&range);
+        if lo == hi {
+            return false;
+        }
+        let (left, right) = self.split_ends(lo, hi, &range);
+        self.entries.splice(lo..hi, left.into_iter().chain(right));
+        true
+    }
+
+    /// Returns the parts of the overlapping ranges between the indices that
+    /// stick out of the start and the end of the given range.
+    #[allow(clippy::type_complexity)]
+    fn split_ends(
+        &self,
+        lo: usize,
+        hi: usize,
+        range: &Range<K>,
+    ) -> (Option<(Range<K>, V)>, Option<(Range<K>, V)>) {
+        let (first, value) = &self.entries[lo];
+        let left = if first.start < range.start {
+            Some((first.start.clone()..range.start.clone(), value.clone()))
+        } else {
+            None
+        };
+        let (last, value) = &self.entries[hi - 1];
+        let right = if last.end > range.end {
+            Some((range.end.clone()..last.end.clone(), value.clone()))
+        } else {
+            None
+        };
+        (left, right)
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> IntervalMap<K, V> {
+    /// Maps the range to the value, and returns true if it overlapped other
+    /// ranges. The overlapped parts of those ranges are overwritten, and the
+    /// range is merged with the ranges that overlap or touch it and have an
+    /// equal value. Inserting an empty range does nothing.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalMap;
+    ///
+    /// let mut calendar = IntervalMap::new();
+    /// assert!(!calendar.insert(9..12, "work"));
+    /// assert!(!calendar.insert(13..17, "work"));
+    /// assert!(calendar.insert(11..14, "work"));
+    /// assert!(calendar.insert(12..13, "lunch"));
+    ///
+    /// let day: Vec<_> =
+    ///     calendar.iter().map(|(r, v)| (r.clone(), *v)).collect();
+    /// assert_eq!(
+    ///     day,
+    ///     [(9..12, "work"), (12..13, "lunch"), (13..17, "work")]
+    /// );
+    /// ```
+    pub fn insert(&mut self, range: Range<K>, value: V) -> bool {
+        if range.start >= range.end {
+            return false;
+        }
+        let (mut lo, mut hi) = self.bounds(&range);
+        let overlapped = lo < hi;
+        let (mut left, mut right) = if overlapped {
+            self.split_ends(lo, hi, &range)
+        } else {
+            (None, None)
+        };
+        let Range { mut start, mut end } = range;
+        // A part sticking out with an equal value is merged into the range.
+        if left.as_ref().is_some_and(|(_, v)| *v == value) {
+            start = left.take().unwrap().0.start;
+        }
+        if right.as_ref().is_some_and(|(_, v)| *v == value) {
+            end = right.take().unwrap().0.end;
+        }
+        // So is a range ending where the range starts, or starting where it
+        // ends, unless a part sticking out is kept between them.
+        if left.is_none() && lo > 0 {
+            let (r, v) = &self.entries[lo - 1];
+            if r.end == start && *v == value {
+                start = r.start.clone();
+                lo -= 1;
+            }
+        }
+        if right.is_none() && hi < self.entries.len() {
+            let (r, v) = &self.entries[hi];
+            if r.start == end && *v == value {
+                end = r.end.clone();
+                hi += 1;
+            }
+        }
+        let entries = left
+            .into_iter()
+            .chain(Some((start..end, value)))
+            .chain(right);
+        self.entries.splice(lo..hi, entries);
+        overlapped
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> FromIterator<(Range<K>, V)>
+    for IntervalMap<K, V>
+{
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> Extend<(Range<K>, V)>
+    for IntervalMap<K, V>
+{
+    /// Inserts the ranges in order, so a later range overwrites the parts of
+    /// the earlier ones that it overlaps.
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a IntervalMap<K, V> {
+    type Item = (&'a Range<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the ranges of an [`IntervalMap`], with their values.
+///
+/// This struct is created by [`IntervalMap::iter()`] and
+/// [`IntervalMap::overlapping()`]. See their documentation for more.
+///
+/// [`IntervalMap`]: crate::containers::IntervalMap
+/// [`IntervalMap::iter()`]: IntervalMap::iter
+/// [`IntervalMap::overlapping()`]: IntervalMap::overlapping
+#[derive(Debug, Clone)]
+pub struct Iter<'a, K, V> {
+    iter: slice::Iter<'a, (Range<K>, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(r, v)| (r, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(r, v)| (r, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    const KEYS: u64 = 64;
+
+    fn random_range(rng: &mut SplitMix64) -> Range<u64> {
+        let a = rng.next_u64() % (KEYS + 1);
+        let b = rng.next_u64() % (KEYS + 1);
+        a.min(b)..a.max(b)
+    }
+
+    fn ranges(map: &IntervalMap<u64, char>) -> Vec<(Range<u64>, char)> {
+        map.iter().map(|(r, &v)| (r.clone(), v)).collect()
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn empty_ranges() {
+        let mut map = IntervalMap::new();
+        assert_eq!(map.get(&0), None);
+        assert_eq!(map.overlapping(0..10).count(), 0);
+        assert!(!map.remove(0..10));
+
+        // Empty and reversed ranges hold no key, so they change nothing and
+        // overlap nothing.
+        assert!(!map.insert(5..5, 'a'));
+        assert!(!map.insert(6..5, 'a'));
+        assert!(map.is_empty());
+        map.insert(0..10, 'a');
+        assert!(!map.insert(5..5, 'b'));
+        assert!(!map.remove(5..5));
+        assert!(!map.remove(6..5));
+        assert_eq!(map.overlapping(5..5).count(), 0);
+        assert_eq!(map.overlapping(6..5).count(), 0);
+        assert_eq!(ranges(&map), [(0..10, 'a')]);
+    }
+
+    #[test]
+    fn touching_ranges() {
+        let mut map = IntervalMap::new();
+        map.insert(0..5, 'a');
+
+        // Ranges that only touch do not overlap, and are merged only if
+        // their values are equal.
+        assert!(!map.insert(5..8, 'b'));
+        assert!(!map.insert(8..9, 'b'));
+        assert_eq!(ranges(&map), [(0..5, 'a'), (5..9, 'b')]);
+        assert_eq!(map.get(&4), Some(&'a'));
+        assert_eq!(map.get(&5), Some(&'b'));
+        assert_eq!(map.get(&9), None);
+        assert_eq!(map.overlapping(9..12).count(), 0);
+        assert_eq!(map.overlapping(0..1).count(), 1);
+
+        // A range that fills the gap between two equal ranges merges all
+        // three.
+        map.insert(10..12, 'b');
+        assert!(!map.insert(9..10, 'b'));
+        assert_eq!(ranges(&map), [(0..5, 'a'), (5..12, 'b')]);
+    }
+
+    #[test]
+    fn splitting_ranges() {
+        let mut map = IntervalMap::new();
+        map.insert(0..10, 'a');
+
+        // A range inside another splits it in three, and merges back when it
+        // is overwritten with the same value.
+        assert!(map.insert(3..5, 'b'));
+        assert_eq!(ranges(&map), [(0..3, 'a'), (3..5, 'b'), (5..10, 'a')]);
+        assert!(map.insert(3..5, 'a'));
+        assert_eq!(ranges(&map), [(0..10, 'a')]);
+
+        // Removing the middle of a range splits it in two, and removing
+        // everything leaves nothing behind.
+        assert!(map.remove(0..1));
+        assert!(map.remove(9..20));
+        assert!(map.remove(4..6));
+        assert_eq!(ranges(&map), [(1..4, 'a'), (6..9, 'a')]);
+        assert!(map.remove(0..20));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(680);
+        let mut map = IntervalMap::new();
+        let mut expected = [None; KEYS as usize];
+        for _ in 0..5_000 {
+            let range = random_range(&mut rng);
+            let covered =
+                || range.clone().any(|k| expected[k as usize].is_some());
+            if rng.next_u64().is_multiple_of(3) {
+                assert_eq!(map.remove(range.clone()), covered());
+                for k in range {
+                    expected[k as usize] = None;
+                }
+            } else {
+                let value = rng.next_u64() % 3;
+                assert_eq!(map.insert(range.clone(), value), covered());
+                for k in range {
+                    expected[k as usize] = Some(value);
+                }
+            }
+
+            for w in map.entries.windows(2) {
+                assert!(w[0].0.end <= w[1].0.start);
+                assert!(w[0].0.end < w[1].0.start || w[0].1 != w[1].1);
+            }
+            assert!(map.iter().all(|(r, _)| r.start < r.end));
+            for k in 0..KEYS {
+                assert_eq!(map.get(&k), expected[k as usize].as_ref());
+            }
+
+            let query = random_range(&mut rng);
+            assert!(map
+                .overlapping(query.clone())
+                .all(|(r, _)| r.start < query.end && query.start < r.end));
+            let found: Vec<_> = map
+                .overlapping(query.clone())
+                .flat_map(|(r, v)| r.clone().map(move |k| (k, *v)))
+                .filter(|(k, _)| query.contains(k))
+                .collect();
+            let brute: Vec<_> = query
+                .filter_map(|k| expected[k as usize].map(|v| (k, v)))
+                .collect();
+            assert_eq!(found, brute);
+        }
+    }
+}
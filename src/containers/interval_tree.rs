@@ -0,0 +1,642 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::Range;
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+#[derive(Debug)]
+struct Node<T, V> {
+    range: Range<T>,
+    value: V,
+    // The greatest end of the intervals in the subtree rooted at this node.
+    max: T,
+    height: usize,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+/// A collection of intervals with values, that finds the intervals
+/// overlapping a range or containing a point.
+///
+/// The intervals are half-open ranges `start..end`, kept in an AVL tree
+/// ordered by their starts and then their ends. The same interval may be
+/// inserted more than once. Every node also holds the greatest end in its
+/// subtree, so a query skips the subtrees whose intervals all end before the
+/// queried range starts, and stops at the first interval that starts after
+/// the queried range ends. Reporting the k matching intervals out of n takes
+/// O(log(n) + k) time.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::IntervalTree;
+///
+/// let mut meetings = IntervalTree::new();
+/// meetings.insert(9..10, "standup");
+/// meetings.insert(13..15, "review");
+/// meetings.insert(14..16, "interview");
+///
+/// let busy: Vec<_> =
+///     meetings.find_overlapping(14..15).map(|(_, v)| *v).collect();
+/// assert_eq!(busy, ["review", "interview"]);
+/// let now: Vec<_> = meetings.stab(9).map(|(_, v)| *v).collect();
+/// assert_eq!(now, ["standup"]);
+/// ```
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalTree<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, V> IntervalTree<T, V> {
+    /// Returns the number of intervals in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(0..1, "a");
+    /// tree.insert(0..1, "b");
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no intervals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(0..1, "a");
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator visiting all the intervals of the tree in
+    /// ascending order of their starts, and then their ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let tree: IntervalTree<_, _> =
+    ///     vec![(5..9, 'b'), (1..3, 'a'), (5..6, 'c')].into_iter().collect();
+    /// let ranges: Vec<_> = tree.iter().map(|(r, _)| r.clone()).collect();
+    /// assert_eq!(ranges, [1..3, 5..6, 5..9]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    /// Creates an empty IntervalTree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(0..1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Inserts an interval with its value into the tree. An interval that is
+    /// already in the tree is added again, next to the existing ones.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the interval is greater than its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(2..5, "a");
+    /// assert_eq!(tree.stab(4).count(), 1);
+    /// ```
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        assert!(
+            range.start <= range.end,
+            "Range start is greater than range end"
+        );
+        let node = Box::new(Node {
+            max: range.end.clone(),
+            range,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        });
+        self.root = Some(insert(self.root.take(), node));
+        self.len += 1;
+    }
+
+    /// Removes one occurrence of the interval from the tree and returns its
+    /// value, or None if the interval was not in the tree.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(2..5, "a");
+    /// assert_eq!(tree.remove(&(2..5)), Some("a"));
+    /// assert_eq!(tree.remove(&(2..5)), None);
+    /// ```
+    pub fn remove(&mut self, range: &Range<T>) -> Option<V> {
+        let value = remove(&mut self.root, range)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns an iterator visiting the intervals that overlap the range, in
+    /// ascending order of their starts, and then their ends. Two intervals
+    /// overlap if they have at least one point in common, so an empty
+    /// interval overlaps nothing.
+    ///
+    /// Visiting the k overlapping intervals should compute in O(log(n) + k)
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(0..10, 'a');
+    /// tree.insert(10..20, 'b');
+    /// tree.insert(15..30, 'c');
+    ///
+    /// let found: Vec<_> =
+    ///     tree.find_overlapping(5..16).map(|(_, v)| *v).collect();
+    /// assert_eq!(found, ['a', 'b', 'c']);
+    /// assert_eq!(tree.find_overlapping(30..40).count(), 0);
+    /// ```
+    pub fn find_overlapping(&self, range: Range<T>) -> Overlapping<'_, T, V> {
+        Overlapping::new(&self.root, Query::Range(range))
+    }
+
+    /// Returns an iterator visiting the intervals that contain the point, in
+    /// ascending order of their starts, and then their ends. An interval
+    /// contains its start but not its end.
+    ///
+    /// Visiting the k intervals containing the point should compute in
+    /// O(log(n) + k) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(0..10, 'a');
+    /// tree.insert(10..20, 'b');
+    ///
+    /// let found: Vec<_> = tree.stab(10).map(|(_, v)| *v).collect();
+    /// assert_eq!(found, ['b']);
+    /// ```
+    pub fn stab(&self, point: T) -> Overlapping<'_, T, V> {
+        Overlapping::new(&self.root, Query::Point(point))
+    }
+}
+
+fn height<T, V>(link: &Link<T, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+/// Recomputes the height and the greatest end of the node from its children.
+fn update<T: Ord + Clone, V>(node: &mut Node<T, V>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+    let mut max = &node.range.end;
+    for child in [&node.left, &node.right].iter().copied().flatten() {
+        if child.max > *max {
+            max = &child.max;
+        }
+    }
+    node.max = max.clone();
+}
+
+fn rotate_left<T: Ord + Clone, V>(
+    mut node: Box<Node<T, V>>,
+) -> Box<Node<T, V>> {
+    let mut right = node.right.take().expect("Rotating without a right child");
+    node.right = right.left.take();
+    update(&mut node);
+    right.left = Some(node);
+    update(&mut right);
+    right
+}
+
+fn rotate_right<T: Ord + Clone, V>(
+    mut node: Box<Node<T, V>>,
+) -> Box<Node<T, V>> {
+    let mut left = node.left.take().expect("Rotating without a left child");
+    node.left = left.right.take();
+    update(&mut node);
+    left.right = Some(node);
+    update(&mut left);
+    left
+}
+
+/// Restores the AVL balance of the node, whose subtrees differ in height by
+/// at most 2, and updates its augmented fields.
+fn balance<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    update(&mut node);
+    let (lh, rh) = (height(&node.left), height(&node.right));
+    if lh > rh + 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if height(&left.left) < height(&left.right) {
+            rotate_left(left)
+        } else {
+            left
+        });
+        rotate_right(node)
+    } else if rh > lh + 1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if height(&right.right) < height(&right.left) {
+            rotate_right(right)
+        } else {
+            right
+        });
+        rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn cmp_ranges<T: Ord>(a: &Range<T>, b: &Range<T>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+fn insert<T: Ord + Clone, V>(
+    link: Link<T, V>,
+    new: Box<Node<T, V>>,
+) -> Box<Node<T, V>> {
+    match link {
+        None => new,
+        Some(mut node) => {
+            if cmp_ranges(&new.range, &node.range) == Ordering::Less {
+                node.left = Some(insert(node.left.take(), new));
+            } else {
+                node.right = Some(insert(node.right.take(), new));
+            }
+            balance(node)
+        }
+    }
+}
+
+fn remove<T: Ord + Clone, V>(
+    link: &mut Link<T, V>,
+    range: &Range<T>,
+) -> Option<V> {
+    let node = link.as_mut()?;
+    let value = match cmp_ranges(range, &node.range) {
+        Ordering::Less => remove(&mut node.left, range),
+        Ordering::Greater => remove(&mut node.right, range),
+        Ordering::Equal => {
+            let mut node = link.take().unwrap();
+            *link = match (node.left.take(), node.right.take()) {
+                (None, child) | (child, None) => child,
+                (left, Some(right)) => {
+                    let (mut min, rest) = remove_min(right);
+                    min.left = left;
+                    min.right = rest;
+                    Some(balance(min))
+                }
+            };
+            return Some(node.value);
+        }
+    }?;
+    *link = link.take().map(balance);
+    Some(value)
+}
+
+/// Detaches the node with the least interval from the subtree, and returns
+/// it with the rest of the subtree.
+fn remove_min<T: Ord + Clone, V>(
+    mut node: Box<Node<T, V>>,
+) -> (Box<Node<T, V>>, Link<T, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, rest) = remove_min(left);
+            node.left = rest;
+            (min, Some(balance(node)))
+        }
+    }
+}
+
+impl<T: Ord + Clone, V> FromIterator<(Range<T>, V)> for IntervalTree<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord + Clone, V> Extend<(Range<T>, V)> for IntervalTree<T, V> {
+    fn extend<I: IntoIterator<Item = (Range<T>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<'a, T, V> IntoIterator for &'a IntervalTree<T, V> {
+    type Item = (&'a Range<T>, &'a V);
+    type IntoIter = Iter<'a, T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the intervals of an IntervalTree, in ascending order of
+/// their starts, and then their ends.
+///
+/// This struct is created by [`IntervalTree::iter()`]. See its documentation
+/// for more.
+///
+/// [`IntervalTree::iter()`]: crate::containers::IntervalTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, T, V> {
+    // The nodes whose left subtrees have been visited but which have not been
+    // yielded themselves, with the next one on top.
+    stack: Vec<&'a Node<T, V>>,
+    len: usize,
+}
+
+impl<'a, T, V> Iter<'a, T, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T, V> Iterator for Iter<'a, T, V> {
+    type Item = (&'a Range<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        self.len -= 1;
+        Some((&node.range, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, V> ExactSizeIterator for Iter<'a, T, V> {}
+
+#[derive(Debug)]
+enum Query<T> {
+    Range(Range<T>),
+    Point(T),
+}
+
+impl<T: Ord> Query<T> {
+    /// Returns true if an interval ending at `end` may match the query.
+    fn ends_after(&self, end: &T) -> bool {
+        match self {
+            Query::Range(range) => range.start < *end,
+            Query::Point(point) => point < end,
+        }
+    }
+
+    /// Returns true if an interval starting at `start` may match the query.
+    fn starts_before(&self, start: &T) -> bool {
+        match self {
+            Query::Range(range) => *start < range.end,
+            Query::Point(point) => start <= point,
+        }
+    }
+
+    fn matches(&self, range: &Range<T>) -> bool {
+        let nonempty = match self {
+            Query::Range(query) => query.start < query.end,
+            Query::Point(_) => true,
+        };
+        nonempty
+            && range.start < range.end
+            && self.starts_before(&range.start)
+            && self.ends_after(&range.end)
+    }
+}
+
+/// An iterator over the intervals of an IntervalTree that overlap a range or
+/// contain a point, in ascending order of their starts, and then their ends.
+///
+/// This struct is created by [`IntervalTree::find_overlapping()`] and
+/// [`IntervalTree::stab()`]. See their documentation for more.
+///
+/// [`IntervalTree::find_overlapping()`]: crate::containers::IntervalTree::find_overlapping
+/// [`IntervalTree::stab()`]: crate::containers::IntervalTree::stab
+#[derive(Debug)]
+pub struct Overlapping<'a, T, V> {
+    // Like in `Iter`, but without the subtrees that cannot match.
+    stack: Vec<&'a Node<T, V>>,
+    query: Query<T>,
+}
+
+impl<'a, T: Ord, V> Overlapping<'a, T, V> {
+    fn new(root: &'a Link<T, V>, query: Query<T>) -> Self {
+        let mut iter = Self {
+            stack: Vec::new(),
+            query,
+        };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            if !self.query.ends_after(&node.max) {
+                break;
+            }
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord, V> Iterator for Overlapping<'a, T, V> {
+    type Item = (&'a Range<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            // The intervals after this one start no earlier, so none of them
+            // can match either.
+            if !self.query.starts_before(&node.range.start) {
+                self.stack.clear();
+                return None;
+            }
+            self.push_left_spine(&node.right);
+            if self.query.matches(&node.range) {
+                return Some((&node.range, &node.value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    /// Checks the order, the AVL balance, the heights and the greatest ends of
+    /// the tree, and returns its size.
+    fn check<T: Ord + Clone + fmt::Debug, V>(link: &Link<T, V>) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                if let Some(left) = &node.left {
+                    assert_ne!(
+                        cmp_ranges(&left.range, &node.range),
+                        Ordering::Greater
+                    );
+                }
+                if let Some(right) = &node.right {
+                    assert_ne!(
+                        cmp_ranges(&right.range, &node.range),
+                        Ordering::Less
+                    );
+                }
+                let (lh, rh) = (height(&node.left), height(&node.right));
+                assert!(lh.abs_diff(rh) <= 1);
+                assert_eq!(node.height, 1 + lh.max(rh));
+                let max = [&node.left, &node.right]
+                    .iter()
+                    .copied()
+                    .flatten()
+                    .map(|child| &child.max)
+                    .fold(&node.range.end, |a, b| a.max(b));
+                assert_eq!(node.max, *max);
+                1 + check(&node.left) + check(&node.right)
+            }
+        }
+    }
+
+    fn random_range(rng: &mut SplitMix64) -> Range<u64> {
+        let start = rng.next_u64() % 1000;
+        start..start + rng.next_u64() % 50
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut rng = SplitMix64::new(7);
+        let mut tree = IntervalTree::new();
+        let mut expected = Vec::new();
+        for i in 0..2000 {
+            let range = random_range(&mut rng);
+            tree.insert(range.clone(), i);
+            expected.push((range, i));
+        }
+        assert_eq!(check(&tree.root), 2000);
+
+        for i in 0..1000 {
+            let (range, _) = expected[(i * 7) % expected.len()].clone();
+            let value = tree.remove(&range).unwrap();
+            let at = expected
+                .iter()
+                .position(|(r, v)| *r == range && *v == value)
+                .unwrap();
+            expected.remove(at);
+        }
+        assert_eq!(check(&tree.root), 1000);
+        assert_eq!(tree.remove(&(2000..2001)), None);
+
+        expected.sort_by(|(a, _), (b, _)| cmp_ranges(a, b));
+        for _ in 0..200 {
+            let query = random_range(&mut rng);
+            let mut found: Vec<_> = tree
+                .find_overlapping(query.clone())
+                .map(|(r, v)| (r.clone(), *v))
+                .collect();
+            let mut wanted: Vec<_> = expected
+                .iter()
+                .filter(|(r, _)| r.start < query.end && query.start < r.end)
+                .filter(|(r, _)| r.start < r.end && query.start < query.end)
+                .cloned()
+                .collect();
+            found.sort_by_key(|(r, v)| (r.start, r.end, *v));
+            wanted.sort_by_key(|(r, v)| (r.start, r.end, *v));
+            assert_eq!(found, wanted);
+
+            let point = query.start;
+            let found = tree.stab(point).count();
+            let wanted = expected
+                .iter()
+                .filter(|(r, _)| r.start <= point && point < r.end)
+                .count();
+            assert_eq!(found, wanted);
+        }
+    }
+
+    #[test]
+    fn sorted_inserts_stay_balanced() {
+        let tree: IntervalTree<_, _> =
+            (0..1024).map(|i| (i..i + 2, i)).collect();
+        assert_eq!(check(&tree.root), 1024);
+        assert_eq!(height(&tree.root), 11);
+        let found: Vec<_> = tree.stab(500).map(|(_, v)| *v).collect();
+        assert_eq!(found, [499, 500]);
+    }
+
+    #[test]
+    fn empty_intervals() {
+        let mut tree = IntervalTree::new();
+        tree.insert(5..5, ());
+        tree.insert(3..7, ());
+        assert_eq!(tree.stab(5).count(), 1);
+        assert_eq!(tree.find_overlapping(5..5).count(), 0);
+        assert_eq!(tree.len(), 2);
+    }
+}
@@ -0,0 +1,509 @@
+use alloc::vec::Vec;
+use core::slice;
+
+/// The index of a missing node.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node<const K: usize, V> {
+    point: [f64; K],
+    value: V,
+    left: usize,
+    right: usize,
+}
+
+/// Returns the squared Euclidean distance between the points.
+fn distance2<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// A binary tree of points in `K` dimensions, which splits the space on one
+/// coordinate at each level, cycling through the coordinates.
+///
+/// A node at depth `d` splits on coordinate `d % K`: the points in its left
+/// subtree have a smaller coordinate than it, and the others are in its right
+/// subtree. A query visits the side of a split that holds the query first,
+/// and skips the other side when the distance to the splitting plane already
+/// rules it out. A tree built from all its points at once splits each level
+/// at the median, so its height is O(log(n)); points inserted afterwards are
+/// added as leaves, which can unbalance the tree.
+///
+/// The distances are squared Euclidean distances, and the coordinates must
+/// not be NaN.
+///
+/// The nodes are kept in a vector and link to each other by index.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::KdTree;
+///
+/// let mut tree = KdTree::from_points(vec![
+///     ([0.0, 0.0], "origin"),
+///     ([5.0, 5.0], "far"),
+///     ([1.0, 2.0], "near"),
+/// ]);
+/// tree.insert([-1.0, 0.5], "left");
+///
+/// assert_eq!(tree.nearest(&[0.9, 1.8]).map(|(_, v)| *v), Some("near"));
+/// let closest: Vec<_> =
+///     tree.k_nearest(&[0.0, 0.0], 2).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(closest, ["origin", "left"]);
+/// let inside: Vec<_> =
+///     tree.range(&[0.0, 0.0], &[2.0, 2.0]).map(|(_, v)| *v).collect();
+/// assert_eq!(inside.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KdTree<const K: usize, V> {
+    nodes: Vec<Node<K, V>>,
+    root: usize,
+}
+
+impl<const K: usize, V> Default for KdTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const K: usize, V> KdTree<K, V> {
+    /// Creates an empty KdTree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let tree: KdTree<3, ()> = KdTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        assert!(K > 0, "Dimensions must be positive");
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+        }
+    }
+
+    /// Creates a balanced KdTree from the points and their values, by
+    /// splitting each subtree at the median of its points.
+    ///
+    /// This operation should compute in O(n log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let points: Vec<_> = (0..100).map(|i| ([i as f64], i)).collect();
+    /// let tree = KdTree::from_points(points);
+    /// assert_eq!(tree.len(), 100);
+    /// assert_eq!(tree.nearest(&[41.7]).map(|(_, v)| *v), Some(42));
+    /// ```
+    pub fn from_points(points: Vec<([f64; K], V)>) -> Self {
+        let mut tree = Self::new();
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        // The nodes are first numbered in the order of the points, then
+        // linked by the median splits.
+        tree.nodes = points
+            .into_iter()
+            .map(|(point, value)| Node {
+                point,
+                value,
+                left: NIL,
+                right: NIL,
+            })
+            .collect();
+        tree.root = tree.build(&mut order, 0);
+        tree
+    }
+
+    /// Returns the number of points in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let mut tree = KdTree::new();
+    /// tree.insert([1.0, 2.0], ());
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the tree has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let mut tree = KdTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert([1.0, 2.0], ());
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the points and their values, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let tree = KdTree::from_points(vec![([1.0], 'a'), ([2.0], 'b')]);
+    /// let mut values: Vec<_> = tree.iter().map(|(_, v)| *v).collect();
+    /// values.sort();
+    /// assert_eq!(values, ['a', 'b']);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.nodes.iter(),
+        }
+    }
+
+    /// Adds the point and its value to the tree as a new leaf. A point equal
+    /// to one in the tree is added again.
+    ///
+    /// This operation should compute in O(h) time, where h is the height of
+    /// the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let mut tree = KdTree::new();
+    /// tree.insert([1.0, 2.0], "a");
+    /// tree.insert([1.0, 2.0], "b");
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn insert(&mut self, point: [f64; K], value: V) {
+        let index = self.nodes.len();
+        if self.root == NIL {
+            self.root = index;
+        } else {
+            let mut parent = self.root;
+            let mut depth = 0;
+            loop {
+                let axis = depth % K;
+                let node = &mut self.nodes[parent];
+                let link = if point[axis] < node.point[axis] {
+                    &mut node.left
+                } else {
+                    &mut node.right
+                };
+                if *link == NIL {
+                    *link = index;
+                    break;
+                }
+                parent = *link;
+                depth += 1;
+            }
+        }
+        self.nodes.push(Node {
+            point,
+            value,
+            left: NIL,
+            right: NIL,
+        });
+    }
+
+    /// Returns the point closest to the query and its value, or None if the
+    /// tree is empty. Ties are broken arbitrarily.
+    ///
+    /// This operation should compute in O(log(n)) expected time for points
+    /// spread evenly in a balanced tree, and O(n) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let tree =
+    ///     KdTree::from_points(vec![([0.0, 0.0], 'a'), ([3.0, 4.0], 'b')]);
+    /// assert_eq!(tree.nearest(&[2.0, 3.0]), Some((&[3.0, 4.0], &'b')));
+    /// ```
+    pub fn nearest(&self, query: &[f64; K]) -> Option<(&[f64; K], &V)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// Returns the `k` points closest to the query and their values, from the
+    /// closest to the farthest. Fewer points are returned if the tree has
+    /// fewer than `k`.
+    ///
+    /// This operation should compute in O(k log(n)) expected time for points
+    /// spread evenly in a balanced tree, and O(nk) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let points: Vec<_> = (0..10).map(|i| ([i as f64, 0.0], i)).collect();
+    /// let tree = KdTree::from_points(points);
+    /// let closest: Vec<_> =
+    ///     tree.k_nearest(&[4.2, 1.0], 3).iter().map(|(_, v)| **v).collect();
+    /// assert_eq!(closest, [4, 5, 3]);
+    /// ```
+    pub fn k_nearest(
+        &self,
+        query: &[f64; K],
+        k: usize,
+    ) -> Vec<(&[f64; K], &V)> {
+        let mut best = Vec::with_capacity(k);
+        if k > 0 {
+            self.search(self.root, 0, query, k, &mut best);
+        }
+        best.into_iter()
+            .map(|(_, i)| {
+                let node = &self.nodes[i];
+                (&node.point, &node.value)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the points inside the box between the
+    /// corners, borders included, and their values, in no particular order.
+    ///
+    /// This operation should compute in O(n^(1 - 1/K) + m) time in a balanced
+    /// tree, where m is the number of points in the box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::KdTree;
+    ///
+    /// let points: Vec<_> =
+    ///     (0..25).map(|i| ([(i % 5) as f64, (i / 5) as f64], i)).collect();
+    /// let tree = KdTree::from_points(points);
+    /// let mut inside: Vec<_> =
+    ///     tree.range(&[1.0, 1.0], &[2.0, 2.0]).map(|(_, v)| *v).collect();
+    /// inside.sort();
+    /// assert_eq!(inside, [6, 7, 11, 12]);
+    /// ```
+    pub fn range<'a>(
+        &'a self,
+        min: &[f64; K],
+        max: &[f64; K],
+    ) -> Range<'a, K, V> {
+        let mut stack = Vec::new();
+        if self.root != NIL {
+            stack.push((self.root, 0));
+        }
+        Range {
+            tree: self,
+            min: *min,
+            max: *max,
+            stack,
+        }
+    }
+
+    /// Links the nodes in `order` into a subtree split on the coordinate of
+    /// `depth`, and returns its root.
+    fn build(&mut self, order: &mut [usize], depth: usize) -> usize {
+        if order.is_empty() {
+            return NIL;
+        }
+        let axis = depth % K;
+        let mid = order.len() / 2;
+        let nodes = &self.nodes;
+        order.select_nth_unstable_by(mid, |&a, &b| {
+            nodes[a].point[axis].total_cmp(&nodes[b].point[axis])
+        });
+        // The points equal to the median on the axis are moved after it, so
+        // that the left subtree only has smaller points, as in `insert`.
+        let median = nodes[order[mid]].point[axis];
+        let mut smaller = 0;
+        for j in 0..mid {
+            if nodes[order[j]].point[axis] < median {
+                order.swap(smaller, j);
+                smaller += 1;
+            }
+        }
+        order.swap(smaller, mid);
+        let root = order[smaller];
+        let (left, rest) = order.split_at_mut(smaller);
+        let right = &mut rest[1..];
+        let left_root = self.build(left, depth + 1);
+        let right_root = self.build(right, depth + 1);
+        self.nodes[root].left = left_root;
+        self.nodes[root].right = right_root;
+        root
+    }
+
+    /// Keeps the `k` closest nodes of the subtree, sorted by their distance
+    /// to the query, in `best`.
+    fn search(
+        &self,
+        i: usize,
+        depth: usize,
+        query: &[f64; K],
+        k: usize,
+        best: &mut Vec<(f64, usize)>,
+    ) {
+        if i == NIL {
+            return;
+        }
+        let node = &self.nodes[i];
+        let d = distance2(query, &node.point);
+        if best.len() < k || d < best[best.len() - 1].0 {
+            if best.len() == k {
+                best.pop();
+            }
+            let at = best.partition_point(|&(other, _)| other <= d);
+            best.insert(at, (d, i));
+        }
+        let axis = depth % K;
+        let diff = query[axis] - node.point[axis];
+        let (near, far) = if diff < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        self.search(near, depth + 1, query, k, best);
+        if best.len() < k || diff * diff < best[best.len() - 1].0 {
+            self.search(far, depth + 1, query, k, best);
+        }
+    }
+}
+
+impl<'a, const K: usize, V> IntoIterator for &'a KdTree<K, V> {
+    type Item = (&'a [f64; K], &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the points of a KdTree and their values.
+///
+/// This struct is created by [`KdTree::iter()`]. See its documentation for
+/// more.
+///
+/// [`KdTree::iter()`]: crate::containers::KdTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, const K: usize, V> {
+    iter: slice::Iter<'a, Node<K, V>>,
+}
+
+impl<'a, const K: usize, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a [f64; K], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|node| (&node.point, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, const K: usize, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// An iterator over the points of a KdTree inside a box.
+///
+/// This struct is created by [`KdTree::range()`]. See its documentation for
+/// more.
+///
+/// [`KdTree::range()`]: crate::containers::KdTree::range
+#[derive(Debug)]
+pub struct Range<'a, const K: usize, V> {
+    tree: &'a KdTree<K, V>,
+    min: [f64; K],
+    max: [f64; K],
+    // The subtrees that may still hold points inside the box, with their
+    // depths.
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, const K: usize, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a [f64; K], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((i, depth)) = self.stack.pop() {
+            let node = &self.tree.nodes[i];
+            let axis = depth % K;
+            if node.left != NIL && self.min[axis] < node.point[axis] {
+                self.stack.push((node.left, depth + 1));
+            }
+            if node.right != NIL && self.max[axis] >= node.point[axis] {
+                self.stack.push((node.right, depth + 1));
+            }
+            let inside = (0..K).all(|j| {
+                self.min[j] <= node.point[j] && node.point[j] <= self.max[j]
+            });
+            if inside {
+                return Some((&node.point, &node.value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn random_point(rng: &mut SplitMix64) -> [f64; 3] {
+        // Few distinct coordinates, so that many points tie on an axis.
+        let mut coordinate = || (rng.next_u64() % 16) as f64;
+        [coordinate(), coordinate(), coordinate()]
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut rng = SplitMix64::new(3);
+        let points: Vec<_> =
+            (0..300).map(|i| (random_point(&mut rng), i)).collect();
+        let mut tree = KdTree::from_points(points.clone());
+        let mut all = points;
+        for i in 300..400 {
+            let point = random_point(&mut rng);
+            tree.insert(point, i);
+            all.push((point, i));
+        }
+        for _ in 0..100 {
+            let query = random_point(&mut rng);
+            let mut expected: Vec<_> =
+                all.iter().map(|(p, _)| distance2(&query, p)).collect();
+            expected.sort_by(f64::total_cmp);
+            let found: Vec<_> = tree
+                .k_nearest(&query, 7)
+                .iter()
+                .map(|(p, _)| distance2(&query, p))
+                .collect();
+            assert_eq!(found, expected[..7]);
+
+            let other = random_point(&mut rng);
+            let min: [f64; 3] = [0, 1, 2].map(|j| query[j].min(other[j]));
+            let max: [f64; 3] = [0, 1, 2].map(|j| query[j].max(other[j]));
+            let mut inside: Vec<_> =
+                tree.range(&min, &max).map(|(_, v)| *v).collect();
+            inside.sort_unstable();
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|(p, _)| {
+                    (0..3).all(|j| min[j] <= p[j] && p[j] <= max[j])
+                })
+                .map(|(_, v)| *v)
+                .collect();
+            assert_eq!(inside, expected);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::mem;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T> {
+    item: T,
+    // The length of the shortest path from this node to a missing child.
+    rank: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn rank<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.rank)
+}
+
+/// Merges two heap-ordered trees along their right spines.
+///
+/// The root with the lesser element becomes the root of the result, and its
+/// right subtree is merged with the other tree. The children are then swapped
+/// if needed so that the right child has the lower rank. Both right spines
+/// have O(log(n)) nodes, which bounds the depth of the recursion.
+fn merge<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    match (a, b) {
+        (None, tree) | (tree, None) => tree,
+        (Some(mut a), Some(mut b)) => {
+            if b.item < a.item {
+                mem::swap(&mut a, &mut b);
+            }
+            a.right = merge(a.right.take(), Some(b));
+            if rank(&a.left) < rank(&a.right) {
+                mem::swap(&mut a.left, &mut a.right);
+            }
+            a.rank = rank(&a.right) + 1;
+            Some(a)
+        }
+    }
+}
+
+/// A priority queue implemented with a leftist heap.
+///
+/// This is a min-heap: the least element is always at the front. The heap is
+/// a binary tree in which no node is less than its parent, and in which the
+/// rank of a left child, the length of its shortest path to a missing node,
+/// is never lower than the rank of its right sibling. The tree therefore
+/// leans to the left, and its right spine is at most logarithmic in length.
+///
+/// Every operation is built on merging two heaps along their right spines:
+/// pushing merges the heap with a single node, and popping merges the two
+/// subtrees of the root.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::LeftistHeap;
+///
+/// let mut a: LeftistHeap<_> = vec![5, 1, 4].into_iter().collect();
+/// let b: LeftistHeap<_> = vec![3, 2].into_iter().collect();
+///
+/// a.merge(b);
+/// assert_eq!(a.len(), 5);
+/// assert_eq!(a.pop(), Some(1));
+/// assert_eq!(a.pop(), Some(2));
+/// ```
+#[derive(Debug)]
+pub struct LeftistHeap<T> {
+    root: Link<T>,
+    len: usize,
+}
+
+impl<T: Ord> Default for LeftistHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for LeftistHeap<T> {
+    /// The left spine can be as long as the heap, so the nodes are freed
+    /// one by one instead of recursively.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> =
+            self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T: Ord> LeftistHeap<T> {
+    /// Creates an empty LeftistHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Moves all the elements of `other` into the heap.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut a: LeftistHeap<_> = vec![1, 3].into_iter().collect();
+    /// let b: LeftistHeap<_> = vec![2, 4].into_iter().collect();
+    /// a.merge(b);
+    /// assert_eq!(a.into_sorted_vec(), [1, 2, 3, 4]);
+    /// ```
+    pub fn merge(&mut self, mut other: LeftistHeap<T>) {
+        self.root = merge(self.root.take(), other.root.take());
+        self.len += mem::take(&mut other.len);
+    }
+
+    /// Pushes an element onto the heap, by merging the heap with a heap that
+    /// only holds the element.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// heap.push(3);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn push(&mut self, item: T) {
+        let node = Box::new(Node {
+            item,
+            rank: 1,
+            left: None,
+            right: None,
+        });
+        self.root = merge(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    /// Returns the least element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.item)
+    }
+
+    /// Removes the least element from the heap and returns it, or None if it
+    /// is empty. The subtrees of the removed root are merged into the new
+    /// heap.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let mut heap = LeftistHeap::new();
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let node = *self.root.take()?;
+        self.root = merge(node.left, node.right);
+        self.len -= 1;
+        Some(node.item)
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LeftistHeap;
+    ///
+    /// let heap: LeftistHeap<_> = vec![3, 1, 2].into_iter().collect();
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len);
+        while let Some(item) = self.pop() {
+            sorted.push(item);
+        }
+        sorted
+    }
+}
+
+impl<T: Ord> FromIterator<T> for LeftistHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<T: Ord> Extend<T> for LeftistHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the tree is heap-ordered and leftist, and returns its size.
+    fn check<T: Ord>(link: &Link<T>) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                for child in [&node.left, &node.right].iter().copied().flatten()
+                {
+                    assert!(node.item <= child.item);
+                }
+                assert!(rank(&node.left) >= rank(&node.right));
+                assert_eq!(node.rank, rank(&node.right) + 1);
+                1 + check(&node.left) + check(&node.right)
+            }
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut heap = LeftistHeap::new();
+        for x in (0..100).map(|x| (x * 37) % 101) {
+            heap.push(x);
+        }
+        assert_eq!(check(&heap.root), heap.len());
+
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            assert_eq!(check(&heap.root), heap.len());
+            popped.push(x);
+        }
+        assert!(popped.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(popped.len(), 100);
+    }
+
+    #[test]
+    fn merge_heaps() {
+        let mut a: LeftistHeap<_> = (0..50).map(|x| x * 2).collect();
+        let b: LeftistHeap<_> = (0..50).map(|x| x * 2 + 1).collect();
+        a.merge(b);
+        assert_eq!(a.len(), 100);
+        assert_eq!(check(&a.root), 100);
+        assert!(a.into_sorted_vec().into_iter().eq(0..100));
+    }
+
+    #[test]
+    fn drop_long_spine() {
+        // Pushing descending elements builds a left spine as long as the heap.
+        let heap: LeftistHeap<_> = (0..100_000).rev().collect();
+        assert_eq!(heap.peek(), Some(&0));
+    }
+}
@@ -0,0 +1,544 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use super::linked_hash_map::{DefaultHashBuilder, LinkedHashMap};
+
+const NIL: usize = usize::MAX;
+
+/// An entry of [`LfuCache`], linked with the other entries that were used as
+/// many times.
+///
+/// [`LfuCache`]: crate::containers::LfuCache
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    bucket: usize,
+    prev: usize,
+    next: usize,
+}
+
+/// The list of the entries of [`LfuCache`] that were used a given number of
+/// times, from the least to the most recently used one.
+///
+/// [`LfuCache`]: crate::containers::LfuCache
+#[derive(Debug)]
+struct Bucket {
+    frequency: usize,
+    head: usize,
+    tail: usize,
+    prev: usize,
+    next: usize,
+}
+
+/// A cache that holds a fixed number of entries, and makes room for a new
+/// entry by evicting the one that was used the least number of times. Among
+/// the entries used as many times, the one used the longest time ago is
+/// evicted.
+///
+/// The entries used as many times are linked in a bucket, and the buckets are
+/// linked in increasing order of their frequencies. Using an entry moves it to
+/// the next bucket, which is created if its frequency is not the next one, and
+/// the entry to evict is the first one of the first bucket, so every
+/// operation takes O(1) time besides hashing the key.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::LfuCache;
+///
+/// let mut cache = LfuCache::new(2);
+/// cache.put("apple", 3);
+/// cache.put("banana", 5);
+/// cache.get("apple");
+/// cache.get("apple");
+/// cache.get("banana");
+///
+/// // "banana" was used fewer times than "apple".
+/// cache.put("cherry", 7);
+/// assert_eq!(cache.get("banana"), None);
+/// assert_eq!(cache.frequency("apple"), Some(3));
+/// ```
+#[derive(Debug)]
+pub struct LfuCache<K, V, S = DefaultHashBuilder> {
+    // The positions of the entries in `nodes`, by their keys.
+    map: LinkedHashMap<K, usize, S>,
+    nodes: Vec<Node<K, V>>,
+    buckets: Vec<Bucket>,
+    free_buckets: Vec<usize>,
+    // The bucket of the least frequency.
+    first: usize,
+    capacity: usize,
+}
+
+impl<K, V> LfuCache<K, V, DefaultHashBuilder>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates an empty LfuCache that holds up to `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let cache: LfuCache<&str, i32> = LfuCache::new(10);
+    /// assert_eq!(cache.capacity(), 10);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> LfuCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Creates an empty LfuCache that holds up to `capacity` entries, and
+    /// uses the given hash builder to hash the keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, LfuCache};
+    ///
+    /// let mut cache = LfuCache::with_hasher(10, FnvBuildHasher::default());
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// ```
+    pub fn with_hasher(capacity: usize, hasher_builder: S) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            map: LinkedHashMap::with_hasher(hasher_builder),
+            nodes: Vec::new(),
+            buckets: Vec::new(),
+            free_buckets: Vec::new(),
+            first: NIL,
+            capacity,
+        }
+    }
+
+    /// Returns the largest number of entries held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let cache: LfuCache<i32, i32> = LfuCache::new(4);
+    /// assert_eq!(cache.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// assert!(cache.is_empty());
+    /// cache.put(1, "a");
+    /// assert!(!cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns a reference to the value of the key, and counts a use of the
+    /// entry.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value of the key, and counts a use
+    /// of the entry.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, 10);
+    /// *cache.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.peek(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+        Some(&mut self.nodes[idx].value)
+    }
+
+    /// Returns a reference to the value of the key, without counting a use
+    /// of the entry.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// assert_eq!(cache.frequency(&1), Some(1));
+    /// ```
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&idx| &self.nodes[idx].value)
+    }
+
+    /// Returns the number of times the entry of the key was used, counting
+    /// its insertion, or None if the cache does not hold the key.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.get(&1);
+    /// cache.put(1, "b");
+    /// assert_eq!(cache.frequency(&1), Some(3));
+    /// assert_eq!(cache.frequency(&2), None);
+    /// ```
+    pub fn frequency<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let &idx = self.map.get(key)?;
+        Some(self.buckets[self.nodes[idx].bucket].frequency)
+    }
+
+    /// Returns true if the cache holds the key, without counting a use of
+    /// the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a key-value pair, and returns the old value of the key if it
+    /// was held, in which case a use of the entry is counted. If the cache
+    /// is full and did not hold the key, the least frequently used entry is
+    /// evicted, and the new entry starts with a single use.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// assert_eq!(cache.put(1, "a"), None);
+    /// assert_eq!(cache.put(1, "b"), Some("a"));
+    /// cache.put(2, "c");
+    /// cache.put(3, "d");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&idx) = self.map.get(&key) {
+            self.touch(idx);
+            return Some(core::mem::replace(&mut self.nodes[idx].value, value));
+        }
+        if self.nodes.len() == self.capacity {
+            let victim = self.buckets[self.first].head;
+            let (key, _) = self.remove_node(victim);
+            self.map.remove(&key);
+        }
+        let bucket = match self.first {
+            first if first != NIL && self.buckets[first].frequency == 1 => {
+                first
+            }
+            first => self.new_bucket(1, NIL, first),
+        };
+        let idx = self.nodes.len();
+        self.nodes.push(Node {
+            key: key.clone(),
+            value,
+            bucket: NIL,
+            prev: NIL,
+            next: NIL,
+        });
+        self.link_node(idx, bucket);
+        self.map.insert(key, idx);
+        None
+    }
+
+    /// Removes the key from the cache, and returns its value if it was held.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LfuCache;
+    ///
+    /// let mut cache = LfuCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.pop(&1), Some("a"));
+    /// assert_eq!(cache.pop(&1), None);
+    /// ```
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.map.remove(key)?;
+        Some(self.remove_node(idx).1)
+    }
+
+    /// Moves the entry at `idx` to the bucket of the next frequency.
+    fn touch(&mut self, idx: usize) {
+        let bucket = self.nodes[idx].bucket;
+        let frequency = self.buckets[bucket].frequency + 1;
+        let next = self.buckets[bucket].next;
+        let target = if next != NIL && self.buckets[next].frequency == frequency
+        {
+            next
+        } else {
+            self.new_bucket(frequency, bucket, next)
+        };
+        self.unlink_node(idx);
+        self.link_node(idx, target);
+    }
+
+    /// Removes the entry at `idx`, whose position is taken by the last entry.
+    /// The key is left in the map.
+    fn remove_node(&mut self, idx: usize) -> (K, V) {
+        self.unlink_node(idx);
+        let node = self.nodes.swap_remove(idx);
+        if idx < self.nodes.len() {
+            let Node {
+                bucket, prev, next, ..
+            } = self.nodes[idx];
+            match prev {
+                NIL => self.buckets[bucket].head = idx,
+                prev => self.nodes[prev].next = idx,
+            }
+            match next {
+                NIL => self.buckets[bucket].tail = idx,
+                next => self.nodes[next].prev = idx,
+            }
+            *self.map.get_mut(&self.nodes[idx].key).unwrap() = idx;
+        }
+        (node.key, node.value)
+    }
+
+    /// Creates an empty bucket of the frequency between the buckets `prev`
+    /// and `next`.
+    fn new_bucket(
+        &mut self,
+        frequency: usize,
+        prev: usize,
+        next: usize,
+    ) -> usize {
+        let bucket = Bucket {
+            frequency,
+            head: NIL,
+            tail: NIL,
+            prev,
+            next,
+        };
+        let b = match self.free_buckets.pop() {
+            Some(b) => {
+                self.buckets[b] = bucket;
+                b
+            }
+            None => {
+                self.buckets.push(bucket);
+                self.buckets.len() - 1
+            }
+        };
+        match prev {
+            NIL => self.first = b,
+            prev => self.buckets[prev].next = b,
+        }
+        if next != NIL {
+            self.buckets[next].prev = b;
+        }
+        b
+    }
+
+    /// Links the entry at `idx` at the end of the bucket.
+    fn link_node(&mut self, idx: usize, bucket: usize) {
+        let tail = self.buckets[bucket].tail;
+        let node = &mut self.nodes[idx];
+        node.bucket = bucket;
+        node.prev = tail;
+        node.next = NIL;
+        match tail {
+            NIL => self.buckets[bucket].head = idx,
+            tail => self.nodes[tail].next = idx,
+        }
+        self.buckets[bucket].tail = idx;
+    }
+
+    /// Unlinks the entry at `idx` from its bucket, and frees the bucket if it
+    /// becomes empty.
+    fn unlink_node(&mut self, idx: usize) {
+        let Node {
+            bucket, prev, next, ..
+        } = self.nodes[idx];
+        match prev {
+            NIL => self.buckets[bucket].head = next,
+            prev => self.nodes[prev].next = next,
+        }
+        match next {
+            NIL => self.buckets[bucket].tail = prev,
+            next => self.nodes[next].prev = prev,
+        }
+        if self.buckets[bucket].head == NIL {
+            let Bucket { prev, next, .. } = self.buckets[bucket];
+            match prev {
+                NIL => self.first = next,
+                prev => self.buckets[prev].next = next,
+            }
+            if next != NIL {
+                self.buckets[next].prev = prev;
+            }
+            self.free_buckets.push(bucket);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_naive_eviction() {
+        let mut rng = SplitMix64::new(101);
+        let mut cache = LfuCache::new(8);
+        // The entries as (key, value, frequency, time of the last use).
+        let mut naive: Vec<(u64, u64, usize, u64)> = Vec::new();
+        for t in 0..3000 {
+            let key = rng.next_u64() % 20;
+            let pos = naive.iter().position(|e| e.0 == key);
+            match rng.next_u64() % 4 {
+                0 | 1 => {
+                    let old = pos.map(|pos| naive[pos].1);
+                    assert_eq!(cache.put(key, t), old);
+                    match pos {
+                        Some(pos) => {
+                            let e = &mut naive[pos];
+                            *e = (key, t, e.2 + 1, t);
+                        }
+                        None => {
+                            if naive.len() == 8 {
+                                let victim = (0..naive.len())
+                                    .min_by_key(|&i| (naive[i].2, naive[i].3))
+                                    .unwrap();
+                                naive.remove(victim);
+                            }
+                            naive.push((key, t, 1, t));
+                        }
+                    }
+                }
+                2 => {
+                    let value = pos.map(|pos| naive[pos].1);
+                    assert_eq!(cache.get(&key).copied(), value);
+                    if let Some(pos) = pos {
+                        naive[pos].2 += 1;
+                        naive[pos].3 = t;
+                    }
+                }
+                _ => {
+                    let value = pos.map(|pos| naive.remove(pos).1);
+                    assert_eq!(cache.pop(&key), value);
+                }
+            }
+            assert_eq!(cache.len(), naive.len());
+            for &(key, value, frequency, _) in &naive {
+                assert_eq!(cache.peek(&key), Some(&value));
+                assert_eq!(cache.frequency(&key), Some(frequency));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity must be positive")]
+    fn zero_capacity() {
+        LfuCache::<i32, i32>::new(0);
+    }
+}
@@ -0,0 +1,672 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use core::mem;
+use core::slice;
+
+use super::linked_hash_map::DefaultHashBuilder;
+
+/// The number of buckets of an empty map, which is a power of two.
+const INITIAL_BUCKETS: usize = 4;
+
+/// The average number of entries per bucket above which a bucket splits.
+const MAX_LOAD_FACTOR: usize = 2;
+
+/// A hash map that grows by one bucket at a time, splitting the buckets in a
+/// fixed order instead of rehashing all of them at once.
+///
+/// The buckets are split in rounds. At the start of round `level`, there are
+/// `n = 4 * 2^level` buckets, and a key belongs to bucket `hash % n`. A split
+/// pointer goes through the buckets in order: whenever an insertion makes
+/// the map hold more than 2 entries per bucket on average, the bucket at the
+/// split pointer, which is not necessarily the one that was inserted into,
+/// moves the keys with `hash % 2n` above `n` to a new bucket at the end, and
+/// the pointer moves on. The buckets before the pointer have thus been split
+/// in this round, and their keys are found with `hash % 2n` instead. When the
+/// pointer reaches `n`, the number of buckets has doubled, and the next round
+/// starts with the pointer back at the first bucket.
+///
+/// Unlike a map that doubles its table, no insertion rehashes more than one
+/// bucket, so the time and memory of growing are spread evenly. Buckets that
+/// are not split yet can have long chains for a while. The map never merges
+/// buckets when entries are removed.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::LinearHashMap;
+///
+/// let mut ages = LinearHashMap::new();
+/// ages.insert("alice", 31);
+/// ages.insert("bob", 27);
+/// assert_eq!(ages.insert("alice", 32), Some(31));
+///
+/// assert_eq!(ages.get("alice"), Some(&32));
+/// assert_eq!(ages.remove("bob"), Some(27));
+/// assert!(!ages.contains_key("bob"));
+/// assert_eq!(ages.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct LinearHashMap<K, V, S = DefaultHashBuilder> {
+    buckets: Vec<Vec<(u64, K, V)>>,
+    level: usize,
+    split_pointer: usize,
+    len: usize,
+    hasher_builder: S,
+}
+
+impl<K, V, S> Default for LinearHashMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<K, V> LinearHashMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty LinearHashMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let map: LinearHashMap<&str, i32> = LinearHashMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<K, V, S> LinearHashMap<K, V, S> {
+    /// Creates an empty LinearHashMap that uses the given hash builder to
+    /// hash the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, LinearHashMap};
+    ///
+    /// let mut map = LinearHashMap::with_hasher(FnvBuildHasher::default());
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        let mut buckets = Vec::with_capacity(INITIAL_BUCKETS);
+        buckets.resize_with(INITIAL_BUCKETS, Vec::new);
+        Self {
+            buckets,
+            level: 0,
+            split_pointer: 0,
+            len: 0,
+            hasher_builder,
+        }
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, LinearHashMap};
+    ///
+    /// let map: LinearHashMap<i32, i32, _> =
+    ///     LinearHashMap::with_hasher(FnvBuildHasher::default());
+    /// let _: &FnvBuildHasher = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the map has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of buckets, which grows by one with each split.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// assert_eq!(map.bucket_count(), 4);
+    /// for i in 0..9 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert_eq!(map.bucket_count(), 5);
+    /// ```
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns the number of times the buckets have doubled, which is the
+    /// number of completed rounds of splits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// for i in 0..16 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert_eq!(map.level(), 1);
+    /// assert_eq!(map.bucket_count(), 8);
+    /// ```
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the bucket that splits next.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// assert_eq!(map.split_pointer(), 0);
+    /// for i in 0..11 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert_eq!(map.split_pointer(), 2);
+    /// ```
+    pub fn split_pointer(&self) -> usize {
+        self.split_pointer
+    }
+
+    /// Returns the average number of entries per bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// assert_eq!(map.load_factor(), 0.0);
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// assert!(map.load_factor() <= 2.0);
+    /// ```
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.buckets.len() as f64
+    }
+
+    /// Returns an iterator over the entries of the map, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// let mut entries: Vec<_> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, [(&"a", &1), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            entries: [].iter(),
+            len: self.len,
+        }
+    }
+
+    /// Removes all entries, going back to the buckets of an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
+    /// }
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// assert_eq!(map.bucket_count(), 4);
+    /// ```
+    pub fn clear(&mut self) {
+        self.buckets.truncate(INITIAL_BUCKETS);
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.level = 0;
+        self.split_pointer = 0;
+        self.len = 0;
+    }
+
+    /// Returns the bucket of the hash.
+    fn bucket_of(&self, hash: u64) -> usize {
+        let round = INITIAL_BUCKETS << self.level;
+        let bucket = hash as usize & (round - 1);
+        if bucket < self.split_pointer {
+            hash as usize & (2 * round - 1)
+        } else {
+            bucket
+        }
+    }
+
+    /// Splits the bucket at the split pointer, and moves the pointer on.
+    fn split(&mut self) {
+        let round = INITIAL_BUCKETS << self.level;
+        let (moved, kept) = mem::take(&mut self.buckets[self.split_pointer])
+            .into_iter()
+            .partition(|(hash, _, _)| *hash as usize & round != 0);
+        self.buckets[self.split_pointer] = kept;
+        self.buckets.push(moved);
+        self.split_pointer += 1;
+        if self.split_pointer == round {
+            self.level += 1;
+            self.split_pointer = 0;
+        }
+    }
+}
+
+impl<K, V, S> LinearHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, None is returned. If the
+    /// map did have this key present, the value is updated, and the old
+    /// value is returned. The key is not updated.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.insert(37, "b"), Some("a"));
+    /// assert_eq!(map.get(&37), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hasher_builder.hash_one(&key);
+        let bucket = self.bucket_of(hash);
+        if let Some((_, _, old)) = self.buckets[bucket]
+            .iter_mut()
+            .find(|(h, k, _)| *h == hash && *k == key)
+        {
+            return Some(mem::replace(old, value));
+        }
+        self.buckets[bucket].push((hash, key, value));
+        self.len += 1;
+        if self.len > MAX_LOAD_FACTOR * self.buckets.len() {
+            self.split();
+        }
+        None
+    }
+
+    /// Returns a reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        Some(&self.buckets[bucket][i].2)
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        Some(&mut self.buckets[bucket][i].2)
+    }
+
+    /// Returns true if the map has a value for the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes a key from the map, returning its value if the key was in the
+    /// map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Hash and
+    /// Eq on the borrowed form must match those for the key type.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinearHashMap;
+    ///
+    /// let mut map = LinearHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, i) = self.find(key)?;
+        let (_, _, value) = self.buckets[bucket].swap_remove(i);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns the bucket and the position in it of the key.
+    fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher_builder.hash_one(key);
+        let bucket = self.bucket_of(hash);
+        let i = self.buckets[bucket]
+            .iter()
+            .position(|(h, k, _)| *h == hash && k.borrow() == key)?;
+        Some((bucket, i))
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for LinearHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for LinearHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a LinearHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a LinearHashMap.
+///
+/// This struct is created by [`LinearHashMap::iter()`]. See its
+/// documentation for more.
+///
+/// [`LinearHashMap::iter()`]: crate::containers::LinearHashMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Vec<(u64, K, V)>>,
+    entries: slice::Iter<'a, (u64, K, V)>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, key, value)) = self.entries.next() {
+                self.len -= 1;
+                return Some((key, value));
+            }
+            self.entries = self.buckets.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use std::collections::HashMap;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    /// A hasher that hashes a `u64` to itself, so that the tests choose the
+    /// buckets of the keys.
+    #[derive(Debug, Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, _: &[u8]) {
+            unreachable!("only u64 keys are hashed");
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n;
+        }
+    }
+
+    type Identity = BuildHasherDefault<IdentityHasher>;
+
+    #[test]
+    fn empty_map() {
+        let mut map: LinearHashMap<u64, u64> = LinearHashMap::new();
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.load_factor(), 0.0);
+        assert_eq!(map.iter().next(), None);
+        assert_eq!(map.bucket_count(), INITIAL_BUCKETS);
+    }
+
+    #[test]
+    fn split_pointer_wraps_at_the_end_of_a_round() {
+        let mut map: LinearHashMap<u64, (), _> =
+            LinearHashMap::with_hasher(Identity::default());
+        let mut rounds = Vec::new();
+        for key in 0..17 {
+            map.insert(key, ());
+            rounds.push((map.level(), map.split_pointer()));
+        }
+        // A bucket splits after every other insertion once there are more
+        // than 2 entries per bucket. The fourth split ends the round.
+        let expected: Vec<_> = [(0, 0); 8]
+            .iter()
+            .chain(&[(0, 1), (0, 1), (0, 2), (0, 2), (0, 3), (0, 3)])
+            .chain(&[(1, 0), (1, 0), (1, 1)])
+            .copied()
+            .collect();
+        assert_eq!(rounds, expected);
+        assert_eq!(map.bucket_count(), 9);
+
+        // The first bucket was split twice, on the bits of 4 and of 8, and
+        // the others once.
+        let buckets: Vec<Vec<u64>> = map
+            .buckets
+            .iter()
+            .map(|bucket| bucket.iter().map(|&(hash, _, _)| hash).collect())
+            .collect();
+        assert_eq!(buckets[0], [0, 16]);
+        assert_eq!(buckets[1], [1, 9]);
+        assert_eq!(buckets[7], [7, 15]);
+        assert_eq!(buckets[8], [8]);
+        assert!((0..17).all(|key| map.contains_key(&key)));
+
+        // Clearing the map starts the rounds over.
+        map.clear();
+        assert_eq!((map.level(), map.split_pointer()), (0, 0));
+        assert_eq!(map.bucket_count(), INITIAL_BUCKETS);
+        map.insert(5, ());
+        assert!(map.contains_key(&5));
+    }
+
+    #[test]
+    fn splits_the_bucket_at_the_pointer() {
+        // Every key goes to the last bucket, but it is the first one that
+        // splits, and then the second one.
+        let mut map: LinearHashMap<u64, (), _> =
+            LinearHashMap::with_hasher(Identity::default());
+        for i in 0..9 {
+            map.insert(3 + 4 * i, ());
+        }
+        assert_eq!(map.split_pointer(), 1);
+        assert!(map.buckets[0].is_empty() && map.buckets[4].is_empty());
+        assert_eq!(map.buckets[3].len(), 9);
+        map.insert(3 + 4 * 9, ());
+        map.insert(3 + 4 * 10, ());
+        assert_eq!(map.split_pointer(), 2);
+        assert_eq!(map.buckets[3].len(), 11);
+    }
+
+    #[test]
+    fn matches_hash_map() {
+        let mut map = LinearHashMap::new();
+        let mut expected = HashMap::new();
+        let mut rng = SplitMix64::new(31);
+        for _ in 0..10_000 {
+            let key = rng.next_u64() % 2_000;
+            let buckets = map.bucket_count();
+            match rng.next_u64() % 4 {
+                0 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                1 => assert_eq!(map.get(&key), expected.get(&key)),
+                _ => {
+                    let value = rng.next_u64();
+                    assert_eq!(
+                        map.insert(key, value),
+                        expected.insert(key, value)
+                    );
+                }
+            }
+            assert_eq!(map.len(), expected.len());
+            assert!(map.bucket_count() - buckets <= 1);
+            assert_eq!(
+                map.bucket_count(),
+                (INITIAL_BUCKETS << map.level()) + map.split_pointer()
+            );
+        }
+        for (i, bucket) in map.buckets.iter().enumerate() {
+            for (hash, _, _) in bucket {
+                assert_eq!(map.bucket_of(*hash), i);
+            }
+        }
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable();
+        let mut expected: Vec<_> = expected.iter().collect();
+        expected.sort_unstable();
+        assert_eq!(entries, expected);
+    }
+}
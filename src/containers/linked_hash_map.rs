@@ -1,10 +1,27 @@
-use std::borrow::Borrow;
+use alloc::boxed::Box;
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::iter::{Chain, FromIterator};
+use core::ops::Index;
+#[cfg(feature = "std")]
 use std::collections::hash_map::RandomState;
-use std::hash::{BuildHasher, Hash, Hasher};
-use std::iter::FromIterator;
-use std::ops::Index;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
-/// A basic hash map.
+#[cfg(feature = "std")]
+use super::clock::{Clock, SystemClock};
+#[cfg(not(feature = "std"))]
+use super::fnv::FnvBuildHasher;
+
+/// A hash map that remembers the order in which its keys were inserted.
+///
+/// Iterating over the map yields its entries from the least recently inserted
+/// to the most recently inserted one. Updating the value of an existing key
+/// does not change its position.
 ///
 /// It is required that the keys implement the [`Eq`] and [`Hash`] traits,
 /// although this can frequently be achieved by using
@@ -157,29 +174,218 @@ use std::ops::Index;
 /// // use the values stored in map
 /// ```
 #[derive(Debug)]
-pub struct LinkedHashMap<K, V, S = RandomState> {
+pub struct LinkedHashMap<K, V, S = DefaultHashBuilder> {
     // This hash map implementation relies on an array of buckets that is
     // indexed by the hash of an entry's key. If 2 different keys are hashed to
     // the same value, the entries are put into the same bucket. These entries
     // can later be retrieved by comparing both the hashed key and the actual
     // key.
-    buckets: Vec<Bucket<K, V>>,
+    //
+    // The entries themselves are kept in an array of slots, buckets only hold
+    // the positions of their entries in that array. Occupied slots are chained
+    // together in a doubly-linked list that records the insertion order, and
+    // vacant slots are chained together in a free list so they can be reused.
+    buckets: Vec<Bucket>,
+    slots: Vec<Slot<K, V>>,
+    free: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
     hasher_builder: S,
+    // The expiry times and the weights of the entries are kept aside, and
+    // only once they are used, so that maps that do not use them stay small.
+    #[cfg(feature = "std")]
+    expiry: Option<Box<Expiry>>,
+    bound: Option<Box<WeightBound<K, V>>>,
     entries_count: usize,
+    rehashes: usize,
+}
+
+/// The hash builder used by [`LinkedHashMap`] when none is specified.
+///
+/// This is [`RandomState`] when the `std` feature is enabled, which protects
+/// the map against HashDoS attacks. Without `std`, there is no source of
+/// randomness to seed the hasher with, so the map falls back to the
+/// deterministic [`FnvBuildHasher`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`FnvBuildHasher`]: crate::containers::FnvBuildHasher
+#[cfg(feature = "std")]
+pub type DefaultHashBuilder = RandomState;
+
+/// The hash builder used by [`LinkedHashMap`] when none is specified.
+///
+/// This is [`RandomState`] when the `std` feature is enabled, which protects
+/// the map against HashDoS attacks. Without `std`, there is no source of
+/// randomness to seed the hasher with, so the map falls back to the
+/// deterministic [`FnvBuildHasher`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`FnvBuildHasher`]: crate::containers::FnvBuildHasher
+/// [`RandomState`]: https://doc.rust-lang.org/std/collections/hash_map/struct.RandomState.html
+#[cfg(not(feature = "std"))]
+pub type DefaultHashBuilder = FnvBuildHasher;
+
+/// A data item that holds the positions of the entries in [`LinkedHashMap`]
+/// whose key is hashed to the same value.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug, Default)]
+struct Bucket {
+    items: Vec<usize>,
+}
+
+/// A slot in the array of entries of [`LinkedHashMap`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug)]
+enum Slot<K, V> {
+    Occupied(Node<K, V>),
+    Vacant { next_free: Option<usize> },
+}
+
+/// An entry of [`LinkedHashMap`] along with its neighbours in insertion
+/// order.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: Option<usize>,
+    next: Option<usize>,
 }
 
-/// A data item that holds entries in [`LinkedHashMap`] whose key is hashed to
-/// the same value.
+/// The expiry times of the entries in [`LinkedHashMap`], and the clock they
+/// are compared against.
 ///
 /// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[cfg(feature = "std")]
 #[derive(Debug)]
-struct Bucket<K, V> {
-    items: Vec<(K, V)>,
+struct Expiry {
+    clock: Box<dyn Clock + Send + Sync>,
+    // Indexed by slot. Entries without an expiry time, vacant slots, and
+    // slots past the end of the array have none.
+    expires_at: Vec<Option<Instant>>,
 }
 
-impl<K, V> Default for Bucket<K, V> {
+#[cfg(feature = "std")]
+impl Default for Expiry {
     fn default() -> Self {
-        Self { items: Vec::new() }
+        Self {
+            clock: Box::new(SystemClock),
+            expires_at: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Expiry {
+    /// Returns the expiry time of the entry stored at the slot `idx`.
+    fn get(&self, idx: usize) -> Option<Instant> {
+        self.expires_at.get(idx).copied().flatten()
+    }
+
+    /// Sets the expiry time of the entry stored at the slot `idx`.
+    fn set(&mut self, idx: usize, expires_at: Option<Instant>) {
+        if idx >= self.expires_at.len() {
+            if expires_at.is_none() {
+                return;
+            }
+            self.expires_at.resize(idx + 1, None);
+        }
+        self.expires_at[idx] = expires_at;
+    }
+}
+
+/// The order in which entries are evicted from a [`LinkedHashMap`] whose
+/// total weight is bounded.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evicts the entry that was inserted the longest time ago.
+    Fifo,
+    /// Evicts the entry that was used the longest time ago. Inserting a key
+    /// that is already in the map, getting its entry, and calling
+    /// [`LinkedHashMap::get_refresh`] count as uses.
+    ///
+    /// [`LinkedHashMap::get_refresh`]: crate::containers::LinkedHashMap::get_refresh
+    Lru,
+}
+
+/// A function giving the weight of an entry in [`LinkedHashMap`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send + Sync>;
+
+/// The limit on the total weight of the entries in [`LinkedHashMap`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+struct WeightBound<K, V> {
+    limit: usize,
+    policy: EvictionPolicy,
+    weigher: Weigher<K, V>,
+    total: usize,
+    // The part of the total that comes from entries heavier than the limit on
+    // their own. Such entries are only kept when they are inserted through
+    // the entry API, and the other entries are not evicted to make room for
+    // them.
+    oversized: usize,
+    // Indexed by slot. Vacant slots and slots past the end of the array weigh
+    // nothing.
+    weights: Vec<usize>,
+}
+
+impl<K, V> WeightBound<K, V> {
+    /// Returns the weight of the entry stored at the slot `idx`.
+    fn weight(&self, idx: usize) -> usize {
+        self.weights.get(idx).copied().unwrap_or(0)
+    }
+
+    /// Sets the weight of the entry stored at the slot `idx`, and updates the
+    /// total weight.
+    fn set_weight(&mut self, idx: usize, weight: usize) {
+        if idx >= self.weights.len() {
+            self.weights.resize(idx + 1, 0);
+        }
+        let old = self.weights[idx];
+        self.total = (self.total - old)
+            .checked_add(weight)
+            .expect("Total weight overflowed");
+        if old > self.limit {
+            self.oversized -= old;
+        }
+        if weight > self.limit {
+            self.oversized += weight;
+        }
+        self.weights[idx] = weight;
+    }
+
+    /// Returns true if the entries that can fit under the limit weigh more
+    /// than it.
+    fn is_exceeded(&self) -> bool {
+        self.total - self.oversized > self.limit
+    }
+}
+
+impl<K, V> fmt::Debug for WeightBound<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeightBound")
+            .field("limit", &self.limit)
+            .field("policy", &self.policy)
+            .field("total", &self.total)
+            .field("oversized", &self.oversized)
+            .finish()
+    }
+}
+
+/// Returns the number of entries that can be stored in `n_buckets` buckets
+/// before the map has to grow.
+fn bucket_capacity(n_buckets: usize) -> usize {
+    match n_buckets {
+        0 => 0,
+        // Same as `3 * n / 4 + 1` but without overflowing.
+        n => n / 4 * 3 + n % 4 * 3 / 4 + 1,
     }
 }
 
@@ -193,17 +399,16 @@ where
     (hasher.finish() % n_buckets as u64) as usize
 }
 
-impl<K, V> Default for LinkedHashMap<K, V, RandomState> {
+impl<K, V, S> Default for LinkedHashMap<K, V, S>
+where
+    S: Default,
+{
     fn default() -> Self {
-        Self {
-            buckets: Vec::new(),
-            hasher_builder: RandomState::new(),
-            entries_count: 0,
-        }
+        Self::with_hasher(Default::default())
     }
 }
 
-impl<K, V> LinkedHashMap<K, V, RandomState> {
+impl<K, V> LinkedHashMap<K, V, DefaultHashBuilder> {
     /// Creates an empty `LinkedHashMap`.
     ///
     /// The hash map is initially created with an empty list of buckets, so it
@@ -221,6 +426,55 @@ impl<K, V> LinkedHashMap<K, V, RandomState> {
 }
 
 impl<K, V, S> LinkedHashMap<K, V, S> {
+    /// Creates an empty `LinkedHashMap` which will use the given hash builder
+    /// to hash keys.
+    ///
+    /// The hash map is initially created with an empty list of buckets, so it
+    /// will not allocate until it is first inserted into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let s = RandomState::new();
+    /// let mut map = LinkedHashMap::with_hasher(s);
+    /// map.insert(1, 2);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self {
+            buckets: Vec::new(),
+            slots: Vec::new(),
+            free: None,
+            head: None,
+            tail: None,
+            hasher_builder,
+            #[cfg(feature = "std")]
+            expiry: None,
+            bound: None,
+            entries_count: 0,
+            rehashes: 0,
+        }
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let map: LinkedHashMap<&str, i32> = LinkedHashMap::new();
+    ///
+    /// // Hash a value the same way the map hashes its keys.
+    /// let _ = map.hasher().hash_one("foo");
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
+    }
+
     /// Returns the number of elements in the map.
     ///
     /// # Examples
@@ -252,20 +506,24 @@ impl<K, V, S> LinkedHashMap<K, V, S> {
     pub fn is_empty(&self) -> bool {
         self.entries_count == 0
     }
-}
 
-impl<K, V, S> LinkedHashMap<K, V, S>
-where
-    K: Hash + Eq,
-    S: BuildHasher,
-{
-    /// Inserts a key-value pair into the map.
+    /// Returns the number of elements the map can hold without reallocating.
     ///
-    /// If the map did not have this key present, [`None`] is returned.
+    /// # Examples
     ///
-    /// If the map did have this key present, the value is updated, and the old 
-    /// value is returned.  The key is not updated, though; this matters for 
-    /// types that can be `==` without being identical.
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map: LinkedHashMap<i32, i32> = LinkedHashMap::new();
+    /// assert_eq!(map.capacity(), 0);
+    /// map.insert(1, 1);
+    /// assert!(map.capacity() >= 1);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        bucket_capacity(self.buckets.len()).min(self.slots.capacity())
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order.
     ///
     /// # Examples
     ///
@@ -273,65 +531,63 @@ where
     /// use dt::containers::LinkedHashMap;
     ///
     /// let mut map = LinkedHashMap::new();
-    /// assert_eq!(map.insert(37, "a"), None);
-    /// assert_eq!(map.is_empty(), false);
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
     ///
-    /// map.insert(37, "b");
-    /// assert_eq!(map.insert(37, "c"), Some("b"));
-    /// assert_eq!(map[&37], "c");
+    /// let mut iter = map.iter();
+    /// assert_eq!(iter.next(), Some((&"a", &1)));
+    /// assert_eq!(iter.next(), Some((&"b", &2)));
+    /// assert_eq!(iter.next(), Some((&"c", &3)));
+    /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if self.buckets.is_empty()
-            || self.entries_count > 3 * self.buckets.len() / 4
-        {
-            self.grow();
-        }
-
-        let idx = self.index(&key);
-        let bucket = &mut self.buckets[idx];
-
-        for &mut (ref k, ref mut v) in bucket.items.iter_mut() {
-            if *k == key {
-                return Some(std::mem::replace(v, value));
-            }
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        Iter {
+            map: self,
+            front: self.head,
+            back: self.tail,
+            len: self.entries_count,
         }
-        bucket.items.push((key, value));
-        self.entries_count += 1;
-        None
     }
 
-    /// Returns a reference to the value corresponding to the key.
+    /// An iterator visiting all keys in ascending order.
     ///
-    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
-    /// on the borrowed form must match those for the key type.
+    /// The map itself is not reordered, [`iter`] still visits the entries in
+    /// insertion order afterwards.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// [`iter`]: LinkedHashMap::iter
     ///
     /// # Examples
     ///
     /// ```
     /// use dt::containers::LinkedHashMap;
+    ///
     /// let mut map = LinkedHashMap::new();
-    /// map.insert(1, "a");
-    /// assert_eq!(map.get(&1), Some(&"a"));
-    /// assert_eq!(map.get(&2), None);
+    /// map.insert("b", 2);
+    /// map.insert("c", 3);
+    /// map.insert("a", 1);
+    ///
+    /// let keys: Vec<_> = map.keys_sorted().collect();
+    /// assert_eq!(keys, [&"a", &"b", &"c"]);
     /// ```
-    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    pub fn keys_sorted(&self) -> SortedKeys<'_, K, V, S>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        K: Ord,
     {
-        let idx = self.index(key);
-        self.buckets[idx]
-            .items
-            .iter()
-            .find(|&(ref k, _)| k.borrow() == key)
-            .map(|&(_, ref v)| v)
+        SortedKeys {
+            iter: self.iter_sorted_by(|(k1, _), (k2, _)| k1.cmp(k2)),
+        }
     }
 
-    /// Removes a key from the map, returning the value at the key if the key 
-    /// was previously in the map.
+    /// An iterator visiting all key-value pairs in the order given by the
+    /// comparator function `cmp`.
     ///
-    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
-    /// on the borrowed form must match those for the key type.
+    /// The sort is stable, so entries that compare equal are visited in
+    /// insertion order. The map itself is not reordered.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
     ///
     /// # Examples
     ///
@@ -339,250 +595,1521 @@ where
     /// use dt::containers::LinkedHashMap;
     ///
     /// let mut map = LinkedHashMap::new();
-    /// map.insert(1, "a");
-    /// assert_eq!(map.remove(&1), Some("a"));
-    /// assert_eq!(map.remove(&1), None);
+    /// map.insert("a", 3);
+    /// map.insert("b", 1);
+    /// map.insert("c", 2);
+    ///
+    /// let by_value: Vec<_> =
+    ///     map.iter_sorted_by(|(_, v1), (_, v2)| v1.cmp(v2)).collect();
+    /// assert_eq!(by_value, [(&"b", &1), (&"c", &2), (&"a", &3)]);
     /// ```
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    pub fn iter_sorted_by<F>(&self, mut cmp: F) -> SortedIter<'_, K, V, S>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        F: FnMut((&K, &V), (&K, &V)) -> Ordering,
     {
-        let idx = self.index(&key);
-        let bucket = &mut self.buckets[idx];
-
-        let entry_idx = bucket
-            .items
-            .iter()
-            .position(|&(ref k, _)| k.borrow() == key)?;
-        self.entries_count -= 1;
-        Some(bucket.items.swap_remove(entry_idx).1)
+        let mut order = Vec::with_capacity(self.entries_count);
+        let mut it = self.head;
+        while let Some(idx) = it {
+            order.push(idx);
+            it = self.node(idx).next;
+        }
+        order.sort_by(|&i, &j| {
+            let (a, b) = (self.node(i), self.node(j));
+            cmp((&a.key, &a.value), (&b.key, &b.value))
+        });
+        SortedIter {
+            map: self,
+            order: order.into_iter(),
+        }
     }
 
-    /// Gets the given key’s corresponding entry in the map for in-place 
-    /// manipulation.
+    /// Returns a snapshot of the map's internal layout.
+    ///
+    /// This is meant for observing how the entries are spread across the
+    /// buckets with different hashers and load factors.
+    ///
+    /// This operation should compute in O(n) time.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::collections::HashMap;
-    ///
-    /// let mut letters = HashMap::new();
+    /// use dt::containers::LinkedHashMap;
     ///
-    /// for ch in "a short treatise on fungi".chars() {
-    ///     let counter = letters.entry(ch).or_insert(0);
-    ///     *counter += 1;
+    /// let mut map = LinkedHashMap::new();
+    /// for i in 0..100 {
+    ///     map.insert(i, i);
     /// }
     ///
-    /// assert_eq!(letters[&'s'], 2);
-    /// assert_eq!(letters[&'t'], 3);
-    /// assert_eq!(letters[&'u'], 1);
-    /// assert_eq!(letters.get(&'y'), None);
+    /// let stats = map.stats();
+    /// assert_eq!(stats.entries, 100);
+    /// assert_eq!(stats.buckets, stats.occupancy.iter().sum::<usize>());
+    /// assert!(stats.longest_chain >= 1);
+    /// assert!(stats.rehashes > 0);
     /// ```
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
-        if self.buckets.is_empty()
-            || self.entries_count > 3 * self.buckets.len() / 4
-        {
-            self.grow();
+    pub fn stats(&self) -> LinkedHashMapStats {
+        let mut occupancy = Vec::new();
+        for bucket in &self.buckets {
+            let chain = bucket.items.len();
+            if chain >= occupancy.len() {
+                occupancy.resize(chain + 1, 0);
+            }
+            occupancy[chain] += 1;
         }
-
-        let bucket_idx = self.index(&key);
-        if let Some(entry_idx) = self.buckets[bucket_idx]
-            .items
-            .iter_mut()
-            .position(|&mut (ref k, _)| *k == key)
-        {
-            // We are using `position` instead of `find` cause `self.buckets` 
-            // will be borrowed inside the scope of the if statement; using 
-            // `find` somehow scoped `self.buckets` within the function, 
-            // disallowing `self` to be re-borrowed in the else case.
-            let &mut (ref key, ref mut value) =
-                &mut self.buckets[bucket_idx].items[entry_idx];
-            return Entry::Occupied(OccupiedEntry { key, value });
+        LinkedHashMapStats {
+            buckets: self.buckets.len(),
+            entries: self.entries_count,
+            longest_chain: occupancy.len().saturating_sub(1),
+            occupancy,
+            rehashes: self.rehashes,
         }
-        Entry::Vacant(VacantEntry {
-            key,
-            bucket_idx,
-            map: self,
-        })
     }
 
-    /// Returns true if the map contains a value for the specified key.
+    /// Returns the total weight of the entries in the map, or 0 if the map
+    /// has no weight bound.
     ///
-    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
-    /// on the borrowed form must match those for the key type.
+    /// See [`set_weight_bound`] for more.
+    ///
+    /// [`set_weight_bound`]: LinkedHashMap::set_weight_bound
+    pub fn total_weight(&self) -> usize {
+        self.bound.as_ref().map_or(0, |bound| bound.total)
+    }
+
+    /// Returns the weight of the entry stored at the slot `idx`, or 0 if the
+    /// map has no weight bound.
+    fn weight(&self, idx: usize) -> usize {
+        self.bound.as_ref().map_or(0, |bound| bound.weight(idx))
+    }
+
+    /// Replaces the clock that is used to expire the entries inserted with
+    /// [`insert_with_ttl`].
+    ///
+    /// The map uses a [`SystemClock`] by default.
+    ///
+    /// [`insert_with_ttl`]: LinkedHashMap::insert_with_ttl
+    /// [`SystemClock`]: crate::containers::SystemClock
     ///
     /// # Examples
     ///
     /// ```
-    /// use dt::containers::LinkedHashMap;
+    /// use std::time::Duration;
+    /// use dt::containers::{LinkedHashMap, ManualClock};
     ///
+    /// let clock = ManualClock::new();
     /// let mut map = LinkedHashMap::new();
-    /// map.insert(1, "a");
-    /// assert_eq!(map.contains_key(&1), true);
-    /// assert_eq!(map.contains_key(&2), false);
+    /// map.set_clock(clock.clone());
+    ///
+    /// map.insert_with_ttl("foo", 42, Duration::from_secs(10));
+    /// assert_eq!(map.get("foo"), Some(&42));
+    ///
+    /// clock.advance(Duration::from_secs(10));
+    /// assert_eq!(map.get("foo"), None);
     /// ```
-    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    #[cfg(feature = "std")]
+    pub fn set_clock<C>(&mut self, clock: C)
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        C: Clock + Send + Sync + 'static,
     {
-        let idx = self.index(key);
-        self.buckets[idx]
-            .items
-            .iter()
-            .find(|&(ref k, _)| k.borrow() == key)
-            .is_some()
+        self.expiry.get_or_insert_with(Default::default).clock =
+            Box::new(clock);
     }
 
-    /// Increase the size of the array of buckets. If there is no bucket, extend 
-    /// the array by one, otherwise, double the array's size and reindex all 
-    /// existing entries.
-    fn grow(&mut self) {
-        let target_size = match self.buckets.len() {
-            0 => 1,
-            n => 2 * n,
-        };
+    /// Returns the expiry time of the entry stored at the slot `idx`.
+    #[cfg(feature = "std")]
+    fn expires_at(&self, idx: usize) -> Option<Instant> {
+        self.expiry.as_ref()?.get(idx)
+    }
 
-        let mut buckets = Vec::with_capacity(target_size);
-        buckets.extend((0..target_size).map(|_| Bucket::default()));
-        for (key, value) in self
-            .buckets
-            .iter_mut()
-            .flat_map(|bucket| bucket.items.drain(..))
-        {
-            let idx = derive_bucket_index(
-                self.hasher_builder.build_hasher(),
-                &key,
-                target_size,
-            );
-            buckets[idx].items.push((key, value));
+    /// Sets the expiry time of the entry stored at the slot `idx`. Nothing is
+    /// allocated to remove an expiry time that the entry does not have.
+    #[cfg(feature = "std")]
+    fn set_expires_at(&mut self, idx: usize, expires_at: Option<Instant>) {
+        match &mut self.expiry {
+            Some(expiry) => expiry.set(idx, expires_at),
+            None if expires_at.is_none() => {}
+            None => self
+                .expiry
+                .get_or_insert_with(Default::default)
+                .set(idx, expires_at),
         }
-        self.buckets = buckets;
     }
 
-    /// Get the index of the bucket for `key`.
-    fn index<Q>(&self, key: &Q) -> usize
-    where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
-    {
-        derive_bucket_index(
-            self.hasher_builder.build_hasher(),
-            key,
-            self.buckets.len(),
-        )
+    /// Returns true if the entry stored at the slot `idx` has expired. The
+    /// clock is only read for entries that have an expiry time.
+    #[cfg(feature = "std")]
+    fn is_expired(&self, idx: usize) -> bool {
+        match &self.expiry {
+            Some(expiry) => match expiry.get(idx) {
+                Some(expires_at) => expires_at <= expiry.clock.now(),
+                None => false,
+            },
+            None => false,
+        }
     }
-}
 
-impl<K, Q, V, S> Index<&Q> for LinkedHashMap<K, V, S>
-where
-    K: Hash + Eq + Borrow<Q>,
-    Q: Hash + Eq + ?Sized,
-    S: BuildHasher,
-{
-    type Output = V;
+    /// Returns true if the entry stored at the slot `idx` has expired. Entries
+    /// can not be given an expiry time without `std`, so they never expire.
+    #[cfg(not(feature = "std"))]
+    fn is_expired(&self, _idx: usize) -> bool {
+        false
+    }
 
-    fn index(&self, key: &Q) -> &Self::Output {
-        self.get(key).unwrap()
+    /// Returns the entry stored at the slot `idx`.
+    fn node(&self, idx: usize) -> &Node<K, V> {
+        match &self.slots[idx] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant { .. } => unreachable!("slot {} is vacant", idx),
+        }
     }
-}
 
-impl<K, V> FromIterator<(K, V)> for LinkedHashMap<K, V, RandomState>
-where
-    K: Hash + Eq,
-{
-    fn from_iter<I>(iter: I) -> Self
-    where
-        I: IntoIterator<Item = (K, V)>,
-    {
-        let mut map = Self::new();
-        for (k, v) in iter {
-            map.insert(k, v);
+    /// Returns the entry stored at the slot `idx`.
+    fn node_mut(&mut self, idx: usize) -> &mut Node<K, V> {
+        match &mut self.slots[idx] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant { .. } => unreachable!("slot {} is vacant", idx),
         }
-        map
     }
-}
 
-/// An iterator over the elements of a [`LinkedHashMap`].
-///
-/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
-#[derive(Debug)]
+    /// Returns the key of the entry stored at the slot `idx`, or None if the
+    /// slot is vacant or past the end of the slots.
+    ///
+    /// The slots are only reused after entries are removed, so in a map that
+    /// never had an entry removed, the slot of an entry is its position in
+    /// the insertion order.
+    pub(crate) fn key_at(&self, idx: usize) -> Option<&K> {
+        match self.slots.get(idx)? {
+            Slot::Occupied(node) => Some(&node.key),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Stores an entry in a vacant slot, or a new slot if there is none, and
+    /// links it at the back of the insertion order. Returns the position of
+    /// the slot.
+    fn alloc(&mut self, key: K, value: V) -> usize {
+        let node = Node {
+            key,
+            value,
+            prev: None,
+            next: None,
+        };
+        let idx = match self.free {
+            Some(idx) => {
+                if let Slot::Vacant { next_free } = self.slots[idx] {
+                    self.free = next_free;
+                }
+                self.slots[idx] = Slot::Occupied(node);
+                idx
+            }
+            None => {
+                self.slots.push(Slot::Occupied(node));
+                self.slots.len() - 1
+            }
+        };
+        self.link_back(idx);
+        idx
+    }
+
+    /// Unlinks the entry stored at the slot `idx` and marks the slot as
+    /// vacant. Returns the entry that was stored.
+    fn dealloc(&mut self, idx: usize) -> (K, V) {
+        self.unlink(idx);
+        #[cfg(feature = "std")]
+        self.set_expires_at(idx, None);
+        let slot = core::mem::replace(
+            &mut self.slots[idx],
+            Slot::Vacant {
+                next_free: self.free,
+            },
+        );
+        self.free = Some(idx);
+        match slot {
+            Slot::Occupied(node) => (node.key, node.value),
+            Slot::Vacant { .. } => unreachable!("slot {} is vacant", idx),
+        }
+    }
+
+    /// Links the entry stored at the slot `idx` at the back of the insertion
+    /// order.
+    fn link_back(&mut self, idx: usize) {
+        self.link_after(idx, self.tail);
+    }
+
+    /// Links the entry stored at the slot `idx` right after the entry stored
+    /// at the slot `prev`, or at the front of the insertion order if `prev`
+    /// is `None`.
+    fn link_after(&mut self, idx: usize, prev: Option<usize>) {
+        let next = match prev {
+            Some(prev) => self.node(prev).next,
+            None => self.head,
+        };
+        let node = self.node_mut(idx);
+        node.prev = prev;
+        node.next = next;
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(idx),
+            None => self.head = Some(idx),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(idx),
+            None => self.tail = Some(idx),
+        }
+    }
+
+    /// Removes the entry stored at the slot `idx` from the insertion order.
+    fn unlink(&mut self, idx: usize) {
+        let node = self.node_mut(idx);
+        let (prev, next) = (node.prev.take(), node.next.take());
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+}
+
+/// A snapshot of the internal layout of a [`LinkedHashMap`].
+///
+/// This struct is created by [`LinkedHashMap::stats()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::stats()`]: crate::containers::LinkedHashMap::stats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedHashMapStats {
+    /// Number of buckets.
+    pub buckets: usize,
+    /// Number of entries.
+    pub entries: usize,
+    /// Number of buckets holding a given number of entries, i.e.,
+    /// `occupancy[i]` buckets hold exactly `i` entries.
+    pub occupancy: Vec<usize>,
+    /// Number of entries in the most crowded bucket, which is also the
+    /// largest number of keys that has to be compared in a lookup.
+    pub longest_chain: usize,
+    /// Number of times the array of buckets has been resized and all of its
+    /// entries rehashed.
+    pub rehashes: usize,
+}
+
+impl LinkedHashMapStats {
+    /// Returns the average number of entries per bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map = LinkedHashMap::new();
+    /// assert_eq!(map.stats().load_factor(), 0.0);
+    ///
+    /// map.insert("foo", 42);
+    /// assert!(map.stats().load_factor() > 0.0);
+    /// ```
+    pub fn load_factor(&self) -> f64 {
+        if self.buckets == 0 {
+            return 0.0;
+        }
+        self.entries as f64 / self.buckets as f64
+    }
+}
+
+impl<K, V, S> LinkedHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, [`None`] is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old 
+    /// value is returned.  The key is not updated, though; this matters for 
+    /// types that can be `==` without being identical.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map = LinkedHashMap::new();
+    /// assert_eq!(map.insert(37, "a"), None);
+    /// assert_eq!(map.is_empty(), false);
+    ///
+    /// map.insert(37, "b");
+    /// assert_eq!(map.insert(37, "c"), Some("b"));
+    /// assert_eq!(map[&37], "c");
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.reserve(1);
+
+        let bucket_idx = self.index(&key);
+        match self.find_live(bucket_idx, &key) {
+            Some(idx) => {
+                #[cfg(feature = "std")]
+                self.set_expires_at(idx, None);
+                let old = self.replace_value(idx, value);
+                self.make_room(bucket_idx, idx);
+                Some(old)
+            }
+            None => {
+                let idx = self.insert_new(bucket_idx, key, value);
+                self.make_room(bucket_idx, idx);
+                None
+            }
+        }
+    }
+
+    /// Inserts a key-value pair into the map that expires after `ttl` has
+    /// passed.
+    ///
+    /// This behaves like [`insert`], except that once the entry has expired,
+    /// it is treated as if it was not in the map. Expired entries are purged
+    /// lazily, either when their key is accessed through a method that takes
+    /// `&mut self`, or when [`evict_expired`] is called. Until then, they are
+    /// still counted by [`len`] and visited by the iterators.
+    ///
+    /// Calling [`insert`] on the key of an entry that has not expired removes
+    /// its expiry time.
+    ///
+    /// [`insert`]: LinkedHashMap::insert
+    /// [`evict_expired`]: LinkedHashMap::evict_expired
+    /// [`len`]: LinkedHashMap::len
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{LinkedHashMap, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut map = LinkedHashMap::new();
+    /// map.set_clock(clock.clone());
+    ///
+    /// assert_eq!(map.insert_with_ttl(1, "a", Duration::from_secs(1)), None);
+    /// assert!(map.contains_key(&1));
+    ///
+    /// clock.advance(Duration::from_secs(1));
+    /// assert!(!map.contains_key(&1));
+    /// assert_eq!(map.insert_with_ttl(1, "b", Duration::from_secs(1)), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn insert_with_ttl(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Duration,
+    ) -> Option<V> {
+        self.reserve(1);
+
+        // An expiry time that can not be represented is so far in the future
+        // that the entry is never going to expire.
+        let clock = &self.expiry.get_or_insert_with(Default::default).clock;
+        let expires_at = clock.now().checked_add(ttl);
+        let bucket_idx = self.index(&key);
+        match self.find_live(bucket_idx, &key) {
+            Some(idx) => {
+                self.set_expires_at(idx, expires_at);
+                let old = self.replace_value(idx, value);
+                self.make_room(bucket_idx, idx);
+                Some(old)
+            }
+            None => {
+                let idx = self.insert_new(bucket_idx, key, value);
+                self.set_expires_at(idx, expires_at);
+                self.make_room(bucket_idx, idx);
+                None
+            }
+        }
+    }
+
+    /// Removes all the entries that have expired. Returns the number of
+    /// removed entries.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{LinkedHashMap, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut map = LinkedHashMap::new();
+    /// map.set_clock(clock.clone());
+    ///
+    /// map.insert(1, "a");
+    /// map.insert_with_ttl(2, "b", Duration::from_secs(1));
+    /// map.insert_with_ttl(3, "c", Duration::from_secs(2));
+    ///
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.evict_expired(), 1);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn evict_expired(&mut self) -> usize {
+        let now = match &self.expiry {
+            Some(expiry) => expiry.clock.now(),
+            None => return 0,
+        };
+        let mut evicted = 0;
+        let mut it = self.head;
+        while let Some(idx) = it {
+            let node = self.node(idx);
+            it = node.next;
+            if self.expires_at(idx).is_some_and(|t| t <= now) {
+                let bucket_idx = self.index(&node.key);
+                self.remove_at(bucket_idx, idx);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
+    /// on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    /// let mut map = LinkedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let idx = self.find(self.index(key), key)?;
+        if self.is_expired(idx) {
+            return None;
+        }
+        Some(&self.node(idx).value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
+    /// on the borrowed form must match those for the key type.
+    ///
+    /// This counts as a use of the entry when the map evicts entries in LRU
+    /// order. The weight of the entry is not recomputed after the value is
+    /// changed through the returned reference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    /// let mut map = LinkedHashMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map[&1], "b");
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let idx = self.find_live(self.index(key), key)?;
+        self.accessed(idx);
+        Some(&mut self.node_mut(idx).value)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key 
+    /// was previously in the map.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
+    /// on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map = LinkedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let bucket_idx = self.index(key);
+        let idx = self.find_live(bucket_idx, key)?;
+        Some(self.remove_at(bucket_idx, idx).1)
+    }
+
+    /// Removes the least recently inserted entry from the map and returns it,
+    /// or [`None`] if the map is empty. Expired entries in front of it are
+    /// removed as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map = LinkedHashMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// assert_eq!(map.pop_front(), Some((1, "a")));
+    /// assert_eq!(map.pop_front(), Some((2, "b")));
+    /// assert_eq!(map.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        while let Some(idx) = self.head {
+            let expired = self.is_expired(idx);
+            let bucket_idx = self.index(&self.node(idx).key);
+            let entry = self.remove_at(bucket_idx, idx);
+            if !expired {
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    /// Gets the given key’s corresponding entry in the map for in-place 
+    /// manipulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// let mut letters = HashMap::new();
+    ///
+    /// for ch in "a short treatise on fungi".chars() {
+    ///     let counter = letters.entry(ch).or_insert(0);
+    ///     *counter += 1;
+    /// }
+    ///
+    /// assert_eq!(letters[&'s'], 2);
+    /// assert_eq!(letters[&'t'], 3);
+    /// assert_eq!(letters[&'u'], 1);
+    /// assert_eq!(letters.get(&'y'), None);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        self.reserve(1);
+
+        let bucket_idx = self.index(&key);
+        if let Some(idx) = self.find_live(bucket_idx, &key) {
+            self.accessed(idx);
+            let Node { key, value, .. } = self.node_mut(idx);
+            return Entry::Occupied(OccupiedEntry { key, value });
+        }
+        Entry::Vacant(VacantEntry {
+            key,
+            bucket_idx,
+            map: self,
+        })
+    }
+
+    /// Gets the given key’s corresponding entry in the map for in-place 
+    /// manipulation, from a borrowed form of the key.
+    ///
+    /// Unlike [`entry`], the owned key is only created, using its [`From`]
+    /// implementation, when a value is inserted into a vacant entry. Looking
+    /// up a key that is already in the map never clones or allocates it.
+    ///
+    /// [`entry`]: LinkedHashMap::entry
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut words: LinkedHashMap<String, usize> = LinkedHashMap::new();
+    ///
+    /// for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+    ///     *words.entry_ref(word).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(words["the"], 2);
+    /// assert_eq!(words["fox"], 1);
+    /// assert_eq!(words.len(), 8);
+    /// ```
+    pub fn entry_ref<'a, 'b, Q>(
+        &'a mut self,
+        key: &'b Q,
+    ) -> EntryRef<'a, 'b, K, Q, V, S>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.reserve(1);
+
+        let bucket_idx = self.index(key);
+        if let Some(idx) = self.find_live(bucket_idx, key) {
+            self.accessed(idx);
+            let Node { key, value, .. } = self.node_mut(idx);
+            return EntryRef::Occupied(OccupiedEntry { key, value });
+        }
+        EntryRef::Vacant(VacantEntryRef {
+            key,
+            bucket_idx,
+            map: self,
+        })
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, and
+    /// moves the entry to the back of the map as if it was the most recently
+    /// inserted one.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
+    /// on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map: LinkedHashMap<_, _> =
+    ///     [(1, "a"), (2, "b"), (3, "c")].iter().cloned().collect();
+    ///
+    /// *map.get_refresh(&1).unwrap() = "d";
+    /// let keys: Vec<_> = map.iter().collect();
+    /// assert_eq!(keys, [(&2, &"b"), (&3, &"c"), (&1, &"d")]);
+    /// ```
+    pub fn get_refresh<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let idx = self.find_live(self.index(key), key)?;
+        self.unlink(idx);
+        self.link_back(idx);
+        Some(&mut self.node_mut(idx).value)
+    }
+
+    /// Bounds the total weight of the entries in the map.
+    ///
+    /// The weight of an entry is given by `weigher` when the entry is inserted
+    /// or when its value is replaced by an insertion. Changes made through
+    /// mutable references to the value are not accounted for.
+    ///
+    /// Whenever an insertion brings the total weight over `limit`, entries are
+    /// evicted in the order given by `policy` until the total weight fits under
+    /// the limit again. The entry that has just been inserted is never evicted
+    /// to make room for itself. Existing entries are evicted right away if they
+    /// do not fit under the new limit.
+    ///
+    /// An entry that is heavier than the limit on its own can never fit, so
+    /// it is evicted as soon as it is inserted, along with the previous value
+    /// of its key, and the other entries are left alone. The only exceptions
+    /// are the entries inserted through the entry API, which have to stay in
+    /// the map to be handed out as references. Such an entry stays in the map
+    /// until it is replaced or removed, and its weight is counted by
+    /// [`total_weight`] but not against the limit, so no other entry is
+    /// evicted to make room for it.
+    ///
+    /// [`total_weight`]: LinkedHashMap::total_weight
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total weight of the entries overflows a `usize`, either
+    /// here or in a later insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{EvictionPolicy, LinkedHashMap};
+    ///
+    /// // A map that holds at most 10 bytes of values.
+    /// let mut map = LinkedHashMap::new();
+    /// map.set_weight_bound(10, EvictionPolicy::Lru, |_, v: &String| v.len());
+    ///
+    /// map.insert(1, "four".to_string());
+    /// map.insert(2, "five!".to_string());
+    /// map.get_refresh(&1);
+    /// map.insert(3, "six!!!".to_string());
+    ///
+    /// // `2` was the least recently used entry.
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// assert!(map.contains_key(&3));
+    /// assert_eq!(map.total_weight(), 10);
+    /// ```
+    pub fn set_weight_bound<F>(
+        &mut self,
+        limit: usize,
+        policy: EvictionPolicy,
+        weigher: F,
+    ) where
+        F: Fn(&K, &V) -> usize + Send + Sync + 'static,
+    {
+        let mut total = 0usize;
+        let mut oversized = 0;
+        let mut weights = Vec::with_capacity(self.slots.len());
+        for slot in &self.slots {
+            let weight = match slot {
+                Slot::Occupied(node) => weigher(&node.key, &node.value),
+                Slot::Vacant { .. } => 0,
+            };
+            total = total.checked_add(weight).expect("Total weight overflowed");
+            if weight > limit {
+                oversized += weight;
+            }
+            weights.push(weight);
+        }
+        self.bound = Some(Box::new(WeightBound {
+            limit,
+            policy,
+            weigher: Box::new(weigher),
+            total,
+            oversized,
+            weights,
+        }));
+        // Entries that can never fit are evicted before the others, which
+        // may fit once they are gone.
+        for idx in 0..self.slots.len() {
+            if let Slot::Occupied(node) = &self.slots[idx] {
+                if self.weight(idx) > limit {
+                    let bucket_idx = self.index(&node.key);
+                    self.remove_at(bucket_idx, idx);
+                }
+            }
+        }
+        self.enforce_bound(None);
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq 
+    /// on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map = LinkedHashMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.contains_key(&1), true);
+    /// assert_eq!(map.contains_key(&2), false);
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return false;
+        }
+        match self.find(self.index(key), key) {
+            Some(idx) => !self.is_expired(idx),
+            None => false,
+        }
+    }
+
+    /// Moves all entries from `other` into the map.
+    ///
+    /// When a key is present in both maps, `f` is called with the key, the
+    /// value from the map, and the value from `other`; its result becomes the
+    /// new value of the key. Keys that are only in `other` are inserted in the
+    /// order they appear in `other`, along with their expiry time. The
+    /// entries of `other` that have expired are dropped, and a key that is in
+    /// both maps keeps the expiry time it has in the map.
+    ///
+    /// If `f` panics, the key it was called with is no longer in the map, and
+    /// the entries of `other` that were not merged yet are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut a: LinkedHashMap<_, _> =
+    ///     [("x", 1), ("y", 2)].iter().cloned().collect();
+    /// let b: LinkedHashMap<_, _> =
+    ///     [("y", 10), ("z", 20)].iter().cloned().collect();
+    ///
+    /// a.merge(b, |_, old, new| old + new);
+    /// assert_eq!(a.len(), 3);
+    /// assert_eq!(a[&"x"], 1);
+    /// assert_eq!(a[&"y"], 12);
+    /// assert_eq!(a[&"z"], 20);
+    /// ```
+    pub fn merge<T, F>(&mut self, mut other: LinkedHashMap<K, V, T>, mut f: F)
+    where
+        F: FnMut(&K, V, V) -> V,
+    {
+        self.reserve(other.len());
+        // The entries are taken out of `other` like its owning iterator does,
+        // but the expired ones are skipped and the others keep their expiry
+        // time.
+        while let Some(other_idx) = other.head {
+            let expired = other.is_expired(other_idx);
+            #[cfg(feature = "std")]
+            let other_expires_at = other.expires_at(other_idx);
+            other.entries_count -= 1;
+            let (key, value) = other.dealloc(other_idx);
+            if expired {
+                continue;
+            }
+            let bucket_idx = self.index(&key);
+            match self.find_live(bucket_idx, &key) {
+                Some(idx) => {
+                    // The entry is removed from the map while `f` runs, so a
+                    // panic in `f` leaves the map consistent, only without
+                    // the key. The merged entry then takes back the position
+                    // of the old one.
+                    let prev = self.node(idx).prev;
+                    #[cfg(feature = "std")]
+                    let expires_at = self.expires_at(idx);
+                    let (key, old) = self.remove_at(bucket_idx, idx);
+                    let value = f(&key, old, value);
+                    let idx = self.alloc(key, value);
+                    self.unlink(idx);
+                    self.link_after(idx, prev);
+                    #[cfg(feature = "std")]
+                    self.set_expires_at(idx, expires_at);
+                    self.buckets[bucket_idx].items.push(idx);
+                    self.entries_count += 1;
+                    self.updated(idx);
+                    self.make_room(bucket_idx, idx);
+                }
+                None => {
+                    let idx = self.insert_new(bucket_idx, key, value);
+                    #[cfg(feature = "std")]
+                    self.set_expires_at(idx, other_expires_at);
+                    self.make_room(bucket_idx, idx);
+                }
+            }
+        }
+    }
+
+    /// Visits the entries representing the union, i.e., all the entries in
+    /// `self` followed by the entries in `other` whose key is not in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let a: LinkedHashMap<_, _> =
+    ///     [(1, "a"), (2, "b")].iter().cloned().collect();
+    /// let b: LinkedHashMap<_, _> =
+    ///     [(2, "B"), (3, "C")].iter().cloned().collect();
+    ///
+    /// let mut union: Vec<_> = a.union(&b).collect();
+    /// union.sort();
+    /// assert_eq!(union, [(&1, &"a"), (&2, &"b"), (&3, &"C")]);
+    /// ```
+    pub fn union<'a>(
+        &'a self,
+        other: &'a LinkedHashMap<K, V, S>,
+    ) -> Union<'a, K, V, S> {
+        Union {
+            iter: self.iter().chain(other.difference(self)),
+        }
+    }
+
+    /// Visits the entries of `self` whose key is also in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let a: LinkedHashMap<_, _> =
+    ///     [(1, "a"), (2, "b")].iter().cloned().collect();
+    /// let b: LinkedHashMap<_, _> =
+    ///     [(2, "B"), (3, "C")].iter().cloned().collect();
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).collect();
+    /// assert_eq!(intersection, [(&2, &"b")]);
+    /// ```
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a LinkedHashMap<K, V, S>,
+    ) -> Intersection<'a, K, V, S> {
+        Intersection {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Visits the entries of `self` whose key is not in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let a: LinkedHashMap<_, _> =
+    ///     [(1, "a"), (2, "b")].iter().cloned().collect();
+    /// let b: LinkedHashMap<_, _> =
+    ///     [(2, "B"), (3, "C")].iter().cloned().collect();
+    ///
+    /// let difference: Vec<_> = a.difference(&b).collect();
+    /// assert_eq!(difference, [(&1, &"a")]);
+    /// ```
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a LinkedHashMap<K, V, S>,
+    ) -> Difference<'a, K, V, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be
+    /// inserted in the map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows [`usize`] or if the
+    /// allocator reports a failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map: LinkedHashMap<&str, i32> = LinkedHashMap::new();
+    /// map.reserve(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements to be
+    /// inserted in the map.
+    ///
+    /// Unlike [`reserve`], this does not panic when the allocation fails, the
+    /// error is returned instead and the entries of the map are left
+    /// unchanged.
+    ///
+    /// [`reserve`]: LinkedHashMap::reserve
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashMap;
+    ///
+    /// let mut map: LinkedHashMap<&str, i32> = LinkedHashMap::new();
+    /// map.try_reserve(10).unwrap();
+    /// assert!(map.capacity() >= 10);
+    ///
+    /// assert!(map.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError> {
+        let required = self.entries_count.saturating_add(additional);
+        if let Some(new_slots) = required.checked_sub(self.slots.len()) {
+            self.slots.try_reserve(new_slots)?;
+        }
+        if required <= bucket_capacity(self.buckets.len()) {
+            return Ok(());
+        }
+
+        // Keep doubling the number of buckets until all entries fit. If we
+        // overflow, asking for `usize::MAX` buckets will make the allocation
+        // fail with the appropriate error.
+        let mut target_size = self.buckets.len().max(1);
+        while bucket_capacity(target_size) < required {
+            target_size = match target_size.checked_mul(2) {
+                Some(n) => n,
+                None => {
+                    target_size = usize::MAX;
+                    break;
+                }
+            };
+        }
+        self.grow(target_size)
+    }
+
+    /// Increase the size of the array of buckets to `target_size` and reindex
+    /// all existing entries.
+    ///
+    /// All the memory that is needed is allocated before any entry is moved so
+    /// that the map stays intact when an allocation fails.
+    fn grow(&mut self, target_size: usize) -> Result<(), TryReserveError> {
+        let mut buckets = Vec::new();
+        buckets.try_reserve_exact(target_size)?;
+        buckets.extend((0..target_size).map(|_| Bucket::default()));
+
+        // Find the new bucket of every entry and the size of every bucket.
+        let mut indices = Vec::new();
+        indices.try_reserve_exact(self.entries_count)?;
+        indices.extend(
+            self.buckets
+                .iter()
+                .flat_map(|bucket| bucket.items.iter())
+                .map(|&idx| {
+                    derive_bucket_index(
+                        self.hasher_builder.build_hasher(),
+                        &self.node(idx).key,
+                        target_size,
+                    )
+                }),
+        );
+        let mut sizes = Vec::new();
+        sizes.try_reserve_exact(target_size)?;
+        sizes.resize(target_size, 0);
+        for &idx in &indices {
+            sizes[idx] += 1;
+        }
+        for (bucket, &size) in buckets.iter_mut().zip(&sizes) {
+            bucket.items.try_reserve_exact(size)?;
+        }
+
+        // Nothing can fail from here on.
+        for (idx, bucket_idx) in self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.items.iter())
+            .zip(indices)
+        {
+            buckets[bucket_idx].items.push(*idx);
+        }
+        self.buckets = buckets;
+        self.rehashes += 1;
+        Ok(())
+    }
+
+    /// Get the index of the bucket for `key`.
+    fn index<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        derive_bucket_index(
+            self.hasher_builder.build_hasher(),
+            key,
+            self.buckets.len(),
+        )
+    }
+
+    /// Get the slot of the entry whose key is `key` within the bucket at
+    /// `bucket_idx`.
+    fn find<Q>(&self, bucket_idx: usize, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.buckets[bucket_idx]
+            .items
+            .iter()
+            .copied()
+            .find(|&idx| self.node(idx).key.borrow() == key)
+    }
+
+    /// Get the slot of the entry whose key is `key` within the bucket at
+    /// `bucket_idx`, purging the entry if it has expired.
+    fn find_live<Q>(&mut self, bucket_idx: usize, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(bucket_idx, key)?;
+        if self.is_expired(idx) {
+            self.remove_at(bucket_idx, idx);
+            return None;
+        }
+        Some(idx)
+    }
+
+    /// Adds an entry whose key is not in the map to the bucket at
+    /// `bucket_idx`. Returns the slot of the new entry.
+    fn insert_new(&mut self, bucket_idx: usize, key: K, value: V) -> usize {
+        let idx = self.alloc(key, value);
+        self.buckets[bucket_idx].items.push(idx);
+        self.entries_count += 1;
+        self.reweigh(idx);
+        idx
+    }
+
+    /// Removes the entry at slot `idx` from the bucket at `bucket_idx` and
+    /// from the map.
+    fn remove_at(&mut self, bucket_idx: usize, idx: usize) -> (K, V) {
+        let items = &mut self.buckets[bucket_idx].items;
+        if let Some(pos) = items.iter().position(|&i| i == idx) {
+            items.swap_remove(pos);
+        }
+        self.entries_count -= 1;
+        if let Some(bound) = &mut self.bound {
+            bound.set_weight(idx, 0);
+        }
+        self.dealloc(idx)
+    }
+
+    /// Replaces the value of the entry at slot `idx`. Returns the old value.
+    fn replace_value(&mut self, idx: usize, value: V) -> V {
+        let old = core::mem::replace(&mut self.node_mut(idx).value, value);
+        self.updated(idx);
+        old
+    }
+
+    /// Updates the bookkeeping after the value of the entry at slot `idx` has
+    /// been replaced.
+    fn updated(&mut self, idx: usize) {
+        self.reweigh(idx);
+        self.accessed(idx);
+    }
+
+    /// Moves the entry at slot `idx` to the back of the map if the entries
+    /// are evicted in LRU order.
+    fn accessed(&mut self, idx: usize) {
+        if let Some(WeightBound {
+            policy: EvictionPolicy::Lru,
+            ..
+        }) = self.bound.as_deref()
+        {
+            self.unlink(idx);
+            self.link_back(idx);
+        }
+    }
+
+    /// Recomputes the weight of the entry at slot `idx`.
+    fn reweigh(&mut self, idx: usize) {
+        if let (Some(bound), Slot::Occupied(node)) =
+            (&mut self.bound, &self.slots[idx])
+        {
+            let weight = (bound.weigher)(&node.key, &node.value);
+            bound.set_weight(idx, weight);
+        }
+    }
+
+    /// Evicts the entry at slot `idx` in the bucket at `bucket_idx` if it is
+    /// heavier than the limit on its own. Otherwise, evicts other entries
+    /// until the total weight fits under the limit.
+    fn make_room(&mut self, bucket_idx: usize, idx: usize) {
+        match &self.bound {
+            Some(bound) if bound.weight(idx) > bound.limit => {
+                self.remove_at(bucket_idx, idx);
+                self.enforce_bound(None);
+            }
+            _ => self.enforce_bound(Some(idx)),
+        }
+    }
+
+    /// Evicts entries from the front of the map, except the one at slot
+    /// `keep` and the ones heavier than the limit on their own, until the
+    /// total weight of the others fits under the limit.
+    fn enforce_bound(&mut self, keep: Option<usize>) {
+        let mut it = self.head;
+        while let Some(idx) = it {
+            let oversized = match &self.bound {
+                Some(bound) if bound.is_exceeded() => {
+                    bound.weight(idx) > bound.limit
+                }
+                _ => break,
+            };
+            let node = self.node(idx);
+            it = node.next;
+            if Some(idx) != keep && !oversized {
+                let bucket_idx = self.index(&node.key);
+                self.remove_at(bucket_idx, idx);
+            }
+        }
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for LinkedHashMap<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).unwrap()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for LinkedHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+/// An iterator over the elements of a [`LinkedHashMap`] in insertion order.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug)]
 pub struct Iter<'a, K, V, S> {
     map: &'a LinkedHashMap<K, V, S>,
-    bucket_idx: usize,
-    bucket_entry_idx: usize,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
 }
 
 impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
     type Item = (&'a K, &'a V);
 
-    /// We keep two indices, one index for the bucket and one index for the 
-    /// entry within the bucket that is currently pointed at.
+    /// We keep the slots at both ends of the part of the list that has not
+    /// been visited, and the number of entries in between so the two ends know
+    /// when they have met.
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(bucket) = self.map.buckets.get(self.bucket_idx) {
-            if let Some(&(ref key, ref value)) =
-                bucket.items.get(self.bucket_entry_idx)
-            {
-                self.bucket_entry_idx += 1;
-                return Some((key, value));
-            }
-            self.bucket_idx += 1;
-            self.bucket_entry_idx = 0;
+        if self.len == 0 {
+            return None;
         }
-        None
+        let node = self.map.node(self.front?);
+        self.front = node.next;
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for Iter<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.map.node(self.back?);
+        self.back = node.prev;
+        self.len -= 1;
+        Some((&node.key, &node.value))
     }
 }
 
+impl<'a, K, V, S> ExactSizeIterator for Iter<'a, K, V, S> {}
+
 impl<'a, K, V, S> IntoIterator for &'a LinkedHashMap<K, V, S> {
     type Item = (&'a K, &'a V);
 
     type IntoIter = Iter<'a, K, V, S>;
 
     fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            map: self,
-            bucket_idx: 0,
-            bucket_entry_idx: 0,
-        }
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`LinkedHashMap`] in a sorted order.
+///
+/// This struct is created by [`LinkedHashMap::iter_sorted_by()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::iter_sorted_by()`]: crate::containers::LinkedHashMap::iter_sorted_by
+#[derive(Debug)]
+pub struct SortedIter<'a, K, V, S> {
+    map: &'a LinkedHashMap<K, V, S>,
+    order: alloc::vec::IntoIter<usize>,
+}
+
+impl<'a, K, V, S> Iterator for SortedIter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.map.node(self.order.next()?);
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for SortedIter<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.map.node(self.order.next_back()?);
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for SortedIter<'a, K, V, S> {}
+
+/// An iterator over the keys of a [`LinkedHashMap`] in ascending order.
+///
+/// This struct is created by [`LinkedHashMap::keys_sorted()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::keys_sorted()`]: crate::containers::LinkedHashMap::keys_sorted
+#[derive(Debug)]
+pub struct SortedKeys<'a, K, V, S> {
+    iter: SortedIter<'a, K, V, S>,
+}
+
+impl<'a, K, V, S> Iterator for SortedKeys<'a, K, V, S> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for SortedKeys<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
     }
 }
 
-/// An iterator over the elements of a [`LinkedHashMap`].
+impl<'a, K, V, S> ExactSizeIterator for SortedKeys<'a, K, V, S> {}
+
+/// An owning iterator over the elements of a [`LinkedHashMap`] in insertion
+/// order.
 ///
 /// [`LinkedHashMap`]: crate::containers::LinkedHashMap
 #[derive(Debug)]
 pub struct IntoIter<K, V, S> {
     map: LinkedHashMap<K, V, S>,
-    bucket_idx: usize,
 }
 
 impl<K, V, S> Iterator for IntoIter<K, V, S> {
     type Item = (K, V);
 
-    /// We keep two indices, one index for the bucket and one index for the 
-    /// entry within the bucket that is currently pointed at.
+    /// The entries are taken out of their slots one by one starting from the
+    /// head of the list. The buckets are left as they are since the map is
+    /// never looked up again.
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(bucket) = self.map.buckets.get_mut(self.bucket_idx) {
-            if let Some((key, value)) = bucket.items.pop() {
-                return Some((key, value));
-            }
-            self.bucket_idx += 1;
-        }
-        None
+        let idx = self.map.head?;
+        self.map.entries_count -= 1;
+        Some(self.map.dealloc(idx))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.entries_count, Some(self.map.entries_count))
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for IntoIter<K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let idx = self.map.tail?;
+        self.map.entries_count -= 1;
+        Some(self.map.dealloc(idx))
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for IntoIter<K, V, S> {}
+
+impl<K, V, S> IntoIterator for LinkedHashMap<K, V, S> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Self::IntoIter { map: self }
+    }
+}
+
+/// A lazy iterator producing the entries in the union of two
+/// [`LinkedHashMap`]s.
+///
+/// This struct is created by [`LinkedHashMap::union()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::union()`]: crate::containers::LinkedHashMap::union
+#[derive(Debug)]
+pub struct Union<'a, K, V, S> {
+    iter: Chain<Iter<'a, K, V, S>, Difference<'a, K, V, S>>,
+}
+
+impl<'a, K, V, S> Iterator for Union<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+/// A lazy iterator producing the entries of a [`LinkedHashMap`] whose key is
+/// also in another map.
+///
+/// This struct is created by [`LinkedHashMap::intersection()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::intersection()`]: crate::containers::LinkedHashMap::intersection
+#[derive(Debug)]
+pub struct Intersection<'a, K, V, S> {
+    iter: Iter<'a, K, V, S>,
+    other: &'a LinkedHashMap<K, V, S>,
+}
+
+impl<'a, K, V, S> Iterator for Intersection<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.find(|&(key, _)| other.contains_key(key))
     }
 }
 
-impl<K, V, S> IntoIterator for LinkedHashMap<K, V, S> {
-    type Item = (K, V);
+/// A lazy iterator producing the entries of a [`LinkedHashMap`] whose key is
+/// not in another map.
+///
+/// This struct is created by [`LinkedHashMap::difference()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::difference()`]: crate::containers::LinkedHashMap::difference
+#[derive(Debug)]
+pub struct Difference<'a, K, V, S> {
+    iter: Iter<'a, K, V, S>,
+    other: &'a LinkedHashMap<K, V, S>,
+}
 
-    type IntoIter = IntoIter<K, V, S>;
+impl<'a, K, V, S> Iterator for Difference<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
 
-    fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            map: self,
-            bucket_idx: 0,
-        }
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.find(|&(key, _)| !other.contains_key(key))
     }
 }
 
@@ -649,11 +2176,9 @@ where
                 bucket_idx,
                 map,
             }) => {
-                map.insert(key, value);
-                // unwrap() cause we just inserted a value
-                let &mut (_, ref mut value) =
-                    map.buckets[bucket_idx].items.last_mut().unwrap();
-                value
+                let idx = map.insert_new(bucket_idx, key, value);
+                map.enforce_bound(Some(idx));
+                &mut map.node_mut(idx).value
             }
         }
     }
@@ -677,19 +2202,224 @@ where
                 map,
             }) => {
                 let value = f(&key);
+                let idx = map.insert_new(bucket_idx, key, value);
+                map.enforce_bound(Some(idx));
+                &mut map.node_mut(idx).value
+            }
+        }
+    }
+}
+
+/// A view into a vacant entry in a [`LinkedHashMap`] that holds a borrowed
+/// form of its key. It is part of the [`EntryRef`] enum.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[derive(Debug)]
+pub struct VacantEntryRef<'a, 'b, K, Q: ?Sized, V, S> {
+    key: &'b Q,
+    bucket_idx: usize,
+    map: &'a mut LinkedHashMap<K, V, S>,
+}
+
+/// A view into a single entry in a map, which may either be vacant or
+/// occupied, that is looked up with a borrowed form of its key.
+///
+/// This enum is constructed from the [`LinkedHashMap::entry_ref()`] method.
+///
+/// [`LinkedHashMap::entry_ref()`]: crate::containers::LinkedHashMap::entry_ref
+#[derive(Debug)]
+pub enum EntryRef<'a, 'b, K, Q: ?Sized, V, S> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// A vacant entry.
+    Vacant(VacantEntryRef<'a, 'b, K, Q, V, S>),
+}
+
+impl<'a, 'b, K, Q, V, S> EntryRef<'a, 'b, K, Q, V, S>
+where
+    K: Borrow<Q>,
+    Q: ?Sized,
+{
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &Q {
+        match *self {
+            Self::Occupied(OccupiedEntry { key, .. }) => key.borrow(),
+            Self::Vacant(VacantEntryRef { key, .. }) => key,
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Self::Occupied(occupied_entry) = self {
+            f(occupied_entry.value);
+            return Self::Occupied(occupied_entry);
+        }
+        self
+    }
+}
+
+impl<'a, 'b, K, Q, V, S> EntryRef<'a, 'b, K, Q, V, S>
+where
+    K: Hash + Eq + Borrow<Q> + From<&'b Q>,
+    Q: ?Sized,
+    S: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting the default value if
+    /// empty, and returns a mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+
+    /// Ensures a value is in the entry by inserting the default if empty, and
+    /// returns a mutable reference to the value in the entry.
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        self.or_insert_with(|| value)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default
+    /// function if empty, and returns a mutable reference to the value in the
+    /// entry.
+    pub fn or_insert_with<F>(self, f: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        self.or_insert_with_key(|_| f())
+    }
+
+    /// Ensures a value is in the entry by inserting, if empty, the result of
+    /// the default function called with a borrowed form of the key, and
+    /// returns a mutable reference to the value in the entry.
+    pub fn or_insert_with_key<F>(self, f: F) -> &'a mut V
+    where
+        F: FnOnce(&Q) -> V,
+    {
+        match self {
+            Self::Occupied(OccupiedEntry { value, .. }) => value,
+            Self::Vacant(VacantEntryRef {
+                key,
+                bucket_idx,
+                map,
+            }) => {
+                let value = f(key);
+                let idx = map.insert_new(bucket_idx, K::from(key), value);
+                map.enforce_bound(Some(idx));
+                &mut map.node_mut(idx).value
+            }
+        }
+    }
+}
+
+/// An operation on a [`LinkedHashMap`] that can be generated by
+/// [`arbitrary`].
+///
+/// The [`Arbitrary`] implementation of [`LinkedHashMap`] builds the map by
+/// applying a sequence of these operations to an empty map, so the generated
+/// maps have gone through resizes and removals. Fuzz targets can also generate
+/// a sequence of operations themselves and replay it on both a
+/// [`LinkedHashMap`] and a model to compare their behaviors.
+///
+/// [`arbitrary`]: https://docs.rs/arbitrary
+/// [`Arbitrary`]: arbitrary::Arbitrary
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+#[cfg(feature = "arbitrary")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkedHashMapOp<K, V> {
+    /// Calls [`LinkedHashMap::insert`] with the key and the value.
+    ///
+    /// [`LinkedHashMap::insert`]: crate::containers::LinkedHashMap::insert
+    Insert(K, V),
+    /// Calls [`LinkedHashMap::remove`] with the key.
+    ///
+    /// [`LinkedHashMap::remove`]: crate::containers::LinkedHashMap::remove
+    Remove(K),
+    /// Calls [`LinkedHashMap::reserve`] with the number of elements.
+    ///
+    /// [`LinkedHashMap::reserve`]: crate::containers::LinkedHashMap::reserve
+    Reserve(usize),
+}
+
+#[cfg(feature = "arbitrary")]
+impl<K, V> LinkedHashMapOp<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Applies the operation to `map`.
+    pub fn apply<S>(self, map: &mut LinkedHashMap<K, V, S>)
+    where
+        S: BuildHasher,
+    {
+        match self {
+            Self::Insert(key, value) => {
                 map.insert(key, value);
-                // unwrap() cause we just inserted a value
-                let &mut (_, ref mut value) =
-                    map.buckets[bucket_idx].items.last_mut().unwrap();
-                value
             }
+            Self::Remove(key) => {
+                map.remove(&key);
+            }
+            Self::Reserve(additional) => map.reserve(additional),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for LinkedHashMapOp<K, V>
+where
+    K: arbitrary::Arbitrary<'a>,
+    V: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(
+        u: &mut arbitrary::Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=4u8)? {
+            // Inserting more often than removing lets the map grow.
+            0..=2 => Self::Insert(u.arbitrary()?, u.arbitrary()?),
+            3 => Self::Remove(u.arbitrary()?),
+            // Keep the reservations small so that the fuzzer does not spend
+            // its time allocating.
+            _ => Self::Reserve(usize::from(u.arbitrary::<u8>()?)),
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V, S> arbitrary::Arbitrary<'a> for LinkedHashMap<K, V, S>
+where
+    K: arbitrary::Arbitrary<'a> + Hash + Eq,
+    V: arbitrary::Arbitrary<'a>,
+    S: BuildHasher + Default,
+{
+    fn arbitrary(
+        u: &mut arbitrary::Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        let mut map = Self::default();
+        for op in u.arbitrary_iter::<LinkedHashMapOp<K, V>>()? {
+            op?.apply(&mut map);
+        }
+        Ok(map)
+    }
+
+    fn arbitrary_take_rest(
+        u: arbitrary::Unstructured<'a>,
+    ) -> arbitrary::Result<Self> {
+        let mut map = Self::default();
+        for op in u.arbitrary_take_rest_iter::<LinkedHashMapOp<K, V>>()? {
+            op?.apply(&mut map);
         }
+        Ok(map)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "std")]
+    use crate::containers::ManualClock;
     use std::collections::HashMap;
 
     #[test]
@@ -711,13 +2441,17 @@ mod tests {
         assert_eq!(map.len(), 1);
         assert!(!map.is_empty());
 
+        *map.get_mut(&"foo").unwrap() += 1;
+        assert_eq!(map.get(&"foo"), Some(&44));
+
         // Test removal.
-        assert_eq!(map.remove(&"foo"), Some(43));
+        assert_eq!(map.remove(&"foo"), Some(44));
         assert_eq!(map.len(), 0);
         assert!(map.is_empty());
 
         // Test operations on a non-existent key.
         assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.get_mut(&"foo"), None);
         assert_eq!(map.remove(&"foo"), None);
         assert_eq!(map.len(), 0);
         assert!(map.is_empty());
@@ -767,4 +2501,537 @@ mod tests {
         // Check if the iterator has gone through all items.
         assert!(has_seen.iter().all(|(_, &v)| v));
     }
+
+    #[test]
+    fn empty_map_lookups() {
+        // A new map has no bucket to hash the keys into.
+        let mut map: LinkedHashMap<&str, i32> = LinkedHashMap::new();
+        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.remove(&"foo"), None);
+        assert!(!map.contains_key(&"foo"));
+    }
+
+    #[test]
+    fn reserve() {
+        let mut map = LinkedHashMap::new();
+        for i in 0..10 {
+            map.insert(i, i * 2);
+        }
+
+        // Reserving does not lose any entry.
+        map.try_reserve(100).unwrap();
+        assert!(map.capacity() >= 110);
+        assert_eq!(map.len(), 10);
+        for i in 0..10 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+
+        // A failed reservation leaves the map untouched.
+        let capacity = map.capacity();
+        assert!(map.try_reserve(usize::MAX).is_err());
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.len(), 10);
+    }
+
+    #[test]
+    fn stats() {
+        let map: LinkedHashMap<u32, u32> = LinkedHashMap::new();
+        let stats = map.stats();
+        assert_eq!(stats.buckets, 0);
+        assert_eq!(stats.entries, 0);
+        assert!(stats.occupancy.is_empty());
+        assert_eq!(stats.longest_chain, 0);
+        assert_eq!(stats.rehashes, 0);
+
+        let map: LinkedHashMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        let stats = map.stats();
+        assert_eq!(stats.entries, 1000);
+        assert_eq!(stats.buckets, stats.occupancy.iter().sum());
+        assert_eq!(
+            stats.entries,
+            stats
+                .occupancy
+                .iter()
+                .enumerate()
+                .map(|(chain, n)| chain * n)
+                .sum()
+        );
+        assert_eq!(stats.longest_chain + 1, stats.occupancy.len());
+        assert_ne!(stats.occupancy.last(), Some(&0));
+        assert_eq!(
+            stats.rehashes,
+            map.buckets.len().trailing_zeros() as usize + 1
+        );
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        let b: LinkedHashMap<_, _> = (5..15).map(|i| (i, i * 10)).collect();
+        let empty = LinkedHashMap::new();
+
+        let mut union: Vec<_> = a.union(&b).map(|(&k, &v)| (k, v)).collect();
+        union.sort_unstable();
+        let expected: Vec<_> = (0..10)
+            .map(|i| (i, i))
+            .chain((10..15).map(|i| (i, i * 10)))
+            .collect();
+        assert_eq!(union, expected);
+
+        let mut intersection: Vec<_> =
+            a.intersection(&b).map(|(&k, _)| k).collect();
+        intersection.sort_unstable();
+        assert_eq!(intersection, (5..10).collect::<Vec<_>>());
+
+        let mut difference: Vec<_> =
+            a.difference(&b).map(|(&k, _)| k).collect();
+        difference.sort_unstable();
+        assert_eq!(difference, (0..5).collect::<Vec<_>>());
+
+        assert_eq!(a.union(&empty).count(), 10);
+        assert_eq!(a.intersection(&empty).count(), 0);
+        assert_eq!(a.difference(&empty).count(), 10);
+        assert_eq!(empty.union(&a).count(), 10);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        let b: LinkedHashMap<_, _> = (5..15).map(|i| (i, i * 10)).collect();
+
+        let mut conflicts = 0;
+        a.merge(b, |_, old, new| {
+            conflicts += 1;
+            old + new
+        });
+        assert_eq!(conflicts, 5);
+        assert_eq!(a.len(), 15);
+        for i in 0..5 {
+            assert_eq!(a[&i], i);
+        }
+        for i in 5..10 {
+            assert_eq!(a[&i], i * 11);
+        }
+        for i in 10..15 {
+            assert_eq!(a[&i], i * 10);
+        }
+        // Merged keys keep their position.
+        assert!(a.iter().map(|(&k, _)| k).eq(0..15));
+    }
+
+    #[test]
+    fn merge_panic() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut a: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        let b: LinkedHashMap<_, _> = (5..15).map(|i| (i, i * 10)).collect();
+
+        // Panicking on the first conflict drops the conflicting key along
+        // with the rest of `b`.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            a.merge(b, |_, _, _| panic!("merge failed"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(a.len(), 9);
+        assert_eq!(a.get(&5), None);
+        assert!(a.iter().map(|(&k, _)| k).eq((0..5).chain(6..10)));
+        assert!(a
+            .iter()
+            .rev()
+            .map(|(&k, _)| k)
+            .eq((0..5).chain(6..10).rev()));
+
+        // The map is still usable afterwards.
+        assert_eq!(a.remove(&6), Some(6));
+        a.insert(5, 50);
+        assert!(a
+            .iter()
+            .map(|(&k, _)| k)
+            .eq((0..5).chain(7..10).chain(5..6)));
+        assert_eq!(a.len(), 9);
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut map = LinkedHashMap::new();
+        for i in (0..100).rev() {
+            map.insert(i, i);
+        }
+        assert!(map.iter().map(|(&k, _)| k).eq((0..100).rev()));
+        assert!(map.iter().rev().map(|(&k, _)| k).eq(0..100));
+
+        // Updating an existing key does not move it.
+        assert_eq!(map.insert(50, 50), Some(50));
+        assert!(map.iter().map(|(&k, _)| k).eq((0..100).rev()));
+
+        // Removed keys go to the back when they are re-inserted, reusing the
+        // freed slots.
+        for i in (0..100).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        let slots = map.slots.len();
+        for i in (0..100).step_by(2) {
+            map.entry(i).or_insert(i);
+        }
+        assert_eq!(map.slots.len(), slots);
+        let expected: Vec<_> = (1..100)
+            .rev()
+            .step_by(2)
+            .chain((0..100).step_by(2))
+            .collect();
+        assert_eq!(map.iter().map(|(&k, _)| k).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            map.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn entry_ref() {
+        let mut map: LinkedHashMap<String, usize> = LinkedHashMap::new();
+
+        let entry = map.entry_ref("foo");
+        assert_eq!(entry.key(), "foo");
+        assert!(matches!(entry, EntryRef::Vacant(_)));
+        *entry.or_default() += 1;
+
+        let entry = map.entry_ref("foo").and_modify(|v| *v += 10);
+        assert!(matches!(entry, EntryRef::Occupied(_)));
+        assert_eq!(*entry.or_insert(0), 11);
+
+        map.entry_ref("bar").or_insert_with_key(|key| key.len());
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["foo"], 11);
+        assert_eq!(map["bar"], 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ttl() {
+        let clock = ManualClock::new();
+        let mut map = LinkedHashMap::new();
+        map.set_clock(clock.clone());
+
+        map.insert_with_ttl("foo", 1, Duration::from_secs(1));
+        map.insert_with_ttl("bar", 2, Duration::from_secs(2));
+        map.insert_with_ttl("baz", 3, Duration::from_secs(1));
+        map.insert("quox", 4);
+        assert_eq!(map.get("foo"), Some(&1));
+
+        // Inserting without a TTL makes the entry permanent.
+        map.insert("baz", 5);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(map.get("foo"), None);
+        assert!(!map.contains_key("foo"));
+        assert_eq!(map.get("bar"), Some(&2));
+        assert_eq!(map.get("baz"), Some(&5));
+        assert_eq!(map.len(), 4);
+
+        // Expired entries are purged when they are accessed mutably.
+        assert_eq!(map.remove("foo"), None);
+        assert_eq!(map.len(), 3);
+        assert_eq!(*map.entry("foo").or_insert(6), 6);
+        assert_eq!(map.len(), 4);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(map.evict_expired(), 1);
+        assert_eq!(map.len(), 3);
+        assert!(map.iter().map(|(&k, &v)| (k, v)).eq(vec![
+            ("baz", 5),
+            ("quox", 4),
+            ("foo", 6)
+        ]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ttl_slot_reuse() {
+        let clock = ManualClock::new();
+        let mut map = LinkedHashMap::new();
+        map.set_clock(clock.clone());
+
+        // A key inserted in the slot of an entry that had an expiry time does
+        // not inherit it.
+        map.insert_with_ttl("foo", 1, Duration::from_secs(1));
+        assert_eq!(map.remove("foo"), Some(1));
+        map.insert("bar", 2);
+        assert_eq!(map.slots.len(), 1);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(map.get("bar"), Some(&2));
+        assert_eq!(map.evict_expired(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn merge_ttl() {
+        let clock = ManualClock::new();
+        let mut a = LinkedHashMap::new();
+        a.set_clock(clock.clone());
+        let mut b = LinkedHashMap::new();
+        b.set_clock(clock.clone());
+
+        a.insert_with_ttl("both", 1, Duration::from_secs(2));
+        b.insert("both", 10);
+        b.insert_with_ttl("expired", 20, Duration::from_secs(1));
+        b.insert_with_ttl("live", 30, Duration::from_secs(3));
+        b.insert("plain", 40);
+        clock.advance(Duration::from_secs(1));
+
+        // The expired entry of `b` is not brought back to life, and the
+        // entries that can still expire keep their expiry time.
+        a.merge(b, |_, old, new| old + new);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.get("expired"), None);
+        assert_eq!(a.get("both"), Some(&11));
+        assert_eq!(a.get("live"), Some(&30));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(a.get("both"), None);
+        assert_eq!(a.get("live"), Some(&30));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(a.get("live"), None);
+        assert_eq!(a.evict_expired(), 2);
+        assert!(a.iter().eq(vec![(&"plain", &40)]));
+    }
+
+    #[test]
+    fn plain_map_layout() {
+        // The expiry times and the weights are kept out of the entries.
+        assert_eq!(
+            core::mem::size_of::<Node<u64, u64>>(),
+            core::mem::size_of::<(u64, u64, Option<usize>, Option<usize>)>()
+        );
+
+        // Maps that do not use them do not allocate for them.
+        let mut map: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        map.insert(1, 10);
+        map.remove(&0);
+        #[cfg(feature = "std")]
+        assert!(map.expiry.is_none());
+        assert!(map.bound.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn pop_front_skips_expired() {
+        let clock = ManualClock::new();
+        let mut map = LinkedHashMap::new();
+        map.set_clock(clock.clone());
+
+        map.insert_with_ttl("foo", 1, Duration::from_secs(1));
+        map.insert("bar", 2);
+        map.insert_with_ttl("baz", 3, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(map.pop_front(), Some(("bar", 2)));
+        assert_eq!(map.pop_front(), None);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn weight_bound() {
+        // FIFO ignores accesses.
+        let mut map = LinkedHashMap::new();
+        map.set_weight_bound(3, EvictionPolicy::Fifo, |_, _| 1);
+        for i in 0..3 {
+            map.insert(i, i);
+        }
+        map.insert(0, 10);
+        map.get_refresh(&1);
+        map.insert(3, 3);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![2, 1, 3]));
+        assert_eq!(map.total_weight(), 3);
+
+        // LRU moves entries to the back whenever they are used.
+        let mut map = LinkedHashMap::new();
+        map.set_weight_bound(3, EvictionPolicy::Lru, |_, _| 1);
+        for i in 0..3 {
+            map.insert(i, i);
+        }
+        map.insert(0, 10);
+        *map.entry(1).or_insert(0) += 1;
+        map.insert(3, 3);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![0, 1, 3]));
+        assert_eq!(map.total_weight(), 3);
+
+        // Heavy entries evict as many entries as needed, but not themselves.
+        let mut map = LinkedHashMap::new();
+        map.set_weight_bound(10, EvictionPolicy::Fifo, |_, &v| v);
+        for i in 1..=4 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.total_weight(), 10);
+        map.insert(5, 9);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![5]));
+        assert_eq!(map.total_weight(), 9);
+        map.insert(6, 1);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![5, 6]));
+        map.insert(7, 1);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![6, 7]));
+
+        // Replacing a value updates its weight, and removing an entry gives
+        // its weight back, so a key inserted in its slot weighs only itself.
+        map.insert(6, 5);
+        assert_eq!(map.total_weight(), 6);
+        map.remove(&6);
+        assert_eq!(map.total_weight(), 1);
+        map.insert(8, 2);
+        assert_eq!(map.total_weight(), 3);
+
+        // Setting a bound evicts the entries that do not fit.
+        let mut map: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        map.set_weight_bound(4, EvictionPolicy::Lru, |_, _| 2);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![8, 9]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Total weight overflowed")]
+    fn weight_bound_overflow() {
+        let mut map = LinkedHashMap::new();
+        map.set_weight_bound(usize::MAX, EvictionPolicy::Fifo, |_, &v| v);
+        map.insert(1, usize::MAX);
+        map.insert(2, 1);
+    }
+
+    #[test]
+    fn weight_bound_oversized() {
+        let mut map = LinkedHashMap::new();
+        map.set_weight_bound(10, EvictionPolicy::Fifo, |_, &v| v);
+        for i in 1..=4 {
+            map.insert(i, i);
+        }
+
+        // Entries heavier than the limit are evicted right away, and leave
+        // the others alone.
+        assert_eq!(map.insert(5, 20), None);
+        assert!(!map.contains_key(&5));
+        assert!(map.iter().map(|(&k, _)| k).eq(1..=4));
+        assert_eq!(map.total_weight(), 10);
+
+        // Replacing a value with one that is too heavy evicts the key.
+        assert_eq!(map.insert(2, 11), Some(2));
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![1, 3, 4]));
+        assert_eq!(map.total_weight(), 8);
+        let other: LinkedHashMap<_, _> =
+            vec![(3, 8), (6, 2)].into_iter().collect();
+        map.merge(other, |_, old, new| old + new);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![1, 4, 6]));
+        assert_eq!(map.total_weight(), 7);
+
+        // The entry API has to hand out a reference to the entry, so the
+        // entry stays until it is replaced, without evicting the others.
+        assert_eq!(*map.entry(7).or_insert(30), 30);
+        assert_eq!(*map.entry(9).or_insert_with_key(|_| 40), 40);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![1, 4, 6, 7, 9]));
+        assert_eq!(map.total_weight(), 77);
+        map.insert(8, 5);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![6, 7, 9, 8]));
+        assert_eq!(map.total_weight(), 77);
+        assert_eq!(map.insert(7, 5), Some(30));
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![7, 9, 8]));
+        assert_eq!(map.total_weight(), 50);
+        assert_eq!(map.remove(&9), Some(40));
+        assert_eq!(map.total_weight(), 10);
+
+        // Setting a bound evicts the entries that can never fit first.
+        let mut map: LinkedHashMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        map.set_weight_bound(8, EvictionPolicy::Fifo, |_, &v| v);
+        assert!(map.iter().map(|(&k, _)| k).eq(vec![8]));
+        map.set_weight_bound(7, EvictionPolicy::Fifo, |_, &v| v);
+        assert!(map.is_empty());
+        assert_eq!(map.total_weight(), 0);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Replay arbitrary operations on both the map and a model.
+        let bytes: Vec<u8> =
+            (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut u = Unstructured::new(&bytes);
+        let mut map = LinkedHashMap::new();
+        let mut model = HashMap::new();
+        while !u.is_empty() {
+            let op = LinkedHashMapOp::<u8, u8>::arbitrary(&mut u).unwrap();
+            match op.clone() {
+                LinkedHashMapOp::Insert(k, v) => {
+                    model.insert(k, v);
+                }
+                LinkedHashMapOp::Remove(k) => {
+                    model.remove(&k);
+                }
+                LinkedHashMapOp::Reserve(_) => {}
+            }
+            op.apply(&mut map);
+            assert_eq!(map.len(), model.len());
+        }
+        for (k, v) in &model {
+            assert_eq!(map.get(k), Some(v));
+        }
+
+        let map = LinkedHashMap::<u16, u16>::arbitrary_take_rest(
+            Unstructured::new(&bytes),
+        )
+        .unwrap();
+        assert_eq!(map.iter().count(), map.len());
+    }
+
+    #[test]
+    fn generic_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type Map = LinkedHashMap<u32, u32, BuildHasherDefault<DefaultHasher>>;
+        let mut map = Map::default();
+        map.insert(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+
+        // The same hasher can be used to hash keys outside of the map.
+        let hash = |map: &Map, key: u32| map.hasher().hash_one(key);
+        let other: Map = (0..10).map(|i| (i, i)).collect();
+        assert_eq!(hash(&map, 42), hash(&other, 42));
+    }
+
+    #[test]
+    fn sorted_iteration() {
+        let mut map = LinkedHashMap::new();
+        for &k in &[5, 3, 9, 1, 7] {
+            map.insert(k, k % 3);
+        }
+        let keys: Vec<_> = map.keys_sorted().copied().collect();
+        assert_eq!(keys, vec![1, 3, 5, 7, 9]);
+        let keys: Vec<_> = map.keys_sorted().rev().copied().collect();
+        assert_eq!(keys, vec![9, 7, 5, 3, 1]);
+
+        // Entries with equal values keep their insertion order.
+        let entries: Vec<_> = map
+            .iter_sorted_by(|(_, v1), (_, v2)| v1.cmp(v2))
+            .map(|(&k, _)| k)
+            .collect();
+        assert_eq!(entries, vec![3, 9, 1, 7, 5]);
+
+        // The map is left in insertion order.
+        let keys: Vec<_> = map.iter().map(|(&k, _)| k).collect();
+        assert_eq!(keys, vec![5, 3, 9, 1, 7]);
+    }
+
+    #[test]
+    fn fnv_hasher() {
+        use crate::containers::FnvBuildHasher;
+
+        let mut map = LinkedHashMap::with_hasher(FnvBuildHasher::default());
+        for i in 0..100 {
+            map.insert(i, i * 2);
+        }
+        assert_eq!(map.len(), 100);
+        assert!((0..100).all(|i| map.get(&i) == Some(&(i * 2))));
+
+        // Hashing is deterministic, so two maps built the same way lay out
+        // their entries the same way.
+        let other: LinkedHashMap<_, _, FnvBuildHasher> =
+            (0..100).map(|i| (i, i * 2)).collect();
+        assert_eq!(map.stats().occupancy, other.stats().occupancy);
+    }
 }
@@ -0,0 +1,603 @@
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::{Chain, FromIterator};
+
+use super::linked_hash_map::{self, DefaultHashBuilder, LinkedHashMap};
+
+/// A hash set that remembers the order in which its values were inserted,
+/// implemented as a [`LinkedHashMap`] where the value is `()`.
+///
+/// As with the [`LinkedHashMap`] type, a `LinkedHashSet` requires that the
+/// elements implement the [`Eq`] and [`Hash`] traits.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::LinkedHashSet;
+///
+/// let mut books = LinkedHashSet::new();
+///
+/// // Add some books.
+/// books.insert("A Dance With Dragons".to_string());
+/// books.insert("To Kill a Mockingbird".to_string());
+/// books.insert("The Odyssey".to_string());
+/// books.insert("The Great Gatsby".to_string());
+///
+/// // Check for a specific one.
+/// if !books.contains("The Winds of Winter") {
+///     println!("We have {} books, but The Winds of Winter ain't one.",
+///              books.len());
+/// }
+///
+/// // Remove a book.
+/// books.remove("The Odyssey");
+///
+/// // Iterate over everything, in the order the books were added.
+/// for book in &books {
+///     println!("{}", book);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LinkedHashSet<T, S = DefaultHashBuilder> {
+    map: LinkedHashMap<T, (), S>,
+}
+
+impl<T, S> Default for LinkedHashSet<T, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<T> LinkedHashSet<T, DefaultHashBuilder> {
+    /// Creates an empty `LinkedHashSet`.
+    ///
+    /// The hash set is initially created with an empty list of buckets, so it
+    /// will not allocate until it is first inserted into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    /// let set: LinkedHashSet<i32> = LinkedHashSet::new();
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T, S> LinkedHashSet<T, S> {
+    /// Creates an empty `LinkedHashSet` which will use the given hash builder
+    /// to hash values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let s = RandomState::new();
+    /// let mut set = LinkedHashSet::with_hasher(s);
+    /// set.insert(2);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self {
+            map: LinkedHashMap::with_hasher(hasher_builder),
+        }
+    }
+
+    /// Returns a reference to the set's [`BuildHasher`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let set: LinkedHashSet<i32> = LinkedHashSet::new();
+    /// let hasher: &RandomState = set.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Returns the number of elements in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut v = LinkedHashSet::new();
+    /// assert_eq!(v.len(), 0);
+    /// v.insert(1);
+    /// assert_eq!(v.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut v = LinkedHashSet::new();
+    /// assert!(v.is_empty());
+    /// v.insert(1);
+    /// assert!(!v.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// An iterator visiting all elements in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut set = LinkedHashSet::new();
+    /// set.insert("c");
+    /// set.insert("a");
+    /// set.insert("b");
+    ///
+    /// let values: Vec<_> = set.iter().collect();
+    /// assert_eq!(values, [&"c", &"a", &"b"]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+}
+
+impl<T, S> LinkedHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Adds a value to the set.
+    ///
+    /// If the set did not have this value present, true is returned.
+    ///
+    /// If the set did have this value present, false is returned and the
+    /// position of the value in the insertion order is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut set = LinkedHashSet::new();
+    ///
+    /// assert_eq!(set.insert(2), true);
+    /// assert_eq!(set.insert(2), false);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Returns true if the set contains a value.
+    ///
+    /// The value may be any borrowed form of the set’s value type, but Hash
+    /// and Eq on the borrowed form must match those for the value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let set: LinkedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// assert_eq!(set.contains(&1), true);
+    /// assert_eq!(set.contains(&4), false);
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Removes a value from the set. Returns whether the value was present in
+    /// the set.
+    ///
+    /// The value may be any borrowed form of the set’s value type, but Hash
+    /// and Eq on the borrowed form must match those for the value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut set = LinkedHashSet::new();
+    ///
+    /// set.insert(2);
+    /// assert_eq!(set.remove(&2), true);
+    /// assert_eq!(set.remove(&2), false);
+    /// ```
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Removes the least recently inserted value from the set and returns it,
+    /// or [`None`] if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let mut set = LinkedHashSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    /// assert_eq!(set.pop_front(), Some(2));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.map.pop_front().map(|(value, _)| value)
+    }
+
+    /// Visits the values representing the union, i.e., all the values in
+    /// `self` followed by the values in `other` that are not in `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let a: LinkedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let b: LinkedHashSet<_> = [4, 2, 3, 4].iter().cloned().collect();
+    ///
+    /// let union: Vec<_> = a.union(&b).collect();
+    /// assert_eq!(union, [&1, &2, &3, &4]);
+    /// ```
+    pub fn union<'a>(
+        &'a self,
+        other: &'a LinkedHashSet<T, S>,
+    ) -> Union<'a, T, S> {
+        Union {
+            iter: self.map.union(&other.map),
+        }
+    }
+
+    /// Visits the values representing the intersection, i.e., the values
+    /// that are both in `self` and `other`, in the order of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let a: LinkedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let b: LinkedHashSet<_> = [4, 2, 3, 4].iter().cloned().collect();
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).collect();
+    /// assert_eq!(intersection, [&2, &3]);
+    /// ```
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a LinkedHashSet<T, S>,
+    ) -> Intersection<'a, T, S> {
+        Intersection {
+            iter: self.map.intersection(&other.map),
+        }
+    }
+
+    /// Visits the values representing the difference, i.e., the values that
+    /// are in `self` but not in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let a: LinkedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let b: LinkedHashSet<_> = [4, 2, 3, 4].iter().cloned().collect();
+    ///
+    /// let diff: Vec<_> = a.difference(&b).collect();
+    /// assert_eq!(diff, [&1]);
+    ///
+    /// // Note that difference is not symmetric,
+    /// // and `b - a` means something else:
+    /// let diff: Vec<_> = b.difference(&a).collect();
+    /// assert_eq!(diff, [&4]);
+    /// ```
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a LinkedHashSet<T, S>,
+    ) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.map.difference(&other.map),
+        }
+    }
+
+    /// Visits the values representing the symmetric difference, i.e., the
+    /// values that are in `self` or in `other` but not in both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LinkedHashSet;
+    ///
+    /// let a: LinkedHashSet<_> = [1, 2, 3].iter().cloned().collect();
+    /// let b: LinkedHashSet<_> = [4, 2, 3, 4].iter().cloned().collect();
+    ///
+    /// let diff: Vec<_> = a.symmetric_difference(&b).collect();
+    /// assert_eq!(diff, [&1, &4]);
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a LinkedHashSet<T, S>,
+    ) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference {
+            iter: self.difference(other).chain(other.difference(self)),
+        }
+    }
+}
+
+impl<T, S> FromIterator<T> for LinkedHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = Self::default();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// An iterator over the elements of a [`LinkedHashSet`] in insertion order.
+///
+/// This struct is created by [`LinkedHashSet::iter()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+/// [`LinkedHashSet::iter()`]: crate::containers::LinkedHashSet::iter
+#[derive(Debug)]
+pub struct Iter<'a, T, S> {
+    iter: linked_hash_map::Iter<'a, T, (), S>,
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T, S> DoubleEndedIterator for Iter<'a, T, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T, S> ExactSizeIterator for Iter<'a, T, S> {}
+
+impl<'a, T, S> IntoIterator for &'a LinkedHashSet<T, S> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the elements of a [`LinkedHashSet`] in insertion
+/// order.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+#[derive(Debug)]
+pub struct IntoIter<T, S> {
+    iter: linked_hash_map::IntoIter<T, (), S>,
+}
+
+impl<T, S> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<T, S> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S> IntoIterator for LinkedHashSet<T, S> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+/// A lazy iterator producing the elements in the union of two
+/// [`LinkedHashSet`]s.
+///
+/// This struct is created by [`LinkedHashSet::union()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+/// [`LinkedHashSet::union()`]: crate::containers::LinkedHashSet::union
+#[derive(Debug)]
+pub struct Union<'a, T, S> {
+    iter: linked_hash_map::Union<'a, T, (), S>,
+}
+
+impl<'a, T, S> Iterator for Union<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+}
+
+/// A lazy iterator producing the elements in the intersection of two
+/// [`LinkedHashSet`]s.
+///
+/// This struct is created by [`LinkedHashSet::intersection()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+/// [`LinkedHashSet::intersection()`]: crate::containers::LinkedHashSet::intersection
+#[derive(Debug)]
+pub struct Intersection<'a, T, S> {
+    iter: linked_hash_map::Intersection<'a, T, (), S>,
+}
+
+impl<'a, T, S> Iterator for Intersection<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+}
+
+/// A lazy iterator producing the elements in the difference of two
+/// [`LinkedHashSet`]s.
+///
+/// This struct is created by [`LinkedHashSet::difference()`]. See its
+/// documentation for more.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+/// [`LinkedHashSet::difference()`]: crate::containers::LinkedHashSet::difference
+#[derive(Debug)]
+pub struct Difference<'a, T, S> {
+    iter: linked_hash_map::Difference<'a, T, (), S>,
+}
+
+impl<'a, T, S> Iterator for Difference<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+}
+
+/// A lazy iterator producing the elements in the symmetric difference of two
+/// [`LinkedHashSet`]s.
+///
+/// This struct is created by [`LinkedHashSet::symmetric_difference()`]. See
+/// its documentation for more.
+///
+/// [`LinkedHashSet`]: crate::containers::LinkedHashSet
+/// [`LinkedHashSet::symmetric_difference()`]: crate::containers::LinkedHashSet::symmetric_difference
+#[derive(Debug)]
+pub struct SymmetricDifference<'a, T, S> {
+    iter: Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+}
+
+impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_crud() {
+        let mut set = LinkedHashSet::new();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+        assert!(!set.contains("foo"));
+
+        assert!(set.insert("foo"));
+        assert!(set.contains("foo"));
+        assert_eq!(set.len(), 1);
+
+        assert!(!set.insert("foo"));
+        assert_eq!(set.len(), 1);
+
+        assert!(set.remove("foo"));
+        assert!(!set.remove("foo"));
+        assert!(!set.contains("foo"));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut set: LinkedHashSet<_> = (0..10).rev().collect();
+        set.remove(&5);
+        set.insert(5);
+        set.insert(0);
+
+        let expected = [9, 8, 7, 6, 4, 3, 2, 1, 0, 5];
+        assert!(set.iter().eq(expected.iter()));
+        assert!(set.iter().rev().eq(expected.iter().rev()));
+        assert!(set.into_iter().eq(expected.iter().cloned()));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: LinkedHashSet<_> = (0..10).collect();
+        let b: LinkedHashSet<_> = (5..15).rev().collect();
+
+        assert!(a.union(&b).cloned().eq((0..10).chain((10..15).rev())));
+        assert!(a.intersection(&b).cloned().eq(5..10));
+        assert!(b.intersection(&a).cloned().eq((5..10).rev()));
+        assert!(a.difference(&b).cloned().eq(0..5));
+        assert!(a
+            .symmetric_difference(&b)
+            .cloned()
+            .eq((0..5).chain((10..15).rev())));
+    }
+}
@@ -0,0 +1,391 @@
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::Rev;
+
+use super::linked_hash_map::{
+    self, DefaultHashBuilder, EvictionPolicy, LinkedHashMap,
+};
+
+/// A cache that holds a fixed number of entries, and makes room for a new
+/// entry by evicting the one that was used the longest time ago.
+///
+/// The cache is a [`LinkedHashMap`] whose total weight is bounded by the
+/// capacity, with every entry weighing 1 and entries evicted in LRU order.
+/// The map keeps its entries from the least to the most recently used one,
+/// and moves an entry to the back whenever it is used.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("apple", 3);
+/// cache.put("banana", 5);
+/// assert_eq!(cache.get("apple"), Some(&3));
+///
+/// // "banana" is the least recently used entry.
+/// cache.put("cherry", 7);
+/// assert_eq!(cache.get("banana"), None);
+/// assert_eq!(cache.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct LruCache<K, V, S = DefaultHashBuilder> {
+    map: LinkedHashMap<K, V, S>,
+    capacity: usize,
+}
+
+impl<K, V> LruCache<K, V, DefaultHashBuilder>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty LruCache that holds up to `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let cache: LruCache<&str, i32> = LruCache::new(10);
+    /// assert_eq!(cache.capacity(), 10);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> LruCache<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Creates an empty LruCache that holds up to `capacity` entries, and
+    /// uses the given hash builder to hash the keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, LruCache};
+    ///
+    /// let mut cache = LruCache::with_hasher(10, FnvBuildHasher::default());
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// ```
+    pub fn with_hasher(capacity: usize, hasher_builder: S) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        let mut map = LinkedHashMap::with_hasher(hasher_builder);
+        map.set_weight_bound(capacity, EvictionPolicy::Lru, |_, _| 1);
+        Self { map, capacity }
+    }
+
+    /// Returns the largest number of entries held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let cache: LruCache<i32, i32> = LruCache::new(4);
+    /// assert_eq!(cache.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// assert!(cache.is_empty());
+    /// cache.put(1, "a");
+    /// assert!(!cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value of the key, and marks the entry as
+    /// the most recently used one.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_refresh(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value of the key, and marks the
+    /// entry as the most recently used one.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, 10);
+    /// *cache.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.peek(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_refresh(key)
+    }
+
+    /// Returns a reference to the value of the key, without marking the
+    /// entry as used.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.peek(&1), None);
+    /// ```
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns true if the cache holds the key, without marking the entry as
+    /// used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, "a");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a key-value pair as the most recently used entry, and returns
+    /// the old value of the key if it was held. If the cache is full and did
+    /// not hold the key, the least recently used entry is evicted.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// assert_eq!(cache.put(1, "a"), None);
+    /// assert_eq!(cache.put(1, "b"), Some("a"));
+    /// cache.put(2, "c");
+    /// cache.put(3, "d");
+    /// assert!(!cache.contains_key(&1));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert(key, value)
+    }
+
+    /// Removes the key from the cache, and returns its value if it was held.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.pop(&1), Some("a"));
+    /// assert_eq!(cache.pop(&1), None);
+    /// ```
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key)
+    }
+}
+
+impl<K, V, S> LruCache<K, V, S> {
+    /// Returns an iterator over the entries from the most to the least
+    /// recently used one. Iterating does not mark the entries as used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::LruCache;
+    ///
+    /// let mut cache = LruCache::new(3);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.put(3, "c");
+    /// cache.get(&1);
+    /// let keys: Vec<_> = cache.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 3, 2]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        Iter {
+            iter: self.map.iter().rev(),
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a LruCache<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`LruCache`] from the most to the least
+/// recently used one.
+///
+/// This struct is created by [`LruCache::iter()`]. See its documentation for
+/// more.
+///
+/// [`LruCache`]: crate::containers::LruCache
+/// [`LruCache::iter()`]: crate::containers::LruCache::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V, S> {
+    iter: Rev<linked_hash_map::Iter<'a, K, V, S>>,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, S> DoubleEndedIterator for Iter<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Iter<'a, K, V, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn matches_naive_recency_list() {
+        let mut rng = SplitMix64::new(97);
+        let mut cache = LruCache::new(8);
+        // The entries from the most to the least recently used one.
+        let mut naive: Vec<(u64, u64)> = Vec::new();
+        for i in 0..2000 {
+            let key = rng.next_u64() % 16;
+            match rng.next_u64() % 3 {
+                0 => {
+                    let pos = naive.iter().position(|&(k, _)| k == key);
+                    let old = pos.map(|pos| naive.remove(pos).1);
+                    assert_eq!(cache.put(key, i), old);
+                    naive.insert(0, (key, i));
+                    naive.truncate(8);
+                }
+                1 => {
+                    let pos = naive.iter().position(|&(k, _)| k == key);
+                    let entry = pos.map(|pos| naive.remove(pos));
+                    assert_eq!(cache.get(&key), entry.as_ref().map(|e| &e.1));
+                    if let Some(entry) = entry {
+                        naive.insert(0, entry);
+                    }
+                }
+                _ => {
+                    let value = naive.iter().find(|&&(k, _)| k == key);
+                    assert_eq!(cache.peek(&key), value.map(|e| &e.1));
+                }
+            }
+            assert!(cache
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .eq(naive.iter().copied()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity must be positive")]
+    fn zero_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+}
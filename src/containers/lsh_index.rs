@@ -0,0 +1,519 @@
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use super::counter::Counter;
+use super::fnv::FnvHasher;
+use super::multi_map::MultiMap;
+use super::rng::SplitMix64;
+
+/// A family of hash functions under which similar inputs are likely to have
+/// equal hashes, which an [`LshIndex`] uses to find similar inputs.
+///
+/// The family holds a fixed number of hash functions, drawn at random when it
+/// is created, and hashes an input with all of them at once. The more similar
+/// two inputs are, the more likely each of their hashes is to be equal.
+///
+/// [`LshIndex`]: crate::containers::LshIndex
+pub trait LshFamily {
+    /// The type of the inputs, such as a vector or a set.
+    type Input: ?Sized;
+
+    /// Returns the number of hash functions.
+    fn hashes(&self) -> usize;
+
+    /// Returns the hashes of the input under every function, in order.
+    fn signature(&self, input: &Self::Input) -> Vec<u64>;
+}
+
+/// A family that hashes vectors by the side of random hyperplanes through
+/// the origin they are on, for the cosine similarity.
+///
+/// Every function is a hyperplane, and hashes a vector to 1 if it is on the
+/// positive side and to 0 otherwise. Two vectors at an angle `θ` are split
+/// by a hyperplane with a probability of about `θ / π`. The normals of the
+/// hyperplanes have random coordinates of 1 and -1, which give nearly the
+/// same probabilities as normally distributed ones without floating-point
+/// math. Hashing a vector panics if it does not have the dimensions of the
+/// family.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{LshFamily, RandomHyperplanes};
+///
+/// let family = RandomHyperplanes::new(3, 16);
+/// let a = family.signature(&[1.0, 2.0, 3.0]);
+/// // Scaling a vector does not change its angle to the hyperplanes.
+/// assert_eq!(a, family.signature(&[2.0, 4.0, 6.0]));
+/// assert!(a.iter().all(|&bit| bit <= 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomHyperplanes {
+    dimensions: usize,
+    // The normals, one after another.
+    normals: Vec<f64>,
+}
+
+impl RandomHyperplanes {
+    /// Creates a family of `hashes` hyperplanes in the given number of
+    /// dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of dimensions is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshFamily, RandomHyperplanes};
+    ///
+    /// let family = RandomHyperplanes::new(100, 32);
+    /// assert_eq!(family.dimensions(), 100);
+    /// assert_eq!(family.hashes(), 32);
+    /// ```
+    pub fn new(dimensions: usize, hashes: usize) -> Self {
+        Self::with_seed(dimensions, hashes, SplitMix64::DEFAULT_SEED)
+    }
+
+    /// Creates a family of `hashes` hyperplanes in the given number of
+    /// dimensions, drawn from the seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of dimensions is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshFamily, RandomHyperplanes};
+    ///
+    /// let a = RandomHyperplanes::with_seed(2, 8, 1);
+    /// let b = RandomHyperplanes::with_seed(2, 8, 1);
+    /// assert_eq!(a.signature(&[1.0, -1.0]), b.signature(&[1.0, -1.0]));
+    /// ```
+    pub fn with_seed(dimensions: usize, hashes: usize, seed: u64) -> Self {
+        assert!(dimensions > 0, "Dimensions must be positive");
+        let mut rng = SplitMix64::new(seed);
+        let normals = (0..dimensions * hashes)
+            .map(|_| if rng.next_u64() >> 63 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        Self {
+            dimensions,
+            normals,
+        }
+    }
+
+    /// Returns the number of dimensions of the vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RandomHyperplanes;
+    ///
+    /// let family = RandomHyperplanes::new(3, 8);
+    /// assert_eq!(family.dimensions(), 3);
+    /// ```
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+}
+
+impl LshFamily for RandomHyperplanes {
+    type Input = [f64];
+
+    fn hashes(&self) -> usize {
+        self.normals.len() / self.dimensions
+    }
+
+    fn signature(&self, input: &[f64]) -> Vec<u64> {
+        assert_eq!(input.len(), self.dimensions, "Dimensions must match");
+        self.normals
+            .chunks(self.dimensions)
+            .map(|normal| {
+                let dot: f64 =
+                    normal.iter().zip(input).map(|(a, b)| a * b).sum();
+                u64::from(dot > 0.0)
+            })
+            .collect()
+    }
+}
+
+/// A family that hashes sets by their smallest element under random
+/// permutations, for the Jaccard similarity.
+///
+/// Every function scrambles the elements with a random seed, and hashes a
+/// set to the smallest scrambled element. The scrambled elements of the
+/// union of two sets are ordered at random, so the smallest one is in both
+/// sets, and their hashes are equal, with a probability equal to the size of
+/// their intersection over the size of their union. The sets are slices of
+/// elements, which are hashed to integers first if they are of another
+/// type. An empty set hashes to `u64::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{LshFamily, MinHash};
+///
+/// let family = MinHash::new(64);
+/// let a = family.signature(&[1, 2, 3, 4]);
+/// let b = family.signature(&[4, 3, 2, 1, 1]);
+/// // The order and the repetitions of the elements do not matter.
+/// assert_eq!(a, b);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinHash {
+    seeds: Vec<u64>,
+}
+
+impl MinHash {
+    /// Creates a family of `hashes` functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshFamily, MinHash};
+    ///
+    /// let family = MinHash::new(32);
+    /// assert_eq!(family.hashes(), 32);
+    /// ```
+    pub fn new(hashes: usize) -> Self {
+        Self::with_seed(hashes, SplitMix64::DEFAULT_SEED)
+    }
+
+    /// Creates a family of `hashes` functions drawn from the seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshFamily, MinHash};
+    ///
+    /// let a = MinHash::with_seed(8, 1);
+    /// let b = MinHash::with_seed(8, 1);
+    /// assert_eq!(a.signature(&[1, 2]), b.signature(&[1, 2]));
+    /// ```
+    pub fn with_seed(hashes: usize, seed: u64) -> Self {
+        let mut rng = SplitMix64::new(seed);
+        Self {
+            seeds: (0..hashes).map(|_| rng.next_u64()).collect(),
+        }
+    }
+}
+
+impl LshFamily for MinHash {
+    type Input = [u64];
+
+    fn hashes(&self) -> usize {
+        self.seeds.len()
+    }
+
+    fn signature(&self, input: &[u64]) -> Vec<u64> {
+        self.seeds
+            .iter()
+            .map(|&seed| {
+                input
+                    .iter()
+                    .map(|&x| SplitMix64::new(x ^ seed).next_u64())
+                    .min()
+                    .unwrap_or(u64::MAX)
+            })
+            .collect()
+    }
+}
+
+/// An index that finds the inputs similar to a query without comparing it to
+/// every input, by locality-sensitive hashing.
+///
+/// Every input is hashed by the [`LshFamily`] `F`, and its signature is cut
+/// into bands of `rows` hashes each. Every band has a table from the hashes
+/// of its rows to the ids of the inputs, and a query looks up its own bands,
+/// so only the inputs that have all the hashes of at least one band equal to
+/// those of the query are candidates. If each hash of two inputs is equal
+/// with probability `s`, they share a band with probability
+/// `1 - (1 - s^rows)^bands`, which rises steeply around a threshold of
+/// similarity: more rows make the threshold higher, and more bands make it
+/// lower. A query returns its candidates ranked by the number of bands they
+/// share with it, which estimates their similarity, but does not compare the
+/// inputs themselves, which the index does not keep.
+///
+/// The rows of a band are hashed together into a single integer, so rarely,
+/// inputs that differ in a band can also be candidates.
+///
+/// [`LshFamily`]: crate::containers::LshFamily
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{LshIndex, MinHash};
+///
+/// // The words of some documents, as sets of integers.
+/// let documents = [
+///     vec![1, 2, 3, 4, 5, 6, 7, 8],
+///     vec![1, 2, 3, 4, 5, 6, 7, 9],
+///     vec![10, 11, 12, 13, 14, 15, 16, 17],
+/// ];
+/// let mut index = LshIndex::new(MinHash::new(64), 32);
+/// for (id, words) in documents.iter().enumerate() {
+///     index.insert(id, words);
+/// }
+/// let similar = index.query(&[1, 2, 3, 4, 5, 6, 9], 2);
+/// assert_eq!(similar, [&1, &0]);
+/// ```
+#[derive(Debug)]
+pub struct LshIndex<F, Id> {
+    family: F,
+    rows: usize,
+    // The ids of the inputs, by the hash of their rows, in every band.
+    bands: Vec<MultiMap<u64, Id>>,
+    len: usize,
+}
+
+impl<F: LshFamily, Id> LshIndex<F, Id> {
+    /// Creates an empty LshIndex that hashes its inputs with the family, and
+    /// cuts their signatures into the given number of bands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bands is zero, or does not divide the number
+    /// of hashes of the family.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, RandomHyperplanes};
+    ///
+    /// let index: LshIndex<_, u32> =
+    ///     LshIndex::new(RandomHyperplanes::new(3, 24), 4);
+    /// assert_eq!((index.bands(), index.rows()), (4, 6));
+    /// ```
+    pub fn new(family: F, bands: usize) -> Self {
+        assert!(bands > 0, "Band count must be positive");
+        let hashes = family.hashes();
+        assert!(
+            hashes.is_multiple_of(bands),
+            "Band count must divide the hash count"
+        );
+        Self {
+            family,
+            rows: hashes / bands,
+            bands: (0..bands).map(|_| MultiMap::new()).collect(),
+            len: 0,
+        }
+    }
+
+    /// Returns the family of hash functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshFamily, LshIndex, MinHash};
+    ///
+    /// let index: LshIndex<_, u32> = LshIndex::new(MinHash::new(8), 2);
+    /// assert_eq!(index.family().hashes(), 8);
+    /// ```
+    pub fn family(&self) -> &F {
+        &self.family
+    }
+
+    /// Returns the number of bands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, MinHash};
+    ///
+    /// let index: LshIndex<_, u32> = LshIndex::new(MinHash::new(8), 2);
+    /// assert_eq!(index.bands(), 2);
+    /// ```
+    pub fn bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Returns the number of hashes in a band.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, MinHash};
+    ///
+    /// let index: LshIndex<_, u32> = LshIndex::new(MinHash::new(8), 2);
+    /// assert_eq!(index.rows(), 4);
+    /// ```
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of inputs in the index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, MinHash};
+    ///
+    /// let mut index = LshIndex::new(MinHash::new(8), 2);
+    /// index.insert("a", &[1, 2, 3]);
+    /// assert_eq!(index.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the index has no inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, MinHash};
+    ///
+    /// let index: LshIndex<_, u32> = LshIndex::new(MinHash::new(8), 2);
+    /// assert!(index.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the keys of the bands of the input.
+    fn keys(&self, input: &F::Input) -> impl Iterator<Item = u64> {
+        let signature = self.family.signature(input);
+        let rows = self.rows;
+        (0..self.bands.len()).map(move |band| {
+            let mut hasher = FnvHasher::default();
+            signature[band * rows..(band + 1) * rows].hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+}
+
+impl<F, Id> LshIndex<F, Id>
+where
+    F: LshFamily,
+    Id: Hash + Eq + Clone,
+{
+    /// Adds the input under the id. An id that is already in the index is
+    /// added again, and can then be returned for either input.
+    ///
+    /// This operation should compute in O(h) expected time, where h is the
+    /// number of hashes, not counting the time to hash the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, RandomHyperplanes};
+    ///
+    /// let mut index = LshIndex::new(RandomHyperplanes::new(2, 8), 2);
+    /// index.insert(1, &[1.0, 0.0]);
+    /// assert_eq!(index.query(&[1.0, 0.1], 1), [&1]);
+    /// ```
+    pub fn insert(&mut self, id: Id, input: &F::Input) {
+        let keys: Vec<_> = self.keys(input).collect();
+        for (band, key) in self.bands.iter_mut().zip(keys) {
+            band.insert(key, id.clone());
+        }
+        self.len += 1;
+    }
+
+    /// Returns the ids of at most `k` inputs that share a band with the
+    /// query, from the one that shares the most bands to the one that shares
+    /// the fewest. Ids that share as many bands are in the order of the
+    /// first band they share.
+    ///
+    /// This operation should compute in O(h + c log(c)) expected time, where
+    /// h is the number of hashes and c is the number of candidates, not
+    /// counting the time to hash the query.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{LshIndex, RandomHyperplanes};
+    ///
+    /// let mut index = LshIndex::new(RandomHyperplanes::new(2, 32), 8);
+    /// index.insert("east", &[1.0, 0.0]);
+    /// index.insert("north", &[0.0, 1.0]);
+    /// index.insert("west", &[-1.0, 0.0]);
+    /// assert_eq!(index.query(&[0.9, 0.1], 1), [&"east"]);
+    /// assert!(!index.query(&[0.9, 0.1], 3).contains(&&"west"));
+    /// ```
+    pub fn query(&self, input: &F::Input, k: usize) -> Vec<&Id> {
+        let mut counter = Counter::new();
+        for (band, key) in self.bands.iter().zip(self.keys(input)) {
+            counter.extend(band.get_all(&key));
+        }
+        counter
+            .most_common(k)
+            .into_iter()
+            .map(|(&id, _)| id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn candidates_share_a_band() {
+        let family = MinHash::new(12);
+        let mut index = LshIndex::new(family.clone(), 4);
+        let mut rng = SplitMix64::new(17);
+        let set = |rng: &mut SplitMix64| -> Vec<u64> {
+            (0..4).map(|_| rng.next_u64() % 10).collect()
+        };
+        let sets: Vec<_> = (0..200).map(|_| set(&mut rng)).collect();
+        for (id, s) in sets.iter().enumerate() {
+            index.insert(id, s);
+        }
+        assert_eq!(index.len(), sets.len());
+        for _ in 0..50 {
+            let query = set(&mut rng);
+            let q = family.signature(&query);
+            let mut expected: Vec<_> = sets
+                .iter()
+                .enumerate()
+                .map(|(id, s)| {
+                    let signature = family.signature(s);
+                    let shared = (0..4)
+                        .filter(|b| {
+                            q[b * 3..(b + 1) * 3]
+                                == signature[b * 3..(b + 1) * 3]
+                        })
+                        .count();
+                    (shared, id)
+                })
+                .filter(|&(shared, _)| shared > 0)
+                .collect();
+            expected.sort_by_key(|&(shared, _)| core::cmp::Reverse(shared));
+            // The ids that share as many bands may come in another order.
+            let found: Vec<_> = index
+                .query(&query, sets.len())
+                .into_iter()
+                .map(|&id| (expected.iter().find(|e| e.1 == id).unwrap().0, id))
+                .collect();
+            let counts = |v: &[(usize, usize)]| -> Vec<usize> {
+                v.iter().map(|e| e.0).collect()
+            };
+            assert_eq!(counts(&found), counts(&expected));
+        }
+    }
+
+    #[test]
+    fn finds_near_duplicates() {
+        let mut rng = SplitMix64::new(5);
+        let family = RandomHyperplanes::new(16, 64);
+        let mut index = LshIndex::new(family, 16);
+        let mut centers = vec![];
+        for id in 0..100 {
+            let center: Vec<f64> = (0..16)
+                .map(|_| (rng.next_u64() % 2001) as f64 / 1000.0 - 1.0)
+                .collect();
+            index.insert(id, &center);
+            centers.push(center);
+        }
+        let mut hits = 0;
+        for (id, center) in centers.iter().enumerate() {
+            let near: Vec<f64> =
+                center.iter().map(|x| x * 1.5 + 0.01).collect();
+            hits += usize::from(index.query(&near, 1) == [&id]);
+        }
+        assert!(hits >= 90, "{}", hits);
+    }
+}
@@ -0,0 +1,615 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::StepBy;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// A grid of elements with a fixed number of rows and columns.
+///
+/// The elements are kept in one array in row-major order, so the element at
+/// row `r` and column `c` is at position `r * cols + c`. A row is a
+/// contiguous slice of the array, and a column is every `cols`-th element
+/// from its first one.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Matrix;
+///
+/// let mut m = Matrix::from_fn(2, 3, |r, c| r * 10 + c);
+/// m[(1, 2)] = 99;
+/// assert_eq!(m.row(1), [10, 11, 99]);
+/// assert_eq!(m.column(1).copied().collect::<Vec<_>>(), [1, 11]);
+///
+/// let t = m.transpose();
+/// assert_eq!((t.rows(), t.cols()), (3, 2));
+/// assert_eq!(t[(2, 1)], 99);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Matrix<T> {
+    /// Creates a Matrix of the given size whose elements are the default
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m: Matrix<i32> = Matrix::new(2, 2);
+    /// assert_eq!(m.as_slice(), [0, 0, 0, 0]);
+    /// ```
+    pub fn new(rows: usize, cols: usize) -> Self
+    where
+        T: Default,
+    {
+        Self::from_fn(rows, cols, |_, _| T::default())
+    }
+
+    /// Creates a Matrix of the given size whose elements are clones of the
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_elem(2, 3, 'x');
+    /// assert_eq!(m.row(1), ['x', 'x', 'x']);
+    /// ```
+    pub fn from_elem(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            data: vec![value; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Creates a Matrix of the given size whose element at row `r` and
+    /// column `c` is `f(r, c)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let identity = Matrix::from_fn(3, 3, |r, c| (r == c) as u8);
+    /// assert_eq!(identity.row(1), [0, 1, 0]);
+    /// ```
+    pub fn from_fn<F>(rows: usize, cols: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                data.push(f(r, c));
+            }
+        }
+        Self { data, rows, cols }
+    }
+
+    /// Creates a Matrix of the given size from its elements in row-major
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements is not `rows * cols`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// assert_eq!(m[(1, 0)], 3);
+    /// ```
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        assert_eq!(data.len(), rows * cols, "Dimensions must be equal");
+        Self { data, rows, cols }
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m: Matrix<i32> = Matrix::new(2, 3);
+    /// assert_eq!(m.rows(), 2);
+    /// ```
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m: Matrix<i32> = Matrix::new(2, 3);
+    /// assert_eq!(m.cols(), 3);
+    /// ```
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns a reference to the element at the row and the column, or
+    /// None if they are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// assert_eq!(m.get(0, 1), Some(&2));
+    /// assert_eq!(m.get(0, 2), None);
+    /// ```
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            self.data.get(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the element at the row and the column,
+    /// or None if they are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let mut m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// *m.get_mut(1, 1).unwrap() = 40;
+    /// assert_eq!(m.as_slice(), [1, 2, 3, 40]);
+    /// ```
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.rows && col < self.cols {
+            self.data.get_mut(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the elements of the row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// assert_eq!(m.row(1), [3, 4]);
+    /// ```
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.rows, "Out of bounds access");
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Returns the elements of the row as a mutable slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let mut m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// m.row_mut(0).reverse();
+    /// assert_eq!(m.as_slice(), [2, 1, 3, 4]);
+    /// ```
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        assert!(row < self.rows, "Out of bounds access");
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Returns an iterator over the elements of the column, from the first
+    /// row to the last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// assert_eq!(m.column(1).collect::<Vec<_>>(), [&2, &4]);
+    /// ```
+    pub fn column(&self, col: usize) -> Column<'_, T> {
+        assert!(col < self.cols, "Out of bounds access");
+        Column {
+            iter: self
+                .data
+                .get(col..)
+                .unwrap_or(&[])
+                .iter()
+                .step_by(self.cols),
+        }
+    }
+
+    /// Returns an iterator over the rows, as slices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// let sums: Vec<i32> = m.iter_rows().map(|r| r.iter().sum()).collect();
+    /// assert_eq!(sums, [3, 7]);
+    /// ```
+    pub fn iter_rows(&self) -> Rows<'_, T> {
+        Rows {
+            matrix: self,
+            front: 0,
+            back: self.rows,
+        }
+    }
+
+    /// Returns an iterator over the columns. Each column is an iterator over
+    /// its elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 2, vec![1, 2, 3, 4]);
+    /// let sums: Vec<i32> = m.iter_columns().map(|col| col.sum()).collect();
+    /// assert_eq!(sums, [4, 6]);
+    /// ```
+    pub fn iter_columns(&self) -> Columns<'_, T> {
+        Columns {
+            matrix: self,
+            front: 0,
+            back: self.cols,
+        }
+    }
+
+    /// Returns an iterator over the elements in row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_fn(2, 2, |r, c| (r, c));
+    /// let cells: Vec<_> = m.iter().copied().collect();
+    /// assert_eq!(cells, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Returns an iterator over mutable references to the elements in
+    /// row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let mut m = Matrix::from_vec(1, 3, vec![1, 2, 3]);
+    /// m.iter_mut().for_each(|x| *x *= 2);
+    /// assert_eq!(m.as_slice(), [2, 4, 6]);
+    /// ```
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// Returns the elements in row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_fn(2, 2, |r, c| r * 2 + c);
+    /// assert_eq!(m.as_slice(), [0, 1, 2, 3]);
+    /// ```
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the elements in row-major order, consuming the matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_fn(2, 2, |r, c| r * 2 + c);
+    /// assert_eq!(m.into_vec(), [0, 1, 2, 3]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Returns the matrix whose rows are the columns of this matrix.
+    ///
+    /// This operation should compute in O(rows * cols) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]);
+    /// let t = m.transpose();
+    /// assert_eq!(t.as_slice(), [1, 4, 2, 5, 3, 6]);
+    /// assert_eq!(t.transpose(), m);
+    /// ```
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.cols, self.rows, |r, c| self[(c, r)].clone())
+    }
+
+    /// Returns the matrix of the results of `f` on the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let m = Matrix::from_vec(1, 3, vec![1, 2, 3]);
+    /// assert_eq!(m.map(|x| x * x).as_slice(), [1, 4, 9]);
+    /// ```
+    pub fn map<U, F>(&self, f: F) -> Matrix<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Matrix {
+            data: self.data.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// Returns the matrix of the results of `f` on the elements of both
+    /// matrices at the same positions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrices do not have the same size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Matrix;
+    ///
+    /// let a = Matrix::from_vec(1, 3, vec![1, 2, 3]);
+    /// let b = Matrix::from_vec(1, 3, vec![10, 20, 30]);
+    /// assert_eq!(a.zip_with(&b, |x, y| x + y).as_slice(), [11, 22, 33]);
+    /// ```
+    pub fn zip_with<U, V, F>(&self, other: &Matrix<U>, mut f: F) -> Matrix<V>
+    where
+        F: FnMut(&T, &U) -> V,
+    {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "Dimensions must be equal"
+        );
+        Matrix {
+            data: self
+                .data
+                .iter()
+                .zip(&other.data)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        self.get(row, col).expect("Out of bounds access")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        self.get_mut(row, col).expect("Out of bounds access")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Matrix<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a column of a Matrix.
+///
+/// This struct is created by [`Matrix::column()`]. See its documentation for
+/// more.
+///
+/// [`Matrix::column()`]: crate::containers::Matrix::column
+#[derive(Debug, Clone)]
+pub struct Column<'a, T> {
+    iter: StepBy<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Column<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Column<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Column<'a, T> {}
+
+/// An iterator over the rows of a Matrix.
+///
+/// This struct is created by [`Matrix::iter_rows()`]. See its documentation
+/// for more.
+///
+/// [`Matrix::iter_rows()`]: crate::containers::Matrix::iter_rows
+#[derive(Debug, Clone)]
+pub struct Rows<'a, T> {
+    matrix: &'a Matrix<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.matrix.row(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Rows<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.matrix.row(self.back))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Rows<'a, T> {}
+
+/// An iterator over the columns of a Matrix.
+///
+/// This struct is created by [`Matrix::iter_columns()`]. See its
+/// documentation for more.
+///
+/// [`Matrix::iter_columns()`]: crate::containers::Matrix::iter_columns
+#[derive(Debug, Clone)]
+pub struct Columns<'a, T> {
+    matrix: &'a Matrix<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Columns<'a, T> {
+    type Item = Column<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.matrix.column(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Columns<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.matrix.column(self.back))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Columns<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_and_columns_agree() {
+        for (rows, cols) in [(0, 0), (0, 3), (3, 0), (1, 1), (4, 7), (7, 4)] {
+            let m = Matrix::from_fn(rows, cols, |r, c| (r, c));
+            assert_eq!(m.iter_rows().len(), rows);
+            assert_eq!(m.iter_columns().len(), cols);
+            for (r, row) in m.iter_rows().enumerate() {
+                let expected: Vec<_> = (0..cols).map(|c| (r, c)).collect();
+                assert_eq!(row, &expected[..]);
+            }
+            for (c, col) in m.iter_columns().rev().enumerate() {
+                let c = cols - 1 - c;
+                let expected: Vec<_> = (0..rows).map(|r| (r, c)).collect();
+                assert_eq!(col.len(), rows);
+                assert!(col.copied().eq(expected.iter().copied()));
+            }
+            let t = m.transpose();
+            assert_eq!((t.rows(), t.cols()), (cols, rows));
+            for r in 0..rows {
+                for c in 0..cols {
+                    assert_eq!(t[(c, r)], (r, c));
+                }
+            }
+            assert_eq!(m.get(rows, 0), None);
+            assert_eq!(m.get(0, cols), None);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn index_out_of_bounds() {
+        let m: Matrix<i32> = Matrix::new(2, 3);
+        let _ = m[(0, 3)];
+    }
+
+    #[test]
+    #[should_panic(expected = "Dimensions must be equal")]
+    fn zip_with_different_sizes() {
+        let a: Matrix<i32> = Matrix::new(2, 3);
+        let b: Matrix<i32> = Matrix::new(3, 2);
+        a.zip_with(&b, |x, y| x + y);
+    }
+}
@@ -0,0 +1,575 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Reverse;
+use core::mem;
+use core::slice;
+
+use super::binary_heap::BinaryHeap;
+use super::skip_list_map::{self, SkipListMap};
+
+/// The in-memory write buffer of a log-structured merge tree, which records
+/// insertions and removals in key order until it is full and frozen into a
+/// [`SortedRun`].
+///
+/// The entries are kept in a [`SkipListMap`]. A removal does not take the
+/// key out, but records a tombstone for it, because an older run may still
+/// hold a value for the key that the removal has to hide. The memtable is
+/// full once it holds as many entries, tombstones included, as its
+/// threshold, after which it should be frozen. Reads go through the
+/// memtable and then through the runs from the newest to the oldest, and
+/// stop at the first one that knows the key.
+///
+/// [`SortedRun`]: crate::containers::SortedRun
+/// [`SkipListMap`]: crate::containers::SkipListMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{MemTable, SortedRun};
+///
+/// let mut table = MemTable::new(2);
+/// table.insert("a", 1);
+/// table.insert("b", 2);
+/// assert!(table.is_full());
+/// let old = table.freeze();
+///
+/// table.insert("c", 3);
+/// table.remove("a");
+/// let new = table.freeze();
+///
+/// assert_eq!(new.get("a"), Some(None));
+/// assert_eq!(old.get("a"), Some(Some(&1)));
+///
+/// let runs = [new, old];
+/// let live: Vec<_> = SortedRun::merge(&runs).collect();
+/// assert_eq!(live, [(&"b", &2), (&"c", &3)]);
+/// ```
+#[derive(Debug)]
+pub struct MemTable<K, V> {
+    // A key maps to None when it was removed.
+    map: SkipListMap<K, Option<V>>,
+    threshold: usize,
+}
+
+impl<K: Ord, V> MemTable<K, V> {
+    /// Creates an empty MemTable that is full once it holds the given number
+    /// of entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the threshold is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let table: MemTable<u32, u32> = MemTable::new(1024);
+    /// assert_eq!(table.threshold(), 1024);
+    /// ```
+    pub fn new(threshold: usize) -> Self {
+        assert!(threshold > 0, "Capacity must be positive");
+        Self {
+            map: SkipListMap::new(),
+            threshold,
+        }
+    }
+
+    /// Returns the number of entries at which the memtable is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let table: MemTable<u32, u32> = MemTable::new(16);
+    /// assert_eq!(table.threshold(), 16);
+    /// ```
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns the number of entries in the memtable, tombstones included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.remove(2);
+    /// assert_eq!(table.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the memtable has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table: MemTable<u32, &str> = MemTable::new(16);
+    /// assert!(table.is_empty());
+    /// table.remove(1);
+    /// assert!(!table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns true if the memtable holds at least as many entries as its
+    /// threshold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(2);
+    /// table.insert(1, "a");
+    /// assert!(!table.is_full());
+    /// table.insert(2, "b");
+    /// assert!(table.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.threshold
+    }
+
+    /// Records the value of the key, replacing what the memtable held for
+    /// it.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.insert(1, "b");
+    /// assert_eq!(table.get(&1), Some(Some(&"b")));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.insert(key, Some(value));
+    }
+
+    /// Records a tombstone for the key, which hides its value in this
+    /// memtable and in every older run.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.remove(1);
+    /// assert_eq!(table.get(&1), Some(None));
+    /// ```
+    pub fn remove(&mut self, key: K) {
+        self.map.insert(key, None);
+    }
+
+    /// Returns what the memtable knows about the key: None if it has no
+    /// entry for it, Some(None) if it has a tombstone for it, and the value
+    /// otherwise.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.remove(2);
+    /// assert_eq!(table.get(&1), Some(Some(&"a")));
+    /// assert_eq!(table.get(&2), Some(None));
+    /// assert_eq!(table.get(&3), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<Option<&V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.get(key).map(Option::as_ref)
+    }
+
+    /// Returns an iterator over the entries of the memtable in ascending
+    /// order of their keys, where a tombstone has no value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(2, "b");
+    /// table.remove(1);
+    /// let entries: Vec<_> = table.iter().collect();
+    /// assert_eq!(entries, [(&1, None), (&2, Some(&"b"))]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Moves the entries of the memtable into an immutable sorted run,
+    /// leaving the memtable empty.
+    ///
+    /// This operation should compute in O(n log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(2, "b");
+    /// table.insert(1, "a");
+    /// let run = table.freeze();
+    /// assert!(table.is_empty());
+    /// assert_eq!(run.len(), 2);
+    /// assert_eq!(run.get(&1), Some(Some(&"a")));
+    /// ```
+    pub fn freeze(&mut self) -> SortedRun<K, V> {
+        let map = mem::take(&mut self.map);
+        SortedRun {
+            entries: map.into_sorted_vec(),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`MemTable`].
+///
+/// This struct is created by [`MemTable::iter()`]. See its documentation for
+/// more.
+///
+/// [`MemTable`]: crate::containers::MemTable
+/// [`MemTable::iter()`]: crate::containers::MemTable::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    iter: skip_list_map::Iter<'a, K, Option<V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, Option<&'a V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, value)| (key, value.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// An immutable array of entries sorted by key, made by freezing a
+/// [`MemTable`], where a tombstone has no value.
+///
+/// [`MemTable`]: crate::containers::MemTable
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::MemTable;
+///
+/// let mut table = MemTable::new(16);
+/// table.insert(3, "c");
+/// table.remove(1);
+/// let run = table.freeze();
+///
+/// let entries: Vec<_> = run.iter().collect();
+/// assert_eq!(entries, [(&1, None), (&3, Some(&"c"))]);
+/// assert_eq!(run.get(&2), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SortedRun<K, V> {
+    entries: Vec<(K, Option<V>)>,
+}
+
+impl<K, V> SortedRun<K, V> {
+    /// Returns the number of entries in the run, tombstones included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.remove(2);
+    /// assert_eq!(table.freeze().len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the run has no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table: MemTable<u32, u32> = MemTable::new(16);
+    /// assert!(table.freeze().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns what the run knows about the key: None if it has no entry for
+    /// it, Some(None) if it has a tombstone for it, and the value otherwise.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "a");
+    /// table.remove(2);
+    /// let run = table.freeze();
+    /// assert_eq!(run.get(&1), Some(Some(&"a")));
+    /// assert_eq!(run.get(&2), Some(None));
+    /// assert_eq!(run.get(&3), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<Option<&V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries
+            .binary_search_by(|(k, _)| k.borrow().cmp(key))
+            .ok()
+            .map(|i| self.entries[i].1.as_ref())
+    }
+
+    /// Returns an iterator over the entries of the run in ascending order of
+    /// their keys, where a tombstone has no value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MemTable;
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(2, "b");
+    /// table.remove(1);
+    /// let run = table.freeze();
+    /// let entries: Vec<_> = run.iter().collect();
+    /// assert_eq!(entries, [(&1, None), (&2, Some(&"b"))]);
+    /// ```
+    pub fn iter(&self) -> RunIter<'_, K, V> {
+        RunIter {
+            iter: self.entries.iter(),
+        }
+    }
+}
+
+impl<K: Ord, V> SortedRun<K, V> {
+    /// Returns an iterator over the live entries of the runs, which are
+    /// given from the newest to the oldest, in ascending order of their
+    /// keys.
+    ///
+    /// The runs are merged with a [`BinaryHeap`] that holds the next key of
+    /// each run. For every key, only the entry of the newest run that has it
+    /// counts, and the key is skipped if that entry is a tombstone.
+    ///
+    /// Visiting all the entries should compute in O(n log(k)) time for k
+    /// runs with n entries in total.
+    ///
+    /// [`BinaryHeap`]: crate::containers::BinaryHeap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{MemTable, SortedRun};
+    ///
+    /// let mut table = MemTable::new(16);
+    /// table.insert(1, "old");
+    /// table.insert(2, "b");
+    /// let old = table.freeze();
+    /// table.insert(1, "new");
+    /// table.remove(2);
+    /// table.insert(3, "c");
+    /// let new = table.freeze();
+    ///
+    /// let runs = [new, old];
+    /// let live: Vec<_> = SortedRun::merge(&runs).collect();
+    /// assert_eq!(live, [(&1, &"new"), (&3, &"c")]);
+    /// ```
+    pub fn merge(runs: &[SortedRun<K, V>]) -> Merge<'_, K, V> {
+        let heap = runs
+            .iter()
+            .enumerate()
+            .filter_map(|(run, r)| {
+                r.entries.first().map(|e| Reverse((&e.0, run)))
+            })
+            .collect();
+        Merge {
+            runs,
+            positions: runs.iter().map(|_| 0).collect(),
+            heap,
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SortedRun<K, V> {
+    type Item = (&'a K, Option<&'a V>);
+    type IntoIter = RunIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`SortedRun`].
+///
+/// This struct is created by [`SortedRun::iter()`]. See its documentation
+/// for more.
+///
+/// [`SortedRun`]: crate::containers::SortedRun
+/// [`SortedRun::iter()`]: crate::containers::SortedRun::iter
+#[derive(Debug)]
+pub struct RunIter<'a, K, V> {
+    iter: slice::Iter<'a, (K, Option<V>)>,
+}
+
+impl<'a, K, V> Iterator for RunIter<'a, K, V> {
+    type Item = (&'a K, Option<&'a V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, value)| (key, value.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for RunIter<'a, K, V> {}
+
+/// An iterator over the live entries of several [`SortedRun`]s.
+///
+/// This struct is created by [`SortedRun::merge()`]. See its documentation
+/// for more.
+///
+/// [`SortedRun`]: crate::containers::SortedRun
+/// [`SortedRun::merge()`]: crate::containers::SortedRun::merge
+#[derive(Debug)]
+pub struct Merge<'a, K, V> {
+    runs: &'a [SortedRun<K, V>],
+    // The position of the next entry of each run.
+    positions: Vec<usize>,
+    // The next key of each run that has one, with the index of the run, so
+    // that the newest run comes first among equal keys.
+    heap: BinaryHeap<Reverse<(&'a K, usize)>>,
+}
+
+impl<'a, K: Ord, V> Merge<'a, K, V> {
+    // Moves the run past its next entry, and returns that entry.
+    fn advance(&mut self, run: usize) -> &'a (K, Option<V>) {
+        let entries = &self.runs[run].entries;
+        let entry = &entries[self.positions[run]];
+        self.positions[run] += 1;
+        if let Some(next) = entries.get(self.positions[run]) {
+            self.heap.push(Reverse((&next.0, run)));
+        }
+        entry
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Merge<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((_, run))) = self.heap.pop() {
+            let (key, value) = self.advance(run);
+            // The older entries of the key are shadowed.
+            while let Some(&Reverse((next, older))) = self.heap.peek() {
+                if next != key {
+                    break;
+                }
+                self.heap.pop();
+                self.advance(older);
+            }
+            if let Some(value) = value {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = SplitMix64::new(61);
+        let mut table = MemTable::new(37);
+        // The runs from the newest to the oldest.
+        let mut runs = Vec::new();
+        let mut expected = BTreeMap::new();
+        for step in 0..2000 {
+            let key = rng.next_u64() % 200;
+            if rng.next_u64().is_multiple_of(4) {
+                table.remove(key);
+                expected.remove(&key);
+            } else {
+                table.insert(key, step);
+                expected.insert(key, step);
+            }
+            if table.is_full() {
+                runs.insert(0, table.freeze());
+            }
+            let probe = rng.next_u64() % 200;
+            let found = table
+                .get(&probe)
+                .or_else(|| runs.iter().find_map(|run| run.get(&probe)))
+                .flatten();
+            assert_eq!(found, expected.get(&probe));
+        }
+        assert!(runs.iter().all(|run| run.len() == 37));
+        assert!(runs.iter().all(|run| run
+            .iter()
+            .zip(run.iter().skip(1))
+            .all(|(a, b)| a.0 < b.0)));
+
+        runs.insert(0, table.freeze());
+        let merged: Vec<_> =
+            SortedRun::merge(&runs).map(|(&k, &v)| (k, v)).collect();
+        let expected: Vec<_> = expected.into_iter().collect();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn merge_without_runs() {
+        let runs: [SortedRun<u32, u32>; 0] = [];
+        assert_eq!(SortedRun::merge(&runs).next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity must be positive")]
+    fn zero_threshold() {
+        MemTable::<u32, u32>::new(0);
+    }
+}
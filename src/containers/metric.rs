@@ -0,0 +1,137 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A distance between values of `T`, which the metric-space containers use to
+/// find the values close to a query.
+///
+/// The distance is given as a type, usually a unit struct, so that one value
+/// type can be measured in more than one way. Implementations must satisfy,
+/// for all `a`, `b` and `c`:
+///
+/// - `distance(&a, &b) >= 0.0`, and is 0 only if `a` and `b` are equal
+/// - `distance(&a, &b) == distance(&b, &a)`
+/// - `distance(&a, &c) <= distance(&a, &b) + distance(&b, &c)`
+///
+/// The containers rely on the last one, the triangle inequality, to skip the
+/// values that are too far from the query. The distance must not be NaN.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{Metric, VpTree};
+///
+/// /// The distance between angles in degrees, around the circle.
+/// #[derive(Debug)]
+/// struct Angle;
+///
+/// impl Metric<f64> for Angle {
+///     fn distance(a: &f64, b: &f64) -> f64 {
+///         let d = (a - b).abs() % 360.0;
+///         d.min(360.0 - d)
+///     }
+/// }
+///
+/// let tree = VpTree::<_, Angle>::new(vec![10.0, 90.0, 180.0, 350.0]);
+/// assert_eq!(tree.nearest(&355.0), Some((&350.0, 5.0)));
+/// assert_eq!(tree.nearest(&2.0), Some((&10.0, 8.0)));
+/// ```
+pub trait Metric<T: ?Sized> {
+    /// Returns the distance between the values.
+    fn distance(a: &T, b: &T) -> f64;
+}
+
+/// The straight-line distance between points.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Euclidean;
+
+/// The sum of the differences between the coordinates of points.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Manhattan;
+
+/// The least number of characters to insert, remove, or replace to turn one
+/// string into another.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Levenshtein;
+
+impl<const K: usize> Metric<[f64; K]> for Euclidean {
+    fn distance(a: &[f64; K], b: &[f64; K]) -> f64 {
+        sqrt(a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum())
+    }
+}
+
+impl<const K: usize> Metric<[f64; K]> for Manhattan {
+    fn distance(a: &[f64; K], b: &[f64; K]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| if x < y { y - x } else { x - y })
+            .sum()
+    }
+}
+
+impl Metric<str> for Levenshtein {
+    fn distance(a: &str, b: &str) -> f64 {
+        // The row holds the distances from the prefixes of `a` to the
+        // current prefix of `b`.
+        let mut row: Vec<usize> = (0..=a.chars().count()).collect();
+        for (j, y) in b.chars().enumerate() {
+            let mut diagonal = row[0];
+            row[0] = j + 1;
+            for (i, x) in a.chars().enumerate() {
+                let replace = diagonal + usize::from(x != y);
+                diagonal = row[i + 1];
+                row[i + 1] = replace.min(row[i] + 1).min(diagonal + 1);
+            }
+        }
+        row[row.len() - 1] as f64
+    }
+}
+
+impl Metric<String> for Levenshtein {
+    fn distance(a: &String, b: &String) -> f64 {
+        <Self as Metric<str>>::distance(a, b)
+    }
+}
+
+impl<'a> Metric<&'a str> for Levenshtein {
+    fn distance(a: &&'a str, b: &&'a str) -> f64 {
+        <Self as Metric<str>>::distance(a, b)
+    }
+}
+
+/// Returns the square root of a non-negative number, which the core library
+/// does not provide.
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 || x.is_infinite() {
+        return x.max(0.0);
+    }
+    // Halving the exponent gives a first guess, and Newton's method then
+    // decreases towards the root from above until it stops changing.
+    let mut y = f64::from_bits((x.to_bits() >> 1) + 0x1ff8_0000_0000_0000);
+    y = (y + x / y) / 2.0;
+    loop {
+        let next = (y + x / y) / 2.0;
+        if next >= y {
+            return y;
+        }
+        y = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        for x in [1e-300, 0.25, 2.0, 1e300] {
+            let root = sqrt(x);
+            assert!((root * root - x).abs() <= x * 1e-15, "{}", x);
+        }
+        assert_eq!(Euclidean::distance(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+        assert_eq!(Manhattan::distance(&[0.0, 0.0], &[3.0, -4.0]), 7.0);
+        assert_eq!(Levenshtein::distance(&"kitten", &"sitting"), 3.0);
+        assert_eq!(Levenshtein::distance(&"", &"abc"), 3.0);
+        assert_eq!(Levenshtein::distance(&"flaw", &"lawn"), 2.0);
+        assert_eq!(Levenshtein::distance(&"héllo", &"hello"), 1.0);
+    }
+}
@@ -0,0 +1,477 @@
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+use core::slice;
+
+/// A double-ended priority queue implemented with a min-max heap.
+///
+/// Both the least and the greatest element can be looked at and removed in
+/// O(log(n)) time. Like a binary heap, the elements are stored in an array
+/// where the children of the element at index `i` are at indices `2 * i + 1`
+/// and `2 * i + 2`. The levels of the tree alternate between min levels and
+/// max levels, starting with a min level at the root: an element on a min
+/// level is not greater than any of its descendants, and an element on a max
+/// level is not less than any of its descendants. The least element is
+/// therefore at the root, and the greatest is one of its children.
+///
+/// # Examples
+///
+/// Keeping the three greatest elements seen so far:
+///
+/// ```
+/// use dt::containers::MinMaxHeap;
+///
+/// let mut top = MinMaxHeap::new();
+/// for x in vec![5, 1, 8, 3, 9, 2] {
+///     top.push(x);
+///     if top.len() > 3 {
+///         top.pop_min();
+///     }
+/// }
+///
+/// assert_eq!(top.peek_min(), Some(&5));
+/// assert_eq!(top.peek_max(), Some(&9));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinMaxHeap<T> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns true if the element at index `i` is on a min level.
+fn is_min_level(i: usize) -> bool {
+    // The level of `i` is the position of the highest set bit of `i + 1`.
+    (usize::BITS - (i + 1).leading_zeros()) % 2 == 1
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+    /// Creates an empty MinMaxHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Creates an empty MinMaxHeap with space for at least `capacity`
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::with_capacity(10);
+    /// heap.push(4);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all the elements from the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(1);
+    /// heap.clear();
+    /// assert!(heap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Pushes an element onto the heap.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(3);
+    /// heap.push(5);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// assert_eq!(heap.peek_max(), Some(&5));
+    /// ```
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Returns the least element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// assert_eq!(heap.peek_min(), None);
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek_min(), Some(&1));
+    /// ```
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns the greatest element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// assert_eq!(heap.peek_max(), None);
+    /// heap.push(1);
+    /// heap.push(2);
+    /// assert_eq!(heap.peek_max(), Some(&2));
+    /// ```
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|i| &self.data[i])
+    }
+
+    /// Removes the least element from the heap and returns it, or None if it
+    /// is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(1);
+    /// heap.push(2);
+    /// assert_eq!(heap.pop_min(), Some(1));
+    /// assert_eq!(heap.pop_min(), Some(2));
+    /// assert_eq!(heap.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(self.remove_at(0))
+    }
+
+    /// Removes the greatest element from the heap and returns it, or None if
+    /// it is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let mut heap = MinMaxHeap::new();
+    /// heap.push(1);
+    /// heap.push(2);
+    /// assert_eq!(heap.pop_max(), Some(2));
+    /// assert_eq!(heap.pop_max(), Some(1));
+    /// assert_eq!(heap.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<T> {
+        let i = self.max_index()?;
+        Some(self.remove_at(i))
+    }
+
+    /// Returns an iterator visiting all the elements of the heap in an
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let heap: MinMaxHeap<_> = vec![1, 2, 3].into_iter().collect();
+    /// assert_eq!(heap.iter().sum::<i32>(), 6);
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Consumes the heap and returns its elements in an arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let heap: MinMaxHeap<_> = vec![1, 2, 3].into_iter().collect();
+    /// let mut vec = heap.into_vec();
+    /// vec.sort();
+    /// assert_eq!(vec, [1, 2, 3]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Consumes the heap and returns its elements in ascending order.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MinMaxHeap;
+    ///
+    /// let heap: MinMaxHeap<_> = vec![3, 1, 2].into_iter().collect();
+    /// assert_eq!(heap.into_sorted_vec(), [1, 2, 3]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(item) = self.pop_min() {
+            sorted.push(item);
+        }
+        sorted
+    }
+
+    /// Returns the index of the greatest element, which is the greater of the
+    /// root's children, or the root itself if it has none.
+    fn max_index(&self) -> Option<usize> {
+        match self.data.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ if self.data[1] < self.data[2] => Some(2),
+            _ => Some(1),
+        }
+    }
+
+    /// Removes the element at index `i` by replacing it with the last element,
+    /// which is then moved down to its place.
+    fn remove_at(&mut self, i: usize) -> T {
+        let item = self.data.swap_remove(i);
+        if i < self.data.len() {
+            self.sift_down(i);
+        }
+        item
+    }
+
+    /// Returns true if `a` should be above `b` on a level of the given kind.
+    fn before(&self, a: usize, b: usize, min: bool) -> bool {
+        if min {
+            self.data[a] < self.data[b]
+        } else {
+            self.data[a] > self.data[b]
+        }
+    }
+
+    /// Moves the element at index `i` up to its place.
+    ///
+    /// If the element belongs on a level of the other kind than its own, it
+    /// is first swapped with its parent. It then moves up through the levels
+    /// of one kind only, by comparing with its grandparents.
+    fn sift_up(&mut self, mut i: usize) {
+        if i == 0 {
+            return;
+        }
+        let mut min = is_min_level(i);
+        let parent = (i - 1) / 2;
+        if self.before(parent, i, min) {
+            self.data.swap(i, parent);
+            i = parent;
+            min = !min;
+        }
+        while i > 2 {
+            let grandparent = ((i - 1) / 2 - 1) / 2;
+            if !self.before(i, grandparent, min) {
+                break;
+            }
+            self.data.swap(i, grandparent);
+            i = grandparent;
+        }
+    }
+
+    /// Moves the element at index `i` down to its place.
+    ///
+    /// The element is swapped with the least (or greatest, on a max level) of
+    /// its children and grandchildren. When that is a grandchild, the element
+    /// may now be on the wrong side of the grandchild's parent, in which case
+    /// the two are swapped before carrying on from the grandchild.
+    fn sift_down(&mut self, mut i: usize) {
+        let min = is_min_level(i);
+        let len = self.data.len();
+        loop {
+            let first_child = 2 * i + 1;
+            if first_child >= len {
+                break;
+            }
+            let first_grandchild = 2 * first_child + 1;
+            let descendants = (first_child..(first_child + 2).min(len))
+                .chain(first_grandchild..(first_grandchild + 4).min(len));
+            let mut m = first_child;
+            for d in descendants {
+                if self.before(d, m, min) {
+                    m = d;
+                }
+            }
+            if !self.before(m, i, min) {
+                break;
+            }
+            self.data.swap(m, i);
+            if m < first_grandchild {
+                break;
+            }
+            let parent = (m - 1) / 2;
+            if self.before(parent, m, min) {
+                self.data.swap(m, parent);
+            }
+            i = m;
+        }
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for MinMaxHeap<T> {
+    /// Turns a vector into a heap, by moving down each element that has
+    /// children, starting from the last one.
+    ///
+    /// This operation should compute in O(n) time.
+    fn from(data: Vec<T>) -> Self {
+        let mut heap = Self { data };
+        for i in (0..heap.len() / 2).rev() {
+            heap.sift_down(i);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinMaxHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Ord> Extend<T> for MinMaxHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every element is ordered against all its descendants as
+    /// required by the kind of its level.
+    fn check<T: Ord>(heap: &MinMaxHeap<T>) {
+        for i in 1..heap.len() {
+            let mut ancestor = (i - 1) / 2;
+            loop {
+                if is_min_level(ancestor) {
+                    assert!(heap.data[ancestor] <= heap.data[i]);
+                } else {
+                    assert!(heap.data[ancestor] >= heap.data[i]);
+                }
+                if ancestor == 0 {
+                    break;
+                }
+                ancestor = (ancestor - 1) / 2;
+            }
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let kinds: Vec<_> = (0..15).map(is_min_level).collect();
+        assert_eq!(&kinds[..3], [true, false, false]);
+        assert!(kinds[3..7].iter().all(|&min| min));
+        assert!(kinds[7..].iter().all(|&min| !min));
+    }
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut heap = MinMaxHeap::new();
+        let mut expected = Vec::new();
+        for x in (0..200).map(|x| (x * 37) % 101) {
+            heap.push(x);
+            expected.push(x);
+            check(&heap);
+        }
+        expected.sort_unstable();
+
+        let mut lo = 0;
+        let mut hi = expected.len();
+        for step in 0..expected.len() {
+            if step % 3 == 0 {
+                hi -= 1;
+                assert_eq!(heap.pop_max(), Some(expected[hi]));
+            } else {
+                assert_eq!(heap.pop_min(), Some(expected[lo]));
+                lo += 1;
+            }
+            check(&heap);
+        }
+        assert_eq!(heap.pop_min(), None);
+        assert_eq!(heap.pop_max(), None);
+    }
+
+    #[test]
+    fn from_vec() {
+        let data: Vec<_> = (0..100).map(|x| (x * 7) % 100).collect();
+        let heap = MinMaxHeap::from(data);
+        check(&heap);
+        assert_eq!(heap.peek_min(), Some(&0));
+        assert_eq!(heap.peek_max(), Some(&99));
+        assert!(heap.into_sorted_vec().into_iter().eq(0..100));
+    }
+}
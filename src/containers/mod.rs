@@ -1,7 +1,182 @@
 //! Container types
-
+mod aho_corasick;
+mod arena;
+mod array_deque;
+mod b_plus_tree_map;
+mod binary_heap;
+mod binomial_heap;
+mod bit_vec;
+mod bst_map;
+mod cartesian_tree;
+mod circular_buffer;
+#[cfg(feature = "std")]
+mod clock;
+mod count_min_sketch;
+mod counter;
+mod cover_tree;
+mod csr_graph;
+mod cuckoo_hash_map;
+mod dag;
+mod dancing_links;
+#[cfg(feature = "std")]
+mod delay_queue;
+mod disjoint_set;
 mod doubly_linked_list;
+mod extendible_hash_map;
+mod fenwick_tree;
+mod fibonacci_heap;
+mod fixed_bit_set;
+mod fks_table;
+mod fnv;
+mod graph;
+mod hat_vec;
+mod indexed_priority_queue;
+mod interval_map;
+mod interval_tree;
+mod kd_tree;
+mod leftist_heap;
+mod lfu_cache;
+mod linear_hash_map;
 mod linked_hash_map;
-
+mod linked_hash_set;
+mod lru_cache;
+mod lsh_index;
+mod matrix;
+mod mem_table;
+mod metric;
+mod min_max_heap;
+mod monotonic_queue;
+mod multi_map;
+mod ops;
+mod pairing_heap;
+mod persistent_list;
+mod persistent_ord_map;
+mod piece_table;
+mod quad_tree;
+mod r_tree;
+mod radix_trie;
+mod range_set;
+mod rb_tree_map;
+mod reservoir_sampler;
+mod rng;
+mod roaring_bitmap;
+mod scapegoat_tree;
+mod segment_tree;
+mod shortest_paths;
+mod singly_linked_list;
+mod skip_list_map;
+mod slab;
+mod slot_map;
+mod small_vec;
+mod sparse_matrix;
+mod sparse_table;
+mod splay_tree;
+mod stable_priority_queue;
+mod string_interner;
+mod suffix_array;
+mod suffix_automaton;
+mod suffix_tree;
+mod t_digest;
+mod ternary_search_tree;
+mod treap;
+mod trie;
+#[cfg(feature = "std")]
+mod ttl_cache;
+mod two_queue_cache;
+mod veb_tree;
+mod vec_map;
+mod vec_set;
+mod vp_tree;
+mod zipper;
+pub use aho_corasick::{AhoCorasick, AhoCorasickMatch};
+pub use arena::{Arena, ArenaStats};
+pub use array_deque::ArrayDeque;
+pub use b_plus_tree_map::BPlusTreeMap;
+pub use binary_heap::{BinaryHeap, PeekMut};
+pub use binomial_heap::BinomialHeap;
+pub use bit_vec::BitVec;
+pub use bst_map::BstMap;
+pub use cartesian_tree::{CartesianTree, EulerTour};
+pub use circular_buffer::{BufferFull, CircularBuffer, Overflow};
+#[cfg(feature = "std")]
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use count_min_sketch::CountMinSketch;
+pub use counter::Counter;
+pub use cover_tree::CoverTree;
+pub use csr_graph::CsrGraph;
+pub use cuckoo_hash_map::{CuckooHashMap, CuckooHashMapStats};
+pub use dag::{Dag, WouldCycle};
+pub use dancing_links::DancingLinks;
+#[cfg(feature = "std")]
+pub use delay_queue::DelayQueue;
+pub use disjoint_set::DisjointSet;
 pub use doubly_linked_list::DoublyLinkedList;
-pub use linked_hash_map::LinkedHashMap;
+pub use extendible_hash_map::ExtendibleHashMap;
+pub use fenwick_tree::FenwickTree;
+pub use fibonacci_heap::{FibonacciHeap, FibonacciHeapHandle};
+pub use fixed_bit_set::FixedBitSet;
+pub use fks_table::FksTable;
+pub use fnv::{FnvBuildHasher, FnvHasher};
+pub use graph::{EdgeIndex, Graph, NodeIndex};
+pub use hat_vec::HatVec;
+pub use indexed_priority_queue::IndexedPriorityQueue;
+pub use interval_map::IntervalMap;
+pub use interval_tree::IntervalTree;
+pub use kd_tree::KdTree;
+pub use leftist_heap::LeftistHeap;
+pub use lfu_cache::LfuCache;
+pub use linear_hash_map::LinearHashMap;
+#[cfg(feature = "arbitrary")]
+pub use linked_hash_map::LinkedHashMapOp;
+pub use linked_hash_map::{
+    DefaultHashBuilder, EvictionPolicy, LinkedHashMap, LinkedHashMapStats,
+};
+pub use linked_hash_set::LinkedHashSet;
+pub use lru_cache::LruCache;
+pub use lsh_index::{LshFamily, LshIndex, MinHash, RandomHyperplanes};
+pub use matrix::Matrix;
+pub use mem_table::{MemTable, SortedRun};
+pub use metric::{Euclidean, Levenshtein, Manhattan, Metric};
+pub use min_max_heap::MinMaxHeap;
+pub use monotonic_queue::MonotonicQueue;
+pub use multi_map::MultiMap;
+pub use ops::{Gcd, Group, Idempotent, Max, Min, Monoid, Sum};
+pub use pairing_heap::{PairingHeap, PairingHeapHandle};
+pub use persistent_list::PersistentList;
+pub use persistent_ord_map::PersistentOrdMap;
+pub use piece_table::PieceTable;
+pub use quad_tree::QuadTree;
+pub use r_tree::RTree;
+pub use radix_trie::RadixTrie;
+pub use range_set::RangeSet;
+pub use rb_tree_map::RbTreeMap;
+pub use reservoir_sampler::ReservoirSampler;
+pub use roaring_bitmap::{RoaringBitmap, RoaringBitmapStats};
+pub use scapegoat_tree::ScapegoatTree;
+pub use segment_tree::SegmentTree;
+pub use shortest_paths::{NegativeCycle, ShortestPaths};
+pub use singly_linked_list::SinglyLinkedList;
+pub use skip_list_map::SkipListMap;
+pub use slab::Slab;
+pub use slot_map::{SlotMap, SlotMapKey};
+pub use small_vec::SmallVec;
+pub use sparse_matrix::SparseMatrix;
+pub use sparse_table::SparseTable;
+pub use splay_tree::SplayTree;
+pub use stable_priority_queue::StablePriorityQueue;
+pub use string_interner::{StringInterner, Symbol};
+pub use suffix_array::SuffixArray;
+pub use suffix_automaton::SuffixAutomaton;
+pub use suffix_tree::SuffixTree;
+pub use t_digest::TDigest;
+pub use ternary_search_tree::TernarySearchTree;
+pub use treap::Treap;
+pub use trie::Trie;
+#[cfg(feature = "std")]
+pub use ttl_cache::TtlCache;
+pub use two_queue_cache::TwoQueueCache;
+pub use veb_tree::VebTree;
+pub use vec_map::VecMap;
+pub use vec_set::VecSet;
+pub use vp_tree::VpTree;
+pub use zipper::Zipper;
@@ -0,0 +1,291 @@
+use super::array_deque::ArrayDeque;
+
+/// A queue over a sliding window of a sequence, which knows the smallest and
+/// the largest values in the window.
+///
+/// Every pushed value gets the next position in the sequence, and the window
+/// is the values pushed since the start position given to
+/// [`MonotonicQueue::pop_expired()`]. Besides the values, two deques keep the
+/// positions of the candidates for the minimum and the maximum: a value that
+/// is pushed drops the candidates for the minimum that are not smaller than
+/// it, since they will expire before it, so the candidates stay in increasing
+/// order of their values and the minimum is the first of them. The maximum
+/// is kept the same way. Every position enters and leaves each deque once,
+/// so the operations take amortized O(1) time.
+///
+/// [`MonotonicQueue::pop_expired()`]: crate::containers::MonotonicQueue::pop_expired
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::MonotonicQueue;
+///
+/// // The minimums of the windows of 3 values.
+/// let values = [4, 2, 12, 3, 8, 5];
+/// let mut queue = MonotonicQueue::new();
+/// let mut mins = Vec::new();
+/// for (i, &v) in values.iter().enumerate() {
+///     queue.push(v);
+///     if i >= 2 {
+///         queue.pop_expired(i - 2);
+///         mins.push(*queue.min().unwrap());
+///     }
+/// }
+/// assert_eq!(mins, [2, 2, 3, 3]);
+/// ```
+#[derive(Debug)]
+pub struct MonotonicQueue<T> {
+    values: ArrayDeque<T>,
+    // The position of the first value of the window.
+    start: usize,
+    // The positions of the candidates for the minimum and the maximum, whose
+    // values are increasing and decreasing respectively.
+    mins: ArrayDeque<usize>,
+    maxs: ArrayDeque<usize>,
+}
+
+impl<T: Ord> Default for MonotonicQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> MonotonicQueue<T> {
+    /// Creates an empty MonotonicQueue, whose next value is at position 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let queue: MonotonicQueue<i32> = MonotonicQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            values: ArrayDeque::new(),
+            start: 0,
+            mins: ArrayDeque::new(),
+            maxs: ArrayDeque::new(),
+        }
+    }
+
+    /// Returns the number of values in the window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the window is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// assert!(queue.is_empty());
+    /// queue.push(1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the position of the first value of the window, which is the
+    /// position of the next pushed value if the window is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// queue.push(1);
+    /// queue.push(2);
+    /// queue.pop_front();
+    /// assert_eq!(queue.start(), 1);
+    /// ```
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Pushes a value at the end of the window, and returns its position.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// assert_eq!(queue.push(5), 0);
+    /// assert_eq!(queue.push(3), 1);
+    /// assert_eq!(queue.min(), Some(&3));
+    /// ```
+    pub fn push(&mut self, value: T) -> usize {
+        let pos = self.start + self.values.len();
+        while self.mins.back().is_some_and(|&p| *self.value(p) >= value) {
+            self.mins.pop_back();
+        }
+        while self.maxs.back().is_some_and(|&p| *self.value(p) <= value) {
+            self.maxs.pop_back();
+        }
+        self.values.push_back(value);
+        self.mins.push_back(pos);
+        self.maxs.push_back(pos);
+        pos
+    }
+
+    /// Removes the first value of the window and returns it, or None if the
+    /// window is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.pop_front(), Some(1));
+    /// assert_eq!(queue.min(), Some(&2));
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        let value = self.values.pop_front()?;
+        if self.mins.front() == Some(&self.start) {
+            self.mins.pop_front();
+        }
+        if self.maxs.front() == Some(&self.start) {
+            self.maxs.pop_front();
+        }
+        self.start += 1;
+        Some(value)
+    }
+
+    /// Removes the values at the positions before `start` from the window.
+    /// A `start` past the end of the window empties it, without moving the
+    /// position of the next pushed value.
+    ///
+    /// This operation should compute in O(k) time, where k is the number of
+    /// values removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// for v in [1, 5, 2, 4] {
+    ///     queue.push(v);
+    /// }
+    /// queue.pop_expired(2);
+    /// assert_eq!(queue.len(), 2);
+    /// assert_eq!(queue.max(), Some(&4));
+    /// ```
+    pub fn pop_expired(&mut self, start: usize) {
+        while self.start < start && self.pop_front().is_some() {}
+    }
+
+    /// Returns the smallest value in the window, or None if it is empty. The
+    /// last pushed of the smallest values is returned.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// queue.push(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.min(), Some(&1));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        self.mins.front().map(|&p| self.value(p))
+    }
+
+    /// Returns the largest value in the window, or None if it is empty. The
+    /// last pushed of the largest values is returned.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MonotonicQueue;
+    ///
+    /// let mut queue = MonotonicQueue::new();
+    /// queue.push(3);
+    /// queue.push(1);
+    /// queue.push(2);
+    /// assert_eq!(queue.max(), Some(&3));
+    /// ```
+    pub fn max(&self) -> Option<&T> {
+        self.maxs.front().map(|&p| self.value(p))
+    }
+
+    fn value(&self, pos: usize) -> &T {
+        &self.values[pos - self.start]
+    }
+}
+
+impl<T: Ord> Extend<T> for MonotonicQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn matches_naive_windows() {
+        let mut rng = SplitMix64::new(89);
+        let values: Vec<u64> = (0..500).map(|_| rng.next_u64() % 20).collect();
+        let mut queue = MonotonicQueue::new();
+        let mut start = 0;
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(queue.push(v), i);
+            if rng.next_u64().is_multiple_of(3) {
+                start = (start + (rng.next_u64() % 4) as usize).min(i + 1);
+                queue.pop_expired(start);
+            }
+            let window = &values[start..i + 1];
+            assert_eq!(queue.len(), window.len());
+            assert_eq!(queue.min(), window.iter().min());
+            assert_eq!(queue.max(), window.iter().max());
+        }
+    }
+
+    #[test]
+    fn expire_past_end() {
+        let mut queue = MonotonicQueue::new();
+        queue.extend(0..3);
+        queue.pop_expired(10);
+        assert!(queue.is_empty());
+        assert_eq!(queue.min(), None);
+        assert_eq!(queue.push(7), 3);
+        assert_eq!(queue.max(), Some(&7));
+    }
+}
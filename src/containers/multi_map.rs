@@ -0,0 +1,554 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use core::slice;
+
+use super::linked_hash_map::{self, DefaultHashBuilder, LinkedHashMap};
+
+/// A hash map where a key can have many values.
+///
+/// The values of a key are kept in a group, in the order they were inserted,
+/// and the groups are kept in a [`LinkedHashMap`] in the order their keys
+/// were first inserted. A key is removed once its group is empty, so every
+/// key in the map has at least one value.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::MultiMap;
+///
+/// let pets: MultiMap<_, _> = [
+///     ("alice", "cat"),
+///     ("bob", "dog"),
+///     ("alice", "parrot"),
+/// ]
+/// .iter()
+/// .cloned()
+/// .collect();
+///
+/// let alice: Vec<_> = pets.get_all("alice").collect();
+/// assert_eq!(alice, [&"cat", &"parrot"]);
+/// assert_eq!(pets.len(), 3);
+/// assert_eq!(pets.keys_len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct MultiMap<K, V, S = DefaultHashBuilder> {
+    map: LinkedHashMap<K, Vec<V>, S>,
+    len: usize,
+}
+
+impl<K, V, S> Default for MultiMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<K, V> MultiMap<K, V, DefaultHashBuilder> {
+    /// Creates an empty MultiMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let map: MultiMap<&str, i32> = MultiMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<K, V, S> MultiMap<K, V, S> {
+    /// Creates an empty MultiMap that uses the given hash builder to hash the
+    /// keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, MultiMap};
+    ///
+    /// let mut map = MultiMap::with_hasher(FnvBuildHasher::default());
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self {
+            map: LinkedHashMap::with_hasher(hasher_builder),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values, counting every value of every key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of distinct keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert_eq!(map.keys_len(), 1);
+    /// ```
+    pub fn keys_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map has no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the keys and their values, one pair per
+    /// value, grouped by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// map.insert(1, "c");
+    /// let pairs: Vec<_> = map.iter().collect();
+    /// assert_eq!(pairs, [(&1, &"a"), (&1, &"c"), (&2, &"b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        Iter {
+            groups: self.groups(),
+            group: None,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and all their values, in the order
+    /// the keys were first inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// map.insert(1, "c");
+    /// let groups: Vec<_> = map.groups().collect();
+    /// assert_eq!(groups, [(&1, &["a", "c"][..]), (&2, &["b"][..])]);
+    /// ```
+    pub fn groups(&self) -> Groups<'_, K, V, S> {
+        Groups {
+            iter: self.map.iter(),
+        }
+    }
+}
+
+impl<K, V, S> MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Appends the value to the values of the key.
+    ///
+    /// This operation should compute in expected amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert("a", 1);
+    /// map.insert("a", 1);
+    /// assert_eq!(map.get_all("a").count(), 2);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+
+    /// Returns a reference to the first value of the key.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)?.first()
+    }
+
+    /// Returns an iterator over the values of the key, in the order they were
+    /// inserted. The iterator is empty if the map does not have the key.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert!(map.get_all(&1).eq(&["a", "b"]));
+    /// assert_eq!(map.get_all(&2).next(), None);
+    /// ```
+    pub fn get_all<Q>(&self, key: &Q) -> GetAll<'_, V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.map.get(key).map_or(&[][..], Vec::as_slice);
+        GetAll {
+            iter: values.iter(),
+        }
+    }
+
+    /// Returns true if the map has a value for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Removes the last inserted value of the key and returns it. The key is
+    /// removed along with its last value.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert_eq!(map.remove_one(&1), Some("b"));
+    /// assert_eq!(map.remove_one(&1), Some("a"));
+    /// assert_eq!(map.remove_one(&1), None);
+    /// assert!(!map.contains_key(&1));
+    /// ```
+    pub fn remove_one<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.map.get_mut(key)?;
+        let value = values.pop();
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        value
+    }
+
+    /// Removes the key and returns all its values, in the order they were
+    /// inserted.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::MultiMap;
+    ///
+    /// let mut map = MultiMap::new();
+    /// map.insert(1, "a");
+    /// map.insert(1, "b");
+    /// assert_eq!(map.remove_all(&1), Some(vec!["a", "b"]));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Option<Vec<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.map.remove(key)?;
+        self.len -= values.len();
+        Some(values)
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a MultiMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a key in a [`MultiMap`].
+///
+/// This struct is created by [`MultiMap::get_all()`]. See its documentation
+/// for more.
+///
+/// [`MultiMap`]: crate::containers::MultiMap
+/// [`MultiMap::get_all()`]: crate::containers::MultiMap::get_all
+#[derive(Debug)]
+pub struct GetAll<'a, V> {
+    iter: slice::Iter<'a, V>,
+}
+
+impl<'a, V> Iterator for GetAll<'a, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for GetAll<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for GetAll<'a, V> {}
+
+/// An iterator over the keys of a [`MultiMap`] and all their values.
+///
+/// This struct is created by [`MultiMap::groups()`]. See its documentation
+/// for more.
+///
+/// [`MultiMap`]: crate::containers::MultiMap
+/// [`MultiMap::groups()`]: crate::containers::MultiMap::groups
+#[derive(Debug)]
+pub struct Groups<'a, K, V, S> {
+    iter: linked_hash_map::Iter<'a, K, Vec<V>, S>,
+}
+
+impl<'a, K, V, S> Iterator for Groups<'a, K, V, S> {
+    type Item = (&'a K, &'a [V]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(key, values)| (key, values.as_slice()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Groups<'a, K, V, S> {}
+
+/// An iterator over the keys and the values of a [`MultiMap`], one pair per
+/// value.
+///
+/// This struct is created by [`MultiMap::iter()`]. See its documentation for
+/// more.
+///
+/// [`MultiMap`]: crate::containers::MultiMap
+/// [`MultiMap::iter()`]: crate::containers::MultiMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V, S> {
+    groups: Groups<'a, K, V, S>,
+    group: Option<(&'a K, slice::Iter<'a, V>)>,
+    len: usize,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.group {
+                if let Some(value) = values.next() {
+                    self.len -= 1;
+                    return Some((key, value));
+                }
+            }
+            let (key, values) = self.groups.next()?;
+            self.group = Some((key, values.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Iter<'a, K, V, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn empty_map() {
+        let mut map: MultiMap<u32, u32> = MultiMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.keys_len(), 0);
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.get_all(&1).next(), None);
+        assert_eq!(map.remove_one(&1), None);
+        assert_eq!(map.remove_all(&1), None);
+        assert_eq!(map.iter().next(), None);
+        assert_eq!(map.groups().next(), None);
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn single_key() {
+        let mut map = MultiMap::new();
+        map.insert("a", 1);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.keys_len(), 1);
+
+        // Equal values are all kept.
+        map.insert("a", 1);
+        assert!(map.get_all("a").eq(&[1, 1]));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.keys_len(), 1);
+
+        // Removing the last value removes the key, which goes to the back of
+        // the groups when it is inserted again.
+        map.insert("b", 2);
+        assert_eq!(map.remove_one("a"), Some(1));
+        assert_eq!(map.remove_one("a"), Some(1));
+        assert!(!map.contains_key("a"));
+        assert_eq!(map.remove_one("a"), None);
+        assert_eq!(map.len(), 1);
+        map.insert("a", 3);
+        let keys: Vec<_> = map.groups().map(|(&k, _)| k).collect();
+        assert_eq!(keys, ["b", "a"]);
+        assert_eq!(map.remove_all("b"), Some(vec![2]));
+        assert_eq!(map.remove_all("b"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn matches_naive_pairs() {
+        let mut rng = SplitMix64::new(113);
+        let mut map = MultiMap::new();
+        let mut naive: Vec<(u64, u64)> = Vec::new();
+        for i in 0..2000 {
+            let key = rng.next_u64() % 10;
+            match rng.next_u64() % 5 {
+                0 => {
+                    let pos = naive.iter().rposition(|&(k, _)| k == key);
+                    let value = pos.map(|pos| naive.remove(pos).1);
+                    assert_eq!(map.remove_one(&key), value);
+                }
+                1 if i % 7 == 0 => {
+                    let values: Vec<_> = naive
+                        .iter()
+                        .filter(|&&(k, _)| k == key)
+                        .map(|&(_, v)| v)
+                        .collect();
+                    naive.retain(|&(k, _)| k != key);
+                    let expected = Some(values).filter(|v| !v.is_empty());
+                    assert_eq!(map.remove_all(&key), expected);
+                }
+                _ => {
+                    map.insert(key, i);
+                    naive.push((key, i));
+                }
+            }
+            assert_eq!(map.len(), naive.len());
+            assert_eq!(map.iter().len(), naive.len());
+            let values = naive.iter().filter(|&&(k, _)| k == key).map(|e| &e.1);
+            assert!(map.get_all(&key).eq(values));
+        }
+        for (key, values) in map.groups() {
+            assert!(!values.is_empty());
+            assert!(values
+                .iter()
+                .eq(naive.iter().filter(|&&(k, _)| k == *key).map(|e| &e.1)));
+        }
+    }
+}
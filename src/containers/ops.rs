@@ -0,0 +1,281 @@
+use core::ops::{Bound, Range, RangeBounds};
+
+/// An associative binary operation on `T` with an identity element, which the
+/// range-query containers use to combine the elements of a range.
+///
+/// The operation is given as a type, usually a unit struct, so that one
+/// element type can be combined in more than one way. Implementations must
+/// satisfy, for all `a`, `b` and `c`:
+///
+/// - `combine(&combine(&a, &b), &c) == combine(&a, &combine(&b, &c))`
+/// - `combine(&identity(), &a) == a == combine(&a, &identity())`
+///
+/// The operation does not need to be commutative.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{Monoid, SegmentTree};
+///
+/// /// Joins strings, which is associative but not commutative.
+/// #[derive(Debug)]
+/// struct Concat;
+///
+/// impl Monoid<String> for Concat {
+///     fn identity() -> String {
+///         String::new()
+///     }
+///
+///     fn combine(a: &String, b: &String) -> String {
+///         format!("{}{}", a, b)
+///     }
+/// }
+///
+/// let words: Vec<_> =
+///     ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+/// let tree = SegmentTree::<_, Concat>::new(&words);
+/// assert_eq!(tree.query(1..), "bc");
+/// ```
+pub trait Monoid<T> {
+    /// Returns the identity element of the operation.
+    fn identity() -> T;
+
+    /// Combines two elements, with `a` coming before `b`.
+    fn combine(a: &T, b: &T) -> T;
+}
+
+/// A [`Monoid`] whose combinations can be undone, which the containers that
+/// answer range queries from prefixes use to take one prefix out of another.
+///
+/// Implementations must satisfy `combine(&b, &difference(&a, &b)) == a`
+/// whenever `a` is the combination of `b` and some other element.
+///
+/// [`Monoid`]: crate::containers::Monoid
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{FenwickTree, Group, Monoid};
+///
+/// /// Toggles bits, where every element undoes itself.
+/// #[derive(Debug, Clone)]
+/// struct Xor;
+///
+/// impl Monoid<u8> for Xor {
+///     fn identity() -> u8 {
+///         0
+///     }
+///
+///     fn combine(a: &u8, b: &u8) -> u8 {
+///         a ^ b
+///     }
+/// }
+///
+/// impl Group<u8> for Xor {
+///     fn difference(a: &u8, b: &u8) -> u8 {
+///         a ^ b
+///     }
+/// }
+///
+/// let tree = FenwickTree::<_, Xor>::from_slice(&[0b01, 0b11, 0b10]);
+/// assert_eq!(tree.range_sum(1..3), 0b01);
+/// ```
+pub trait Group<T>: Monoid<T> {
+    /// Returns the element that, combined after `b`, gives `a`.
+    fn difference(a: &T, b: &T) -> T;
+}
+
+/// A [`Monoid`] where combining an element with itself gives the same
+/// element, which lets a range be covered by overlapping parts.
+///
+/// Implementations must satisfy `combine(&a, &a) == a` for all `a`.
+///
+/// [`Monoid`]: crate::containers::Monoid
+pub trait Idempotent<T>: Monoid<T> {}
+
+/// The operation that adds numbers, whose identity is zero.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sum;
+
+/// The operation that takes the least of two numbers, whose identity is the
+/// greatest value of the type.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Min;
+
+/// The operation that takes the greatest of two numbers, whose identity is the
+/// least value of the type.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Max;
+
+/// The operation that takes the greatest common divisor of two unsigned
+/// integers, whose identity is zero.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Gcd;
+
+macro_rules! impl_numeric_ops {
+    ($($t:ty => $zero:expr, $min:expr, $max:expr;)*) => {$(
+        impl Monoid<$t> for Sum {
+            fn identity() -> $t {
+                $zero
+            }
+
+            fn combine(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+        }
+
+        impl Group<$t> for Sum {
+            fn difference(a: &$t, b: &$t) -> $t {
+                a - b
+            }
+        }
+
+        impl Monoid<$t> for Min {
+            fn identity() -> $t {
+                $max
+            }
+
+            fn combine(a: &$t, b: &$t) -> $t {
+                if b < a {
+                    *b
+                } else {
+                    *a
+                }
+            }
+        }
+
+        impl Idempotent<$t> for Min {}
+
+        impl Monoid<$t> for Max {
+            fn identity() -> $t {
+                $min
+            }
+
+            fn combine(a: &$t, b: &$t) -> $t {
+                if b > a {
+                    *b
+                } else {
+                    *a
+                }
+            }
+        }
+
+        impl Idempotent<$t> for Max {}
+    )*};
+}
+
+macro_rules! impl_gcd {
+    ($($t:ty)*) => {$(
+        impl Monoid<$t> for Gcd {
+            fn identity() -> $t {
+                0
+            }
+
+            fn combine(a: &$t, b: &$t) -> $t {
+                let (mut a, mut b) = (*a, *b);
+                while b != 0 {
+                    let r = a % b;
+                    a = b;
+                    b = r;
+                }
+                a
+            }
+        }
+
+        impl Idempotent<$t> for Gcd {}
+    )*};
+}
+
+impl_gcd! { u8 u16 u32 u64 u128 usize }
+
+impl_numeric_ops! {
+    i8 => 0, i8::MIN, i8::MAX;
+    i16 => 0, i16::MIN, i16::MAX;
+    i32 => 0, i32::MIN, i32::MAX;
+    i64 => 0, i64::MIN, i64::MAX;
+    i128 => 0, i128::MIN, i128::MAX;
+    isize => 0, isize::MIN, isize::MAX;
+    u8 => 0, u8::MIN, u8::MAX;
+    u16 => 0, u16::MIN, u16::MAX;
+    u32 => 0, u32::MIN, u32::MAX;
+    u64 => 0, u64::MIN, u64::MAX;
+    u128 => 0, u128::MIN, u128::MAX;
+    usize => 0, usize::MIN, usize::MAX;
+    f32 => 0.0, f32::NEG_INFINITY, f32::INFINITY;
+    f64 => 0.0, f64::NEG_INFINITY, f64::INFINITY;
+}
+
+/// Combines `n` copies of the value, with O(log(n)) combinations.
+pub(crate) fn repeat<T: Clone, Op: Monoid<T>>(value: &T, mut n: usize) -> T {
+    let mut result = Op::identity();
+    let mut power = value.clone();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = Op::combine(&result, &power);
+        }
+        n >>= 1;
+        if n > 0 {
+            power = Op::combine(&power, &power);
+        }
+    }
+    result
+}
+
+/// Turns the bounds of a range of indices into a `Range`, panicking like
+/// slice indexing does if they are not within `0..len`.
+pub(crate) fn to_range<R: RangeBounds<usize>>(
+    range: R,
+    len: usize,
+) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => {
+            start.checked_add(1).expect("Out of bounds access")
+        }
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => {
+            end.checked_add(1).expect("Out of bounds access")
+        }
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "Range start is greater than range end");
+    assert!(end <= len, "Out of bounds access");
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_combines_copies() {
+        assert_eq!(repeat::<_, Sum>(&3, 0), 0);
+        assert_eq!(repeat::<_, Sum>(&3, 1), 3);
+        assert_eq!(repeat::<_, Sum>(&3, 13), 39);
+        assert_eq!(repeat::<_, Max>(&-3, 5), -3);
+    }
+
+    #[test]
+    fn gcd() {
+        assert_eq!(<Gcd as Monoid<u32>>::combine(&12, &18), 6);
+        assert_eq!(<Gcd as Monoid<u32>>::combine(&0, &7), 7);
+        assert_eq!(<Gcd as Monoid<u32>>::combine(&7, &0), 7);
+    }
+
+    #[test]
+    fn to_range_bounds() {
+        assert_eq!(to_range(.., 5), 0..5);
+        assert_eq!(to_range(1..=3, 5), 1..4);
+        assert_eq!(to_range(2.., 5), 2..5);
+        assert_eq!(to_range(5..5, 5), 5..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn to_range_out_of_bounds() {
+        to_range(..6, 5);
+    }
+}
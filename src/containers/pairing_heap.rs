@@ -0,0 +1,512 @@
+use alloc::vec::Vec;
+
+/// A handle to an element of a [`PairingHeap`].
+///
+/// A handle stays valid until its element is removed from the heap, no matter
+/// how the heap is restructured in the meantime. Handles of removed elements
+/// are never reused, so using one afterward is detected instead of silently
+/// referring to another element.
+///
+/// [`PairingHeap`]: crate::containers::PairingHeap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PairingHeapHandle {
+    index: usize,
+    generation: u64,
+}
+
+/// A node of [`PairingHeap`]. The children of a node are chained together
+/// through `sibling`, from its first child to its last one.
+///
+/// [`PairingHeap`]: crate::containers::PairingHeap
+#[derive(Debug)]
+struct Node<T> {
+    item: T,
+    child: Option<usize>,
+    sibling: Option<usize>,
+    // The previous sibling, or the parent for the first child.
+    prev: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    generation: u64,
+    node: Option<Node<T>>,
+}
+
+/// A priority queue implemented with a pairing heap.
+///
+/// This is a min-heap: the least element is always at the front. The heap is
+/// a tree in which no node is less than its parent. Pushing an element
+/// compares it with the root and makes the greater of the two a child of the
+/// other. Popping the root merges its children in pairs, then merges the
+/// pairs into a single tree.
+///
+/// Unlike [`BinaryHeap`], each pushed element gets a [`PairingHeapHandle`]
+/// that can be used to decrease its key or remove it, which is what
+/// algorithms like Dijkstra's and Prim's need.
+///
+/// [`BinaryHeap`]: crate::containers::BinaryHeap
+/// [`PairingHeapHandle`]: crate::containers::PairingHeapHandle
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::PairingHeap;
+///
+/// let mut heap = PairingHeap::new();
+/// heap.push(5);
+/// let h = heap.push(8);
+/// heap.push(3);
+///
+/// heap.decrease_key(h, 1);
+/// assert_eq!(heap.pop(), Some(1));
+/// assert_eq!(heap.pop(), Some(3));
+/// assert_eq!(heap.pop(), Some(5));
+/// assert_eq!(heap.pop(), None);
+/// ```
+#[derive(Debug)]
+pub struct PairingHeap<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Creates an empty PairingHeap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(4);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// assert!(heap.is_empty());
+    /// heap.push(1);
+    /// assert!(!heap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all elements from the heap. Every handle becomes invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(1);
+    /// heap.clear();
+    /// assert!(heap.is_empty());
+    /// assert!(!heap.contains(h));
+    /// ```
+    pub fn clear(&mut self) {
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.node.take().is_some() {
+                slot.generation += 1;
+            }
+            self.free.push(index);
+        }
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Pushes an element onto the heap and returns a handle to it.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(3);
+    /// assert_eq!(heap.get(h), Some(&3));
+    /// ```
+    pub fn push(&mut self, item: T) -> PairingHeapHandle {
+        let node = Node {
+            item,
+            child: None,
+            sibling: None,
+            prev: None,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = Some(node);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.root = Some(match self.root {
+            Some(root) => self.meld(root, index),
+            None => index,
+        });
+        self.len += 1;
+        PairingHeapHandle {
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+
+    /// Returns the least element in the heap, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// assert_eq!(heap.peek(), None);
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|root| &self.node(root).item)
+    }
+
+    /// Removes the least element from the heap and returns it, or None if it
+    /// is empty.
+    ///
+    /// This operation should compute in amortized O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(2);
+    /// heap.push(1);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(2));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root?;
+        self.root = self.combine(self.node(root).child);
+        Some(self.dealloc(root))
+    }
+
+    /// Returns true if the handle refers to an element of the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push(1);
+    /// assert!(heap.contains(h));
+    /// heap.pop();
+    /// assert!(!heap.contains(h));
+    /// ```
+    pub fn contains(&self, handle: PairingHeapHandle) -> bool {
+        self.slots.get(handle.index).is_some_and(|slot| {
+            slot.generation == handle.generation && slot.node.is_some()
+        })
+    }
+
+    /// Returns the element the handle refers to, or None if it has been
+    /// removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// let h = heap.push("a");
+    /// assert_eq!(heap.get(h), Some(&"a"));
+    /// ```
+    pub fn get(&self, handle: PairingHeapHandle) -> Option<&T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        Some(&self.node(handle.index).item)
+    }
+
+    /// Replaces the element the handle refers to with a lesser one.
+    ///
+    /// The node is cut off from its parent along with its children, and
+    /// merged back with the root.
+    ///
+    /// This operation should compute in amortized O(log(n)) time, although it
+    /// is conjectured to be much faster in practice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle does not refer to an element of the heap, or if
+    /// `item` is greater than the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(2);
+    /// let h = heap.push(3);
+    ///
+    /// heap.decrease_key(h, 1);
+    /// assert_eq!(heap.peek(), Some(&1));
+    /// ```
+    pub fn decrease_key(&mut self, handle: PairingHeapHandle, item: T) {
+        assert!(self.contains(handle), "Invalid handle");
+        let index = handle.index;
+        let node = self.node_mut(index);
+        assert!(item <= node.item, "New key is greater than the current key");
+        node.item = item;
+
+        if self.root != Some(index) {
+            self.detach(index);
+            let root = self.root.expect("heap is not empty");
+            self.root = Some(self.meld(root, index));
+        }
+    }
+
+    /// Removes the element the handle refers to and returns it, or None if it
+    /// has already been removed.
+    ///
+    /// This operation should compute in amortized O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PairingHeap;
+    ///
+    /// let mut heap = PairingHeap::new();
+    /// heap.push(1);
+    /// let h = heap.push(2);
+    /// heap.push(3);
+    ///
+    /// assert_eq!(heap.remove(h), Some(2));
+    /// assert_eq!(heap.remove(h), None);
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), Some(3));
+    /// ```
+    pub fn remove(&mut self, handle: PairingHeapHandle) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        let index = handle.index;
+        if self.root == Some(index) {
+            return self.pop();
+        }
+        self.detach(index);
+        if let Some(subtree) = self.combine(self.node(index).child) {
+            let root = self.root.expect("heap is not empty");
+            self.root = Some(self.meld(root, subtree));
+        }
+        Some(self.dealloc(index))
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        self.slots[index].node.as_ref().expect("slot is vacant")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.slots[index].node.as_mut().expect("slot is vacant")
+    }
+
+    /// Frees the slot at `index` and returns the element it held.
+    fn dealloc(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        let node = slot.node.take().expect("slot is vacant");
+        slot.generation += 1;
+        self.free.push(index);
+        self.len -= 1;
+        node.item
+    }
+
+    /// Merges the trees rooted at `a` and `b`, by making the root with the
+    /// greater element the first child of the other. Returns the root of the
+    /// merged tree.
+    fn meld(&mut self, a: usize, b: usize) -> usize {
+        let (parent, child) = if self.node(b).item < self.node(a).item {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first = self.node(parent).child;
+        if let Some(first) = first {
+            self.node_mut(first).prev = Some(child);
+        }
+        let node = self.node_mut(child);
+        node.sibling = first;
+        node.prev = Some(parent);
+        self.node_mut(parent).child = Some(child);
+        parent
+    }
+
+    /// Merges a list of siblings into a single tree, and returns its root.
+    ///
+    /// The siblings are first merged in pairs from left to right, then the
+    /// resulting trees are merged from right to left. Doing it in two passes
+    /// is what gives the heap its amortized bounds.
+    fn combine(&mut self, first: Option<usize>) -> Option<usize> {
+        let mut trees = Vec::new();
+        let mut it = first;
+        while let Some(a) = it {
+            let b = self.node(a).sibling;
+            it = b.and_then(|b| self.node(b).sibling);
+            self.unlink(a);
+            trees.push(match b {
+                Some(b) => {
+                    self.unlink(b);
+                    self.meld(a, b)
+                }
+                None => a,
+            });
+        }
+        let mut root = trees.pop()?;
+        while let Some(tree) = trees.pop() {
+            root = self.meld(tree, root);
+        }
+        Some(root)
+    }
+
+    /// Cuts the subtree rooted at `index` off from its parent and siblings.
+    fn detach(&mut self, index: usize) {
+        let Node { prev, sibling, .. } = *self.node(index);
+        if let Some(prev) = prev {
+            let prev = self.node_mut(prev);
+            if prev.child == Some(index) {
+                prev.child = sibling;
+            } else {
+                prev.sibling = sibling;
+            }
+        }
+        if let Some(sibling) = sibling {
+            self.node_mut(sibling).prev = prev;
+        }
+        self.unlink(index);
+    }
+
+    /// Forgets the parent and siblings of the node at `index`, without
+    /// updating them.
+    fn unlink(&mut self, index: usize) {
+        let node = self.node_mut(index);
+        node.prev = None;
+        node.sibling = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let mut heap = PairingHeap::new();
+        for &x in &[5, 1, 8, 3, 9, 2, 7, 3] {
+            heap.push(x);
+        }
+        assert_eq!(heap.len(), 8);
+
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, vec![1, 2, 3, 3, 5, 7, 8, 9]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn decrease_key_and_remove() {
+        let mut heap = PairingHeap::new();
+        let handles: Vec<_> = (0..50).map(|x| heap.push(x * 10)).collect();
+        // Restructure the heap before using the handles.
+        assert_eq!(heap.pop(), Some(0));
+
+        for (i, &h) in handles.iter().enumerate().skip(1).step_by(2) {
+            heap.decrease_key(h, i as i32 * 10 - 15);
+        }
+        for &h in handles.iter().skip(2).step_by(4) {
+            assert!(heap.remove(h).is_some());
+        }
+        assert_eq!(heap.remove(handles[0]), None);
+
+        let mut expected: Vec<_> = (1..50)
+            .filter(|i| i % 4 != 2)
+            .map(|i| if i % 2 == 1 { i * 10 - 15 } else { i * 10 })
+            .collect();
+        expected.sort_unstable();
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn stale_handles() {
+        let mut heap = PairingHeap::new();
+        let h = heap.push(1);
+        heap.pop();
+        let other = heap.push(2);
+        assert!(!heap.contains(h));
+        assert_eq!(heap.get(h), None);
+        assert_eq!(heap.get(other), Some(&2));
+    }
+
+    #[test]
+    #[should_panic(expected = "New key is greater than the current key")]
+    fn increase_key() {
+        let mut heap = PairingHeap::new();
+        let h = heap.push(1);
+        heap.decrease_key(h, 2);
+    }
+}
@@ -0,0 +1,348 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T> {
+    next: Link<T>,
+    data: T,
+}
+
+/// An immutable singly-linked list whose versions share their nodes.
+///
+/// Pushing to the front of a list returns a new list whose first node points
+/// to the nodes of the old one, and taking the tail of a list returns a list
+/// that starts at its second node, so neither copies anything and the old
+/// list stays valid. The nodes are reference-counted with [`Arc`], so the
+/// versions of a list can be sent to and shared between threads, and a node
+/// is freed once no version of the list reaches it.
+///
+/// [`Arc`]: alloc::sync::Arc
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::PersistentList;
+///
+/// let empty = PersistentList::new();
+/// let one = empty.push_front(1);
+/// let two = one.push_front(2);
+/// let other = one.push_front(3);
+///
+/// // All the versions are still valid, and `two` and `other` share `one`.
+/// assert!(empty.is_empty());
+/// assert_eq!(two.iter().collect::<Vec<_>>(), [&2, &1]);
+/// assert_eq!(other.iter().collect::<Vec<_>>(), [&3, &1]);
+/// assert_eq!(two.tail(), Some(one));
+/// ```
+pub struct PersistentList<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    /// Returns another handle to the same list, which shares all its nodes.
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    /// Nodes that are not shared with another list are unlinked one by one,
+    /// because the default drop of a chain of nodes is recursive and can
+    /// overflow the stack for long lists.
+    fn drop(&mut self) {
+        let mut it = self.head.take();
+        while let Some(node) = it {
+            it = match Arc::try_unwrap(node) {
+                Ok(mut node) => node.next.take(),
+                Err(_) => break,
+            };
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T> PersistentList<T> {
+    /// Creates an empty PersistentList.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list: PersistentList<u32> = PersistentList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list = PersistentList::new().push_front(1).push_front(2);
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list = PersistentList::new();
+    /// assert!(list.is_empty());
+    /// assert!(!list.push_front(1).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns a reference to the first element of the list.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list = PersistentList::new();
+    /// assert_eq!(list.front(), None);
+    /// assert_eq!(list.push_front(1).front(), Some(&1));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.data)
+    }
+
+    /// Returns a new list with the element in front of the elements of this
+    /// list, which it shares with this list.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let a = PersistentList::new().push_front(1);
+    /// let b = a.push_front(2);
+    /// assert_eq!(a.len(), 1);
+    /// assert_eq!(b.front(), Some(&2));
+    /// ```
+    pub fn push_front(&self, data: T) -> Self {
+        Self {
+            head: Some(Arc::new(Node {
+                next: self.head.clone(),
+                data,
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    /// Returns the list of all the elements but the first, which shares its
+    /// nodes with this list, or [`None`] if the list is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list: PersistentList<_> = (1..=3).collect();
+    /// let tail = list.tail().unwrap();
+    /// assert_eq!(tail.front(), Some(&2));
+    /// assert_eq!(list.len(), 3);
+    /// assert_eq!(PersistentList::<i32>::new().tail(), None);
+    /// ```
+    pub fn tail(&self) -> Option<Self> {
+        self.head.as_ref().map(|node| Self {
+            head: node.next.clone(),
+            len: self.len - 1,
+        })
+    }
+
+    /// Returns a reference to the element at the index.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list: PersistentList<_> = (0..5).map(|x| x * 10).collect();
+    /// assert_eq!(list.get(2), Some(&20));
+    /// assert_eq!(list.get(5), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    /// Returns true if both lists start at the same node, so they are the
+    /// same list without comparing their elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let a = PersistentList::new().push_front(1);
+    /// let b = PersistentList::new().push_front(1);
+    /// assert!(a.ptr_eq(&a.clone()));
+    /// assert!(!a.ptr_eq(&b));
+    /// assert!(a.push_front(2).tail().unwrap().ptr_eq(&a));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Provides a forward iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentList;
+    ///
+    /// let list = PersistentList::new().push_front(1).push_front(2);
+    /// let mut iter = list.iter();
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), Some(&1));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PersistentList<T> {
+    /// Creates a list with the elements in the order of the iterator.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut list = Self::new();
+        for data in values.into_iter().rev() {
+            list.head = Some(Arc::new(Node {
+                next: list.head.take(),
+                data,
+            }));
+            list.len += 1;
+        }
+        list
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a PersistentList.
+///
+/// This struct is created by [`PersistentList::iter()`]. See its
+/// documentation for more.
+///
+/// [`PersistentList::iter()`]: crate::containers::PersistentList::iter
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.len -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_share_nodes() {
+        let base: PersistentList<_> = (0..100).collect();
+        let mut versions = Vec::new();
+        let mut list = base.clone();
+        for i in 0..50 {
+            list = list.tail().unwrap().push_front(i * 1000);
+            versions.push(list.clone());
+        }
+        for (i, version) in versions.iter().enumerate() {
+            assert_eq!(version.len(), 100);
+            assert_eq!(version.front(), Some(&(i * 1000)));
+            // Every version shares all but its first node with `base`.
+            assert!(version.tail().unwrap().ptr_eq(&base.tail().unwrap()));
+        }
+        assert!(base.iter().copied().eq(0..100));
+    }
+
+    #[test]
+    fn drops_long_list() {
+        let mut list = PersistentList::new();
+        for i in 0..200_000 {
+            list = list.push_front(i);
+        }
+        let shared = list.tail().unwrap();
+        drop(list);
+        assert_eq!(shared.len(), 199_999);
+        drop(shared);
+    }
+}
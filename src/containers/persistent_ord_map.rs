@@ -0,0 +1,747 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::{Bound, RangeBounds};
+
+/// A subtree is rebalanced when one side is more than `DELTA` times as large
+/// as the other.
+const DELTA: usize = 3;
+
+/// A rotation is doubled when the inner grandchild of the larger side is at
+/// least `RATIO` times as large as the outer one.
+const RATIO: usize = 2;
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    size: usize,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn node<K, V>(
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Link<K, V> {
+    Some(Arc::new(Node {
+        size: size(&left) + size(&right) + 1,
+        key,
+        value,
+        left,
+        right,
+    }))
+}
+
+/// Builds a node whose subtrees were balanced before one of them gained or
+/// lost a node, rotating it if it is now out of balance.
+fn balance<K: Clone, V: Clone>(
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Link<K, V> {
+    let (l, r) = (size(&left), size(&right));
+    if l + r <= 1 {
+        node(key, value, left, right)
+    } else if r > DELTA * l {
+        let pivot = right.expect("Right subtree must be larger");
+        if size(&pivot.left) < RATIO * size(&pivot.right) {
+            let inner = node(key, value, left, pivot.left.clone());
+            node(
+                pivot.key.clone(),
+                pivot.value.clone(),
+                inner,
+                pivot.right.clone(),
+            )
+        } else {
+            let mid =
+                pivot.left.as_ref().expect("Inner subtree must be larger");
+            let outer = node(key, value, left, mid.left.clone());
+            let inner = node(
+                pivot.key.clone(),
+                pivot.value.clone(),
+                mid.right.clone(),
+                pivot.right.clone(),
+            );
+            node(mid.key.clone(), mid.value.clone(), outer, inner)
+        }
+    } else if l > DELTA * r {
+        let pivot = left.expect("Left subtree must be larger");
+        if size(&pivot.right) < RATIO * size(&pivot.left) {
+            let inner = node(key, value, pivot.right.clone(), right);
+            node(
+                pivot.key.clone(),
+                pivot.value.clone(),
+                pivot.left.clone(),
+                inner,
+            )
+        } else {
+            let mid =
+                pivot.right.as_ref().expect("Inner subtree must be larger");
+            let outer = node(key, value, mid.right.clone(), right);
+            let inner = node(
+                pivot.key.clone(),
+                pivot.value.clone(),
+                pivot.left.clone(),
+                mid.left.clone(),
+            );
+            node(mid.key.clone(), mid.value.clone(), inner, outer)
+        }
+    } else {
+        node(key, value, left, right)
+    }
+}
+
+fn insert<K: Ord + Clone, V: Clone>(
+    link: &Link<K, V>,
+    key: K,
+    value: V,
+) -> Link<K, V> {
+    let n = match link {
+        Some(n) => n,
+        None => return node(key, value, None, None),
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => balance(
+            n.key.clone(),
+            n.value.clone(),
+            insert(&n.left, key, value),
+            n.right.clone(),
+        ),
+        Ordering::Greater => balance(
+            n.key.clone(),
+            n.value.clone(),
+            n.left.clone(),
+            insert(&n.right, key, value),
+        ),
+        Ordering::Equal => node(key, value, n.left.clone(), n.right.clone()),
+    }
+}
+
+/// Returns the subtree without the key, or None if the key is not in it, so
+/// that nothing is copied.
+fn remove<K, V, Q>(link: &Link<K, V>, key: &Q) -> Option<Link<K, V>>
+where
+    K: Borrow<Q> + Clone,
+    V: Clone,
+    Q: Ord + ?Sized,
+{
+    let n = link.as_ref()?;
+    Some(match key.cmp(n.key.borrow()) {
+        Ordering::Less => balance(
+            n.key.clone(),
+            n.value.clone(),
+            remove(&n.left, key)?,
+            n.right.clone(),
+        ),
+        Ordering::Greater => balance(
+            n.key.clone(),
+            n.value.clone(),
+            n.left.clone(),
+            remove(&n.right, key)?,
+        ),
+        Ordering::Equal => glue(&n.left, &n.right),
+    })
+}
+
+/// Joins two balanced subtrees whose keys are all smaller on the left, by
+/// moving the extreme key of the larger one to the root.
+fn glue<K: Clone, V: Clone>(
+    left: &Link<K, V>,
+    right: &Link<K, V>,
+) -> Link<K, V> {
+    match (left, right) {
+        (None, _) => right.clone(),
+        (_, None) => left.clone(),
+        (Some(l), Some(r)) => {
+            if l.size > r.size {
+                let (key, value, left) = remove_max(l);
+                balance(key, value, left, right.clone())
+            } else {
+                let (key, value, right) = remove_min(r);
+                balance(key, value, left.clone(), right)
+            }
+        }
+    }
+}
+
+fn remove_min<K: Clone, V: Clone>(n: &Node<K, V>) -> (K, V, Link<K, V>) {
+    match &n.left {
+        None => (n.key.clone(), n.value.clone(), n.right.clone()),
+        Some(left) => {
+            let (key, value, left) = remove_min(left);
+            let rest =
+                balance(n.key.clone(), n.value.clone(), left, n.right.clone());
+            (key, value, rest)
+        }
+    }
+}
+
+fn remove_max<K: Clone, V: Clone>(n: &Node<K, V>) -> (K, V, Link<K, V>) {
+    match &n.right {
+        None => (n.key.clone(), n.value.clone(), n.left.clone()),
+        Some(right) => {
+            let (key, value, right) = remove_max(right);
+            let rest =
+                balance(n.key.clone(), n.value.clone(), n.left.clone(), right);
+            (key, value, rest)
+        }
+    }
+}
+
+/// An immutable ordered map whose versions share their unchanged subtrees.
+///
+/// The map is a weight-balanced tree, where every node knows the size of its
+/// subtree, and neither side of a node is more than three times as large as
+/// the other, so the height of the tree is O(log(n)). Inserting or removing a
+/// key copies only the nodes on the path from the root to the key, rotating
+/// them where the balance is broken, and returns a new map; the other nodes
+/// are shared through [`Arc`]s, and the old map stays valid. Since copied
+/// nodes clone their keys and values, both must implement [`Clone`], which
+/// should be cheap.
+///
+/// [`Arc`]: alloc::sync::Arc
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::PersistentOrdMap;
+///
+/// let v1: PersistentOrdMap<_, _> = (0..5).map(|k| (k, k * k)).collect();
+/// let v2 = v1.insert(5, 25).remove(&0);
+///
+/// // The old version is unchanged.
+/// assert_eq!(v1.get(&0), Some(&0));
+/// assert_eq!(v1.get(&5), None);
+/// assert_eq!(v2.get(&0), None);
+/// let squares: Vec<_> = v2.range(3..).map(|(_, v)| *v).collect();
+/// assert_eq!(squares, [9, 16, 25]);
+/// ```
+pub struct PersistentOrdMap<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K, V> Default for PersistentOrdMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Clone for PersistentOrdMap<K, V> {
+    /// Returns another handle to the same map, which shares all its nodes.
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentOrdMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for PersistentOrdMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for PersistentOrdMap<K, V> {}
+
+impl<K, V> PersistentOrdMap<K, V> {
+    /// Creates an empty PersistentOrdMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<i32, i32> = PersistentOrdMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map = PersistentOrdMap::new().insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map = PersistentOrdMap::new();
+    /// assert!(map.is_empty());
+    /// assert!(!map.insert(1, "a").is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            range: self.seek(|_| false, self.len()),
+        }
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.first_key_value(), Some((&1, &"a")));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut n = self.root.as_deref()?;
+        while let Some(left) = n.left.as_deref() {
+            n = left;
+        }
+        Some((&n.key, &n.value))
+    }
+
+    /// Returns the entry with the largest key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.last_key_value(), Some((&2, &"b")));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut n = self.root.as_deref()?;
+        while let Some(right) = n.right.as_deref() {
+            n = right;
+        }
+        Some((&n.key, &n.value))
+    }
+
+    /// Returns true if both maps have the same root node, so they are the
+    /// same map without comparing their entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let a = PersistentOrdMap::new().insert(1, "a");
+    /// assert!(a.ptr_eq(&a.clone()));
+    /// assert!(!a.ptr_eq(&a.insert(1, "a")));
+    /// // Removing a missing key does not copy anything.
+    /// assert!(a.ptr_eq(&a.remove(&2)));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Checks that every node is balanced, that the sizes of the subtrees
+    /// are right, and that the keys are in order.
+    ///
+    /// This is meant for tests, and computes in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree is not a valid weight-balanced tree, which can only
+    /// happen if the Ord implementation of the keys is inconsistent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<_, _> = (0..100).map(|k| (k, ())).collect();
+    /// map.assert_valid();
+    /// ```
+    pub fn assert_valid(&self)
+    where
+        K: Ord,
+    {
+        fn check<K, V>(link: &Link<K, V>) -> usize {
+            let n = match link {
+                Some(n) => n,
+                None => return 0,
+            };
+            let (l, r) = (check(&n.left), check(&n.right));
+            assert_eq!(n.size, l + r + 1, "Wrong size");
+            assert!(
+                l + r <= 1 || (l <= DELTA * r && r <= DELTA * l),
+                "Unbalanced"
+            );
+            n.size
+        }
+        check(&self.root);
+        let keys: Vec<_> = self.iter().map(|(k, _)| k).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "Unordered keys");
+    }
+
+    /// Returns an iterator over the `len` entries from the first key for
+    /// which `before` is false.
+    fn seek<F>(&self, before: F, len: usize) -> Range<'_, K, V>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut stack = Vec::new();
+        let mut link = self.root.as_deref();
+        while let Some(n) = link {
+            if before(&n.key) {
+                link = n.right.as_deref();
+            } else {
+                stack.push(n);
+                link = n.left.as_deref();
+            }
+        }
+        Range { stack, len }
+    }
+
+    /// Returns the number of keys for which `before` is true, which must hold
+    /// for a prefix of the keys.
+    fn rank<F>(&self, before: F) -> usize
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut rank = 0;
+        let mut link = self.root.as_deref();
+        while let Some(n) = link {
+            if before(&n.key) {
+                rank += size(&n.left) + 1;
+                link = n.right.as_deref();
+            } else {
+                link = n.left.as_deref();
+            }
+        }
+        rank
+    }
+}
+
+impl<K: Ord, V> PersistentOrdMap<K, V> {
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map = PersistentOrdMap::new().insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = self.root.as_deref();
+        while let Some(n) = link {
+            link = match key.cmp(n.key.borrow()) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+        None
+    }
+
+    /// Returns true if the map contains the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map = PersistentOrdMap::new().insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator visiting the entries of the map whose keys are in
+    /// the range, in ascending order of their keys. The iterator is empty if
+    /// the start of the range is after its end.
+    ///
+    /// This operation should compute in O(log(n)) time, and each step of the
+    /// iterator in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let map: PersistentOrdMap<_, _> = (0..10).map(|k| (k, k * k)).collect();
+    /// let squares: Vec<_> = map.range(3..6).map(|(_, v)| *v).collect();
+    /// assert_eq!(squares, [9, 16, 25]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.rank(|k| k.borrow() <= end),
+            Bound::Excluded(end) => self.rank(|k| k.borrow() < end),
+            Bound::Unbounded => self.len(),
+        };
+        match range.start_bound() {
+            Bound::Included(start) => {
+                let before = |k: &K| k.borrow() < start;
+                self.seek(before, end.saturating_sub(self.rank(before)))
+            }
+            Bound::Excluded(start) => {
+                let before = |k: &K| k.borrow() <= start;
+                self.seek(before, end.saturating_sub(self.rank(before)))
+            }
+            Bound::Unbounded => self.seek(|_| false, end),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentOrdMap<K, V> {
+    /// Returns a new map with the key-value pair, replacing the old value of
+    /// the key if the map had it.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let a = PersistentOrdMap::new().insert(1, "a");
+    /// let b = a.insert(1, "b");
+    /// assert_eq!(a.get(&1), Some(&"a"));
+    /// assert_eq!(b.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Self {
+        Self {
+            root: insert(&self.root, key, value),
+        }
+    }
+
+    /// Returns a new map without the key. If the map does not have the key,
+    /// the new map shares the root of this one.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PersistentOrdMap;
+    ///
+    /// let a = PersistentOrdMap::new().insert(1, "a");
+    /// let b = a.remove(&1);
+    /// assert!(b.is_empty());
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        Self {
+            root: remove(&self.root, key).unwrap_or_else(|| self.root.clone()),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> FromIterator<(K, V)> for PersistentOrdMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.root = insert(&map.root, key, value);
+        }
+        map
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a PersistentOrdMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over a range of the entries of a PersistentOrdMap, in
+/// ascending order of their keys.
+///
+/// This struct is created by [`PersistentOrdMap::range()`]. See its
+/// documentation for more.
+///
+/// [`PersistentOrdMap::range()`]: crate::containers::PersistentOrdMap::range
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    // The nodes whose entries and right subtrees are still to be visited,
+    // with the next entry on top.
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let n = self.stack.pop()?;
+        let mut link = n.right.as_deref();
+        while let Some(next) = link {
+            self.stack.push(next);
+            link = next.left.as_deref();
+        }
+        self.len -= 1;
+        Some((&n.key, &n.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Range<'a, K, V> {}
+
+/// An iterator over the entries of a PersistentOrdMap, in ascending order of
+/// their keys.
+///
+/// This struct is created by [`PersistentOrdMap::iter()`]. See its
+/// documentation for more.
+///
+/// [`PersistentOrdMap::iter()`]: crate::containers::PersistentOrdMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    #[test]
+    fn versions_match_btree_map() {
+        let mut rng = SplitMix64::new(17);
+        let mut versions = vec![(PersistentOrdMap::new(), BTreeMap::new())];
+        for i in 0..1500 {
+            let (map, expected) =
+                &versions[rng.next_u64() as usize % versions.len()];
+            let (mut map, mut expected) = (map.clone(), expected.clone());
+            let key = rng.next_u64() % 300;
+            if rng.next_u64().is_multiple_of(3) {
+                map = map.remove(&key);
+                expected.remove(&key);
+            } else {
+                map = map.insert(key, i);
+                expected.insert(key, i);
+            }
+            versions.push((map, expected));
+        }
+        for (map, expected) in &versions {
+            map.assert_valid();
+            assert_eq!(map.len(), expected.len());
+            assert!(map.iter().eq(expected.iter()));
+            assert!(map.range(100..200).eq(expected.range(100..200)));
+            assert!(map.range(..=50).eq(expected.range(..=50)));
+            let after = (Bound::Excluded(250), Bound::Unbounded);
+            assert!(map.range(after).eq(expected.range(after)));
+            assert_eq!(
+                map.range(100..200).len(),
+                expected.range(100..200).count()
+            );
+        }
+    }
+
+    #[test]
+    fn sorted_insertions_stay_shallow() {
+        fn height<K, V>(link: &Link<K, V>) -> usize {
+            link.as_ref()
+                .map_or(0, |n| 1 + height(&n.left).max(height(&n.right)))
+        }
+        let map: PersistentOrdMap<_, _> = (0..4096).map(|k| (k, ())).collect();
+        map.assert_valid();
+        // A weight-balanced tree with a ratio of 3 has a height of at most
+        // about 2.5 log2(n).
+        assert!(height(&map.root) <= 30);
+    }
+}
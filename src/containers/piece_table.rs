@@ -0,0 +1,504 @@
+use alloc::vec::Vec;
+use core::ops::RangeBounds;
+use core::slice;
+
+use super::ops;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Original,
+    Add,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Piece {
+    source: Source,
+    start: usize,
+    len: usize,
+}
+
+/// A text document stored as a list of pieces of two buffers: the original
+/// text, which is never changed, and an add buffer, which is only appended to.
+///
+/// Inserting text appends it to the add buffer and splits the piece at the
+/// insertion point to make room for a piece of the new text. Deleting text
+/// shortens or splits the pieces at the ends of the range and drops the ones
+/// in between. No text is ever copied or moved, and since the buffers never
+/// change, an old piece list still describes an old version of the document:
+/// undoing an edit is putting back the piece list from before it.
+///
+/// Edits take O(p) time, where p is the number of pieces, which grows with the
+/// number of edits rather than with the length of the document.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::PieceTable;
+///
+/// let mut doc = PieceTable::new("hello world");
+/// doc.insert(5, ",");
+/// doc.delete(7..);
+/// doc.insert(7, "there");
+/// assert_eq!(doc.to_vec(), b"hello, there");
+///
+/// doc.undo();
+/// assert_eq!(doc.to_vec(), b"hello, ");
+/// doc.redo();
+/// assert_eq!(doc.to_vec(), b"hello, there");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PieceTable {
+    original: Vec<u8>,
+    add: Vec<u8>,
+    pieces: Vec<Piece>,
+    len: usize,
+    // The piece lists from before the edits, and from before the undos.
+    undo: Vec<Vec<Piece>>,
+    redo: Vec<Vec<Piece>>,
+}
+
+impl Default for PieceTable {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl PieceTable {
+    /// Creates a PieceTable whose document is a copy of the original text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let doc = PieceTable::new("abc");
+    /// assert_eq!(doc.len(), 3);
+    /// ```
+    pub fn new<T>(original: &T) -> Self
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        let original = original.as_ref().to_vec();
+        let len = original.len();
+        let mut pieces = Vec::new();
+        if len > 0 {
+            pieces.push(Piece {
+                source: Source::Original,
+                start: 0,
+                len,
+            });
+        }
+        Self {
+            original,
+            add: Vec::new(),
+            pieces,
+            len,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Returns the length of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("abc");
+    /// doc.insert(3, "de");
+    /// assert_eq!(doc.len(), 5);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the document is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("abc");
+    /// assert!(!doc.is_empty());
+    /// doc.delete(..);
+    /// assert!(doc.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of pieces the document is made of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("ac");
+    /// doc.insert(1, "b");
+    /// assert_eq!(doc.piece_count(), 3);
+    /// ```
+    pub fn piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Inserts the text at the position in the document. Text inserted right
+    /// after the previous insertion extends its piece instead of adding one.
+    ///
+    /// This operation should compute in O(p + m) time, where m is the length
+    /// of the text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is greater than the length of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("ad");
+    /// doc.insert(1, "b");
+    /// doc.insert(2, "c");
+    /// assert_eq!(doc.to_vec(), b"abcd");
+    /// assert_eq!(doc.piece_count(), 3);
+    /// ```
+    pub fn insert<T>(&mut self, pos: usize, text: &T)
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        assert!(pos <= self.len, "Out of bounds access");
+        let text = text.as_ref();
+        if text.is_empty() {
+            return;
+        }
+        self.save();
+        let i = self.split(pos);
+        let start = self.add.len();
+        self.add.extend_from_slice(text);
+        self.len += text.len();
+        if i > 0 {
+            let prev = &mut self.pieces[i - 1];
+            if prev.source == Source::Add && prev.start + prev.len == start {
+                prev.len += text.len();
+                return;
+            }
+        }
+        self.pieces.insert(
+            i,
+            Piece {
+                source: Source::Add,
+                start,
+                len: text.len(),
+            },
+        );
+    }
+
+    /// Deletes the text in the range of positions of the document.
+    ///
+    /// This operation should compute in O(p) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or if its start is greater than
+    /// its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("abcdef");
+    /// doc.delete(1..=2);
+    /// assert_eq!(doc.to_vec(), b"adef");
+    /// ```
+    pub fn delete<R: RangeBounds<usize>>(&mut self, range: R) {
+        let range = ops::to_range(range, self.len);
+        if range.is_empty() {
+            return;
+        }
+        self.save();
+        let start = self.split(range.start);
+        let end = self.split(range.end);
+        self.pieces.drain(start..end);
+        self.len -= range.len();
+    }
+
+    /// Puts back the document from before the last edit that was not undone.
+    /// Returns false if there is no such edit.
+    ///
+    /// This operation should compute in O(p) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("a");
+    /// doc.insert(1, "b");
+    /// assert!(doc.undo());
+    /// assert_eq!(doc.to_vec(), b"a");
+    /// assert!(!doc.undo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(pieces) => {
+                let pieces = core::mem::replace(&mut self.pieces, pieces);
+                self.redo.push(pieces);
+                self.len = self.pieces.iter().map(|p| p.len).sum();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Puts back the document from before the last undo, if there was no edit
+    /// since. Returns false if there is no such undo.
+    ///
+    /// This operation should compute in O(p) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("a");
+    /// doc.insert(1, "b");
+    /// doc.undo();
+    /// assert!(doc.redo());
+    /// assert_eq!(doc.to_vec(), b"ab");
+    /// assert!(!doc.redo());
+    /// ```
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(pieces) => {
+                let pieces = core::mem::replace(&mut self.pieces, pieces);
+                self.undo.push(pieces);
+                self.len = self.pieces.iter().map(|p| p.len).sum();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an iterator visiting the pieces of the document in order, as
+    /// slices of the buffers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("ac");
+    /// doc.insert(1, "b");
+    /// let pieces: Vec<_> = doc.pieces().collect();
+    /// assert_eq!(pieces, [b"a", b"b", b"c"]);
+    /// ```
+    pub fn pieces(&self) -> Pieces<'_> {
+        Pieces {
+            table: self,
+            iter: self.pieces.iter(),
+        }
+    }
+
+    /// Returns an iterator visiting the bytes of the document in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("ac");
+    /// doc.insert(1, "b");
+    /// assert!(doc.bytes().eq(b"abc".iter().copied()));
+    /// ```
+    pub fn bytes(&self) -> Bytes<'_> {
+        Bytes {
+            pieces: self.pieces(),
+            bytes: [].iter(),
+        }
+    }
+
+    /// Returns the document as a vector of bytes.
+    ///
+    /// This operation should compute in O(n + p) time, where n is the length
+    /// of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::PieceTable;
+    ///
+    /// let mut doc = PieceTable::new("ac");
+    /// doc.insert(1, "b");
+    /// assert_eq!(doc.to_vec(), b"abc");
+    /// ```
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut doc = Vec::with_capacity(self.len);
+        for piece in self.pieces() {
+            doc.extend_from_slice(piece);
+        }
+        doc
+    }
+
+    fn save(&mut self) {
+        self.undo.push(self.pieces.clone());
+        self.redo.clear();
+    }
+
+    /// Returns the index of the piece starting at the position, splitting the
+    /// piece the position is in if needed.
+    fn split(&mut self, pos: usize) -> usize {
+        let mut at = 0;
+        for i in 0..self.pieces.len() {
+            let piece = self.pieces[i];
+            if at == pos {
+                return i;
+            }
+            if pos < at + piece.len {
+                let offset = pos - at;
+                self.pieces[i].len = offset;
+                self.pieces.insert(
+                    i + 1,
+                    Piece {
+                        source: piece.source,
+                        start: piece.start + offset,
+                        len: piece.len - offset,
+                    },
+                );
+                return i + 1;
+            }
+            at += piece.len;
+        }
+        self.pieces.len()
+    }
+
+    fn buffer(&self, source: Source) -> &[u8] {
+        match source {
+            Source::Original => &self.original,
+            Source::Add => &self.add,
+        }
+    }
+}
+
+/// An iterator over the pieces of the document of a PieceTable.
+///
+/// This struct is created by [`PieceTable::pieces()`]. See its documentation
+/// for more.
+///
+/// [`PieceTable::pieces()`]: crate::containers::PieceTable::pieces
+#[derive(Debug, Clone)]
+pub struct Pieces<'a> {
+    table: &'a PieceTable,
+    iter: slice::Iter<'a, Piece>,
+}
+
+impl<'a> Iterator for Pieces<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let piece = self.iter.next()?;
+        let buffer = self.table.buffer(piece.source);
+        Some(&buffer[piece.start..piece.start + piece.len])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Pieces<'a> {}
+
+/// An iterator over the bytes of the document of a PieceTable.
+///
+/// This struct is created by [`PieceTable::bytes()`]. See its documentation
+/// for more.
+///
+/// [`PieceTable::bytes()`]: crate::containers::PieceTable::bytes
+#[derive(Debug, Clone)]
+pub struct Bytes<'a> {
+    pieces: Pieces<'a>,
+    bytes: slice::Iter<'a, u8>,
+}
+
+impl<'a> Iterator for Bytes<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&b) = self.bytes.next() {
+                return Some(b);
+            }
+            self.bytes = self.pieces.next()?.iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_vec_edits() {
+        let mut rng = SplitMix64::new(53);
+        let mut doc = PieceTable::new("the quick brown fox");
+        let mut versions = vec![doc.to_vec()];
+        for _ in 0..300 {
+            let mut text = versions.last().unwrap().clone();
+            let a = (rng.next_u64() % (text.len() as u64 + 1)) as usize;
+            match rng.next_u64() % 2 {
+                0 => {
+                    let len = rng.next_u64() % 4;
+                    let insert: Vec<u8> = (0..len)
+                        .map(|_| b'a' + (rng.next_u64() % 26) as u8)
+                        .collect();
+                    doc.insert(a, &insert);
+                    text.splice(a..a, insert);
+                }
+                _ => {
+                    let b = a
+                        + (rng.next_u64() % (text.len() - a + 1) as u64)
+                            as usize;
+                    doc.delete(a..b);
+                    text.drain(a..b);
+                }
+            }
+            assert_eq!(doc.len(), text.len());
+            assert_eq!(doc.to_vec(), text);
+            assert!(doc.pieces().all(|p| !p.is_empty()));
+            if versions.last() != Some(&text) {
+                versions.push(text);
+            }
+        }
+        // Every edit that changed the document is undone one by one.
+        while versions.len() > 1 {
+            assert!(doc.undo());
+            versions.pop();
+            assert_eq!(&doc.to_vec(), versions.last().unwrap());
+        }
+        assert!(!doc.undo());
+    }
+
+    #[test]
+    fn edit_clears_redo() {
+        let mut doc = PieceTable::default();
+        doc.insert(0, "ab");
+        doc.delete(0..1);
+        doc.undo();
+        doc.insert(0, "c");
+        assert!(!doc.redo());
+        assert_eq!(doc.to_vec(), b"cab");
+        doc.undo();
+        doc.undo();
+        assert!(doc.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn insert_out_of_bounds() {
+        PieceTable::new("ab").insert(3, "c");
+    }
+}
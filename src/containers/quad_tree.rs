@@ -0,0 +1,592 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::slice;
+
+/// The index of a missing node.
+const NIL: usize = usize::MAX;
+
+/// The number of points a leaf holds before it is split, by default.
+const DEFAULT_CAPACITY: usize = 8;
+
+/// The depth below which leaves are not split, by default.
+const DEFAULT_MAX_DEPTH: usize = 16;
+
+type Item<V> = ([f64; 2], V);
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    // The points of a leaf, empty for an inner node.
+    items: Vec<Item<V>>,
+    // The index of the first of the 4 consecutive children, or NIL for a
+    // leaf.
+    children: usize,
+    // The number of points in the subtree.
+    len: usize,
+}
+
+impl<V> Node<V> {
+    fn leaf() -> Self {
+        Self {
+            items: Vec::new(),
+            children: NIL,
+            len: 0,
+        }
+    }
+}
+
+/// Returns the quadrant of the box from `min` to `max` that holds the point:
+/// the first bit is set for the upper half of x, and the second bit for the
+/// upper half of y.
+fn quadrant(min: &[f64; 2], max: &[f64; 2], point: &[f64; 2]) -> usize {
+    let mut q = 0;
+    for j in 0..2 {
+        if point[j] >= (min[j] + max[j]) / 2.0 {
+            q |= 1 << j;
+        }
+    }
+    q
+}
+
+/// Returns the corners of the quadrant of the box from `min` to `max`.
+fn quadrant_bounds(
+    min: &[f64; 2],
+    max: &[f64; 2],
+    q: usize,
+) -> ([f64; 2], [f64; 2]) {
+    let (mut lo, mut hi) = (*min, *max);
+    for j in 0..2 {
+        let mid = (min[j] + max[j]) / 2.0;
+        if q & (1 << j) == 0 {
+            hi[j] = mid;
+        } else {
+            lo[j] = mid;
+        }
+    }
+    (lo, hi)
+}
+
+/// Returns true if the point is in the box from `min` to `max`, borders
+/// included.
+fn contains(min: &[f64; 2], max: &[f64; 2], point: &[f64; 2]) -> bool {
+    (0..2).all(|j| min[j] <= point[j] && point[j] <= max[j])
+}
+
+/// A tree of points in a rectangle, where each inner node splits its
+/// rectangle into 4 equal quadrants.
+///
+/// The points are kept in the leaves. A leaf that holds more points than the
+/// capacity of the tree is split into 4 leaves, one per quadrant, unless it
+/// is at the maximum depth, which bounds the height of the tree when many
+/// points are close or equal. When removing points leaves an inner node with
+/// no more points than the capacity, its subtree is merged back into a leaf.
+/// A range query only visits the nodes whose quadrant meets the queried
+/// rectangle.
+///
+/// The nodes are kept in a vector, with the 4 children of a node next to
+/// each other, and the children of merged nodes are reused by later splits.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::QuadTree;
+///
+/// let mut tree = QuadTree::new([0.0, 0.0], [100.0, 100.0]);
+/// tree.insert([10.0, 10.0], "a");
+/// tree.insert([60.0, 20.0], "b");
+/// tree.insert([55.0, 70.0], "c");
+///
+/// let mut inside: Vec<_> =
+///     tree.range(&[50.0, 0.0], &[100.0, 100.0]).map(|(_, v)| *v).collect();
+/// inside.sort();
+/// assert_eq!(inside, ["b", "c"]);
+/// assert_eq!(tree.remove(&[60.0, 20.0]), Some("b"));
+/// assert_eq!(tree.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuadTree<V> {
+    nodes: Vec<Node<V>>,
+    // The first nodes of the groups of 4 children that are not used.
+    free: Vec<usize>,
+    min: [f64; 2],
+    max: [f64; 2],
+    capacity: usize,
+    max_depth: usize,
+}
+
+impl<V> QuadTree<V> {
+    /// Creates an empty QuadTree over the rectangle from `min` to `max`,
+    /// borders included, with the default splitting thresholds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max` on a coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let tree: QuadTree<()> = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new(min: [f64; 2], max: [f64; 2]) -> Self {
+        Self::with_thresholds(min, max, DEFAULT_CAPACITY, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Creates an empty QuadTree over the rectangle from `min` to `max`,
+    /// borders included, whose leaves are split when they hold more than
+    /// `capacity` points and are less than `max_depth` deep.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, or if `min` is greater than `max` on a
+    /// coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::with_thresholds([0.0, 0.0], [1.0, 1.0], 1, 4);
+    /// tree.insert([0.2, 0.2], 'a');
+    /// tree.insert([0.8, 0.8], 'b');
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn with_thresholds(
+        min: [f64; 2],
+        max: [f64; 2],
+        capacity: usize,
+        max_depth: usize,
+    ) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        assert!(min[0] <= max[0] && min[1] <= max[1], "Invalid bounds");
+        Self {
+            nodes: vec![Node::leaf()],
+            free: Vec::new(),
+            min,
+            max,
+            capacity,
+            max_depth,
+        }
+    }
+
+    /// Returns the corners of the rectangle covered by the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let tree: QuadTree<()> = QuadTree::new([0.0, -1.0], [2.0, 1.0]);
+    /// assert_eq!(tree.bounds(), ([0.0, -1.0], [2.0, 1.0]));
+    /// ```
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        (self.min, self.max)
+    }
+
+    /// Returns the number of points in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// tree.insert([0.5, 0.5], ());
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes[0].len
+    }
+
+    /// Returns true if the tree has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// assert!(tree.is_empty());
+    /// tree.insert([0.5, 0.5], ());
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the points and their values, in no particular
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// tree.insert([0.1, 0.1], 'a');
+    /// tree.insert([0.9, 0.9], 'b');
+    /// let mut values: Vec<_> = tree.iter().map(|(_, v)| *v).collect();
+    /// values.sort();
+    /// assert_eq!(values, ['a', 'b']);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            nodes: self.nodes.iter(),
+            items: [].iter(),
+            len: self.len(),
+        }
+    }
+
+    /// Adds the point and its value to the tree. A point equal to one in the
+    /// tree is added again.
+    ///
+    /// This operation should compute in O(d + c) time, where d is the
+    /// maximum depth and c the capacity of the leaves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point is outside the rectangle of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// tree.insert([0.5, 0.5], "a");
+    /// tree.insert([0.5, 0.5], "b");
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn insert(&mut self, point: [f64; 2], value: V) {
+        assert!(
+            contains(&self.min, &self.max, &point),
+            "Out of bounds access"
+        );
+        let (mut min, mut max) = (self.min, self.max);
+        let mut i = 0;
+        let mut depth = 0;
+        loop {
+            self.nodes[i].len += 1;
+            let children = self.nodes[i].children;
+            if children == NIL {
+                break;
+            }
+            let q = quadrant(&min, &max, &point);
+            let (lo, hi) = quadrant_bounds(&min, &max, q);
+            min = lo;
+            max = hi;
+            i = children + q;
+            depth += 1;
+        }
+        self.nodes[i].items.push((point, value));
+        self.split(i, min, max, depth);
+    }
+
+    /// Removes a point equal to the given one from the tree and returns its
+    /// value, or None if there is no such point. If several points are
+    /// equal, which one is removed is unspecified.
+    ///
+    /// This operation should compute in O(d + c) time, where d is the
+    /// maximum depth and c the capacity of the leaves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new([0.0, 0.0], [1.0, 1.0]);
+    /// tree.insert([0.5, 0.5], "a");
+    /// assert_eq!(tree.remove(&[0.5, 0.5]), Some("a"));
+    /// assert_eq!(tree.remove(&[0.5, 0.5]), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove(&mut self, point: &[f64; 2]) -> Option<V> {
+        if !contains(&self.min, &self.max, point) {
+            return None;
+        }
+        let (mut min, mut max) = (self.min, self.max);
+        let mut path = Vec::new();
+        let mut i = 0;
+        while self.nodes[i].children != NIL {
+            path.push(i);
+            let q = quadrant(&min, &max, point);
+            let (lo, hi) = quadrant_bounds(&min, &max, q);
+            min = lo;
+            max = hi;
+            i = self.nodes[i].children + q;
+        }
+        let leaf = &mut self.nodes[i];
+        let at = leaf.items.iter().position(|(p, _)| p == point)?;
+        let (_, value) = leaf.items.swap_remove(at);
+        leaf.len -= 1;
+        for &j in &path {
+            self.nodes[j].len -= 1;
+        }
+        // Only the nodes on the path lost a point, and the highest one that
+        // no longer needs to be split holds the others.
+        if let Some(&j) =
+            path.iter().find(|&&j| self.nodes[j].len <= self.capacity)
+        {
+            self.merge(j);
+        }
+        Some(value)
+    }
+
+    /// Returns an iterator over the points in the rectangle from `min` to
+    /// `max`, borders included, and their values, in no particular order.
+    ///
+    /// This operation should compute in O(d + m) time for a small rectangle,
+    /// where d is the maximum depth and m the number of points in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::QuadTree;
+    ///
+    /// let mut tree = QuadTree::with_thresholds([0.0, 0.0], [4.0, 4.0], 2, 8);
+    /// for i in 0..16 {
+    ///     tree.insert([(i % 4) as f64, (i / 4) as f64], i);
+    /// }
+    /// let mut inside: Vec<_> =
+    ///     tree.range(&[1.0, 1.0], &[2.0, 2.0]).map(|(_, v)| *v).collect();
+    /// inside.sort();
+    /// assert_eq!(inside, [5, 6, 9, 10]);
+    /// ```
+    pub fn range<'a>(&'a self, min: &[f64; 2], max: &[f64; 2]) -> Range<'a, V> {
+        Range {
+            tree: self,
+            min: *min,
+            max: *max,
+            stack: vec![(0, self.min, self.max)],
+            items: [].iter(),
+        }
+    }
+
+    /// Returns the index of the first of 4 unused leaves.
+    fn alloc_children(&mut self) -> usize {
+        self.free.pop().unwrap_or_else(|| {
+            let children = self.nodes.len();
+            self.nodes.extend((0..4).map(|_| Node::leaf()));
+            children
+        })
+    }
+
+    /// Splits the leaf if it holds too many points, and then its children
+    /// that still do.
+    fn split(&mut self, i: usize, min: [f64; 2], max: [f64; 2], depth: usize) {
+        if self.nodes[i].items.len() <= self.capacity || depth >= self.max_depth
+        {
+            return;
+        }
+        let children = self.alloc_children();
+        let items = mem::take(&mut self.nodes[i].items);
+        self.nodes[i].children = children;
+        for (point, value) in items {
+            let child =
+                &mut self.nodes[children + quadrant(&min, &max, &point)];
+            child.items.push((point, value));
+            child.len += 1;
+        }
+        for q in 0..4 {
+            let (lo, hi) = quadrant_bounds(&min, &max, q);
+            self.split(children + q, lo, hi, depth + 1);
+        }
+    }
+
+    /// Turns the inner node into a leaf with all the points of its subtree.
+    fn merge(&mut self, i: usize) {
+        let mut items = Vec::with_capacity(self.nodes[i].len);
+        let mut stack = Vec::new();
+        stack.push(self.nodes[i].children);
+        self.nodes[i].children = NIL;
+        while let Some(children) = stack.pop() {
+            for child in &mut self.nodes[children..children + 4] {
+                items.append(&mut child.items);
+                if child.children != NIL {
+                    stack.push(child.children);
+                }
+                *child = Node::leaf();
+            }
+            self.free.push(children);
+        }
+        self.nodes[i].items = items;
+    }
+}
+
+impl<'a, V> IntoIterator for &'a QuadTree<V> {
+    type Item = (&'a [f64; 2], &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the points of a QuadTree and their values.
+///
+/// This struct is created by [`QuadTree::iter()`]. See its documentation for
+/// more.
+///
+/// [`QuadTree::iter()`]: crate::containers::QuadTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    nodes: slice::Iter<'a, Node<V>>,
+    items: slice::Iter<'a, Item<V>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a [f64; 2], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((point, value)) = self.items.next() {
+                self.len -= 1;
+                return Some((point, value));
+            }
+            self.items = self.nodes.next()?.items.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+/// An iterator over the points of a QuadTree inside a rectangle.
+///
+/// This struct is created by [`QuadTree::range()`]. See its documentation for
+/// more.
+///
+/// [`QuadTree::range()`]: crate::containers::QuadTree::range
+#[derive(Debug)]
+pub struct Range<'a, V> {
+    tree: &'a QuadTree<V>,
+    min: [f64; 2],
+    max: [f64; 2],
+    // The nodes that may still hold points inside the rectangle, with the
+    // corners of their quadrants.
+    stack: Vec<(usize, [f64; 2], [f64; 2])>,
+    items: slice::Iter<'a, Item<V>>,
+}
+
+impl<'a, V> Iterator for Range<'a, V> {
+    type Item = (&'a [f64; 2], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for (point, value) in &mut self.items {
+                if contains(&self.min, &self.max, point) {
+                    return Some((point, value));
+                }
+            }
+            let (i, min, max) = self.stack.pop()?;
+            let meets =
+                (0..2).all(|j| min[j] <= self.max[j] && self.min[j] <= max[j]);
+            if !meets {
+                continue;
+            }
+            let node = &self.tree.nodes[i];
+            if node.children == NIL {
+                self.items = node.items.iter();
+            } else {
+                for q in 0..4 {
+                    let (lo, hi) = quadrant_bounds(&min, &max, q);
+                    self.stack.push((node.children + q, lo, hi));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn random_point(rng: &mut SplitMix64) -> [f64; 2] {
+        // Few distinct coordinates, so that some points are equal.
+        let mut coordinate = || (rng.next_u64() % 64) as f64;
+        [coordinate(), coordinate()]
+    }
+
+    #[test]
+    fn operations_match_brute_force() {
+        let mut rng = SplitMix64::new(7);
+        let mut tree =
+            QuadTree::with_thresholds([0.0, 0.0], [63.0, 63.0], 4, 5);
+        let mut all: Vec<([f64; 2], u64)> = Vec::new();
+        for step in 0..3000 {
+            let point = random_point(&mut rng);
+            if rng.next_u64().is_multiple_of(3) {
+                let removed = tree.remove(&point);
+                match all.iter().position(|(p, _)| *p == point) {
+                    Some(_) => {
+                        let value = removed.unwrap();
+                        let at = all.iter().position(|e| *e == (point, value));
+                        all.swap_remove(at.unwrap());
+                    }
+                    None => assert_eq!(removed, None),
+                }
+            } else {
+                tree.insert(point, step);
+                all.push((point, step));
+            }
+            assert_eq!(tree.len(), all.len());
+
+            let other = random_point(&mut rng);
+            let min = [point[0].min(other[0]), point[1].min(other[1])];
+            let max = [point[0].max(other[0]), point[1].max(other[1])];
+            let mut inside: Vec<_> =
+                tree.range(&min, &max).map(|(_, v)| *v).collect();
+            inside.sort_unstable();
+            let mut expected: Vec<_> = all
+                .iter()
+                .filter(|(p, _)| contains(&min, &max, p))
+                .map(|(_, v)| *v)
+                .collect();
+            expected.sort_unstable();
+            assert_eq!(inside, expected);
+        }
+        let mut values: Vec<_> = tree.iter().map(|(_, v)| *v).collect();
+        values.sort_unstable();
+        let mut expected: Vec<_> = all.iter().map(|(_, v)| *v).collect();
+        expected.sort_unstable();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn merges_and_reuses_nodes() {
+        let mut tree = QuadTree::with_thresholds([0.0, 0.0], [1.0, 1.0], 1, 32);
+        let points: Vec<_> = (0..100)
+            .map(|i| [i as f64 / 100.0, i as f64 / 100.0])
+            .collect();
+        for &point in &points {
+            tree.insert(point, ());
+        }
+        let nodes = tree.nodes.len();
+        for point in &points {
+            assert_eq!(tree.remove(point), Some(()));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.nodes[0].children, NIL);
+        for &point in &points {
+            tree.insert(point, ());
+        }
+        assert_eq!(tree.nodes.len(), nodes);
+    }
+
+    #[test]
+    fn equal_points_stop_at_max_depth() {
+        let mut tree = QuadTree::with_thresholds([0.0, 0.0], [1.0, 1.0], 2, 3);
+        for i in 0..50 {
+            tree.insert([0.3, 0.3], i);
+        }
+        assert_eq!(tree.range(&[0.3, 0.3], &[0.3, 0.3]).count(), 50);
+        assert!(tree.nodes.len() <= 1 + 4 * 3);
+    }
+}
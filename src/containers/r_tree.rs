@@ -0,0 +1,921 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::slice;
+
+/// The largest number of entries in a node, by default.
+const DEFAULT_MAX_ENTRIES: usize = 8;
+
+type Item<V> = ([f64; 2], [f64; 2], V);
+
+/// The corners of a rectangle, borders included.
+type Rect = ([f64; 2], [f64; 2]);
+
+/// The rectangle that contains nothing, which is the identity of `union`.
+const EMPTY: Rect = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+
+fn union(a: &Rect, b: &Rect) -> Rect {
+    (
+        [a.0[0].min(b.0[0]), a.0[1].min(b.0[1])],
+        [a.1[0].max(b.1[0]), a.1[1].max(b.1[1])],
+    )
+}
+
+fn area(r: &Rect) -> f64 {
+    (r.1[0] - r.0[0]) * (r.1[1] - r.0[1])
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    (0..2).all(|j| a.0[j] <= b.1[j] && b.0[j] <= a.1[j])
+}
+
+fn covers(a: &Rect, b: &Rect) -> bool {
+    (0..2).all(|j| a.0[j] <= b.0[j] && b.1[j] <= a.1[j])
+}
+
+/// Returns the squared distance from the point to the closest point of the
+/// rectangle.
+fn distance2(r: &Rect, point: &[f64; 2]) -> f64 {
+    (0..2)
+        .map(|j| {
+            let d = (r.0[j] - point[j]).max(point[j] - r.1[j]).max(0.0);
+            d * d
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    rect: Rect,
+    // The entries of a leaf, empty for an inner node.
+    items: Vec<Item<V>>,
+    // The children of an inner node, empty for a leaf.
+    children: Vec<usize>,
+    leaf: bool,
+}
+
+impl<V> Node<V> {
+    fn new(leaf: bool) -> Self {
+        Self {
+            rect: EMPTY,
+            items: Vec::new(),
+            children: Vec::new(),
+            leaf,
+        }
+    }
+
+    fn len(&self) -> usize {
+        if self.leaf {
+            self.items.len()
+        } else {
+            self.children.len()
+        }
+    }
+}
+
+/// Splits the rectangles into two groups with Guttman's quadratic method, and
+/// returns for each rectangle whether it goes to the second group.
+///
+/// The two rectangles that would waste the most area together start the
+/// groups. Then the rectangle whose enlargement of the groups differs the
+/// most goes to the group it enlarges the least, until a group needs all the
+/// remaining rectangles to have `min_entries`.
+fn quadratic_split(rects: &[Rect], min_entries: usize) -> Vec<bool> {
+    let n = rects.len();
+    let (mut seed_a, mut seed_b) = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..n {
+        for j in i + 1..n {
+            let waste = area(&union(&rects[i], &rects[j]))
+                - area(&rects[i])
+                - area(&rects[j]);
+            if waste > worst {
+                worst = waste;
+                seed_a = i;
+                seed_b = j;
+            }
+        }
+    }
+    let mut group: Vec<Option<bool>> = (0..n).map(|_| None).collect();
+    group[seed_a] = Some(false);
+    group[seed_b] = Some(true);
+    let mut rect = [rects[seed_a], rects[seed_b]];
+    let mut len = [1, 1];
+    let mut remaining = n - 2;
+    while remaining > 0 {
+        if let Some(g) = (0..2).find(|&g| len[g] + remaining == min_entries) {
+            for slot in group.iter_mut().filter(|slot| slot.is_none()) {
+                *slot = Some(g == 1);
+            }
+            break;
+        }
+        let mut pick = 0;
+        let mut preference = f64::NEG_INFINITY;
+        let mut growth = [0.0; 2];
+        for i in (0..n).filter(|&i| group[i].is_none()) {
+            let d = [0, 1]
+                .map(|g| area(&union(&rect[g], &rects[i])) - area(&rect[g]));
+            if (d[0] - d[1]).abs() > preference {
+                preference = (d[0] - d[1]).abs();
+                pick = i;
+                growth = d;
+            }
+        }
+        let g = if growth[0] != growth[1] {
+            usize::from(growth[1] < growth[0])
+        } else if area(&rect[0]) != area(&rect[1]) {
+            usize::from(area(&rect[1]) < area(&rect[0]))
+        } else {
+            usize::from(len[1] < len[0])
+        };
+        group[pick] = Some(g == 1);
+        rect[g] = union(&rect[g], &rects[pick]);
+        len[g] += 1;
+        remaining -= 1;
+    }
+    group.into_iter().map(Option::unwrap).collect()
+}
+
+/// Groups the entries into nodes of at most `max_entries` with the
+/// Sort-Tile-Recursive method: the entries are sorted by the x of their
+/// centers and cut into vertical slices, and each slice is sorted by the y
+/// of their centers and cut into nodes.
+fn sort_tile<T>(
+    mut entries: Vec<T>,
+    max_entries: usize,
+    rect: impl Fn(&T) -> Rect,
+) -> Vec<Vec<T>> {
+    let nodes = entries.len().div_ceil(max_entries);
+    let mut slices = 1;
+    while slices * slices < nodes {
+        slices += 1;
+    }
+    let center = |entry: &T, j: usize| {
+        let r = rect(entry);
+        r.0[j] + r.1[j]
+    };
+    entries.sort_by(|a, b| center(a, 0).total_cmp(&center(b, 0)));
+    let mut groups = Vec::with_capacity(nodes);
+    let mut entries = entries.into_iter().peekable();
+    while entries.peek().is_some() {
+        let mut slice: Vec<T> =
+            entries.by_ref().take(slices * max_entries).collect();
+        slice.sort_by(|a, b| center(a, 1).total_cmp(&center(b, 1)));
+        let mut slice = slice.into_iter().peekable();
+        while slice.peek().is_some() {
+            groups.push(slice.by_ref().take(max_entries).collect());
+        }
+    }
+    groups
+}
+
+/// A balanced tree of rectangles, where each node holds the smallest
+/// rectangle that contains the rectangles of its entries.
+///
+/// All the leaves are at the same depth, and every node but the root has at
+/// most the maximum number of entries, and at least 40% of it unless the
+/// tree was bulk loaded. A rectangle is inserted in the leaf whose rectangle
+/// it enlarges the least, and a node that overflows is split in two with
+/// Guttman's quadratic split, which may split its ancestors in turn. A node
+/// that underflows after a removal is taken out of the tree and its
+/// rectangles are inserted again. A query only visits the nodes whose
+/// rectangle meets the queried region, and [`RTree::bulk_load()`] builds a
+/// tree with little overlap between its nodes from all its rectangles at
+/// once.
+///
+/// The nodes are kept in a vector, and the nodes that are taken out are
+/// reused.
+///
+/// [`RTree::bulk_load()`]: crate::containers::RTree::bulk_load
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::RTree;
+///
+/// let mut tree = RTree::new();
+/// tree.insert([0.0, 0.0], [2.0, 1.0], "park");
+/// tree.insert([5.0, 5.0], [6.0, 8.0], "lake");
+/// tree.insert([1.0, 3.0], [1.0, 3.0], "well");
+///
+/// let found: Vec<_> =
+///     tree.search(&[0.5, 0.5], &[4.0, 4.0]).map(|(_, _, v)| *v).collect();
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(tree.nearest(&[6.0, 4.0]).map(|(_, _, v)| *v), Some("lake"));
+/// assert_eq!(tree.remove(&[5.0, 5.0], &[6.0, 8.0]), Some("lake"));
+/// assert_eq!(tree.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RTree<V> {
+    nodes: Vec<Node<V>>,
+    // The nodes that are not used.
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+    max_entries: usize,
+    min_entries: usize,
+}
+
+impl<V> Default for RTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> RTree<V> {
+    /// Creates an empty RTree whose nodes hold at most 8 entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let tree: RTree<()> = RTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Creates an empty RTree whose nodes hold at most `max_entries`
+    /// entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_entries` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::with_max_entries(2);
+    /// for i in 0..10 {
+    ///     tree.insert([i as f64, 0.0], [i as f64, 1.0], i);
+    /// }
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        assert!(max_entries >= 2, "Capacity must be at least 2");
+        Self {
+            nodes: vec![Node::new(true)],
+            free: Vec::new(),
+            root: 0,
+            len: 0,
+            max_entries,
+            min_entries: (max_entries * 2 / 5).max(1),
+        }
+    }
+
+    /// Creates an RTree whose nodes hold at most 8 entries from the
+    /// rectangles, given by their corners, and their values, with the
+    /// Sort-Tile-Recursive method.
+    ///
+    /// This operation should compute in O(n log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let items: Vec<_> = (0..100)
+    ///     .map(|i| {
+    ///         let (x, y) = ((i % 10) as f64, (i / 10) as f64);
+    ///         ([x, y], [x + 0.5, y + 0.5], i)
+    ///     })
+    ///     .collect();
+    /// let tree = RTree::bulk_load(items);
+    /// assert_eq!(tree.len(), 100);
+    /// assert_eq!(tree.search(&[2.0, 2.0], &[3.0, 3.0]).count(), 4);
+    /// ```
+    pub fn bulk_load(items: Vec<([f64; 2], [f64; 2], V)>) -> Self {
+        let mut tree = Self::new();
+        if items.is_empty() {
+            return tree;
+        }
+        tree.len = items.len();
+        tree.nodes.clear();
+        let mut level: Vec<usize> =
+            sort_tile(items, tree.max_entries, |item| (item.0, item.1))
+                .into_iter()
+                .map(|items| {
+                    let mut node = Node::new(true);
+                    node.items = items;
+                    let i = tree.push_node(node);
+                    tree.update_rect(i);
+                    i
+                })
+                .collect();
+        while level.len() > 1 {
+            let nodes = &tree.nodes;
+            level = sort_tile(level, tree.max_entries, |&i| nodes[i].rect)
+                .into_iter()
+                .map(|children| {
+                    let mut node = Node::new(false);
+                    node.children = children;
+                    let i = tree.push_node(node);
+                    tree.update_rect(i);
+                    i
+                })
+                .collect();
+        }
+        tree.root = level[0];
+        tree
+    }
+
+    /// Returns the number of rectangles in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], ());
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no rectangles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], ());
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the corners of the rectangles and their
+    /// values, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], 'a');
+    /// tree.insert([2.0, 2.0], [3.0, 3.0], 'b');
+    /// let mut values: Vec<_> = tree.iter().map(|(_, _, v)| *v).collect();
+    /// values.sort();
+    /// assert_eq!(values, ['a', 'b']);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            nodes: self.nodes.iter(),
+            items: [].iter(),
+            len: self.len,
+        }
+    }
+
+    /// Adds the rectangle from `min` to `max` and its value to the tree. A
+    /// rectangle equal to one in the tree is added again.
+    ///
+    /// This operation should compute in O(M log(n)) time, where M is the
+    /// maximum number of entries of a node, plus O(M^2) for each split.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], "a");
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], "b");
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn insert(&mut self, min: [f64; 2], max: [f64; 2], value: V) {
+        let rect = (min, max);
+        let mut path = Vec::new();
+        let mut i = self.root;
+        while !self.nodes[i].leaf {
+            path.push(i);
+            let nodes = &self.nodes;
+            i = *nodes[i]
+                .children
+                .iter()
+                .min_by(|&&a, &&b| {
+                    let cost = |c: usize| {
+                        let r = &nodes[c].rect;
+                        (area(&union(r, &rect)) - area(r), area(r))
+                    };
+                    let (a, b) = (cost(a), cost(b));
+                    a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+                })
+                .unwrap();
+        }
+        self.nodes[i].items.push((min, max, value));
+        self.nodes[i].rect = union(&self.nodes[i].rect, &rect);
+        self.len += 1;
+
+        // The sibling from splitting the last node, to add to its parent.
+        let mut sibling = self.split(i);
+        while let Some(parent) = path.pop() {
+            self.nodes[parent].rect = union(&self.nodes[parent].rect, &rect);
+            if let Some(s) = sibling {
+                self.nodes[parent].children.push(s);
+                sibling = self.split(parent);
+            }
+        }
+        if let Some(s) = sibling {
+            let mut root = Node::new(false);
+            root.children = vec![self.root, s];
+            self.root = self.push_node(root);
+            self.update_rect(self.root);
+        }
+    }
+
+    /// Removes a rectangle from `min` to `max` from the tree and returns its
+    /// value, or None if there is no such rectangle. If several rectangles
+    /// are equal, which one is removed is unspecified.
+    ///
+    /// This operation should compute in O(n) time in the worst case, and
+    /// visits only the nodes whose rectangle contains the given one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], "a");
+    /// assert_eq!(tree.remove(&[0.0, 0.0], &[1.0, 1.0]), Some("a"));
+    /// assert_eq!(tree.remove(&[0.0, 0.0], &[1.0, 1.0]), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove(&mut self, min: &[f64; 2], max: &[f64; 2]) -> Option<V> {
+        let rect = (*min, *max);
+        let mut path = Vec::new();
+        let at = self.find(self.root, &rect, &mut path)?;
+        let leaf = path.pop().unwrap();
+        let (_, _, value) = self.nodes[leaf].items.swap_remove(at);
+        self.len -= 1;
+
+        // The nodes on the path that underflow are taken out, and the
+        // rectangles under them are inserted again.
+        let mut orphans = Vec::new();
+        let mut child = leaf;
+        while let Some(parent) = path.pop() {
+            if self.nodes[child].len() < self.min_entries {
+                let children = &mut self.nodes[parent].children;
+                let at = children.iter().position(|&c| c == child).unwrap();
+                children.swap_remove(at);
+                self.take_subtree(child, &mut orphans);
+            } else {
+                self.update_rect(child);
+            }
+            child = parent;
+        }
+        self.update_rect(self.root);
+        while !self.nodes[self.root].leaf && self.nodes[self.root].len() <= 1 {
+            let root = self.root;
+            match self.nodes[root].children.pop() {
+                Some(child) => self.root = child,
+                None => self.nodes[root].leaf = true,
+            }
+            if self.root != root {
+                self.free_node(root);
+            }
+        }
+        self.len -= orphans.len();
+        for (min, max, value) in orphans {
+            self.insert(min, max, value);
+        }
+        Some(value)
+    }
+
+    /// Returns an iterator over the rectangles that overlap the rectangle
+    /// from `min` to `max`, borders included, with their values, in no
+    /// particular order.
+    ///
+    /// This operation should compute in O(log(n) + m) time for a small
+    /// region in a tree whose nodes overlap little, where m is the number of
+    /// rectangles found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [2.0, 2.0], 'a');
+    /// tree.insert([3.0, 3.0], [4.0, 4.0], 'b');
+    /// let found: Vec<_> =
+    ///     tree.search(&[2.0, 1.0], &[2.5, 5.0]).map(|(_, _, v)| *v).collect();
+    /// assert_eq!(found, ['a']);
+    /// ```
+    pub fn search<'a>(
+        &'a self,
+        min: &[f64; 2],
+        max: &[f64; 2],
+    ) -> Search<'a, V> {
+        Search {
+            tree: self,
+            rect: (*min, *max),
+            stack: vec![self.root],
+            items: [].iter(),
+        }
+    }
+
+    /// Returns the rectangle closest to the point and its value, or None if
+    /// the tree is empty. A rectangle that contains the point is at distance
+    /// 0, and ties are broken arbitrarily.
+    ///
+    /// This operation should compute in O(log(n)) expected time in a tree
+    /// whose nodes overlap little, and O(n) time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// tree.insert([0.0, 0.0], [1.0, 1.0], 'a');
+    /// tree.insert([4.0, 0.0], [5.0, 1.0], 'b');
+    /// assert_eq!(tree.nearest(&[3.0, 3.0]).map(|(_, _, v)| *v), Some('b'));
+    /// ```
+    pub fn nearest(
+        &self,
+        point: &[f64; 2],
+    ) -> Option<(&[f64; 2], &[f64; 2], &V)> {
+        let mut best = None;
+        let mut best_distance = f64::INFINITY;
+        self.search_nearest(self.root, point, &mut best, &mut best_distance);
+        best.map(|(min, max, value)| (min, max, value))
+    }
+
+    /// Keeps the closest entry of the subtree in `best` if it is closer than
+    /// `best_distance`.
+    fn search_nearest<'a>(
+        &'a self,
+        i: usize,
+        point: &[f64; 2],
+        best: &mut Option<&'a Item<V>>,
+        best_distance: &mut f64,
+    ) {
+        let node = &self.nodes[i];
+        if node.leaf {
+            for item in &node.items {
+                let d = distance2(&(item.0, item.1), point);
+                if d < *best_distance {
+                    *best_distance = d;
+                    *best = Some(item);
+                }
+            }
+            return;
+        }
+        let mut children: Vec<(f64, usize)> = node
+            .children
+            .iter()
+            .map(|&c| (distance2(&self.nodes[c].rect, point), c))
+            .collect();
+        children.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (d, c) in children {
+            if d >= *best_distance {
+                break;
+            }
+            self.search_nearest(c, point, best, best_distance);
+        }
+    }
+
+    /// Returns the index of an entry equal to the rectangle in the subtree,
+    /// and pushes the nodes from the subtree to its leaf on the path.
+    fn find(
+        &self,
+        i: usize,
+        rect: &Rect,
+        path: &mut Vec<usize>,
+    ) -> Option<usize> {
+        let node = &self.nodes[i];
+        path.push(i);
+        if node.leaf {
+            let at =
+                node.items.iter().position(|item| (item.0, item.1) == *rect);
+            if at.is_some() {
+                return at;
+            }
+        } else {
+            for &c in &node.children {
+                if covers(&self.nodes[c].rect, rect) {
+                    if let Some(at) = self.find(c, rect, path) {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        path.pop();
+        None
+    }
+
+    /// Splits the node if it has too many entries, and returns the new node
+    /// that holds part of them.
+    fn split(&mut self, i: usize) -> Option<usize> {
+        let node = &mut self.nodes[i];
+        if node.len() <= self.max_entries {
+            return None;
+        }
+        let mut sibling = Node::new(node.leaf);
+        if node.leaf {
+            let rects: Vec<Rect> =
+                node.items.iter().map(|item| (item.0, item.1)).collect();
+            let group = quadratic_split(&rects, self.min_entries);
+            let items = mem::take(&mut node.items);
+            for (item, second) in items.into_iter().zip(group) {
+                if second {
+                    sibling.items.push(item);
+                } else {
+                    node.items.push(item);
+                }
+            }
+        } else {
+            let children = mem::take(&mut node.children);
+            let rects: Vec<Rect> =
+                children.iter().map(|&c| self.nodes[c].rect).collect();
+            let group = quadratic_split(&rects, self.min_entries);
+            for (c, second) in children.into_iter().zip(group) {
+                if second {
+                    sibling.children.push(c);
+                } else {
+                    self.nodes[i].children.push(c);
+                }
+            }
+        }
+        self.update_rect(i);
+        let sibling = self.push_node(sibling);
+        self.update_rect(sibling);
+        Some(sibling)
+    }
+
+    /// Sets the rectangle of the node to the smallest one that contains its
+    /// entries.
+    fn update_rect(&mut self, i: usize) {
+        let node = &self.nodes[i];
+        let rect = if node.leaf {
+            node.items
+                .iter()
+                .fold(EMPTY, |r, item| union(&r, &(item.0, item.1)))
+        } else {
+            node.children
+                .iter()
+                .fold(EMPTY, |r, &c| union(&r, &self.nodes[c].rect))
+        };
+        self.nodes[i].rect = rect;
+    }
+
+    /// Frees the nodes of the subtree, and moves its entries to `items`.
+    fn take_subtree(&mut self, i: usize, items: &mut Vec<Item<V>>) {
+        let mut stack = vec![i];
+        while let Some(i) = stack.pop() {
+            let node = &mut self.nodes[i];
+            items.append(&mut node.items);
+            stack.append(&mut node.children);
+            self.free_node(i);
+        }
+    }
+
+    fn push_node(&mut self, node: Node<V>) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn free_node(&mut self, i: usize) {
+        self.nodes[i] = Node::new(true);
+        self.free.push(i);
+    }
+}
+
+impl<'a, V> IntoIterator for &'a RTree<V> {
+    type Item = (&'a [f64; 2], &'a [f64; 2], &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the rectangles of an RTree and their values.
+///
+/// This struct is created by [`RTree::iter()`]. See its documentation for
+/// more.
+///
+/// [`RTree::iter()`]: crate::containers::RTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    nodes: slice::Iter<'a, Node<V>>,
+    items: slice::Iter<'a, Item<V>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a [f64; 2], &'a [f64; 2], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((min, max, value)) = self.items.next() {
+                self.len -= 1;
+                return Some((min, max, value));
+            }
+            self.items = self.nodes.next()?.items.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+/// An iterator over the rectangles of an RTree that overlap a region.
+///
+/// This struct is created by [`RTree::search()`]. See its documentation for
+/// more.
+///
+/// [`RTree::search()`]: crate::containers::RTree::search
+#[derive(Debug)]
+pub struct Search<'a, V> {
+    tree: &'a RTree<V>,
+    rect: Rect,
+    // The nodes whose rectangle overlaps the region and that are not
+    // visited yet.
+    stack: Vec<usize>,
+    items: slice::Iter<'a, Item<V>>,
+}
+
+impl<'a, V> Iterator for Search<'a, V> {
+    type Item = (&'a [f64; 2], &'a [f64; 2], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for (min, max, value) in &mut self.items {
+                if overlaps(&(*min, *max), &self.rect) {
+                    return Some((min, max, value));
+                }
+            }
+            let node = &self.tree.nodes[self.stack.pop()?];
+            if node.leaf {
+                self.items = node.items.iter();
+            } else {
+                let nodes = &self.tree.nodes;
+                let rect = &self.rect;
+                self.stack.extend(
+                    node.children
+                        .iter()
+                        .filter(|&&c| overlaps(&nodes[c].rect, rect)),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn random_rect(rng: &mut SplitMix64) -> Rect {
+        let mut coordinate = || (rng.next_u64() % 100) as f64;
+        let (x, y) = (coordinate(), coordinate());
+        let (w, h) = (coordinate() / 10.0, coordinate() / 10.0);
+        ([x, y], [x + w, y + h])
+    }
+
+    /// Checks that the leaves are at the same depth, that the rectangles of
+    /// the nodes are the smallest ones, and that the nodes are not too full.
+    fn assert_valid<V>(tree: &RTree<V>) {
+        fn depth<V>(tree: &RTree<V>, i: usize, root: bool) -> usize {
+            let node = &tree.nodes[i];
+            assert!(node.len() <= tree.max_entries);
+            assert!(root || node.len() > 0);
+            let mut expected = EMPTY;
+            let mut depths = Vec::new();
+            if node.leaf {
+                for item in &node.items {
+                    expected = union(&expected, &(item.0, item.1));
+                }
+                depths.push(0);
+            } else {
+                for &c in &node.children {
+                    expected = union(&expected, &tree.nodes[c].rect);
+                    depths.push(depth(tree, c, false) + 1);
+                }
+            }
+            assert_eq!(node.rect, expected);
+            depths.dedup();
+            assert!(depths.len() <= 1);
+            depths.first().copied().unwrap_or(0)
+        }
+        depth(tree, tree.root, true);
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    fn check_queries(
+        tree: &RTree<u64>,
+        all: &[Item<u64>],
+        rng: &mut SplitMix64,
+    ) {
+        let query = random_rect(rng);
+        let mut found: Vec<_> = tree
+            .search(&query.0, &query.1)
+            .map(|(_, _, v)| *v)
+            .collect();
+        found.sort_unstable();
+        let mut expected: Vec<_> = all
+            .iter()
+            .filter(|item| overlaps(&(item.0, item.1), &query))
+            .map(|item| item.2)
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(found, expected);
+
+        let point = query.0;
+        let closest = all
+            .iter()
+            .map(|item| distance2(&(item.0, item.1), &point))
+            .min_by(f64::total_cmp);
+        let nearest = tree
+            .nearest(&point)
+            .map(|(min, max, _)| distance2(&(*min, *max), &point));
+        assert_eq!(nearest, closest);
+    }
+
+    #[test]
+    fn operations_match_brute_force() {
+        let mut rng = SplitMix64::new(11);
+        let mut tree = RTree::with_max_entries(4);
+        let mut all: Vec<Item<u64>> = Vec::new();
+        for step in 0..2000 {
+            if !all.is_empty() && rng.next_u64().is_multiple_of(3) {
+                let at = (rng.next_u64() % all.len() as u64) as usize;
+                let (min, max, _) = all[at];
+                let value = tree.remove(&min, &max).unwrap();
+                let at = all.iter().position(|e| *e == (min, max, value));
+                all.swap_remove(at.unwrap());
+                let missing = random_rect(&mut rng);
+                if !all.iter().any(|e| (e.0, e.1) == missing) {
+                    assert_eq!(tree.remove(&missing.0, &missing.1), None);
+                }
+            } else {
+                let (min, max) = random_rect(&mut rng);
+                tree.insert(min, max, step);
+                all.push((min, max, step));
+            }
+            assert_eq!(tree.len(), all.len());
+            check_queries(&tree, &all, &mut rng);
+        }
+        assert_valid(&tree);
+    }
+
+    #[test]
+    fn bulk_load_matches_brute_force() {
+        let mut rng = SplitMix64::new(12);
+        for n in [0, 1, 7, 8, 9, 100, 1000] {
+            let all: Vec<Item<u64>> = (0..n)
+                .map(|i| {
+                    let (min, max) = random_rect(&mut rng);
+                    (min, max, i)
+                })
+                .collect();
+            let mut tree = RTree::bulk_load(all.clone());
+            assert_valid(&tree);
+            for _ in 0..20 {
+                check_queries(&tree, &all, &mut rng);
+            }
+            tree.insert([0.0, 0.0], [1.0, 1.0], n);
+            assert_valid(&tree);
+        }
+    }
+
+    #[test]
+    fn removes_everything() {
+        let mut tree = RTree::with_max_entries(3);
+        for i in 0..200 {
+            let x = i as f64;
+            tree.insert([x, x], [x + 1.0, x + 1.0], i);
+        }
+        for i in (0..200).rev() {
+            let x = i as f64;
+            assert_eq!(tree.remove(&[x, x], &[x + 1.0, x + 1.0]), Some(i));
+            assert_valid(&tree);
+        }
+        assert!(tree.is_empty());
+        assert!(tree.nodes[tree.root].leaf);
+    }
+}
@@ -0,0 +1,694 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+struct Node<V> {
+    // The bytes on the edge from the parent to this node, empty for the root.
+    label: Vec<u8>,
+    value: Option<V>,
+    // The number of keys ending at this node or below it.
+    count: usize,
+    // The children, sorted by the first bytes of their labels, which are
+    // distinct.
+    children: Vec<Box<Node<V>>>,
+}
+
+impl<V> Node<V> {
+    fn child(&self, b: u8) -> Result<usize, usize> {
+        self.children
+            .binary_search_by_key(&b, |child| child.label[0])
+    }
+
+    /// Merges the only child into this node, which has no value.
+    fn merge_child(&mut self) {
+        let child = *self.children.pop().unwrap();
+        self.label.extend_from_slice(&child.label);
+        self.value = child.value;
+        self.children = child.children;
+    }
+}
+
+/// A map from byte strings to values, implemented with a compressed prefix
+/// tree, also known as a Patricia trie.
+///
+/// Like a [`Trie`], but the chains of nodes with a single child and no value
+/// are merged into one edge, labelled with all their bytes. Every node then
+/// either holds a value or has at least two children, so there are fewer
+/// nodes than twice the number of keys, however long the keys are. The
+/// children of a node are kept in a sorted vector of at most 256 entries,
+/// one for each byte that may start a label.
+///
+/// Inserting a key that ends in the middle of a label splits the edge in
+/// two, and removing a key merges the edges back.
+///
+/// [`Trie`]: crate::containers::Trie
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::RadixTrie;
+///
+/// let mut routes = RadixTrie::new();
+/// routes.insert("10.0.0.0", "lan");
+/// routes.insert("10.0.1.0", "dmz");
+/// routes.insert("10.1.0.0", "vpn");
+///
+/// assert_eq!(routes.get("10.0.1.0"), Some(&"dmz"));
+/// assert_eq!(routes.count_prefix("10.0."), 2);
+/// let found: Vec<_> = routes.iter_prefix("10.0").map(|(_, v)| *v).collect();
+/// assert_eq!(found, ["lan", "dmz"]);
+/// ```
+pub struct RadixTrie<V> {
+    root: Box<Node<V>>,
+}
+
+impl<V> Default for RadixTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for RadixTrie<V> {
+    /// Many keys extending each other make a deep tree, so the nodes are
+    /// freed one by one instead of recursively.
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.root.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for RadixTrie<V> {
+    /// Formats the keys as strings, replacing the bytes that are not valid
+    /// UTF-8.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter().map(|(k, v)| {
+                    (String::from_utf8_lossy(&k).into_owned(), v)
+                }),
+            )
+            .finish()
+    }
+}
+
+impl<V> RadixTrie<V> {
+    /// Creates an empty RadixTrie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("a", 1);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            root: Box::new(Node {
+                label: Vec::new(),
+                value: None,
+                count: 0,
+                children: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns the number of keys in the trie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("a", 1);
+    /// trie.insert("ab", 2);
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    /// Returns true if the trie has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// assert!(trie.is_empty());
+    /// trie.insert("a", 1);
+    /// assert!(!trie.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.count == 0
+    }
+
+    /// Inserts a key-value pair into the trie and returns the old value of the
+    /// key, or None if the key was not in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// assert_eq!(trie.insert("abc", 1), None);
+    /// assert_eq!(trie.insert("ab", 2), None);
+    /// assert_eq!(trie.insert("abc", 3), Some(1));
+    /// ```
+    pub fn insert<K>(&mut self, key: &K, value: V) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut rest = key.as_ref();
+        if let Some(old) = self.get_mut(rest) {
+            return Some(mem::replace(old, value));
+        }
+        let mut node = &mut *self.root;
+        loop {
+            node.count += 1;
+            if rest.is_empty() {
+                node.value = Some(value);
+                return None;
+            }
+            let i = match node.child(rest[0]) {
+                Ok(i) => i,
+                Err(i) => {
+                    let leaf = Node {
+                        label: rest.to_vec(),
+                        value: Some(value),
+                        count: 1,
+                        children: Vec::new(),
+                    };
+                    node.children.insert(i, Box::new(leaf));
+                    return None;
+                }
+            };
+            let child = &mut node.children[i];
+            let common = common_prefix(&child.label, rest);
+            if common < child.label.len() {
+                // The key leaves the label in the middle, so the child keeps
+                // the common part and everything else moves to a new node
+                // below it.
+                let lower = Node {
+                    label: child.label.split_off(common),
+                    value: child.value.take(),
+                    count: child.count,
+                    children: mem::take(&mut child.children),
+                };
+                child.children = vec![Box::new(lower)];
+            }
+            rest = &rest[common..];
+            node = child;
+        }
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("ab", 1);
+    /// assert_eq!(trie.get("ab"), Some(&1));
+    /// assert_eq!(trie.get("a"), None);
+    /// ```
+    pub fn get<K>(&self, key: &K) -> Option<&V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let (node, extra) = self.find_prefix(key.as_ref())?;
+        if extra.is_empty() {
+            node.value.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("ab", 1);
+    /// *trie.get_mut("ab").unwrap() += 1;
+    /// assert_eq!(trie.get("ab"), Some(&2));
+    /// ```
+    pub fn get_mut<K>(&mut self, key: &K) -> Option<&mut V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut rest = key.as_ref();
+        let mut node = &mut *self.root;
+        while !rest.is_empty() {
+            let i = node.child(rest[0]).ok()?;
+            node = &mut node.children[i];
+            rest = rest.strip_prefix(&node.label[..])?;
+        }
+        node.value.as_mut()
+    }
+
+    /// Returns true if the key is in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("ab", 1);
+    /// assert!(trie.contains_key("ab"));
+    /// assert!(!trie.contains_key("a"));
+    /// ```
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key from the trie and returns its value, or None if the key
+    /// was not in the trie. The edges that the key no longer separates are
+    /// merged back.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("ab", 1);
+    /// assert_eq!(trie.remove("ab"), Some(1));
+    /// assert_eq!(trie.remove("ab"), None);
+    /// ```
+    pub fn remove<K>(&mut self, key: &K) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        // The path to the node of the key, as the positions of the children.
+        let mut path = Vec::new();
+        let mut rest = key.as_ref();
+        let mut node = &*self.root;
+        while !rest.is_empty() {
+            let i = node.child(rest[0]).ok()?;
+            node = &node.children[i];
+            rest = rest.strip_prefix(&node.label[..])?;
+            path.push(i);
+        }
+        node.value.as_ref()?;
+
+        let (&last, ancestors) = match path.split_last() {
+            Some(split) => split,
+            None => {
+                self.root.count -= 1;
+                return self.root.value.take();
+            }
+        };
+        let mut parent = &mut *self.root;
+        for &i in ancestors {
+            parent.count -= 1;
+            parent = &mut parent.children[i];
+        }
+        parent.count -= 1;
+        let target = &mut parent.children[last];
+        target.count -= 1;
+        let value = target.value.take();
+        match target.children.len() {
+            0 => {
+                parent.children.remove(last);
+                let is_root = ancestors.is_empty();
+                if !is_root
+                    && parent.value.is_none()
+                    && parent.children.len() == 1
+                {
+                    parent.merge_child();
+                }
+            }
+            1 => target.merge_child(),
+            _ => {}
+        }
+        value
+    }
+
+    /// Returns the number of keys that start with the prefix, including the
+    /// prefix itself if it is a key.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut trie = RadixTrie::new();
+    /// trie.insert("car", 1);
+    /// trie.insert("cart", 2);
+    /// trie.insert("cat", 3);
+    /// assert_eq!(trie.count_prefix("ca"), 3);
+    /// assert_eq!(trie.count_prefix("car"), 2);
+    /// assert_eq!(trie.count_prefix("dog"), 0);
+    /// ```
+    pub fn count_prefix<K>(&self, prefix: &K) -> usize
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.find_prefix(prefix.as_ref())
+            .map_or(0, |(node, _)| node.count)
+    }
+
+    /// Returns the longest key that is a prefix of the given key, as a slice
+    /// of the given key, with its value, or None if no key is a prefix of it.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let mut routes = RadixTrie::new();
+    /// routes.insert("/", "root");
+    /// routes.insert("/api", "api");
+    /// routes.insert("/api/users", "users");
+    ///
+    /// let (path, route) = routes.longest_prefix_match("/api/posts").unwrap();
+    /// assert_eq!((path, *route), (&b"/api"[..], "api"));
+    /// assert_eq!(routes.longest_prefix_match("api"), None);
+    /// ```
+    pub fn longest_prefix_match<'k, K>(
+        &self,
+        key: &'k K,
+    ) -> Option<(&'k [u8], &V)>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        let mut node = &*self.root;
+        let mut best = node.value.as_ref().map(|v| (0, v));
+        let mut matched = 0;
+        while matched < key.len() {
+            let i = match node.child(key[matched]) {
+                Ok(i) => i,
+                Err(_) => break,
+            };
+            node = &node.children[i];
+            if !key[matched..].starts_with(&node.label) {
+                break;
+            }
+            matched += node.label.len();
+            if let Some(value) = &node.value {
+                best = Some((matched, value));
+            }
+        }
+        best.map(|(len, value)| (&key[..len], value))
+    }
+
+    /// Returns an iterator visiting the keys that start with the prefix, with
+    /// their values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let entries = vec![("bat", 1), ("bar", 2), ("cat", 3), ("ba", 4)];
+    /// let trie: RadixTrie<_> = entries.into_iter().collect();
+    /// let found: Vec<_> = trie.iter_prefix("ba").map(|(_, v)| *v).collect();
+    /// assert_eq!(found, [4, 2, 1]);
+    /// ```
+    pub fn iter_prefix<K>(&self, prefix: &K) -> Iter<'_, V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let prefix = prefix.as_ref();
+        let start = self.find_prefix(prefix);
+        let mut key = prefix.to_vec();
+        if let Some((_, extra)) = start {
+            key.extend_from_slice(extra);
+        }
+        Iter {
+            key,
+            start: start.map(|(node, _)| node),
+            stack: Vec::new(),
+            len: start.map_or(0, |(node, _)| node.count),
+        }
+    }
+
+    /// Returns an iterator visiting all the keys of the trie, with their
+    /// values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RadixTrie;
+    ///
+    /// let trie: RadixTrie<_> = vec![("b", 1), ("a", 2)].into_iter().collect();
+    /// let keys: Vec<_> = trie.iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [b"a".to_vec(), b"b".to_vec()]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix("")
+    }
+
+    /// Returns the highest node whose key starts with the prefix, with the
+    /// bytes of its label that come after the prefix.
+    fn find_prefix<'a>(
+        &'a self,
+        prefix: &[u8],
+    ) -> Option<(&'a Node<V>, &'a [u8])> {
+        let mut rest = prefix;
+        let mut node = &*self.root;
+        while !rest.is_empty() {
+            let i = node.child(rest[0]).ok()?;
+            node = &node.children[i];
+            if rest.len() <= node.label.len() {
+                let extra = node.label.strip_prefix(rest)?;
+                return Some((node, extra));
+            }
+            rest = rest.strip_prefix(&node.label[..])?;
+        }
+        Some((node, &[]))
+    }
+}
+
+/// Returns the length of the longest common prefix of the two strings.
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl<K: AsRef<[u8]>, V> FromIterator<(K, V)> for RadixTrie<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<K: AsRef<[u8]>, V> Extend<(K, V)> for RadixTrie<V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(&key, value);
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a RadixTrie<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of a RadixTrie, with their values, in
+/// lexicographic order of the keys.
+///
+/// This struct is created by [`RadixTrie::iter()`] and
+/// [`RadixTrie::iter_prefix()`]. See their documentation for more.
+///
+/// [`RadixTrie::iter()`]: crate::containers::RadixTrie::iter
+/// [`RadixTrie::iter_prefix()`]: crate::containers::RadixTrie::iter_prefix
+pub struct Iter<'a, V> {
+    // The key of the node on top of the stack.
+    key: Vec<u8>,
+    // The node of the prefix, until it has been visited.
+    start: Option<&'a Node<V>>,
+    // The nodes on the path from the node of the prefix, with the position of
+    // the next child to visit.
+    stack: Vec<(&'a Node<V>, usize)>,
+    len: usize,
+}
+
+impl<'a, V: fmt::Debug> fmt::Debug for Iter<'a, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter")
+            .field("key", &self.key)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            self.stack.push((start, 0));
+            if let Some(value) = &start.value {
+                self.len -= 1;
+                return Some((self.key.clone(), value));
+            }
+        }
+        while let Some(&mut (node, ref mut next)) = self.stack.last_mut() {
+            match node.children.get(*next) {
+                Some(child) => {
+                    *next += 1;
+                    self.key.extend_from_slice(&child.label);
+                    self.stack.push((child, 0));
+                    if let Some(value) = &child.value {
+                        self.len -= 1;
+                        return Some((self.key.clone(), value));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        let len = self.key.len() - node.label.len();
+                        self.key.truncate(len);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the counts, that the labels are not empty and start with
+    /// distinct sorted bytes, and that every node but the root holds a value
+    /// or has two children, and returns the number of nodes.
+    fn check<V>(node: &Node<V>, is_root: bool) -> usize {
+        let count: usize = node.children.iter().map(|c| c.count).sum();
+        assert_eq!(node.count, count + node.value.is_some() as usize);
+        assert!(is_root || node.value.is_some() || node.children.len() >= 2);
+        let firsts: Vec<_> = node.children.iter().map(|c| c.label[0]).collect();
+        assert!(firsts.windows(2).all(|w| w[0] < w[1]));
+        1 + node.children.iter().map(|c| check(c, false)).sum::<usize>()
+    }
+
+    fn random_key(rng: &mut SplitMix64) -> Vec<u8> {
+        let len = rng.next_u64() % 8;
+        (0..len)
+            .map(|_| b"ab"[(rng.next_u64() % 2) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = SplitMix64::new(23);
+        let mut trie = RadixTrie::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..3000 {
+            let key = random_key(&mut rng);
+            if rng.next_u64().is_multiple_of(3) {
+                assert_eq!(trie.remove(&key), expected.remove(&key));
+            } else {
+                assert_eq!(trie.insert(&key, i), expected.insert(key, i));
+            }
+            assert_eq!(trie.len(), expected.len());
+            if i % 100 == 0 {
+                check(&trie.root, true);
+            }
+        }
+        check(&trie.root, true);
+        assert!(trie.iter().eq(expected.iter().map(|(k, v)| (k.clone(), v))));
+
+        for _ in 0..100 {
+            let prefix = random_key(&mut rng);
+            let wanted: Vec<_> = expected
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix))
+                .map(|(k, v)| (k.clone(), v))
+                .collect();
+            assert_eq!(trie.count_prefix(&prefix), wanted.len());
+            let found: Vec<_> = trie.iter_prefix(&prefix).collect();
+            assert_eq!(found, wanted);
+
+            let longest = (0..=prefix.len())
+                .rev()
+                .find(|&n| expected.contains_key(&prefix[..n]))
+                .map(|n| (&prefix[..n], &expected[&prefix[..n]]));
+            assert_eq!(trie.longest_prefix_match(&prefix), longest);
+        }
+    }
+
+    #[test]
+    fn splits_and_merges_edges() {
+        let mut trie = RadixTrie::new();
+        trie.insert("romane", 1);
+        trie.insert("romanus", 2);
+        trie.insert("romulus", 3);
+        trie.insert("rubens", 4);
+        // The root, "r", "om", "an", and the four leaves.
+        assert_eq!(check(&trie.root, true), 8);
+        assert_eq!(trie.remove("romanus"), Some(2));
+        // "an" merges with "e".
+        assert_eq!(check(&trie.root, true), 6);
+        assert_eq!(trie.remove("rubens"), Some(4));
+        // "r" merges with "om".
+        assert_eq!(check(&trie.root, true), 4);
+        assert_eq!(trie.root.children[0].label, b"rom");
+    }
+
+    #[test]
+    fn fewer_nodes_than_keys() {
+        let keys: Vec<_> =
+            (0..1000).map(|i| alloc::format!("key-{:08}", i)).collect();
+        let trie: RadixTrie<_> = keys.iter().map(|k| (k, ())).collect();
+        assert!(check(&trie.root, true) < 2 * keys.len());
+    }
+}
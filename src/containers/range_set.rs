@@ -0,0 +1,454 @@
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::Range;
+
+use super::interval_map::{self, IntervalMap};
+
+/// A set of values stored as disjoint half-open ranges, which are merged
+/// when they overlap or touch.
+///
+/// The ranges are kept in an [`IntervalMap`] whose values are all `()`, so
+/// inserting a range merges it with every range that it overlaps or that
+/// ends or starts exactly at its ends, and removing a range splits the
+/// ranges that stick out of both of its ends. The set thus holds as few
+/// ranges as possible, and the gaps between consecutive ranges are never
+/// empty.
+///
+/// [`IntervalMap`]: crate::containers::IntervalMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::RangeSet;
+///
+/// let mut received = RangeSet::new();
+/// received.insert(0..100);
+/// received.insert(200..300);
+/// received.insert(100..150);
+/// let ranges: Vec<_> = received.iter().collect();
+/// assert_eq!(ranges, [&(0..150), &(200..300)]);
+///
+/// // The bytes still missing between the received ones.
+/// assert_eq!(received.gaps().collect::<Vec<_>>(), [150..200]);
+///
+/// received.remove(50..60);
+/// assert!(!received.contains(&55));
+/// assert_eq!(received.len(), 3);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    map: IntervalMap<T, ()>,
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> RangeSet<T> {
+    /// Creates an empty RangeSet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let set: RangeSet<u32> = RangeSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            map: IntervalMap::new(),
+        }
+    }
+
+    /// Returns the number of disjoint ranges in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// set.insert(0..5);
+    /// set.insert(5..9);
+    /// set.insert(10..12);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set has no ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// assert!(set.is_empty());
+    /// set.insert(0..5);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all the ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// set.insert(0..5);
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns an iterator visiting the disjoint ranges of the set in
+    /// ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let set: RangeSet<_> = vec![4..6, 0..2, 1..3].into_iter().collect();
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&(0..3), &(4..6)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Returns an iterator visiting the gaps between consecutive ranges of
+    /// the set in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let set: RangeSet<_> = vec![0..2, 4..6, 7..9].into_iter().collect();
+    /// assert_eq!(set.gaps().collect::<Vec<_>>(), [2..4, 6..7]);
+    /// ```
+    pub fn gaps(&self) -> Gaps<'_, T> {
+        let mut iter = self.iter();
+        let end = iter.next().map(|r| &r.end);
+        Gaps { iter, end }
+    }
+}
+
+impl<T: Ord> RangeSet<T> {
+    /// Returns true if a range of the set holds the value.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// set.insert(0..5);
+    /// assert!(set.contains(&4));
+    /// assert!(!set.contains(&5));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Returns true if the set holds every value of the range. An empty
+    /// range is always contained.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// set.insert(0..5);
+    /// set.insert(5..9);
+    /// assert!(set.contains_range(&(2..8)));
+    /// assert!(!set.contains_range(&(8..10)));
+    /// ```
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        if range.start >= range.end {
+            return true;
+        }
+        match self.map.get_key_value(&range.start) {
+            Some((r, _)) => r.end >= range.end,
+            None => false,
+        }
+    }
+
+    /// Returns an iterator visiting the ranges of the set that overlap the
+    /// given range in ascending order.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let set: RangeSet<_> = vec![0..2, 4..6, 8..9].into_iter().collect();
+    /// let found: Vec<_> = set.overlapping(1..5).collect();
+    /// assert_eq!(found, [&(0..2), &(4..6)]);
+    /// ```
+    pub fn overlapping(&self, range: Range<T>) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.overlapping(range),
+        }
+    }
+}
+
+impl<T: Ord + Clone> RangeSet<T> {
+    /// Adds the values of the range to the set, and returns true if any of
+    /// them was not already in it.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// assert!(set.insert(0..5));
+    /// assert!(!set.insert(1..3));
+    /// assert!(set.insert(3..8));
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&(0..8)]);
+    /// ```
+    pub fn insert(&mut self, range: Range<T>) -> bool {
+        if self.contains_range(&range) {
+            return false;
+        }
+        self.map.insert(range, ());
+        true
+    }
+
+    /// Removes the values of the range from the set, and returns true if any
+    /// of them was in it. A range that sticks out of both ends of the given
+    /// range is split in two.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RangeSet;
+    ///
+    /// let mut set = RangeSet::new();
+    /// set.insert(0..10);
+    /// assert!(set.remove(3..5));
+    /// assert!(!set.remove(3..5));
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [&(0..3), &(5..10)]);
+    /// ```
+    pub fn remove(&mut self, range: Range<T>) -> bool {
+        self.map.remove(range)
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Ord + Clone> Extend<Range<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RangeSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the disjoint ranges of a [`RangeSet`].
+///
+/// This struct is created by [`RangeSet::iter()`] and
+/// [`RangeSet::overlapping()`]. See their documentation for more.
+///
+/// [`RangeSet`]: crate::containers::RangeSet
+/// [`RangeSet::iter()`]: RangeSet::iter
+/// [`RangeSet::overlapping()`]: RangeSet::overlapping
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    iter: interval_map::Iter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(r, _)| r)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(r, _)| r)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// An iterator over the gaps between the ranges of a [`RangeSet`].
+///
+/// This struct is created by [`RangeSet::gaps()`]. See its documentation
+/// for more.
+///
+/// [`RangeSet`]: crate::containers::RangeSet
+/// [`RangeSet::gaps()`]: crate::containers::RangeSet::gaps
+#[derive(Debug, Clone)]
+pub struct Gaps<'a, T> {
+    iter: Iter<'a, T>,
+    // The end of the last range visited.
+    end: Option<&'a T>,
+}
+
+impl<'a, T: Clone> Iterator for Gaps<'a, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.iter.next()?;
+        let start = self.end.replace(&next.end)?;
+        Some(start.clone()..next.start.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T: Clone> ExactSizeIterator for Gaps<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::vec::Vec;
+
+    const VALUES: u64 = 64;
+
+    fn random_range(rng: &mut SplitMix64) -> Range<u64> {
+        let a = rng.next_u64() % (VALUES + 1);
+        let b = rng.next_u64() % (VALUES + 1);
+        a.min(b)..a.max(b)
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn empty_and_single_range_sets() {
+        let mut set = RangeSet::new();
+        assert_eq!(set.gaps().len(), 0);
+        assert_eq!(set.gaps().next(), None);
+        assert!(!set.contains(&0));
+        assert!(set.contains_range(&(3..3)));
+        assert!(!set.contains_range(&(3..4)));
+        assert!(!set.remove(0..10));
+
+        // Empty ranges add nothing.
+        assert!(!set.insert(3..3));
+        assert!(!set.insert(4..3));
+        assert!(set.is_empty());
+
+        // A single range has no gap.
+        assert!(set.insert(3..6));
+        assert_eq!(set.gaps().len(), 0);
+        assert_eq!(set.gaps().next(), None);
+        assert!(set.contains_range(&(3..6)));
+        assert!(!set.contains_range(&(2..6)));
+        assert!(!set.contains_range(&(3..7)));
+        assert!(set.contains_range(&(9..2)));
+    }
+
+    #[test]
+    fn touching_ranges_coalesce() {
+        let mut set: RangeSet<_> = vec![4..6, 0..2].into_iter().collect();
+        assert_eq!(set.gaps().len(), 1);
+        assert_eq!(set.gaps().next(), Some(2..4));
+
+        // Filling the gap exactly merges the three ranges.
+        assert!(set.insert(2..4));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&(0..6)]);
+        assert!(set.contains_range(&(0..6)));
+
+        // Removing a range at an end shortens it rather than splitting it.
+        assert!(set.remove(0..1));
+        assert!(set.remove(5..6));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [&(1..5)]);
+        assert!(!set.remove(5..9));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(681);
+        let mut set = RangeSet::new();
+        let mut expected = [false; VALUES as usize];
+        for _ in 0..5_000 {
+            let range = random_range(&mut rng);
+            if rng.next_u64().is_multiple_of(3) {
+                let removed = range.clone().any(|v| expected[v as usize]);
+                assert_eq!(set.remove(range.clone()), removed);
+                for v in range {
+                    expected[v as usize] = false;
+                }
+            } else {
+                let added = range.clone().any(|v| !expected[v as usize]);
+                assert_eq!(set.insert(range.clone()), added);
+                for v in range {
+                    expected[v as usize] = true;
+                }
+            }
+
+            for v in 0..VALUES {
+                assert_eq!(set.contains(&v), expected[v as usize]);
+            }
+            let ranges: Vec<_> = set.iter().cloned().collect();
+            let gaps: Vec<_> = set.gaps().collect();
+            assert_eq!(gaps.len(), ranges.len().saturating_sub(1));
+            for (gap, w) in gaps.iter().zip(ranges.windows(2)) {
+                assert!(gap.start < gap.end);
+                assert_eq!(*gap, w[0].end..w[1].start);
+            }
+
+            let query = random_range(&mut rng);
+            assert_eq!(
+                set.contains_range(&query),
+                query.clone().all(|v| expected[v as usize])
+            );
+        }
+    }
+}
@@ -0,0 +1,857 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+
+/// The index of a missing node.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black,
+}
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    color: Color,
+    parent: usize,
+    // The left child is at index 0 and the right child at index 1, so that
+    // the mirrored cases of the fix-ups can share their code.
+    child: [usize; 2],
+}
+
+/// An ordered map implemented with a red-black tree.
+///
+/// Every node is colored red or black, the root is black, no red node has a
+/// red child, and every path from a node down to a missing child goes through
+/// the same number of black nodes. These rules keep the longest path from the
+/// root at most twice as long as the shortest, so the height of the tree is
+/// O(log(n)). An insertion or a removal may break the rules locally, and is
+/// followed by a fix-up that recolors nodes and does at most three rotations.
+///
+/// The nodes are kept in a vector and link to each other, including to their
+/// parents, by index. A removed node is replaced by the last node of the
+/// vector, whose links are updated to its new index.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::RbTreeMap;
+///
+/// let mut map = RbTreeMap::new();
+/// for (i, word) in ["zero", "one", "two", "three"].iter().enumerate() {
+///     map.insert(i, *word);
+/// }
+///
+/// assert_eq!(map.get(&2), Some(&"two"));
+/// assert_eq!(map.remove(&0), Some("zero"));
+/// let words: Vec<_> = map.range(1..3).map(|(_, w)| *w).collect();
+/// assert_eq!(words, ["one", "two"]);
+/// map.assert_valid();
+/// ```
+pub struct RbTreeMap<K, V> {
+    nodes: Vec<Node<K, V>>,
+    root: usize,
+}
+
+impl<K: Ord, V> Default for RbTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for RbTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> RbTreeMap<K, V> {
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let map: RbTreeMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// let keys: Vec<_> = map.iter().rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [3, 2, 1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            range: Range {
+                map: self,
+                front: self.extreme(self.root, 0),
+                back: self.extreme(self.root, 1),
+            },
+            len: self.len(),
+        }
+    }
+
+    fn is_red(&self, i: usize) -> bool {
+        i != NIL && self.nodes[i].color == Color::Red
+    }
+
+    /// Returns the leftmost (`dir` is 0) or rightmost (`dir` is 1) node of the
+    /// subtree rooted at `i`.
+    fn extreme(&self, mut i: usize, dir: usize) -> usize {
+        if i == NIL {
+            return NIL;
+        }
+        while self.nodes[i].child[dir] != NIL {
+            i = self.nodes[i].child[dir];
+        }
+        i
+    }
+
+    /// Returns the next node after `i` in the direction `dir`, which is 1 for
+    /// the successor and 0 for the predecessor.
+    fn step(&self, mut i: usize, dir: usize) -> usize {
+        let next = self.nodes[i].child[dir];
+        if next != NIL {
+            return self.extreme(next, 1 - dir);
+        }
+        loop {
+            let parent = self.nodes[i].parent;
+            if parent == NIL || self.nodes[parent].child[1 - dir] == i {
+                return parent;
+            }
+            i = parent;
+        }
+    }
+
+    /// Makes `new` take the place of `old` as a child of `parent`, or as the
+    /// root if `parent` is missing.
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if parent == NIL {
+            self.root = new;
+        } else if self.nodes[parent].child[0] == old {
+            self.nodes[parent].child[0] = new;
+        } else {
+            self.nodes[parent].child[1] = new;
+        }
+    }
+
+    /// Rotates the subtree rooted at `x` so that `x` moves down in the
+    /// direction `dir`, and its child on the other side takes its place.
+    fn rotate(&mut self, x: usize, dir: usize) {
+        let y = self.nodes[x].child[1 - dir];
+        let inner = self.nodes[y].child[dir];
+        self.nodes[x].child[1 - dir] = inner;
+        if inner != NIL {
+            self.nodes[inner].parent = x;
+        }
+        let parent = self.nodes[x].parent;
+        self.replace_child(parent, x, y);
+        self.nodes[y].parent = parent;
+        self.nodes[y].child[dir] = x;
+        self.nodes[x].parent = y;
+    }
+
+    /// Makes the subtree rooted at `v` take the place of the one rooted at
+    /// `u`.
+    fn transplant(&mut self, u: usize, v: usize) {
+        let parent = self.nodes[u].parent;
+        self.replace_child(parent, u, v);
+        if v != NIL {
+            self.nodes[v].parent = parent;
+        }
+    }
+
+    /// Restores the rules after the red node `z` has been added as a leaf.
+    fn insert_fixup(&mut self, mut z: usize) {
+        while self.is_red(self.nodes[z].parent) {
+            let mut p = self.nodes[z].parent;
+            // The parent is red, so it is not the root.
+            let g = self.nodes[p].parent;
+            let dir = if self.nodes[g].child[0] == p { 0 } else { 1 };
+            let uncle = self.nodes[g].child[1 - dir];
+            if self.is_red(uncle) {
+                self.nodes[p].color = Color::Black;
+                self.nodes[uncle].color = Color::Black;
+                self.nodes[g].color = Color::Red;
+                z = g;
+            } else {
+                if self.nodes[p].child[1 - dir] == z {
+                    self.rotate(p, dir);
+                    z = p;
+                    p = self.nodes[z].parent;
+                }
+                self.nodes[p].color = Color::Black;
+                self.nodes[g].color = Color::Red;
+                self.rotate(g, 1 - dir);
+            }
+        }
+        let root = self.root;
+        self.nodes[root].color = Color::Black;
+    }
+
+    /// Restores the rules after a black node has been unlinked from above
+    /// `x`, whose parent is `parent`. The path through `x` is then one black
+    /// node short, which `x` makes up for if it is red, and otherwise the
+    /// missing black node is moved up the tree or taken from the sibling.
+    fn remove_fixup(&mut self, mut x: usize, mut parent: usize) {
+        while x != self.root && !self.is_red(x) {
+            // The sibling's subtree holds at least one black node, so the
+            // sibling exists and `x` is on the other side even if missing.
+            let dir = if self.nodes[parent].child[0] == x {
+                0
+            } else {
+                1
+            };
+            let mut w = self.nodes[parent].child[1 - dir];
+            if self.is_red(w) {
+                self.nodes[w].color = Color::Black;
+                self.nodes[parent].color = Color::Red;
+                self.rotate(parent, dir);
+                w = self.nodes[parent].child[1 - dir];
+            }
+            let [near, far] = if dir == 0 {
+                self.nodes[w].child
+            } else {
+                let [a, b] = self.nodes[w].child;
+                [b, a]
+            };
+            if !self.is_red(near) && !self.is_red(far) {
+                self.nodes[w].color = Color::Red;
+                x = parent;
+                parent = self.nodes[x].parent;
+            } else {
+                if !self.is_red(far) {
+                    self.nodes[near].color = Color::Black;
+                    self.nodes[w].color = Color::Red;
+                    self.rotate(w, 1 - dir);
+                    w = self.nodes[parent].child[1 - dir];
+                }
+                self.nodes[w].color = self.nodes[parent].color;
+                self.nodes[parent].color = Color::Black;
+                let far = self.nodes[w].child[1 - dir];
+                self.nodes[far].color = Color::Black;
+                self.rotate(parent, dir);
+                x = self.root;
+                parent = NIL;
+            }
+        }
+        if x != NIL {
+            self.nodes[x].color = Color::Black;
+        }
+    }
+
+    /// Unlinks the node `z` from the tree, then removes it from the vector.
+    fn remove_node(&mut self, z: usize) -> (K, V) {
+        let [left, right] = self.nodes[z].child;
+        let mut removed_color = self.nodes[z].color;
+        let (x, x_parent);
+        if left == NIL || right == NIL {
+            x = if left == NIL { right } else { left };
+            x_parent = self.nodes[z].parent;
+            self.transplant(z, x);
+        } else {
+            // The successor of `z` has no left child and takes its place.
+            let y = self.extreme(right, 0);
+            removed_color = self.nodes[y].color;
+            x = self.nodes[y].child[1];
+            if self.nodes[y].parent == z {
+                x_parent = y;
+            } else {
+                x_parent = self.nodes[y].parent;
+                self.transplant(y, x);
+                self.nodes[y].child[1] = right;
+                self.nodes[right].parent = y;
+            }
+            self.transplant(z, y);
+            self.nodes[y].child[0] = left;
+            self.nodes[left].parent = y;
+            self.nodes[y].color = self.nodes[z].color;
+        }
+        if removed_color == Color::Black {
+            self.remove_fixup(x, x_parent);
+        }
+
+        let node = self.nodes.swap_remove(z);
+        if z < self.nodes.len() {
+            // The last node has moved into the slot of `z`.
+            let moved = self.nodes.len();
+            let parent = self.nodes[z].parent;
+            self.replace_child(parent, moved, z);
+            for child in self.nodes[z].child {
+                if child != NIL {
+                    self.nodes[child].parent = z;
+                }
+            }
+        }
+        (node.key, node.value)
+    }
+
+    /// Checks the rules of the red-black tree and the links between its
+    /// nodes, and returns the number of black nodes on each path from `i`
+    /// down to a missing child.
+    fn check(&self, i: usize, parent: usize) -> usize {
+        if i == NIL {
+            return 1;
+        }
+        let node = &self.nodes[i];
+        assert_eq!(node.parent, parent, "Broken parent link");
+        if node.color == Color::Red {
+            assert!(
+                !self.is_red(node.child[0]) && !self.is_red(node.child[1]),
+                "Red node with a red child"
+            );
+        }
+        let left = self.check(node.child[0], i);
+        let right = self.check(node.child[1], i);
+        assert_eq!(left, right, "Unequal black heights");
+        left + (node.color == Color::Black) as usize
+    }
+}
+
+impl<K: Ord, V> RbTreeMap<K, V> {
+    /// Creates an empty RbTreeMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+        }
+    }
+
+    /// Inserts a key-value pair into the map and returns the old value of the
+    /// key, or None if the key was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut parent = NIL;
+        let mut dir = 0;
+        let mut i = self.root;
+        while i != NIL {
+            parent = i;
+            dir = match key.cmp(&self.nodes[i].key) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => {
+                    return Some(mem::replace(&mut self.nodes[i].value, value));
+                }
+            };
+            i = self.nodes[i].child[dir];
+        }
+
+        let z = self.nodes.len();
+        self.nodes.push(Node {
+            key,
+            value,
+            color: Color::Red,
+            parent,
+            child: [NIL, NIL],
+        });
+        if parent == NIL {
+            self.root = z;
+        } else {
+            self.nodes[parent].child[dir] = z;
+        }
+        self.insert_fixup(z);
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&self.nodes[i].value)
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, 10);
+    /// *map.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.nodes[i].value)
+    }
+
+    /// Returns true if the key is in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes the key from the map and returns its value, or None if the key
+    /// was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map = RbTreeMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(self.remove_node(i).1)
+    }
+
+    /// Returns the entry with the least key, or None if the map is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let map: RbTreeMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.first_key_value(), Some((&1, &"a")));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.entry(self.extreme(self.root, 0))
+    }
+
+    /// Returns the entry with the greatest key, or None if the map is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let map: RbTreeMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.last_key_value(), Some((&2, &"b")));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.entry(self.extreme(self.root, 1))
+    }
+
+    /// Returns an iterator visiting the entries of the map whose keys are in
+    /// the range, in ascending order of their keys. The iterator is empty if
+    /// the start of the range is after its end.
+    ///
+    /// This operation should compute in O(log(n)) time, and each step of the
+    /// iterator in O(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let map: RbTreeMap<_, _> = (0..10).map(|k| (k, k * k)).collect();
+    /// let squares: Vec<_> = map.range(3..6).map(|(_, v)| *v).collect();
+    /// assert_eq!(squares, [9, 16, 25]);
+    /// let keys: Vec<_> = map.range(..=2).rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [2, 1, 0]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let front = self.bound(range.start_bound(), 1);
+        let back = self.bound(range.end_bound(), 0);
+        let empty = front == NIL
+            || back == NIL
+            || self.nodes[front].key > self.nodes[back].key;
+        Range {
+            map: self,
+            front: if empty { NIL } else { front },
+            back: if empty { NIL } else { back },
+        }
+    }
+
+    /// Checks that the tree follows the rules of a red-black tree and that
+    /// its keys are in order.
+    ///
+    /// This is meant for tests, and computes in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree is not a valid red-black tree, which can only happen
+    /// if the Ord implementation of the keys is inconsistent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RbTreeMap;
+    ///
+    /// let mut map: RbTreeMap<_, _> = (0..100).map(|k| (k, ())).collect();
+    /// map.assert_valid();
+    /// for k in 0..50 {
+    ///     map.remove(&(k * 2));
+    /// }
+    /// map.assert_valid();
+    /// ```
+    pub fn assert_valid(&self) {
+        assert!(!self.is_red(self.root), "Red root");
+        self.check(self.root, NIL);
+        let keys: Vec<_> = self.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys.len(), self.len(), "Unreachable nodes");
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "Unordered keys");
+    }
+
+    fn entry(&self, i: usize) -> Option<(&K, &V)> {
+        self.nodes.get(i).map(|node| (&node.key, &node.value))
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut i = self.root;
+        while i != NIL {
+            i = match key.cmp(self.nodes[i].key.borrow()) {
+                Ordering::Less => self.nodes[i].child[0],
+                Ordering::Greater => self.nodes[i].child[1],
+                Ordering::Equal => return Some(i),
+            };
+        }
+        None
+    }
+
+    /// Returns the first node inside the bound when looking from below
+    /// (`dir` is 1), or the last node inside it when looking from above
+    /// (`dir` is 0).
+    fn bound<Q>(&self, bound: Bound<&Q>, dir: usize) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (key, inclusive) = match bound {
+            Bound::Included(key) => (key, true),
+            Bound::Excluded(key) => (key, false),
+            Bound::Unbounded => return self.extreme(self.root, 1 - dir),
+        };
+        let mut found = NIL;
+        let mut i = self.root;
+        while i != NIL {
+            let ord = self.nodes[i].key.borrow().cmp(key);
+            let inside = match (ord, dir) {
+                (Ordering::Equal, _) => inclusive,
+                (Ordering::Greater, 1) | (Ordering::Less, 0) => true,
+                _ => false,
+            };
+            if inside {
+                found = i;
+                i = self.nodes[i].child[1 - dir];
+            } else {
+                i = self.nodes[i].child[dir];
+            }
+        }
+        found
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for RbTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for RbTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a RbTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over a range of the entries of a RbTreeMap, in ascending order
+/// of their keys.
+///
+/// This struct is created by [`RbTreeMap::range()`]. See its documentation
+/// for more.
+///
+/// [`RbTreeMap::range()`]: crate::containers::RbTreeMap::range
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    map: &'a RbTreeMap<K, V>,
+    // The next nodes to yield from each end, both missing once the ends meet.
+    front: usize,
+    back: usize,
+}
+
+impl<'a, K, V> Range<'a, K, V> {
+    fn next_from(&mut self, dir: usize) -> Option<(&'a K, &'a V)> {
+        let (from, other) = if dir == 1 {
+            (self.front, self.back)
+        } else {
+            (self.back, self.front)
+        };
+        if from == NIL {
+            return None;
+        }
+        let next = if from == other {
+            self.front = NIL;
+            self.back = NIL;
+            NIL
+        } else {
+            self.map.step(from, dir)
+        };
+        if dir == 1 {
+            self.front = next;
+        } else {
+            self.back = next;
+        }
+        let node = &self.map.nodes[from];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_from(1)
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next_from(0)
+    }
+}
+
+/// An iterator over the entries of a RbTreeMap, in ascending order of their
+/// keys.
+///
+/// This struct is created by [`RbTreeMap::iter()`]. See its documentation for
+/// more.
+///
+/// [`RbTreeMap::iter()`]: crate::containers::RbTreeMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next_back()?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    #[test]
+    fn insert_remove_valid() {
+        let mut map = RbTreeMap::new();
+        let mut expected = BTreeMap::new();
+        for k in (0..500).map(|k| (k * 37) % 499) {
+            assert_eq!(map.insert(k, k), expected.insert(k, k));
+            map.assert_valid();
+        }
+        for k in (0..500).map(|k| (k * 53) % 509) {
+            assert_eq!(map.remove(&k), expected.remove(&k));
+            map.assert_valid();
+            if k % 7 == 0 {
+                assert_eq!(
+                    map.insert(k + 1000, k),
+                    expected.insert(k + 1000, k)
+                );
+                map.assert_valid();
+            }
+        }
+        assert!(map.iter().eq(expected.iter()));
+        assert!(map.iter().rev().eq(expected.iter().rev()));
+        assert_eq!(map.iter().len(), expected.len());
+    }
+
+    #[test]
+    fn sorted_insertions_stay_shallow() {
+        let map: RbTreeMap<_, _> = (0..1024).map(|k| (k, ())).collect();
+        map.assert_valid();
+        // The black height is at most log2(n + 1), and the height at most
+        // twice that.
+        let mut height = 0;
+        for mut i in 0..map.len() {
+            let mut depth = 1;
+            while map.nodes[i].parent != NIL {
+                i = map.nodes[i].parent;
+                depth += 1;
+            }
+            height = height.max(depth);
+        }
+        assert!(height <= 20);
+    }
+
+    #[test]
+    fn ranges() {
+        let map: RbTreeMap<_, _> = (0..20).map(|k| (k * 2, ())).collect();
+        let expected: BTreeMap<_, _> = (0..20).map(|k| (k * 2, ())).collect();
+        let keys =
+            |r: Range<'_, i32, ()>| r.map(|(k, _)| *k).collect::<Vec<_>>();
+        let expected_keys =
+            |r: alloc::collections::btree_map::Range<'_, i32, ()>| {
+                r.map(|(k, _)| *k).collect::<Vec<_>>()
+            };
+
+        assert_eq!(keys(map.range(3..9)), expected_keys(expected.range(3..9)));
+        assert_eq!(
+            keys(map.range(4..=10)),
+            expected_keys(expected.range(4..=10))
+        );
+        assert_eq!(keys(map.range(..5)), expected_keys(expected.range(..5)));
+        assert_eq!(keys(map.range(35..)), expected_keys(expected.range(35..)));
+        assert_eq!(
+            keys(map.range((Bound::Excluded(4), Bound::Excluded(10)))),
+            [6, 8]
+        );
+        assert!(keys(map.range(50..)).is_empty());
+        assert!(keys(map.range(5..5)).is_empty());
+        assert!(map.range(3..9).rev().map(|(k, _)| *k).eq([8, 6, 4]));
+    }
+}
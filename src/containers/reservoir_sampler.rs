@@ -0,0 +1,224 @@
+use alloc::vec::Vec;
+
+use super::rng::SplitMix64;
+
+/// A uniform random sample of up to k items of a stream of unknown length,
+/// kept in memory for k items.
+///
+/// This is Algorithm R: the first k items fill the reservoir, and the n-th
+/// item after them replaces a random one of the reservoir with probability
+/// k / n. After any number of items, every item seen so far is in the sample
+/// with the same probability.
+///
+/// The random choices come from a pseudorandom number generator. A sampler
+/// created with [`ReservoirSampler::with_seed()`] and given the same items
+/// always keeps the same sample.
+///
+/// [`ReservoirSampler::with_seed()`]: crate::containers::ReservoirSampler::with_seed
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::ReservoirSampler;
+///
+/// let mut sampler = ReservoirSampler::with_seed(3, 42);
+/// for i in 0..1000 {
+///     sampler.add(i);
+/// }
+/// assert_eq!(sampler.seen(), 1000);
+/// assert_eq!(sampler.sample().len(), 3);
+/// assert!(sampler.sample().iter().all(|&i| i < 1000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReservoirSampler<T> {
+    capacity: usize,
+    sample: Vec<T>,
+    seen: u64,
+    rng: SplitMix64,
+}
+
+impl<T> ReservoirSampler<T> {
+    /// Creates an empty ReservoirSampler that keeps up to `capacity` items,
+    /// whose random choices come from a generator with a fixed seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let sampler: ReservoirSampler<i32> = ReservoirSampler::new(10);
+    /// assert_eq!(sampler.capacity(), 10);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_seed(capacity, SplitMix64::DEFAULT_SEED)
+    }
+
+    /// Creates an empty ReservoirSampler that keeps up to `capacity` items,
+    /// whose random choices come from a generator with the given seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut a = ReservoirSampler::with_seed(5, 7);
+    /// let mut b = ReservoirSampler::with_seed(5, 7);
+    /// a.extend(0..100);
+    /// b.extend(0..100);
+    /// assert_eq!(a.sample(), b.sample());
+    /// ```
+    pub fn with_seed(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            sample: Vec::with_capacity(capacity),
+            seen: 0,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Returns the largest number of items kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let sampler: ReservoirSampler<i32> = ReservoirSampler::new(4);
+    /// assert_eq!(sampler.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of items added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut sampler = ReservoirSampler::new(2);
+    /// sampler.extend(0..5);
+    /// assert_eq!(sampler.seen(), 5);
+    /// ```
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Adds an item of the stream, which may be kept in the sample in place
+    /// of another one.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut sampler = ReservoirSampler::new(2);
+    /// sampler.add("a");
+    /// sampler.add("b");
+    /// assert_eq!(sampler.sample(), ["a", "b"]);
+    /// ```
+    pub fn add(&mut self, item: T) {
+        self.seen += 1;
+        if self.sample.len() < self.capacity {
+            self.sample.push(item);
+            return;
+        }
+        let i = self.rng.next_u64() % self.seen;
+        if i < self.capacity as u64 {
+            self.sample[i as usize] = item;
+        }
+    }
+
+    /// Returns the items kept, which are all the items added if there were
+    /// at most `capacity` of them. The order of the items is unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut sampler = ReservoirSampler::new(10);
+    /// sampler.extend(0..3);
+    /// assert_eq!(sampler.sample(), [0, 1, 2]);
+    /// ```
+    pub fn sample(&self) -> &[T] {
+        &self.sample
+    }
+
+    /// Returns the items kept, consuming the sampler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut sampler = ReservoirSampler::new(10);
+    /// sampler.extend(0..3);
+    /// assert_eq!(sampler.into_sample(), vec![0, 1, 2]);
+    /// ```
+    pub fn into_sample(self) -> Vec<T> {
+        self.sample
+    }
+
+    /// Forgets the items added, keeping the state of the generator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ReservoirSampler;
+    ///
+    /// let mut sampler = ReservoirSampler::new(10);
+    /// sampler.extend(0..3);
+    /// sampler.clear();
+    /// assert!(sampler.sample().is_empty());
+    /// assert_eq!(sampler.seen(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.sample.clear();
+        self.seen = 0;
+    }
+}
+
+impl<T> Extend<T> for ReservoirSampler<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_uniformly() {
+        // Every item of a stream of 20 should be kept in about a quarter of
+        // the samples of 5.
+        let runs = 20_000;
+        let mut kept = [0u32; 20];
+        let mut sampler = ReservoirSampler::with_seed(5, 83);
+        for _ in 0..runs {
+            sampler.clear();
+            sampler.extend(0..20);
+            for &i in sampler.sample() {
+                kept[i] += 1;
+            }
+        }
+        let expected = runs as f64 / 4.0;
+        for &k in &kept {
+            assert!((k as f64 - expected).abs() < expected * 0.05, "{}", k);
+        }
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut sampler = ReservoirSampler::new(0);
+        sampler.extend(0..10);
+        assert!(sampler.sample().is_empty());
+        assert_eq!(sampler.seen(), 10);
+    }
+}
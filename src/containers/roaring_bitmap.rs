@@ -0,0 +1,620 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::slice;
+
+use super::bit_vec::{Ones, BITS};
+
+// The largest number of values kept in a sorted array. Past it, a bitmap of
+// the 2^16 values of a chunk takes less memory.
+const ARRAY_MAX: usize = 4096;
+const WORDS: usize = (1 << 16) / BITS;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Container {
+    // The sorted values, when there are at most ARRAY_MAX of them.
+    Array(Vec<u16>),
+    // The bits of the values, and their number, when there are more.
+    Bitmap(Box<[u64; WORDS]>, usize),
+}
+
+impl Container {
+    fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Bitmap(_, len) => *len,
+        }
+    }
+
+    fn contains(&self, value: u16) -> bool {
+        match self {
+            Self::Array(values) => values.binary_search(&value).is_ok(),
+            Self::Bitmap(words, _) => {
+                let value = value as usize;
+                words[value / BITS] >> (value % BITS) & 1 == 1
+            }
+        }
+    }
+
+    fn insert(&mut self, value: u16) -> bool {
+        match self {
+            Self::Array(values) => match values.binary_search(&value) {
+                Ok(_) => false,
+                Err(i) => {
+                    values.insert(i, value);
+                    if values.len() > ARRAY_MAX {
+                        *self = Self::bitmap(values);
+                    }
+                    true
+                }
+            },
+            Self::Bitmap(words, len) => {
+                let value = value as usize;
+                let mask = 1 << (value % BITS);
+                let inserted = words[value / BITS] & mask == 0;
+                words[value / BITS] |= mask;
+                *len += inserted as usize;
+                inserted
+            }
+        }
+    }
+
+    fn remove(&mut self, value: u16) -> bool {
+        let removed = match self {
+            Self::Array(values) => match values.binary_search(&value) {
+                Ok(i) => {
+                    values.remove(i);
+                    true
+                }
+                Err(_) => false,
+            },
+            Self::Bitmap(words, len) => {
+                let value = value as usize;
+                let mask = 1 << (value % BITS);
+                let removed = words[value / BITS] & mask != 0;
+                words[value / BITS] &= !mask;
+                *len -= removed as usize;
+                removed
+            }
+        };
+        *self = mem::replace(self, Self::Array(Vec::new())).shrink();
+        removed
+    }
+
+    fn bitmap(values: &[u16]) -> Self {
+        let mut words = Box::new([0; WORDS]);
+        for &value in values {
+            let value = value as usize;
+            words[value / BITS] |= 1 << (value % BITS);
+        }
+        Self::Bitmap(words, values.len())
+    }
+
+    /// Turns a bitmap into an array if it has few enough values.
+    fn shrink(self) -> Self {
+        match self {
+            Self::Bitmap(_, len) if len <= ARRAY_MAX => {
+                Self::Array(self.iter().collect())
+            }
+            _ => self,
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) => {
+                let mut values = Vec::with_capacity(a.len() + b.len());
+                let (mut i, mut j) = (0, 0);
+                while i < a.len() && j < b.len() {
+                    values.push(a[i].min(b[j]));
+                    let (x, y) = (a[i], b[j]);
+                    i += (x <= y) as usize;
+                    j += (y <= x) as usize;
+                }
+                values.extend_from_slice(&a[i..]);
+                values.extend_from_slice(&b[j..]);
+                if values.len() > ARRAY_MAX {
+                    Self::bitmap(&values)
+                } else {
+                    Self::Array(values)
+                }
+            }
+            (Self::Bitmap(a, _), Self::Bitmap(b, _)) => {
+                let mut words = a.clone();
+                for (w, &b) in words.iter_mut().zip(b.iter()) {
+                    *w |= b;
+                }
+                let len = words.iter().map(|w| w.count_ones() as usize).sum();
+                Self::Bitmap(words, len)
+            }
+            (Self::Bitmap(..), Self::Array(values)) => {
+                let mut bitmap = self.clone();
+                for &value in values {
+                    bitmap.insert(value);
+                }
+                bitmap
+            }
+            (Self::Array(_), Self::Bitmap(..)) => other.union(self),
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Bitmap(a, _), Self::Bitmap(b, _)) => {
+                let mut words = a.clone();
+                for (w, &b) in words.iter_mut().zip(b.iter()) {
+                    *w &= b;
+                }
+                let len = words.iter().map(|w| w.count_ones() as usize).sum();
+                Self::Bitmap(words, len).shrink()
+            }
+            (Self::Array(values), other) | (other, Self::Array(values)) => {
+                Self::Array(
+                    values
+                        .iter()
+                        .copied()
+                        .filter(|&v| other.contains(v))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    fn iter(&self) -> ContainerIter<'_> {
+        match self {
+            Self::Array(values) => ContainerIter::Array(values.iter()),
+            Self::Bitmap(words, _) => {
+                ContainerIter::Bitmap(Ones::new(&words[..]))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ContainerIter<'a> {
+    Array(slice::Iter<'a, u16>),
+    Bitmap(Ones<'a>),
+}
+
+impl<'a> Iterator for ContainerIter<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Array(iter) => iter.next().copied(),
+            Self::Bitmap(iter) => iter.next().map(|v| v as u16),
+        }
+    }
+}
+
+/// A compressed set of `u32` values, in the style of Roaring bitmaps.
+///
+/// The values are split by their upper 16 bits into chunks of 2^16 values,
+/// and only the chunks that have values are stored. Each chunk picks its own
+/// container: a sorted array of the lower 16 bits when it holds at most 4096
+/// values, and a bitmap of 8 KiB otherwise, which is smaller past that point.
+/// Sparse chunks thus take 2 bytes per value and dense ones 1 bit per
+/// possible value, and the set operations between two chunks use whichever
+/// of merging, probing, or word-at-a-time operations fits their containers.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::RoaringBitmap;
+///
+/// let mut a: RoaringBitmap = (0..10_000).collect();
+/// a.insert(1 << 20);
+/// let stats = a.stats();
+/// assert_eq!((stats.arrays, stats.bitmaps), (1, 1));
+///
+/// let b: RoaringBitmap = (5_000..6_000).chain(Some(1 << 20)).collect();
+/// a.intersect_with(&b);
+/// assert_eq!(a.len(), 1_001);
+/// assert!(a.contains(1 << 20));
+/// ```
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct RoaringBitmap {
+    // The containers of the chunks that have values, sorted by the upper 16
+    // bits of their values.
+    chunks: Vec<(u16, Container)>,
+}
+
+impl fmt::Debug for RoaringBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+fn split(value: u32) -> (u16, u16) {
+    ((value >> 16) as u16, value as u16)
+}
+
+impl RoaringBitmap {
+    /// Creates an empty RoaringBitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let set = RoaringBitmap::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of values in the set.
+    ///
+    /// This operation should compute in O(c) time, where c is the number of
+    /// chunks that have values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let set: RoaringBitmap = (0..5).collect();
+    /// assert_eq!(set.len(), 5);
+    /// ```
+    pub fn len(&self) -> u64 {
+        self.chunks.iter().map(|(_, c)| c.len() as u64).sum()
+    }
+
+    /// Returns true if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut set = RoaringBitmap::new();
+    /// assert!(set.is_empty());
+    /// set.insert(1);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Inserts the value. Returns true if it was not already in the set.
+    ///
+    /// This operation should compute in O(log(c) + 4096) time, for moving
+    /// the values of an array or turning it into a bitmap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut set = RoaringBitmap::new();
+    /// assert!(set.insert(7));
+    /// assert!(!set.insert(7));
+    /// ```
+    pub fn insert(&mut self, value: u32) -> bool {
+        let (high, low) = split(value);
+        match self.chunks.binary_search_by_key(&high, |&(h, _)| h) {
+            Ok(i) => self.chunks[i].1.insert(low),
+            Err(i) => {
+                self.chunks
+                    .insert(i, (high, Container::Array(alloc::vec![low])));
+                true
+            }
+        }
+    }
+
+    /// Removes the value. Returns true if it was in the set.
+    ///
+    /// This operation should compute in O(log(c) + 4096) time, for moving
+    /// the values of an array or turning a bitmap into one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut set = RoaringBitmap::new();
+    /// set.insert(7);
+    /// assert!(set.remove(7));
+    /// assert!(!set.remove(7));
+    /// ```
+    pub fn remove(&mut self, value: u32) -> bool {
+        let (high, low) = split(value);
+        match self.chunks.binary_search_by_key(&high, |&(h, _)| h) {
+            Ok(i) => {
+                let removed = self.chunks[i].1.remove(low);
+                if self.chunks[i].1.len() == 0 {
+                    self.chunks.remove(i);
+                }
+                removed
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Returns true if the value is in the set.
+    ///
+    /// This operation should compute in O(log(c) + log(4096)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let set: RoaringBitmap = (0..100).step_by(3).collect();
+    /// assert!(set.contains(99));
+    /// assert!(!set.contains(98));
+    /// ```
+    pub fn contains(&self, value: u32) -> bool {
+        let (high, low) = split(value);
+        match self.chunks.binary_search_by_key(&high, |&(h, _)| h) {
+            Ok(i) => self.chunks[i].1.contains(low),
+            Err(_) => false,
+        }
+    }
+
+    /// Adds the values of the other set to this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut a: RoaringBitmap = (0..3).collect();
+    /// let b: RoaringBitmap = (2..5).collect();
+    /// a.union_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    /// ```
+    pub fn union_with(&mut self, other: &Self) {
+        let mut chunks =
+            Vec::with_capacity(self.chunks.len() + other.chunks.len());
+        let mut a = mem::take(&mut self.chunks).into_iter().peekable();
+        let mut b = other.chunks.iter().peekable();
+        loop {
+            match (a.peek(), b.peek()) {
+                (Some(&(x, _)), Some(&&(y, ref cy))) => {
+                    if x < y {
+                        chunks.extend(a.next());
+                    } else if y < x {
+                        chunks.push((y, cy.clone()));
+                        b.next();
+                    } else {
+                        let (_, cx) = a.next().unwrap();
+                        chunks.push((x, cx.union(cy)));
+                        b.next();
+                    }
+                }
+                (Some(_), None) => chunks.extend(a.next()),
+                (None, Some(&&(y, ref cy))) => {
+                    chunks.push((y, cy.clone()));
+                    b.next();
+                }
+                (None, None) => break,
+            }
+        }
+        self.chunks = chunks;
+    }
+
+    /// Keeps the values that are also in the other set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut a: RoaringBitmap = (0..3).collect();
+    /// let b: RoaringBitmap = (2..5).collect();
+    /// a.intersect_with(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [2]);
+    /// ```
+    pub fn intersect_with(&mut self, other: &Self) {
+        let chunks = mem::take(&mut self.chunks);
+        self.chunks = chunks
+            .into_iter()
+            .filter_map(|(high, container)| {
+                let i = other
+                    .chunks
+                    .binary_search_by_key(&high, |&(h, _)| h)
+                    .ok()?;
+                let container = container.intersection(&other.chunks[i].1);
+                if container.len() == 0 {
+                    None
+                } else {
+                    Some((high, container))
+                }
+            })
+            .collect();
+    }
+
+    /// Returns the number of chunks stored in each kind of container.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let mut set: RoaringBitmap = (0..4096).collect();
+    /// assert_eq!(set.stats().arrays, 1);
+    /// set.insert(4096);
+    /// assert_eq!(set.stats().bitmaps, 1);
+    /// set.remove(0);
+    /// assert_eq!(set.stats().arrays, 1);
+    /// ```
+    pub fn stats(&self) -> RoaringBitmapStats {
+        let bitmaps = self
+            .chunks
+            .iter()
+            .filter(|(_, c)| matches!(c, Container::Bitmap(..)))
+            .count();
+        RoaringBitmapStats {
+            arrays: self.chunks.len() - bitmaps,
+            bitmaps,
+        }
+    }
+
+    /// Returns an iterator visiting the values of the set in increasing
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::RoaringBitmap;
+    ///
+    /// let set: RoaringBitmap = [70_000, 3, 1].iter().copied().collect();
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [1, 3, 70_000]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            chunks: self.chunks.iter(),
+            current: None,
+        }
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u32> for RoaringBitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a RoaringBitmap {
+    type Item = u32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The number of chunks of a [`RoaringBitmap`] stored in each kind of
+/// container.
+///
+/// This struct is created by [`RoaringBitmap::stats()`].
+///
+/// [`RoaringBitmap`]: crate::containers::RoaringBitmap
+/// [`RoaringBitmap::stats()`]: crate::containers::RoaringBitmap::stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoaringBitmapStats {
+    /// Number of chunks stored as sorted arrays.
+    pub arrays: usize,
+    /// Number of chunks stored as bitmaps.
+    pub bitmaps: usize,
+}
+
+/// An iterator over the values of a RoaringBitmap.
+///
+/// This struct is created by [`RoaringBitmap::iter()`]. See its
+/// documentation for more.
+///
+/// [`RoaringBitmap::iter()`]: crate::containers::RoaringBitmap::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    chunks: slice::Iter<'a, (u16, Container)>,
+    current: Option<(u32, ContainerIter<'a>)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((high, iter)) = &mut self.current {
+                if let Some(low) = iter.next() {
+                    return Some(*high << 16 | low as u32);
+                }
+            }
+            let (high, container) = self.chunks.next()?;
+            self.current = Some(((*high).into(), container.iter()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeSet;
+
+    // Values in a few chunks, dense enough for some of them to be bitmaps.
+    fn random(rng: &mut SplitMix64, n: usize) -> BTreeSet<u32> {
+        (0..n)
+            .map(|_| {
+                let high = (rng.next_u64() % 3) as u32;
+                let low = (rng.next_u64() % (1 << (8 + high * 4))) as u32;
+                high << 16 | low
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = SplitMix64::new(67);
+        for n in [0, 10, 5_000, 20_000] {
+            let sa = random(&mut rng, n);
+            let sb = random(&mut rng, n);
+            let a: RoaringBitmap = sa.iter().copied().collect();
+            let b: RoaringBitmap = sb.iter().copied().collect();
+            assert_eq!(a.len(), sa.len() as u64);
+            assert!(a.iter().eq(sa.iter().copied()));
+
+            let mut union = a.clone();
+            union.union_with(&b);
+            assert!(union.iter().eq(sa.union(&sb).copied()));
+            assert_eq!(union, sa.union(&sb).copied().collect());
+            let mut inter = a.clone();
+            inter.intersect_with(&b);
+            assert!(inter.iter().eq(sa.intersection(&sb).copied()));
+            assert_eq!(inter, sa.intersection(&sb).copied().collect());
+        }
+    }
+
+    #[test]
+    fn containers_adapt() {
+        let mut set = RoaringBitmap::new();
+        for v in 0..=ARRAY_MAX as u32 {
+            set.insert(v * 2);
+        }
+        assert_eq!(
+            set.stats(),
+            RoaringBitmapStats {
+                arrays: 0,
+                bitmaps: 1
+            }
+        );
+        for v in 0..=ARRAY_MAX as u32 {
+            assert!(set.contains(v * 2));
+            assert!(!set.contains(v * 2 + 1));
+        }
+        set.remove(0);
+        assert_eq!(
+            set.stats(),
+            RoaringBitmapStats {
+                arrays: 1,
+                bitmaps: 0
+            }
+        );
+        for v in 1..=ARRAY_MAX as u32 {
+            assert!(set.remove(v * 2));
+        }
+        assert!(set.is_empty());
+        assert_eq!(
+            set.stats(),
+            RoaringBitmapStats {
+                arrays: 0,
+                bitmaps: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,677 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+/// The index of a missing node.
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The number of nodes in the subtree rooted at this node.
+    size: usize,
+    child: [usize; 2],
+}
+
+/// An ordered map implemented with a scapegoat tree.
+///
+/// A scapegoat tree is a binary search tree that keeps no balance information
+/// in its nodes, and is instead rebuilt in parts when it gets too deep. For a
+/// parameter α between 1/2 and 1, a node is α-weight-balanced if neither of
+/// its subtrees holds more than α times as many nodes as the node's own
+/// subtree. When an insertion lands deeper than log<sub>1/α</sub>(n), some
+/// ancestor of the new node is not α-weight-balanced. The lowest such
+/// ancestor, the scapegoat, has its subtree rebuilt into a perfectly balanced
+/// one. When removals shrink the tree below α times its largest size since
+/// the last full rebuild, the whole tree is rebuilt. All the operations take
+/// O(log(n)) amortized time.
+///
+/// The value of α trades the height of the tree against how often it is
+/// rebuilt: an α close to 1/2 keeps the tree almost perfectly balanced at the
+/// cost of frequent rebuilds, while an α close to 1 allows deeper trees that
+/// are rarely rebuilt. [`ScapegoatTree::height()`] and
+/// [`ScapegoatTree::rebuilds()`] show both sides of the trade-off.
+///
+/// [`ScapegoatTree::height()`]: crate::containers::ScapegoatTree::height
+/// [`ScapegoatTree::rebuilds()`]: crate::containers::ScapegoatTree::rebuilds
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::ScapegoatTree;
+///
+/// let mut tight = ScapegoatTree::with_alpha(0.55);
+/// let mut loose = ScapegoatTree::with_alpha(0.9);
+/// for k in 0..1000 {
+///     tight.insert(k, ());
+///     loose.insert(k, ());
+/// }
+///
+/// assert!(tight.height() < loose.height());
+/// assert!(tight.rebuilds() > loose.rebuilds());
+/// ```
+pub struct ScapegoatTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    root: usize,
+    alpha: f64,
+    // The largest size of the tree since it was last rebuilt as a whole.
+    max_len: usize,
+    rebuilds: usize,
+}
+
+impl<K: Ord, V> Default for ScapegoatTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ScapegoatTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> ScapegoatTree<K, V> {
+    /// Returns the number of entries in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, "a");
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the balance parameter α of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let tree: ScapegoatTree<i32, ()> = ScapegoatTree::with_alpha(0.75);
+    /// assert_eq!(tree.alpha(), 0.75);
+    /// ```
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Returns the number of times a subtree, or the whole tree, has been
+    /// rebuilt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::with_alpha(0.5);
+    /// tree.insert(1, ());
+    /// tree.insert(2, ());
+    /// assert_eq!(tree.rebuilds(), 0);
+    /// tree.insert(3, ());
+    /// assert_eq!(tree.rebuilds(), 1);
+    /// ```
+    pub fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+
+    /// Returns the number of nodes on the longest path from the root down to a
+    /// leaf, or 0 if the tree is empty.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::with_alpha(0.5);
+    /// tree.extend((0..7).map(|k| (k, ())));
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = Vec::new();
+        if self.root != NIL {
+            stack.push((self.root, 1));
+        }
+        while let Some((i, depth)) = stack.pop() {
+            height = height.max(depth);
+            for &child in &self.nodes[i].child {
+                if child != NIL {
+                    stack.push((child, depth + 1));
+                }
+            }
+        }
+        height
+    }
+
+    /// Returns an iterator visiting all the entries of the tree in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let tree: ScapegoatTree<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = tree.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            nodes: &self.nodes,
+            stack: Vec::new(),
+        };
+        iter.push_left_spine(self.root);
+        iter
+    }
+
+    fn size(&self, i: usize) -> usize {
+        if i == NIL {
+            0
+        } else {
+            self.nodes[i].size
+        }
+    }
+
+    /// Makes `new` the child of `parent` in place of `old`, or the root if
+    /// `parent` is missing.
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if parent == NIL {
+            self.root = new;
+        } else if self.nodes[parent].child[0] == old {
+            self.nodes[parent].child[0] = new;
+        } else {
+            self.nodes[parent].child[1] = new;
+        }
+    }
+
+    /// Rebuilds the subtree rooted at `i`, whose parent is `parent`, into a
+    /// perfectly balanced one.
+    fn rebuild(&mut self, i: usize, parent: usize) {
+        let mut order = Vec::with_capacity(self.size(i));
+        let mut stack = Vec::new();
+        let mut next = i;
+        while next != NIL || !stack.is_empty() {
+            while next != NIL {
+                stack.push(next);
+                next = self.nodes[next].child[0];
+            }
+            let top = stack.pop().expect("stack is not empty");
+            order.push(top);
+            next = self.nodes[top].child[1];
+        }
+        let root = self.build(&order);
+        self.replace_child(parent, i, root);
+        self.rebuilds += 1;
+    }
+
+    /// Links the nodes, given in order, into a perfectly balanced tree and
+    /// returns its root.
+    fn build(&mut self, order: &[usize]) -> usize {
+        if order.is_empty() {
+            return NIL;
+        }
+        let mid = order.len() / 2;
+        let left = self.build(&order[..mid]);
+        let right = self.build(&order[mid + 1..]);
+        let node = &mut self.nodes[order[mid]];
+        node.child = [left, right];
+        node.size = order.len();
+        order[mid]
+    }
+
+    /// Returns true if a node at `depth` edges below the root is too deep for
+    /// a tree of `len` nodes, that is if (1/α)^depth > len.
+    fn too_deep(&self, depth: usize, len: usize) -> bool {
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            weight /= self.alpha;
+            if weight > len as f64 {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<K: Ord, V> ScapegoatTree<K, V> {
+    /// Creates an empty ScapegoatTree, with α set to 2/3.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self::with_alpha(2.0 / 3.0)
+    }
+
+    /// Creates an empty ScapegoatTree with the given α.
+    ///
+    /// # Panics
+    ///
+    /// Panics if α is not at least 0.5 and less than 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::with_alpha(0.6);
+    /// tree.insert(1, "a");
+    /// ```
+    pub fn with_alpha(alpha: f64) -> Self {
+        assert!(
+            (0.5..1.0).contains(&alpha),
+            "Alpha must be at least 0.5 and less than 1"
+        );
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            alpha,
+            max_len: 0,
+            rebuilds: 0,
+        }
+    }
+
+    /// Inserts a key-value pair into the tree and returns the old value of the
+    /// key, or None if the key was not in the tree.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// assert_eq!(tree.insert(1, "a"), None);
+    /// assert_eq!(tree.insert(1, "b"), Some("a"));
+    /// assert_eq!(tree.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut path = Vec::new();
+        let mut dir = 0;
+        let mut i = self.root;
+        while i != NIL {
+            dir = match key.cmp(&self.nodes[i].key) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => {
+                    return Some(mem::replace(&mut self.nodes[i].value, value));
+                }
+            };
+            path.push(i);
+            i = self.nodes[i].child[dir];
+        }
+
+        let z = self.nodes.len();
+        self.nodes.push(Node {
+            key,
+            value,
+            size: 1,
+            child: [NIL, NIL],
+        });
+        match path.last() {
+            None => self.root = z,
+            Some(&parent) => self.nodes[parent].child[dir] = z,
+        }
+        for &ancestor in &path {
+            self.nodes[ancestor].size += 1;
+        }
+        self.max_len = self.max_len.max(self.len());
+
+        if self.too_deep(path.len(), self.len()) {
+            // Some ancestor of the new node is not α-weight-balanced, and the
+            // lowest one is the scapegoat.
+            let mut child = z;
+            for (depth, &ancestor) in path.iter().enumerate().rev() {
+                let size = self.nodes[ancestor].size as f64;
+                if self.size(child) as f64 > self.alpha * size {
+                    let parent = if depth == 0 { NIL } else { path[depth - 1] };
+                    self.rebuild(ancestor, parent);
+                    break;
+                }
+                child = ancestor;
+            }
+        }
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the tree.
+    ///
+    /// The key may be any borrowed form of the tree's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.get(&1), Some(&"a"));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&self.nodes[i].value)
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the tree.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, 10);
+    /// *tree.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.nodes[i].value)
+    }
+
+    /// Returns true if the key is in the tree.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, "a");
+    /// assert!(tree.contains_key(&1));
+    /// assert!(!tree.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes the key from the tree and returns its value, or None if the key
+    /// was not in the tree.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::ScapegoatTree;
+    ///
+    /// let mut tree = ScapegoatTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.remove(&1), Some("a"));
+    /// assert_eq!(tree.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut path = Vec::new();
+        let mut z = self.root;
+        loop {
+            if z == NIL {
+                return None;
+            }
+            let dir = match key.cmp(self.nodes[z].key.borrow()) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => break,
+            };
+            path.push(z);
+            z = self.nodes[z].child[dir];
+        }
+
+        // A node with two children trades its entry with its successor, which
+        // has no left child and is unlinked instead.
+        let mut y = z;
+        if self.nodes[z].child[0] != NIL && self.nodes[z].child[1] != NIL {
+            path.push(z);
+            y = self.nodes[z].child[1];
+            while self.nodes[y].child[0] != NIL {
+                path.push(y);
+                y = self.nodes[y].child[0];
+            }
+            self.swap_entries(z, y);
+        }
+        let [left, right] = self.nodes[y].child;
+        let child = if left == NIL { right } else { left };
+        self.replace_child(path.last().copied().unwrap_or(NIL), y, child);
+        for &ancestor in &path {
+            self.nodes[ancestor].size -= 1;
+        }
+
+        // The last node moves into the slot of `y`, and is found by its key to
+        // update the link from its parent.
+        let last = self.nodes.len() - 1;
+        if y != last {
+            let parent = self.parent_of(&self.nodes[last].key);
+            self.replace_child(parent, last, y);
+        }
+        let node = self.nodes.swap_remove(y);
+
+        if (self.len() as f64) < self.alpha * self.max_len as f64 {
+            if self.root != NIL {
+                self.rebuild(self.root, NIL);
+            }
+            self.max_len = self.len();
+        }
+        Some(node.value)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut i = self.root;
+        while i != NIL {
+            i = match key.cmp(self.nodes[i].key.borrow()) {
+                Ordering::Less => self.nodes[i].child[0],
+                Ordering::Greater => self.nodes[i].child[1],
+                Ordering::Equal => return Some(i),
+            };
+        }
+        None
+    }
+
+    /// Returns the parent of the node holding the key, which must be in the
+    /// tree, or NIL if it is the root.
+    fn parent_of(&self, key: &K) -> usize {
+        let mut parent = NIL;
+        let mut i = self.root;
+        loop {
+            let dir = match key.cmp(&self.nodes[i].key) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => return parent,
+            };
+            parent = i;
+            i = self.nodes[i].child[dir];
+        }
+    }
+
+    /// Swaps the keys and values of the nodes at `a` and `b`.
+    fn swap_entries(&mut self, a: usize, b: usize) {
+        let (lo, hi) = (a.min(b), a.max(b));
+        let (head, tail) = self.nodes.split_at_mut(hi);
+        mem::swap(&mut head[lo].key, &mut tail[0].key);
+        mem::swap(&mut head[lo].value, &mut tail[0].value);
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for ScapegoatTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for ScapegoatTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a ScapegoatTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a ScapegoatTree, in ascending order of
+/// their keys.
+///
+/// This struct is created by [`ScapegoatTree::iter()`]. See its documentation
+/// for more.
+///
+/// [`ScapegoatTree::iter()`]: crate::containers::ScapegoatTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    // The nodes whose left subtrees have been visited but which have not been
+    // yielded themselves, with the next one on top.
+    stack: Vec<usize>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut i: usize) {
+        while i != NIL {
+            self.stack.push(i);
+            i = self.nodes[i].child[0];
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.stack.pop()?;
+        let node = &self.nodes[i];
+        self.push_left_spine(node.child[1]);
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the order of the keys and the sizes, and returns the size of the
+    /// subtree rooted at `i`.
+    fn check<K: Ord, V>(
+        tree: &ScapegoatTree<K, V>,
+        i: usize,
+        lo: Option<&K>,
+        hi: Option<&K>,
+    ) -> usize {
+        if i == NIL {
+            return 0;
+        }
+        let node = &tree.nodes[i];
+        assert!(lo.is_none_or(|lo| *lo < node.key));
+        assert!(hi.is_none_or(|hi| node.key < *hi));
+        let size = 1
+            + check(tree, node.child[0], lo, Some(&node.key))
+            + check(tree, node.child[1], Some(&node.key), hi);
+        assert_eq!(node.size, size);
+        size
+    }
+
+    #[test]
+    fn insert_stays_shallow() {
+        for &alpha in &[0.5, 0.6, 0.75, 0.9] {
+            let mut tree = ScapegoatTree::with_alpha(alpha);
+            for k in 0..500 {
+                tree.insert(k, ());
+                // No node is deeper than log_{1/alpha}(n).
+                assert!(!tree.too_deep(tree.height() - 1, tree.len()));
+            }
+            assert_eq!(check(&tree, tree.root, None, None), 500);
+        }
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut tree = ScapegoatTree::new();
+        let mut expected = BTreeMap::new();
+        for k in (0..500).map(|k| (k * 37) % 499) {
+            assert_eq!(tree.insert(k, k), expected.insert(k, k));
+        }
+        for k in (0..500).map(|k| (k * 53) % 509) {
+            assert_eq!(tree.remove(&k), expected.remove(&k));
+            assert_eq!(check(&tree, tree.root, None, None), tree.len());
+        }
+        assert!(tree.iter().eq(expected.iter()));
+        assert_eq!(tree.len(), expected.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "Alpha must be at least 0.5 and less than 1")]
+    fn invalid_alpha() {
+        let _: ScapegoatTree<i32, ()> = ScapegoatTree::with_alpha(1.0);
+    }
+}
@@ -0,0 +1,375 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Range, RangeBounds};
+
+use super::ops::{self, Monoid};
+
+/// A sequence that combines the elements of any range, and assigns a value to
+/// any range, in O(log(n)) time.
+///
+/// The elements are combined with the operation `Op`, which is a [`Monoid`]
+/// over `T`. Every node of the tree holds the combination of a range of the
+/// sequence, the root holding the whole sequence and the leaves single
+/// elements, so a range is the combination of O(log(n)) nodes.
+///
+/// Assigning a value to a range is done lazily: the nodes that cover the
+/// range are updated and tagged with the value, and the tag is only pushed
+/// down to their children when a later operation needs to look inside them.
+/// The combination of `k` copies of a value is found with O(log(k))
+/// combinations, which works for any monoid.
+///
+/// [`Monoid`]: crate::containers::Monoid
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{Min, SegmentTree, Sum};
+///
+/// let mut sums = SegmentTree::<_, Sum>::new(&[1, 2, 3, 4, 5]);
+/// assert_eq!(sums.query(1..4), 9);
+/// sums.set_range(..3, 10);
+/// assert_eq!(sums.query(..), 39);
+///
+/// let mut mins = SegmentTree::<_, Min>::new(&[5, 3, 8, 1]);
+/// assert_eq!(mins.query(..3), 3);
+/// mins.set(1, 7);
+/// assert_eq!(mins.query(..3), 5);
+/// ```
+pub struct SegmentTree<T, Op> {
+    // The node at index 1 covers the whole sequence, and the children of the
+    // node at index i are at 2i and 2i + 1.
+    tree: Vec<T>,
+    // The value assigned to every element covered by the node, which has not
+    // been pushed down to its children yet.
+    lazy: Vec<Option<T>>,
+    len: usize,
+    op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op> Clone for SegmentTree<T, Op> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            lazy: self.lazy.clone(),
+            len: self.len,
+            op: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug, Op: Monoid<T>> fmt::Debug for SegmentTree<T, Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len).map(|i| self.query(i..=i)))
+            .finish()
+    }
+}
+
+impl<T, Op> SegmentTree<T, Op> {
+    /// Returns the number of elements in the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{SegmentTree, Sum};
+    ///
+    /// let tree = SegmentTree::<_, Sum>::new(&[1, 2, 3]);
+    /// assert_eq!(tree.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the sequence has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{SegmentTree, Sum};
+    ///
+    /// let tree = SegmentTree::<i32, Sum>::new(&[]);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> SegmentTree<T, Op> {
+    /// Creates a SegmentTree over a copy of the values.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Max, SegmentTree};
+    ///
+    /// let tree = SegmentTree::<_, Max>::new(&[3, 1, 4, 1, 5]);
+    /// assert_eq!(tree.query(..), 5);
+    /// ```
+    pub fn new(values: &[T]) -> Self {
+        let size = 2 * values.len().next_power_of_two();
+        let mut tree = Self {
+            tree: vec![Op::identity(); size],
+            lazy: vec![None; size],
+            len: values.len(),
+            op: PhantomData,
+        };
+        if !values.is_empty() {
+            tree.build(1, 0..values.len(), values);
+        }
+        tree
+    }
+
+    /// Returns the combination of the elements in the range, or the identity
+    /// of the operation if the range is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or if its start is greater than
+    /// its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{SegmentTree, Sum};
+    ///
+    /// let tree = SegmentTree::<_, Sum>::new(&[1, 2, 3, 4]);
+    /// assert_eq!(tree.query(1..=2), 5);
+    /// assert_eq!(tree.query(2..2), 0);
+    /// ```
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let range = ops::to_range(range, self.len);
+        if range.is_empty() {
+            return Op::identity();
+        }
+        self.query_node(1, 0..self.len, &range)
+    }
+
+    /// Sets the element at the index to the value.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{SegmentTree, Sum};
+    ///
+    /// let mut tree = SegmentTree::<_, Sum>::new(&[1, 2, 3]);
+    /// tree.set(0, 10);
+    /// assert_eq!(tree.query(..), 15);
+    /// ```
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "Out of bounds access");
+        self.set_range(index..=index, value);
+    }
+
+    /// Sets every element in the range to the value.
+    ///
+    /// This operation should compute in O(log(n) * log(k)) time, where k is
+    /// the length of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or if its start is greater than
+    /// its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{SegmentTree, Sum};
+    ///
+    /// let mut tree = SegmentTree::<_, Sum>::new(&[1, 2, 3, 4]);
+    /// tree.set_range(1..3, 0);
+    /// assert_eq!(tree.query(..), 5);
+    /// ```
+    pub fn set_range<R: RangeBounds<usize>>(&mut self, range: R, value: T) {
+        let range = ops::to_range(range, self.len);
+        if range.is_empty() {
+            return;
+        }
+        self.set_node(1, 0..self.len, &range, &value);
+    }
+
+    fn build(&mut self, node: usize, span: Range<usize>, values: &[T]) {
+        if span.len() == 1 {
+            self.tree[node] = values[span.start].clone();
+            return;
+        }
+        let mid = span.start + span.len() / 2;
+        self.build(2 * node, span.start..mid, values);
+        self.build(2 * node + 1, mid..span.end, values);
+        self.pull(node);
+    }
+
+    fn query_node(
+        &self,
+        node: usize,
+        span: Range<usize>,
+        range: &Range<usize>,
+    ) -> T {
+        let start = span.start.max(range.start);
+        let end = span.end.min(range.end);
+        if start >= end {
+            return Op::identity();
+        }
+        if let Some(value) = &self.lazy[node] {
+            return ops::repeat::<T, Op>(value, end - start);
+        }
+        if start == span.start && end == span.end {
+            return self.tree[node].clone();
+        }
+        let mid = span.start + span.len() / 2;
+        let left = self.query_node(2 * node, span.start..mid, range);
+        let right = self.query_node(2 * node + 1, mid..span.end, range);
+        Op::combine(&left, &right)
+    }
+
+    fn set_node(
+        &mut self,
+        node: usize,
+        span: Range<usize>,
+        range: &Range<usize>,
+        value: &T,
+    ) {
+        if span.end <= range.start || range.end <= span.start {
+            return;
+        }
+        if range.start <= span.start && span.end <= range.end {
+            self.assign(node, span.len(), value);
+            return;
+        }
+        let mid = span.start + span.len() / 2;
+        self.push(node, &span);
+        self.set_node(2 * node, span.start..mid, range, value);
+        self.set_node(2 * node + 1, mid..span.end, range, value);
+        self.pull(node);
+    }
+
+    fn assign(&mut self, node: usize, len: usize, value: &T) {
+        self.tree[node] = ops::repeat::<T, Op>(value, len);
+        self.lazy[node] = if len > 1 { Some(value.clone()) } else { None };
+    }
+
+    /// Moves the pending assignment of the node down to its children.
+    fn push(&mut self, node: usize, span: &Range<usize>) {
+        if let Some(value) = self.lazy[node].take() {
+            let left = span.len() / 2;
+            self.assign(2 * node, left, &value);
+            self.assign(2 * node + 1, span.len() - left, &value);
+        }
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.tree[node] =
+            Op::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::ops::{Max, Min, Sum};
+    use crate::containers::rng::SplitMix64;
+    use alloc::string::{String, ToString};
+
+    #[derive(Debug)]
+    struct Concat;
+
+    impl Monoid<String> for Concat {
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            let mut s = a.clone();
+            s.push_str(b);
+            s
+        }
+    }
+
+    fn random_range(rng: &mut SplitMix64, len: usize) -> Range<usize> {
+        let a = rng.next_u64() as usize % (len + 1);
+        let b = rng.next_u64() as usize % (len + 1);
+        a.min(b)..a.max(b)
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(11);
+        let mut values: Vec<i64> =
+            (0..100).map(|_| (rng.next_u64() % 1000) as i64).collect();
+        let mut sums = SegmentTree::<_, Sum>::new(&values);
+        let mut mins = SegmentTree::<_, Min>::new(&values);
+        let mut maxs = SegmentTree::<_, Max>::new(&values);
+        for _ in 0..2000 {
+            let range = random_range(&mut rng, values.len());
+            let value = (rng.next_u64() % 1000) as i64;
+            match rng.next_u64() % 3 {
+                0 => {
+                    let i = range.start.min(values.len() - 1);
+                    values[i] = value;
+                    sums.set(i, value);
+                    mins.set(i, value);
+                    maxs.set(i, value);
+                }
+                1 => {
+                    values[range.clone()].iter_mut().for_each(|v| *v = value);
+                    sums.set_range(range.clone(), value);
+                    mins.set_range(range.clone(), value);
+                    maxs.set_range(range, value);
+                }
+                _ => {
+                    let slice = &values[range.clone()];
+                    assert_eq!(sums.query(range.clone()), slice.iter().sum());
+                    let min = slice.iter().copied().min().unwrap_or(i64::MAX);
+                    assert_eq!(mins.query(range.clone()), min);
+                    let max = slice.iter().copied().max().unwrap_or(i64::MIN);
+                    assert_eq!(maxs.query(range), max);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_order() {
+        let words: Vec<_> = ["a", "b", "c", "d", "e", "f", "g"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut tree = SegmentTree::<_, Concat>::new(&words);
+        assert_eq!(tree.query(..), "abcdefg");
+        tree.set_range(1..5, "x".to_string());
+        assert_eq!(tree.query(..), "axxxxfg");
+        tree.set(3, "y".to_string());
+        assert_eq!(tree.query(2..6), "xyxf");
+        assert_eq!(
+            format!("{:?}", tree),
+            r#"["a", "x", "x", "y", "x", "f", "g"]"#
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let mut tree = SegmentTree::<u32, Sum>::new(&[]);
+        assert_eq!(tree.query(..), 0);
+        tree.set_range(.., 1);
+        assert_eq!(tree.query(..), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn set_out_of_bounds() {
+        let mut tree = SegmentTree::<u32, Sum>::new(&[1, 2]);
+        tree.set(2, 0);
+    }
+}
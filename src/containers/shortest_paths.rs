@@ -0,0 +1,470 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use super::binary_heap::BinaryHeap;
+use super::graph::{Graph, NodeIndex};
+use super::ops::{Monoid, Sum};
+
+/// The distances from a source to the nodes of a [`Graph`], and the tree of
+/// the shortest paths that reach them.
+///
+/// This struct is created by [`Graph::dijkstra()`] and
+/// [`Graph::bellman_ford()`]. See their documentation for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::dijkstra()`]: crate::containers::Graph::dijkstra
+/// [`Graph::bellman_ford()`]: crate::containers::Graph::bellman_ford
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W> {
+    source: NodeIndex,
+    distances: Vec<Option<W>>,
+    // The node before each node on its shortest path.
+    predecessors: Vec<Option<NodeIndex>>,
+}
+
+impl<W> ShortestPaths<W> {
+    /// Returns the node the paths start from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph: Graph<(), u32> = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// assert_eq!(graph.dijkstra(a).source(), a);
+    /// ```
+    pub fn source(&self) -> NodeIndex {
+        self.source
+    }
+
+    /// Returns the length of the shortest path from the source to the node,
+    /// or None if the node is not reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let b = graph.add_node(());
+    /// graph.add_edge(a, b, 3);
+    /// let paths = graph.dijkstra(a);
+    /// assert_eq!(paths.distance(a), Some(&0));
+    /// assert_eq!(paths.distance(b), Some(&3));
+    /// assert_eq!(graph.dijkstra(b).distance(a), None);
+    /// ```
+    pub fn distance(&self, node: NodeIndex) -> Option<&W> {
+        self.distances.get(node.index())?.as_ref()
+    }
+
+    /// Returns the node before the node on its shortest path from the
+    /// source, or None for the source and the nodes that are not reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let b = graph.add_node(());
+    /// graph.add_edge(a, b, 3);
+    /// let paths = graph.dijkstra(a);
+    /// assert_eq!(paths.predecessor(b), Some(a));
+    /// assert_eq!(paths.predecessor(a), None);
+    /// ```
+    pub fn predecessor(&self, node: NodeIndex) -> Option<NodeIndex> {
+        *self.predecessors.get(node.index())?
+    }
+
+    /// Returns the nodes of the shortest path from the source to the node,
+    /// both included, or None if the node is not reachable.
+    ///
+    /// This operation should compute in O(k) time, where k is the number of
+    /// nodes on the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let b = graph.add_node(());
+    /// let c = graph.add_node(());
+    /// graph.add_edge(a, b, 1);
+    /// graph.add_edge(b, c, 1);
+    /// graph.add_edge(a, c, 5);
+    /// assert_eq!(graph.dijkstra(a).path_to(c), Some(vec![a, b, c]));
+    /// ```
+    pub fn path_to(&self, node: NodeIndex) -> Option<Vec<NodeIndex>> {
+        self.distance(node)?;
+        let mut path = vec![node];
+        let mut node = node;
+        while let Some(previous) = self.predecessor(node) {
+            path.push(previous);
+            node = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// A cycle of a [`Graph`] whose edges have a negative total weight, which
+/// makes the paths through it as short as wanted.
+///
+/// This struct is returned by [`Graph::bellman_ford()`]. See its
+/// documentation for more.
+///
+/// [`Graph`]: crate::containers::Graph
+/// [`Graph::bellman_ford()`]: crate::containers::Graph::bellman_ford
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    nodes: Vec<NodeIndex>,
+}
+
+impl NegativeCycle {
+    /// Returns the nodes of the cycle, in the order of its edges. The last
+    /// node has an edge to the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node(());
+    /// let b = graph.add_node(());
+    /// graph.add_edge(a, b, 1);
+    /// graph.add_edge(b, a, -2);
+    /// let cycle = graph.bellman_ford(a).unwrap_err();
+    /// assert_eq!(cycle.nodes().len(), 2);
+    /// ```
+    pub fn nodes(&self) -> &[NodeIndex] {
+        &self.nodes
+    }
+}
+
+impl<N, W> Graph<N, W>
+where
+    W: Clone + Ord,
+    Sum: Monoid<W>,
+{
+    /// Returns the shortest paths from the source to all the nodes, where
+    /// the data of an edge is its length, with Dijkstra's algorithm.
+    ///
+    /// The nodes are settled in the order of their distance to the source,
+    /// using a [`BinaryHeap`] of the reached nodes in which a node is pushed
+    /// again every time a shorter path to it is found. A node taken out of
+    /// the heap after it was settled is skipped.
+    ///
+    /// This operation should compute in O((n + e) log(e)) time.
+    ///
+    /// [`BinaryHeap`]: crate::containers::BinaryHeap
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source is not in the graph, or if an edge reachable from
+    /// the source has a negative length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_undirected();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let c = graph.add_node('c');
+    /// graph.add_edge(a, b, 7);
+    /// graph.add_edge(a, c, 2);
+    /// graph.add_edge(c, b, 3);
+    /// let paths = graph.dijkstra(a);
+    /// assert_eq!(paths.distance(b), Some(&5));
+    /// assert_eq!(paths.predecessor(b), Some(c));
+    /// ```
+    pub fn dijkstra(&self, source: NodeIndex) -> ShortestPaths<W> {
+        assert!(self.contains_node(source), "Invalid node");
+        let zero = Sum::identity();
+        let mut paths = ShortestPaths {
+            source,
+            distances: vec![None; self.node_bound()],
+            predecessors: vec![None; self.node_bound()],
+        };
+        let mut settled = vec![false; self.node_bound()];
+        let mut heap = BinaryHeap::new();
+        paths.distances[source.index()] = Some(zero.clone());
+        heap.push(Reverse((zero.clone(), source)));
+        while let Some(Reverse((distance, node))) = heap.pop() {
+            if settled[node.index()] {
+                continue;
+            }
+            settled[node.index()] = true;
+            for (_, next, length) in self.edges(node) {
+                assert!(*length >= zero, "Negative weight");
+                let candidate = Sum::combine(&distance, length);
+                let shorter = match &paths.distances[next.index()] {
+                    Some(known) => candidate < *known,
+                    None => true,
+                };
+                if shorter {
+                    paths.distances[next.index()] = Some(candidate.clone());
+                    paths.predecessors[next.index()] = Some(node);
+                    heap.push(Reverse((candidate, next)));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Returns the shortest paths from the source to all the nodes, where
+    /// the data of an edge is its length, with the Bellman-Ford algorithm.
+    /// Unlike [`Graph::dijkstra()`], the lengths can be negative.
+    ///
+    /// Every round shortens the known paths with every edge, and stops once
+    /// a round changes nothing. Without a negative cycle, a shortest path
+    /// has fewer than n edges, so n rounds always end with a round that
+    /// changes nothing.
+    ///
+    /// This operation should compute in O(ne) time.
+    ///
+    /// [`Graph::dijkstra()`]: crate::containers::Graph::dijkstra
+    ///
+    /// # Errors
+    ///
+    /// Returns a cycle with a negative length that is reachable from the
+    /// source, in which case some paths have no shortest one. An undirected
+    /// edge with a negative length is such a cycle by itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Graph;
+    ///
+    /// let mut graph = Graph::new_directed();
+    /// let a = graph.add_node('a');
+    /// let b = graph.add_node('b');
+    /// let c = graph.add_node('c');
+    /// graph.add_edge(a, b, 4);
+    /// graph.add_edge(a, c, 5);
+    /// graph.add_edge(c, b, -3);
+    /// let paths = graph.bellman_ford(a).unwrap();
+    /// assert_eq!(paths.distance(b), Some(&2));
+    /// assert_eq!(paths.path_to(b), Some(vec![a, c, b]));
+    /// ```
+    pub fn bellman_ford(
+        &self,
+        source: NodeIndex,
+    ) -> Result<ShortestPaths<W>, NegativeCycle> {
+        assert!(self.contains_node(source), "Invalid node");
+        let mut paths = ShortestPaths {
+            source,
+            distances: vec![None; self.node_bound()],
+            predecessors: vec![None; self.node_bound()],
+        };
+        paths.distances[source.index()] = Some(Sum::identity());
+        let mut changed = None;
+        for _ in 0..self.node_count() {
+            changed = self.relax(&mut paths);
+            if changed.is_none() {
+                return Ok(paths);
+            }
+        }
+        // A node that still changes in the last round has a chain of
+        // predecessors that ends in a negative cycle, and going back n times
+        // from it lands on the cycle.
+        let mut node = changed.unwrap();
+        for _ in 0..self.node_count() {
+            node = paths.predecessors[node.index()].unwrap();
+        }
+        let mut nodes = vec![node];
+        let mut previous = paths.predecessors[node.index()].unwrap();
+        while previous != node {
+            nodes.push(previous);
+            previous = paths.predecessors[previous.index()].unwrap();
+        }
+        nodes.reverse();
+        Err(NegativeCycle { nodes })
+    }
+
+    /// Shortens the known paths with every edge, and returns the last node
+    /// whose path was shortened.
+    fn relax(&self, paths: &mut ShortestPaths<W>) -> Option<NodeIndex> {
+        let mut changed = None;
+        for node in self.node_indices() {
+            let distance = match &paths.distances[node.index()] {
+                Some(distance) => distance.clone(),
+                None => continue,
+            };
+            for (_, next, length) in self.edges(node) {
+                let candidate = Sum::combine(&distance, length);
+                let shorter = match &paths.distances[next.index()] {
+                    Some(known) => candidate < *known,
+                    None => true,
+                };
+                if shorter {
+                    paths.distances[next.index()] = Some(candidate);
+                    paths.predecessors[next.index()] = Some(node);
+                    changed = Some(next);
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    /// An edge given by its source, its target, and its length.
+    type Edge = (usize, usize, i64);
+
+    /// Returns the distances between all the nodes of a graph given by its
+    /// edges, with the Floyd-Warshall algorithm.
+    fn floyd_warshall(n: usize, edges: &[Edge]) -> Vec<Vec<Option<i64>>> {
+        let mut d = vec![vec![None; n]; n];
+        for (i, row) in d.iter_mut().enumerate() {
+            row[i] = Some(0);
+        }
+        for &(a, b, w) in edges {
+            if d[a][b].is_none_or(|known| w < known) {
+                d[a][b] = Some(w);
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    if let (Some(x), Some(y)) = (d[i][k], d[k][j]) {
+                        if d[i][j].is_none_or(|known| x + y < known) {
+                            d[i][j] = Some(x + y);
+                        }
+                    }
+                }
+            }
+        }
+        d
+    }
+
+    fn random_graph(
+        rng: &mut SplitMix64,
+        n: usize,
+        m: usize,
+        min_weight: i64,
+    ) -> (Graph<(), i64>, Vec<NodeIndex>, Vec<Edge>) {
+        let mut graph = Graph::new_directed();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        let mut edges = Vec::new();
+        for _ in 0..m {
+            let a = (rng.next_u64() % n as u64) as usize;
+            let b = (rng.next_u64() % n as u64) as usize;
+            let w = min_weight + (rng.next_u64() % 20) as i64;
+            graph.add_edge(nodes[a], nodes[b], w);
+            edges.push((a, b, w));
+        }
+        (graph, nodes, edges)
+    }
+
+    /// Checks that the distances are right, and that the predecessors give
+    /// paths of these lengths.
+    fn check_paths(
+        graph: &Graph<(), i64>,
+        paths: &ShortestPaths<i64>,
+        nodes: &[NodeIndex],
+        expected: &[Option<i64>],
+    ) {
+        for (i, &node) in nodes.iter().enumerate() {
+            assert_eq!(paths.distance(node).copied(), expected[i]);
+            if let Some(path) = paths.path_to(node) {
+                assert_eq!(path[0], paths.source());
+                let length: i64 = path
+                    .windows(2)
+                    .map(|w| {
+                        graph
+                            .edges(w[0])
+                            .filter(|&(_, next, _)| next == w[1])
+                            .map(|(_, _, &weight)| weight)
+                            .min()
+                            .unwrap()
+                    })
+                    .sum();
+                assert_eq!(Some(length), expected[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn dijkstra_matches_floyd_warshall() {
+        let mut rng = SplitMix64::new(21);
+        for _ in 0..20 {
+            let (graph, nodes, edges) = random_graph(&mut rng, 15, 40, 0);
+            let expected = floyd_warshall(15, &edges);
+            for (i, &node) in nodes.iter().enumerate() {
+                let paths = graph.dijkstra(node);
+                check_paths(&graph, &paths, &nodes, &expected[i]);
+                let paths = graph.bellman_ford(node).unwrap();
+                check_paths(&graph, &paths, &nodes, &expected[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn bellman_ford_matches_floyd_warshall() {
+        let mut rng = SplitMix64::new(22);
+        let mut cycles = 0;
+        for _ in 0..200 {
+            let (graph, nodes, edges) = random_graph(&mut rng, 8, 12, -3);
+            let expected = floyd_warshall(8, &edges);
+            for (i, &node) in nodes.iter().enumerate() {
+                // A node on a negative cycle reaches itself with a negative
+                // length.
+                let negative = (0..8).any(|j| {
+                    expected[i][j].is_some()
+                        && expected[j][j].is_some_and(|d| d < 0)
+                });
+                match graph.bellman_ford(node) {
+                    Ok(paths) => {
+                        assert!(!negative);
+                        check_paths(&graph, &paths, &nodes, &expected[i]);
+                    }
+                    Err(cycle) => {
+                        assert!(negative);
+                        cycles += 1;
+                        let nodes = cycle.nodes();
+                        let length: i64 = (0..nodes.len())
+                            .map(|k| {
+                                let next = nodes[(k + 1) % nodes.len()];
+                                graph
+                                    .edges(nodes[k])
+                                    .filter(|&(_, other, _)| other == next)
+                                    .map(|(_, _, &weight)| weight)
+                                    .min()
+                                    .unwrap()
+                            })
+                            .sum();
+                        assert!(length < 0);
+                    }
+                }
+            }
+        }
+        assert!(cycles > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Negative weight")]
+    fn dijkstra_rejects_negative_weights() {
+        let mut graph = Graph::new_directed();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, -1);
+        graph.dijkstra(a);
+    }
+}
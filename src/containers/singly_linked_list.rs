@@ -0,0 +1,497 @@
+use alloc::boxed::Box;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T> {
+    next: Link<T>,
+    data: T,
+}
+
+/// A singly-linked list with owned nodes.
+///
+/// The SinglyLinkedList allows pushing and popping elements at the front in
+/// constant time, which makes it a natural fit for a stack. Unlike
+/// [`DoublyLinkedList`], each node only knows about the node after it, so the
+/// list can be built without any unsafe code.
+///
+/// NOTE: It is almost always better to use Vec or VecDeque because array-based
+/// containers are generally faster, more memory efficient, and make better use
+/// of CPU cache.
+///
+/// [`DoublyLinkedList`]: crate::containers::DoublyLinkedList
+#[derive(Debug)]
+pub struct SinglyLinkedList<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> Default for SinglyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for SinglyLinkedList<T> {
+    /// Nodes are unlinked one by one, because the default drop of a chain of
+    /// boxes is recursive and can overflow the stack for long lists.
+    fn drop(&mut self) {
+        let mut it = self.head.take();
+        while let Some(mut node) = it {
+            it = node.next.take();
+        }
+    }
+}
+
+impl<T> SinglyLinkedList<T> {
+    /// Creates an empty SinglyLinkedList.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let list: SinglyLinkedList<u32> = SinglyLinkedList::new();
+    /// ```
+    pub const fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    /// Provides a forward iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut list: SinglyLinkedList<u32> = SinglyLinkedList::new();
+    ///
+    /// list.push_front(2);
+    /// list.push_front(1);
+    /// list.push_front(0);
+    ///
+    /// let mut iter = list.iter();
+    /// assert_eq!(iter.next(), Some(&0));
+    /// assert_eq!(iter.next(), Some(&1));
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            it: self.head.as_deref(),
+        }
+    }
+
+    /// Provides a forward iterator with mutable references.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut list: SinglyLinkedList<u32> = SinglyLinkedList::new();
+    ///
+    /// list.push_front(2);
+    /// list.push_front(1);
+    /// list.push_front(0);
+    ///
+    /// for element in list.iter_mut() {
+    ///     *element += 10;
+    /// }
+    ///
+    /// let mut iter = list.iter();
+    /// assert_eq!(iter.next(), Some(&10));
+    /// assert_eq!(iter.next(), Some(&11));
+    /// assert_eq!(iter.next(), Some(&12));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            it: self.head.as_deref_mut(),
+        }
+    }
+
+    /// Returns true if the SinglyLinkedList is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    /// assert!(sl.is_empty());
+    ///
+    /// sl.push_front("foo");
+    /// assert!(!sl.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the length of the SinglyLinkedList.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    ///
+    /// sl.push_front(2);
+    /// assert_eq!(sl.len(), 1);
+    ///
+    /// sl.push_front(1);
+    /// assert_eq!(sl.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Removes all elements from the SinglyLinkedList.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    ///
+    /// sl.push_front(2);
+    /// sl.push_front(1);
+    /// assert_eq!(sl.len(), 2);
+    /// assert_eq!(sl.front(), Some(&1));
+    ///
+    /// sl.clear();
+    /// assert_eq!(sl.len(), 0);
+    /// assert_eq!(sl.front(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns true if the SinglyLinkedList contains an element equal to the
+    /// given value.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut list: SinglyLinkedList<u32> = SinglyLinkedList::new();
+    ///
+    /// list.push_front(0);
+    /// list.push_front(1);
+    /// list.push_front(2);
+    ///
+    /// assert_eq!(list.contains(&0), true);
+    /// assert_eq!(list.contains(&10), false);
+    /// ```
+    pub fn contains(&self, data: &T) -> bool
+    where
+        T: PartialEq<T>,
+    {
+        self.iter().any(|x| x == data)
+    }
+
+    /// Provides a reference to the front element, or None if the list is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    /// assert_eq!(sl.front(), None);
+    ///
+    /// sl.push_front(1);
+    /// assert_eq!(sl.front(), Some(&1));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.data)
+    }
+
+    /// Provides a mutable reference to the front element, or None if the list
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    /// assert_eq!(sl.front(), None);
+    ///
+    /// sl.push_front(1);
+    /// assert_eq!(sl.front(), Some(&1));
+    ///
+    /// match sl.front_mut() {
+    ///     None => {},
+    ///     Some(x) => *x = 5,
+    /// }
+    /// assert_eq!(sl.front(), Some(&5));
+    /// ```
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|node| &mut node.data)
+    }
+
+    /// Adds an element first in the list.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut sl = SinglyLinkedList::new();
+    ///
+    /// sl.push_front(2);
+    /// assert_eq!(sl.front().unwrap(), &2);
+    ///
+    /// sl.push_front(1);
+    /// assert_eq!(sl.front().unwrap(), &1);
+    /// ```
+    pub fn push_front(&mut self, data: T) {
+        let node = Box::new(Node {
+            next: self.head.take(),
+            data,
+        });
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the first element and returns it, or None if the list is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut s = SinglyLinkedList::new();
+    /// assert_eq!(s.pop_front(), None);
+    ///
+    /// s.push_front(1);
+    /// s.push_front(3);
+    /// assert_eq!(s.pop_front(), Some(3));
+    /// assert_eq!(s.pop_front(), Some(1));
+    /// assert_eq!(s.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.take().map(|node| {
+            let node = *node;
+            self.head = node.next;
+            self.len -= 1;
+            node.data
+        })
+    }
+
+    /// Reverses the order of the elements in the list, in place.
+    ///
+    /// The nodes are relinked instead of moving their data around.
+    ///
+    /// This operation should compute in O(n) time and O(1) memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut s = SinglyLinkedList::new();
+    /// s.push_front(1);
+    /// s.push_front(2);
+    /// s.push_front(3);
+    ///
+    /// s.reverse();
+    ///
+    /// let mut iter = s.iter();
+    /// assert_eq!(iter.next(), Some(&1));
+    /// assert_eq!(iter.next(), Some(&2));
+    /// assert_eq!(iter.next(), Some(&3));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn reverse(&mut self) {
+        let mut reversed: Link<T> = None;
+        let mut it = self.head.take();
+        while let Some(mut node) = it {
+            it = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+        self.head = reversed;
+    }
+
+    /// Splits the list into two at the given index. Returns everything after
+    /// the given index, including the index.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SinglyLinkedList;
+    ///
+    /// let mut s = SinglyLinkedList::new();
+    ///
+    /// s.push_front(1);
+    /// s.push_front(2);
+    /// s.push_front(3);
+    ///
+    /// let mut split = s.split_off(2);
+    ///
+    /// assert_eq!(split.pop_front(), Some(1));
+    /// assert_eq!(split.pop_front(), None);
+    /// assert_eq!(s.len(), 2);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> SinglyLinkedList<T> {
+        let len = self.len();
+        assert!(at <= len, "Cannot split off at a nonexistent index");
+
+        // Walk up to the link that points at the node at index `at`, then
+        // detach everything from there on.
+        let mut link = &mut self.head;
+        for _ in 0..at {
+            match link {
+                Some(node) => link = &mut node.next,
+                None => unreachable!(),
+            }
+        }
+        let tail = SinglyLinkedList {
+            head: link.take(),
+            len: len - at,
+        };
+        self.len = at;
+        tail
+    }
+}
+
+impl<T> Extend<T> for SinglyLinkedList<T> {
+    /// Elements are pushed to the front one by one, so they end up in the
+    /// reverse order of the iterator.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_front(data);
+        }
+    }
+}
+
+/// An iterator over the elements of a SinglyLinkedList.
+///
+/// This struct is created by [`SinglyLinkedList::iter()`]. See its
+/// documentation for more.
+///
+/// [`SinglyLinkedList::iter()`]: crate::containers::SinglyLinkedList::iter
+#[derive(Debug)]
+pub struct Iter<'a, T: 'a> {
+    it: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.it.map(|node| {
+            self.it = node.next.as_deref();
+            &node.data
+        })
+    }
+}
+
+/// A mutable iterator over the elements of a SinglyLinkedList.
+///
+/// This struct is created by [`SinglyLinkedList::iter_mut()`]. See its
+/// documentation for more.
+///
+/// [`SinglyLinkedList::iter_mut()`]: crate::containers::SinglyLinkedList::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, T: 'a> {
+    it: Option<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.it.take().map(|node| {
+            self.it = node.next.as_deref_mut();
+            &mut node.data
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_front_pop_front() {
+        let mut ll = SinglyLinkedList::new();
+        assert!(ll.is_empty());
+        assert_eq!(ll.len(), 0);
+
+        ll.push_front(0);
+        ll.push_front(1);
+        ll.push_front(2);
+
+        assert!(!ll.is_empty());
+        assert_eq!(ll.len(), 3);
+
+        assert_eq!(ll.pop_front(), Some(2));
+        assert_eq!(ll.pop_front(), Some(1));
+        assert_eq!(ll.pop_front(), Some(0));
+        assert_eq!(ll.pop_front(), None);
+
+        assert!(ll.is_empty());
+        assert_eq!(ll.len(), 0);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut ll = SinglyLinkedList::new();
+        ll.reverse();
+        assert!(ll.is_empty());
+
+        ll.extend(0..5);
+        ll.reverse();
+        assert!(ll.iter().copied().eq(0..5));
+        assert_eq!(ll.len(), 5);
+    }
+
+    #[test]
+    fn split_off() {
+        let mut ll = SinglyLinkedList::new();
+        ll.extend((0..5).rev());
+
+        let tail = ll.split_off(5);
+        assert!(tail.is_empty());
+
+        let mut tail = ll.split_off(2);
+        assert!(ll.iter().copied().eq(0..2));
+        assert!(tail.iter().copied().eq(2..5));
+        assert_eq!(ll.len(), 2);
+        assert_eq!(tail.len(), 3);
+
+        let head = tail.split_off(0);
+        assert!(tail.is_empty());
+        assert!(head.iter().copied().eq(2..5));
+    }
+
+    #[test]
+    fn drop_long_list() {
+        let mut ll = SinglyLinkedList::new();
+        ll.extend(0..1_000_000);
+    }
+}
@@ -0,0 +1,537 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+use super::rng::SplitMix64;
+
+/// The index of a missing node, or of the head when used as a predecessor.
+const NIL: usize = usize::MAX;
+
+/// The maximum height of a tower.
+const MAX_LEVEL: usize = 32;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The next node on each level that the tower of this node reaches.
+    next: Vec<usize>,
+}
+
+/// An ordered map implemented with a skip list.
+///
+/// A skip list is a sorted linked list with extra levels of links that skip
+/// over many nodes at once. Every node has a tower of random height: it is on
+/// level 0, and on each level above with probability 1/2 if it is on the
+/// level below. A search starts on the highest level and moves down a level
+/// every time the next node on the current level would overshoot the key. The
+/// expected number of nodes visited is O(log(n)), whatever the order of the
+/// insertions, which makes the skip list a simpler, probabilistic alternative
+/// to balanced search trees.
+///
+/// The heights of the towers come from a pseudorandom number generator. A
+/// skip list created with [`SkipListMap::with_seed()`] and given the same
+/// operations always has the same shape, which makes tests deterministic.
+///
+/// [`SkipListMap::with_seed()`]: crate::containers::SkipListMap::with_seed
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SkipListMap;
+///
+/// let mut map = SkipListMap::with_seed(42);
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+///
+/// assert_eq!(map.get(&2), Some(&"b"));
+/// assert_eq!(map.remove(&1), Some("a"));
+/// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+/// assert_eq!(keys, [2, 3]);
+/// ```
+pub struct SkipListMap<K, V> {
+    nodes: Vec<Node<K, V>>,
+    // The first node on each level, up to the highest tower in the list.
+    head: Vec<usize>,
+    rng: SplitMix64,
+}
+
+impl<K: Ord, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> SkipListMap<K, V> {
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let map: SkipListMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: &self.nodes,
+            next: self.head.first().copied().unwrap_or(NIL),
+            len: self.len(),
+        }
+    }
+
+    /// Returns the entries of the map in ascending order of their keys.
+    pub(crate) fn into_sorted_vec(self) -> Vec<(K, V)> {
+        let mut ranks = vec![0; self.nodes.len()];
+        let mut i = self.head.first().copied().unwrap_or(NIL);
+        let mut rank = 0;
+        while i != NIL {
+            ranks[i] = rank;
+            rank += 1;
+            i = self.nodes[i].next[0];
+        }
+        let mut entries: Vec<_> = self
+            .nodes
+            .into_iter()
+            .zip(ranks)
+            .map(|(node, rank)| (rank, node.key, node.value))
+            .collect();
+        entries.sort_unstable_by_key(|&(rank, _, _)| rank);
+        entries.into_iter().map(|(_, k, v)| (k, v)).collect()
+    }
+
+    /// Returns the node after `i` on the level, where `i` is NIL for the
+    /// head.
+    fn next_of(&self, i: usize, level: usize) -> usize {
+        if i == NIL {
+            self.head[level]
+        } else {
+            self.nodes[i].next[level]
+        }
+    }
+
+    fn set_next(&mut self, i: usize, level: usize, next: usize) {
+        if i == NIL {
+            self.head[level] = next;
+        } else {
+            self.nodes[i].next[level] = next;
+        }
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    /// Creates an empty SkipListMap, whose levels come from a generator with a
+    /// fixed seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self::with_seed(SplitMix64::DEFAULT_SEED)
+    }
+
+    /// Creates an empty SkipListMap, whose levels come from a generator with
+    /// the given seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::with_seed(7);
+    /// map.insert(1, "a");
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            head: Vec::new(),
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Inserts a key-value pair into the map and returns the old value of the
+    /// key, or None if the key was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut preds = self.predecessors(&key);
+        let candidate = if preds.is_empty() {
+            NIL
+        } else {
+            self.next_of(preds[0], 0)
+        };
+        if candidate != NIL && self.nodes[candidate].key == key {
+            let old = &mut self.nodes[candidate].value;
+            return Some(mem::replace(old, value));
+        }
+
+        // The height of the tower is 1 plus the number of trailing zeros of
+        // a random number, which follows a geometric distribution.
+        let height =
+            (self.rng.next_u64().trailing_zeros() as usize + 1).min(MAX_LEVEL);
+        if height > self.head.len() {
+            self.head.resize(height, NIL);
+            preds.resize(height, NIL);
+        }
+        let new = self.nodes.len();
+        let next = (0..height).map(|l| self.next_of(preds[l], l)).collect();
+        self.nodes.push(Node { key, value, next });
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            self.set_next(pred, level, new);
+        }
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&self.nodes[i].value)
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the map.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, 10);
+    /// *map.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(map.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.find(key)?;
+        Some(&mut self.nodes[i].value)
+    }
+
+    /// Returns true if the key is in the map.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes the key from the map and returns its value, or None if the key
+    /// was not in the map.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SkipListMap;
+    ///
+    /// let mut map = SkipListMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let preds = self.predecessors(key);
+        let target = self.next_of(*preds.first()?, 0);
+        if target == NIL || self.nodes[target].key.borrow() != key {
+            return None;
+        }
+        let height = self.nodes[target].next.len();
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            let next = self.nodes[target].next[level];
+            self.set_next(pred, level, next);
+        }
+        while self.head.last() == Some(&NIL) {
+            self.head.pop();
+        }
+
+        // The last node moves into the slot of the removed one, and its
+        // predecessors are found by its key to update their links.
+        let last = self.nodes.len() - 1;
+        if target != last {
+            let preds = self.predecessors::<K>(&self.nodes[last].key);
+            let height = self.nodes[last].next.len();
+            for (level, &pred) in preds.iter().enumerate().take(height) {
+                self.set_next(pred, level, target);
+            }
+        }
+        Some(self.nodes.swap_remove(target).value)
+    }
+
+    /// Returns, for each level, the last node whose key is less than the given
+    /// key, or NIL if there is none and the head comes before it.
+    fn predecessors<Q>(&self, key: &Q) -> Vec<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut preds = vec![NIL; self.head.len()];
+        let mut i = NIL;
+        for level in (0..self.head.len()).rev() {
+            loop {
+                let next = self.next_of(i, level);
+                if next == NIL || self.nodes[next].key.borrow() >= key {
+                    break;
+                }
+                i = next;
+            }
+            preds[level] = i;
+        }
+        preds
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut i = NIL;
+        for level in (0..self.head.len()).rev() {
+            loop {
+                let next = self.next_of(i, level);
+                if next == NIL {
+                    break;
+                }
+                match self.nodes[next].key.borrow().cmp(key) {
+                    Ordering::Less => i = next,
+                    Ordering::Equal => return Some(next),
+                    Ordering::Greater => break,
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipListMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipListMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipListMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a SkipListMap, in ascending order of their
+/// keys.
+///
+/// This struct is created by [`SkipListMap::iter()`]. See its documentation
+/// for more.
+///
+/// [`SkipListMap::iter()`]: crate::containers::SkipListMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    next: usize,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == NIL {
+            return None;
+        }
+        let node = &self.nodes[self.next];
+        self.next = node.next[0];
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    /// Checks that every level is sorted and only skips over nodes with lower
+    /// towers, and that level 0 holds every node.
+    fn check<K: Ord, V>(map: &SkipListMap<K, V>) {
+        assert!(map.head.last().is_none_or(|&first| first != NIL));
+        for level in 0..map.head.len() {
+            let mut count = 0;
+            let mut prev: Option<usize> = None;
+            let mut i = map.head[level];
+            while i != NIL {
+                assert!(map.nodes[i].next.len() > level);
+                if let Some(p) = prev {
+                    assert!(map.nodes[p].key < map.nodes[i].key);
+                }
+                prev = Some(i);
+                i = map.nodes[i].next[level];
+                count += 1;
+            }
+            let expected =
+                map.nodes.iter().filter(|n| n.next.len() > level).count();
+            assert_eq!(count, expected);
+        }
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut map = SkipListMap::with_seed(1);
+        let mut expected = BTreeMap::new();
+        for k in (0..1000).map(|k| (k * 37) % 997) {
+            assert_eq!(map.insert(k, k), expected.insert(k, k));
+        }
+        check(&map);
+        for k in (0..1000).map(|k| (k * 53) % 1009) {
+            assert_eq!(map.remove(&k), expected.remove(&k));
+            if k % 50 == 0 {
+                check(&map);
+            }
+        }
+        check(&map);
+        assert!(map.iter().eq(expected.iter()));
+        assert_eq!(map.iter().len(), expected.len());
+    }
+
+    #[test]
+    fn levels() {
+        let map: SkipListMap<_, _> = (0..4096).map(|k| (k, ())).collect();
+        // About half the towers reach each next level.
+        let on_level_1 = map.nodes.iter().filter(|n| n.next.len() > 1).count();
+        assert!((1536..2560).contains(&on_level_1));
+        assert!((8..MAX_LEVEL).contains(&map.head.len()));
+    }
+
+    #[test]
+    fn deterministic_shape() {
+        let heights = |seed| {
+            let mut map = SkipListMap::with_seed(seed);
+            map.extend((0..100).map(|k| (k, ())));
+            map.nodes.iter().map(|n| n.next.len()).collect::<Vec<_>>()
+        };
+        assert_eq!(heights(3), heights(3));
+        assert_ne!(heights(3), heights(4));
+    }
+}
@@ -0,0 +1,515 @@
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// A slot of [`Slab`].
+///
+/// [`Slab`]: crate::containers::Slab
+#[derive(Debug, Clone)]
+enum Entry<V> {
+    Occupied(V),
+    Vacant { next_free: Option<usize> },
+}
+
+/// An array of values addressed by the positions of their slots, which are
+/// reused once their values are removed.
+///
+/// The vacant slots are chained in a free list, and inserting takes the slot
+/// that was freed last, so the slab only grows when all its slots are
+/// occupied. Unlike the keys of a [`SlotMap`], a key does not know whether
+/// its value has been removed and replaced, so it is up to the caller not to
+/// use a key after removing its value.
+///
+/// [`SlotMap`]: crate::containers::SlotMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Slab;
+///
+/// let mut slab = Slab::new();
+/// let hello = slab.insert("hello");
+/// let world = slab.insert("world");
+/// assert_eq!(slab[hello], "hello");
+///
+/// slab.remove(hello);
+/// // The slot of "hello" is reused.
+/// assert_eq!(slab.insert("again"), hello);
+/// assert_eq!(slab.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Slab<V> {
+    entries: Vec<Entry<V>>,
+    free: Option<usize>,
+    len: usize,
+}
+
+impl<V> Default for Slab<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Slab<V> {
+    /// Creates an empty Slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let slab: Slab<i32> = Slab::new();
+    /// assert!(slab.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty Slab with space for at least `capacity` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let slab: Slab<i32> = Slab::with_capacity(10);
+    /// assert!(slab.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values the slab can hold without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let slab: Slab<i32> = Slab::with_capacity(10);
+    /// assert!(slab.capacity() >= 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// slab.insert(1);
+    /// assert_eq!(slab.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// assert!(slab.is_empty());
+    /// slab.insert(1);
+    /// assert!(!slab.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value, and returns its key.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(42);
+    /// assert_eq!(slab.get(key), Some(&42));
+    /// ```
+    pub fn insert(&mut self, value: V) -> usize {
+        let entry = self.vacant_entry();
+        let key = entry.key();
+        entry.insert(value);
+        key
+    }
+
+    /// Returns a handle to the slot of the next inserted value, whose key is
+    /// known before the value is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let entry = slab.vacant_entry();
+    /// let key = entry.key();
+    /// entry.insert((key, "node"));
+    /// assert_eq!(slab[key], (key, "node"));
+    /// ```
+    pub fn vacant_entry(&mut self) -> VacantEntry<'_, V> {
+        let key = self.free.unwrap_or(self.entries.len());
+        VacantEntry { slab: self, key }
+    }
+
+    /// Removes the value of the key, and returns it if the slot was
+    /// occupied.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(42);
+    /// assert_eq!(slab.remove(key), Some(42));
+    /// assert_eq!(slab.remove(key), None);
+    /// ```
+    pub fn remove(&mut self, key: usize) -> Option<V> {
+        self.get(key)?;
+        let entry = core::mem::replace(
+            &mut self.entries[key],
+            Entry::Vacant {
+                next_free: self.free,
+            },
+        );
+        self.free = Some(key);
+        self.len -= 1;
+        match entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant { .. } => unreachable!("slot {} is vacant", key),
+        }
+    }
+
+    /// Returns a reference to the value of the key, or None if the slot is
+    /// vacant.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(42);
+    /// assert_eq!(slab.get(key), Some(&42));
+    /// assert_eq!(slab.get(key + 1), None);
+    /// ```
+    pub fn get(&self, key: usize) -> Option<&V> {
+        match self.entries.get(key)? {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the
+    /// slot is vacant.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(42);
+    /// *slab.get_mut(key).unwrap() += 1;
+    /// assert_eq!(slab[key], 43);
+    /// ```
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut V> {
+        match self.entries.get_mut(key)? {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns true if the slot of the key is occupied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(42);
+    /// assert!(slab.contains(key));
+    /// slab.remove(key);
+    /// assert!(!slab.contains(key));
+    /// ```
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes all the values, keeping the allocated memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// slab.insert(1);
+    /// slab.clear();
+    /// assert!(slab.is_empty());
+    /// assert_eq!(slab.insert(2), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.free = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the keys and the values, in increasing order
+    /// of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// let a = slab.insert("a");
+    /// slab.insert("b");
+    /// slab.remove(a);
+    /// let entries: Vec<_> = slab.iter().collect();
+    /// assert_eq!(entries, [(1, &"b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            entries: self.entries.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the
+    /// values, in increasing order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Slab;
+    ///
+    /// let mut slab = Slab::new();
+    /// slab.insert(1);
+    /// slab.insert(2);
+    /// for (key, value) in slab.iter_mut() {
+    ///     *value += key;
+    /// }
+    /// assert_eq!((slab[0], slab[1]), (1, 3));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        IterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<V> Index<usize> for Slab<V> {
+    type Output = V;
+
+    fn index(&self, key: usize) -> &Self::Output {
+        self.get(key).expect("Invalid key")
+    }
+}
+
+impl<V> IndexMut<usize> for Slab<V> {
+    fn index_mut(&mut self, key: usize) -> &mut Self::Output {
+        self.get_mut(key).expect("Invalid key")
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Slab<V> {
+    type Item = (usize, &'a V);
+
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut Slab<V> {
+    type Item = (usize, &'a mut V);
+
+    type IntoIter = IterMut<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A handle to the vacant slot of a [`Slab`] that the next inserted value
+/// goes to.
+///
+/// This struct is created by [`Slab::vacant_entry()`]. See its documentation
+/// for more.
+///
+/// [`Slab`]: crate::containers::Slab
+/// [`Slab::vacant_entry()`]: crate::containers::Slab::vacant_entry
+#[derive(Debug)]
+pub struct VacantEntry<'a, V> {
+    slab: &'a mut Slab<V>,
+    key: usize,
+}
+
+impl<'a, V> VacantEntry<'a, V> {
+    /// Returns the key of the slot.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Inserts the value in the slot, and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let slab = self.slab;
+        if self.key == slab.entries.len() {
+            slab.entries.push(Entry::Occupied(value));
+        } else {
+            let entry = &mut slab.entries[self.key];
+            if let Entry::Vacant { next_free } = *entry {
+                slab.free = next_free;
+            }
+            *entry = Entry::Occupied(value);
+        }
+        slab.len += 1;
+        match &mut slab.entries[self.key] {
+            Entry::Occupied(value) => value,
+            Entry::Vacant { .. } => unreachable!("slot {} is vacant", self.key),
+        }
+    }
+}
+
+/// An iterator over the keys and the values of a [`Slab`].
+///
+/// This struct is created by [`Slab::iter()`]. See its documentation for
+/// more.
+///
+/// [`Slab`]: crate::containers::Slab
+/// [`Slab::iter()`]: crate::containers::Slab::iter
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    entries: Enumerate<slice::Iter<'a, Entry<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in self.entries.by_ref() {
+            if let Entry::Occupied(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+/// An iterator over the keys and mutable references to the values of a
+/// [`Slab`].
+///
+/// This struct is created by [`Slab::iter_mut()`]. See its documentation for
+/// more.
+///
+/// [`Slab`]: crate::containers::Slab
+/// [`Slab::iter_mut()`]: crate::containers::Slab::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, V> {
+    entries: Enumerate<slice::IterMut<'a, Entry<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for IterMut<'a, V> {
+    type Item = (usize, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in self.entries.by_ref() {
+            if let Entry::Occupied(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for IterMut<'a, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn reuses_freed_keys() {
+        let mut rng = SplitMix64::new(109);
+        let mut slab = Slab::new();
+        let mut live: Vec<(usize, u64)> = Vec::new();
+        let mut freed: Vec<usize> = Vec::new();
+        for i in 0..2000 {
+            match rng.next_u64() % 3 {
+                0 if !live.is_empty() => {
+                    let pos = (rng.next_u64() % live.len() as u64) as usize;
+                    let (key, value) = live.swap_remove(pos);
+                    assert_eq!(slab.remove(key), Some(value));
+                    assert_eq!(slab.remove(key), None);
+                    freed.push(key);
+                }
+                _ => {
+                    let key = slab.insert(i);
+                    // The last freed slot is reused first.
+                    match freed.pop() {
+                        Some(expected) => assert_eq!(key, expected),
+                        None => assert_eq!(key, slab.entries.len() - 1),
+                    }
+                    live.push((key, i));
+                }
+            }
+            assert_eq!(slab.len(), live.len());
+        }
+        let mut entries: Vec<_> = slab.iter().map(|(k, &v)| (k, v)).collect();
+        live.sort_unstable();
+        entries.sort_unstable();
+        assert_eq!(entries, live);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid key")]
+    fn index_vacant_key() {
+        let mut slab = Slab::new();
+        let key = slab.insert(1);
+        slab.remove(key);
+        let _ = slab[key];
+    }
+}
@@ -0,0 +1,522 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::iter::Enumerate;
+use core::slice;
+
+/// A key to a value of a [`SlotMap`].
+///
+/// A key is the position of its slot along with the version of the slot when
+/// the value was inserted. Removing the value bumps the version, so the key
+/// no longer finds anything even after the slot is reused.
+///
+/// [`SlotMap`]: crate::containers::SlotMap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SlotMapKey {
+    index: u32,
+    version: u32,
+}
+
+/// A slot of [`SlotMap`], whose version is bumped whenever its value is
+/// removed.
+///
+/// [`SlotMap`]: crate::containers::SlotMap
+#[derive(Debug, Clone)]
+struct Slot<V> {
+    version: u32,
+    entry: Entry<V>,
+}
+
+#[derive(Debug, Clone)]
+enum Entry<V> {
+    Occupied(V),
+    Vacant { next_free: Option<u32> },
+}
+
+/// An array of values addressed by keys that are never confused with the
+/// keys of removed values.
+///
+/// The values are stored in slots, and the vacant slots are chained in a
+/// free list so that inserting reuses them. Every slot has a version that is
+/// part of the keys to its values and that changes when its value is
+/// removed, so a stale key is detected by a version mismatch instead of
+/// returning the value that took its place. Inserting, removing and getting
+/// take O(1) time.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SlotMap;
+///
+/// let mut map = SlotMap::new();
+/// let alice = map.insert("alice");
+/// let bob = map.insert("bob");
+/// assert_eq!(map[alice], "alice");
+///
+/// map.remove(alice);
+/// let carol = map.insert("carol");
+/// // The slot of "alice" is reused, but her key does not find "carol".
+/// assert_eq!(map.get(alice), None);
+/// assert_eq!(map.get(carol), Some(&"carol"));
+/// assert_eq!(map.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlotMap<V> {
+    slots: Vec<Slot<V>>,
+    free: Option<u32>,
+    len: usize,
+}
+
+impl<V> Default for SlotMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> SlotMap<V> {
+    /// Creates an empty SlotMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let map: SlotMap<i32> = SlotMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty SlotMap with space for at least `capacity` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let map: SlotMap<i32> = SlotMap::with_capacity(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values the map can hold without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let map: SlotMap<i32> = SlotMap::with_capacity(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// map.insert(1);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1);
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value, and returns its key.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map needs more than `u32::MAX + 1` slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert(42);
+    /// assert_eq!(map.get(key), Some(&42));
+    /// ```
+    pub fn insert(&mut self, value: V) -> SlotMapKey {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts the value returned by `f` given the key of the value, and
+    /// returns the key. This lets a value hold its own key.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the map needs more than `u32::MAX + 1` slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert_with_key(|key| (key, "node"));
+    /// assert_eq!(map[key].0, key);
+    /// ```
+    pub fn insert_with_key<F>(&mut self, f: F) -> SlotMapKey
+    where
+        F: FnOnce(SlotMapKey) -> V,
+    {
+        let key = match self.free {
+            Some(index) => SlotMapKey {
+                index,
+                version: self.slots[index as usize].version,
+            },
+            None => SlotMapKey {
+                index: u32::try_from(self.slots.len())
+                    .expect("Capacity overflow"),
+                version: 0,
+            },
+        };
+        let entry = Entry::Occupied(f(key));
+        match self.slots.get_mut(key.index as usize) {
+            Some(slot) => {
+                if let Entry::Vacant { next_free } = slot.entry {
+                    self.free = next_free;
+                }
+                slot.entry = entry;
+            }
+            None => self.slots.push(Slot { version: 0, entry }),
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Removes the value of the key, and returns it if the key was valid.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert(42);
+    /// assert_eq!(map.remove(key), Some(42));
+    /// assert_eq!(map.remove(key), None);
+    /// ```
+    pub fn remove(&mut self, key: SlotMapKey) -> Option<V> {
+        self.get(key)?;
+        let slot = &mut self.slots[key.index as usize];
+        slot.version = slot.version.wrapping_add(1);
+        let entry = core::mem::replace(
+            &mut slot.entry,
+            Entry::Vacant {
+                next_free: self.free,
+            },
+        );
+        self.free = Some(key.index);
+        self.len -= 1;
+        match entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant { .. } => {
+                unreachable!("slot {} is vacant", key.index)
+            }
+        }
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is
+    /// stale.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert(42);
+    /// assert_eq!(map.get(key), Some(&42));
+    /// ```
+    pub fn get(&self, key: SlotMapKey) -> Option<&V> {
+        match self.slots.get(key.index as usize)? {
+            Slot {
+                version,
+                entry: Entry::Occupied(value),
+            } if *version == key.version => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the
+    /// key is stale.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert(42);
+    /// *map.get_mut(key).unwrap() += 1;
+    /// assert_eq!(map[key], 43);
+    /// ```
+    pub fn get_mut(&mut self, key: SlotMapKey) -> Option<&mut V> {
+        match self.slots.get_mut(key.index as usize)? {
+            Slot {
+                version,
+                entry: Entry::Occupied(value),
+            } if *version == key.version => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the key has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let key = map.insert(42);
+    /// assert!(map.contains_key(key));
+    /// map.remove(key);
+    /// assert!(!map.contains_key(key));
+    /// ```
+    pub fn contains_key(&self, key: SlotMapKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over the keys and the values, in the order of
+    /// their slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let a = map.insert("a");
+    /// let b = map.insert("b");
+    /// map.remove(a);
+    /// let entries: Vec<_> = map.iter().collect();
+    /// assert_eq!(entries, [(b, &"b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the
+    /// values, in the order of their slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SlotMap;
+    ///
+    /// let mut map = SlotMap::new();
+    /// let a = map.insert(1);
+    /// let b = map.insert(2);
+    /// for (_, value) in map.iter_mut() {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!((map[a], map[b]), (10, 20));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<V> core::ops::Index<SlotMapKey> for SlotMap<V> {
+    type Output = V;
+
+    fn index(&self, key: SlotMapKey) -> &Self::Output {
+        self.get(key).expect("Invalid key")
+    }
+}
+
+impl<V> core::ops::IndexMut<SlotMapKey> for SlotMap<V> {
+    fn index_mut(&mut self, key: SlotMapKey) -> &mut Self::Output {
+        self.get_mut(key).expect("Invalid key")
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SlotMap<V> {
+    type Item = (SlotMapKey, &'a V);
+
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut SlotMap<V> {
+    type Item = (SlotMapKey, &'a mut V);
+
+    type IntoIter = IterMut<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the keys and the values of a [`SlotMap`].
+///
+/// This struct is created by [`SlotMap::iter()`]. See its documentation for
+/// more.
+///
+/// [`SlotMap`]: crate::containers::SlotMap
+/// [`SlotMap::iter()`]: crate::containers::SlotMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    slots: Enumerate<slice::Iter<'a, Slot<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (SlotMapKey, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Entry::Occupied(value) = &slot.entry {
+                self.len -= 1;
+                let key = SlotMapKey {
+                    index: index as u32,
+                    version: slot.version,
+                };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+/// An iterator over the keys and mutable references to the values of a
+/// [`SlotMap`].
+///
+/// This struct is created by [`SlotMap::iter_mut()`]. See its documentation
+/// for more.
+///
+/// [`SlotMap`]: crate::containers::SlotMap
+/// [`SlotMap::iter_mut()`]: crate::containers::SlotMap::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, V> {
+    slots: Enumerate<slice::IterMut<'a, Slot<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for IterMut<'a, V> {
+    type Item = (SlotMapKey, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.slots.by_ref() {
+            if let Entry::Occupied(value) = &mut slot.entry {
+                self.len -= 1;
+                let key = SlotMapKey {
+                    index: index as u32,
+                    version: slot.version,
+                };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for IterMut<'a, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn stale_keys_never_resolve() {
+        let mut rng = SplitMix64::new(107);
+        let mut map = SlotMap::new();
+        let mut live: Vec<(SlotMapKey, u64)> = Vec::new();
+        let mut dead: Vec<SlotMapKey> = Vec::new();
+        for i in 0..2000 {
+            match rng.next_u64() % 3 {
+                0 if !live.is_empty() => {
+                    let pos = (rng.next_u64() % live.len() as u64) as usize;
+                    let (key, value) = live.swap_remove(pos);
+                    assert_eq!(map.remove(key), Some(value));
+                    dead.push(key);
+                }
+                _ => live.push((map.insert(i), i)),
+            }
+            assert_eq!(map.len(), live.len());
+        }
+        assert!(map.slots.len() < 2000);
+        for &(key, value) in &live {
+            assert_eq!(map.get(key), Some(&value));
+        }
+        for &key in &dead {
+            assert_eq!(map.get(key), None);
+            assert_eq!(map.remove(key), None);
+        }
+        let mut entries: Vec<_> = map.iter().map(|(k, &v)| (k, v)).collect();
+        live.sort_unstable();
+        entries.sort_unstable();
+        assert_eq!(entries, live);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid key")]
+    fn index_stale_key() {
+        let mut map = SlotMap::new();
+        let key = map.insert(1);
+        map.remove(key);
+        map.insert(2);
+        let _ = map[key];
+    }
+}
@@ -0,0 +1,874 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::slice;
+
+/// The storage of a [`SmallVec`], which is either an inline array or the
+/// pointer to and the capacity of a heap allocation. Which one is in use is
+/// recorded by the vector itself.
+///
+/// [`SmallVec`]: crate::containers::SmallVec
+union RawData<T, const N: usize> {
+    inline: ManuallyDrop<[MaybeUninit<T>; N]>,
+    heap: (*mut T, usize),
+}
+
+impl<T, const N: usize> RawData<T, N> {
+    /// Creates an inline array whose elements are all uninitialized.
+    #[allow(unsafe_code)]
+    fn inline() -> Self {
+        Self {
+            // SAFETY: An array of `MaybeUninit` does not need to be
+            // initialized.
+            inline: ManuallyDrop::new(unsafe {
+                MaybeUninit::uninit().assume_init()
+            }),
+        }
+    }
+}
+
+/// A vector that stores up to `N` elements inline, and moves them to the heap
+/// once it grows larger than that.
+///
+/// Most vectors in a program only ever hold a few elements. Keeping those
+/// elements inside the vector itself avoids allocating, and keeps them close
+/// to the rest of the data that owns the vector. The inline array and the
+/// pointer to the heap allocation share the same memory, so a spilled
+/// vector is no larger than an inline one.
+///
+/// The vector dereferences to a slice, so every method of slices can be used
+/// on it.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SmallVec;
+///
+/// let mut v: SmallVec<i32, 2> = SmallVec::new();
+/// v.push(1);
+/// v.push(2);
+/// assert!(!v.spilled());
+///
+/// v.push(3);
+/// assert!(v.spilled());
+/// assert_eq!(&v[..], &[1, 2, 3]);
+/// ```
+pub struct SmallVec<T, const N: usize> {
+    len: usize,
+    spilled: bool,
+    data: RawData<T, N>,
+}
+
+// SAFETY: The vector owns its elements, whether they are inline or on the
+// heap, like a `Vec` does. The raw pointer to the heap allocation is never
+// shared with another vector, so sending the vector sends the elements, and
+// sharing it only hands out shared references to them.
+#[allow(unsafe_code)]
+unsafe impl<T: Send, const N: usize> Send for SmallVec<T, N> {}
+#[allow(unsafe_code)]
+unsafe impl<T: Sync, const N: usize> Sync for SmallVec<T, N> {}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        self.clear();
+        if self.spilled {
+            // SAFETY: The vector is spilled, so the heap allocation is in
+            // use. It was made by a `Vec` with this capacity, and no element
+            // is left in it.
+            unsafe {
+                let (ptr, capacity) = self.data.heap;
+                drop(Vec::from_raw_parts(ptr, 0, capacity));
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    /// Creates an empty SmallVec that stores its elements inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 4> = SmallVec::new();
+    /// assert_eq!(v.capacity(), 4);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            spilled: false,
+            data: RawData::inline(),
+        }
+    }
+
+    /// Creates an empty SmallVec with space for at least `capacity` elements.
+    /// The elements are stored inline if `capacity <= N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 4> = SmallVec::with_capacity(2);
+    /// assert!(!v.spilled());
+    ///
+    /// let v: SmallVec<u8, 4> = SmallVec::with_capacity(10);
+    /// assert!(v.spilled());
+    /// assert!(v.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut v = Self::new();
+        v.reserve(capacity);
+        v
+    }
+
+    /// Returns the number of elements in the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = SmallVec::new();
+    /// v.push(1);
+    /// assert_eq!(v.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the vector contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = SmallVec::new();
+    /// assert!(v.is_empty());
+    /// v.push(1);
+    /// assert!(!v.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the vector can hold without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 2> = SmallVec::new();
+    /// assert_eq!(v.capacity(), 2);
+    /// v.extend([1, 2, 3].iter().copied());
+    /// assert!(v.capacity() >= 3);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn capacity(&self) -> usize {
+        if self.spilled {
+            // SAFETY: The vector is spilled, so the heap allocation is in use.
+            unsafe { self.data.heap.1 }
+        } else {
+            N
+        }
+    }
+
+    /// Returns true if the elements are stored on the heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 1> = SmallVec::new();
+    /// v.push(1);
+    /// assert!(!v.spilled());
+    /// v.push(2);
+    /// assert!(v.spilled());
+    /// ```
+    pub fn spilled(&self) -> bool {
+        self.spilled
+    }
+
+    /// Extracts a slice containing the entire vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 4> = [1, 2, 3].iter().copied().collect();
+    /// assert_eq!(v.as_slice(), &[1, 2, 3]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements of the storage are initialized.
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    /// Extracts a mutable slice containing the entire vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [3, 1, 2].iter().copied().collect();
+    /// v.as_mut_slice().sort();
+    /// assert_eq!(v.as_slice(), &[1, 2, 3]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: The first `len` elements of the storage are initialized.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    /// Appends an element to the back of the vector, moving the elements to
+    /// the heap if the inline storage is full.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = SmallVec::new();
+    /// v.push(1);
+    /// v.push(2);
+    /// assert_eq!(v.as_slice(), &[1, 2]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        // SAFETY: There is room for at least one more element.
+        unsafe { self.as_mut_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    /// Removes the last element from the vector and returns it, or None if it
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = SmallVec::new();
+    /// v.push(1);
+    /// assert_eq!(v.pop(), Some(1));
+    /// assert_eq!(v.pop(), None);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: The element was initialized, and it is no longer counted in
+        // `len` so it won't be read again.
+        Some(unsafe { self.as_ptr().add(self.len).read() })
+    }
+
+    /// Inserts an element at position `index`, shifting all elements after
+    /// it to the right.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [1, 3].iter().copied().collect();
+    /// v.insert(1, 2);
+    /// assert_eq!(v.as_slice(), &[1, 2, 3]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "Out of bounds access");
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        // SAFETY: There is room for one more element, and the elements from
+        // `index` on are moved one position to the right before the hole they
+        // leave is filled.
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(value);
+        }
+        self.len += 1;
+    }
+
+    /// Removes and returns the element at position `index`, shifting all
+    /// elements after it to the left.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [1, 2, 3].iter().copied().collect();
+    /// assert_eq!(v.remove(1), 2);
+    /// assert_eq!(v.as_slice(), &[1, 3]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Out of bounds access");
+        self.len -= 1;
+        // SAFETY: The element at `index` is initialized. Once it is read, the
+        // elements after it are moved one position to the left to fill the
+        // hole.
+        unsafe {
+            let p = self.as_mut_ptr().add(index);
+            let value = p.read();
+            ptr::copy(p.add(1), p, self.len - index);
+            value
+        }
+    }
+
+    /// Removes and returns the element at position `index`, replacing it with
+    /// the last element.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [1, 2, 3, 4].iter().copied().collect();
+    /// assert_eq!(v.swap_remove(0), 1);
+    /// assert_eq!(v.as_slice(), &[4, 2, 3]);
+    /// ```
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Out of bounds access");
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop().unwrap()
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the
+    /// rest. This has no effect if `len` is greater than the vector's length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [1, 2, 3].iter().copied().collect();
+    /// v.truncate(1);
+    /// assert_eq!(v.as_slice(), &[1]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = self.len - len;
+        // The length is updated first, so that the elements are not dropped
+        // twice if dropping one of them panics.
+        self.len = len;
+        // SAFETY: The elements after `len` are initialized and are no longer
+        // counted in `len`.
+        unsafe {
+            let tail =
+                slice::from_raw_parts_mut(self.as_mut_ptr().add(len), tail);
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Removes all elements from the vector, keeping its storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = [1, 2, 3].iter().copied().collect();
+    /// v.clear();
+    /// assert!(v.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 4> = SmallVec::new();
+    /// v.reserve(2);
+    /// assert!(!v.spilled());
+    /// v.reserve(5);
+    /// assert!(v.capacity() >= 5);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        let required =
+            self.len.checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity() {
+            return;
+        }
+        let doubled = self.capacity().saturating_mul(2);
+        self.grow(required.max(doubled));
+    }
+
+    /// Moves the elements back inline if they fit, or shrinks the heap
+    /// allocation to fit them otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let mut v: SmallVec<u8, 2> = [1, 2, 3].iter().copied().collect();
+    /// assert!(v.spilled());
+    ///
+    /// v.pop();
+    /// v.shrink_to_fit();
+    /// assert!(!v.spilled());
+    /// assert_eq!(v.as_slice(), &[1, 2]);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if self.spilled && self.len < self.capacity() {
+            self.grow(self.len);
+        }
+    }
+
+    /// Converts the vector into a [`Vec`], reusing the heap allocation if the
+    /// vector is spilled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SmallVec;
+    ///
+    /// let v: SmallVec<u8, 2> = [1, 2, 3].iter().copied().collect();
+    /// assert_eq!(v.into_vec(), vec![1, 2, 3]);
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn into_vec(self) -> Vec<T> {
+        let mut this = ManuallyDrop::new(self);
+        if this.spilled {
+            // SAFETY: The heap allocation was made by a `Vec` with this
+            // capacity and holds `len` elements. `this` won't be dropped so
+            // the allocation is only owned by the returned `Vec`.
+            unsafe {
+                let (ptr, capacity) = this.data.heap;
+                Vec::from_raw_parts(ptr, this.len, capacity)
+            }
+        } else {
+            let mut vec = Vec::with_capacity(this.len);
+            // SAFETY: The inline elements are moved into the `Vec`. `this`
+            // won't be dropped so they won't be dropped twice.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    this.as_mut_ptr(),
+                    vec.as_mut_ptr(),
+                    this.len,
+                );
+                vec.set_len(this.len);
+            }
+            vec
+        }
+    }
+
+    /// Returns a raw pointer to the first element of the storage in use.
+    #[allow(unsafe_code)]
+    fn as_ptr(&self) -> *const T {
+        // SAFETY: The flag tells which variant of the union is in use.
+        unsafe {
+            if self.spilled {
+                self.data.heap.0
+            } else {
+                self.data.inline.as_ptr() as *const T
+            }
+        }
+    }
+
+    /// Returns a raw pointer to the first element of the storage in use.
+    #[allow(unsafe_code)]
+    fn as_mut_ptr(&mut self) -> *mut T {
+        // SAFETY: The flag tells which variant of the union is in use.
+        unsafe {
+            if self.spilled {
+                self.data.heap.0
+            } else {
+                (*self.data.inline).as_mut_ptr() as *mut T
+            }
+        }
+    }
+
+    /// Moves the elements into a storage that can hold `capacity` elements,
+    /// which is the inline array if `capacity <= N`.
+    #[allow(unsafe_code)]
+    fn grow(&mut self, capacity: usize) {
+        debug_assert!(capacity >= self.len);
+        let len = self.len;
+        let old = if self.spilled {
+            // SAFETY: The vector is spilled, so the heap allocation is in use.
+            Some(unsafe { self.data.heap })
+        } else {
+            None
+        };
+
+        if capacity <= N {
+            let (ptr, old_capacity) = match old {
+                Some(heap) => heap,
+                None => return,
+            };
+            let mut inline = RawData::<T, N>::inline();
+            // SAFETY: The elements are moved to the inline array, then the
+            // heap allocation is freed without dropping them.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    ptr,
+                    (*inline.inline).as_mut_ptr() as *mut T,
+                    len,
+                );
+                drop(Vec::from_raw_parts(ptr, 0, old_capacity));
+            }
+            self.data = inline;
+            self.spilled = false;
+            return;
+        }
+
+        let mut vec = ManuallyDrop::new(Vec::<T>::with_capacity(capacity));
+        // SAFETY: The new allocation has room for `len` elements, which are
+        // moved into it. The old storage is then given up without dropping
+        // them.
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr(), vec.as_mut_ptr(), len);
+            if let Some((ptr, old_capacity)) = old {
+                drop(Vec::from_raw_parts(ptr, 0, old_capacity));
+            }
+        }
+        self.data = RawData {
+            heap: (vec.as_mut_ptr(), vec.capacity()),
+        };
+        self.spilled = true;
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    /// The elements are moved inline if they fit, otherwise the allocation of
+    /// the `Vec` is reused.
+    fn from(vec: Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        if vec.capacity() <= N {
+            let mut v = Self::new();
+            // SAFETY: The `Vec`'s elements fit inline and are moved there,
+            // then its allocation is freed without dropping them.
+            #[allow(unsafe_code)]
+            unsafe {
+                let len = vec.len();
+                ptr::copy_nonoverlapping(vec.as_ptr(), v.as_mut_ptr(), len);
+                v.len = len;
+                vec.set_len(0);
+                ManuallyDrop::drop(&mut vec);
+            }
+            return v;
+        }
+        Self {
+            len: vec.len(),
+            spilled: true,
+            data: RawData {
+                heap: (vec.as_mut_ptr(), vec.capacity()),
+            },
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An owning iterator over the elements of a [`SmallVec`].
+///
+/// [`SmallVec`]: crate::containers::SmallVec
+pub struct IntoIter<T, const N: usize> {
+    // The vector's length is kept at 0, the elements in `start..end` are the
+    // ones that have not been yielded.
+    vec: SmallVec<T, N>,
+    start: usize,
+    end: usize,
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for IntoIter<T, N> {
+    #[allow(unsafe_code)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: The elements in `start..end` are initialized.
+        let rest = unsafe {
+            slice::from_raw_parts(
+                self.vec.as_ptr().add(self.start),
+                self.end - self.start,
+            )
+        };
+        f.debug_tuple("IntoIter").field(&rest).finish()
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    #[allow(unsafe_code)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        // SAFETY: The element has not been yielded yet, and it is moved out of
+        // the range so it won't be read again.
+        Some(unsafe { self.vec.as_ptr().add(self.start - 1).read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    #[allow(unsafe_code)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: The element has not been yielded yet, and it is moved out of
+        // the range so it won't be read again.
+        Some(unsafe { self.vec.as_ptr().add(self.end).read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        for _ in self {}
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let end = mem::replace(&mut self.len, 0);
+        IntoIter {
+            vec: self,
+            start: 0,
+            end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        // The vector is as thread-safe as its elements, whether they are
+        // inline or spilled.
+        assert_send_sync::<SmallVec<u8, 4>>();
+        assert_send_sync::<SmallVec<Vec<u8>, 0>>();
+
+        let mut v: SmallVec<String, 1> = SmallVec::new();
+        v.push("foo".to_string());
+        v.push("bar".to_string());
+        assert!(v.spilled());
+        let v = std::thread::spawn(move || v).join().unwrap();
+        assert_eq!(&v[..], &["foo", "bar"]);
+    }
+
+    #[test]
+    fn spill_and_shrink() {
+        let mut v: SmallVec<usize, 4> = SmallVec::new();
+        for i in 0..4 {
+            v.push(i);
+        }
+        assert!(!v.spilled());
+        assert_eq!(v.capacity(), 4);
+
+        v.push(4);
+        assert!(v.spilled());
+        assert!(v.capacity() >= 5);
+        assert!(v.iter().copied().eq(0..5));
+
+        v.truncate(3);
+        v.shrink_to_fit();
+        assert!(!v.spilled());
+        assert!(v.iter().copied().eq(0..3));
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut v: SmallVec<i32, 2> = SmallVec::new();
+        v.insert(0, 3);
+        v.insert(0, 1);
+        v.insert(1, 2);
+        v.insert(3, 4);
+        assert_eq!(v.as_slice(), &[1, 2, 3, 4]);
+
+        assert_eq!(v.remove(0), 1);
+        assert_eq!(v.swap_remove(0), 2);
+        assert_eq!(v.as_slice(), &[4, 3]);
+        assert_eq!(v.pop(), Some(3));
+        assert_eq!(v.pop(), Some(4));
+        assert_eq!(v.pop(), None);
+    }
+
+    #[test]
+    fn conversions() {
+        let v: SmallVec<i32, 2> = SmallVec::from(vec![1, 2, 3]);
+        assert!(v.spilled());
+        assert_eq!(v.clone().into_vec(), vec![1, 2, 3]);
+        assert!(v.into_iter().rev().eq(vec![3, 2, 1]));
+
+        let v: SmallVec<i32, 8> = SmallVec::from(Vec::with_capacity(4));
+        assert!(!v.spilled());
+        assert!(v.into_vec().is_empty());
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        let mut v: SmallVec<Rc<()>, 2> = SmallVec::new();
+        for _ in 0..2 {
+            v.push(Rc::clone(&rc));
+        }
+        let inline = v.clone();
+        for _ in 0..3 {
+            v.push(Rc::clone(&rc));
+        }
+        assert_eq!(Rc::strong_count(&rc), 8);
+
+        let mut iter = v.into_iter();
+        iter.next();
+        assert_eq!(Rc::strong_count(&rc), 7);
+        drop(iter);
+        drop(inline);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let mut v: SmallVec<(), 0> = SmallVec::new();
+        for _ in 0..10 {
+            v.push(());
+        }
+        assert_eq!(v.len(), 10);
+        assert_eq!(v.into_iter().count(), 10);
+    }
+}
@@ -0,0 +1,600 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::Mul;
+use core::slice;
+
+use super::matrix::Matrix;
+use super::ops::{Monoid, Sum};
+
+/// A matrix that only stores the elements that were given to it, with the
+/// others taken as zero.
+///
+/// The elements are stored in one of two forms:
+///
+/// - The coordinate (COO) form is a list of `(row, col, value)` triplets in
+///   the order they were added. Adding an element takes O(1) time, and the
+///   same position may be given more than once, in which case the values are
+///   added up.
+/// - The compressed sparse row (CSR) form keeps the columns and the values
+///   of all the elements in two arrays, sorted by row and then by column,
+///   with the elements of row `i` at `offsets[i]..offsets[i + 1]`. Looking
+///   up an element takes O(log(k)) time for a row with `k` elements, and
+///   walking through a row reads contiguous memory.
+///
+/// A matrix is built in COO form and can be converted between the two forms
+/// in place. Converting to CSR form merges the elements at the same position.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SparseMatrix;
+///
+/// let mut m = SparseMatrix::from_triplets(
+///     3,
+///     3,
+///     vec![(0, 0, 2), (1, 2, 3), (2, 1, 4), (1, 2, 1)],
+/// );
+/// assert_eq!(m.mul_vec(&[1, 2, 3]), [2, 12, 8]);
+///
+/// m.to_csr();
+/// assert!(m.is_csr());
+/// assert_eq!(m.get(1, 2), Some(&4));
+/// assert_eq!(m.get(1, 1), None);
+/// assert_eq!(m.nnz(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SparseMatrix<T> {
+    rows: usize,
+    cols: usize,
+    storage: Storage<T>,
+}
+
+#[derive(Debug, Clone)]
+enum Storage<T> {
+    Coo(Vec<(usize, usize, T)>),
+    Csr {
+        // The elements of row `i` are at `offsets[i]..offsets[i + 1]` in
+        // `indices` and `values`, sorted by column.
+        offsets: Vec<usize>,
+        indices: Vec<usize>,
+        values: Vec<T>,
+    },
+}
+
+impl<T> SparseMatrix<T> {
+    /// Creates a SparseMatrix of the given size without elements, in COO
+    /// form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let m: SparseMatrix<i32> = SparseMatrix::new(2, 3);
+    /// assert_eq!((m.rows(), m.cols()), (2, 3));
+    /// assert_eq!(m.nnz(), 0);
+    /// ```
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            storage: Storage::Coo(Vec::new()),
+        }
+    }
+
+    /// Creates a SparseMatrix of the given size from the rows, the columns
+    /// and the values of its elements, in COO form.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position of an element is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let m = SparseMatrix::from_triplets(2, 2, vec![(0, 1, 5), (1, 0, 7)]);
+    /// assert_eq!(m.get(1, 0), Some(&7));
+    /// ```
+    pub fn from_triplets<I>(rows: usize, cols: usize, triplets: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, T)>,
+    {
+        let triplets: Vec<_> = triplets.into_iter().collect();
+        for &(row, col, _) in &triplets {
+            assert!(row < rows && col < cols, "Out of bounds access");
+        }
+        Self {
+            rows,
+            cols,
+            storage: Storage::Coo(triplets),
+        }
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let m: SparseMatrix<i32> = SparseMatrix::new(2, 3);
+    /// assert_eq!(m.rows(), 2);
+    /// ```
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let m: SparseMatrix<i32> = SparseMatrix::new(2, 3);
+    /// assert_eq!(m.cols(), 3);
+    /// ```
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the number of stored elements. In COO form, an element given
+    /// more than once is counted every time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let mut m = SparseMatrix::new(2, 2);
+    /// m.push(0, 0, 1);
+    /// m.push(0, 0, 2);
+    /// assert_eq!(m.nnz(), 2);
+    /// m.to_csr();
+    /// assert_eq!(m.nnz(), 1);
+    /// ```
+    pub fn nnz(&self) -> usize {
+        match &self.storage {
+            Storage::Coo(triplets) => triplets.len(),
+            Storage::Csr { values, .. } => values.len(),
+        }
+    }
+
+    /// Returns true if the matrix is in CSR form, and false if it is in COO
+    /// form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let mut m: SparseMatrix<i32> = SparseMatrix::new(2, 2);
+    /// assert!(!m.is_csr());
+    /// m.to_csr();
+    /// assert!(m.is_csr());
+    /// ```
+    pub fn is_csr(&self) -> bool {
+        matches!(self.storage, Storage::Csr { .. })
+    }
+
+    /// Adds an element at the row and the column. A matrix in CSR form is
+    /// converted to COO form first.
+    ///
+    /// This operation should compute in O(1) amortized time in COO form.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let mut m = SparseMatrix::new(2, 2);
+    /// m.push(1, 1, 3);
+    /// m.push(1, 1, 4);
+    /// m.to_csr();
+    /// assert_eq!(m.get(1, 1), Some(&7));
+    /// ```
+    pub fn push(&mut self, row: usize, col: usize, value: T) {
+        assert!(row < self.rows && col < self.cols, "Out of bounds access");
+        self.to_coo();
+        if let Storage::Coo(triplets) = &mut self.storage {
+            triplets.push((row, col, value));
+        }
+    }
+
+    /// Converts the matrix to COO form, where the elements are in row-major
+    /// order. Does nothing if it is already in COO form.
+    ///
+    /// This operation should compute in O(n + nnz) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let triplets = vec![(1, 0, 1), (0, 1, 2)];
+    /// let mut m = SparseMatrix::from_triplets(2, 2, triplets);
+    /// m.to_csr();
+    /// m.to_coo();
+    /// assert!(!m.is_csr());
+    /// let elements: Vec<_> = m.iter().collect();
+    /// assert_eq!(elements, [(0, 1, &2), (1, 0, &1)]);
+    /// ```
+    pub fn to_coo(&mut self) {
+        let storage = mem::replace(&mut self.storage, Storage::Coo(Vec::new()));
+        self.storage = match storage {
+            Storage::Csr {
+                offsets,
+                indices,
+                values,
+            } => {
+                let mut row = 0;
+                let triplets = indices
+                    .into_iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(at, (col, value))| {
+                        while offsets[row + 1] <= at {
+                            row += 1;
+                        }
+                        (row, col, value)
+                    })
+                    .collect();
+                Storage::Coo(triplets)
+            }
+            coo => coo,
+        };
+    }
+
+    /// Returns a reference to the element at the row and the column, or
+    /// None if there is no element there. In COO form, the first element
+    /// given at the position is returned.
+    ///
+    /// This operation should compute in O(log(k)) time in CSR form, for a
+    /// row with `k` elements, and in O(nnz) time in COO form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let mut m = SparseMatrix::from_triplets(2, 2, vec![(0, 1, 5)]);
+    /// assert_eq!(m.get(0, 1), Some(&5));
+    /// m.to_csr();
+    /// assert_eq!(m.get(0, 1), Some(&5));
+    /// assert_eq!(m.get(1, 1), None);
+    /// ```
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        match &self.storage {
+            Storage::Coo(triplets) => triplets
+                .iter()
+                .find(|&&(r, c, _)| r == row && c == col)
+                .map(|(_, _, value)| value),
+            Storage::Csr {
+                offsets,
+                indices,
+                values,
+            } => {
+                let (start, end) = (offsets[row], offsets[row + 1]);
+                indices[start..end]
+                    .binary_search(&col)
+                    .ok()
+                    .map(|at| &values[start + at])
+            }
+        }
+    }
+
+    /// Returns an iterator over the rows, the columns and the values of the
+    /// stored elements. In COO form, they are in the order they were added,
+    /// and in CSR form, they are in row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let triplets = vec![(1, 1, 'b'), (0, 0, 'a')];
+    /// let m = SparseMatrix::from_triplets(2, 2, triplets);
+    /// let elements: Vec<_> = m.iter().collect();
+    /// assert_eq!(elements, [(1, 1, &'b'), (0, 0, &'a')]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let inner = match &self.storage {
+            Storage::Coo(triplets) => IterInner::Coo(triplets.iter()),
+            Storage::Csr {
+                offsets,
+                indices,
+                values,
+            } => IterInner::Csr {
+                offsets,
+                indices,
+                values,
+                row: 0,
+                pos: 0,
+            },
+        };
+        Iter { inner }
+    }
+}
+
+impl<T> SparseMatrix<T>
+where
+    Sum: Monoid<T>,
+{
+    /// Converts the matrix to CSR form, adding up the values of the elements
+    /// at the same position. Does nothing if it is already in CSR form.
+    ///
+    /// The elements are counted and placed by row, then the elements of each
+    /// row are sorted by column, so that equal columns end up next to each
+    /// other.
+    ///
+    /// This operation should compute in O(n + nnz log(nnz)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let mut m = SparseMatrix::from_triplets(
+    ///     2,
+    ///     3,
+    ///     vec![(1, 2, 1), (0, 1, 2), (1, 0, 3), (1, 2, 4)],
+    /// );
+    /// m.to_csr();
+    /// let elements: Vec<_> = m.iter().collect();
+    /// assert_eq!(elements, [(0, 1, &2), (1, 0, &3), (1, 2, &5)]);
+    /// ```
+    pub fn to_csr(&mut self) {
+        let triplets =
+            match mem::replace(&mut self.storage, Storage::Coo(Vec::new())) {
+                Storage::Coo(triplets) => triplets,
+                csr => {
+                    self.storage = csr;
+                    return;
+                }
+            };
+        let mut counts = vec![0; self.rows + 1];
+        for &(row, _, _) in &triplets {
+            counts[row + 1] += 1;
+        }
+        for i in 0..self.rows {
+            counts[i + 1] += counts[i];
+        }
+        // A counting sort by row, which places each element at the next free
+        // position of its row.
+        let mut next = counts.clone();
+        let mut slots: Vec<Option<(usize, T)>> =
+            triplets.iter().map(|_| None).collect();
+        for (row, col, value) in triplets {
+            slots[next[row]] = Some((col, value));
+            next[row] += 1;
+        }
+        let mut offsets = Vec::with_capacity(self.rows + 1);
+        let mut indices = Vec::new();
+        let mut values: Vec<T> = Vec::new();
+        offsets.push(0);
+        for row in 0..self.rows {
+            let elements = &mut slots[counts[row]..counts[row + 1]];
+            elements.sort_by_key(|slot| slot.as_ref().map(|&(col, _)| col));
+            let start = values.len();
+            for (col, value) in elements.iter_mut().filter_map(Option::take) {
+                if indices.len() > start && indices.last() == Some(&col) {
+                    let last = values.last_mut().unwrap();
+                    *last = Sum::combine(last, &value);
+                } else {
+                    indices.push(col);
+                    values.push(value);
+                }
+            }
+            offsets.push(values.len());
+        }
+        self.storage = Storage::Csr {
+            offsets,
+            indices,
+            values,
+        };
+    }
+
+    /// Returns the product of the matrix with the column vector.
+    ///
+    /// This operation should compute in O(n + nnz) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector is not the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let triplets = vec![(0, 0, 1.0), (1, 2, 2.0)];
+    /// let m = SparseMatrix::from_triplets(2, 3, triplets);
+    /// assert_eq!(m.mul_vec(&[3.0, 4.0, 5.0]), [3.0, 10.0]);
+    /// ```
+    pub fn mul_vec(&self, x: &[T]) -> Vec<T>
+    where
+        T: Clone + Mul<Output = T>,
+    {
+        assert_eq!(x.len(), self.cols, "Dimensions must be equal");
+        let mut y: Vec<T> = (0..self.rows).map(|_| Sum::identity()).collect();
+        for (row, col, value) in self {
+            y[row] = Sum::combine(&y[row], &(value.clone() * x[col].clone()));
+        }
+        y
+    }
+
+    /// Returns the matrix with all its elements stored, where the positions
+    /// without elements are zero and the values of the elements at the same
+    /// position are added up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SparseMatrix;
+    ///
+    /// let m = SparseMatrix::from_triplets(2, 2, vec![(0, 1, 5), (0, 1, 1)]);
+    /// assert_eq!(m.to_dense().as_slice(), [0, 6, 0, 0]);
+    /// ```
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut dense =
+            Matrix::from_fn(self.rows, self.cols, |_, _| Sum::identity());
+        for (row, col, value) in self {
+            dense[(row, col)] = Sum::combine(&dense[(row, col)], value);
+        }
+        dense
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SparseMatrix<T> {
+    type Item = (usize, usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the stored elements of a [`SparseMatrix`].
+///
+/// This struct is created by [`SparseMatrix::iter()`]. See its documentation
+/// for more.
+///
+/// [`SparseMatrix`]: crate::containers::SparseMatrix
+/// [`SparseMatrix::iter()`]: crate::containers::SparseMatrix::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    inner: IterInner<'a, T>,
+}
+
+#[derive(Debug, Clone)]
+enum IterInner<'a, T> {
+    Coo(slice::Iter<'a, (usize, usize, T)>),
+    Csr {
+        offsets: &'a [usize],
+        indices: &'a [usize],
+        values: &'a [T],
+        row: usize,
+        pos: usize,
+    },
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IterInner::Coo(iter) => {
+                iter.next().map(|(row, col, value)| (*row, *col, value))
+            }
+            IterInner::Csr {
+                offsets,
+                indices,
+                values,
+                row,
+                pos,
+            } => {
+                if *pos == values.len() {
+                    return None;
+                }
+                while offsets[*row + 1] <= *pos {
+                    *row += 1;
+                }
+                *pos += 1;
+                Some((*row, indices[*pos - 1], &values[*pos - 1]))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IterInner::Coo(iter) => iter.size_hint(),
+            IterInner::Csr { values, pos, .. } => {
+                let len = values.len() - pos;
+                (len, Some(len))
+            }
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_dense_matrix() {
+        let mut rng = SplitMix64::new(43);
+        for (rows, cols) in [(1, 1), (5, 9), (9, 5), (20, 20)] {
+            let mut m = SparseMatrix::new(rows, cols);
+            let mut dense = Matrix::<i64>::new(rows, cols);
+            for _ in 0..rows * cols / 2 {
+                let r = (rng.next_u64() % rows as u64) as usize;
+                let c = (rng.next_u64() % cols as u64) as usize;
+                let v = (rng.next_u64() % 21) as i64 - 10;
+                m.push(r, c, v);
+                dense[(r, c)] += v;
+            }
+            let x: Vec<i64> = (0..cols)
+                .map(|_| (rng.next_u64() % 21) as i64 - 10)
+                .collect();
+            let expected: Vec<i64> = dense
+                .iter_rows()
+                .map(|row| row.iter().zip(&x).map(|(a, b)| a * b).sum())
+                .collect();
+            assert_eq!(m.mul_vec(&x), expected);
+            assert_eq!(m.to_dense(), dense);
+
+            m.to_csr();
+            assert_eq!(m.mul_vec(&x), expected);
+            assert_eq!(m.to_dense(), dense);
+            let elements: Vec<_> = m.iter().map(|(r, c, _)| (r, c)).collect();
+            assert_eq!(elements.len(), m.iter().len());
+            assert!(elements.windows(2).all(|w| w[0] < w[1]));
+            for r in 0..rows {
+                for c in 0..cols {
+                    let found = m.get(r, c).copied();
+                    if elements.binary_search(&(r, c)).is_ok() {
+                        assert_eq!(found, Some(dense[(r, c)]));
+                    } else {
+                        assert_eq!(found, None);
+                    }
+                }
+            }
+
+            m.to_coo();
+            let coo: Vec<_> = m.iter().map(|(r, c, _)| (r, c)).collect();
+            assert_eq!(coo, elements);
+            assert_eq!(m.to_dense(), dense);
+        }
+    }
+
+    #[test]
+    fn empty_rows_and_columns() {
+        let mut m = SparseMatrix::<i32>::new(0, 3);
+        m.to_csr();
+        assert_eq!(m.mul_vec(&[1, 2, 3]), []);
+        let mut m = SparseMatrix::<i32>::from_triplets(3, 3, vec![(2, 2, 1)]);
+        m.to_csr();
+        assert_eq!(m.iter().collect::<Vec<_>>(), [(2, 2, &1)]);
+        m.to_coo();
+        assert_eq!(m.iter().collect::<Vec<_>>(), [(2, 2, &1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn rejects_out_of_bounds() {
+        SparseMatrix::from_triplets(2, 2, vec![(0, 2, 1)]);
+    }
+}
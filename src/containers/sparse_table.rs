@@ -0,0 +1,177 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::RangeBounds;
+
+use super::ops::{self, Idempotent};
+
+/// An immutable sequence that combines the elements of any range in O(1)
+/// time, for operations like the minimum, the maximum and the greatest common
+/// divisor.
+///
+/// The elements are combined with the operation `Op`, which must be an
+/// [`Idempotent`] monoid over `T`. For every power of two `2^k` and every
+/// index i, the table holds the combination of the `2^k` elements starting at
+/// i, which takes O(n log(n)) time and space to build. A range is covered by
+/// the two blocks of the largest power of two that fits in it, one starting
+/// at its start and one ending at its end. The blocks may overlap, which is
+/// harmless because the operation is idempotent.
+///
+/// Compared to a [`SegmentTree`], the elements cannot be changed, but every
+/// query takes a single combination.
+///
+/// [`Idempotent`]: crate::containers::Idempotent
+/// [`SegmentTree`]: crate::containers::SegmentTree
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{Gcd, Min, SparseTable};
+///
+/// let mins = SparseTable::<_, Min>::new(&[5, 2, 8, 6, 3, 7]);
+/// assert_eq!(mins.query(..), 2);
+/// assert_eq!(mins.query(2..5), 3);
+///
+/// let gcds = SparseTable::<u32, Gcd>::new(&[12, 18, 24, 9]);
+/// assert_eq!(gcds.query(..3), 6);
+/// assert_eq!(gcds.query(..), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SparseTable<T, Op> {
+    // The level k holds the combination of the 2^k elements starting at each
+    // index, for the indices where that many elements remain.
+    levels: Vec<Vec<T>>,
+    op: PhantomData<Op>,
+}
+
+impl<T, Op> SparseTable<T, Op> {
+    /// Returns the number of elements in the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Max, SparseTable};
+    ///
+    /// let table = SparseTable::<_, Max>::new(&[1, 2, 3]);
+    /// assert_eq!(table.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    /// Returns true if the sequence has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Max, SparseTable};
+    ///
+    /// let table = SparseTable::<i32, Max>::new(&[]);
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone, Op: Idempotent<T>> SparseTable<T, Op> {
+    /// Creates a SparseTable over a copy of the values.
+    ///
+    /// This operation should compute in O(n log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Max, SparseTable};
+    ///
+    /// let table = SparseTable::<_, Max>::new(&[3, 1, 4, 1, 5]);
+    /// assert_eq!(table.query(..4), 4);
+    /// ```
+    pub fn new(values: &[T]) -> Self {
+        let mut levels = Vec::new();
+        if !values.is_empty() {
+            levels.push(values.to_vec());
+        }
+        let mut width = 1;
+        while 2 * width <= values.len() {
+            let prev = &levels[levels.len() - 1];
+            let level = (0..=values.len() - 2 * width)
+                .map(|i| Op::combine(&prev[i], &prev[i + width]))
+                .collect();
+            levels.push(level);
+            width *= 2;
+        }
+        Self {
+            levels,
+            op: PhantomData,
+        }
+    }
+
+    /// Returns the combination of the elements in the range, or the identity
+    /// of the operation if the range is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or if its start is greater than
+    /// its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Min, SparseTable};
+    ///
+    /// let table = SparseTable::<_, Min>::new(&[4, 2, 6]);
+    /// assert_eq!(table.query(2..), 6);
+    /// assert_eq!(table.query(1..1), i32::MAX);
+    /// ```
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let range = ops::to_range(range, self.len());
+        if range.is_empty() {
+            return Op::identity();
+        }
+        let k = (usize::BITS - 1 - range.len().leading_zeros()) as usize;
+        let level = &self.levels[k];
+        Op::combine(&level[range.start], &level[range.end - (1 << k)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::ops::{Gcd, Max, Min};
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = SplitMix64::new(3);
+        for len in [0, 1, 2, 7, 64, 100].iter().copied() {
+            let values: Vec<u64> =
+                (0..len).map(|_| rng.next_u64() % 1000).collect();
+            let mins = SparseTable::<_, Min>::new(&values);
+            let maxs = SparseTable::<_, Max>::new(&values);
+            let gcds = SparseTable::<_, Gcd>::new(&values);
+            assert_eq!(mins.len(), len);
+            for a in 0..=len {
+                for b in a..=len {
+                    let slice = &values[a..b];
+                    let min = slice.iter().copied().min().unwrap_or(u64::MAX);
+                    assert_eq!(mins.query(a..b), min);
+                    let max = slice.iter().copied().max().unwrap_or(0);
+                    assert_eq!(maxs.query(a..b), max);
+                    let gcd = slice.iter().fold(0, |g, v| {
+                        <Gcd as ops::Monoid<u64>>::combine(&g, v)
+                    });
+                    assert_eq!(gcds.query(a..b), gcd);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn query_out_of_bounds() {
+        let table = SparseTable::<_, Min>::new(&[1, 2, 3]);
+        table.query(1..4);
+    }
+}
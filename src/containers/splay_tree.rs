@@ -0,0 +1,656 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The number of nodes in the subtree rooted at this node.
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Splays the tree rooted at `t` top-down, and returns the new root.
+///
+/// `cmp` compares the target with the key of a node. The walk down the tree
+/// follows the target, rotating at every second step when the path goes twice
+/// in the same direction, and splits off the nodes it passes into a left tree
+/// of lesser keys and a right tree of greater keys. The last node on the path,
+/// which holds the target if it is in the tree, becomes the root with the two
+/// trees as its subtrees.
+fn splay<K, V, F>(mut t: Box<Node<K, V>>, mut cmp: F) -> Box<Node<K, V>>
+where
+    F: FnMut(&K) -> Ordering,
+{
+    // The nodes of the left tree, each the right child of the one before, and
+    // likewise for the right tree.
+    let mut lefts: Vec<Box<Node<K, V>>> = Vec::new();
+    let mut rights: Vec<Box<Node<K, V>>> = Vec::new();
+    loop {
+        match cmp(&t.key) {
+            Ordering::Equal => break,
+            Ordering::Less => {
+                let mut l = match t.left.take() {
+                    None => break,
+                    Some(l) => l,
+                };
+                if cmp(&l.key) == Ordering::Less {
+                    t.left = l.right.take();
+                    t.update();
+                    l.right = Some(t);
+                    t = l;
+                    l = match t.left.take() {
+                        None => break,
+                        Some(l) => l,
+                    };
+                }
+                rights.push(t);
+                t = l;
+            }
+            Ordering::Greater => {
+                let mut r = match t.right.take() {
+                    None => break,
+                    Some(r) => r,
+                };
+                if cmp(&r.key) == Ordering::Greater {
+                    t.right = r.left.take();
+                    t.update();
+                    r.left = Some(t);
+                    t = r;
+                    r = match t.right.take() {
+                        None => break,
+                        Some(r) => r,
+                    };
+                }
+                lefts.push(t);
+                t = r;
+            }
+        }
+    }
+
+    let mut left = t.left.take();
+    while let Some(mut node) = lefts.pop() {
+        node.right = left;
+        node.update();
+        left = Some(node);
+    }
+    let mut right = t.right.take();
+    while let Some(mut node) = rights.pop() {
+        node.left = right;
+        node.update();
+        right = Some(node);
+    }
+    t.left = left;
+    t.right = right;
+    t.update();
+    t
+}
+
+/// An ordered map implemented with a splay tree.
+///
+/// A splay tree is a binary search tree that is not kept balanced. Instead,
+/// every access moves the accessed node to the root with a sequence of
+/// rotations, called a splay, which also roughly halves the depth of every
+/// node on the way. A single operation may take O(n) time, but any sequence
+/// of m operations takes O(m*log(n)) time, so each takes O(log(n)) amortized
+/// time. Recently accessed keys stay near the root, which makes lookups with
+/// locality of reference especially fast.
+///
+/// Because lookups change the shape of the tree, [`SplayTree::get()`] takes
+/// the tree by mutable reference.
+///
+/// [`SplayTree::get()`]: crate::containers::SplayTree::get
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SplayTree;
+///
+/// let mut tree: SplayTree<_, _> = (0..10).map(|k| (k, k * k)).collect();
+/// assert_eq!(tree.get(&3), Some(&9));
+/// assert_eq!(tree.root(), Some((&3, &9)));
+///
+/// let high = tree.split(&5);
+/// assert_eq!(tree.len(), 5);
+/// assert_eq!(high.len(), 5);
+///
+/// tree.join(high);
+/// assert_eq!(tree.len(), 10);
+/// ```
+pub struct SplayTree<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for SplayTree<K, V> {
+    /// A splay tree can be as deep as it is large, so the nodes are freed one
+    /// by one instead of recursively.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> =
+            self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> SplayTree<K, V> {
+    /// Returns the number of entries in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, "a");
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the entry at the root of the tree, which is the one that was
+    /// accessed last, or None if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree: SplayTree<_, _> = (0..10).map(|k| (k, ())).collect();
+    /// tree.get(&4);
+    /// assert_eq!(tree.root(), Some((&4, &())));
+    /// ```
+    pub fn root(&self) -> Option<(&K, &V)> {
+        self.root.as_ref().map(|node| (&node.key, &node.value))
+    }
+
+    /// Returns an iterator visiting all the entries of the tree in ascending
+    /// order of their keys. The iteration does not splay the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let tree: SplayTree<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = tree.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    /// Creates an empty SplayTree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Inserts a key-value pair into the tree and returns the old value of
+    /// the key, or None if the key was not in the tree. The key ends up at the
+    /// root.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// assert_eq!(tree.insert(1, "a"), None);
+    /// assert_eq!(tree.insert(2, "b"), None);
+    /// assert_eq!(tree.insert(1, "c"), Some("a"));
+    /// assert_eq!(tree.root(), Some((&1, &"c")));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut node = Box::new(Node {
+            key,
+            value,
+            size: 1,
+            left: None,
+            right: None,
+        });
+        if let Some(root) = self.root.take() {
+            let mut root = splay(root, |k| node.key.cmp(k));
+            match node.key.cmp(&root.key) {
+                Ordering::Equal => {
+                    let old = mem::replace(&mut root.value, node.value);
+                    self.root = Some(root);
+                    return Some(old);
+                }
+                Ordering::Less => {
+                    node.left = root.left.take();
+                    root.update();
+                    node.right = Some(root);
+                }
+                Ordering::Greater => {
+                    node.right = root.right.take();
+                    root.update();
+                    node.left = Some(root);
+                }
+            }
+            node.update();
+        }
+        self.root = Some(node);
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the tree. The key, or the last key compared with it, ends up at the
+    /// root.
+    ///
+    /// The key may be any borrowed form of the tree's key type, but Ord on the
+    /// borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.get(&1), Some(&"a"));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the tree. The key, or the last key compared with it, ends up
+    /// at the root.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, 10);
+    /// *tree.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = splay(self.root.take()?, |k| key.cmp(k.borrow()));
+        let root = self.root.insert(root);
+        if root.key.borrow() == key {
+            Some(&mut root.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the key is in the tree. Like [`SplayTree::get()`], this
+    /// splays the tree.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// [`SplayTree::get()`]: crate::containers::SplayTree::get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, "a");
+    /// assert!(tree.contains_key(&1));
+    /// assert!(!tree.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_mut(key).is_some()
+    }
+
+    /// Removes the key from the tree and returns its value, or None if the
+    /// key was not in the tree. The key is splayed to the root, and its two
+    /// subtrees are then joined.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree = SplayTree::new();
+    /// tree.insert(1, "a");
+    /// assert_eq!(tree.remove(&1), Some("a"));
+    /// assert_eq!(tree.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut root = splay(self.root.take()?, |k| key.cmp(k.borrow()));
+        if root.key.borrow() != key {
+            self.root = Some(root);
+            return None;
+        }
+        self.root = root.left.take();
+        self.join_link(root.right.take());
+        Some(root.value)
+    }
+
+    /// Splits the tree in two at the key, and returns a tree holding the
+    /// entries whose keys are greater than or equal to it. The entries whose
+    /// keys are less than it stay in this tree.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut tree: SplayTree<_, _> = (0..10).map(|k| (k, ())).collect();
+    /// let high = tree.split(&7);
+    /// assert!(tree.iter().map(|(k, _)| *k).eq(0..7));
+    /// assert!(high.iter().map(|(k, _)| *k).eq(7..10));
+    /// ```
+    pub fn split<Q>(&mut self, key: &Q) -> SplayTree<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut root = match self.root.take() {
+            None => return Self::new(),
+            Some(root) => splay(root, |k| key.cmp(k.borrow())),
+        };
+        if root.key.borrow() < key {
+            let high = root.right.take();
+            root.update();
+            self.root = Some(root);
+            SplayTree { root: high }
+        } else {
+            self.root = root.left.take();
+            root.update();
+            SplayTree { root: Some(root) }
+        }
+    }
+
+    /// Moves all the entries of `other` into the tree. The maximum of the tree
+    /// is splayed to the root, and `other` becomes its right subtree.
+    ///
+    /// This operation should compute in O(log(n)) amortized time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any key of `other` is not greater than all the keys of the
+    /// tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SplayTree;
+    ///
+    /// let mut low: SplayTree<_, _> = (0..5).map(|k| (k, ())).collect();
+    /// let high: SplayTree<_, _> = (5..10).map(|k| (k, ())).collect();
+    /// low.join(high);
+    /// assert!(low.iter().map(|(k, _)| *k).eq(0..10));
+    /// ```
+    pub fn join(&mut self, mut other: SplayTree<K, V>) {
+        let other = match other.root.take() {
+            None => return,
+            Some(root) => splay(root, |_| Ordering::Less),
+        };
+        if let Some(root) = self.root.take() {
+            let root = splay(root, |_| Ordering::Greater);
+            let ordered = root.key < other.key;
+            self.root = Some(root);
+            assert!(
+                ordered,
+                "Cannot join a tree whose keys are not all greater"
+            );
+        }
+        self.join_link(Some(other));
+    }
+
+    /// Makes `other`, whose keys are all greater than those of the tree, the
+    /// right subtree of the maximum of the tree.
+    fn join_link(&mut self, other: Link<K, V>) {
+        self.root = match self.root.take() {
+            None => other,
+            Some(root) => {
+                let mut root = splay(root, |_| Ordering::Greater);
+                root.right = other;
+                root.update();
+                Some(root)
+            }
+        };
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SplayTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SplayTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a SplayTree, in ascending order of their
+/// keys.
+///
+/// This struct is created by [`SplayTree::iter()`]. See its documentation for
+/// more.
+///
+/// [`SplayTree::iter()`]: crate::containers::SplayTree::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    // The nodes whose left subtrees have been visited but which have not been
+    // yielded themselves, with the next one on top.
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the keys are ordered and the sizes are correct, and
+    /// returns the size of the tree.
+    fn check<K: Ord, V>(
+        link: &Link<K, V>,
+        lo: Option<&K>,
+        hi: Option<&K>,
+    ) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                assert!(lo.is_none_or(|lo| *lo < node.key));
+                assert!(hi.is_none_or(|hi| node.key < *hi));
+                let size = 1
+                    + check(&node.left, lo, Some(&node.key))
+                    + check(&node.right, Some(&node.key), hi);
+                assert_eq!(node.size, size);
+                size
+            }
+        }
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut tree = SplayTree::new();
+        for k in (0..200).map(|k| (k * 37) % 200) {
+            assert_eq!(tree.insert(k, k * 2), None);
+            assert_eq!(tree.root(), Some((&k, &(k * 2))));
+            check(&tree.root, None, None);
+        }
+        assert_eq!(tree.len(), 200);
+
+        for k in (0..200).step_by(3) {
+            assert_eq!(tree.get(&k), Some(&(k * 2)));
+            assert_eq!(tree.remove(&k), Some(k * 2));
+            assert_eq!(tree.get(&k), None);
+            check(&tree.root, None, None);
+        }
+        assert_eq!(tree.len(), 133);
+        assert!(tree
+            .iter()
+            .map(|(k, _)| *k)
+            .eq((0..200).filter(|k| k % 3 != 0)));
+    }
+
+    #[test]
+    fn split_join() {
+        let mut tree: SplayTree<_, _> = (0..100).map(|k| (k * 2, ())).collect();
+        let high = tree.split(&51);
+        check(&tree.root, None, None);
+        check(&high.root, None, None);
+        assert!(tree.iter().map(|(k, _)| *k).eq((0..26).map(|k| k * 2)));
+        assert!(high.iter().map(|(k, _)| *k).eq((26..100).map(|k| k * 2)));
+
+        let empty = tree.split(&1000);
+        assert!(empty.is_empty());
+        tree.join(empty);
+        tree.join(high);
+        check(&tree.root, None, None);
+        assert_eq!(tree.len(), 100);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot join a tree whose keys are not all greater"
+    )]
+    fn join_overlapping() {
+        let mut a: SplayTree<_, _> = (0..10).map(|k| (k, ())).collect();
+        let b: SplayTree<_, _> = (5..15).map(|k| (k, ())).collect();
+        a.join(b);
+    }
+
+    #[test]
+    fn sequential_access() {
+        // Sorted insertions build a path, and accessing the keys in order
+        // afterwards still takes O(n) time in total.
+        let mut tree: SplayTree<_, _> = (0..100_000).map(|k| (k, ())).collect();
+        for k in 0..100_000 {
+            assert!(tree.contains_key(&k));
+        }
+        assert!(tree.iter().map(|(k, _)| *k).eq(0..100_000));
+    }
+}
@@ -0,0 +1,453 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::slice;
+
+use super::binary_heap::BinaryHeap;
+
+/// An element of the queue with the number of elements pushed before it.
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    item: T,
+    seq: u64,
+}
+
+impl<T: Ord> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Entry<T> {}
+
+impl<T: Ord> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Among equal elements, the earlier one is the greater.
+        self.item
+            .cmp(&other.item)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A priority queue that pops equal elements in the order they were pushed.
+///
+/// This is a max-queue like [`BinaryHeap`], whose elements are paired with
+/// the number of elements pushed before them. The pairs are ordered by their
+/// elements, and then by reversed sequence numbers, so no two pairs are
+/// equal, and the greatest element that was pushed first is always at the
+/// front. A binary heap alone does not keep the order of equal elements,
+/// which a scheduler needs to run the jobs of equal priorities first come,
+/// first served.
+///
+/// [`BinaryHeap`]: crate::containers::BinaryHeap
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// use dt::containers::StablePriorityQueue;
+///
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct Job(u32, &'static str);
+///
+/// // Jobs are only ordered by their priorities.
+/// impl Ord for Job {
+///     fn cmp(&self, other: &Self) -> Ordering {
+///         self.0.cmp(&other.0)
+///     }
+/// }
+///
+/// impl PartialOrd for Job {
+///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+///         Some(self.cmp(other))
+///     }
+/// }
+///
+/// let mut jobs = StablePriorityQueue::new();
+/// jobs.push(Job(1, "backup"));
+/// jobs.push(Job(2, "email"));
+/// jobs.push(Job(1, "cleanup"));
+/// jobs.push(Job(2, "deploy"));
+///
+/// let order: Vec<_> = std::iter::from_fn(|| jobs.pop())
+///     .map(|job| job.1)
+///     .collect();
+/// assert_eq!(order, ["email", "deploy", "backup", "cleanup"]);
+/// ```
+#[derive(Clone)]
+pub struct StablePriorityQueue<T> {
+    heap: BinaryHeap<Entry<T>>,
+    // The sequence number of the next element pushed.
+    seq: u64,
+}
+
+impl<T: Ord> Default for StablePriorityQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for StablePriorityQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> StablePriorityQueue<T> {
+    /// Creates an empty StablePriorityQueue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let queue: StablePriorityQueue<u32> = StablePriorityQueue::new();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            seq: 0,
+        }
+    }
+
+    /// Creates an empty StablePriorityQueue with space for at least
+    /// `capacity` elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue = StablePriorityQueue::with_capacity(10);
+    /// queue.push(4);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(capacity),
+            seq: 0,
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue = StablePriorityQueue::new();
+    /// queue.push(1);
+    /// queue.push(1);
+    /// assert_eq!(queue.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns true if the queue has no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue = StablePriorityQueue::new();
+    /// assert!(queue.is_empty());
+    /// queue.push(1);
+    /// assert!(!queue.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Pushes an element onto the queue, after the elements equal to it.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue = StablePriorityQueue::new();
+    /// queue.push(3);
+    /// queue.push(5);
+    /// assert_eq!(queue.peek(), Some(&5));
+    /// ```
+    pub fn push(&mut self, item: T) {
+        let seq = self.seq;
+        self.seq += 1;
+        self.heap.push(Entry { item, seq });
+    }
+
+    /// Removes the greatest element that was pushed first and returns it, or
+    /// None if the queue is empty.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue: StablePriorityQueue<_> =
+    ///     vec![1, 3, 2].into_iter().collect();
+    /// assert_eq!(queue.pop(), Some(3));
+    /// assert_eq!(queue.pop(), Some(2));
+    /// assert_eq!(queue.pop(), Some(1));
+    /// assert_eq!(queue.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let entry = self.heap.pop()?;
+        if self.heap.is_empty() {
+            self.seq = 0;
+        }
+        Some(entry.item)
+    }
+
+    /// Returns the greatest element that was pushed first, or None if the
+    /// queue is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue = StablePriorityQueue::new();
+    /// assert_eq!(queue.peek(), None);
+    /// queue.push(1);
+    /// queue.push(5);
+    /// assert_eq!(queue.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|entry| &entry.item)
+    }
+
+    /// Removes all the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let mut queue: StablePriorityQueue<_> = (0..5).collect();
+    /// queue.clear();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.seq = 0;
+    }
+
+    /// Returns an iterator visiting all the elements of the queue in
+    /// arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let queue: StablePriorityQueue<_> = vec![1, 2, 3].into_iter().collect();
+    /// assert_eq!(queue.iter().sum::<i32>(), 6);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.heap.iter(),
+        }
+    }
+
+    /// Consumes the queue and returns its elements in the order they would
+    /// be popped.
+    ///
+    /// This operation should compute in O(n*log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StablePriorityQueue;
+    ///
+    /// let queue: StablePriorityQueue<_> = vec![2, 5, 1].into_iter().collect();
+    /// assert_eq!(queue.into_sorted_vec(), [5, 2, 1]);
+    /// ```
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .rev()
+            .map(|entry| entry.item)
+            .collect()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for StablePriorityQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T: Ord> Extend<T> for StablePriorityQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a StablePriorityQueue<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`StablePriorityQueue`], in arbitrary
+/// order.
+///
+/// This struct is created by [`StablePriorityQueue::iter()`]. See its
+/// documentation for more.
+///
+/// [`StablePriorityQueue`]: crate::containers::StablePriorityQueue
+/// [`StablePriorityQueue::iter()`]: StablePriorityQueue::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    iter: slice::Iter<'a, Entry<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|entry| &entry.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use core::cmp::Reverse;
+
+    /// A job ordered only by its priority.
+    #[derive(Debug, Clone, Copy)]
+    struct Job {
+        priority: u64,
+        id: u64,
+    }
+
+    impl PartialEq for Job {
+        fn eq(&self, other: &Self) -> bool {
+            self.priority == other.priority
+        }
+    }
+
+    impl Eq for Job {}
+
+    impl PartialOrd for Job {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Job {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.priority.cmp(&other.priority)
+        }
+    }
+
+    fn job(priority: u64, id: u64) -> Job {
+        Job { priority, id }
+    }
+
+    #[test]
+    fn empty_queue() {
+        let mut queue: StablePriorityQueue<Job> = StablePriorityQueue::new();
+        assert_eq!(queue.peek(), None);
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.iter().count(), 0);
+        assert!(queue.into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn equal_elements_are_first_in_first_out() {
+        let mut queue = StablePriorityQueue::new();
+        for id in 0..10 {
+            queue.push(job(1, id));
+        }
+        assert_eq!(queue.peek().map(|j| j.id), Some(0));
+        let ids: Vec<_> = (0..10).map(|_| queue.pop().unwrap().id).collect();
+        assert_eq!(ids, (0..10).collect::<Vec<_>>());
+
+        // Draining the queue restarts the sequence numbers, which must not
+        // reorder the elements pushed afterwards, nor those pushed after a
+        // clear.
+        queue.push(job(1, 10));
+        queue.push(job(1, 11));
+        assert_eq!(queue.pop().map(|j| j.id), Some(10));
+        queue.push(job(1, 12));
+        assert_eq!(queue.pop().map(|j| j.id), Some(11));
+        queue.clear();
+        queue.push(job(1, 13));
+        queue.push(job(2, 14));
+        queue.push(job(1, 15));
+        let ids: Vec<_> =
+            queue.into_sorted_vec().iter().map(|j| j.id).collect();
+        assert_eq!(ids, [14, 13, 15]);
+    }
+
+    #[test]
+    fn pops_equal_elements_in_push_order() {
+        let mut rng = SplitMix64::new(682);
+        let mut queue: StablePriorityQueue<Job> = StablePriorityQueue::new();
+        let mut expected: Vec<Job> = Vec::new();
+        for id in 0..5_000 {
+            if rng.next_u64().is_multiple_of(3) {
+                // The first of the jobs with the greatest priority.
+                let best = expected
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .max_by_key(|(_, job)| job.priority)
+                    .map(|(i, _)| i);
+                let job = best.map(|i| expected.remove(i));
+                assert_eq!(
+                    queue.pop().map(|job| job.id),
+                    job.map(|job| job.id)
+                );
+            } else {
+                let job = Job {
+                    priority: rng.next_u64() % 8,
+                    id,
+                };
+                queue.push(job);
+                expected.push(job);
+            }
+            assert_eq!(queue.len(), expected.len());
+        }
+        let rest: Vec<_> =
+            queue.into_sorted_vec().iter().map(|j| j.id).collect();
+        expected.sort_by_key(|job| Reverse(job.priority));
+        let brute: Vec<_> = expected.iter().map(|j| j.id).collect();
+        assert_eq!(rest, brute);
+    }
+}
@@ -0,0 +1,339 @@
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::hash::BuildHasher;
+use core::iter::FromIterator;
+
+use super::linked_hash_map::{self, DefaultHashBuilder, LinkedHashMap};
+
+/// The id of a string interned by a [`StringInterner`].
+///
+/// Symbols are numbered from 0 in the order their strings were first
+/// interned, so they can index arrays of data about the strings.
+///
+/// [`StringInterner`]: crate::containers::StringInterner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the number of the symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// interner.get_or_intern("a");
+    /// assert_eq!(interner.get_or_intern("b").index(), 1);
+    /// ```
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A set of strings, where each string is given a small id that is cheap to
+/// copy and compare.
+///
+/// The strings are stored once, as the keys of a [`LinkedHashMap`] from the
+/// strings to their symbols. No entry is ever removed from the map, so the
+/// entries stay in the slots they were inserted in, and the symbol of a
+/// string is the slot of its entry, which resolves the symbol without
+/// hashing. Looking up a string goes through
+/// [`LinkedHashMap::entry_ref()`], so it is hashed once whether it was
+/// interned before or not, and it is only copied when it is new.
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`LinkedHashMap::entry_ref()`]: crate::containers::LinkedHashMap::entry_ref
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::StringInterner;
+///
+/// let mut interner = StringInterner::new();
+/// let words: Vec<_> = "to be or not to be"
+///     .split(' ')
+///     .map(|word| interner.get_or_intern(word))
+///     .collect();
+/// assert_eq!(words[0], words[4]);
+/// assert_eq!(interner.len(), 4);
+/// assert_eq!(interner.resolve(words[3]), "not");
+/// ```
+#[derive(Debug)]
+pub struct StringInterner<S = DefaultHashBuilder> {
+    map: LinkedHashMap<Box<str>, Symbol, S>,
+}
+
+impl Default for StringInterner<DefaultHashBuilder> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StringInterner<DefaultHashBuilder> {
+    /// Creates an empty StringInterner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let interner = StringInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<S> StringInterner<S> {
+    /// Creates an empty StringInterner that uses the given hash builder to
+    /// hash the strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{FnvBuildHasher, StringInterner};
+    ///
+    /// let hasher = FnvBuildHasher::default();
+    /// let mut interner = StringInterner::with_hasher(hasher);
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.resolve(symbol), "foo");
+    /// ```
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self {
+            map: LinkedHashMap::with_hasher(hasher_builder),
+        }
+    }
+
+    /// Returns the number of strings interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// interner.get_or_intern("a");
+    /// interner.get_or_intern("a");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if no strings have been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// assert!(interner.is_empty());
+    /// interner.get_or_intern("a");
+    /// assert!(!interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the string of the symbol.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol was not given by this interner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// let symbol = interner.get_or_intern("hello");
+    /// assert_eq!(interner.resolve(symbol), "hello");
+    /// ```
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        self.map.key_at(symbol.index()).expect("Invalid symbol")
+    }
+
+    /// Returns an iterator over the symbols and their strings, in the order
+    /// the strings were first interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// let a = interner.get_or_intern("a");
+    /// let b = interner.get_or_intern("b");
+    /// let all: Vec<_> = interner.iter().collect();
+    /// assert_eq!(all, [(a, "a"), (b, "b")]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+}
+
+impl<S> StringInterner<S>
+where
+    S: BuildHasher,
+{
+    /// Returns the symbol of the string, interning it if it is new.
+    ///
+    /// This operation should compute in expected O(m) time, where m is the
+    /// length of the string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX + 1` strings are interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// let a = interner.get_or_intern("foo");
+    /// let b = interner.get_or_intern(&String::from("foo"));
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn get_or_intern(&mut self, string: &str) -> Symbol {
+        // The new entry takes the next slot, whose position is the number of
+        // entries.
+        let len = self.map.len();
+        *self.map.entry_ref(string).or_insert_with(|| {
+            Symbol(u32::try_from(len).expect("Capacity overflow"))
+        })
+    }
+
+    /// Returns the symbol of the string if it has been interned.
+    ///
+    /// This operation should compute in expected O(m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::StringInterner;
+    ///
+    /// let mut interner = StringInterner::new();
+    /// let symbol = interner.get_or_intern("foo");
+    /// assert_eq!(interner.get("foo"), Some(symbol));
+    /// assert_eq!(interner.get("bar"), None);
+    /// ```
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.map.get(string).copied()
+    }
+}
+
+impl<'a, S> Extend<&'a str> for StringInterner<S>
+where
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for string in iter {
+            self.get_or_intern(string);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a str> for StringInterner<DefaultHashBuilder> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut interner = Self::new();
+        interner.extend(iter);
+        interner
+    }
+}
+
+impl<'a, S> IntoIterator for &'a StringInterner<S> {
+    type Item = (Symbol, &'a str);
+
+    type IntoIter = Iter<'a, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the symbols and the strings of a [`StringInterner`].
+///
+/// This struct is created by [`StringInterner::iter()`]. See its
+/// documentation for more.
+///
+/// [`StringInterner`]: crate::containers::StringInterner
+/// [`StringInterner::iter()`]: crate::containers::StringInterner::iter
+#[derive(Debug)]
+pub struct Iter<'a, S> {
+    iter: linked_hash_map::Iter<'a, Box<str>, Symbol, S>,
+}
+
+impl<'a, S> Iterator for Iter<'a, S> {
+    type Item = (Symbol, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (string, &symbol) = self.iter.next()?;
+        Some((symbol, string))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, S> ExactSizeIterator for Iter<'a, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::String;
+
+    #[test]
+    fn symbols_round_trip() {
+        let mut interner = StringInterner::new();
+        let strings: Vec<String> =
+            (0..500).map(|i| format!("s{}", i % 97)).collect();
+        let symbols: Vec<Symbol> =
+            strings.iter().map(|s| interner.get_or_intern(s)).collect();
+        assert_eq!(interner.len(), 97);
+        for (s, &symbol) in strings.iter().zip(&symbols) {
+            assert_eq!(interner.resolve(symbol), s);
+            assert_eq!(interner.get(s), Some(symbol));
+        }
+        // Symbols are numbered in order of first appearance.
+        for (i, (symbol, s)) in interner.iter().enumerate() {
+            assert_eq!(symbol.index(), i);
+            assert_eq!(s, format!("s{}", i));
+        }
+    }
+
+    #[test]
+    fn duplicates_are_stored_once() {
+        let mut interner = StringInterner::new();
+        for round in 0..10 {
+            for i in 0..100 {
+                let symbol = interner.get_or_intern(&format!("s{}", i));
+                assert_eq!(symbol.index(), i);
+            }
+            assert_eq!(interner.len(), 100, "round {}", round);
+        }
+        for i in 0..100 {
+            assert_eq!(interner.resolve(Symbol(i as u32)), format!("s{}", i));
+        }
+        assert_eq!(interner.iter().len(), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid symbol")]
+    fn resolve_foreign_symbol() {
+        let mut a = StringInterner::new();
+        let b = StringInterner::new();
+        let symbol = a.get_or_intern("x");
+        b.resolve(symbol);
+    }
+}
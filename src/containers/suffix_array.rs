@@ -0,0 +1,348 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The sorted suffixes of a text, with the lengths of the common prefixes of
+/// neighboring suffixes, for searching the text for any pattern.
+///
+/// The suffixes are stored as their start positions in the text, sorted in
+/// lexicographic order. They are sorted by prefix doubling: once the suffixes
+/// are sorted by their first k bytes, the ranks of the first k bytes of the
+/// suffixes starting at i and i + k together give the order of their first 2k
+/// bytes, which a stable counting sort puts in place in linear time. Building
+/// takes O(n log(n)) time for a text of length n.
+///
+/// The longest common prefixes (LCP) of the neighboring suffixes are then
+/// found with Kasai's algorithm in O(n) time, which relies on the LCP of a
+/// suffix being at least the LCP of the suffix one byte longer, minus one.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SuffixArray;
+///
+/// let sa = SuffixArray::new("banana");
+/// assert_eq!(sa.suffixes(), [5, 3, 1, 0, 4, 2]);
+/// assert_eq!(sa.lcp(), [0, 1, 3, 0, 0, 2]);
+///
+/// let mut found = sa.find("ana").to_vec();
+/// found.sort();
+/// assert_eq!(found, [1, 3]);
+/// assert_eq!(sa.longest_repeated_substring(), Some(&b"ana"[..]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    suffixes: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl SuffixArray {
+    /// Creates the SuffixArray of a copy of the text.
+    ///
+    /// This operation should compute in O(n log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new(b"abab");
+    /// assert_eq!(sa.suffixes(), [2, 0, 3, 1]);
+    /// ```
+    pub fn new<T>(text: &T) -> Self
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        let text = text.as_ref().to_vec();
+        let suffixes = sort_suffixes(&text);
+        let lcp = kasai(&text, &suffixes);
+        Self {
+            text,
+            suffixes,
+            lcp,
+        }
+    }
+
+    /// Returns the length of the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("abc");
+    /// assert_eq!(sa.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Returns true if the text is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("");
+    /// assert!(sa.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Returns the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("abc");
+    /// assert_eq!(sa.text(), b"abc");
+    /// ```
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Returns the start positions of the suffixes of the text, in
+    /// lexicographic order of the suffixes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("cab");
+    /// assert_eq!(sa.suffixes(), [1, 2, 0]);
+    /// ```
+    pub fn suffixes(&self) -> &[usize] {
+        &self.suffixes
+    }
+
+    /// Returns the lengths of the longest common prefixes of the neighboring
+    /// suffixes: the element at index i is for the suffixes at indices i - 1
+    /// and i of [`SuffixArray::suffixes()`], and the first element is 0.
+    ///
+    /// [`SuffixArray::suffixes()`]: crate::containers::SuffixArray::suffixes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// // The suffixes are "a", "aa", and "aaa".
+    /// let sa = SuffixArray::new("aaa");
+    /// assert_eq!(sa.lcp(), [0, 1, 2]);
+    /// ```
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    /// Returns the start positions of the occurrences of the pattern in the
+    /// text, in lexicographic order of the suffixes starting there.
+    ///
+    /// The suffixes that start with the pattern are next to each other in the
+    /// suffix array, and are found with two binary searches.
+    ///
+    /// This operation should compute in O(m log(n)) time, where m is the
+    /// length of the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("mississippi");
+    /// assert_eq!(sa.find("ssi"), [5, 2]);
+    /// assert!(sa.find("spi").is_empty());
+    /// ```
+    pub fn find<P>(&self, pattern: &P) -> &[usize]
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        let pattern = pattern.as_ref();
+        let prefix = |s: usize| {
+            let suffix = &self.text[s..];
+            &suffix[..suffix.len().min(pattern.len())]
+        };
+        let start = self.suffixes.partition_point(|&s| prefix(s) < pattern);
+        let end = self.suffixes.partition_point(|&s| prefix(s) <= pattern);
+        &self.suffixes[start..end]
+    }
+
+    /// Returns true if the pattern occurs in the text.
+    ///
+    /// This operation should compute in O(m log(n)) time, where m is the
+    /// length of the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("mississippi");
+    /// assert!(sa.contains("sip"));
+    /// assert!(!sa.contains("spi"));
+    /// ```
+    pub fn contains<P>(&self, pattern: &P) -> bool
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        !self.find(pattern).is_empty()
+    }
+
+    /// Returns the longest substring that occurs at least twice in the text,
+    /// possibly overlapping, or None if no byte occurs twice. Among the
+    /// longest ones, the lexicographically least is returned.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixArray;
+    ///
+    /// let sa = SuffixArray::new("abcabxabcd");
+    /// assert_eq!(sa.longest_repeated_substring(), Some(&b"abc"[..]));
+    /// assert_eq!(SuffixArray::new("abc").longest_repeated_substring(), None);
+    /// ```
+    pub fn longest_repeated_substring(&self) -> Option<&[u8]> {
+        let (i, &len) = self
+            .lcp
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, &len)| len)?;
+        if len == 0 {
+            return None;
+        }
+        let start = self.suffixes[i];
+        Some(&self.text[start..start + len])
+    }
+}
+
+/// Sorts the suffixes of the text by prefix doubling.
+fn sort_suffixes(text: &[u8]) -> Vec<usize> {
+    let n = text.len();
+    let mut suffixes: Vec<usize> = (0..n).collect();
+    suffixes.sort_by_key(|&i| text[i]);
+    let mut rank: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    let mut next_rank = vec![0; n];
+    let mut by_second = Vec::with_capacity(n);
+    if n == 0 {
+        return suffixes;
+    }
+    let mut k = 1;
+    loop {
+        // The suffixes ordered by the rank of the k bytes after their first
+        // k: the ones that have no such bytes come first, and then the others
+        // in the order of the suffixes starting k bytes later.
+        by_second.clear();
+        by_second.extend(n.saturating_sub(k)..n);
+        by_second.extend(suffixes.iter().filter(|&&s| s >= k).map(|&s| s - k));
+
+        // A stable counting sort by the rank of the first k bytes.
+        let mut counts = vec![0; rank.iter().max().unwrap() + 2];
+        for &s in &by_second {
+            counts[rank[s] + 1] += 1;
+        }
+        for r in 1..counts.len() {
+            counts[r] += counts[r - 1];
+        }
+        for &s in &by_second {
+            suffixes[counts[rank[s]]] = s;
+            counts[rank[s]] += 1;
+        }
+
+        let key = |s: usize| (rank[s], rank.get(s + k).map_or(0, |&r| r + 1));
+        next_rank[suffixes[0]] = 0;
+        for i in 1..n {
+            let differs = key(suffixes[i - 1]) != key(suffixes[i]);
+            next_rank[suffixes[i]] =
+                next_rank[suffixes[i - 1]] + differs as usize;
+        }
+        core::mem::swap(&mut rank, &mut next_rank);
+        if rank[suffixes[n - 1]] == n - 1 {
+            break;
+        }
+        k *= 2;
+    }
+    suffixes
+}
+
+/// Computes the longest common prefixes of the neighboring suffixes with
+/// Kasai's algorithm.
+fn kasai(text: &[u8], suffixes: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank = vec![0; n];
+    for (i, &s) in suffixes.iter().enumerate() {
+        rank[s] = i;
+    }
+    let mut lcp = vec![0; n];
+    let mut h: usize = 0;
+    for s in 0..n {
+        if rank[s] == 0 {
+            h = 0;
+            continue;
+        }
+        let prev = suffixes[rank[s] - 1];
+        while s + h < n && prev + h < n && text[s + h] == text[prev + h] {
+            h += 1;
+        }
+        lcp[rank[s]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn naive(text: &[u8]) -> (Vec<usize>, Vec<usize>) {
+        let mut suffixes: Vec<_> = (0..text.len()).collect();
+        suffixes.sort_by_key(|&s| &text[s..]);
+        let mut lcp = vec![0; text.len()];
+        for i in 1..text.len() {
+            let (a, b) = (&text[suffixes[i - 1]..], &text[suffixes[i]..]);
+            lcp[i] = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+        }
+        (suffixes, lcp)
+    }
+
+    #[test]
+    fn matches_naive_sort() {
+        let mut rng = SplitMix64::new(37);
+        for len in 0..200 {
+            let alphabet = 1 + rng.next_u64() % 4;
+            let text: Vec<u8> = (0..len)
+                .map(|_| (rng.next_u64() % alphabet) as u8)
+                .collect();
+            let sa = SuffixArray::new(&text);
+            let (suffixes, lcp) = naive(&text);
+            assert_eq!(sa.suffixes(), &suffixes[..]);
+            assert_eq!(sa.lcp(), &lcp[..]);
+
+            let pattern: Vec<u8> = (0..rng.next_u64() % 4)
+                .map(|_| (rng.next_u64() % alphabet) as u8)
+                .collect();
+            let mut found = sa.find(&pattern).to_vec();
+            found.sort_unstable();
+            let wanted: Vec<_> = (0..text.len())
+                .filter(|&s| text[s..].starts_with(&pattern))
+                .collect();
+            assert_eq!(found, wanted);
+        }
+    }
+
+    #[test]
+    fn longest_repeated_substring() {
+        let sa = SuffixArray::new("to be or not to be");
+        assert_eq!(sa.longest_repeated_substring(), Some(&b"to be"[..]));
+        let sa = SuffixArray::new("aaaa");
+        assert_eq!(sa.longest_repeated_substring(), Some(&b"aaa"[..]));
+        assert_eq!(SuffixArray::new("").longest_repeated_substring(), None);
+    }
+}
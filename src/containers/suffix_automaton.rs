@@ -0,0 +1,309 @@
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+const ROOT: usize = 0;
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct State {
+    // The length of the longest substring reaching this state.
+    len: usize,
+    // The state of the longest suffix of this state's substrings that reaches
+    // another state, or NIL for the root.
+    link: usize,
+    // The transitions, sorted by their bytes.
+    next: Vec<(u8, usize)>,
+}
+
+impl State {
+    fn search(&self, b: u8) -> Result<usize, usize> {
+        self.next.binary_search_by_key(&b, |&(b, _)| b)
+    }
+
+    fn goto(&self, b: u8) -> Option<usize> {
+        self.search(b).ok().map(|i| self.next[i].1)
+    }
+
+    fn set(&mut self, b: u8, s: usize) {
+        match self.search(b) {
+            Ok(i) => self.next[i].1 = s,
+            Err(i) => self.next.insert(i, (b, s)),
+        }
+    }
+}
+
+/// The smallest automaton that accepts the substrings of a text, also known
+/// as the directed acyclic word graph (DAWG) of the text.
+///
+/// Every state stands for the substrings that end at the same set of
+/// positions in the text, which are suffixes of each other with consecutive
+/// lengths. The suffix link of a state goes to the state of the longest
+/// suffix that ends at more positions. Appending a byte adds a state for the
+/// whole text and follows the suffix links to add the transitions of the new
+/// suffixes, cloning a state when only some of its substrings end at the new
+/// position. The automaton has at most 2n states and 3n transitions for a
+/// text of length n, and is built in O(n) time.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SuffixAutomaton;
+///
+/// let mut sa = SuffixAutomaton::new();
+/// sa.extend(b"abab".iter().copied());
+/// assert!(sa.contains("bab"));
+/// assert!(!sa.contains("bb"));
+/// // "a", "b", "ab", "ba", "aba", "bab", and "abab".
+/// assert_eq!(sa.count_distinct_substrings(), 7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    // The state of the whole text.
+    last: usize,
+    // The number of bytes appended.
+    len: usize,
+}
+
+impl Default for SuffixAutomaton {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuffixAutomaton {
+    /// Creates a SuffixAutomaton of the empty text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let sa = SuffixAutomaton::new();
+    /// assert!(sa.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            states: alloc::vec![State {
+                len: 0,
+                link: NIL,
+                next: Vec::new(),
+            }],
+            last: ROOT,
+            len: 0,
+        }
+    }
+
+    /// Returns the length of the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let sa: SuffixAutomaton = b"abc".iter().copied().collect();
+    /// assert_eq!(sa.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the text is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let mut sa = SuffixAutomaton::new();
+    /// assert!(sa.is_empty());
+    /// sa.push(b'a');
+    /// assert!(!sa.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of states of the automaton, including the initial
+    /// state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let sa: SuffixAutomaton = b"aaa".iter().copied().collect();
+    /// assert_eq!(sa.state_count(), 4);
+    /// ```
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Appends a byte to the text.
+    ///
+    /// This operation should compute in amortized O(1) time, for a constant
+    /// size alphabet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let mut sa = SuffixAutomaton::new();
+    /// sa.push(b'a');
+    /// sa.push(b'b');
+    /// assert!(sa.contains("ab"));
+    /// ```
+    pub fn push(&mut self, b: u8) {
+        self.len += 1;
+        let cur = self.states.len();
+        self.states.push(State {
+            len: self.len,
+            link: ROOT,
+            next: Vec::new(),
+        });
+        // The suffixes of the old text without a transition on the byte end
+        // the new substrings, so they get a transition to the new state.
+        let mut p = self.last;
+        while p != NIL && self.states[p].goto(b).is_none() {
+            self.states[p].set(b, cur);
+            p = self.states[p].link;
+        }
+        if p != NIL {
+            let q = self.states[p].goto(b).unwrap();
+            if self.states[p].len + 1 == self.states[q].len {
+                self.states[cur].link = q;
+            } else {
+                // Only the substrings of q up to the length of p's plus one
+                // now end at the new position too, so they move to a clone.
+                let clone = self.states.len();
+                self.states.push(State {
+                    len: self.states[p].len + 1,
+                    link: self.states[q].link,
+                    next: self.states[q].next.clone(),
+                });
+                while p != NIL && self.states[p].goto(b) == Some(q) {
+                    self.states[p].set(b, clone);
+                    p = self.states[p].link;
+                }
+                self.states[q].link = clone;
+                self.states[cur].link = clone;
+            }
+        }
+        self.last = cur;
+    }
+
+    /// Returns true if the pattern is a substring of the text. The empty
+    /// pattern is a substring of every text.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let sa: SuffixAutomaton = b"mississippi".iter().copied().collect();
+    /// assert!(sa.contains("ssip"));
+    /// assert!(!sa.contains("spi"));
+    /// ```
+    pub fn contains<P>(&self, pattern: &P) -> bool
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        let mut s = ROOT;
+        for &b in pattern.as_ref() {
+            match self.states[s].goto(b) {
+                Some(next) => s = next,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Returns the number of distinct non-empty substrings of the text.
+    ///
+    /// Every state stands for the substrings whose lengths are between the
+    /// length of its suffix link's longest substring, exclusive, and its own.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixAutomaton;
+    ///
+    /// let sa: SuffixAutomaton = b"aaa".iter().copied().collect();
+    /// assert_eq!(sa.count_distinct_substrings(), 3);
+    /// ```
+    pub fn count_distinct_substrings(&self) -> usize {
+        self.states[1..]
+            .iter()
+            .map(|s| s.len - self.states[s.link].len)
+            .sum()
+    }
+}
+
+impl FromIterator<u8> for SuffixAutomaton {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut sa = Self::new();
+        sa.extend(iter);
+        sa
+    }
+}
+
+impl Extend<u8> for SuffixAutomaton {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for b in iter {
+            self.push(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeSet;
+
+    #[test]
+    fn matches_naive_substrings() {
+        let mut rng = SplitMix64::new(47);
+        for len in 0..60 {
+            let alphabet = 1 + rng.next_u64() % 3;
+            let text: Vec<u8> = (0..len)
+                .map(|_| b'a' + (rng.next_u64() % alphabet) as u8)
+                .collect();
+            let sa: SuffixAutomaton = text.iter().copied().collect();
+            let mut substrings = BTreeSet::new();
+            for a in 0..text.len() {
+                for b in a + 1..=text.len() {
+                    substrings.insert(&text[a..b]);
+                }
+            }
+            assert_eq!(sa.count_distinct_substrings(), substrings.len());
+            assert!(sa.state_count() <= 2 * text.len().max(1));
+            for s in &substrings {
+                assert!(sa.contains(s));
+            }
+            for _ in 0..20 {
+                let pattern: Vec<u8> = (0..1 + rng.next_u64() % 5)
+                    .map(|_| b'a' + (rng.next_u64() % alphabet) as u8)
+                    .collect();
+                assert_eq!(
+                    sa.contains(&pattern),
+                    substrings.contains(&pattern[..])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let sa = SuffixAutomaton::new();
+        assert!(sa.contains(""));
+        assert!(!sa.contains("a"));
+        assert_eq!(sa.count_distinct_substrings(), 0);
+    }
+}
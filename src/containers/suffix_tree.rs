@@ -0,0 +1,661 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+const ROOT: usize = 0;
+const NIL: usize = usize::MAX;
+
+// The symbol after the last byte of the text, which makes every suffix end
+// at a leaf since it occurs nowhere else.
+const END: u16 = 256;
+
+#[derive(Debug, Clone)]
+struct Node {
+    // The label of the edge into this node is the text from start to end,
+    // where the leaves end with the text read so far, at NIL.
+    start: usize,
+    end: usize,
+    // The node of the path label without its first byte, for the internal
+    // nodes.
+    link: usize,
+    // The length of the path label of an internal node, or the start of the
+    // suffix of a leaf.
+    depth: usize,
+    // The children, sorted by the first symbols of their edges.
+    children: Vec<(u16, usize)>,
+}
+
+impl Node {
+    fn new(start: usize, end: usize, depth: usize) -> Self {
+        Self {
+            start,
+            end,
+            link: ROOT,
+            depth,
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.end == NIL
+    }
+
+    fn child(&self, c: u16) -> Option<usize> {
+        self.children
+            .binary_search_by_key(&c, |&(c, _)| c)
+            .ok()
+            .map(|i| self.children[i].1)
+    }
+
+    fn set_child(&mut self, c: u16, node: usize) {
+        match self.children.binary_search_by_key(&c, |&(c, _)| c) {
+            Ok(i) => self.children[i].1 = node,
+            Err(i) => self.children.insert(i, (c, node)),
+        }
+    }
+}
+
+/// A compressed trie of all the suffixes of a text, for searching the text
+/// for any pattern in time proportional to the length of the pattern.
+///
+/// The tree is built online with Ukkonen's algorithm, which adds the bytes of
+/// the text one at a time. After adding a byte, the tree holds all the
+/// suffixes of the text read so far, and the edges into the leaves grow with
+/// the text without being touched. Every internal node has a suffix link to
+/// the node of its path label without the first byte, so the next suffix to
+/// extend is found by following a link instead of walking down from the root.
+/// Building takes O(n) time for a text of length n.
+///
+/// A unique terminator is added after the text so that every suffix ends at
+/// a leaf. The construction can be watched with
+/// [`SuffixTree::with_steps()`] and drawn with [`SuffixTree::write_dot()`].
+///
+/// [`SuffixTree::with_steps()`]: crate::containers::SuffixTree::with_steps
+/// [`SuffixTree::write_dot()`]: crate::containers::SuffixTree::write_dot
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::SuffixTree;
+///
+/// let st = SuffixTree::new("banana");
+/// assert!(st.contains("nan"));
+/// assert_eq!(st.find("ana"), [1, 3]);
+///
+/// let links: Vec<_> = st.suffix_links("ana").collect();
+/// assert_eq!(links, [&b"ana"[..], b"na", b"a", b""]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SuffixTree {
+    text: Vec<u8>,
+    nodes: Vec<Node>,
+    // The end of the edges into the leaves: the number of symbols added.
+    end: usize,
+}
+
+impl SuffixTree {
+    /// Creates the SuffixTree of a copy of the text.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new(b"abab");
+    /// assert!(st.contains("bab"));
+    /// ```
+    pub fn new<T>(text: &T) -> Self
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        Self::with_steps(text, |_| {})
+    }
+
+    /// Creates the SuffixTree of a copy of the text, calling the function
+    /// with the tree after every symbol is added: once for every byte of the
+    /// text, and once more for the terminator. Before the terminator is
+    /// added, the suffixes that occur elsewhere in the text read so far end
+    /// inside edges instead of at leaves.
+    ///
+    /// This operation should compute in O(n) time, besides the calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let mut leaves = Vec::new();
+    /// SuffixTree::with_steps("aab", |st| {
+    ///     let mut dot = String::new();
+    ///     st.write_dot(&mut dot).unwrap();
+    ///     leaves.push(dot.matches("shape=box").count());
+    /// });
+    /// // "a" is both a suffix and a prefix of "aa", so it has no leaf yet.
+    /// assert_eq!(leaves, [1, 1, 3, 4]);
+    /// ```
+    pub fn with_steps<T, F>(text: &T, mut step: F) -> Self
+    where
+        T: AsRef<[u8]> + ?Sized,
+        F: FnMut(&Self),
+    {
+        let mut st = Self {
+            text: text.as_ref().to_vec(),
+            nodes: alloc::vec![Node::new(0, 0, 0)],
+            end: 0,
+        };
+        let mut active_node = ROOT;
+        // The active point is active_len symbols down the edge of active_node
+        // starting with the symbol at active_edge.
+        let mut active_edge = 0;
+        let mut active_len = 0;
+        // The number of suffixes that are yet to be added explicitly.
+        let mut remainder = 0;
+        for i in 0..=st.text.len() {
+            let c = st.symbol(i);
+            st.end = i + 1;
+            remainder += 1;
+            let mut last_new = NIL;
+            while remainder > 0 {
+                if active_len == 0 {
+                    active_edge = i;
+                }
+                let edge = st.symbol(active_edge);
+                let next = match st.nodes[active_node].child(edge) {
+                    Some(next) => next,
+                    None => {
+                        let leaf = st.add_leaf(i, i + 1 - remainder);
+                        st.nodes[active_node].set_child(edge, leaf);
+                        if last_new != NIL {
+                            st.nodes[last_new].link = active_node;
+                            last_new = NIL;
+                        }
+                        st.advance(
+                            &mut active_node,
+                            &mut active_edge,
+                            &mut active_len,
+                            &mut remainder,
+                            i,
+                        );
+                        continue;
+                    }
+                };
+                let len = st.edge_len(next);
+                if active_len >= len {
+                    // Walk down to the node at the end of the edge.
+                    active_edge += len;
+                    active_len -= len;
+                    active_node = next;
+                    continue;
+                }
+                let split_at = st.nodes[next].start + active_len;
+                if st.symbol(split_at) == c {
+                    // The suffix is already in the tree, and so are all the
+                    // shorter ones.
+                    if last_new != NIL {
+                        st.nodes[last_new].link = active_node;
+                    }
+                    active_len += 1;
+                    break;
+                }
+                let depth = st.nodes[active_node].depth + active_len;
+                let mid = st.nodes.len();
+                st.nodes
+                    .push(Node::new(st.nodes[next].start, split_at, depth));
+                st.nodes[active_node].set_child(edge, mid);
+                let leaf = st.add_leaf(i, i + 1 - remainder);
+                st.nodes[mid].set_child(c, leaf);
+                let split = st.symbol(split_at);
+                st.nodes[mid].set_child(split, next);
+                st.nodes[next].start = split_at;
+                if last_new != NIL {
+                    st.nodes[last_new].link = mid;
+                }
+                last_new = mid;
+                st.advance(
+                    &mut active_node,
+                    &mut active_edge,
+                    &mut active_len,
+                    &mut remainder,
+                    i,
+                );
+            }
+            step(&st);
+        }
+        st
+    }
+
+    /// Returns the length of the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("abc");
+    /// assert_eq!(st.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Returns true if the text is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("");
+    /// assert!(st.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Returns the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("abc");
+    /// assert_eq!(st.text(), b"abc");
+    /// ```
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Returns true if the pattern occurs in the text.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("mississippi");
+    /// assert!(st.contains("sip"));
+    /// assert!(!st.contains("spi"));
+    /// ```
+    pub fn contains<P>(&self, pattern: &P) -> bool
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        self.locate(pattern.as_ref()).is_some()
+    }
+
+    /// Returns the start positions of the occurrences of the pattern in the
+    /// text, in increasing order.
+    ///
+    /// The occurrences are the leaves below the point where the pattern ends.
+    ///
+    /// This operation should compute in O(m + k log(k)) time, where m is the
+    /// length of the pattern and k is the number of occurrences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("mississippi");
+    /// assert_eq!(st.find("ssi"), [2, 5]);
+    /// assert!(st.find("spi").is_empty());
+    /// ```
+    pub fn find<P>(&self, pattern: &P) -> Vec<usize>
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        let mut found = Vec::new();
+        let mut stack: Vec<_> =
+            self.locate(pattern.as_ref()).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            // The leaf of the empty suffix is not an occurrence.
+            if node.is_leaf() && node.depth < self.text.len() {
+                found.push(node.depth);
+            }
+            stack.extend(node.children.iter().map(|&(_, child)| child));
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the longest substring that occurs at least twice in the text,
+    /// possibly overlapping, or None if no byte occurs twice. Among the
+    /// longest ones, the lexicographically least is returned.
+    ///
+    /// This is the path label of the deepest internal node.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("abcabxabcd");
+    /// assert_eq!(st.longest_repeated_substring(), Some(&b"abc"[..]));
+    /// assert_eq!(SuffixTree::new("abc").longest_repeated_substring(), None);
+    /// ```
+    pub fn longest_repeated_substring(&self) -> Option<&[u8]> {
+        let mut deepest = ROOT;
+        let mut stack = alloc::vec![ROOT];
+        while let Some(node) = stack.pop() {
+            let n = &self.nodes[node];
+            if n.is_leaf() {
+                continue;
+            }
+            if n.depth > self.nodes[deepest].depth {
+                deepest = node;
+            }
+            // Pushed in reverse to visit the children in increasing order.
+            stack.extend(n.children.iter().rev().map(|&(_, child)| child));
+        }
+        if deepest == ROOT {
+            None
+        } else {
+            Some(self.label(deepest))
+        }
+    }
+
+    /// Returns an iterator visiting the path labels of the nodes along the
+    /// suffix links, starting from the internal node whose path label is the
+    /// pattern and ending with the empty label of the root. The iterator is
+    /// empty if no internal node has the pattern as its path label, which
+    /// happens when the pattern always occurs followed by the same byte.
+    ///
+    /// Every label is the previous one without its first byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("abcxbcyabc");
+    /// let links: Vec<_> = st.suffix_links("abc").collect();
+    /// assert_eq!(links, [&b"abc"[..], b"bc", b"c", b""]);
+    /// assert_eq!(st.suffix_links("ab").count(), 0);
+    /// ```
+    pub fn suffix_links<P>(&self, pattern: &P) -> SuffixLinks<'_>
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        let pattern = pattern.as_ref();
+        let node = match self.locate(pattern) {
+            Some(node)
+                if !self.nodes[node].is_leaf()
+                    && self.nodes[node].depth == pattern.len() =>
+            {
+                node
+            }
+            _ => NIL,
+        };
+        SuffixLinks { st: self, node }
+    }
+
+    /// Writes the tree in the DOT language of Graphviz. The edges are labeled
+    /// with their bytes, with `$` for the terminator, and the leaves with the
+    /// start positions of their suffixes. The suffix links are drawn as
+    /// dashed edges.
+    ///
+    /// Together with [`SuffixTree::with_steps()`], this draws every step of
+    /// the construction.
+    ///
+    /// [`SuffixTree::with_steps()`]: crate::containers::SuffixTree::with_steps
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::SuffixTree;
+    ///
+    /// let st = SuffixTree::new("aa");
+    /// let mut dot = String::new();
+    /// st.write_dot(&mut dot).unwrap();
+    /// assert!(dot.starts_with("digraph {"));
+    /// assert!(dot.contains("label=\"a\""));
+    /// assert!(dot.contains("label=\"a$\""));
+    /// ```
+    pub fn write_dot<W>(&self, out: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        writeln!(out, "digraph {{")?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.is_leaf() {
+                writeln!(
+                    out,
+                    "    n{} [shape=box, label=\"{}\"];",
+                    i, node.depth
+                )?;
+            } else {
+                writeln!(out, "    n{} [shape=point];", i)?;
+            }
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &(_, child) in &node.children {
+                write!(out, "    n{} -> n{} [label=\"", i, child)?;
+                let child = &self.nodes[child];
+                for j in child.start..self.edge_end(child) {
+                    match self.text.get(j) {
+                        Some(&b) => {
+                            for c in core::ascii::escape_default(b) {
+                                out.write_char(c as char)?;
+                            }
+                        }
+                        None => out.write_char('$')?,
+                    }
+                }
+                writeln!(out, "\"];")?;
+            }
+            if i != ROOT && !node.is_leaf() {
+                writeln!(out, "    n{} -> n{} [style=dashed];", i, node.link)?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    fn symbol(&self, i: usize) -> u16 {
+        self.text.get(i).map_or(END, |&b| b as u16)
+    }
+
+    fn edge_end(&self, node: &Node) -> usize {
+        if node.is_leaf() {
+            self.end
+        } else {
+            node.end
+        }
+    }
+
+    fn edge_len(&self, node: usize) -> usize {
+        self.edge_end(&self.nodes[node]) - self.nodes[node].start
+    }
+
+    fn add_leaf(&mut self, start: usize, suffix: usize) -> usize {
+        self.nodes.push(Node::new(start, NIL, suffix));
+        self.nodes.len() - 1
+    }
+
+    /// Moves the active point to the next shorter suffix, after the current
+    /// one was added.
+    fn advance(
+        &self,
+        active_node: &mut usize,
+        active_edge: &mut usize,
+        active_len: &mut usize,
+        remainder: &mut usize,
+        i: usize,
+    ) {
+        *remainder -= 1;
+        if *active_node == ROOT {
+            if *active_len > 0 {
+                *active_len -= 1;
+                *active_edge = i + 1 - *remainder;
+            }
+        } else {
+            *active_node = self.nodes[*active_node].link;
+        }
+    }
+
+    /// Returns the path label of an internal node.
+    fn label(&self, node: usize) -> &[u8] {
+        let node = &self.nodes[node];
+        &self.text[node.end - node.depth..node.end]
+    }
+
+    /// Returns the highest node whose path label starts with the pattern.
+    fn locate(&self, pattern: &[u8]) -> Option<usize> {
+        let mut node = ROOT;
+        let mut matched = 0;
+        while matched < pattern.len() {
+            node = self.nodes[node].child(pattern[matched] as u16)?;
+            let n = &self.nodes[node];
+            for j in n.start..self.edge_end(n) {
+                if matched == pattern.len() {
+                    break;
+                }
+                if self.symbol(j) != pattern[matched] as u16 {
+                    return None;
+                }
+                matched += 1;
+            }
+        }
+        Some(node)
+    }
+}
+
+/// An iterator over the path labels of the nodes along the suffix links of a
+/// SuffixTree.
+///
+/// This struct is created by [`SuffixTree::suffix_links()`]. See its
+/// documentation for more.
+///
+/// [`SuffixTree::suffix_links()`]: crate::containers::SuffixTree::suffix_links
+#[derive(Debug)]
+pub struct SuffixLinks<'a> {
+    st: &'a SuffixTree,
+    node: usize,
+}
+
+impl<'a> Iterator for SuffixLinks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.node == NIL {
+            return None;
+        }
+        let label = self.st.label(self.node);
+        self.node = if self.node == ROOT {
+            NIL
+        } else {
+            self.st.nodes[self.node].link
+        };
+        Some(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::string::String;
+
+    fn random_text(rng: &mut SplitMix64, len: u64, alphabet: u64) -> Vec<u8> {
+        (0..len)
+            .map(|_| b'a' + (rng.next_u64() % alphabet) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn matches_naive_search() {
+        let mut rng = SplitMix64::new(41);
+        for len in 0..150 {
+            let alphabet = 1 + rng.next_u64() % 4;
+            let text = random_text(&mut rng, len, alphabet);
+            let st = SuffixTree::new(&text);
+            for _ in 0..5 {
+                let len = rng.next_u64() % 5;
+                let pattern = random_text(&mut rng, len, alphabet);
+                let wanted: Vec<_> = (0..text.len())
+                    .filter(|&s| text[s..].starts_with(&pattern))
+                    .collect();
+                assert_eq!(st.find(&pattern), wanted);
+                // The empty pattern also occurs in the empty text.
+                let contained =
+                    (0..=text.len()).any(|s| text[s..].starts_with(&pattern));
+                assert_eq!(st.contains(&pattern), contained);
+            }
+
+            let mut longest: Option<&[u8]> = None;
+            for a in 0..text.len() {
+                for b in a + 1..text.len() {
+                    let common = text[a..]
+                        .iter()
+                        .zip(&text[b..])
+                        .take_while(|(x, y)| x == y)
+                        .count();
+                    let s = &text[a..a + common];
+                    if common > 0
+                        && longest.is_none_or(|l| {
+                            (s.len(), core::cmp::Reverse(s))
+                                > (l.len(), core::cmp::Reverse(l))
+                        })
+                    {
+                        longest = Some(s);
+                    }
+                }
+            }
+            assert_eq!(st.longest_repeated_substring(), longest);
+        }
+    }
+
+    #[test]
+    fn suffix_links_drop_first_byte() {
+        let mut rng = SplitMix64::new(43);
+        for _ in 0..50 {
+            let text = random_text(&mut rng, 60, 3);
+            let st = SuffixTree::new(&text);
+            let leaves = st.nodes.iter().filter(|n| n.is_leaf()).count();
+            assert_eq!(leaves, text.len() + 1);
+            for node in 1..st.nodes.len() {
+                if st.nodes[node].is_leaf() {
+                    continue;
+                }
+                let labels: Vec<_> = st.suffix_links(st.label(node)).collect();
+                assert_eq!(labels.len(), st.nodes[node].depth + 1);
+                for (k, label) in labels.iter().enumerate() {
+                    assert_eq!(*label, &st.label(node)[k..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steps_build_prefixes() {
+        let text = b"abcabxabcd";
+        let mut steps = 0;
+        SuffixTree::with_steps(text, |st| {
+            steps += 1;
+            let prefix = &text[..steps.min(text.len())];
+            for a in 0..prefix.len() {
+                assert!(st.contains(&prefix[a..]));
+            }
+            let mut dot = String::new();
+            st.write_dot(&mut dot).unwrap();
+            assert_eq!(
+                dot.matches("->").count(),
+                st.nodes.len() - 1 + {
+                    st.nodes[1..].iter().filter(|n| !n.is_leaf()).count()
+                }
+            );
+        });
+        assert_eq!(steps, text.len() + 1);
+    }
+}
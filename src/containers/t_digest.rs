@@ -0,0 +1,463 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A summary of a stream of numbers that estimates its quantiles, in memory
+/// that does not depend on the length of the stream.
+///
+/// The numbers are grouped into centroids, each keeping only its mean and its
+/// number of values, sorted by their means. A centroid whose middle is at
+/// quantile q may hold up to 2πn√(q(1 - q)) / δ of the n values, where δ is
+/// the compression, so the centroids are small near the extremes, where
+/// precision matters most, and large in the middle. Quantiles are estimated by
+/// interpolating between the means of neighboring centroids.
+///
+/// New values are buffered and merged into the centroids in batches. The
+/// digest keeps O(δ) centroids, and two digests are merged by merging their
+/// centroids.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::TDigest;
+///
+/// let mut digest = TDigest::new(100.0);
+/// for i in 0..=10_000 {
+///     digest.add(i as f64);
+/// }
+/// let median = digest.quantile(0.5).unwrap();
+/// assert!((median - 5_000.0).abs() < 50.0);
+/// let p = digest.cdf(9_900.0).unwrap();
+/// assert!((p - 0.99).abs() < 0.001);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    // The values and centroids that are yet to be merged, unsorted.
+    buffer: Vec<Centroid>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl TDigest {
+    /// Creates an empty TDigest with the given compression. A larger
+    /// compression keeps more centroids, for more precise estimates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the compression is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let digest = TDigest::new(200.0);
+    /// assert!(digest.is_empty());
+    /// ```
+    pub fn new(compression: f64) -> Self {
+        assert!(compression > 0.0, "Compression must be positive");
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Returns the number of values added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// digest.add(1.0);
+    /// digest.add(1.0);
+    /// assert_eq!(digest.len(), 2);
+    /// ```
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns true if no value was added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// assert!(digest.is_empty());
+    /// digest.add(1.0);
+    /// assert!(!digest.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the smallest value added, or None if no value was added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// digest.add(3.0);
+    /// digest.add(-1.0);
+    /// assert_eq!(digest.min(), Some(-1.0));
+    /// ```
+    pub fn min(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.min)
+        }
+    }
+
+    /// Returns the largest value added, or None if no value was added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// digest.add(3.0);
+    /// digest.add(-1.0);
+    /// assert_eq!(digest.max(), Some(3.0));
+    /// ```
+    pub fn max(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.max)
+        }
+    }
+
+    /// Adds a value to the digest.
+    ///
+    /// This operation should compute in amortized O(log(δ)) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// digest.add(4.0);
+    /// assert_eq!(digest.quantile(0.5), Some(4.0));
+    /// ```
+    pub fn add(&mut self, value: f64) {
+        assert!(!value.is_nan(), "Values must not be NaN");
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        if self.buffer.len() >= self.buffer_capacity() {
+            self.flush();
+        }
+    }
+
+    /// Adds the values of the other digest to this one, as if they had been
+    /// added to this one.
+    ///
+    /// This operation should compute in O(δ log(δ)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut a = TDigest::default();
+    /// let mut b = TDigest::default();
+    /// (0..500).for_each(|i| a.add(i as f64));
+    /// (500..1000).for_each(|i| b.add(i as f64));
+    /// a.merge(&b);
+    /// assert_eq!(a.len(), 1000);
+    /// assert_eq!(a.max(), Some(999.0));
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.buffer.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.flush();
+    }
+
+    /// Returns an estimate of the value below which the fraction `q` of the
+    /// values lie, or None if no value was added.
+    ///
+    /// This operation should compute in O(δ) time, plus the time to merge
+    /// the buffered values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// for i in 1..=100 {
+    ///     digest.add(i as f64);
+    /// }
+    /// assert_eq!(digest.quantile(0.0), Some(1.0));
+    /// assert_eq!(digest.quantile(1.0), Some(100.0));
+    /// ```
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        assert!((0.0..=1.0).contains(&q), "Quantile must be between 0 and 1");
+        if self.is_empty() {
+            return None;
+        }
+        let centroids = self.merged();
+        let n = self.count as f64;
+        let target = q * n;
+        let first = centroids[0];
+        let last = centroids[centroids.len() - 1];
+        if target <= first.weight / 2.0 {
+            return Some(lerp(
+                self.min,
+                first.mean,
+                target / (first.weight / 2.0),
+            ));
+        }
+        if target >= n - last.weight / 2.0 {
+            let rest = (n - target) / (last.weight / 2.0);
+            return Some(lerp(self.max, last.mean, rest));
+        }
+        // The position of the middle of the current centroid.
+        let mut center = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let next = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if target < next {
+                let t = (target - center) / (next - center);
+                return Some(lerp(pair[0].mean, pair[1].mean, t));
+            }
+            center = next;
+        }
+        Some(last.mean)
+    }
+
+    /// Returns an estimate of the fraction of the values that are at most
+    /// `x`, or None if no value was added.
+    ///
+    /// This operation should compute in O(δ) time, plus the time to merge
+    /// the buffered values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::default();
+    /// for i in 1..=100 {
+    ///     digest.add(i as f64);
+    /// }
+    /// assert_eq!(digest.cdf(0.0), Some(0.0));
+    /// assert_eq!(digest.cdf(100.0), Some(1.0));
+    /// ```
+    pub fn cdf(&self, x: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        if x < self.min {
+            return Some(0.0);
+        }
+        if x >= self.max {
+            return Some(1.0);
+        }
+        let centroids = self.merged();
+        let n = self.count as f64;
+        let first = centroids[0];
+        let last = centroids[centroids.len() - 1];
+        if x < first.mean {
+            let t = (x - self.min) / (first.mean - self.min);
+            return Some(t * first.weight / 2.0 / n);
+        }
+        if x >= last.mean {
+            let t = (self.max - x) / (self.max - last.mean);
+            return Some(1.0 - t * last.weight / 2.0 / n);
+        }
+        let mut center = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let next = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if x < pair[1].mean {
+                let t = (x - pair[0].mean) / (pair[1].mean - pair[0].mean);
+                return Some(lerp(center, next, t) / n);
+            }
+            center = next;
+        }
+        Some(1.0)
+    }
+
+    /// Returns the number of centroids, after merging the buffered values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TDigest;
+    ///
+    /// let mut digest = TDigest::new(50.0);
+    /// for i in 0..100_000 {
+    ///     digest.add(i as f64);
+    /// }
+    /// assert!(digest.centroid_count() < 100);
+    /// ```
+    pub fn centroid_count(&self) -> usize {
+        self.merged().len()
+    }
+
+    fn buffer_capacity(&self) -> usize {
+        (self.compression as usize).max(1) * 4
+    }
+
+    /// Merges the buffered values into the centroids.
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            self.centroids = self.merged().into_owned();
+            self.buffer.clear();
+        }
+    }
+
+    /// Returns the centroids with the buffered values merged into them.
+    fn merged(&self) -> Cow<'_, [Centroid]> {
+        if self.buffer.is_empty() {
+            return Cow::Borrowed(&self.centroids);
+        }
+        let mut all = self.centroids.clone();
+        all.extend_from_slice(&self.buffer);
+        all.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap());
+
+        let n: f64 = all.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::new();
+        let mut current = all[0];
+        // The weight of the centroids before the current one.
+        let mut before = 0.0;
+        for &c in &all[1..] {
+            let weight = current.weight + c.weight;
+            let q = (before + weight / 2.0) / n;
+            let limit = 2.0 * PI * n * sqrt(q * (1.0 - q)) / self.compression;
+            if weight <= limit {
+                current.mean += (c.mean - current.mean) * c.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                merged.push(current);
+                current = c;
+            }
+        }
+        merged.push(current);
+        Cow::Owned(merged)
+    }
+}
+
+/// Returns the square root of a number between 0 and 1, which the core
+/// library does not provide.
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // Halving the exponent gives a guess within a few percent, which Newton's
+    // method makes exact in a few steps.
+    let mut y = f64::from_bits((x.to_bits() >> 1) + 0x1ff8_0000_0000_0000);
+    for _ in 0..5 {
+        y = (y + x / y) / 2.0;
+    }
+    y
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    fn shuffled(rng: &mut SplitMix64, n: usize) -> Vec<f64> {
+        let mut values: Vec<f64> = (0..n).map(|i| i as f64).collect();
+        for i in (1..n).rev() {
+            values.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+        }
+        values
+    }
+
+    #[test]
+    fn estimates_uniform_quantiles() {
+        let mut rng = SplitMix64::new(73);
+        let n = 100_000;
+        let mut digest = TDigest::new(100.0);
+        for x in shuffled(&mut rng, n) {
+            digest.add(x);
+        }
+        assert!(digest.centroid_count() <= 100);
+        for &q in &[0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999] {
+            let x = digest.quantile(q).unwrap();
+            assert!((x / n as f64 - q).abs() < 0.001, "{}", q);
+            let p = digest.cdf(q * n as f64).unwrap();
+            assert!((p - q).abs() < 0.001, "{}", q);
+        }
+    }
+
+    #[test]
+    fn merge_of_parts() {
+        let mut rng = SplitMix64::new(79);
+        let values = shuffled(&mut rng, 20_000);
+        let mut parts: Vec<TDigest> =
+            (0..4).map(|_| TDigest::default()).collect();
+        for (i, &x) in values.iter().enumerate() {
+            parts[i % 4].add(x);
+        }
+        let mut digest = TDigest::default();
+        for part in &parts {
+            digest.merge(part);
+        }
+        assert_eq!(digest.len(), 20_000);
+        assert_eq!((digest.min(), digest.max()), (Some(0.0), Some(19_999.0)));
+        for &q in &[0.01, 0.5, 0.99] {
+            let x = digest.quantile(q).unwrap();
+            assert!((x / 20_000.0 - q).abs() < 0.005, "{}", q);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let digest = TDigest::default();
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(digest.cdf(0.0), None);
+        assert_eq!(digest.min(), None);
+    }
+}
@@ -0,0 +1,648 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+
+const NIL: usize = usize::MAX;
+
+// The children of a node, by how the next byte compares to the node's byte.
+const LO: usize = 0;
+const EQ: usize = 1;
+const HI: usize = 2;
+
+#[derive(Debug)]
+struct Node<V> {
+    byte: u8,
+    // The value of the key that ends with this node's byte.
+    value: Option<V>,
+    child: [usize; 3],
+}
+
+/// A map from byte strings to values, implemented with a ternary search tree.
+///
+/// Every node holds one byte of the keys and has three children: the keys
+/// whose byte at this position is lower, the keys that continue after this
+/// byte, and the keys whose byte is higher. It is a trie whose nodes keep
+/// their children in a binary search tree, so a node only takes three links
+/// instead of an array of 256, while a lookup of a key of length m takes
+/// O(m + log(n)) steps on random keys.
+///
+/// Walking the tree while allowing some bytes to differ finds the keys within
+/// a Hamming distance of a query, for example to suggest corrections for a
+/// misspelled word.
+///
+/// The nodes are kept in an arena. Removing a key only clears its value, and
+/// the nodes stay in the tree.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::TernarySearchTree;
+///
+/// let mut words = TernarySearchTree::new();
+/// for (i, word) in ["cat", "cot", "cut", "dog", "cats"].iter().enumerate() {
+///     words.insert(word, i);
+/// }
+///
+/// assert_eq!(words.get("cot"), Some(&1));
+/// let cats: Vec<_> = words.iter_prefix("cat").map(|(_, v)| *v).collect();
+/// assert_eq!(cats, [0, 4]);
+/// let near: Vec<_> =
+///     words.near_neighbors("cbt", 1).map(|(k, _)| k).collect();
+/// assert_eq!(near, [b"cat".to_vec(), b"cot".to_vec(), b"cut".to_vec()]);
+/// ```
+pub struct TernarySearchTree<V> {
+    nodes: Vec<Node<V>>,
+    root: usize,
+    // The value of the empty key, which has no node.
+    empty: Option<V>,
+    len: usize,
+}
+
+impl<V> Default for TernarySearchTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for TernarySearchTree<V> {
+    /// Formats the keys as strings, replacing the bytes that are not valid
+    /// UTF-8.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter().map(|(k, v)| {
+                    (String::from_utf8_lossy(&k).into_owned(), v)
+                }),
+            )
+            .finish()
+    }
+}
+
+impl<V> TernarySearchTree<V> {
+    /// Creates an empty TernarySearchTree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("a", 1);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            empty: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of keys in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("a", 1);
+    /// tree.insert("ab", 2);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert("a", 1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a key-value pair into the tree and returns the old value of the
+    /// key, or None if the key was not in the tree.
+    ///
+    /// This operation should compute in O(m + log(n)) time on random keys,
+    /// where m is the length of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// assert_eq!(tree.insert("a", 1), None);
+    /// assert_eq!(tree.insert("a", 2), Some(1));
+    /// ```
+    pub fn insert<K>(&mut self, key: &K, value: V) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        let slot = match key.split_last() {
+            None => &mut self.empty,
+            Some(_) => {
+                if self.root == NIL {
+                    self.root = self.new_node(key[0]);
+                }
+                let mut n = self.root;
+                let mut i = 0;
+                loop {
+                    let dir = match key[i].cmp(&self.nodes[n].byte) {
+                        Ordering::Less => LO,
+                        Ordering::Greater => HI,
+                        Ordering::Equal if i + 1 == key.len() => {
+                            break;
+                        }
+                        Ordering::Equal => {
+                            i += 1;
+                            EQ
+                        }
+                    };
+                    if self.nodes[n].child[dir] == NIL {
+                        let new = self.new_node(key[i]);
+                        self.nodes[n].child[dir] = new;
+                    }
+                    n = self.nodes[n].child[dir];
+                }
+                &mut self.nodes[n].value
+            }
+        };
+        let old = slot.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the tree.
+    ///
+    /// This operation should compute in O(m + log(n)) time on random keys,
+    /// where m is the length of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("ab", 1);
+    /// assert_eq!(tree.get("ab"), Some(&1));
+    /// assert_eq!(tree.get("a"), None);
+    /// ```
+    pub fn get<K>(&self, key: &K) -> Option<&V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        match self.find(key.as_ref()) {
+            None => self.empty.as_ref(),
+            Some(n) => self.nodes.get(n)?.value.as_ref(),
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the tree.
+    ///
+    /// This operation should compute in O(m + log(n)) time on random keys,
+    /// where m is the length of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("ab", 1);
+    /// *tree.get_mut("ab").unwrap() += 1;
+    /// assert_eq!(tree.get("ab"), Some(&2));
+    /// ```
+    pub fn get_mut<K>(&mut self, key: &K) -> Option<&mut V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        match self.find(key.as_ref()) {
+            None => self.empty.as_mut(),
+            Some(n) => self.nodes.get_mut(n)?.value.as_mut(),
+        }
+    }
+
+    /// Returns true if the key is in the tree.
+    ///
+    /// This operation should compute in O(m + log(n)) time on random keys,
+    /// where m is the length of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("ab", 1);
+    /// assert!(tree.contains_key("ab"));
+    /// assert!(!tree.contains_key("a"));
+    /// ```
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key from the tree and returns its value, or None if the key
+    /// was not in the tree. The nodes of the key are kept.
+    ///
+    /// This operation should compute in O(m + log(n)) time on random keys,
+    /// where m is the length of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let mut tree = TernarySearchTree::new();
+    /// tree.insert("ab", 1);
+    /// assert_eq!(tree.remove("ab"), Some(1));
+    /// assert_eq!(tree.remove("ab"), None);
+    /// ```
+    pub fn remove<K>(&mut self, key: &K) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let value = match self.find(key.as_ref()) {
+            None => self.empty.take(),
+            Some(n) => self.nodes.get_mut(n)?.value.take(),
+        }?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns an iterator visiting the keys that start with the prefix, with
+    /// their values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let entries = vec![("bat", 1), ("bar", 2), ("cat", 3), ("ba", 4)];
+    /// let tree: TernarySearchTree<_> = entries.into_iter().collect();
+    /// let found: Vec<_> = tree.iter_prefix("ba").map(|(_, v)| *v).collect();
+    /// assert_eq!(found, [4, 2, 1]);
+    /// ```
+    pub fn iter_prefix<K>(&self, prefix: &K) -> Iter<'_, V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let prefix = prefix.as_ref();
+        let (first, stack) = match self.find(prefix) {
+            None => (self.empty.as_ref(), vec![Step::Enter(self.root, 0)]),
+            Some(n) => match self.nodes.get(n) {
+                Some(node) => (
+                    node.value.as_ref(),
+                    vec![Step::Enter(node.child[EQ], prefix.len())],
+                ),
+                None => (None, Vec::new()),
+            },
+        };
+        Iter {
+            nodes: &self.nodes,
+            key: prefix.to_vec(),
+            first,
+            stack,
+        }
+    }
+
+    /// Returns an iterator visiting all the keys of the tree, with their
+    /// values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let tree: TernarySearchTree<_> =
+    ///     vec![("b", 1), ("a", 2)].into_iter().collect();
+    /// let keys: Vec<_> = tree.iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [b"a".to_vec(), b"b".to_vec()]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix("")
+    }
+
+    /// Returns an iterator visiting the keys that have the same length as the
+    /// given key and differ from it in at most `max_distance` positions, with
+    /// their values, in lexicographic order of the keys.
+    ///
+    /// The search only follows the branches that stay within the distance, so
+    /// it is much faster than comparing the key with every key of the tree
+    /// when the distance is small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TernarySearchTree;
+    ///
+    /// let tree: TernarySearchTree<_> =
+    ///     vec![("abc", 1), ("abd", 2), ("xyz", 3)].into_iter().collect();
+    /// let found: Vec<_> =
+    ///     tree.near_neighbors("abz", 1).map(|(_, v)| *v).collect();
+    /// assert_eq!(found, [1, 2]);
+    /// assert_eq!(tree.near_neighbors("abz", 2).count(), 3);
+    /// assert_eq!(tree.near_neighbors("ab", 3).count(), 0);
+    /// ```
+    pub fn near_neighbors<K>(
+        &self,
+        key: &K,
+        max_distance: usize,
+    ) -> Neighbors<'_, V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let query = key.as_ref().to_vec();
+        let (first, stack) = if query.is_empty() {
+            (self.empty.as_ref(), Vec::new())
+        } else {
+            (None, vec![(Step::Enter(self.root, 0), 0)])
+        };
+        Neighbors {
+            nodes: &self.nodes,
+            query,
+            max_distance,
+            key: Vec::new(),
+            first,
+            stack,
+        }
+    }
+
+    fn new_node(&mut self, byte: u8) -> usize {
+        self.nodes.push(Node {
+            byte,
+            value: None,
+            child: [NIL; 3],
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Returns None for the empty key, and otherwise the node of the key's
+    /// last byte, which is NIL if the key has no node.
+    fn find(&self, key: &[u8]) -> Option<usize> {
+        if key.is_empty() {
+            return None;
+        }
+        let mut n = self.root;
+        let mut i = 0;
+        while n != NIL {
+            let node = &self.nodes[n];
+            n = match key[i].cmp(&node.byte) {
+                Ordering::Less => node.child[LO],
+                Ordering::Greater => node.child[HI],
+                Ordering::Equal if i + 1 == key.len() => break,
+                Ordering::Equal => {
+                    i += 1;
+                    node.child[EQ]
+                }
+            };
+        }
+        Some(n)
+    }
+}
+
+impl<K: AsRef<[u8]>, V> FromIterator<(K, V)> for TernarySearchTree<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: AsRef<[u8]>, V> Extend<(K, V)> for TernarySearchTree<V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(&key, value);
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a TernarySearchTree<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A pending step of a traversal, with the position of the node's byte in
+/// the keys.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// Visits the subtree of the node.
+    Enter(usize, usize),
+    /// Puts the node's byte in the key, and yields or goes past the node.
+    Visit(usize, usize),
+}
+
+/// An iterator over the keys of a TernarySearchTree, with their values, in
+/// lexicographic order of the keys.
+///
+/// This struct is created by [`TernarySearchTree::iter()`] and
+/// [`TernarySearchTree::iter_prefix()`]. See their documentation for more.
+///
+/// [`TernarySearchTree::iter()`]: crate::containers::TernarySearchTree::iter
+/// [`TernarySearchTree::iter_prefix()`]: crate::containers::TernarySearchTree::iter_prefix
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    nodes: &'a [Node<V>],
+    key: Vec<u8>,
+    // The value of the prefix itself, until it has been yielded.
+    first: Option<&'a V>,
+    // The steps left, with the next one on top.
+    stack: Vec<Step>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.first.take() {
+            return Some((self.key.clone(), value));
+        }
+        while let Some(step) = self.stack.pop() {
+            match step {
+                Step::Enter(n, depth) => {
+                    if n == NIL {
+                        continue;
+                    }
+                    // The children are pushed in reverse so that the lower
+                    // keys come out first.
+                    let child = self.nodes[n].child;
+                    self.stack.push(Step::Enter(child[HI], depth));
+                    self.stack.push(Step::Visit(n, depth));
+                    self.stack.push(Step::Enter(child[LO], depth));
+                }
+                Step::Visit(n, depth) => {
+                    let node = &self.nodes[n];
+                    self.key.truncate(depth);
+                    self.key.push(node.byte);
+                    self.stack.push(Step::Enter(node.child[EQ], depth + 1));
+                    if let Some(value) = &node.value {
+                        return Some((self.key.clone(), value));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the keys of a TernarySearchTree that are within a Hamming
+/// distance of a key, with their values, in lexicographic order of the keys.
+///
+/// This struct is created by [`TernarySearchTree::near_neighbors()`]. See its
+/// documentation for more.
+///
+/// [`TernarySearchTree::near_neighbors()`]: crate::containers::TernarySearchTree::near_neighbors
+#[derive(Debug)]
+pub struct Neighbors<'a, V> {
+    nodes: &'a [Node<V>],
+    query: Vec<u8>,
+    max_distance: usize,
+    key: Vec<u8>,
+    // The value of the empty key, if it is the query.
+    first: Option<&'a V>,
+    // The steps left, with the distance so far, and the next one on top.
+    stack: Vec<(Step, usize)>,
+}
+
+impl<'a, V> Iterator for Neighbors<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.first.take() {
+            return Some((Vec::new(), value));
+        }
+        while let Some((step, distance)) = self.stack.pop() {
+            match step {
+                Step::Enter(n, i) => {
+                    if n == NIL {
+                        continue;
+                    }
+                    // With no differences left, only the branch with the
+                    // query's byte may match.
+                    let node = &self.nodes[n];
+                    let b = self.query[i];
+                    let exact = distance == self.max_distance;
+                    if !exact || b > node.byte {
+                        self.stack
+                            .push((Step::Enter(node.child[HI], i), distance));
+                    }
+                    if !exact || b == node.byte {
+                        let distance = distance + (b != node.byte) as usize;
+                        self.stack.push((Step::Visit(n, i), distance));
+                    }
+                    if !exact || b < node.byte {
+                        self.stack
+                            .push((Step::Enter(node.child[LO], i), distance));
+                    }
+                }
+                Step::Visit(n, i) => {
+                    let node = &self.nodes[n];
+                    self.key.truncate(i);
+                    self.key.push(node.byte);
+                    if i + 1 < self.query.len() {
+                        self.stack.push((
+                            Step::Enter(node.child[EQ], i + 1),
+                            distance,
+                        ));
+                    } else if let Some(value) = &node.value {
+                        return Some((self.key.clone(), value));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    fn random_key(rng: &mut SplitMix64) -> Vec<u8> {
+        let len = rng.next_u64() % 6;
+        (0..len)
+            .map(|_| b"abcd"[(rng.next_u64() % 4) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = SplitMix64::new(29);
+        let mut tree = TernarySearchTree::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..3000 {
+            let key = random_key(&mut rng);
+            if rng.next_u64().is_multiple_of(4) {
+                assert_eq!(tree.remove(&key), expected.remove(&key));
+            } else {
+                assert_eq!(tree.insert(&key, i), expected.insert(key, i));
+            }
+            assert_eq!(tree.len(), expected.len());
+        }
+        assert!(tree.iter().eq(expected.iter().map(|(k, v)| (k.clone(), v))));
+
+        for _ in 0..200 {
+            let query = random_key(&mut rng);
+            let wanted: Vec<_> = expected
+                .iter()
+                .filter(|(k, _)| k.starts_with(&query))
+                .map(|(k, v)| (k.clone(), v))
+                .collect();
+            let found: Vec<_> = tree.iter_prefix(&query).collect();
+            assert_eq!(found, wanted);
+
+            let max_distance = (rng.next_u64() % 3) as usize;
+            let wanted: Vec<_> = expected
+                .iter()
+                .filter(|(k, _)| {
+                    k.len() == query.len()
+                        && k.iter().zip(&query).filter(|(a, b)| a != b).count()
+                            <= max_distance
+                })
+                .map(|(k, v)| (k.clone(), v))
+                .collect();
+            let found: Vec<_> =
+                tree.near_neighbors(&query, max_distance).collect();
+            assert_eq!(found, wanted);
+        }
+    }
+
+    #[test]
+    fn sorted_inserts() {
+        // Sorted keys make long chains of low and high children, which the
+        // traversals walk without recursion.
+        let keys: Vec<_> = (0..=255u8).map(|b| [b, b]).collect();
+        let tree: TernarySearchTree<_> =
+            keys.iter().map(|k| (k, k[0])).collect();
+        assert!(tree.iter().map(|(_, v)| *v).eq(0..=255));
+        assert_eq!(tree.near_neighbors(&[7, 7], 0).count(), 1);
+        assert_eq!(tree.near_neighbors(&[7, 8], 1).count(), 2);
+    }
+}
@@ -0,0 +1,607 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+use super::rng::SplitMix64;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    // The number of nodes in the subtree rooted at this node.
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Splits a tree into the nodes whose keys are less than the key, and those
+/// whose keys are greater than or equal to it.
+fn split<K, V, Q>(link: Link<K, V>, key: &Q) -> (Link<K, V>, Link<K, V>)
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.key.borrow() < key {
+                let (less, rest) = split(node.right.take(), key);
+                node.right = less;
+                node.update();
+                (Some(node), rest)
+            } else {
+                let (less, rest) = split(node.left.take(), key);
+                node.left = rest;
+                node.update();
+                (less, Some(node))
+            }
+        }
+    }
+}
+
+/// Returns the least (`min` is true) or greatest key of a tree.
+fn extreme<K, V>(mut link: &Link<K, V>, min: bool) -> Option<&K> {
+    let mut key = None;
+    while let Some(node) = link {
+        key = Some(&node.key);
+        link = if min { &node.left } else { &node.right };
+    }
+    key
+}
+
+/// Merges two trees, where all the keys of `a` are less than those of `b`.
+/// The root with the higher priority becomes the root of the result.
+fn merge<K, V>(a: Link<K, V>, b: Link<K, V>) -> Link<K, V> {
+    match (a, b) {
+        (None, tree) | (tree, None) => tree,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+/// An ordered map implemented with a treap.
+///
+/// A treap is a binary search tree on the keys that is also a max-heap on
+/// random priorities given to the nodes: no node has a higher priority than
+/// its parent. The shape of the tree is then the one a plain binary search
+/// tree would have if the keys were inserted in the order of their
+/// priorities, that is in random order, so its expected height is O(log(n))
+/// whatever the order of the insertions.
+///
+/// Every operation is built on splitting a tree by key and merging two trees,
+/// which also lets a whole range of keys be split off or merged back in
+/// O(log(n)) expected time.
+///
+/// The priorities come from a pseudorandom number generator. A treap created
+/// with [`Treap::with_seed()`] and given the same operations always has the
+/// same shape, which makes tests deterministic.
+///
+/// [`Treap::with_seed()`]: crate::containers::Treap::with_seed
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Treap;
+///
+/// let mut treap = Treap::with_seed(42);
+/// for k in 0..10 {
+///     treap.insert(k, k * k);
+/// }
+///
+/// assert_eq!(treap.get(&3), Some(&9));
+/// let high = treap.split(&5);
+/// assert!(treap.iter().map(|(k, _)| *k).eq(0..5));
+/// assert!(high.iter().map(|(k, _)| *k).eq(5..10));
+///
+/// treap.merge(high);
+/// assert_eq!(treap.len(), 10);
+/// ```
+pub struct Treap<K, V> {
+    root: Link<K, V>,
+    rng: SplitMix64,
+}
+
+impl<K: Ord, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Treap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Treap<K, V> {
+    /// Returns the number of entries in the treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, "a");
+    /// assert_eq!(treap.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the treap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// assert!(treap.is_empty());
+    /// treap.insert(1, "a");
+    /// assert!(!treap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns an iterator visiting all the entries of the treap in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let treap: Treap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = treap.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> Treap<K, V> {
+    /// Creates an empty Treap, whose priorities come from a generator with a
+    /// fixed seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, "a");
+    /// ```
+    pub fn new() -> Self {
+        Self::with_seed(SplitMix64::DEFAULT_SEED)
+    }
+
+    /// Creates an empty Treap, whose priorities come from a generator with the
+    /// given seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::with_seed(7);
+    /// treap.insert(1, "a");
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Inserts a key-value pair into the treap and returns the old value of
+    /// the key, or None if the key was not in the treap.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// assert_eq!(treap.insert(1, "a"), None);
+    /// assert_eq!(treap.insert(1, "b"), Some("a"));
+    /// assert_eq!(treap.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(mem::replace(old, value));
+        }
+        let node = Box::new(Node {
+            key,
+            value,
+            priority: self.rng.next_u64(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        let (less, rest) = split(self.root.take(), &node.key);
+        self.root = merge(merge(less, Some(node)), rest);
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the treap.
+    ///
+    /// The key may be any borrowed form of the treap's key type, but Ord on
+    /// the borrowed form must match that of the key type.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, "a");
+    /// assert_eq!(treap.get(&1), Some(&"a"));
+    /// assert_eq!(treap.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the treap.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, 10);
+    /// *treap.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(treap.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    /// Returns true if the key is in the treap.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, "a");
+    /// assert!(treap.contains_key(&1));
+    /// assert!(!treap.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key from the treap and returns its value, or None if the
+    /// key was not in the treap. The node of the key is replaced by the merge
+    /// of its two subtrees.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap = Treap::new();
+    /// treap.insert(1, "a");
+    /// assert_eq!(treap.remove(&1), Some("a"));
+    /// assert_eq!(treap.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        fn remove<K, V, Q>(link: &mut Link<K, V>, key: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Ord + ?Sized,
+        {
+            let node = link.as_mut()?;
+            let value = match key.cmp(node.key.borrow()) {
+                Ordering::Less => remove(&mut node.left, key),
+                Ordering::Greater => remove(&mut node.right, key),
+                Ordering::Equal => {
+                    let mut node = link.take()?;
+                    *link = merge(node.left.take(), node.right.take());
+                    return Some(node.value);
+                }
+            };
+            if value.is_some() {
+                node.update();
+            }
+            value
+        }
+        remove(&mut self.root, key)
+    }
+
+    /// Splits the treap in two at the key, and returns a treap holding the
+    /// entries whose keys are greater than or equal to it. The entries whose
+    /// keys are less than it stay in this treap.
+    ///
+    /// The returned treap has its own generator, seeded from this one.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut treap: Treap<_, _> = (0..10).map(|k| (k, ())).collect();
+    /// let high = treap.split(&7);
+    /// assert!(treap.iter().map(|(k, _)| *k).eq(0..7));
+    /// assert!(high.iter().map(|(k, _)| *k).eq(7..10));
+    /// ```
+    pub fn split<Q>(&mut self, key: &Q) -> Treap<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (less, rest) = split(self.root.take(), key);
+        self.root = less;
+        Treap {
+            root: rest,
+            rng: SplitMix64::new(self.rng.next_u64()),
+        }
+    }
+
+    /// Moves all the entries of `other` into the treap.
+    ///
+    /// This operation should compute in O(log(n)) expected time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any key of `other` is not greater than all the keys of the
+    /// treap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Treap;
+    ///
+    /// let mut low: Treap<_, _> = (0..5).map(|k| (k, ())).collect();
+    /// let high: Treap<_, _> = (5..10).map(|k| (k, ())).collect();
+    /// low.merge(high);
+    /// assert!(low.iter().map(|(k, _)| *k).eq(0..10));
+    /// ```
+    pub fn merge(&mut self, mut other: Treap<K, V>) {
+        if let (Some(max), Some(min)) =
+            (extreme(&self.root, false), extreme(&other.root, true))
+        {
+            assert!(
+                max < min,
+                "Cannot merge a treap whose keys are not all greater"
+            );
+        }
+        self.root = merge(self.root.take(), other.root.take());
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for Treap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut treap = Self::new();
+        treap.extend(iter);
+        treap
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for Treap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Treap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a Treap, in ascending order of their keys.
+///
+/// This struct is created by [`Treap::iter()`]. See its documentation for
+/// more.
+///
+/// [`Treap::iter()`]: crate::containers::Treap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    // The nodes whose left subtrees have been visited but which have not been
+    // yielded themselves, with the next one on top.
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the order of the keys, the order of the priorities and the
+    /// sizes, and returns the height of the tree.
+    fn check<K: Ord, V>(link: &Link<K, V>) -> usize {
+        match link {
+            None => 0,
+            Some(node) => {
+                for child in [&node.left, &node.right].iter().copied().flatten()
+                {
+                    assert!(child.priority <= node.priority);
+                }
+                assert!(node.left.as_ref().is_none_or(|l| l.key < node.key));
+                assert!(node.right.as_ref().is_none_or(|r| node.key < r.key));
+                assert_eq!(node.size, 1 + size(&node.left) + size(&node.right));
+                1 + check(&node.left).max(check(&node.right))
+            }
+        }
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut treap = Treap::with_seed(1);
+        let mut expected = BTreeMap::new();
+        // Sorted insertions, which would make a plain binary search tree a
+        // path.
+        for k in 0..1000 {
+            assert_eq!(treap.insert(k, k), expected.insert(k, k));
+        }
+        assert!(check(&treap.root) < 40);
+        for k in (0..1200).step_by(7) {
+            assert_eq!(treap.remove(&k), expected.remove(&k));
+        }
+        check(&treap.root);
+        assert_eq!(treap.len(), expected.len());
+        assert!(treap.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn deterministic_shape() {
+        fn preorder<K: Copy, V>(link: &Link<K, V>, keys: &mut Vec<K>) {
+            if let Some(node) = link {
+                keys.push(node.key);
+                preorder(&node.left, keys);
+                preorder(&node.right, keys);
+            }
+        }
+        let shape = |seed| {
+            let mut treap = Treap::with_seed(seed);
+            treap.extend((0..100).map(|k| (k, ())));
+            let mut keys = Vec::new();
+            preorder(&treap.root, &mut keys);
+            keys
+        };
+        assert_eq!(shape(3), shape(3));
+        assert_ne!(shape(3), shape(4));
+    }
+
+    #[test]
+    fn split_merge() {
+        let mut treap: Treap<_, _> = (0..100).map(|k| (k * 2, ())).collect();
+        let high = treap.split(&51);
+        check(&treap.root);
+        check(&high.root);
+        assert_eq!(treap.len(), 26);
+        assert_eq!(high.len(), 74);
+        assert!(high.iter().map(|(k, _)| *k).eq((26..100).map(|k| k * 2)));
+
+        treap.merge(high);
+        check(&treap.root);
+        assert!(treap.iter().map(|(k, _)| *k).eq((0..100).map(|k| k * 2)));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Cannot merge a treap whose keys are not all greater"
+    )]
+    fn merge_overlapping() {
+        let mut a: Treap<_, _> = (0..10).map(|k| (k, ())).collect();
+        let b: Treap<_, _> = (5..15).map(|k| (k, ())).collect();
+        a.merge(b);
+    }
+}
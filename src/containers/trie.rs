@@ -0,0 +1,597 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::array;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+
+struct Node<V> {
+    value: Option<V>,
+    // The number of keys ending at this node or below it.
+    count: usize,
+    children: [Option<Box<Node<V>>>; 256],
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Self {
+            value: None,
+            count: 0,
+            children: array::from_fn(|_| None),
+        }
+    }
+}
+
+/// A map from byte strings to values, implemented with a prefix tree.
+///
+/// Every node stands for a prefix of the keys and has an array of 256
+/// children, one for each byte that may extend the prefix, so looking up a key
+/// of length m takes O(m) time however many keys there are. The keys are not
+/// stored: they are the paths from the root to the nodes that hold values.
+/// Every node also counts the keys below it, which makes counting the keys
+/// with a prefix as fast as finding the prefix.
+///
+/// The arrays of children make every node take a few kilobytes, which is the
+/// price of the simplest possible lookups. A [`RadixTrie`] stores the same
+/// keys with far fewer nodes, and a [`TernarySearchTree`] with far smaller
+/// ones.
+///
+/// [`RadixTrie`]: crate::containers::RadixTrie
+/// [`TernarySearchTree`]: crate::containers::TernarySearchTree
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Trie;
+///
+/// let mut trie = Trie::new();
+/// trie.insert("tea", 1);
+/// trie.insert("ten", 2);
+/// trie.insert("to", 3);
+///
+/// assert_eq!(trie.get("ten"), Some(&2));
+/// assert_eq!(trie.count_prefix("te"), 2);
+/// let keys: Vec<_> = trie.iter_prefix("te").map(|(k, _)| k).collect();
+/// assert_eq!(keys, [b"tea".to_vec(), b"ten".to_vec()]);
+/// assert_eq!(trie.longest_prefix_match("town"), Some((&b"to"[..], &3)));
+/// ```
+pub struct Trie<V> {
+    root: Box<Node<V>>,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for Trie<V> {
+    /// A long key makes a deep tree, so the nodes are freed one by one instead
+    /// of recursively.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        stack.extend(self.root.children.iter_mut().filter_map(Option::take));
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.children.iter_mut().filter_map(Option::take));
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Trie<V> {
+    /// Formats the keys as strings, replacing the bytes that are not valid
+    /// UTF-8.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter().map(|(k, v)| {
+                    (String::from_utf8_lossy(&k).into_owned(), v)
+                }),
+            )
+            .finish()
+    }
+}
+
+impl<V> Trie<V> {
+    /// Creates an empty Trie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("a", 1);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            root: Box::new(Node::new()),
+        }
+    }
+
+    /// Returns the number of keys in the trie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("a", 1);
+    /// trie.insert("ab", 2);
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    /// Returns true if the trie has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// assert!(trie.is_empty());
+    /// trie.insert("a", 1);
+    /// assert!(!trie.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.count == 0
+    }
+
+    /// Inserts a key-value pair into the trie and returns the old value of the
+    /// key, or None if the key was not in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// assert_eq!(trie.insert("a", 1), None);
+    /// assert_eq!(trie.insert("a", 2), Some(1));
+    /// assert_eq!(trie.insert(b"\xff", 3), None);
+    /// ```
+    pub fn insert<K>(&mut self, key: &K, value: V) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        if let Some(node) = self.find_mut(key) {
+            if let Some(old) = &mut node.value {
+                return Some(mem::replace(old, value));
+            }
+        }
+        let mut node = &mut *self.root;
+        for &b in key {
+            node.count += 1;
+            node = node.children[b as usize]
+                .get_or_insert_with(|| Box::new(Node::new()));
+        }
+        node.count += 1;
+        node.value = Some(value);
+        None
+    }
+
+    /// Returns a reference to the value of the key, or None if the key is not
+    /// in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("ab", 1);
+    /// assert_eq!(trie.get("ab"), Some(&1));
+    /// assert_eq!(trie.get("a"), None);
+    /// ```
+    pub fn get<K>(&self, key: &K) -> Option<&V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.find(key.as_ref())?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the key
+    /// is not in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("ab", 1);
+    /// *trie.get_mut("ab").unwrap() += 1;
+    /// assert_eq!(trie.get("ab"), Some(&2));
+    /// ```
+    pub fn get_mut<K>(&mut self, key: &K) -> Option<&mut V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.find_mut(key.as_ref())?.value.as_mut()
+    }
+
+    /// Returns true if the key is in the trie.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("ab", 1);
+    /// assert!(trie.contains_key("ab"));
+    /// assert!(!trie.contains_key("a"));
+    /// ```
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key from the trie and returns its value, or None if the key
+    /// was not in the trie. The nodes that no longer lead to any key are
+    /// freed.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("ab", 1);
+    /// assert_eq!(trie.remove("ab"), Some(1));
+    /// assert_eq!(trie.remove("ab"), None);
+    /// ```
+    pub fn remove<K>(&mut self, key: &K) -> Option<V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        self.find(key)?.value.as_ref()?;
+        let mut node = &mut *self.root;
+        for (i, &b) in key.iter().enumerate() {
+            node.count -= 1;
+            let slot = &mut node.children[b as usize];
+            if slot.as_ref().is_some_and(|child| child.count == 1) {
+                // The removed key is the only one below the child, so the
+                // whole branch goes, and is taken apart on the way down.
+                let mut branch = slot.take().unwrap();
+                for &b in &key[i + 1..] {
+                    branch = branch.children[b as usize].take().unwrap();
+                }
+                return branch.value.take();
+            }
+            node = slot.as_mut().unwrap();
+        }
+        node.count -= 1;
+        node.value.take()
+    }
+
+    /// Returns the number of keys that start with the prefix, including the
+    /// prefix itself if it is a key.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("car", 1);
+    /// trie.insert("cart", 2);
+    /// trie.insert("cat", 3);
+    /// assert_eq!(trie.count_prefix("car"), 2);
+    /// assert_eq!(trie.count_prefix(""), 3);
+    /// assert_eq!(trie.count_prefix("dog"), 0);
+    /// ```
+    pub fn count_prefix<K>(&self, prefix: &K) -> usize
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        self.find(prefix.as_ref()).map_or(0, |node| node.count)
+    }
+
+    /// Returns the longest key that is a prefix of the given key, as a slice
+    /// of the given key, with its value, or None if no key is a prefix of it.
+    ///
+    /// This operation should compute in O(m) time, where m is the length of
+    /// the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let mut routes = Trie::new();
+    /// routes.insert("/", "root");
+    /// routes.insert("/api", "api");
+    /// routes.insert("/api/users", "users");
+    ///
+    /// let (path, route) = routes.longest_prefix_match("/api/posts").unwrap();
+    /// assert_eq!((path, *route), (&b"/api"[..], "api"));
+    /// assert_eq!(routes.longest_prefix_match("api"), None);
+    /// ```
+    pub fn longest_prefix_match<'k, K>(
+        &self,
+        key: &'k K,
+    ) -> Option<(&'k [u8], &V)>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let key = key.as_ref();
+        let mut node = &*self.root;
+        let mut best = node.value.as_ref().map(|v| (0, v));
+        for (i, &b) in key.iter().enumerate() {
+            match &node.children[b as usize] {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(value) = &node.value {
+                best = Some((i + 1, value));
+            }
+        }
+        best.map(|(len, value)| (&key[..len], value))
+    }
+
+    /// Returns an iterator visiting the keys that start with the prefix, with
+    /// their values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let entries = vec![("bat", 1), ("bar", 2), ("cat", 3), ("ba", 4)];
+    /// let trie: Trie<_> = entries.into_iter().collect();
+    /// let found: Vec<_> = trie.iter_prefix("ba").map(|(_, v)| *v).collect();
+    /// assert_eq!(found, [4, 2, 1]);
+    /// ```
+    pub fn iter_prefix<K>(&self, prefix: &K) -> Iter<'_, V>
+    where
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let prefix = prefix.as_ref();
+        let start = self.find(prefix);
+        Iter {
+            key: prefix.to_vec(),
+            start,
+            stack: Vec::new(),
+            len: start.map_or(0, |node| node.count),
+        }
+    }
+
+    /// Returns an iterator visiting all the keys of the trie, with their
+    /// values, in lexicographic order of the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Trie;
+    ///
+    /// let trie: Trie<_> = vec![("b", 1), ("a", 2)].into_iter().collect();
+    /// let keys: Vec<_> = trie.iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [b"a".to_vec(), b"b".to_vec()]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix("")
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&Node<V>> {
+        let mut node = &*self.root;
+        for &b in key {
+            node = node.children[b as usize].as_ref()?;
+        }
+        Some(node)
+    }
+
+    fn find_mut(&mut self, key: &[u8]) -> Option<&mut Node<V>> {
+        let mut node = &mut *self.root;
+        for &b in key {
+            node = node.children[b as usize].as_mut()?;
+        }
+        Some(node)
+    }
+}
+
+impl<K: AsRef<[u8]>, V> FromIterator<(K, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<K: AsRef<[u8]>, V> Extend<(K, V)> for Trie<V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(&key, value);
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Trie<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of a Trie, with their values, in lexicographic
+/// order of the keys.
+///
+/// This struct is created by [`Trie::iter()`] and [`Trie::iter_prefix()`].
+/// See their documentation for more.
+///
+/// [`Trie::iter()`]: crate::containers::Trie::iter
+/// [`Trie::iter_prefix()`]: crate::containers::Trie::iter_prefix
+pub struct Iter<'a, V> {
+    // The key of the node on top of the stack.
+    key: Vec<u8>,
+    // The node of the prefix, until it has been visited.
+    start: Option<&'a Node<V>>,
+    // The nodes on the path from the node of the prefix, with the next byte
+    // whose child has not been visited yet.
+    stack: Vec<(&'a Node<V>, usize)>,
+    len: usize,
+}
+
+impl<'a, V: fmt::Debug> fmt::Debug for Iter<'a, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter")
+            .field("key", &self.key)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            self.stack.push((start, 0));
+            if let Some(value) = &start.value {
+                self.len -= 1;
+                return Some((self.key.clone(), value));
+            }
+        }
+        while let Some(&mut (node, ref mut next)) = self.stack.last_mut() {
+            let child = (*next..256).find(|&b| node.children[b].is_some());
+            match child {
+                Some(b) => {
+                    *next = b + 1;
+                    let child = node.children[b].as_deref().unwrap();
+                    self.key.push(b as u8);
+                    self.stack.push((child, 0));
+                    if let Some(value) = &child.value {
+                        self.len -= 1;
+                        return Some((self.key.clone(), value));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.key.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the counts of every node, that every leaf holds a value, and
+    /// returns the number of nodes.
+    fn check<V>(node: &Node<V>, is_root: bool) -> usize {
+        let children = node.children.iter().flatten();
+        let count: usize = children.clone().map(|c| c.count).sum();
+        assert_eq!(node.count, count + node.value.is_some() as usize);
+        assert!(is_root || node.count > 0);
+        1 + children.map(|c| check(c, false)).sum::<usize>()
+    }
+
+    fn random_key(rng: &mut SplitMix64) -> Vec<u8> {
+        let len = rng.next_u64() % 6;
+        (0..len)
+            .map(|_| b"abc"[(rng.next_u64() % 3) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = SplitMix64::new(17);
+        let mut trie = Trie::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..2000 {
+            let key = random_key(&mut rng);
+            if rng.next_u64().is_multiple_of(3) {
+                assert_eq!(trie.remove(&key), expected.remove(&key));
+            } else {
+                assert_eq!(trie.insert(&key, i), expected.insert(key, i));
+            }
+            assert_eq!(trie.len(), expected.len());
+        }
+        check(&trie.root, true);
+        assert!(trie.iter().eq(expected.iter().map(|(k, v)| (k.clone(), v))));
+
+        for _ in 0..100 {
+            let prefix = random_key(&mut rng);
+            let wanted: Vec<_> = expected
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix))
+                .map(|(k, v)| (k.clone(), v))
+                .collect();
+            assert_eq!(trie.count_prefix(&prefix), wanted.len());
+            let found: Vec<_> = trie.iter_prefix(&prefix).collect();
+            assert_eq!(found, wanted);
+
+            let longest = (0..=prefix.len())
+                .rev()
+                .find(|&n| expected.contains_key(&prefix[..n]))
+                .map(|n| (&prefix[..n], &expected[&prefix[..n]]));
+            assert_eq!(trie.longest_prefix_match(&prefix), longest);
+        }
+    }
+
+    #[test]
+    fn remove_frees_nodes() {
+        let mut trie = Trie::new();
+        trie.insert("abc", 1);
+        trie.insert("abd", 2);
+        trie.insert("", 0);
+        assert_eq!(check(&trie.root, true), 5);
+        assert_eq!(trie.remove("abc"), Some(1));
+        assert_eq!(check(&trie.root, true), 4);
+        assert_eq!(trie.remove("ab"), None);
+        assert_eq!(trie.remove("abd"), Some(2));
+        assert_eq!(check(&trie.root, true), 1);
+        assert_eq!(trie.remove(""), Some(0));
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    fn long_key() {
+        // Every node takes a few kilobytes, so the key is not too long.
+        let key = alloc::vec![b'x'; 5000];
+        let mut trie = Trie::new();
+        trie.insert(&key, ());
+        assert_eq!(trie.count_prefix(&key[..2500]), 1);
+    }
+}
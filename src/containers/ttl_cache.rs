@@ -0,0 +1,352 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::time::Duration;
+
+use super::clock::Clock;
+use super::linked_hash_map::LinkedHashMap;
+
+/// A cache whose entries expire once a given time has passed since their
+/// insertion.
+///
+/// The cache is a [`LinkedHashMap`] whose entries are all inserted with an
+/// expiry time. Expired entries are never returned, but they are only
+/// reclaimed when their key is accessed mutably or when
+/// [`TtlCache::purge_expired()`] is called, and they are counted by
+/// [`TtlCache::len()`] until then.
+///
+/// The time is read from a [`Clock`], which is the system's clock unless
+/// another one is given with [`TtlCache::with_clock()`].
+///
+/// [`LinkedHashMap`]: crate::containers::LinkedHashMap
+/// [`Clock`]: crate::containers::Clock
+/// [`TtlCache::purge_expired()`]: crate::containers::TtlCache::purge_expired
+/// [`TtlCache::len()`]: crate::containers::TtlCache::len
+/// [`TtlCache::with_clock()`]: crate::containers::TtlCache::with_clock
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dt::containers::{ManualClock, TtlCache};
+///
+/// let clock = ManualClock::new();
+/// let ttl = Duration::from_secs(60);
+/// let mut cache = TtlCache::with_clock(ttl, clock.clone());
+/// cache.insert("session", 42);
+/// cache.insert_with_ttl("token", 7, Duration::from_secs(10));
+///
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(cache.get("session"), Some(&42));
+/// assert_eq!(cache.get("token"), None);
+/// assert_eq!(cache.purge_expired(), 1);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    map: LinkedHashMap<K, V>,
+    ttl: Duration,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty TtlCache whose entries expire after `ttl` by
+    /// default, measured by the system's clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(5));
+    /// assert_eq!(cache.ttl(), Duration::from_secs(5));
+    /// ```
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            map: LinkedHashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Creates an empty TtlCache whose entries expire after `ttl` by
+    /// default, measured by the given clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{ManualClock, TtlCache};
+    ///
+    /// let clock = ManualClock::new();
+    /// let ttl = Duration::from_secs(5);
+    /// let mut cache = TtlCache::with_clock(ttl, clock.clone());
+    /// cache.insert(1, "a");
+    /// clock.advance(Duration::from_secs(5));
+    /// assert!(!cache.contains_key(&1));
+    /// ```
+    pub fn with_clock<C>(ttl: Duration, clock: C) -> Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        let mut cache = Self::new(ttl);
+        cache.map.set_clock(clock);
+        cache
+    }
+
+    /// Returns the time after which the entries inserted with
+    /// [`TtlCache::insert()`] expire.
+    ///
+    /// [`TtlCache::insert()`]: crate::containers::TtlCache::insert
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let cache: TtlCache<i32, i32> =
+    ///     TtlCache::new(Duration::from_millis(10));
+    /// assert_eq!(cache.ttl(), Duration::from_millis(10));
+    /// ```
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the number of entries held, including the expired ones that
+    /// have not been reclaimed yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, "a");
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the cache holds no entries, including expired ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// assert!(cache.is_empty());
+    /// cache.insert(1, "a");
+    /// assert!(!cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Inserts a key-value pair that expires after the default time to live,
+    /// and returns the old value of the key if it had not expired.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// assert_eq!(cache.insert(1, "a"), None);
+    /// assert_eq!(cache.insert(1, "b"), Some("a"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert_with_ttl(key, value, self.ttl)
+    }
+
+    /// Inserts a key-value pair that expires after `ttl`, and returns the old
+    /// value of the key if it had not expired.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{ManualClock, TtlCache};
+    ///
+    /// let clock = ManualClock::new();
+    /// let ttl = Duration::from_secs(5);
+    /// let mut cache = TtlCache::with_clock(ttl, clock.clone());
+    /// cache.insert_with_ttl(1, "a", Duration::from_secs(60));
+    /// clock.advance(Duration::from_secs(30));
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// ```
+    pub fn insert_with_ttl(
+        &mut self,
+        key: K,
+        value: V,
+        ttl: Duration,
+    ) -> Option<V> {
+        self.map.insert_with_ttl(key, value, ttl)
+    }
+
+    /// Returns a reference to the value of the key, or None if the cache does
+    /// not hold the key or its entry has expired.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, "a");
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value of the key, or None if the
+    /// cache does not hold the key or its entry has expired, in which case
+    /// the entry is reclaimed.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, 10);
+    /// *cache.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the cache holds the key and its entry has not expired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, "a");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Removes the key from the cache, and returns its value if its entry
+    /// had not expired.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::TtlCache;
+    ///
+    /// let mut cache = TtlCache::new(Duration::from_secs(5));
+    /// cache.insert(1, "a");
+    /// assert_eq!(cache.remove(&1), Some("a"));
+    /// assert_eq!(cache.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key)
+    }
+
+    /// Reclaims all the expired entries, and returns their number.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dt::containers::{ManualClock, TtlCache};
+    ///
+    /// let clock = ManualClock::new();
+    /// let ttl = Duration::from_secs(5);
+    /// let mut cache = TtlCache::with_clock(ttl, clock.clone());
+    /// cache.insert(1, "a");
+    /// cache.insert(2, "b");
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(cache.len(), 2);
+    /// assert_eq!(cache.purge_expired(), 2);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn purge_expired(&mut self) -> usize {
+        self.map.evict_expired()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::ManualClock;
+
+    #[test]
+    fn expires_in_order_of_deadlines() {
+        let clock = ManualClock::new();
+        let mut cache =
+            TtlCache::with_clock(Duration::from_secs(10), clock.clone());
+        for i in 0..20u64 {
+            cache.insert_with_ttl(i, i, Duration::from_secs(20 - i));
+        }
+        for step in 1..=20u64 {
+            clock.advance(Duration::from_secs(1));
+            // The keys above 20 - step have expired.
+            for i in 0..20 {
+                assert_eq!(cache.get(&i).is_some(), i < 20 - step);
+            }
+            assert_eq!(cache.purge_expired(), 1);
+            assert_eq!(cache.len() as u64, 20 - step);
+        }
+    }
+
+    #[test]
+    fn reinserting_expired_key() {
+        let clock = ManualClock::new();
+        let mut cache =
+            TtlCache::with_clock(Duration::from_secs(1), clock.clone());
+        cache.insert(1, "a");
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get_mut(&1), None);
+        assert!(cache.is_empty());
+        assert_eq!(cache.insert(1, "b"), None);
+        assert_eq!(cache.get(&1), Some(&"b"));
+    }
+}
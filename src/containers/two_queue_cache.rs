@@ -0,0 +1,371 @@
+use core::borrow::Borrow;
+use core::hash::Hash;
+
+use super::linked_hash_map::LinkedHashMap;
+use super::linked_hash_set::LinkedHashSet;
+
+/// A cache that holds a fixed number of entries, and resists being flushed
+/// by a scan over keys that are used only once.
+///
+/// This is the full version of the 2Q algorithm of Johnson and Shasha. A new
+/// entry goes to the A1in queue, from which entries are evicted in FIFO
+/// order, and hits in A1in do not promote the entry. The keys evicted from
+/// A1in are remembered, without their values, in the A1out queue. A key that
+/// is put again while in A1out has been used more than once in a short time,
+/// and its entry goes to the Am queue, from which entries are evicted in LRU
+/// order. Entries are evicted from A1in while it holds more than its share
+/// of the capacity, and from Am otherwise, so a scan only churns A1in.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::TwoQueueCache;
+///
+/// let mut cache = TwoQueueCache::with_queue_sizes(4, 1, 4);
+/// // A hot key is put twice, which moves it to the Am queue.
+/// cache.put("hot", 0);
+/// cache.put("a", 1);
+/// cache.put("hot", 0);
+///
+/// // Scanning through many keys does not evict it.
+/// for i in 0..100 {
+///     cache.put(if i % 2 == 0 { "x" } else { "y" }, i);
+/// }
+/// assert_eq!(cache.get("hot"), Some(&0));
+/// ```
+#[derive(Debug)]
+pub struct TwoQueueCache<K, V> {
+    // The entries used once recently, in FIFO order.
+    a1in: LinkedHashMap<K, V>,
+    // The keys evicted from A1in, in FIFO order.
+    a1out: LinkedHashSet<K>,
+    // The entries used more than once, in LRU order.
+    am: LinkedHashMap<K, V>,
+    capacity: usize,
+    in_capacity: usize,
+    out_capacity: usize,
+}
+
+impl<K, V> TwoQueueCache<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates an empty TwoQueueCache that holds up to `capacity` entries,
+    /// with the sizes of the queues recommended by the paper: A1in holds a
+    /// quarter of the capacity, and A1out remembers half as many keys as the
+    /// capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let cache: TwoQueueCache<&str, i32> = TwoQueueCache::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::with_queue_sizes(capacity, capacity / 4, capacity / 2)
+    }
+
+    /// Creates an empty TwoQueueCache that holds up to `capacity` entries,
+    /// evicts from A1in while it holds more than `in_capacity` entries, and
+    /// remembers up to `out_capacity` keys in A1out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::with_queue_sizes(2, 1, 1);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.put(3, "c");
+    /// assert!(!cache.contains_key(&1));
+    /// ```
+    pub fn with_queue_sizes(
+        capacity: usize,
+        in_capacity: usize,
+        out_capacity: usize,
+    ) -> Self {
+        assert!(capacity > 0, "Capacity must be positive");
+        Self {
+            a1in: LinkedHashMap::new(),
+            a1out: LinkedHashSet::new(),
+            am: LinkedHashMap::new(),
+            capacity,
+            in_capacity,
+            out_capacity,
+        }
+    }
+
+    /// Returns the largest number of entries held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let cache: TwoQueueCache<i32, i32> = TwoQueueCache::new(4);
+    /// assert_eq!(cache.capacity(), 4);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries held, which does not count the keys
+    /// remembered in A1out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, "a");
+    /// cache.put(2, "b");
+    /// cache.put(3, "c");
+    /// assert_eq!(cache.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.a1in.len() + self.am.len()
+    }
+
+    /// Returns true if the cache holds no entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// assert!(cache.is_empty());
+    /// cache.put(1, "a");
+    /// assert!(!cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the value of the key. An entry in Am is marked
+    /// as the most recently used one, and an entry in A1in keeps its place.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value of the key. An entry in Am is
+    /// marked as the most recently used one, and an entry in A1in keeps its
+    /// place.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, 10);
+    /// *cache.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.peek(&1), Some(&11));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.am.get_refresh(key) {
+            Some(value) => Some(value),
+            None => self.a1in.get_mut(key),
+        }
+    }
+
+    /// Returns a reference to the value of the key, without marking the
+    /// entry as used.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// ```
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.am.get(key).or_else(|| self.a1in.get(key))
+    }
+
+    /// Returns true if the cache holds an entry for the key. The keys that
+    /// are only remembered in A1out are not held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, "a");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.am.contains_key(key) || self.a1in.contains_key(key)
+    }
+
+    /// Inserts a key-value pair, and returns the old value of the key if it
+    /// was held. A new entry goes to Am if its key was remembered in A1out,
+    /// and to A1in otherwise, after evicting an entry if the cache is full.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// assert_eq!(cache.put(1, "a"), None);
+    /// assert_eq!(cache.put(1, "b"), Some("a"));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.am.get_refresh(&key) {
+            return Some(core::mem::replace(old, value));
+        }
+        if let Some(old) = self.a1in.get_mut(&key) {
+            return Some(core::mem::replace(old, value));
+        }
+        if self.len() == self.capacity {
+            self.reclaim();
+        }
+        if self.a1out.remove(&key) {
+            self.am.insert(key, value);
+        } else {
+            self.a1in.insert(key, value);
+        }
+        None
+    }
+
+    /// Removes the key from the cache, and returns its value if it was held.
+    /// The key is also forgotten by A1out.
+    ///
+    /// This operation should compute in expected O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(2);
+    /// cache.put(1, "a");
+    /// assert_eq!(cache.pop(&1), Some("a"));
+    /// assert_eq!(cache.pop(&1), None);
+    /// ```
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.a1out.remove(key);
+        self.am.remove(key).or_else(|| self.a1in.remove(key))
+    }
+
+    /// Evicts an entry to make room for a new one.
+    fn reclaim(&mut self) {
+        if self.a1in.len() > self.in_capacity || self.am.is_empty() {
+            if let Some((key, _)) = self.a1in.pop_front() {
+                self.a1out.insert(key);
+                if self.a1out.len() > self.out_capacity {
+                    self.a1out.pop_front();
+                }
+            }
+        } else {
+            self.am.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use crate::containers::LruCache;
+
+    #[test]
+    fn resists_scans_better_than_lru() {
+        // Hot keys are used half of the time, and the other half is a scan
+        // over keys that are never used again.
+        let mut rng = SplitMix64::new(103);
+        let mut two_queue = TwoQueueCache::new(32);
+        let mut lru = LruCache::new(32);
+        let (mut two_queue_hits, mut lru_hits) = (0, 0);
+        for i in 0..20_000u64 {
+            let key = match rng.next_u64() % 2 {
+                0 => rng.next_u64() % 16,
+                _ => 1000 + i,
+            };
+            match two_queue.get(&key) {
+                Some(_) => two_queue_hits += 1,
+                None => {
+                    two_queue.put(key, i);
+                }
+            }
+            match lru.get(&key) {
+                Some(_) => lru_hits += 1,
+                None => {
+                    lru.put(key, i);
+                }
+            }
+            assert!(two_queue.len() <= 32);
+        }
+        assert!(two_queue_hits > lru_hits, "{} {}", two_queue_hits, lru_hits);
+    }
+
+    #[test]
+    fn ghost_hit_promotes_to_am() {
+        let mut cache = TwoQueueCache::with_queue_sizes(2, 1, 2);
+        cache.put(1, "a");
+        cache.put(2, "b");
+        cache.put(3, "c");
+        assert!(!cache.contains_key(&1));
+        assert!(cache.a1out.contains(&1));
+        cache.put(1, "d");
+        assert!(cache.am.contains_key(&1));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.peek(&1), Some(&"d"));
+    }
+}
@@ -0,0 +1,670 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+// The number of bits of the universe below which a node is a single word.
+const LEAF_BITS: u32 = 6;
+
+/// A set of the integers below a capacity fixed at construction, where
+/// finding the next or the previous integer of the set is as fast as a
+/// membership test.
+///
+/// A van Emde Boas tree over a universe of `2^k` integers splits an integer
+/// into its high `k - k / 2` bits and its low `k / 2` bits. The integers
+/// with the same high bits go into one cluster, which is itself a tree over
+/// a universe of `2^(k / 2)` integers, and a summary tree over the high bits
+/// records which clusters are not empty. Every node also keeps its minimum
+/// and its maximum, and the minimum is not stored in any cluster, so each
+/// operation recurses into only one node of half as many bits, which gives
+/// O(log(log(U))) time for a universe of U integers. Universes of at most
+/// 64 integers are single words, and empty clusters are not allocated, but
+/// every allocated node keeps a table of its O(sqrt(U)) clusters, so the
+/// capacity should stay well below the memory of the machine.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::VebTree;
+///
+/// let mut set = VebTree::with_capacity(1 << 20);
+/// set.insert(42);
+/// set.insert(7);
+/// set.insert(100_000);
+///
+/// assert_eq!(set.successor(42), Some(100_000));
+/// assert_eq!(set.predecessor(42), Some(7));
+/// assert_eq!(set.min(), Some(7));
+///
+/// set.remove(7);
+/// assert_eq!(set.predecessor(42), None);
+/// assert_eq!(set.iter().collect::<Vec<_>>(), [42, 100_000]);
+/// ```
+#[derive(Clone)]
+pub struct VebTree {
+    root: Option<Node>,
+    bits: u32,
+    capacity: usize,
+    len: usize,
+}
+
+#[derive(Clone)]
+enum Node {
+    Leaf(u64),
+    Branch(Box<Branch>),
+}
+
+#[derive(Clone)]
+struct Branch {
+    // The minimum is not stored in the clusters, the maximum is unless it is
+    // also the minimum.
+    min: usize,
+    max: usize,
+    summary: Option<Node>,
+    clusters: Vec<Option<Node>>,
+}
+
+impl fmt::Debug for VebTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl VebTree {
+    /// Creates an empty VebTree that can hold the integers below the
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let set = VebTree::with_capacity(1000);
+    /// assert_eq!(set.capacity(), 1000);
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let bits = match capacity {
+            0 | 1 => 0,
+            _ => usize::BITS - (capacity - 1).leading_zeros(),
+        };
+        Self {
+            root: None,
+            bits,
+            capacity,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of integers the set can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let set = VebTree::with_capacity(1000);
+    /// assert_eq!(set.capacity(), 1000);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of integers in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// set.insert(3);
+    /// set.insert(3);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the set contains no integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// assert!(set.is_empty());
+    /// set.insert(3);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts the integer. Returns true if it was not already in the set.
+    ///
+    /// This operation should compute in O(log(log(U))) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integer is not below the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// assert!(set.insert(3));
+    /// assert!(!set.insert(3));
+    /// ```
+    pub fn insert(&mut self, value: usize) -> bool {
+        assert!(value < self.capacity, "Out of bounds access");
+        let inserted = match &mut self.root {
+            Some(root) => root.insert(self.bits, value),
+            None => {
+                self.root = Some(Node::singleton(self.bits, value));
+                true
+            }
+        };
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    /// Removes the integer. Returns true if it was in the set.
+    ///
+    /// This operation should compute in O(log(log(U))) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integer is not below the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// set.insert(3);
+    /// assert!(set.remove(3));
+    /// assert!(!set.remove(3));
+    /// ```
+    pub fn remove(&mut self, value: usize) -> bool {
+        assert!(value < self.capacity, "Out of bounds access");
+        let (removed, empty) = match &mut self.root {
+            Some(root) => root.remove(self.bits, value),
+            None => (false, false),
+        };
+        if empty {
+            self.root = None;
+        }
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Returns true if the integer is in the set. The integers that are not
+    /// below the capacity are never in the set.
+    ///
+    /// This operation should compute in O(log(log(U))) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// set.insert(3);
+    /// assert!(set.contains(3));
+    /// assert!(!set.contains(4));
+    /// assert!(!set.contains(100));
+    /// ```
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity
+            && self
+                .root
+                .as_ref()
+                .is_some_and(|root| root.contains(self.bits, value))
+    }
+
+    /// Returns the smallest integer in the set, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// assert_eq!(set.min(), None);
+    /// set.insert(5);
+    /// set.insert(2);
+    /// assert_eq!(set.min(), Some(2));
+    /// ```
+    pub fn min(&self) -> Option<usize> {
+        self.root.as_ref().map(Node::min)
+    }
+
+    /// Returns the largest integer in the set, or None if it is empty.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// assert_eq!(set.max(), None);
+    /// set.insert(5);
+    /// set.insert(2);
+    /// assert_eq!(set.max(), Some(5));
+    /// ```
+    pub fn max(&self) -> Option<usize> {
+        self.root.as_ref().map(Node::max)
+    }
+
+    /// Returns the smallest integer in the set that is greater than the
+    /// given one, or None if there is none.
+    ///
+    /// This operation should compute in O(log(log(U))) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(100);
+    /// set.insert(10);
+    /// set.insert(20);
+    /// assert_eq!(set.successor(10), Some(20));
+    /// assert_eq!(set.successor(15), Some(20));
+    /// assert_eq!(set.successor(20), None);
+    /// ```
+    pub fn successor(&self, value: usize) -> Option<usize> {
+        if value >= self.capacity {
+            return None;
+        }
+        self.root.as_ref()?.successor(self.bits, value)
+    }
+
+    /// Returns the largest integer in the set that is less than the given
+    /// one, or None if there is none.
+    ///
+    /// This operation should compute in O(log(log(U))) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(100);
+    /// set.insert(10);
+    /// set.insert(20);
+    /// assert_eq!(set.predecessor(20), Some(10));
+    /// assert_eq!(set.predecessor(15), Some(10));
+    /// assert_eq!(set.predecessor(10), None);
+    /// assert_eq!(set.predecessor(500), Some(20));
+    /// ```
+    pub fn predecessor(&self, value: usize) -> Option<usize> {
+        if value >= self.capacity {
+            return self.max();
+        }
+        self.root.as_ref()?.predecessor(self.bits, value)
+    }
+
+    /// Removes all the integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(10);
+    /// set.insert(3);
+    /// set.clear();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator visiting the integers of the set in increasing
+    /// order.
+    ///
+    /// Visiting the k integers should compute in O(k log(log(U))) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VebTree;
+    ///
+    /// let mut set = VebTree::with_capacity(200);
+    /// set.insert(150);
+    /// set.insert(3);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), [3, 150]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            tree: self,
+            next: self.min(),
+            len: self.len,
+        }
+    }
+}
+
+impl Node {
+    fn singleton(bits: u32, value: usize) -> Self {
+        if bits <= LEAF_BITS {
+            return Node::Leaf(1 << value);
+        }
+        let clusters = (0..1usize << (bits - bits / 2)).map(|_| None);
+        Node::Branch(Box::new(Branch {
+            min: value,
+            max: value,
+            summary: None,
+            clusters: clusters.collect(),
+        }))
+    }
+
+    fn min(&self) -> usize {
+        match self {
+            Node::Leaf(word) => word.trailing_zeros() as usize,
+            Node::Branch(branch) => branch.min,
+        }
+    }
+
+    fn max(&self) -> usize {
+        match self {
+            Node::Leaf(word) => 63 - word.leading_zeros() as usize,
+            Node::Branch(branch) => branch.max,
+        }
+    }
+
+    fn contains(&self, bits: u32, value: usize) -> bool {
+        match self {
+            Node::Leaf(word) => word >> value & 1 == 1,
+            Node::Branch(branch) => {
+                let (high, low) = split(bits, value);
+                value == branch.min
+                    || value == branch.max
+                    || branch.clusters[high]
+                        .as_ref()
+                        .is_some_and(|cluster| cluster.contains(bits / 2, low))
+            }
+        }
+    }
+
+    fn insert(&mut self, bits: u32, mut value: usize) -> bool {
+        let branch = match self {
+            Node::Leaf(word) => {
+                let inserted = *word >> value & 1 == 0;
+                *word |= 1 << value;
+                return inserted;
+            }
+            Node::Branch(branch) => branch,
+        };
+        if value == branch.min {
+            return false;
+        }
+        if value < branch.min {
+            // The new minimum stays out of the clusters, and the old one
+            // goes into them instead.
+            core::mem::swap(&mut value, &mut branch.min);
+        }
+        branch.max = branch.max.max(value);
+        let (high, low) = split(bits, value);
+        match &mut branch.clusters[high] {
+            Some(cluster) => cluster.insert(bits / 2, low),
+            slot => {
+                *slot = Some(Node::singleton(bits / 2, low));
+                match &mut branch.summary {
+                    Some(summary) => {
+                        summary.insert(bits - bits / 2, high);
+                    }
+                    None => {
+                        branch.summary =
+                            Some(Node::singleton(bits - bits / 2, high));
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    // Returns whether the value was removed, and whether the node is now
+    // empty, in which case it is left as it was and must be dropped.
+    fn remove(&mut self, bits: u32, mut value: usize) -> (bool, bool) {
+        let branch = match self {
+            Node::Leaf(word) => {
+                let removed = *word >> value & 1 == 1;
+                *word &= !(1 << value);
+                return (removed, *word == 0);
+            }
+            Node::Branch(branch) => branch,
+        };
+        if branch.min == branch.max {
+            let removed = value == branch.min;
+            return (removed, removed);
+        }
+        let low_bits = bits / 2;
+        if value == branch.min {
+            // The smallest integer of the clusters becomes the minimum, and
+            // is removed from its cluster instead.
+            let high = branch.summary.as_ref().unwrap().min();
+            let low = branch.clusters[high].as_ref().unwrap().min();
+            value = high << low_bits | low;
+            branch.min = value;
+        }
+        let (high, low) = split(bits, value);
+        let (removed, empty) = match &mut branch.clusters[high] {
+            Some(cluster) => cluster.remove(low_bits, low),
+            None => return (false, false),
+        };
+        if !removed {
+            return (false, false);
+        }
+        if empty {
+            branch.clusters[high] = None;
+            let summary = branch.summary.as_mut().unwrap();
+            if summary.remove(bits - low_bits, high).1 {
+                branch.summary = None;
+            }
+        }
+        if value == branch.max {
+            branch.max = match &branch.summary {
+                Some(summary) => {
+                    let high = summary.max();
+                    high << low_bits
+                        | branch.clusters[high].as_ref().unwrap().max()
+                }
+                None => branch.min,
+            };
+        }
+        (true, false)
+    }
+
+    fn successor(&self, bits: u32, value: usize) -> Option<usize> {
+        let branch = match self {
+            Node::Leaf(word) => {
+                let above = word.checked_shr(value as u32 + 1).unwrap_or(0);
+                return match above {
+                    0 => None,
+                    _ => Some(value + 1 + above.trailing_zeros() as usize),
+                };
+            }
+            Node::Branch(branch) => branch,
+        };
+        if value < branch.min {
+            return Some(branch.min);
+        }
+        let low_bits = bits / 2;
+        let (high, low) = split(bits, value);
+        if let Some(cluster) = &branch.clusters[high] {
+            if low < cluster.max() {
+                let low = cluster.successor(low_bits, low).unwrap();
+                return Some(high << low_bits | low);
+            }
+        }
+        let high = branch.summary.as_ref()?.successor(bits - low_bits, high)?;
+        Some(high << low_bits | branch.clusters[high].as_ref().unwrap().min())
+    }
+
+    fn predecessor(&self, bits: u32, value: usize) -> Option<usize> {
+        let branch = match self {
+            Node::Leaf(word) => {
+                let below = word & ((1 << value) - 1);
+                return match below {
+                    0 => None,
+                    _ => Some(63 - below.leading_zeros() as usize),
+                };
+            }
+            Node::Branch(branch) => branch,
+        };
+        if value > branch.max {
+            return Some(branch.max);
+        }
+        let low_bits = bits / 2;
+        let (high, low) = split(bits, value);
+        if let Some(cluster) = &branch.clusters[high] {
+            if low > cluster.min() {
+                let low = cluster.predecessor(low_bits, low).unwrap();
+                return Some(high << low_bits | low);
+            }
+        }
+        let summary = branch.summary.as_ref();
+        match summary.and_then(|s| s.predecessor(bits - low_bits, high)) {
+            Some(high) => Some(
+                high << low_bits
+                    | branch.clusters[high].as_ref().unwrap().max(),
+            ),
+            None if value > branch.min => Some(branch.min),
+            None => None,
+        }
+    }
+}
+
+// Splits the value into its cluster and its position in the cluster.
+fn split(bits: u32, value: usize) -> (usize, usize) {
+    let low_bits = bits / 2;
+    (value >> low_bits, value & ((1 << low_bits) - 1))
+}
+
+impl<'a> IntoIterator for &'a VebTree {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for VebTree {
+    /// Inserts the integers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an integer is not below the capacity.
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// An iterator over the integers of a [`VebTree`] in increasing order.
+///
+/// This struct is created by [`VebTree::iter()`]. See its documentation for
+/// more.
+///
+/// [`VebTree`]: crate::containers::VebTree
+/// [`VebTree::iter()`]: crate::containers::VebTree::iter
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    tree: &'a VebTree,
+    next: Option<usize>,
+    len: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next?;
+        self.next = self.tree.successor(value);
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeSet;
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = SplitMix64::new(47);
+        for capacity in [1, 2, 63, 64, 65, 1000, 1 << 12, 100_000] {
+            let mut set = VebTree::with_capacity(capacity);
+            let mut expected = BTreeSet::new();
+            let range = capacity.min(2000) as u64;
+            for _ in 0..3000 {
+                let value = (rng.next_u64() % range) as usize;
+                match rng.next_u64() % 3 {
+                    0 => assert_eq!(set.remove(value), expected.remove(&value)),
+                    _ => assert_eq!(set.insert(value), expected.insert(value)),
+                }
+                let probe = (rng.next_u64() % capacity as u64) as usize;
+                assert_eq!(set.contains(probe), expected.contains(&probe));
+                assert_eq!(
+                    set.successor(probe),
+                    expected.range(probe + 1..).next().copied()
+                );
+                assert_eq!(
+                    set.predecessor(probe),
+                    expected.range(..probe).next_back().copied()
+                );
+                assert_eq!(set.min(), expected.first().copied());
+                assert_eq!(set.max(), expected.last().copied());
+                assert_eq!(set.len(), expected.len());
+            }
+            assert!(set.iter().eq(expected.iter().copied()));
+            for value in expected {
+                assert!(set.remove(value));
+            }
+            assert!(set.is_empty());
+            assert_eq!(set.min(), None);
+        }
+    }
+
+    #[test]
+    fn sparse_large_universe() {
+        let mut set = VebTree::with_capacity(1 << 30);
+        let values = [0, 1, 1 << 20, (1 << 20) + 1, (1 << 30) - 1];
+        set.extend(values.iter().rev().copied());
+        assert!(set.iter().eq(values.iter().copied()));
+        assert_eq!(set.successor(1), Some(1 << 20));
+        assert_eq!(set.predecessor((1 << 30) - 1), Some((1 << 20) + 1));
+        assert!(set.remove(1 << 20));
+        assert_eq!(set.successor(1), Some((1 << 20) + 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn insert_out_of_bounds() {
+        VebTree::with_capacity(10).insert(10);
+    }
+}
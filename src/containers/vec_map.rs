@@ -0,0 +1,769 @@
+use alloc::vec::{self, Vec};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+use core::slice;
+
+/// An ordered map implemented with a vector of entries sorted by their keys.
+///
+/// A key is looked up with a binary search, in O(log(n)) time, and the
+/// entries sit next to each other in memory, so lookups and iteration touch
+/// few cache lines. Inserting or removing a key shifts the entries after it,
+/// which takes O(n) time, so the map suits small and medium sizes, or maps
+/// that are built once and mostly read. Building the map from an iterator
+/// sorts the entries once instead of inserting them one by one.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::VecMap;
+///
+/// let mut map: VecMap<_, _> =
+///     vec![(3, "c"), (1, "a"), (2, "b")].into_iter().collect();
+/// assert_eq!(map.get(&2), Some(&"b"));
+///
+/// *map.entry(4).or_insert("") = "d";
+/// assert_eq!(map.remove(&1), Some("a"));
+/// let values: Vec<_> = map.range(2..).map(|(_, v)| *v).collect();
+/// assert_eq!(values, ["b", "c", "d"]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct VecMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for VecMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> VecMap<K, V> {
+    /// Creates an empty VecMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<i32, i32> = VecMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Creates an empty VecMap with room for `capacity` entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<i32, i32> = VecMap::with_capacity(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of entries the map can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<i32, i32> = VecMap::with_capacity(10);
+    /// assert!(map.capacity() >= 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// assert!(map.is_empty());
+    /// map.insert(1, "a");
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<_, _> =
+    ///     vec![(2, "b"), (1, "a"), (3, "c")].into_iter().collect();
+    /// let keys: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator visiting all the entries of the map in ascending
+    /// order of their keys, with mutable references to the values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map: VecMap<_, _> = (0..3).map(|k| (k, k)).collect();
+    /// for (_, v) in map.iter_mut() {
+    ///     *v *= 10;
+    /// }
+    /// assert_eq!(map.get(&2), Some(&20));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.entries.iter_mut(),
+        }
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.first_key_value(), Some((&1, &"a")));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.first().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry with the largest key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<_, _> =
+    ///     vec![(2, "b"), (1, "a")].into_iter().collect();
+    /// assert_eq!(map.last_key_value(), Some((&2, &"b")));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: Ord, V> VecMap<K, V> {
+    /// Creates a VecMap from entries that are already in strictly ascending
+    /// order of their keys, without sorting them.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys are not in strictly ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map = VecMap::from_sorted_iter((0..5).map(|k| (k, k * k)));
+    /// assert_eq!(map.get(&3), Some(&9));
+    /// ```
+    pub fn from_sorted_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries: Vec<_> = iter.into_iter().collect();
+        assert!(
+            entries.windows(2).all(|w| w[0].0 < w[1].0),
+            "Keys must be sorted"
+        );
+        Self { entries }
+    }
+
+    /// Inserts a key-value pair into the map. If the map did not have the
+    /// key, None is returned. Otherwise, the value is updated, and the old
+    /// value is returned.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// assert_eq!(map.insert(1, "a"), None);
+    /// assert_eq!(map.insert(1, "b"), Some("a"));
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(i) => Some(mem::replace(&mut self.entries[i].1, value)),
+            Err(i) => {
+                self.entries.insert(i, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the value of the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.search(key).ok()?;
+        Some(&self.entries[i].1)
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// map.insert(1, "a");
+    /// if let Some(v) = map.get_mut(&1) {
+    ///     *v = "b";
+    /// }
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.search(key).ok()?;
+        Some(&mut self.entries[i].1)
+    }
+
+    /// Returns true if the map contains the key.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// map.insert(1, "a");
+    /// assert!(map.contains_key(&1));
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Removes the key from the map, and returns its value.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut map = VecMap::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.search(key).ok()?;
+        Some(self.entries.remove(i).1)
+    }
+
+    /// Returns the entry of the key, for in-place manipulation.
+    ///
+    /// This operation should compute in O(log(n)) time, plus O(n) time if a
+    /// value is inserted into a vacant entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let mut letters = VecMap::new();
+    /// for c in "hello".chars() {
+    ///     *letters.entry(c).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(letters.get(&'l'), Some(&2));
+    /// assert_eq!(letters.len(), 4);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.search(&key) {
+            Ok(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                index,
+            }),
+        }
+    }
+
+    /// Returns an iterator visiting the entries of the map whose keys are in
+    /// the range, in ascending order of their keys. The iterator is empty if
+    /// the start of the range is after its end.
+    ///
+    /// This operation should compute in O(log(n)) time, and each step of the
+    /// iterator in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecMap;
+    ///
+    /// let map: VecMap<_, _> = (0..10).map(|k| (k, k * k)).collect();
+    /// let squares: Vec<_> = map.range(3..6).map(|(_, v)| *v).collect();
+    /// assert_eq!(squares, [9, 16, 25]);
+    /// let keys: Vec<_> = map.range(..=2).rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [2, 1, 0]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(key) => self.position(key, false),
+            Bound::Excluded(key) => self.position(key, true),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => self.position(key, true),
+            Bound::Excluded(key) => self.position(key, false),
+            Bound::Unbounded => self.len(),
+        };
+        let end = end.max(start);
+        Range {
+            iter: self.entries[start..end].iter(),
+        }
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Returns the number of keys less than the key, also counting the key
+    /// itself if `after` is true.
+    fn position<Q>(&self, key: &Q, after: bool) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries
+            .partition_point(|(k, _)| match k.borrow().cmp(key) {
+                Ordering::Less => true,
+                Ordering::Equal => after,
+                Ordering::Greater => false,
+            })
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for VecMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for VecMap<K, V> {
+    /// Inserts the entries, with a later value of a key replacing an earlier
+    /// one. The entries are appended and the map is sorted once, which takes
+    /// O((n + m) log(n + m)) time for m new entries.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.entries.extend(iter);
+        // The sort is stable, so the last of the entries with equal keys is
+        // the latest one, and it is swapped into the place that is kept.
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        self.entries.dedup_by(|later, kept| {
+            let duplicate = later.0 == kept.0;
+            if duplicate {
+                mem::swap(later, kept);
+            }
+            duplicate
+        });
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut VecMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// A view into a single entry in a [`VecMap`], which may either be vacant or
+/// occupied.
+///
+/// This enum is created by [`VecMap::entry()`]. See its documentation for
+/// more.
+///
+/// [`VecMap`]: crate::containers::VecMap
+/// [`VecMap::entry()`]: crate::containers::VecMap::entry
+#[derive(Debug)]
+pub enum Entry<'a, K, V> {
+    /// An entry whose key is in the map.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// An entry whose key is not in the map.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Calls `f` on the value if the entry is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Inserts the value if the entry is vacant, and returns a mutable
+    /// reference to the value of the entry.
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        self.or_insert_with(|| value)
+    }
+
+    /// Inserts the result of `f` if the entry is vacant, and returns a mutable
+    /// reference to the value of the entry.
+    pub fn or_insert_with<F>(self, f: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Inserts the default value if the entry is vacant, and returns a
+    /// mutable reference to the value of the entry.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+}
+
+/// A view into an occupied entry in a [`VecMap`]. It is part of the
+/// [`Entry`] enum.
+///
+/// [`VecMap`]: crate::containers::VecMap
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut VecMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    /// Returns a reference to the value of the entry.
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Returns a mutable reference to the value of the entry, that lives as
+    /// long as the borrow of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Replaces the value of the entry, and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, and returns its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.entries.remove(self.index)
+    }
+}
+
+/// A view into a vacant entry in a [`VecMap`]. It is part of the [`Entry`]
+/// enum.
+///
+/// [`VecMap`]: crate::containers::VecMap
+#[derive(Debug)]
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut VecMap<K, V>,
+    key: K,
+    index: usize,
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Inserts the value at the position of the key, and returns a mutable
+    /// reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.entries.insert(self.index, (self.key, value));
+        &mut self.map.entries[self.index].1
+    }
+}
+
+/// An iterator over the entries of a VecMap, in ascending order of their
+/// keys.
+///
+/// This struct is created by [`VecMap::iter()`]. See its documentation for
+/// more.
+///
+/// [`VecMap::iter()`]: crate::containers::VecMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    iter: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// An iterator over the entries of a VecMap, in ascending order of their
+/// keys, with mutable references to the values.
+///
+/// This struct is created by [`VecMap::iter_mut()`]. See its documentation
+/// for more.
+///
+/// [`VecMap::iter_mut()`]: crate::containers::VecMap::iter_mut
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    iter: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+/// An iterator over a range of the entries of a VecMap, in ascending order of
+/// their keys.
+///
+/// This struct is created by [`VecMap::range()`]. See its documentation for
+/// more.
+///
+/// [`VecMap::range()`]: crate::containers::VecMap::range
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    iter: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Range<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeMap;
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = SplitMix64::new(41);
+        let mut map = VecMap::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..2000 {
+            let key = rng.next_u64() % 200;
+            match rng.next_u64() % 4 {
+                0 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                1 => {
+                    *map.entry(key).or_insert(0) += i;
+                    *expected.entry(key).or_insert(0) += i;
+                }
+                _ => assert_eq!(map.insert(key, i), expected.insert(key, i)),
+            }
+            assert_eq!(map.get(&key), expected.get(&key));
+        }
+        assert!(map.iter().eq(expected.iter()));
+        for &(lo, hi) in &[(0, 200), (50, 60), (199, 199), (300, 400)] {
+            assert!(map.range(lo..hi).eq(expected.range(lo..hi)));
+            assert!(map.range(lo..=hi).rev().eq(expected.range(lo..=hi).rev()));
+            let after = (Bound::Excluded(lo), Bound::Unbounded);
+            assert!(map.range(after).eq(expected.range(after)));
+        }
+        let (lo, hi) = (60, 50);
+        assert_eq!(map.range(lo..hi).next(), None);
+    }
+
+    #[test]
+    fn collect_keeps_last_value() {
+        let map: VecMap<_, _> =
+            vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e')]
+                .into_iter()
+                .collect();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), [(1, 'd'), (2, 'e')]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys must be sorted")]
+    fn from_unsorted_iter() {
+        VecMap::from_sorted_iter(vec![(1, ()), (1, ())]);
+    }
+}
@@ -0,0 +1,722 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FromIterator, Peekable};
+use core::ops::{BitAnd, BitOr, BitXor, RangeBounds, Sub};
+
+use super::vec_map::{self, VecMap};
+
+/// An ordered set implemented as a [`VecMap`] where the value is `()`.
+///
+/// The values are kept sorted in a vector, so membership is a binary search,
+/// and the set operations walk both sets side by side like the merge step of
+/// merge sort, in O(n + m) time without looking anything up.
+///
+/// [`VecMap`]: crate::containers::VecMap
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::VecSet;
+///
+/// let a: VecSet<_> = [5, 1, 3, 7].iter().copied().collect();
+/// let b: VecSet<_> = [3, 4, 5].iter().copied().collect();
+///
+/// assert!(a.contains(&3));
+/// assert_eq!(a.union(&b).collect::<Vec<_>>(), [&1, &3, &4, &5, &7]);
+/// assert_eq!(a.intersection(&b).collect::<Vec<_>>(), [&3, &5]);
+/// assert_eq!((&a - &b).iter().collect::<Vec<_>>(), [&1, &7]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct VecSet<T> {
+    map: VecMap<T, ()>,
+}
+
+impl<T> Default for VecSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for VecSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> VecSet<T> {
+    /// Creates an empty VecSet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<i32> = VecSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self { map: VecMap::new() }
+    }
+
+    /// Creates an empty VecSet with room for `capacity` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<i32> = VecSet::with_capacity(10);
+    /// assert!(set.capacity() >= 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: VecMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of values the set can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<i32> = VecSet::with_capacity(10);
+    /// assert!(set.capacity() >= 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Returns the number of values in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let mut set = VecSet::new();
+    /// set.insert(1);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let mut set = VecSet::new();
+    /// assert!(set.is_empty());
+    /// set.insert(1);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns an iterator visiting all the values of the set in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<_> = [3, 1, 2].iter().copied().collect();
+    /// let values: Vec<_> = set.iter().collect();
+    /// assert_eq!(values, [&1, &2, &3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Returns the smallest value of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<_> = [3, 1, 2].iter().copied().collect();
+    /// assert_eq!(set.first(), Some(&1));
+    /// ```
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(value, _)| value)
+    }
+
+    /// Returns the largest value of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<_> = [3, 1, 2].iter().copied().collect();
+    /// assert_eq!(set.last(), Some(&3));
+    /// ```
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(value, _)| value)
+    }
+}
+
+impl<T: Ord> VecSet<T> {
+    /// Creates a VecSet from values that are already in strictly ascending
+    /// order, without sorting them.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the values are not in strictly ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set = VecSet::from_sorted_iter(0..5);
+    /// assert!(set.contains(&3));
+    /// ```
+    pub fn from_sorted_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self {
+            map: VecMap::from_sorted_iter(
+                iter.into_iter().map(|value| (value, ())),
+            ),
+        }
+    }
+
+    /// Adds a value to the set, and returns true if the set did not have it.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let mut set = VecSet::new();
+    /// assert!(set.insert(2));
+    /// assert!(!set.insert(2));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Returns true if the set contains the value.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// assert!(set.contains(&1));
+    /// assert!(!set.contains(&4));
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Removes a value from the set, and returns true if the set had it.
+    ///
+    /// This operation should compute in O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let mut set = VecSet::new();
+    /// set.insert(2);
+    /// assert!(set.remove(&2));
+    /// assert!(!set.remove(&2));
+    /// ```
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Returns an iterator visiting the values of the set that are in the
+    /// range, in ascending order. The iterator is empty if the start of the
+    /// range is after its end.
+    ///
+    /// This operation should compute in O(log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let set: VecSet<_> = (0..10).collect();
+    /// let values: Vec<_> = set.range(3..6).collect();
+    /// assert_eq!(values, [&3, &4, &5]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Range {
+            iter: self.map.range(range),
+        }
+    }
+
+    /// Visits the values that are in `self` or in `other`, in ascending
+    /// order.
+    ///
+    /// This operation should compute in O(n + m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let a: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// let b: VecSet<_> = [2, 3, 4].iter().copied().collect();
+    ///
+    /// let union: Vec<_> = a.union(&b).collect();
+    /// assert_eq!(union, [&1, &2, &3, &4]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a VecSet<T>) -> Union<'a, T> {
+        Union {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Visits the values that are both in `self` and in `other`, in
+    /// ascending order.
+    ///
+    /// This operation should compute in O(n + m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let a: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// let b: VecSet<_> = [2, 3, 4].iter().copied().collect();
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).collect();
+    /// assert_eq!(intersection, [&2, &3]);
+    /// ```
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a VecSet<T>,
+    ) -> Intersection<'a, T> {
+        Intersection {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Visits the values that are in `self` but not in `other`, in ascending
+    /// order.
+    ///
+    /// This operation should compute in O(n + m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let a: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// let b: VecSet<_> = [2, 3, 4].iter().copied().collect();
+    ///
+    /// let diff: Vec<_> = a.difference(&b).collect();
+    /// assert_eq!(diff, [&1]);
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a VecSet<T>) -> Difference<'a, T> {
+        Difference {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Visits the values that are in `self` or in `other` but not in both,
+    /// in ascending order.
+    ///
+    /// This operation should compute in O(n + m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let a: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// let b: VecSet<_> = [2, 3, 4].iter().copied().collect();
+    ///
+    /// let diff: Vec<_> = a.symmetric_difference(&b).collect();
+    /// assert_eq!(diff, [&1, &4]);
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a VecSet<T>,
+    ) -> SymmetricDifference<'a, T> {
+        SymmetricDifference {
+            merge: Merge::new(self, other),
+        }
+    }
+
+    /// Returns true if every value of `self` is in `other`.
+    ///
+    /// This operation should compute in O(n + m) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::VecSet;
+    ///
+    /// let a: VecSet<_> = [1, 2].iter().copied().collect();
+    /// let b: VecSet<_> = [1, 2, 3].iter().copied().collect();
+    /// assert!(a.is_subset(&b));
+    /// assert!(!b.is_subset(&a));
+    /// ```
+    pub fn is_subset(&self, other: &VecSet<T>) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for VecSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Ord> Extend<T> for VecSet<T> {
+    /// Inserts the values, sorting the set once after appending them.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|value| (value, ())));
+    }
+}
+
+impl<T: Ord + Clone> BitOr for &VecSet<T> {
+    type Output = VecSet<T>;
+
+    fn bitor(self, other: &VecSet<T>) -> VecSet<T> {
+        VecSet::from_sorted_iter(self.union(other).cloned())
+    }
+}
+
+impl<T: Ord + Clone> BitAnd for &VecSet<T> {
+    type Output = VecSet<T>;
+
+    fn bitand(self, other: &VecSet<T>) -> VecSet<T> {
+        VecSet::from_sorted_iter(self.intersection(other).cloned())
+    }
+}
+
+impl<T: Ord + Clone> Sub for &VecSet<T> {
+    type Output = VecSet<T>;
+
+    fn sub(self, other: &VecSet<T>) -> VecSet<T> {
+        VecSet::from_sorted_iter(self.difference(other).cloned())
+    }
+}
+
+impl<T: Ord + Clone> BitXor for &VecSet<T> {
+    type Output = VecSet<T>;
+
+    fn bitxor(self, other: &VecSet<T>) -> VecSet<T> {
+        VecSet::from_sorted_iter(self.symmetric_difference(other).cloned())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a VecSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a VecSet, in ascending order.
+///
+/// This struct is created by [`VecSet::iter()`]. See its documentation for
+/// more.
+///
+/// [`VecSet::iter()`]: crate::containers::VecSet::iter
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    iter: vec_map::Iter<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// An iterator over a range of the values of a VecSet, in ascending order.
+///
+/// This struct is created by [`VecSet::range()`]. See its documentation for
+/// more.
+///
+/// [`VecSet::range()`]: crate::containers::VecSet::range
+#[derive(Debug)]
+pub struct Range<'a, T> {
+    iter: vec_map::Range<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Range<'a, T> {}
+
+/// Walks two sets side by side in ascending order, yielding each value with
+/// the sides it is on.
+#[derive(Debug)]
+struct Merge<'a, T> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+}
+
+impl<'a, T: Ord> Merge<'a, T> {
+    fn new(a: &'a VecSet<T>, b: &'a VecSet<T>) -> Self {
+        Self {
+            a: a.iter().peekable(),
+            b: b.iter().peekable(),
+        }
+    }
+
+    /// Returns the next value, and whether it is in the first set and in the
+    /// second set.
+    fn next(&mut self) -> Option<(&'a T, bool, bool)> {
+        let ord = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b),
+        };
+        match ord {
+            Ordering::Less => self.a.next().map(|a| (a, true, false)),
+            Ordering::Greater => self.b.next().map(|b| (b, false, true)),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next().map(|a| (a, true, true))
+            }
+        }
+    }
+}
+
+/// An iterator over the union of two VecSets, in ascending order.
+///
+/// This struct is created by [`VecSet::union()`]. See its documentation for
+/// more.
+///
+/// [`VecSet::union()`]: crate::containers::VecSet::union
+#[derive(Debug)]
+pub struct Union<'a, T> {
+    merge: Merge<'a, T>,
+}
+
+impl<'a, T: Ord> Iterator for Union<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.merge.next().map(|(value, _, _)| value)
+    }
+}
+
+/// An iterator over the intersection of two VecSets, in ascending order.
+///
+/// This struct is created by [`VecSet::intersection()`]. See its
+/// documentation for more.
+///
+/// [`VecSet::intersection()`]: crate::containers::VecSet::intersection
+#[derive(Debug)]
+pub struct Intersection<'a, T> {
+    merge: Merge<'a, T>,
+}
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Stop as soon as either side runs out.
+            self.merge.a.peek()?;
+            self.merge.b.peek()?;
+            if let (value, true, true) = self.merge.next()? {
+                return Some(value);
+            }
+        }
+    }
+}
+
+/// An iterator over the difference of two VecSets, in ascending order.
+///
+/// This struct is created by [`VecSet::difference()`]. See its documentation
+/// for more.
+///
+/// [`VecSet::difference()`]: crate::containers::VecSet::difference
+#[derive(Debug)]
+pub struct Difference<'a, T> {
+    merge: Merge<'a, T>,
+}
+
+impl<'a, T: Ord> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.merge.a.peek()?;
+            if let (value, true, false) = self.merge.next()? {
+                return Some(value);
+            }
+        }
+    }
+}
+
+/// An iterator over the symmetric difference of two VecSets, in ascending
+/// order.
+///
+/// This struct is created by [`VecSet::symmetric_difference()`]. See its
+/// documentation for more.
+///
+/// [`VecSet::symmetric_difference()`]:
+/// crate::containers::VecSet::symmetric_difference
+#[derive(Debug)]
+pub struct SymmetricDifference<'a, T> {
+    merge: Merge<'a, T>,
+}
+
+impl<'a, T: Ord> Iterator for SymmetricDifference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.merge.next()? {
+                (_, true, true) => continue,
+                (value, _, _) => return Some(value),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+    use alloc::collections::BTreeSet;
+    use alloc::vec::Vec;
+
+    fn random_sets(rng: &mut SplitMix64) -> (VecSet<u64>, BTreeSet<u64>) {
+        let values: Vec<_> = (0..30).map(|_| rng.next_u64() % 50).collect();
+        (
+            values.iter().copied().collect(),
+            values.into_iter().collect(),
+        )
+    }
+
+    #[test]
+    fn set_operations_match_btree_set() {
+        let mut rng = SplitMix64::new(7);
+        for _ in 0..50 {
+            let (a, x) = random_sets(&mut rng);
+            let (b, y) = random_sets(&mut rng);
+            assert!(a.iter().eq(x.iter()));
+            assert!(a.union(&b).eq(x.union(&y)));
+            assert!(a.intersection(&b).eq(x.intersection(&y)));
+            assert!(a.difference(&b).eq(x.difference(&y)));
+            assert!(a.symmetric_difference(&b).eq(x.symmetric_difference(&y)));
+            assert!((&a & &b).iter().eq((&x & &y).iter()));
+            assert_eq!(a.is_subset(&b), x.is_subset(&y));
+            assert!((&a & &b).is_subset(&a));
+        }
+    }
+
+    #[test]
+    fn empty_and_single_value_sets() {
+        let empty: VecSet<u32> = VecSet::new();
+        let one: VecSet<_> = [1].iter().copied().collect();
+        assert_eq!(empty.first(), None);
+        assert_eq!(empty.last(), None);
+        assert_eq!(one.first(), Some(&1));
+        assert_eq!(one.last(), Some(&1));
+
+        assert!(empty.union(&empty).next().is_none());
+        assert!(one.union(&empty).eq(&[1]));
+        assert!(empty.union(&one).eq(&[1]));
+        assert!(one.intersection(&empty).next().is_none());
+        assert!(one.difference(&empty).eq(&[1]));
+        assert!(empty.difference(&one).next().is_none());
+        assert!(empty.symmetric_difference(&one).eq(&[1]));
+        assert!(one.symmetric_difference(&one).next().is_none());
+        assert!(empty.is_subset(&empty));
+        assert!(empty.is_subset(&one));
+        assert!(!one.is_subset(&empty));
+        assert!(one.is_subset(&one));
+    }
+
+    #[test]
+    fn ranges_at_the_edges() {
+        let set: VecSet<_> = (0..10).collect();
+        assert!(set.range(..).eq(set.iter()));
+        assert!(set.range(10..).next().is_none());
+        assert!(set.range(..0).next().is_none());
+        assert!(set.range(9..=9).eq(&[9]));
+        // The range is empty rather than invalid when its start is after
+        // its end.
+        #[allow(clippy::reversed_empty_ranges)]
+        let mut reversed = set.range(6..3);
+        assert!(reversed.next().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys must be sorted")]
+    fn from_sorted_iter_rejects_duplicates() {
+        VecSet::from_sorted_iter(vec![1, 2, 2, 3]);
+    }
+}
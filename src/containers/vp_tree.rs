@@ -0,0 +1,462 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+
+use super::metric::Metric;
+use super::rng::SplitMix64;
+
+/// A vantage-point tree, which finds the values close to a query in any
+/// metric space.
+///
+/// Every subtree picks one of its values at random as its vantage point, and
+/// splits the others in two halves at the median of their distances to it:
+/// the inside half is at most that radius away from the vantage point, and
+/// the outside half is at least that far. A query measures its distance to
+/// the vantage point, and the triangle inequality then tells whether a half
+/// can hold values close enough to the query, so the halves that cannot are
+/// skipped. Unlike a [`KdTree`], which splits on coordinates, the tree only
+/// needs the distance between values, given by the [`Metric`] `M`, so it
+/// works for strings under the edit distance as well as for points.
+///
+/// The tree is built once from all its values, and its height is O(log(n)).
+/// The values are kept in a vector, in the order of a preorder walk of the
+/// tree, so a subtree is a range of the vector and no links are stored.
+///
+/// [`KdTree`]: crate::containers::KdTree
+/// [`Metric`]: crate::containers::Metric
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::{Levenshtein, VpTree};
+///
+/// let words = vec!["book", "books", "cake", "boo", "cape", "cart", "boon"];
+/// let tree = VpTree::<_, Levenshtein>::new(words);
+///
+/// assert_eq!(tree.nearest(&"bool"), Some((&"book", 1.0)));
+/// let mut close: Vec<_> = tree.range(&"caqe", 1.0).map(|(w, _)| *w).collect();
+/// close.sort();
+/// assert_eq!(close, ["cake", "cape"]);
+/// ```
+pub struct VpTree<T, M> {
+    // The subtree rooted at index i spans the indices from i to the end of
+    // its range: its vantage point is at i, its inside half follows it, and
+    // its outside half comes after, the inside half taking the extra value
+    // when the others do not split evenly.
+    items: Vec<T>,
+    // The radius of the vantage point at the same index.
+    radii: Vec<f64>,
+    metric: PhantomData<M>,
+}
+
+impl<T: Clone, M> Clone for VpTree<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            radii: self.radii.clone(),
+            metric: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, M> fmt::Debug for VpTree<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.items.iter()).finish()
+    }
+}
+
+impl<T, M: Metric<T>> Default for VpTree<T, M> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T, M> VpTree<T, M> {
+    /// Returns the number of values in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Euclidean, VpTree};
+    ///
+    /// let tree = VpTree::<_, Euclidean>::new(vec![[0.0], [1.0]]);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the tree has no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Euclidean, VpTree};
+    ///
+    /// let tree = VpTree::<[f64; 2], Euclidean>::new(Vec::new());
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns an iterator over the values, in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Levenshtein, VpTree};
+    ///
+    /// let tree = VpTree::<_, Levenshtein>::new(vec!["a", "b"]);
+    /// let mut words: Vec<_> = tree.iter().copied().collect();
+    /// words.sort();
+    /// assert_eq!(words, ["a", "b"]);
+    /// ```
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+}
+
+impl<T, M: Metric<T>> VpTree<T, M> {
+    /// Creates a VpTree over the values.
+    ///
+    /// This operation should compute in O(n log(n)) time, counting the
+    /// distances it measures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Manhattan, VpTree};
+    ///
+    /// let points: Vec<_> = (0..100).map(|i| [i as f64, 0.0]).collect();
+    /// let tree = VpTree::<_, Manhattan>::new(points);
+    /// assert_eq!(tree.nearest(&[41.75, 1.0]), Some((&[42.0, 0.0], 1.25)));
+    /// ```
+    pub fn new(items: Vec<T>) -> Self {
+        let n = items.len();
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut distances = vec![0.0; n];
+        let mut radii = vec![0.0; n];
+        let mut rng = SplitMix64::new(SplitMix64::DEFAULT_SEED);
+        Self::build(&items, &mut order, &mut radii, &mut distances, &mut rng);
+        // The values are moved into the order of the tree, which `order`
+        // gives as their old indices.
+        let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+        let items = order
+            .iter()
+            .map(|&i| slots[i].take().expect("Index appears once"))
+            .collect();
+        Self {
+            items,
+            radii,
+            metric: PhantomData,
+        }
+    }
+
+    /// Returns the value closest to the query and its distance, or None if
+    /// the tree is empty. Ties are broken arbitrarily.
+    ///
+    /// This operation should compute in O(log(n)) expected time for values
+    /// spread evenly in a space of low dimension, and O(n) time in the worst
+    /// case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Euclidean, VpTree};
+    ///
+    /// let tree = VpTree::<_, Euclidean>::new(vec![[0.0, 0.0], [3.0, 4.0]]);
+    /// assert_eq!(tree.nearest(&[3.0, 3.0]), Some((&[3.0, 4.0], 1.0)));
+    /// ```
+    pub fn nearest(&self, query: &T) -> Option<(&T, f64)> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// Returns the `k` values closest to the query and their distances, from
+    /// the closest to the farthest. Fewer values are returned if the tree has
+    /// fewer than `k`.
+    ///
+    /// This operation should compute in O(k log(n)) expected time for values
+    /// spread evenly in a space of low dimension, and O(nk) time in the worst
+    /// case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Euclidean, VpTree};
+    ///
+    /// let points: Vec<_> = (0..10).map(|i| [i as f64]).collect();
+    /// let tree = VpTree::<_, Euclidean>::new(points);
+    /// let closest: Vec<_> =
+    ///     tree.k_nearest(&[4.2], 3).iter().map(|(p, _)| p[0]).collect();
+    /// assert_eq!(closest, [4.0, 5.0, 3.0]);
+    /// ```
+    pub fn k_nearest(&self, query: &T, k: usize) -> Vec<(&T, f64)> {
+        let mut best = Vec::with_capacity(k);
+        if k > 0 {
+            self.search(0, self.items.len(), query, k, &mut best);
+        }
+        best.into_iter().map(|(d, i)| (&self.items[i], d)).collect()
+    }
+
+    /// Returns an iterator over the values at most `radius` away from the
+    /// query and their distances, in no particular order.
+    ///
+    /// This operation should compute in O(log(n) + m) expected time for a
+    /// small radius and values spread evenly in a space of low dimension,
+    /// where m is the number of values returned, and O(n) time in the worst
+    /// case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::{Euclidean, VpTree};
+    ///
+    /// let points: Vec<_> = (0..10).map(|i| [i as f64]).collect();
+    /// let tree = VpTree::<_, Euclidean>::new(points);
+    /// let mut inside: Vec<_> =
+    ///     tree.range(&[4.5], 1.0).map(|(p, _)| p[0]).collect();
+    /// inside.sort_by(f64::total_cmp);
+    /// assert_eq!(inside, [4.0, 5.0]);
+    /// ```
+    pub fn range<'a>(&'a self, query: &'a T, radius: f64) -> Range<'a, T, M> {
+        let mut stack = Vec::new();
+        if !self.items.is_empty() {
+            stack.push((0, self.items.len()));
+        }
+        Range {
+            tree: self,
+            query,
+            radius,
+            stack,
+        }
+    }
+
+    /// Arranges the values whose indices are in `order` into a subtree, in
+    /// the layout of `items`, and sets the radii of its vantage points.
+    fn build(
+        items: &[T],
+        order: &mut [usize],
+        radii: &mut [f64],
+        distances: &mut [f64],
+        rng: &mut SplitMix64,
+    ) {
+        if order.len() < 2 {
+            return;
+        }
+        let vantage = (rng.next_u64() % order.len() as u64) as usize;
+        order.swap(0, vantage);
+        let (vantage, others) = order.split_first_mut().expect("Not empty");
+        for &i in others.iter() {
+            distances[i] = M::distance(&items[*vantage], &items[i]);
+        }
+        // The inside half ends with the median, whose distance is the radius.
+        let inside = others.len().div_ceil(2);
+        others.select_nth_unstable_by(inside - 1, |&a, &b| {
+            distances[a].total_cmp(&distances[b])
+        });
+        radii[0] = distances[others[inside - 1]];
+        let (inside_order, outside_order) = others.split_at_mut(inside);
+        let (inside_radii, outside_radii) = radii[1..].split_at_mut(inside);
+        Self::build(items, inside_order, inside_radii, distances, rng);
+        Self::build(items, outside_order, outside_radii, distances, rng);
+    }
+
+    /// Keeps the `k` closest values of the subtree spanning the indices from
+    /// `start` to `end`, sorted by their distance to the query, in `best`.
+    fn search(
+        &self,
+        start: usize,
+        end: usize,
+        query: &T,
+        k: usize,
+        best: &mut Vec<(f64, usize)>,
+    ) {
+        if start == end {
+            return;
+        }
+        let d = M::distance(query, &self.items[start]);
+        if best.len() < k || d < best[best.len() - 1].0 {
+            if best.len() == k {
+                best.pop();
+            }
+            let at = best.partition_point(|&(other, _)| other <= d);
+            best.insert(at, (d, start));
+        }
+        let radius = self.radii[start];
+        let split = start + 1 + (end - start) / 2;
+        // A value closer to the query than the farthest of the best ones is
+        // in a half only if the half intersects the ball around the query.
+        let worst = |best: &Vec<(f64, usize)>| {
+            if best.len() < k {
+                f64::INFINITY
+            } else {
+                best[best.len() - 1].0
+            }
+        };
+        if d < radius {
+            self.search(start + 1, split, query, k, best);
+            if d + worst(best) > radius {
+                self.search(split, end, query, k, best);
+            }
+        } else {
+            self.search(split, end, query, k, best);
+            if d - worst(best) < radius {
+                self.search(start + 1, split, query, k, best);
+            }
+        }
+    }
+}
+
+impl<T, M: Metric<T>> FromIterator<T> for VpTree<T, M> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<'a, T, M> IntoIterator for &'a VpTree<T, M> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a VpTree close to a query.
+///
+/// This struct is created by [`VpTree::range()`]. See its documentation for
+/// more.
+///
+/// [`VpTree::range()`]: crate::containers::VpTree::range
+pub struct Range<'a, T, M> {
+    tree: &'a VpTree<T, M>,
+    query: &'a T,
+    radius: f64,
+    // The subtrees that may still hold values close to the query, as ranges
+    // of indices.
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, T: fmt::Debug, M> fmt::Debug for Range<'a, T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Range")
+            .field("query", &self.query)
+            .field("radius", &self.radius)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, M: Metric<T>> Iterator for Range<'a, T, M> {
+    type Item = (&'a T, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((start, end)) = self.stack.pop() {
+            let item = &self.tree.items[start];
+            let d = M::distance(self.query, item);
+            let radius = self.tree.radii[start];
+            let split = start + 1 + (end - start) / 2;
+            if split < end && d + self.radius >= radius {
+                self.stack.push((split, end));
+            }
+            if start + 1 < split && d - self.radius <= radius {
+                self.stack.push((start + 1, split));
+            }
+            if d <= self.radius {
+                return Some((item, d));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::metric::{Euclidean, Levenshtein, Manhattan};
+    use alloc::string::String;
+
+    fn random_point(rng: &mut SplitMix64) -> [f64; 3] {
+        // Few distinct coordinates, so that many distances tie.
+        let mut coordinate = || (rng.next_u64() % 8) as f64;
+        [coordinate(), coordinate(), coordinate()]
+    }
+
+    fn check<T: Clone, M: Metric<T>>(items: &[T], queries: &[T]) {
+        let tree = VpTree::<T, M>::new(items.to_vec());
+        assert_eq!(tree.len(), items.len());
+        for query in queries {
+            let mut expected: Vec<_> =
+                items.iter().map(|x| M::distance(query, x)).collect();
+            expected.sort_by(f64::total_cmp);
+            for k in [0, 1, 5, items.len() + 1] {
+                let found: Vec<_> =
+                    tree.k_nearest(query, k).iter().map(|&(_, d)| d).collect();
+                assert_eq!(found, expected[..k.min(items.len())]);
+            }
+            for radius in [0.0, 1.0, 2.5] {
+                let mut found: Vec<_> =
+                    tree.range(query, radius).map(|(_, d)| d).collect();
+                found.sort_by(f64::total_cmp);
+                let inside: Vec<_> =
+                    expected.iter().copied().filter(|&d| d <= radius).collect();
+                assert_eq!(found, inside);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_and_single_point_trees() {
+        let empty = VpTree::<[f64; 2], Euclidean>::new(Vec::new());
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest(&[0.0, 0.0]), None);
+        assert!(empty.k_nearest(&[0.0, 0.0], 3).is_empty());
+        assert_eq!(empty.range(&[0.0, 0.0], f64::INFINITY).count(), 0);
+
+        let single = VpTree::<_, Euclidean>::new(vec![[3.0, 4.0]]);
+        assert_eq!(single.nearest(&[0.0, 0.0]), Some((&[3.0, 4.0], 5.0)));
+        assert_eq!(single.k_nearest(&[0.0, 0.0], 3).len(), 1);
+        assert_eq!(single.range(&[0.0, 0.0], 4.9).count(), 0);
+        assert_eq!(single.range(&[0.0, 0.0], 5.0).count(), 1);
+        check::<_, Euclidean>(&[[3.0, 4.0]], &[[0.0, 0.0], [3.0, 4.0]]);
+    }
+
+    #[test]
+    fn duplicate_points() {
+        // Every point is at distance 0 from the vantage point, so all of
+        // them fall on the same side of its median.
+        let points = vec![[1.0, 1.0]; 20];
+        let tree = VpTree::<_, Euclidean>::new(points.clone());
+        assert_eq!(tree.k_nearest(&[1.0, 1.0], 25).len(), 20);
+        assert_eq!(tree.range(&[1.0, 1.0], 0.0).count(), 20);
+        assert_eq!(tree.nearest(&[0.0, 1.0]), Some((&[1.0, 1.0], 1.0)));
+
+        let mut mixed = points;
+        mixed.extend(vec![[5.0, 1.0]; 5]);
+        check::<_, Euclidean>(&mixed, &[[1.0, 1.0], [3.0, 1.0], [9.0, 9.0]]);
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut rng = SplitMix64::new(7);
+        let points: Vec<_> = (0..300).map(|_| random_point(&mut rng)).collect();
+        let queries: Vec<_> = (0..50).map(|_| random_point(&mut rng)).collect();
+        check::<_, Euclidean>(&points, &queries);
+        check::<_, Manhattan>(&points, &queries);
+
+        let word = |rng: &mut SplitMix64| -> String {
+            let len = rng.next_u64() % 6;
+            (0..len)
+                .map(|_| (b'a' + (rng.next_u64() % 3) as u8) as char)
+                .collect()
+        };
+        let words: Vec<_> = (0..200).map(|_| word(&mut rng)).collect();
+        let queries: Vec<_> = (0..50).map(|_| word(&mut rng)).collect();
+        check::<_, Levenshtein>(&words, &queries);
+    }
+}
@@ -0,0 +1,506 @@
+use alloc::vec::Vec;
+use core::iter::{Chain, FromIterator, Rev};
+use core::{mem, option, slice};
+
+use super::{DoublyLinkedList, SinglyLinkedList};
+
+/// A list with a focus on one of its elements, which can be moved, replaced,
+/// and removed, and next to which elements can be inserted, all in constant
+/// time.
+///
+/// The elements before the focus are kept in a stack whose top is right
+/// before the focus, and the elements after it in another stack whose top is
+/// right after it. Moving the focus pops an element from one stack and pushes
+/// the old focus onto the other, so the zipper works like the gap buffer of
+/// a text editor. Only an empty zipper has no focus.
+///
+/// # Examples
+///
+/// ```
+/// use dt::containers::Zipper;
+///
+/// let mut zipper: Zipper<_> = "helo".chars().collect();
+/// zipper.move_right();
+/// zipper.move_right();
+/// zipper.insert_right('l');
+/// zipper.move_right();
+/// zipper.move_right();
+/// assert_eq!(zipper.replace('!'), Some('o'));
+/// assert_eq!(zipper.into_vec(), ['h', 'e', 'l', 'l', '!']);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Zipper<T> {
+    left: Vec<T>,
+    focus: Option<T>,
+    // Stored in reverse, so that its last element is right after the focus.
+    right: Vec<T>,
+}
+
+impl<T> Default for Zipper<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Zipper<T> {
+    /// Creates an empty Zipper.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let zipper: Zipper<u32> = Zipper::new();
+    /// assert_eq!(zipper.focus(), None);
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            left: Vec::new(),
+            focus: None,
+            right: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements in the zipper.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let zipper: Zipper<_> = (0..3).collect();
+    /// assert_eq!(zipper.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.left.len() + self.focus.is_some() as usize + self.right.len()
+    }
+
+    /// Returns true if the zipper is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper = Zipper::new();
+    /// assert!(zipper.is_empty());
+    /// zipper.insert_left(1);
+    /// assert!(!zipper.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.focus.is_none()
+    }
+
+    /// Returns the index of the focus, which is 0 if the zipper is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..3).collect();
+    /// zipper.move_right();
+    /// assert_eq!(zipper.position(), 1);
+    /// ```
+    pub fn position(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Returns a reference to the focus.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let zipper: Zipper<_> = (0..3).collect();
+    /// assert_eq!(zipper.focus(), Some(&0));
+    /// ```
+    pub fn focus(&self) -> Option<&T> {
+        self.focus.as_ref()
+    }
+
+    /// Returns a mutable reference to the focus.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..3).collect();
+    /// if let Some(x) = zipper.focus_mut() {
+    ///     *x = 10;
+    /// }
+    /// assert_eq!(zipper.focus(), Some(&10));
+    /// ```
+    pub fn focus_mut(&mut self) -> Option<&mut T> {
+        self.focus.as_mut()
+    }
+
+    /// Replaces the focus with the value and returns the old focus. If the
+    /// zipper is empty, the value becomes its only element.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper = Zipper::new();
+    /// assert_eq!(zipper.replace(1), None);
+    /// assert_eq!(zipper.replace(2), Some(1));
+    /// assert_eq!(zipper.len(), 1);
+    /// ```
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        self.focus.replace(value)
+    }
+
+    /// Moves the focus to the previous element, and returns false if the
+    /// focus is already on the first element.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..2).collect();
+    /// assert!(zipper.move_right());
+    /// assert!(zipper.move_left());
+    /// assert!(!zipper.move_left());
+    /// assert_eq!(zipper.focus(), Some(&0));
+    /// ```
+    pub fn move_left(&mut self) -> bool {
+        match self.left.pop() {
+            Some(prev) => {
+                let focus = self.focus.replace(prev);
+                self.right.extend(focus);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the focus to the next element, and returns false if the focus
+    /// is already on the last element.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..2).collect();
+    /// assert!(zipper.move_right());
+    /// assert!(!zipper.move_right());
+    /// assert_eq!(zipper.focus(), Some(&1));
+    /// ```
+    pub fn move_right(&mut self) -> bool {
+        match self.right.pop() {
+            Some(next) => {
+                let focus = self.focus.replace(next);
+                self.left.extend(focus);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts the value right before the focus, keeping the focus. If the
+    /// zipper is empty, the value becomes the focus.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (1..3).collect();
+    /// zipper.insert_left(0);
+    /// assert_eq!(zipper.focus(), Some(&1));
+    /// assert_eq!(zipper.into_vec(), [0, 1, 2]);
+    /// ```
+    pub fn insert_left(&mut self, value: T) {
+        if self.focus.is_none() {
+            self.focus = Some(value);
+        } else {
+            self.left.push(value);
+        }
+    }
+
+    /// Inserts the value right after the focus, keeping the focus. If the
+    /// zipper is empty, the value becomes the focus.
+    ///
+    /// This operation should compute in amortized O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..2).collect();
+    /// zipper.insert_right(5);
+    /// assert_eq!(zipper.focus(), Some(&0));
+    /// assert_eq!(zipper.into_vec(), [0, 5, 1]);
+    /// ```
+    pub fn insert_right(&mut self, value: T) {
+        if self.focus.is_none() {
+            self.focus = Some(value);
+        } else {
+            self.right.push(value);
+        }
+    }
+
+    /// Removes the focus and returns it. The focus moves to the next element,
+    /// or to the previous one if the removed element was the last.
+    ///
+    /// This operation should compute in O(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..3).collect();
+    /// zipper.move_right();
+    /// assert_eq!(zipper.remove(), Some(1));
+    /// assert_eq!(zipper.focus(), Some(&2));
+    /// assert_eq!(zipper.remove(), Some(2));
+    /// assert_eq!(zipper.focus(), Some(&0));
+    /// ```
+    pub fn remove(&mut self) -> Option<T> {
+        let next = self.right.pop().or_else(|| self.left.pop());
+        mem::replace(&mut self.focus, next)
+    }
+
+    /// Returns an iterator over the elements of the zipper, from the first to
+    /// the last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let mut zipper: Zipper<_> = (0..3).collect();
+    /// zipper.move_right();
+    /// assert!(zipper.iter().eq(&[0, 1, 2]));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self
+                .left
+                .iter()
+                .chain(self.focus.iter())
+                .chain(self.right.iter().rev()),
+        }
+    }
+
+    /// Returns the elements of the zipper in a vector, from the first to the
+    /// last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dt::containers::Zipper;
+    ///
+    /// let zipper: Zipper<_> = vec![1, 2, 3].into();
+    /// assert_eq!(zipper.into_vec(), [1, 2, 3]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        let mut values = self.left;
+        values.reserve(self.right.len() + 1);
+        values.extend(self.focus);
+        values.extend(self.right.into_iter().rev());
+        values
+    }
+}
+
+impl<T> From<Vec<T>> for Zipper<T> {
+    /// Creates a zipper focused on the first element of the vector.
+    fn from(mut values: Vec<T>) -> Self {
+        values.reverse();
+        let focus = values.pop();
+        Self {
+            left: Vec::new(),
+            focus,
+            right: values,
+        }
+    }
+}
+
+impl<T> From<SinglyLinkedList<T>> for Zipper<T> {
+    /// Creates a zipper focused on the first element of the list.
+    fn from(mut list: SinglyLinkedList<T>) -> Self {
+        let mut values = Vec::with_capacity(list.len());
+        while let Some(value) = list.pop_front() {
+            values.push(value);
+        }
+        values.into()
+    }
+}
+
+impl<T> From<DoublyLinkedList<T>> for Zipper<T> {
+    /// Creates a zipper focused on the first element of the list.
+    fn from(mut list: DoublyLinkedList<T>) -> Self {
+        let mut values = Vec::with_capacity(list.len());
+        while let Some(value) = list.pop_front() {
+            values.push(value);
+        }
+        values.into()
+    }
+}
+
+impl<T> From<Zipper<T>> for SinglyLinkedList<T> {
+    fn from(zipper: Zipper<T>) -> Self {
+        let mut list = SinglyLinkedList::new();
+        list.extend(zipper.into_vec().into_iter().rev());
+        list
+    }
+}
+
+impl<T> From<Zipper<T>> for DoublyLinkedList<T> {
+    fn from(zipper: Zipper<T>) -> Self {
+        let mut list = DoublyLinkedList::new();
+        for value in zipper.into_vec() {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T> FromIterator<T> for Zipper<T> {
+    /// Creates a zipper focused on the first element of the iterator.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Zipper<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+type Elements<'a, T> = Chain<
+    Chain<slice::Iter<'a, T>, option::Iter<'a, T>>,
+    Rev<slice::Iter<'a, T>>,
+>;
+
+/// An iterator over the elements of a Zipper.
+///
+/// This struct is created by [`Zipper::iter()`]. See its documentation for
+/// more.
+///
+/// [`Zipper::iter()`]: crate::containers::Zipper::iter
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    iter: Elements<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::rng::SplitMix64;
+
+    #[test]
+    fn matches_vec_with_cursor() {
+        let mut rng = SplitMix64::new(5);
+        let mut zipper = Zipper::new();
+        let mut naive: Vec<u64> = Vec::new();
+        let mut cursor = 0;
+        for i in 0..2000 {
+            match rng.next_u64() % 6 {
+                0 => {
+                    let moved = cursor > 0;
+                    assert_eq!(zipper.move_left(), moved);
+                    cursor -= moved as usize;
+                }
+                1 => {
+                    let moved = cursor + 1 < naive.len();
+                    assert_eq!(zipper.move_right(), moved);
+                    cursor += moved as usize;
+                }
+                2 => {
+                    let removed =
+                        (!naive.is_empty()).then(|| naive.remove(cursor));
+                    assert_eq!(zipper.remove(), removed);
+                    if cursor == naive.len() && cursor > 0 {
+                        cursor -= 1;
+                    }
+                }
+                3 => {
+                    zipper.insert_right(i);
+                    naive.insert((cursor + 1).min(naive.len()), i);
+                }
+                4 => {
+                    zipper.insert_left(i);
+                    if naive.is_empty() {
+                        naive.push(i);
+                    } else {
+                        naive.insert(cursor, i);
+                        cursor += 1;
+                    }
+                }
+                _ => {
+                    let old = zipper.replace(i);
+                    if naive.is_empty() {
+                        assert_eq!(old, None);
+                        naive.push(i);
+                    } else {
+                        assert_eq!(
+                            old,
+                            Some(mem::replace(&mut naive[cursor], i))
+                        );
+                    }
+                }
+            }
+            assert_eq!(zipper.len(), naive.len());
+            assert_eq!(zipper.position(), cursor);
+            assert_eq!(zipper.focus(), naive.get(cursor));
+        }
+        assert!(zipper.iter().eq(naive.iter()));
+        assert!(zipper.iter().rev().eq(naive.iter().rev()));
+    }
+
+    #[test]
+    fn converts_between_lists() {
+        let mut list = DoublyLinkedList::new();
+        for i in 0..5 {
+            list.push_back(i);
+        }
+        let mut zipper = Zipper::from(list);
+        zipper.move_right();
+        zipper.remove();
+        let list = SinglyLinkedList::from(zipper);
+        assert!(list.iter().eq(&[0, 2, 3, 4]));
+        let zipper = Zipper::from(list);
+        assert_eq!(zipper.focus(), Some(&0));
+        let list = DoublyLinkedList::from(zipper);
+        assert!(list.iter().eq(&[0, 2, 3, 4]));
+    }
+}
@@ -7,7 +7,24 @@
 //! to Rust's standard library implementations and are proned to errors or may 
 //! exhibit unexpected behaviors.
 
+//! # Features
+//!
+//! - `std` (enabled by default): links against the standard library. Without
+//!   it, the crate only needs `alloc`, [`LinkedHashMap`] hashes its keys with
+//!   the deterministic [`FnvBuildHasher`] by default, and neither the
+//!   containers that need a clock nor the [`concurrent`] module are
+//!   available.
+//! - `arbitrary`: implements `Arbitrary` for the containers that support it.
+//!
+//! [`LinkedHashMap`]: crate::containers::LinkedHashMap
+//! [`FnvBuildHasher`]: crate::containers::FnvBuildHasher
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![warn(missing_docs, unsafe_code)]
 #![deny(missing_debug_implementations)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod concurrent;
 pub mod containers;